@@ -1,5 +1,4 @@
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 const DESKTOP_ANILIST_CLIENT_ID: &str = "45197";
@@ -34,10 +33,7 @@ async fn exchange_authorization_code(
     redirect_uri: &str,
     code: String,
 ) -> Result<String, String> {
-    let client = Client::builder()
-        .user_agent("Shiori")
-        .build()
-        .unwrap_or_else(|_| Client::new());
+    let client = crate::utils::http::client();
 
     let req_body = TokenRequest {
         grant_type: "authorization_code".to_string(),
@@ -1,3 +1,4 @@
+use crate::db::{CompactReport, DatabaseHealthReport};
 use crate::error::Result;
 use crate::services::backup_service;
 use crate::AppState;
@@ -47,7 +48,77 @@ pub fn restore_backup(
     )
 }
 
+/// Copy the database and cover/RSS folders into `backup_dir`
+#[tauri::command]
+pub async fn backup_library(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    backup_dir: String,
+) -> Result<backup_service::LibrarySnapshotInfo> {
+    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| {
+        crate::error::ShioriError::Other(format!("Failed to get app data dir: {}", e))
+    })?;
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        backup_service::backup_library(&db, &app_data_dir, std::path::Path::new(&backup_dir))
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(crate::error::ShioriError::Other(format!(
+            "Task panicked: {}",
+            e
+        )))
+    })
+}
+
+/// Replace the library with a folder written by `backup_library`
+#[tauri::command]
+pub async fn restore_library(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    backup_dir: String,
+) -> Result<backup_service::LibrarySnapshotInfo> {
+    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| {
+        crate::error::ShioriError::Other(format!("Failed to get app data dir: {}", e))
+    })?;
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        backup_service::restore_library(&db, &app_data_dir, std::path::Path::new(&backup_dir))
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(crate::error::ShioriError::Other(format!(
+            "Task panicked: {}",
+            e
+        )))
+    })
+}
+
 #[tauri::command]
 pub fn get_backup_info(backup_path: String) -> Result<backup_service::BackupInfo> {
     backup_service::get_backup_info(&std::path::Path::new(&backup_path))
 }
+
+#[tauri::command]
+pub fn check_database_health(state: State<AppState>) -> Result<DatabaseHealthReport> {
+    state.db.integrity_check()
+}
+
+#[tauri::command]
+pub fn rebuild_search_index(state: State<AppState>) -> Result<()> {
+    state.db.rebuild_search_index()
+}
+
+/// VACUUM rewrites the whole file, so it runs off the main thread
+#[tauri::command]
+pub async fn compact_database(state: State<'_, AppState>) -> Result<CompactReport> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || db.compact())
+        .await
+        .unwrap_or_else(|e| {
+            Err(crate::error::ShioriError::Other(format!(
+                "Task panicked: {}",
+                e
+            )))
+        })
+}
@@ -133,10 +133,18 @@ pub fn add_books_to_collection(
 }
 
 #[tauri::command]
-pub fn get_collection_books(collection_id: i64, state: State<AppState>) -> Result<Vec<Book>> {
+pub fn get_collection_books(
+    collection_id: i64,
+    include_descendants: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<Book>> {
     validate::require_positive_id(collection_id, "collection_id")?;
     let conn = state.db.get_connection()?;
-    CollectionService::get_collection_books(&conn, collection_id)
+    CollectionService::get_collection_books(
+        &conn,
+        collection_id,
+        include_descendants.unwrap_or(false),
+    )
 }
 
 #[tauri::command]
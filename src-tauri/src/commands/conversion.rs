@@ -5,7 +5,11 @@ use tauri::{Emitter, State};
 
 use crate::error::ShioriError;
 use crate::services::calibre_service::{self, CalibreProfile};
-use crate::services::conversion_engine::{ConversionEngine, ConversionJob, CONVERSION_MATRIX};
+use crate::services::conversion_engine::{
+    self, ConversionEngine, ConversionJob, ConversionOptions, ConversionProfile, PdfOutputOptions,
+    CONVERSION_MATRIX,
+};
+use crate::services::format_adapter::ErrorDetails;
 use crate::utils::validate;
 use crate::AppState;
 
@@ -17,6 +21,8 @@ pub async fn convert_book(
     output_format: String,
     output_dir: Option<String>,
     book_id: Option<i64>,
+    ocr: Option<bool>,
+    pdf_options: Option<PdfOutputOptions>,
 ) -> crate::error::Result<String> {
     validate::require_safe_path(&input_path, "input_path")?;
     validate::require_non_empty(&output_format, "output_format")?;
@@ -32,11 +38,75 @@ pub async fn convert_book(
             &output_format,
             output_dir.map(PathBuf::from),
             book_id,
+            ocr.unwrap_or(false),
+            ConversionOptions {
+                pdf: pdf_options.unwrap_or_default(),
+                ..Default::default()
+            },
         )
         .await
         .map_err(|e| ShioriError::Other(e.to_string()))
 }
 
+/// Submit a conversion job using a saved conversion profile's target format
+/// and options
+#[tauri::command]
+pub async fn convert_book_with_profile(
+    engine: State<'_, Arc<ConversionEngine>>,
+    input_path: String,
+    profile_name: String,
+    book_id: Option<i64>,
+) -> crate::error::Result<String> {
+    validate::require_safe_path(&input_path, "input_path")?;
+    validate::require_non_empty(&profile_name, "profile_name")?;
+    if let Some(id) = book_id {
+        validate::require_positive_id(id, "book_id")?;
+    }
+    engine
+        .submit_with_profile(PathBuf::from(&input_path), &profile_name, book_id)
+        .await
+        .map_err(|e| ShioriError::Other(e.to_string()))
+}
+
+/// Save a named conversion profile
+#[tauri::command]
+pub async fn create_conversion_profile(
+    state: State<'_, AppState>,
+    name: String,
+    source_format: String,
+    target_format: String,
+    options: Option<ConversionOptions>,
+) -> crate::error::Result<ConversionProfile> {
+    let conn = state.db.get_connection()?;
+    conversion_engine::create_conversion_profile(
+        &conn,
+        &name,
+        &source_format,
+        &target_format,
+        options.unwrap_or_default(),
+    )
+}
+
+/// List saved conversion profiles by name
+#[tauri::command]
+pub async fn list_conversion_profiles(
+    state: State<'_, AppState>,
+) -> crate::error::Result<Vec<ConversionProfile>> {
+    let conn = state.db.get_connection()?;
+    conversion_engine::list_conversion_profiles(&conn)
+}
+
+/// Delete a conversion profile; returns false if it did not exist
+#[tauri::command]
+pub async fn delete_conversion_profile(
+    state: State<'_, AppState>,
+    id: i64,
+) -> crate::error::Result<bool> {
+    validate::require_positive_id(id, "id")?;
+    let conn = state.db.get_connection()?;
+    conversion_engine::delete_conversion_profile(&conn, id)
+}
+
 /// Get conversion job status
 #[tauri::command]
 pub async fn get_conversion_status(
@@ -85,6 +155,15 @@ pub async fn get_supported_conversions() -> crate::error::Result<Vec<serde_json:
     Ok(result)
 }
 
+/// Map a `conversion:error` code to user-facing remediation text
+#[tauri::command]
+pub async fn get_conversion_error_details(
+    error_code: String,
+) -> crate::error::Result<ErrorDetails> {
+    validate::require_non_empty(&error_code, "error_code")?;
+    Ok(ErrorDetails::for_code(&error_code))
+}
+
 // ==================== Calibre Conversion ====================
 
 /// Response for Calibre conversion
@@ -1,10 +1,11 @@
 use std::sync::Arc;
-use tauri::State;
+use tauri::{Emitter, State};
 use uuid::Uuid;
 
 use crate::error::ShioriError;
-use crate::services::cover_service::CoverService;
-use crate::services::format_adapter::BookMetadata;
+use crate::services::cover_service::{self, CoverService, CoverSource};
+use crate::services::format_adapter::{BookMetadata, FormatError};
+use crate::utils::validate;
 
 /// Generate cover for a book
 #[tauri::command]
@@ -244,9 +245,116 @@ pub async fn get_cover_paths_batch(
     Ok(result)
 }
 
+/// Replace a book's cover with an image from a local file or URL. All three
+/// resolutions are regenerated, `cover_path` points at the medium one and
+/// `cover_cache` is rewritten. Returns the new cover path.
+#[tauri::command]
+pub async fn set_custom_cover(
+    app_handle: tauri::AppHandle,
+    app_state: State<'_, crate::AppState>,
+    service: State<'_, Arc<CoverService>>,
+    book_id: i64,
+    source: CoverSource,
+) -> crate::error::Result<String> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let book = crate::services::library_service::get_book_by_id(&app_state.db, book_id)?;
+    let uuid = Uuid::parse_str(&book.uuid)
+        .map_err(|e| ShioriError::Other(format!("Invalid book UUID: {}", e)))?;
+
+    let bytes = cover_service::load_cover_source(&source).await?;
+    let cover_set = service
+        .store_custom_cover(uuid, &bytes)
+        .await
+        .map_err(|e| match e {
+            FormatError::Image(e) => {
+                ShioriError::InvalidFormat(format!("Cover is not a valid image: {}", e))
+            }
+            e => ShioriError::Other(e.to_string()),
+        })?;
+
+    let cover_path = cover_set.medium.to_string_lossy().to_string();
+    {
+        let mut conn = app_state.db.get_connection()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE books SET cover_path = ?1 WHERE id = ?2",
+            rusqlite::params![cover_path, book_id],
+        )?;
+        tx.execute(
+            "DELETE FROM cover_cache WHERE book_id = ?1",
+            rusqlite::params![book_id],
+        )?;
+        for (size, path) in [
+            ("thumb", &cover_set.thumbnail),
+            ("medium", &cover_set.medium),
+            ("full", &cover_set.full),
+        ] {
+            let file_size = std::fs::metadata(path)?.len() as i64;
+            let (width, height) = image::image_dimensions(path)
+                .map_err(|e| ShioriError::Other(format!("Failed to read cover: {}", e)))?;
+            tx.execute(
+                "INSERT INTO cover_cache (book_id, size, file_path, file_size, width, height)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    book_id,
+                    size,
+                    path.to_string_lossy().to_string(),
+                    file_size,
+                    width,
+                    height
+                ],
+            )?;
+        }
+        tx.commit()?;
+    }
+
+    log::info!("[set_custom_cover] Updated cover for book {}", book_id);
+    let _ = app_handle.emit("library-updated", ());
+    Ok(cover_path)
+}
+
 /// Clear cover cache
 #[tauri::command]
 pub async fn clear_cover_cache(service: State<'_, Arc<CoverService>>) -> crate::error::Result<()> {
     service.inner().clear_cache().await;
     Ok(())
 }
+
+#[derive(Clone, serde::Serialize)]
+pub struct CoverRegenerationProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// Re-extract or generate covers for books with a missing or broken
+/// `cover_path`. Emits `cover-regeneration-progress` after each batch and
+/// returns the number of covers rebuilt.
+#[tauri::command]
+pub async fn regenerate_missing_covers(
+    app_handle: tauri::AppHandle,
+    app_state: State<'_, crate::AppState>,
+) -> crate::error::Result<usize> {
+    let db = app_state.db.clone();
+    let covers_dir = app_state.covers_dir.clone();
+    let progress_handle = app_handle.clone();
+
+    let regenerated = tokio::task::spawn_blocking(move || {
+        crate::services::library_service::regenerate_missing_covers(
+            &db,
+            &covers_dir,
+            |processed, total| {
+                let _ = progress_handle.emit(
+                    "cover-regeneration-progress",
+                    CoverRegenerationProgress { processed, total },
+                );
+            },
+        )
+    })
+    .await
+    .map_err(|e| ShioriError::Other(e.to_string()))??;
+
+    if regenerated > 0 {
+        let _ = app_handle.emit("library-updated", ());
+    }
+    Ok(regenerated)
+}
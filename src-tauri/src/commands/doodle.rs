@@ -1,5 +1,6 @@
 use crate::error::Result;
 use crate::models::Doodle;
+use crate::services::doodle_service::{self, DoodleFormat};
 use crate::utils::validate;
 use crate::AppState;
 use tauri::State;
@@ -18,7 +19,7 @@ pub fn save_doodle(
     let conn = state.db.get_connection()?;
 
     // Enforce 5MB size limit per doodle entry
-    if strokes_json.len() > 5 * 1024 * 1024 {
+    if strokes_json.len() > doodle_service::MAX_DOODLE_BYTES {
         return Err(crate::error::ShioriError::Other(
             "Doodle data exceeds 5MB limit. Please clear some strokes.".into(),
         ));
@@ -119,3 +120,84 @@ pub fn delete_book_doodles(book_id: i64, state: State<AppState>) -> Result<i64>
 
     Ok(deleted as i64)
 }
+
+/// Render a page's doodle as an SVG or PNG overlay of `width` x `height`
+/// pixels and write it to `file_path`. A page without a doodle exports as a
+/// transparent overlay.
+#[tauri::command]
+pub async fn export_doodle(
+    book_id: i64,
+    page_number: String,
+    format: String,
+    width: u32,
+    height: u32,
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<()> {
+    validate::require_positive_id(book_id, "book_id")?;
+    validate::require_non_empty(&page_number, "page_number")?;
+    validate::require_in_range(width as i64, 1, 8192, "width")?;
+    validate::require_in_range(height as i64, 1, 8192, "height")?;
+    validate::require_safe_path(&file_path, "file_path")?;
+    let format = DoodleFormat::parse(&format)?;
+
+    let strokes_json: String = {
+        let conn = state.db.get_connection()?;
+        match conn.query_row(
+            "SELECT strokes_json FROM doodles WHERE book_id = ?1 AND page_number = ?2",
+            rusqlite::params![book_id, page_number],
+            |row| row.get(0),
+        ) {
+            Ok(json) => json,
+            Err(rusqlite::Error::QueryReturnedNoRows) => String::new(),
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let bytes = doodle_service::render(&strokes_json, format, width, height)?;
+        std::fs::write(&file_path, bytes)?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+/// Pages of a book that have doodles, for page indicators
+#[tauri::command]
+pub fn list_doodle_pages(book_id: i64, state: State<AppState>) -> Result<Vec<String>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let conn = state.db.get_connection()?;
+    doodle_service::list_doodle_pages(&conn, book_id)
+}
+
+/// Copy all doodles of one book onto another, e.g. after a re-import gave
+/// the book a new id. Returns the number of pages copied.
+#[tauri::command]
+pub fn copy_doodles(
+    from_book_id: i64,
+    to_book_id: i64,
+    overwrite: bool,
+    state: State<AppState>,
+) -> Result<usize> {
+    validate::require_positive_id(from_book_id, "from_book_id")?;
+    validate::require_positive_id(to_book_id, "to_book_id")?;
+    let conn = state.db.get_connection()?;
+    doodle_service::copy_doodles(&conn, from_book_id, to_book_id, overwrite)
+}
+
+/// Move a doodle to another page of the same book
+#[tauri::command]
+pub fn move_doodle(
+    book_id: i64,
+    from_page: String,
+    to_page: String,
+    overwrite: bool,
+    state: State<AppState>,
+) -> Result<()> {
+    validate::require_positive_id(book_id, "book_id")?;
+    validate::require_non_empty(&from_page, "from_page")?;
+    validate::require_non_empty(&to_page, "to_page")?;
+    let conn = state.db.get_connection()?;
+    doodle_service::move_doodle(&conn, book_id, &from_page, &to_page, overwrite)
+}
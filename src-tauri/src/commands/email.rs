@@ -0,0 +1,47 @@
+use tauri::State;
+
+use crate::error::{Result, ShioriError};
+use crate::services::email_service::{self, SentBook, SmtpSettings};
+use crate::utils::validate;
+use crate::AppState;
+
+/// SMTP account used for emailing books. The password is never returned.
+/// The OS credential store can block (e.g. an unlock prompt), so it is
+/// read off the async runtime.
+#[tauri::command]
+pub async fn get_smtp_settings(state: State<'_, AppState>) -> Result<SmtpSettings> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || email_service::load_smtp_settings(&db))
+        .await
+        .map_err(|e| ShioriError::Other(e.to_string()))?
+}
+
+/// Save the SMTP account. Omit `password` to keep the stored one, or pass
+/// an empty string to remove it.
+#[tauri::command]
+pub async fn update_smtp_settings(
+    state: State<'_, AppState>,
+    settings: SmtpSettings,
+    password: Option<String>,
+) -> Result<SmtpSettings> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        email_service::save_smtp_settings(&db, &settings, password.as_deref())
+    })
+    .await
+    .map_err(|e| ShioriError::Other(e.to_string()))?
+}
+
+/// Email a book as an attachment, optionally converting it first
+/// (e.g. `convert_to: "mobi"` for older Kindles)
+#[tauri::command]
+pub async fn send_book_to_email(
+    state: State<'_, AppState>,
+    book_id: i64,
+    to_address: String,
+    convert_to: Option<String>,
+) -> Result<SentBook> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let db = state.db.clone();
+    email_service::send_book(&db, book_id, &to_address, convert_to.as_deref()).await
+}
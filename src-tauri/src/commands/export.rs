@@ -33,6 +33,100 @@ pub fn export_library(state: State<AppState>, options: ExportOptions) -> Result<
     export_service::export_library(&db, export_opts)
 }
 
+/// Write a spreadsheet-friendly catalog (one record per book) to `file_path`.
+/// Returns the number of books exported.
+#[tauri::command]
+pub async fn export_library_catalog(
+    state: State<'_, AppState>,
+    format: String,
+    file_path: String,
+) -> Result<usize> {
+    crate::utils::validate::require_safe_path(&file_path, "file_path")?;
+    let format = match format.to_lowercase().as_str() {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        _ => {
+            return Err(crate::error::ShioriError::InvalidOperation(format!(
+                "Unsupported catalog format: {}",
+                format
+            )))
+        }
+    };
+    let db = state.db.clone();
+
+    tokio::task::spawn_blocking(move || export_service::export_catalog(&db, format, &file_path))
+        .await
+        .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+/// Serialize reading progress and annotations, keyed by book UUID, for transfer to another machine.
+#[tauri::command]
+pub fn export_reading_state(state: State<AppState>) -> Result<String> {
+    export_service::export_reading_state(&state.db)
+}
+
+/// Merge reading state produced by `export_reading_state` on another machine.
+#[tauri::command]
+pub fn import_reading_state(
+    json: String,
+    state: State<AppState>,
+) -> Result<export_service::ReadingStateImportReport> {
+    crate::utils::validate::require_non_empty(&json, "json")?;
+    export_service::import_reading_state(&state.db, &json)
+}
+
+/// Render a book's annotations as a Markdown or CSV document.
+#[tauri::command]
+pub fn export_book_annotations(
+    book_id: i64,
+    format: export_service::AnnotationExportFormat,
+    state: State<AppState>,
+) -> Result<String> {
+    crate::utils::validate::require_positive_id(book_id, "book_id")?;
+    export_service::export_annotations(&state.db, book_id, format)
+}
+
+/// Merge a collection's EPUB/TXT books into one EPUB under `<app data>/exports`.
+#[tauri::command]
+pub async fn export_collection_anthology(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    collection_id: i64,
+) -> Result<export_service::AnthologyExport> {
+    crate::utils::validate::require_positive_id(collection_id, "collection_id")?;
+    use tauri::Manager;
+    let db = state.db.clone();
+    let output_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| crate::error::ShioriError::Other(format!("Failed to get app dir: {}", e)))?
+        .join("exports");
+
+    tokio::task::spawn_blocking(move || {
+        export_service::export_collection_as_anthology(&db, collection_id, &output_dir)
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+/// Write a book as one self-contained HTML file under `<app data>/exports`.
+#[tauri::command]
+pub async fn export_book_html(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    book_id: i64,
+) -> Result<export_service::HtmlExport> {
+    crate::utils::validate::require_positive_id(book_id, "book_id")?;
+    use tauri::Manager;
+    let output_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| crate::error::ShioriError::Other(format!("Failed to get app dir: {}", e)))?
+        .join("exports");
+
+    export_service::export_book_as_html(&state.db, book_id, &output_dir).await
+}
+
 /// Write arbitrary text content to a user-selected file path.
 /// Used by the annotation export dialog's "Save to File" button.
 #[tauri::command]
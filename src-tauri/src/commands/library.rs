@@ -1,4 +1,4 @@
-use crate::services::library_service;
+use crate::services::{author_service, auto_scan_scheduler, library_service};
 use crate::utils::validate;
 use crate::{
     error::Result,
@@ -84,9 +84,15 @@ pub fn start_background_scan(
 }
 
 #[tauri::command]
-pub fn get_books(state: State<AppState>, limit: u32, offset: u32) -> Result<Vec<Book>> {
+pub fn get_books(
+    state: State<AppState>,
+    limit: u32,
+    offset: u32,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
+) -> Result<Vec<Book>> {
     let db = &state.db;
-    library_service::get_all_books(db, limit, offset)
+    library_service::get_all_books(db, limit, offset, sort_by.as_deref(), sort_order.as_deref())
 }
 
 #[tauri::command]
@@ -114,14 +120,66 @@ pub fn add_book(state: State<AppState>, book: Book) -> Result<i64> {
 }
 
 #[tauri::command]
-pub fn update_book(state: State<AppState>, book: Book) -> Result<()> {
+pub fn update_book(
+    state: State<AppState>,
+    book: Book,
+    write_to_file: Option<bool>,
+) -> Result<()> {
     if let Some(id) = book.id {
         validate::require_positive_id(id, "book id")?;
     }
     validate::require_non_empty(&book.title, "title")?;
     validate::require_max_length(&book.title, 1000, "title")?;
     let db = &state.db;
-    library_service::update_book(db, book)
+    library_service::update_book(db, book, write_to_file.unwrap_or(false))
+}
+
+/// Set the same fields on many books at once; see `library_service::BookPatch`
+#[tauri::command]
+pub fn bulk_update_books(
+    state: State<AppState>,
+    book_ids: Vec<i64>,
+    patch: library_service::BookPatch,
+) -> Result<usize> {
+    validate::require_non_empty_vec(&book_ids, "book ids")?;
+    for &id in &book_ids {
+        validate::require_positive_id(id, "book id")?;
+    }
+    library_service::bulk_update(&state.db, &book_ids, &patch)
+}
+
+/// Every file attached to a book, primary first
+#[tauri::command]
+pub fn get_book_formats(
+    state: State<AppState>,
+    book_id: i64,
+) -> Result<Vec<library_service::BookFormat>> {
+    validate::require_positive_id(book_id, "book id")?;
+    library_service::get_book_formats(&state.db, book_id)
+}
+
+#[tauri::command]
+pub fn add_book_format(
+    state: State<AppState>,
+    book_id: i64,
+    path: String,
+) -> Result<library_service::BookFormat> {
+    validate::require_positive_id(book_id, "book id")?;
+    validate::require_safe_path(&path, "path")?;
+    library_service::add_book_format(&state.db, book_id, &path, &state.covers_dir)
+}
+
+#[tauri::command]
+pub fn set_primary_format(state: State<AppState>, book_id: i64, format: String) -> Result<()> {
+    validate::require_positive_id(book_id, "book id")?;
+    validate::require_non_empty(&format, "format")?;
+    library_service::set_primary_format(&state.db, book_id, &format)
+}
+
+#[tauri::command]
+pub fn delete_book_format(state: State<AppState>, format_id: i64) -> Result<()> {
+    validate::require_positive_id(format_id, "format id")?;
+    library_service::delete_book_format(&state.db, format_id)
 }
 
 #[tauri::command]
@@ -258,22 +316,93 @@ pub fn clean_up_database(state: State<AppState>) -> Result<(usize, usize)> {
     result
 }
 
+/// Import files and ZIP bundles of ebooks, emitting `import:progress` as each file or
+/// archive entry finishes.
+/// `policy` decides what happens to files already in the library (skipped by default).
 #[tauri::command]
 pub async fn import_books(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     paths: Vec<String>,
+    policy: Option<library_service::ImportPolicy>,
 ) -> Result<ImportResult> {
     validate::require_non_empty_vec(&paths, "file paths")?;
     // Per-path safety validation happens inside library_service::import_books so that one
     // unsafe/invalid path is recorded as a failed entry instead of aborting the whole batch
     // (see library_service::import_books).
     let db = state.db.clone();
+    use tauri::Manager;
     let covers_dir = state.covers_dir.clone();
-    let result =
-        tokio::task::spawn_blocking(move || library_service::import_books(&db, paths, &covers_dir))
-            .await
-            .map_err(|e| crate::error::ShioriError::Other(e.to_string()))??;
+    let storage_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| crate::error::ShioriError::Other(format!("Failed to get app dir: {}", e)))?
+        .join("storage");
+
+    // ZIP bundles of ebooks are unpacked and imported entry by entry
+    let (archives, files): (Vec<String>, Vec<String>) = paths
+        .into_iter()
+        .partition(|p| p.to_lowercase().ends_with(".zip"));
+
+    let policy = policy.unwrap_or_default();
+    let progress_handle = app_handle.clone();
+    let result = tokio::task::spawn_blocking(move || -> Result<ImportResult> {
+        // Archive entries count toward the total so progress doesn't finish early
+        let archive_entries: Vec<usize> = archives
+            .iter()
+            .map(|archive| library_service::count_archive_entries(archive))
+            .collect();
+        let total = files.len() + archive_entries.iter().sum::<usize>();
+        let processed = std::cell::Cell::new(0);
+        let report = |path: &str| {
+            processed.set(processed.get() + 1);
+            let _ = progress_handle.emit(
+                "import:progress",
+                library_service::ImportProgress {
+                    processed: processed.get(),
+                    total,
+                    path: path.to_string(),
+                },
+            );
+        };
+
+        let mut result = library_service::import_books_with_progress(
+            &db,
+            files,
+            &covers_dir,
+            policy,
+            |progress| report(&progress.path),
+        )?;
+        for (archive, entries) in archives.into_iter().zip(archive_entries) {
+            let reported = processed.get() + entries;
+            match library_service::import_archive_with_progress(
+                &db,
+                &archive,
+                "books",
+                &storage_dir,
+                &covers_dir,
+                policy,
+                report,
+            ) {
+                Ok(archive_result) => {
+                    result.success.extend(archive_result.success);
+                    result.failed.extend(archive_result.failed);
+                    result.duplicates.extend(archive_result.duplicates);
+                    result.merged.extend(archive_result.merged);
+                    result.replaced.extend(archive_result.replaced);
+                }
+                Err(e) => result.failed.push((archive.clone(), e.to_string())),
+            }
+            // An archive that failed part way still accounts for all its entries
+            if processed.get() < reported {
+                processed.set(reported - 1);
+                report(&archive);
+            }
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))??;
 
     let _ = app_handle.emit("library-updated", ());
     Ok(result)
@@ -312,6 +441,118 @@ pub async fn scan_folder_unified(
     Ok(result)
 }
 
+#[tauri::command]
+pub fn get_managed_library(state: State<'_, AppState>) -> Result<bool> {
+    library_service::get_managed_library(&state.db)
+}
+
+/// Toggle copying imported files into app storage instead of referencing them in place
+#[tauri::command]
+pub fn set_managed_library(state: State<'_, AppState>, enabled: bool) -> Result<()> {
+    library_service::set_managed_library(&state.db, enabled)
+}
+
+#[tauri::command]
+pub fn get_series_autofill(state: State<'_, AppState>) -> Result<bool> {
+    library_service::get_series_autofill(&state.db)
+}
+
+/// Toggle reading series name and number from filenames on import
+#[tauri::command]
+pub fn set_series_autofill(state: State<'_, AppState>, enabled: bool) -> Result<()> {
+    library_service::set_series_autofill(&state.db, enabled)
+}
+
+/// Repoint books whose files were moved, searching managed storage and `search_dirs`
+#[tauri::command]
+pub async fn relocate_missing_files(
+    state: State<'_, AppState>,
+    search_dirs: Option<Vec<String>>,
+) -> Result<library_service::RelocationReport> {
+    let search_dirs = search_dirs.unwrap_or_default();
+    for dir in &search_dirs {
+        validate::require_safe_path(dir, "search dir")?;
+    }
+    let db = state.db.clone();
+    let covers_dir = state.covers_dir.clone();
+
+    tokio::task::spawn_blocking(move || {
+        library_service::relocate_missing_files(&db, &covers_dir, &search_dirs)
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+#[derive(Clone, Serialize)]
+struct LibraryValidationProgress {
+    processed: usize,
+    total: usize,
+}
+
+/// Check every book's file for being missing, changed since import or
+/// corrupt. Emits `library-validation-progress` while it runs.
+#[tauri::command]
+pub async fn validate_library(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<library_service::BookIntegrity>> {
+    let db = state.db.clone();
+
+    tokio::task::spawn_blocking(move || {
+        library_service::validate_library(&db, |processed, total| {
+            let _ = app_handle.emit(
+                "library-validation-progress",
+                LibraryValidationProgress { processed, total },
+            );
+        })
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+/// Folders the background auto-scan keeps imported
+#[tauri::command]
+pub fn get_auto_scan_folders(
+    state: State<'_, AppState>,
+) -> Result<Vec<auto_scan_scheduler::AutoScanFolder>> {
+    auto_scan_scheduler::get_auto_scan_folders(&state.db)
+}
+
+/// Replace the auto-scan folder list and scan the new list right away
+#[tauri::command]
+pub fn set_auto_scan_folders(
+    state: State<'_, AppState>,
+    scheduler: State<'_, std::sync::Arc<auto_scan_scheduler::AutoScanScheduler>>,
+    folders: Vec<auto_scan_scheduler::AutoScanFolder>,
+) -> Result<()> {
+    auto_scan_scheduler::set_auto_scan_folders(&state.db, &folders)?;
+    scheduler.trigger();
+    Ok(())
+}
+
+/// Re-scan a previously scanned folder, only importing new or modified files
+#[tauri::command]
+pub async fn rescan_folder(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+) -> Result<crate::models::RescanResult> {
+    validate::require_safe_path(&folder_path, "folder path")?;
+    let db = state.db.clone();
+    let covers_dir = state.covers_dir.clone();
+
+    let result = tokio::task::spawn_blocking(move || {
+        library_service::rescan_folder(&db, &folder_path, &covers_dir)
+    })
+    .await
+    .map_err(|e| crate::error::ShioriError::Other(e.to_string()))??;
+
+    if result.added > 0 || result.updated > 0 {
+        let _ = app_handle.emit("library-updated", ());
+    }
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn import_manga(
     app_handle: tauri::AppHandle,
@@ -436,10 +677,19 @@ pub fn get_books_by_domain(
     domain: String,
     limit: u32,
     offset: u32,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
 ) -> Result<Vec<Book>> {
     validate::require_one_of(&domain, &["books", "manga", "comics", "all"], "domain")?;
     let db = &state.db;
-    library_service::get_books_by_domain(db, &domain, limit, offset)
+    library_service::get_books_by_domain(
+        db,
+        &domain,
+        limit,
+        offset,
+        sort_by.as_deref(),
+        sort_order.as_deref(),
+    )
 }
 
 #[tauri::command]
@@ -494,6 +744,64 @@ pub fn get_reading_history(
     library_service::get_reading_history(&app_state.db, limit, offset)
 }
 
+/// Books the user opened most recently in a reader
+#[tauri::command]
+pub fn get_recently_opened(
+    app_state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> Result<Vec<Book>> {
+    library_service::get_recently_opened(&app_state.db, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+pub fn get_continue_reading(
+    app_state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> Result<Vec<crate::models::BookWithProgress>> {
+    library_service::get_continue_reading(&app_state.db, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+pub fn get_recently_finished(
+    app_state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> Result<Vec<crate::models::BookWithProgress>> {
+    library_service::get_recently_finished(&app_state.db, limit.unwrap_or(20))
+}
+
+/// Exact duplicate report: `mode` is "hash", "isbn" or "title_author"
+#[tauri::command]
+pub fn find_duplicate_groups(
+    state: State<'_, AppState>,
+    mode: String,
+) -> Result<Vec<crate::models::DuplicateGroup>> {
+    let mode = library_service::DuplicateMode::parse(&mode)?;
+    library_service::find_duplicates(&state.db, mode)
+}
+
+#[tauri::command]
+pub fn merge_books(state: State<'_, AppState>, keep_id: i64, merge_ids: Vec<i64>) -> Result<usize> {
+    validate::require_positive_id(keep_id, "keep_id")?;
+    validate::require_non_empty_vec(&merge_ids, "merge_ids")?;
+    library_service::merge_books(&state.db, keep_id, &merge_ids)
+}
+
+/// Author rows whose names normalize to the same person
+#[tauri::command]
+pub fn find_duplicate_authors(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::models::DuplicateAuthorGroup>> {
+    author_service::find_duplicate_authors(&state.db)
+}
+
+/// Fold author `from_id` into `into_id`; returns the number of books re-credited
+#[tauri::command]
+pub fn merge_authors(state: State<'_, AppState>, from_id: i64, into_id: i64) -> Result<usize> {
+    validate::require_positive_id(from_id, "from_id")?;
+    validate::require_positive_id(into_id, "into_id")?;
+    author_service::merge_authors(&state.db, from_id, into_id)
+}
+
 #[tauri::command]
 pub async fn find_duplicate_books(
     criteria: String,
@@ -501,7 +809,7 @@ pub async fn find_duplicate_books(
     state: State<'_, AppState>,
 ) -> Result<Vec<Vec<Book>>> {
     let db = &state.db;
-    let books = crate::services::library_service::get_all_books(db, u32::MAX, 0)?;
+    let books = crate::services::library_service::get_all_books(db, u32::MAX, 0, None, None)?;
 
     let duplicates = tokio::task::spawn_blocking(move || {
         let mut duplicates: Vec<Vec<Book>> = Vec::new();
@@ -910,6 +1218,21 @@ pub fn get_recommended_books(
     crate::services::library_service::get_recommended_books(db, limit)
 }
 
+/// Library page where each series is collapsed into one group
+#[tauri::command]
+pub fn get_books_grouped_by_series(
+    state: State<'_, AppState>,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<crate::models::SeriesGroup>> {
+    library_service::get_books_grouped_by_series(&state.db, limit, offset)
+}
+
+#[tauri::command]
+pub fn get_series_list(state: State<'_, AppState>) -> Result<Vec<crate::models::SeriesSummary>> {
+    library_service::get_series_list(&state.db)
+}
+
 #[tauri::command]
 pub fn get_next_book_in_series(
     state: State<'_, AppState>,
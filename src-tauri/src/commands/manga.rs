@@ -1,6 +1,9 @@
 use crate::error::Result;
 use crate::models::{MangaSeries, MangaVolume};
-use crate::services::manga_service::{MangaMetadata, MangaService};
+use crate::services::library_service;
+use crate::services::manga_service::{
+    MangaCacheStats, MangaMetadata, MangaService, MangaSpread, ReadingDirection, StripPage,
+};
 use crate::utils::validate;
 use crate::AppState;
 use lazy_static::lazy_static;
@@ -15,9 +18,9 @@ pub struct MangaState {
 }
 
 impl MangaState {
-    pub fn new() -> Self {
+    pub fn new(cache_budget_bytes: usize) -> Self {
         Self {
-            service: Arc::new(MangaService::new()),
+            service: Arc::new(MangaService::new(cache_budget_bytes)),
         }
     }
 }
@@ -25,10 +28,17 @@ impl MangaState {
 // ==================== Manga Reader Commands ====================
 
 #[tauri::command]
-pub fn open_manga(book_id: i64, path: String, state: State<MangaState>) -> Result<MangaMetadata> {
+pub fn open_manga(
+    book_id: i64,
+    path: String,
+    state: State<MangaState>,
+    app_state: State<AppState>,
+) -> Result<MangaMetadata> {
     validate::require_positive_id(book_id, "book_id")?;
     validate::require_safe_path(&path, "path")?;
-    state.service.open(book_id, &path)
+    let metadata = state.service.open(book_id, &path)?;
+    library_service::mark_book_opened_in_background(app_state.db.clone(), book_id);
+    Ok(metadata)
 }
 
 #[tauri::command]
@@ -46,21 +56,85 @@ pub async fn get_manga_page(
     Ok(tauri::ipc::Response::new(bytes))
 }
 
+/// Preload pages into the cache. Only the first `manga_preload_count`
+/// (from preferences) of `page_indices` are loaded.
 #[tauri::command]
 pub async fn preload_manga_pages(
     book_id: i64,
     page_indices: Vec<usize>,
     max_dimension: u32,
     state: State<'_, MangaState>,
+    app_state: State<'_, AppState>,
 ) -> Result<()> {
     validate::require_positive_id(book_id, "book_id")?;
     validate::require_non_empty_vec(&page_indices, "page_indices")?;
+    let limit = app_state.db.manga_preload_count();
+    let page_indices = &page_indices[..page_indices.len().min(limit)];
+    state
+        .service
+        .preload_pages(book_id, page_indices, max_dimension)
+        .await
+}
+
+/// Long-strip layout of the open manga with every page scaled to
+/// `column_width`. Reads every page's size, so it runs on the blocking pool.
+#[tauri::command]
+pub async fn get_manga_strip_manifest(
+    book_id: i64,
+    column_width: u32,
+    state: State<'_, MangaState>,
+) -> Result<Vec<StripPage>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    require_column_width(column_width)?;
+    let service = state.service.clone();
+    tokio::task::spawn_blocking(move || service.get_strip_manifest(book_id, column_width))
+        .await
+        .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
+/// Decode the long-strip pages around `scroll_y`, plus `manga_preload_count`
+/// pages below the viewport, so they are cached (at the `max_dimension` the
+/// reader fetches with) before they scroll into view
+#[tauri::command]
+pub async fn prefetch_manga_strip(
+    book_id: i64,
+    column_width: u32,
+    scroll_y: u64,
+    viewport_height: u64,
+    max_dimension: u32,
+    state: State<'_, MangaState>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<usize>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    require_column_width(column_width)?;
+    let ahead = app_state.db.manga_preload_count();
     state
         .service
-        .preload_pages(book_id, &page_indices, max_dimension)
+        .prefetch_strip(
+            book_id,
+            column_width,
+            scroll_y,
+            viewport_height,
+            max_dimension,
+            ahead,
+        )
         .await
 }
 
+fn require_column_width(column_width: u32) -> Result<()> {
+    if !(1..=8192).contains(&column_width) {
+        return Err(crate::error::ShioriError::Validation(
+            "column_width must be between 1 and 8192".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_manga_cache_stats(state: State<MangaState>) -> Result<MangaCacheStats> {
+    Ok(state.service.cache_stats())
+}
+
 #[tauri::command]
 pub fn get_manga_page_dimensions(
     book_id: i64,
@@ -72,6 +146,23 @@ pub fn get_manga_page_dimensions(
     state.service.get_page_dimensions(book_id, &page_indices)
 }
 
+/// Group the open manga's pages into double-page spreads for `direction`
+/// (`ltr` or `rtl`). Page sizes may have to be read from the archive, so
+/// this runs on the blocking pool.
+#[tauri::command]
+pub async fn get_manga_spreads(
+    book_id: i64,
+    direction: String,
+    state: State<'_, MangaState>,
+) -> Result<Vec<MangaSpread>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let direction = ReadingDirection::parse(&direction)?;
+    let service = state.service.clone();
+    tokio::task::spawn_blocking(move || service.get_spreads(book_id, direction))
+        .await
+        .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?
+}
+
 #[tauri::command]
 pub fn close_manga(book_id: i64, state: State<MangaState>) -> Result<()> {
     validate::require_positive_id(book_id, "book_id")?;
@@ -1,7 +1,9 @@
 use crate::error::{Result, ShioriError};
 use crate::models::Metadata;
 use crate::services::book_metadata_service::BookMetadataService;
-use crate::services::manga_metadata_service::{parse_manga_title, MangaMetadataService};
+use crate::services::manga_metadata_service::{
+    parse_manga_title, MangaMetadataService, ParsedMangaFilename,
+};
 use crate::services::metadata_service;
 use crate::utils::validate;
 use std::sync::Arc;
@@ -29,6 +31,20 @@ pub async fn search_manga_metadata(
     service.search_manga(&title, include_nsfw).await
 }
 
+/// Search AniList light novels, also returning manga entries with a matching title
+#[tauri::command]
+pub async fn search_light_novel_metadata(
+    service: State<'_, Arc<MangaMetadataService>>,
+    title: String,
+    include_nsfw: bool,
+) -> Result<Vec<crate::services::manga_metadata_service::MangaMetadata>> {
+    validate::require_non_empty(&title, "title")?;
+    validate::require_max_length(&title, 500, "title")?;
+    service
+        .search_light_novel_or_manga(&title, include_nsfw)
+        .await
+}
+
 #[tauri::command]
 pub async fn get_manga_metadata_by_id(
     service: State<'_, Arc<MangaMetadataService>>,
@@ -44,6 +60,13 @@ pub fn parse_manga_filename(filename: String) -> Result<String> {
     Ok(parse_manga_title(&filename))
 }
 
+/// Parse a manga filename into series, volume, chapter, year and chapter title
+#[tauri::command]
+pub fn parse_manga_filename_details(filename: String) -> Result<ParsedMangaFilename> {
+    validate::require_non_empty(&filename, "filename")?;
+    Ok(crate::services::manga_metadata_service::parse_manga_filename(&filename))
+}
+
 // ═══════════════════════════════════════════════════════════
 // BOOK METADATA COMMANDS (Open Library API)
 // ═══════════════════════════════════════════════════════════
@@ -73,59 +96,75 @@ pub async fn search_book_by_isbn(
 // METADATA ENRICHMENT (Background)
 // ═══════════════════════════════════════════════════════════
 
-/// Auto-fetch metadata for a book/manga by dispatching to the background worker
-#[tauri::command]
-pub async fn enrich_book_metadata(
-    app_state: State<'_, crate::AppState>,
-    metadata_state: State<'_, crate::MetadataState>,
-    book_id: i64,
-) -> Result<bool> {
-    validate::require_positive_id(book_id, "book_id")?;
-    use crate::services::library_service;
-    use crate::services::manga_metadata_service::parse_manga_title;
+/// Build the background enrichment job for a book, picking the provider route
+/// (cbz/cbr → Comic Vine when configured, then AniList; light novel →
+/// AniList novels; otherwise Open Library)
+fn build_metadata_job(
+    book: crate::models::Book,
+    force_refresh: bool,
+    batch: Option<Arc<crate::services::online::worker::MetadataBatch>>,
+    comicvine_configured: bool,
+) -> crate::services::online::worker::MetadataJob {
     use crate::services::online::provider::{ItemType, MetadataQuery};
     use crate::services::online::worker::MetadataJob;
 
-    let book = {
-        let db = &app_state.db;
-        library_service::get_book_by_id(db, book_id)?
-    };
-
     let is_manga = matches!(book.file_format.to_lowercase().as_str(), "cbz" | "cbr");
+    let is_light_novel = is_light_novel(&book);
 
     let query = if is_manga {
         let parsed_title = parse_manga_title(&book.title);
         MetadataQuery::Title(parsed_title)
+    } else if is_light_novel {
+        MetadataQuery::Title(book.title.clone())
+    } else if let Some(isbn) = book.isbn.clone().or(book.isbn13.clone()) {
+        MetadataQuery::Isbn(isbn)
     } else {
-        if let Some(isbn) = book.isbn.or(book.isbn13) {
-            MetadataQuery::Isbn(isbn)
-        } else {
-            let author = book.authors.first().map(|a| a.name.clone());
-            MetadataQuery::TitleAuthor {
-                title: book.title.clone(),
-                author,
-            }
+        let author = book.authors.first().map(|a| a.name.clone());
+        MetadataQuery::TitleAuthor {
+            title: book.title.clone(),
+            author,
         }
     };
 
-    let item_type = if is_manga {
+    // Comic jobs fall back to AniList when Comic Vine has no match
+    let item_type = if is_manga && comicvine_configured {
+        ItemType::Comic
+    } else if is_manga {
         ItemType::Manga
+    } else if is_light_novel {
+        ItemType::LightNovel
     } else {
         ItemType::Book
     };
 
-    let job = MetadataJob {
-        item_id: book_id,
+    MetadataJob {
+        item_id: book.id.unwrap_or_default(),
         item_type,
         query,
-        force_refresh: true, // Manual refresh skips local cache checks
+        force_refresh,
+        batch,
+    }
+}
+
+/// Auto-fetch metadata for a book/manga by dispatching to the background worker
+#[tauri::command]
+pub async fn enrich_book_metadata(
+    app_state: State<'_, crate::AppState>,
+    metadata_state: State<'_, crate::MetadataState>,
+    book_id: i64,
+) -> Result<bool> {
+    validate::require_positive_id(book_id, "book_id")?;
+    use crate::services::library_service;
+
+    let book = {
+        let db = &app_state.db;
+        library_service::get_book_by_id(db, book_id)?
     };
 
-    metadata_state
-        .sender
-        .send(job)
-        .await
-        .map_err(|e| ShioriError::Other(format!("Failed to dispatch metadata job: {}", e)))?;
+    // Manual refresh skips local cache checks
+    let job = build_metadata_job(book, true, None, app_state.db.comicvine_api_key().is_some());
+    let item_type = job.item_type;
+    dispatch_metadata_job(&metadata_state, job).await?;
 
     log::info!(
         "[enrich_book_metadata] Dispatched background fetch for book {} ({:?})",
@@ -137,6 +176,185 @@ pub async fn enrich_book_metadata(
     Ok(true)
 }
 
+/// Queue background enrichment for every book in a collection. Books already
+/// enriched are skipped unless `force` is set. Returns the number of jobs queued;
+/// progress is reported through `metadata:collection_progress` events.
+#[tauri::command]
+pub async fn enrich_collection_metadata(
+    app_state: State<'_, crate::AppState>,
+    metadata_state: State<'_, crate::MetadataState>,
+    collection_id: i64,
+    force: Option<bool>,
+) -> Result<usize> {
+    validate::require_positive_id(collection_id, "collection_id")?;
+    use crate::services::collection_service::CollectionService;
+    use crate::services::library_service;
+    use crate::services::online::worker::MetadataBatch;
+
+    let force = force.unwrap_or(false);
+    let db = &app_state.db;
+
+    let members = {
+        let conn = db.get_connection()?;
+        CollectionService::get_collection_books(&conn, collection_id, false)?
+    };
+
+    // Reload each member so authors/tags are available for query building
+    let mut books = Vec::new();
+    for member in members {
+        if member.online_metadata_fetched && !force {
+            continue;
+        }
+        if let Some(id) = member.id {
+            books.push(library_service::get_book_by_id(db, id)?);
+        }
+    }
+
+    let batch = Arc::new(MetadataBatch::new(collection_id, books.len()));
+    let queued = books.len();
+    let comicvine_configured = db.comicvine_api_key().is_some();
+    for book in books {
+        let job = build_metadata_job(book, force, Some(Arc::clone(&batch)), comicvine_configured);
+        dispatch_metadata_job(&metadata_state, job).await?;
+    }
+
+    log::info!(
+        "[enrich_collection_metadata] Queued {} books from collection {}",
+        queued,
+        collection_id
+    );
+    Ok(queued)
+}
+
+/// Stop background enrichment for a book. Queued jobs are skipped and a
+/// running one stops before its next provider call or before writing
+/// results; the worker emits `metadata:cancelled` when it does.
+#[tauri::command]
+pub fn cancel_metadata_job(
+    metadata_state: State<'_, crate::MetadataState>,
+    book_id: i64,
+) -> Result<()> {
+    validate::require_positive_id(book_id, "book_id")?;
+    metadata_state.cancelled.insert(book_id);
+    Ok(())
+}
+
+/// Stop background enrichment for several books at once, see [`cancel_metadata_job`]
+#[tauri::command]
+pub fn cancel_metadata_jobs(
+    metadata_state: State<'_, crate::MetadataState>,
+    book_ids: Vec<i64>,
+) -> Result<()> {
+    for &book_id in &book_ids {
+        validate::require_positive_id(book_id, "book_id")?;
+    }
+    for book_id in book_ids {
+        metadata_state.cancelled.insert(book_id);
+    }
+    Ok(())
+}
+
+/// Stop every queued or running enrichment job. Returns the number of books
+/// whose jobs were cancelled.
+#[tauri::command]
+pub fn cancel_all_metadata_jobs(metadata_state: State<'_, crate::MetadataState>) -> Result<usize> {
+    use crate::services::online::worker::MetadataWorker;
+
+    Ok(MetadataWorker::cancel_all(
+        &metadata_state.cancelled,
+        &metadata_state.queued,
+    ))
+}
+
+/// Hand a job to the background worker, keeping the queue bookkeeping in step
+async fn dispatch_metadata_job(
+    metadata_state: &crate::MetadataState,
+    job: crate::services::online::worker::MetadataJob,
+) -> Result<()> {
+    use crate::services::online::worker::MetadataWorker;
+
+    let item_id = job.item_id;
+    MetadataWorker::track_queued(&metadata_state.cancelled, &metadata_state.queued, item_id);
+    if let Err(e) = metadata_state.sender.send(job).await {
+        MetadataWorker::finish_job(&metadata_state.cancelled, &metadata_state.queued, item_id);
+        return Err(ShioriError::Other(format!(
+            "Failed to dispatch metadata job: {}",
+            e
+        )));
+    }
+    Ok(())
+}
+
+/// Remove expired metadata cache entries and trim the cache to its row cap.
+/// Returns the number of entries removed.
+#[tauri::command]
+pub async fn cleanup_metadata_cache(app_state: State<'_, crate::AppState>) -> Result<usize> {
+    use crate::services::online::cache;
+
+    let db = app_state.db.clone();
+    tokio::task::spawn_blocking(move || cache::cleanup_metadata_cache(&db, cache::MAX_CACHE_ROWS))
+        .await
+        .map_err(|e| ShioriError::Other(format!("Cache cleanup task failed: {}", e)))?
+}
+
+/// Size of the online metadata cache, overall and per provider
+#[tauri::command]
+pub fn get_metadata_cache_stats(
+    app_state: State<'_, crate::AppState>,
+) -> Result<crate::services::online::cache::MetadataCacheStats> {
+    crate::services::online::cache::get_metadata_cache_stats(&app_state.db)
+}
+
+/// List low-confidence metadata matches waiting for the user to confirm them
+#[tauri::command]
+pub fn list_pending_metadata_matches(
+    app_state: State<'_, crate::AppState>,
+) -> Result<Vec<crate::services::online::worker::PendingMetadataMatch>> {
+    crate::services::online::worker::MetadataWorker::list_pending_matches(&app_state.db)
+}
+
+/// Apply a pending low-confidence match to its book
+#[tauri::command]
+pub async fn confirm_metadata_match(
+    app_state: State<'_, crate::AppState>,
+    match_id: i64,
+) -> Result<i64> {
+    validate::require_positive_id(match_id, "match_id")?;
+    use crate::services::online::worker::MetadataWorker;
+
+    let db = &app_state.db;
+    let pending = MetadataWorker::take_pending_match(db, match_id)?
+        .ok_or_else(|| ShioriError::Other(format!("Metadata match {} not found", match_id)))?;
+
+    MetadataWorker::apply_metadata(db, pending.book_id, pending.metadata, false).await;
+    log::info!(
+        "[confirm_metadata_match] Applied match {} to book {}",
+        match_id,
+        pending.book_id
+    );
+    Ok(pending.book_id)
+}
+
+/// Discard a pending low-confidence match without touching the book
+#[tauri::command]
+pub fn reject_metadata_match(app_state: State<'_, crate::AppState>, match_id: i64) -> Result<()> {
+    validate::require_positive_id(match_id, "match_id")?;
+    use crate::services::online::worker::MetadataWorker;
+
+    MetadataWorker::take_pending_match(&app_state.db, match_id)?
+        .ok_or_else(|| ShioriError::Other(format!("Metadata match {} not found", match_id)))?;
+    Ok(())
+}
+
+/// Prose books tagged as light novels are looked up on AniList instead of Open Library
+fn is_light_novel(book: &crate::models::Book) -> bool {
+    matches!(book.file_format.to_lowercase().as_str(), "epub" | "txt")
+        && book.tags.iter().any(|tag| {
+            let name = tag.name.to_lowercase().replace(['-', '_'], " ");
+            name.trim() == "light novel" || name.trim() == "light novels"
+        })
+}
+
 // ═══════════════════════════════════════════════════════════
 // PREVIEW COVER
 // ═══════════════════════════════════════════════════════════
@@ -304,7 +522,7 @@ pub async fn apply_selected_metadata(
     );
     book.metadata_last_sync = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
 
-    library_service::update_book(db, book)?;
+    library_service::update_book(db, book, false)?;
 
     let conn = db.get_connection()?;
     conn.execute(
@@ -573,3 +791,58 @@ pub async fn apply_selected_series_metadata(
     );
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::online::provider::{ItemType, MetadataQuery};
+
+    fn book(format: &str, tags: &[&str]) -> crate::models::Book {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uuid": "uuid-1",
+            "title": "Spice and Wolf",
+            "isbn": "9780759531048",
+            "file_path": format!("book.{}", format),
+            "file_format": format,
+            "language": "en",
+            "added_date": "2024-01-01T00:00:00Z",
+            "modified_date": "2024-01-01T00:00:00Z",
+            "is_favorite": false,
+            "tags": tags.iter().map(|name| serde_json::json!({ "name": name })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_light_novels_are_looked_up_by_title() {
+        let job = build_metadata_job(book("epub", &["Light-Novel"]), false, None, false);
+        assert_eq!(job.item_type, ItemType::LightNovel);
+        assert!(matches!(job.query, MetadataQuery::Title(ref t) if t == "Spice and Wolf"));
+
+        // Untagged prose still goes through the ISBN lookup
+        let job = build_metadata_job(book("epub", &["Fantasy"]), false, None, false);
+        assert_eq!(job.item_type, ItemType::Book);
+        assert!(matches!(job.query, MetadataQuery::Isbn(_)));
+
+        // The tag alone doesn't turn a comic archive into a novel
+        let job = build_metadata_job(book("cbz", &["light novel"]), false, None, false);
+        assert_ne!(job.item_type, ItemType::LightNovel);
+    }
+
+    #[test]
+    fn test_comic_archives_go_to_comic_vine_only_when_configured() {
+        for tags in [&["manga"][..], &[][..]] {
+            let job = build_metadata_job(book("cbz", tags), false, None, true);
+            assert_eq!(job.item_type, ItemType::Comic);
+            assert!(matches!(job.query, MetadataQuery::Title(_)));
+
+            let job = build_metadata_job(book("cbr", tags), false, None, false);
+            assert_eq!(job.item_type, ItemType::Manga);
+        }
+
+        // Prose is unaffected by the key
+        let job = build_metadata_job(book("epub", &[]), false, None, true);
+        assert_eq!(job.item_type, ItemType::Book);
+    }
+}
@@ -15,6 +15,7 @@ pub mod cover;
 pub mod debrid;
 pub mod discord;
 pub mod doodle;
+pub mod email;
 pub mod folder_watch;
 pub mod manga;
 pub mod migration;
@@ -37,11 +38,17 @@ macro_rules! generate_shiori_handlers {
             commands::library::get_book_summaries,
             commands::library::get_book_summaries_by_domain,
             commands::library::find_duplicate_books,
+            commands::library::find_duplicate_groups,
+            commands::library::merge_books,
+            commands::library::find_duplicate_authors,
+            commands::library::merge_authors,
             commands::library::get_total_books,
             commands::library::get_library_stats,
             commands::library::get_thumbnail,
             commands::library::get_recommended_books,
             commands::library::get_next_book_in_series,
+            commands::library::get_books_grouped_by_series,
+            commands::library::get_series_list,
             commands::library::get_book,
             commands::library::add_book,
             commands::library::update_book,
@@ -49,10 +56,24 @@ macro_rules! generate_shiori_handlers {
             commands::library::restore_book,
             commands::library::permanent_delete_book,
             commands::library::empty_trash,
+            commands::library::bulk_update_books,
+            commands::library::get_book_formats,
+            commands::library::add_book_format,
+            commands::library::set_primary_format,
+            commands::library::delete_book_format,
             commands::library::delete_books,
             commands::library::clean_up_database,
             commands::library::import_books,
             commands::library::scan_folder_unified,
+            commands::library::rescan_folder,
+            commands::library::get_auto_scan_folders,
+            commands::library::set_auto_scan_folders,
+            commands::library::get_managed_library,
+            commands::library::set_managed_library,
+            commands::library::get_series_autofill,
+            commands::library::set_series_autofill,
+            commands::library::relocate_missing_files,
+            commands::library::validate_library,
             commands::library::import_manga,
             commands::library::import_online_manga_chapters,
             commands::library::download_gutenberg_epub,
@@ -67,22 +88,48 @@ macro_rules! generate_shiori_handlers {
             commands::library::update_reading_status,
             commands::library::get_books_by_reading_status,
             commands::library::get_reading_history,
+            commands::library::get_recently_opened,
+            commands::library::get_continue_reading,
+            commands::library::get_recently_finished,
             commands::search::search_books,
+            commands::search::search_books_ranked,
             commands::metadata::extract_metadata,
             commands::metadata::search_manga_metadata,
+            commands::metadata::search_light_novel_metadata,
             commands::metadata::get_manga_metadata_by_id,
             commands::metadata::parse_manga_filename,
+            commands::metadata::parse_manga_filename_details,
             commands::metadata::search_book_metadata,
             commands::metadata::search_book_by_isbn,
             commands::metadata::enrich_book_metadata,
+            commands::metadata::enrich_collection_metadata,
+            commands::metadata::cancel_metadata_job,
+            commands::metadata::cancel_metadata_jobs,
+            commands::metadata::cancel_all_metadata_jobs,
+            commands::metadata::cleanup_metadata_cache,
+            commands::metadata::get_metadata_cache_stats,
+            commands::metadata::list_pending_metadata_matches,
+            commands::metadata::confirm_metadata_match,
+            commands::metadata::reject_metadata_match,
             commands::metadata::apply_selected_metadata,
             commands::metadata::apply_selected_series_metadata,
             commands::metadata::preview_cover_url,
             commands::tags::get_tags,
+            commands::tags::get_tag_tree,
+            commands::tags::get_books_by_tag,
+            commands::tags::get_books_by_tags,
             commands::tags::get_book_tag_ids,
             commands::tags::create_tag,
             commands::tags::add_tag_to_book,
             commands::tags::remove_tag_from_book,
+            commands::tags::add_tag_to_books,
+            commands::tags::remove_tag_from_books,
+            commands::tags::auto_assign_tag_colors,
+            commands::tags::merge_tags,
+            commands::tags::list_tag_rules,
+            commands::tags::set_tag_rule,
+            commands::tags::delete_tag_rule,
+            commands::tags::apply_tag_rules_to_library,
             commands::reader::get_reading_progress,
             commands::reader::get_reading_progress_batch,
             commands::reader::save_reading_progress,
@@ -90,11 +137,17 @@ macro_rules! generate_shiori_handlers {
             commands::reader::create_annotation,
             commands::reader::update_annotation,
             commands::reader::delete_annotation,
+            commands::reader::get_annotations_by_color,
+            commands::reader::get_color_distribution,
+            commands::reader::get_bookmarks,
+            commands::reader::add_bookmark,
+            commands::reader::delete_bookmark,
             commands::reader::get_annotation_categories,
             commands::reader::create_annotation_category,
             commands::reader::update_annotation_category,
             commands::reader::delete_annotation_category,
             commands::reader::search_annotations_global,
+            commands::reader::search_all_annotations,
             commands::reader::get_all_annotations,
             commands::reader::export_annotations,
             crate::commands::reader::get_reader_settings,
@@ -102,6 +155,7 @@ macro_rules! generate_shiori_handlers {
             crate::commands::reader::save_reader_settings,
             crate::commands::reader::get_book_file_path,
             crate::commands::reader::detect_book_format,
+            crate::commands::reader::detect_format_detailed,
             crate::commands::reader::validate_book_file,
 
             commands::reader::get_error_details,
@@ -111,19 +165,29 @@ macro_rules! generate_shiori_handlers {
             commands::reader::get_daily_reading_stats,
             commands::reader::get_book_reading_stats,
             commands::reader::get_reading_streak,
+            commands::reader::get_reading_stats,
             commands::reader::get_reading_goal,
             commands::reader::update_reading_goal,
+            commands::reader::set_goal,
+            commands::reader::get_goal_progress,
             commands::reader::get_today_reading_time,
             commands::rendering::open_book_renderer,
             commands::rendering::close_book_renderer,
             commands::rendering::get_book_toc,
             commands::rendering::get_book_chapter,
+            commands::rendering::preview_chapter_css,
+            commands::rendering::get_book_reading_estimate,
+            commands::rendering::compute_epub_progress,
+            commands::rendering::get_tts_segments,
+            commands::rendering::set_reading_speed,
             commands::rendering::get_book_chapter_count,
             commands::rendering::search_in_book,
             commands::rendering::get_epub_resource,
             commands::rendering::get_renderer_cache_stats,
+            commands::rendering::set_renderer_cache_size,
             commands::rendering::clear_renderer_cache,
             commands::rendering::render_pdf_page,
+            commands::rendering::render_pdf_pages,
             commands::rendering::get_pdf_page_dimensions,
             commands::collections::get_collections,
             commands::collections::get_collection,
@@ -141,12 +205,23 @@ macro_rules! generate_shiori_handlers {
             commands::collections::get_collections_by_type,
             commands::collections::preview_smart_collection,
             commands::export::export_library,
+            commands::export::export_library_catalog,
+            commands::export::export_reading_state,
+            commands::export::import_reading_state,
+            commands::export::export_book_annotations,
+            commands::export::export_collection_anthology,
+            commands::export::export_book_html,
             // v2.0 commands
             commands::conversion::convert_book,
+            commands::conversion::convert_book_with_profile,
+            commands::conversion::create_conversion_profile,
+            commands::conversion::list_conversion_profiles,
+            commands::conversion::delete_conversion_profile,
             commands::conversion::get_conversion_status,
             commands::conversion::list_conversion_jobs,
             commands::conversion::cancel_conversion,
             commands::conversion::get_supported_conversions,
+            commands::conversion::get_conversion_error_details,
             commands::conversion::check_calibre_available,
             commands::conversion::convert_with_calibre,
             commands::conversion::convert_and_replace_book,
@@ -160,6 +235,8 @@ macro_rules! generate_shiori_handlers {
             commands::cover::get_cover_path_by_id,
             commands::cover::get_cover_paths_batch,
             commands::cover::clear_cover_cache,
+            commands::cover::set_custom_cover,
+            commands::cover::regenerate_missing_covers,
             commands::rss::add_rss_feed,
             commands::rss::get_rss_feed,
             commands::rss::list_rss_feeds,
@@ -171,6 +248,7 @@ macro_rules! generate_shiori_handlers {
             commands::rss::get_unread_articles,
             commands::rss::mark_article_read,
             commands::rss::mark_all_rss_articles_read,
+            commands::rss::cleanup_rss_articles,
             commands::rss::generate_daily_epub,
             commands::rss::trigger_feed_update,
             commands::rss::trigger_daily_epub_generation,
@@ -183,12 +261,26 @@ macro_rules! generate_shiori_handlers {
             commands::share::stop_share_server,
             commands::share::is_share_server_running,
             commands::share::cleanup_expired_shares,
+            commands::share::get_opds_settings,
+            commands::share::update_opds_settings,
+            commands::share::get_api_settings,
+            commands::share::update_api_settings,
+            commands::share::regenerate_api_token,
+            commands::share::is_api_server_running,
+            // Email commands
+            commands::email::get_smtp_settings,
+            commands::email::update_smtp_settings,
+            commands::email::send_book_to_email,
             // Manga reader commands
             commands::manga::open_manga,
             commands::manga::get_manga_page,
             commands::manga::get_manga_page_path,
             commands::manga::preload_manga_pages,
             commands::manga::get_manga_page_dimensions,
+            commands::manga::get_manga_spreads,
+            commands::manga::get_manga_cache_stats,
+            commands::manga::get_manga_strip_manifest,
+            commands::manga::prefetch_manga_strip,
             commands::manga::close_manga,
             commands::manga::get_manga_series_list,
             commands::manga::get_series_volumes,
@@ -203,7 +295,14 @@ macro_rules! generate_shiori_handlers {
             // Preferences commands
             commands::preferences::get_user_preferences,
             commands::preferences::get_theme_sync,
+            commands::preferences::list_themes,
+            commands::preferences::save_custom_theme,
+            commands::preferences::delete_custom_theme,
             commands::preferences::update_user_preferences,
+            commands::preferences::get_network_settings,
+            commands::preferences::update_network_settings,
+            commands::preferences::export_preferences,
+            commands::preferences::import_preferences,
             commands::preferences::get_book_preference_overrides,
             commands::preferences::set_book_preference_override,
             commands::preferences::clear_book_preference_override,
@@ -216,15 +315,28 @@ macro_rules! generate_shiori_handlers {
             commands::preferences::reset_onboarding,
             commands::preferences::torrent_network_get_config,
             commands::preferences::torrent_network_set_config,
+            commands::preferences::get_conversion_worker_count,
+            commands::preferences::set_conversion_worker_count,
+            commands::preferences::get_conversion_progress_rate,
+            commands::preferences::set_conversion_progress_rate,
             // Doodle commands
             commands::doodle::save_doodle,
             commands::doodle::get_doodle,
             commands::doodle::delete_doodle,
             commands::doodle::delete_book_doodles,
+            commands::doodle::export_doodle,
+            commands::doodle::list_doodle_pages,
+            commands::doodle::copy_doodles,
+            commands::doodle::move_doodle,
             // Backup commands
             commands::backup::create_backup,
             commands::backup::restore_backup,
             commands::backup::get_backup_info,
+            commands::backup::backup_library,
+            commands::backup::restore_library,
+            commands::backup::check_database_health,
+            commands::backup::rebuild_search_index,
+            commands::backup::compact_database,
             // File write command
             commands::export::write_text_to_file,
             // Translation/dictionary commands
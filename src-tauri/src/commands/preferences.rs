@@ -1,9 +1,14 @@
-use crate::error::Result;
+use crate::error::{Result, ShioriError};
+use crate::services::conversion_engine::{ConversionEngine, PROGRESS_RATE_RANGE, WORKER_COUNT_RANGE};
+use crate::services::theme_service::{self, ThemeInfo, ThemePalette};
+use crate::utils::http::{self, NetworkSettings};
+use crate::utils::validate;
 use crate::AppState;
 /// Preferences IPC Commands
 ///
 /// Handles user preferences, theme, and per-book overrides
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tauri::State;
 
 // ═══════════════════════════════════════════════════════════════
@@ -30,6 +35,7 @@ pub struct UserPreferences {
     pub translation_target_language: String,
     pub auto_group_manga: bool,
     pub auto_translate: bool,
+    #[serde(rename = "cacheSizeLimitMB")]
     pub cache_size_limit_mb: i32,
     pub library_size_limit: i32,
     pub send_analytics: bool,
@@ -51,6 +57,7 @@ pub struct UserPreferences {
     pub enable_recycle_bin: bool,
     pub legacy_library_migration_status: String,
     pub anilist_token: Option<String>,
+    pub comicvine_api_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -65,6 +72,7 @@ pub struct BookPreferences {
     pub paragraph_spacing: i32,
     pub animation_speed: i32,
     pub hyphenation: bool,
+    #[serde(rename = "customCSS")]
     pub custom_css: String,
 }
 
@@ -109,10 +117,7 @@ pub struct OnboardingState {
 // IPC COMMANDS
 // ═══════════════════════════════════════════════════════════════
 
-/// Get all user preferences (called once on app start)
-#[tauri::command]
-pub async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPreferences> {
-    let conn = state.db.get_connection()?;
+pub(crate) fn read_user_preferences(conn: &rusqlite::Connection) -> Result<UserPreferences> {
     let prefs = conn.query_row(
         "SELECT 
             theme,
@@ -135,7 +140,8 @@ pub async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPref
             COALESCE(auto_export_annotations, 0), COALESCE(annotations_export_path, ''), COALESCE(annotations_export_format, 'markdown'),
             COALESCE(enable_recycle_bin, 1),
             COALESCE(legacy_library_migration_status, 'none'),
-            anilist_token
+            anilist_token,
+            comicvine_api_key
         FROM user_preferences WHERE id = 1",
         [],
         |row| {
@@ -201,6 +207,7 @@ pub async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPref
                 enable_recycle_bin: row.get::<_, bool>(52).unwrap_or(true),
                 legacy_library_migration_status: row.get(53).unwrap_or_else(|_| "none".to_string()),
                 anilist_token: row.get(54).unwrap_or(None),
+                comicvine_api_key: row.get(55).unwrap_or(None),
             })
         },
     )?;
@@ -208,31 +215,128 @@ pub async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPref
     Ok(prefs)
 }
 
-/// Get theme synchronously (for no-flash initialization)
+/// Get all user preferences (called once on app start)
 #[tauri::command]
-pub async fn get_theme_sync(state: State<'_, AppState>) -> Result<String> {
+pub async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPreferences> {
     let conn = state.db.get_connection()?;
-    let theme: String = conn.query_row(
-        "SELECT theme FROM user_preferences WHERE id = 1",
-        [],
-        |row| row.get(0),
-    )?;
+    read_user_preferences(&conn)
+}
 
-    Ok(theme)
+/// Get the active theme and its palette (for no-flash initialization)
+#[tauri::command]
+pub async fn get_theme_sync(state: State<'_, AppState>) -> Result<ThemeInfo> {
+    let conn = state.db.get_connection()?;
+    theme_service::resolve_active_theme(&conn)
 }
 
-/// Update user preferences (partial update)
+/// Built-in themes followed by the user's custom themes
 #[tauri::command]
-pub async fn update_user_preferences(
+pub async fn list_themes(state: State<'_, AppState>) -> Result<Vec<ThemeInfo>> {
+    let conn = state.db.get_connection()?;
+    theme_service::list_themes(&conn)
+}
+
+/// Create or replace a custom theme palette
+#[tauri::command]
+pub async fn save_custom_theme(
     state: State<'_, AppState>,
-    updates: serde_json::Value,
+    name: String,
+    palette: ThemePalette,
 ) -> Result<()> {
     let conn = state.db.get_connection()?;
+    theme_service::save_custom_theme(&conn, &name, &palette)
+}
+
+#[tauri::command]
+pub async fn delete_custom_theme(state: State<'_, AppState>, name: String) -> Result<()> {
+    let conn = state.db.get_connection()?;
+    theme_service::delete_custom_theme(&conn, &name)
+}
+
+/// Accepted ranges for numeric book preferences, as `(field, min, max)`.
+/// Wide enough to cover every slider the frontend offers.
+const BOOK_PREFERENCE_BOUNDS: [(&str, f64, f64); 4] = [
+    ("fontSize", 10.0, 40.0),
+    ("lineHeight", 1.0, 3.0),
+    ("pageWidth", 320.0, 2000.0),
+    ("animationSpeed", 0.0, 1000.0),
+];
+
+const MANGA_PREFERENCE_BOUNDS: [(&str, f64, f64); 2] =
+    [("marginSize", 0.0, 100.0), ("preloadCount", 0.0, 10.0)];
+
+const GENERAL_PREFERENCE_BOUNDS: [(&str, f64, f64); 1] = [("uiScale", 0.75, 1.5)];
+
+fn collect_out_of_range(
+    values: Option<&serde_json::Value>,
+    bounds: &[(&str, f64, f64)],
+    prefix: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(values) = values else {
+        return;
+    };
+    for (field, min, max) in bounds {
+        match values.get(field) {
+            None | Some(serde_json::Value::Null) => {}
+            Some(value) => match value.as_f64() {
+                Some(n) if n >= *min && n <= *max => {}
+                Some(n) => errors.push(format!(
+                    "{}{} must be between {} and {}, got {}",
+                    prefix, field, min, max, n
+                )),
+                None => errors.push(format!("{}{} must be a number", prefix, field)),
+            },
+        }
+    }
+}
+
+fn require_valid_preferences(errors: Vec<String>) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(ShioriError::Validation(format!(
+        "Invalid preferences: {}",
+        errors.join("; ")
+    )))
+}
+
+/// Reject out-of-range numeric fields before anything is written, listing
+/// every offending field
+fn validate_user_preference_updates(updates: &serde_json::Value) -> Result<()> {
+    let mut errors = Vec::new();
+    collect_out_of_range(
+        updates.get("book"),
+        &BOOK_PREFERENCE_BOUNDS,
+        "book.",
+        &mut errors,
+    );
+    collect_out_of_range(
+        updates.get("manga"),
+        &MANGA_PREFERENCE_BOUNDS,
+        "manga.",
+        &mut errors,
+    );
+    collect_out_of_range(Some(updates), &GENERAL_PREFERENCE_BOUNDS, "", &mut errors);
+    require_valid_preferences(errors)
+}
+
+/// Apply a partial camelCase update (as sent by the frontend) to
+/// `user_preferences`
+pub(crate) fn apply_user_preference_updates(
+    conn: &rusqlite::Connection,
+    updates: &serde_json::Value,
+) -> Result<()> {
+    validate_user_preference_updates(updates)?;
+
     let mut set_clauses = Vec::new();
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
     // Theme
     if let Some(theme) = updates.get("theme").and_then(|v| v.as_str()) {
+        if theme_service::get_theme(&conn, theme)?.is_none() {
+            return Err(ShioriError::Validation(format!("Unknown theme: {}", theme)));
+        }
         set_clauses.push("theme = ?".to_string());
         params.push(Box::new(theme.to_string()));
     }
@@ -508,6 +612,17 @@ pub async fn update_user_preferences(
         params.push(Box::new(anilist_token));
     }
 
+    if let Some(comicvine_api_key) = updates.get("comicvineApiKey").and_then(|v| {
+        if v.is_null() {
+            Some(None)
+        } else {
+            v.as_str().map(|s| Some(s.to_string()))
+        }
+    }) {
+        set_clauses.push("comicvine_api_key = ?".to_string());
+        params.push(Box::new(comicvine_api_key));
+    }
+
     if set_clauses.is_empty() {
         return Ok(());
     }
@@ -526,12 +641,76 @@ pub async fn update_user_preferences(
     Ok(())
 }
 
-/// Get book-specific overrides
+/// Update user preferences (partial update)
 #[tauri::command]
-pub async fn get_book_preference_overrides(
+pub async fn update_user_preferences(
     state: State<'_, AppState>,
-) -> Result<Vec<PreferenceOverride>> {
+    rendering: State<'_, crate::commands::rendering::RenderingState>,
+    manga: State<'_, crate::commands::manga::MangaState>,
+    auto_scan: State<'_, std::sync::Arc<crate::services::auto_scan_scheduler::AutoScanScheduler>>,
+    updates: serde_json::Value,
+) -> Result<()> {
     let conn = state.db.get_connection()?;
+    apply_user_preference_updates(&conn, &updates)?;
+
+    if let Some(mode) = updates.get("performanceMode").and_then(|v| v.as_str()) {
+        apply_performance_mode(&rendering, &manga, mode);
+    }
+    // Start or stop watching right away instead of at the next poll
+    if updates.get("autoScanEnabled").is_some() {
+        auto_scan.trigger();
+    }
+
+    Ok(())
+}
+
+/// Resize the renderer cache and manga page cache for a performance mode
+fn apply_performance_mode(
+    rendering: &crate::commands::rendering::RenderingState,
+    manga: &crate::commands::manga::MangaState,
+    mode: &str,
+) {
+    rendering
+        .service
+        .resize_cache(crate::services::cache::cache_size_for_performance_mode(
+            mode,
+        ));
+    manga.service.set_cache_budget(
+        crate::services::manga_service::page_cache_budget_for_performance_mode(mode),
+    );
+}
+
+/// Proxy, timeout and user agent used by online services
+#[tauri::command]
+pub fn get_network_settings(state: State<'_, AppState>) -> Result<NetworkSettings> {
+    Ok(state.db.network_settings())
+}
+
+/// Validate and save network settings, then rebuild the shared HTTP client
+/// so online services use them from their next request
+#[tauri::command]
+pub fn update_network_settings(
+    state: State<'_, AppState>,
+    settings: NetworkSettings,
+) -> Result<NetworkSettings> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let settings = NetworkSettings {
+        proxy_url: trimmed(settings.proxy_url),
+        user_agent: trimmed(settings.user_agent),
+        ..settings
+    };
+
+    // Building first rejects a bad proxy or timeout before anything is saved
+    http::apply(&settings)?;
+    state.db.save_network_settings(&settings)?;
+    Ok(settings)
+}
+
+fn read_book_overrides(conn: &rusqlite::Connection) -> Result<Vec<PreferenceOverride>> {
     let mut stmt = conn.prepare(
         "SELECT book_id, 
             font_family, font_size, line_height, page_width,
@@ -601,14 +780,24 @@ pub async fn get_book_preference_overrides(
     Ok(overrides)
 }
 
-/// Set book-specific override
+/// Get book-specific overrides
 #[tauri::command]
-pub async fn set_book_preference_override(
+pub async fn get_book_preference_overrides(
     state: State<'_, AppState>,
+) -> Result<Vec<PreferenceOverride>> {
+    let conn = state.db.get_connection()?;
+    read_book_overrides(&conn)
+}
+
+fn apply_book_override(
+    conn: &rusqlite::Connection,
     book_id: i32,
-    overrides: serde_json::Value,
+    overrides: &serde_json::Value,
 ) -> Result<()> {
-    let conn = state.db.get_connection()?;
+    let mut errors = Vec::new();
+    collect_out_of_range(Some(overrides), &BOOK_PREFERENCE_BOUNDS, "", &mut errors);
+    require_valid_preferences(errors)?;
+
     // Upsert book override entry
     conn.execute(
         "INSERT OR IGNORE INTO book_preference_overrides (book_id) VALUES (?)",
@@ -677,6 +866,54 @@ pub async fn set_book_preference_override(
     Ok(())
 }
 
+/// Set book-specific override
+#[tauri::command]
+pub async fn set_book_preference_override(
+    state: State<'_, AppState>,
+    book_id: i32,
+    overrides: serde_json::Value,
+) -> Result<()> {
+    let conn = state.db.get_connection()?;
+    apply_book_override(&conn, book_id, &overrides)
+}
+
+/// Global `book_custom_css` followed by the book's override CSS, in cascade
+/// order. `override_css` stands in for the stored override when previewing.
+pub(crate) fn resolve_book_css(
+    conn: &rusqlite::Connection,
+    book_id: i64,
+    override_css: Option<&str>,
+) -> Result<String> {
+    use rusqlite::OptionalExtension;
+
+    let global: Option<String> = conn
+        .query_row(
+            "SELECT book_custom_css FROM user_preferences WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    let book: Option<String> = match override_css {
+        Some(css) => Some(css.to_string()),
+        None => conn
+            .query_row(
+                "SELECT custom_css FROM book_preference_overrides WHERE book_id = ?1",
+                [book_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten(),
+    };
+
+    Ok([global, book]
+        .into_iter()
+        .flatten()
+        .filter(|css| !css.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
 /// Clear book-specific override
 #[tauri::command]
 pub async fn clear_book_preference_override(
@@ -692,12 +929,7 @@ pub async fn clear_book_preference_override(
     Ok(())
 }
 
-/// Get manga preference overrides
-#[tauri::command]
-pub async fn get_manga_preference_overrides(
-    state: State<'_, AppState>,
-) -> Result<Vec<PreferenceOverride>> {
-    let conn = state.db.get_connection()?;
+fn read_manga_overrides(conn: &rusqlite::Connection) -> Result<Vec<PreferenceOverride>> {
     let mut stmt = conn.prepare(
         "SELECT book_id, 
             mode, direction, margin_size, fit_width,
@@ -759,14 +991,24 @@ pub async fn get_manga_preference_overrides(
     Ok(overrides)
 }
 
-/// Set manga-specific override
+/// Get manga preference overrides
 #[tauri::command]
-pub async fn set_manga_preference_override(
+pub async fn get_manga_preference_overrides(
     state: State<'_, AppState>,
+) -> Result<Vec<PreferenceOverride>> {
+    let conn = state.db.get_connection()?;
+    read_manga_overrides(&conn)
+}
+
+fn apply_manga_override(
+    conn: &rusqlite::Connection,
     book_id: i32,
-    overrides: serde_json::Value,
+    overrides: &serde_json::Value,
 ) -> Result<()> {
-    let conn = state.db.get_connection()?;
+    let mut errors = Vec::new();
+    collect_out_of_range(Some(overrides), &MANGA_PREFERENCE_BOUNDS, "", &mut errors);
+    require_valid_preferences(errors)?;
+
     // Upsert manga override entry
     conn.execute(
         "INSERT OR IGNORE INTO manga_preference_overrides (book_id) VALUES (?)",
@@ -830,6 +1072,17 @@ pub async fn set_manga_preference_override(
     Ok(())
 }
 
+/// Set manga-specific override
+#[tauri::command]
+pub async fn set_manga_preference_override(
+    state: State<'_, AppState>,
+    book_id: i32,
+    overrides: serde_json::Value,
+) -> Result<()> {
+    let conn = state.db.get_connection()?;
+    apply_manga_override(&conn, book_id, &overrides)
+}
+
 /// Clear manga-specific override
 #[tauri::command]
 pub async fn clear_manga_preference_override(
@@ -845,6 +1098,290 @@ pub async fn clear_manga_preference_override(
     Ok(())
 }
 
+// ═══════════════════════════════════════════════════════════════
+// PREFERENCES BUNDLE (export / import)
+// ═══════════════════════════════════════════════════════════════
+
+/// Bumped whenever the bundle layout changes incompatibly
+pub const PREFERENCES_BUNDLE_VERSION: u32 = 1;
+
+/// Singleton settings tables copied column by column
+const BUNDLE_SETTINGS_TABLES: [(&str, &str); 3] = [
+    ("librarySettings", "library_settings"),
+    ("rssSettings", "rss_settings"),
+    ("conversionSettings", "conversion_settings"),
+];
+
+/// Preferences that are per-machine state or credentials, not settings
+const BUNDLE_EXCLUDED_PREFERENCES: [&str; 3] = [
+    "anilistToken",
+    "comicvineApiKey",
+    "legacyLibraryMigrationStatus",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookOverrideExport {
+    pub book_uuid: String,
+    pub preferences: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferencesBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub user_preferences: serde_json::Value,
+    #[serde(default)]
+    pub custom_themes: Vec<ThemeInfo>,
+    #[serde(default)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub book_overrides: Vec<BookOverrideExport>,
+    #[serde(default)]
+    pub manga_overrides: Vec<BookOverrideExport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferencesImportReport {
+    pub book_overrides: usize,
+    pub manga_overrides: usize,
+    /// Overrides for books that are not in this library
+    pub skipped_overrides: usize,
+}
+
+fn book_uuid_map(conn: &rusqlite::Connection) -> Result<std::collections::HashMap<i64, String>> {
+    let mut stmt = conn.prepare("SELECT id, uuid FROM books")?;
+    let map = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(map)
+}
+
+/// Column values of a singleton (`id = 1`) settings table, minus bookkeeping
+fn read_settings_row(
+    conn: &rusqlite::Connection,
+    table: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    use rusqlite::types::ValueRef;
+
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {} WHERE id = 1", table))?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt.query([])?;
+    let mut values = serde_json::Map::new();
+    if let Some(row) = rows.next()? {
+        for (i, name) in names.iter().enumerate() {
+            if name == "id" || name == "updated_at" {
+                continue;
+            }
+            let value = match row.get_ref(i)? {
+                ValueRef::Null | ValueRef::Blob(_) => serde_json::Value::Null,
+                ValueRef::Integer(n) => n.into(),
+                ValueRef::Real(f) => serde_json::Number::from_f64(f)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+            };
+            values.insert(name.clone(), value);
+        }
+    }
+    Ok(values)
+}
+
+/// Update the columns of a singleton settings table that exist locally;
+/// unknown keys from a newer or older install are ignored
+fn apply_settings_row(
+    conn: &rusqlite::Connection,
+    table: &str,
+    values: &serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    use rusqlite::types::Value;
+
+    let columns: std::collections::HashSet<String> = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut set_clauses = Vec::new();
+    let mut params: Vec<Value> = Vec::new();
+    for (name, value) in values {
+        if name == "id" || name == "updated_at" || !columns.contains(name) {
+            continue;
+        }
+        let value = match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Integer(i),
+                None => Value::Real(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::Text(s.clone()),
+            other => Value::Text(other.to_string()),
+        };
+        set_clauses.push(format!("{} = ?", name));
+        params.push(value);
+    }
+    if set_clauses.is_empty() {
+        return Ok(());
+    }
+
+    conn.execute(
+        &format!(
+            "UPDATE {} SET {} WHERE id = 1",
+            table,
+            set_clauses.join(", ")
+        ),
+        rusqlite::params_from_iter(params),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn build_preferences_bundle(conn: &rusqlite::Connection) -> Result<PreferencesBundle> {
+    let mut user_preferences = serde_json::to_value(read_user_preferences(conn)?)?;
+    if let Some(prefs) = user_preferences.as_object_mut() {
+        for key in BUNDLE_EXCLUDED_PREFERENCES {
+            prefs.remove(key);
+        }
+    }
+
+    let mut settings = serde_json::Map::new();
+    for (key, table) in BUNDLE_SETTINGS_TABLES {
+        settings.insert(key.to_string(), read_settings_row(conn, table)?.into());
+    }
+
+    let uuids = book_uuid_map(conn)?;
+    let by_uuid = |overrides: Vec<PreferenceOverride>| -> Vec<BookOverrideExport> {
+        overrides
+            .into_iter()
+            .filter_map(|o| {
+                uuids
+                    .get(&(o.book_id as i64))
+                    .map(|uuid| BookOverrideExport {
+                        book_uuid: uuid.clone(),
+                        preferences: o.preferences,
+                    })
+            })
+            .collect()
+    };
+
+    Ok(PreferencesBundle {
+        version: PREFERENCES_BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        user_preferences,
+        custom_themes: theme_service::list_themes(conn)?
+            .into_iter()
+            .filter(|t| !t.builtin)
+            .collect(),
+        settings,
+        book_overrides: by_uuid(read_book_overrides(conn)?),
+        manga_overrides: by_uuid(read_manga_overrides(conn)?),
+    })
+}
+
+/// Apply exported overrides to the local books with matching UUIDs.
+/// Returns (applied, skipped).
+fn import_overrides(
+    conn: &rusqlite::Connection,
+    overrides: &[BookOverrideExport],
+    ids: &std::collections::HashMap<String, i64>,
+    apply: fn(&rusqlite::Connection, i32, &serde_json::Value) -> Result<()>,
+) -> Result<(usize, usize)> {
+    let mut applied = 0;
+    for o in overrides {
+        if let Some(&id) = ids.get(&o.book_uuid) {
+            apply(conn, id as i32, &o.preferences)?;
+            applied += 1;
+        }
+    }
+    Ok((applied, overrides.len() - applied))
+}
+
+/// Apply a bundle in one transaction. Overrides are matched to local books
+/// by UUID; those for books missing here are skipped.
+pub(crate) fn apply_preferences_bundle(
+    conn: &rusqlite::Connection,
+    bundle: &PreferencesBundle,
+) -> Result<PreferencesImportReport> {
+    if bundle.version == 0 || bundle.version > PREFERENCES_BUNDLE_VERSION {
+        return Err(ShioriError::Validation(format!(
+            "Unsupported preferences bundle version {} (this version of Shiori reads up to {})",
+            bundle.version, PREFERENCES_BUNDLE_VERSION
+        )));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+
+    // Themes first so `userPreferences.theme` can name a custom one
+    for theme in &bundle.custom_themes {
+        if let Some(palette) = &theme.palette {
+            theme_service::save_custom_theme(&tx, &theme.name, palette)?;
+        }
+    }
+    apply_user_preference_updates(&tx, &bundle.user_preferences)?;
+
+    for (key, table) in BUNDLE_SETTINGS_TABLES {
+        if let Some(values) = bundle.settings.get(key).and_then(|v| v.as_object()) {
+            apply_settings_row(&tx, table, values)?;
+        }
+    }
+
+    let ids: std::collections::HashMap<String, i64> = book_uuid_map(&tx)?
+        .into_iter()
+        .map(|(id, uuid)| (uuid, id))
+        .collect();
+    let (book_overrides, skipped_books) =
+        import_overrides(&tx, &bundle.book_overrides, &ids, apply_book_override)?;
+    let (manga_overrides, skipped_manga) =
+        import_overrides(&tx, &bundle.manga_overrides, &ids, apply_manga_override)?;
+
+    tx.commit()?;
+    Ok(PreferencesImportReport {
+        book_overrides,
+        manga_overrides,
+        skipped_overrides: skipped_books + skipped_manga,
+    })
+}
+
+/// Serialize preferences, settings and per-book overrides to a versioned
+/// JSON bundle for moving to another machine
+#[tauri::command]
+pub async fn export_preferences(state: State<'_, AppState>) -> Result<String> {
+    let conn = state.db.get_connection()?;
+    let bundle = build_preferences_bundle(&conn)?;
+    Ok(serde_json::to_string_pretty(&bundle)?)
+}
+
+/// Apply a bundle produced by `export_preferences`
+#[tauri::command]
+pub async fn import_preferences(
+    state: State<'_, AppState>,
+    rendering: State<'_, crate::commands::rendering::RenderingState>,
+    manga: State<'_, crate::commands::manga::MangaState>,
+    engine: State<'_, Arc<ConversionEngine>>,
+    json: String,
+) -> Result<PreferencesImportReport> {
+    let bundle: PreferencesBundle = serde_json::from_str(&json)
+        .map_err(|e| ShioriError::Validation(format!("Invalid preferences bundle: {}", e)))?;
+    let conn = state.db.get_connection()?;
+    let report = apply_preferences_bundle(&conn, &bundle)?;
+    drop(conn);
+
+    engine
+        .set_worker_count(ConversionEngine::stored_worker_count(&state.db))
+        .map_err(|e| ShioriError::Validation(e.to_string()))?;
+
+    if let Some(mode) = bundle
+        .user_preferences
+        .get("performanceMode")
+        .and_then(|v| v.as_str())
+    {
+        apply_performance_mode(&rendering, &manga, mode);
+    }
+
+    Ok(report)
+}
+
 /// Get onboarding state
 #[tauri::command]
 pub async fn get_onboarding_state(state: State<'_, AppState>) -> Result<OnboardingState> {
@@ -956,7 +1493,8 @@ pub async fn get_startup_data(state: State<'_, AppState>) -> Result<StartupData>
             COALESCE(auto_export_annotations, 0), COALESCE(annotations_export_path, ''), COALESCE(annotations_export_format, 'markdown'),
             COALESCE(enable_recycle_bin, 1),
             COALESCE(legacy_library_migration_status, 'none'),
-            anilist_token
+            anilist_token,
+            comicvine_api_key
         FROM user_preferences WHERE id = 1",
         [],
         |row| {
@@ -1022,6 +1560,7 @@ pub async fn get_startup_data(state: State<'_, AppState>) -> Result<StartupData>
                 enable_recycle_bin: row.get::<_, bool>(52).unwrap_or(true),
                 legacy_library_migration_status: row.get(53).unwrap_or_else(|_| "none".to_string()),
                 anilist_token: row.get(54).unwrap_or(None),
+                comicvine_api_key: row.get(55).unwrap_or(None),
             })
         },
     )?;
@@ -1231,3 +1770,192 @@ pub async fn torrent_network_set_config(
     )?;
     Ok(())
 }
+
+#[tauri::command]
+pub async fn get_conversion_worker_count(
+    engine: State<'_, Arc<ConversionEngine>>,
+) -> Result<usize> {
+    Ok(engine.worker_count())
+}
+
+/// Save `conversion_settings.worker_thread_count` and resize the running
+/// worker pool to match
+#[tauri::command]
+pub async fn set_conversion_worker_count(
+    state: State<'_, AppState>,
+    engine: State<'_, Arc<ConversionEngine>>,
+    count: i64,
+) -> Result<()> {
+    validate::require_in_range(
+        count,
+        *WORKER_COUNT_RANGE.start() as i64,
+        *WORKER_COUNT_RANGE.end() as i64,
+        "worker count",
+    )?;
+    let conn = state.db.get_connection()?;
+    conn.execute(
+        "UPDATE conversion_settings SET worker_thread_count = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        [count],
+    )?;
+    engine
+        .set_worker_count(count as usize)
+        .map_err(|e| ShioriError::Validation(e.to_string()))
+}
+
+#[tauri::command]
+pub async fn get_conversion_progress_rate(
+    engine: State<'_, Arc<ConversionEngine>>,
+) -> Result<u32> {
+    Ok(engine.progress_rate())
+}
+
+/// Save `conversion_settings.progress_events_per_second` and apply it to
+/// the running engine
+#[tauri::command]
+pub async fn set_conversion_progress_rate(
+    state: State<'_, AppState>,
+    engine: State<'_, Arc<ConversionEngine>>,
+    rate: i64,
+) -> Result<()> {
+    validate::require_in_range(
+        rate,
+        *PROGRESS_RATE_RANGE.start() as i64,
+        *PROGRESS_RATE_RANGE.end() as i64,
+        "progress event rate",
+    )?;
+    let conn = state.db.get_connection()?;
+    conn.execute(
+        "UPDATE conversion_settings SET progress_events_per_second = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        [rate],
+    )?;
+    engine
+        .set_progress_rate(rate as u32)
+        .map_err(|e| ShioriError::Validation(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn add_book(conn: &rusqlite::Connection, uuid: &str) -> i32 {
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+            rusqlite::params![uuid, format!("/tmp/{}.epub", uuid)],
+        )
+        .unwrap();
+        conn.last_insert_rowid() as i32
+    }
+
+    #[test]
+    fn test_preferences_bundle_round_trip() {
+        let dir = tempdir().unwrap();
+        let source = Database::new(dir.path().join("source.db")).unwrap();
+        let conn = source.get_connection().unwrap();
+        let shared = add_book(&conn, "shared-book");
+        let local_only = add_book(&conn, "local-only");
+
+        theme_service::save_custom_theme(
+            &conn,
+            "Midnight",
+            &ThemePalette {
+                background: "#101018".to_string(),
+                text: "#e0e0e0".to_string(),
+                accent: "#ff8800".to_string(),
+                link: "#88aaff".to_string(),
+            },
+        )
+        .unwrap();
+        apply_user_preference_updates(
+            &conn,
+            &json!({
+                "theme": "Midnight",
+                "book": { "fontSize": 30, "customCSS": "p { margin: 0 }" },
+                "cacheSizeLimitMB": 800,
+                "anilistToken": "secret"
+            }),
+        )
+        .unwrap();
+        conn.execute_batch(
+            "UPDATE library_settings SET duplicate_detection_mode = 'isbn' WHERE id = 1;
+             UPDATE rss_settings SET article_cleanup_days = 7 WHERE id = 1;
+             UPDATE conversion_settings SET worker_thread_count = 4 WHERE id = 1;",
+        )
+        .unwrap();
+        apply_book_override(&conn, shared, &json!({ "fontSize": 20 })).unwrap();
+        apply_manga_override(&conn, local_only, &json!({ "mode": "single" })).unwrap();
+
+        let exported = serde_json::to_string(&build_preferences_bundle(&conn).unwrap()).unwrap();
+        assert!(!exported.contains("secret"));
+
+        // A fresh install that only has one of the two books
+        let target = Database::new(dir.path().join("target.db")).unwrap();
+        let target_conn = target.get_connection().unwrap();
+        add_book(&target_conn, "shared-book");
+
+        let bundle: PreferencesBundle = serde_json::from_str(&exported).unwrap();
+        let report = apply_preferences_bundle(&target_conn, &bundle).unwrap();
+        assert_eq!(report.book_overrides, 1);
+        assert_eq!(report.manga_overrides, 0);
+        assert_eq!(report.skipped_overrides, 1);
+
+        let prefs = read_user_preferences(&target_conn).unwrap();
+        assert_eq!(prefs.theme, "Midnight");
+        assert_eq!(prefs.book.font_size, 30);
+        assert_eq!(prefs.book.custom_css, "p { margin: 0 }");
+        assert_eq!(prefs.cache_size_limit_mb, 800);
+        assert_eq!(prefs.anilist_token, None);
+
+        let reexported = build_preferences_bundle(&target_conn).unwrap();
+        assert_eq!(reexported.settings, bundle.settings);
+        assert_eq!(reexported.custom_themes, bundle.custom_themes);
+        assert_eq!(reexported.book_overrides.len(), 1);
+        assert_eq!(
+            reexported.book_overrides[0].preferences,
+            json!({ "fontSize": 20 })
+        );
+
+        let future = PreferencesBundle {
+            version: PREFERENCES_BUNDLE_VERSION + 1,
+            ..serde_json::from_str(&exported).unwrap()
+        };
+        assert!(apply_preferences_bundle(&target_conn, &future).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_preferences_are_rejected() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("prefs.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        let before = read_user_preferences(&conn).unwrap();
+
+        let err = apply_user_preference_updates(
+            &conn,
+            &json!({ "book": { "fontSize": 99, "lineHeight": 1.8 }, "uiScale": 4.0 }),
+        )
+        .unwrap_err();
+        match err {
+            ShioriError::Validation(msg) => {
+                assert!(msg.contains("book.fontSize must be between 10 and 40, got 99"));
+                assert!(msg.contains("uiScale"));
+                assert!(!msg.contains("lineHeight"));
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        // Nothing from the rejected update was written
+        let after = read_user_preferences(&conn).unwrap();
+        assert_eq!(after.book.font_size, before.book.font_size);
+        assert_eq!(after.book.line_height, before.book.line_height);
+
+        let book_id = add_book(&conn, "override-book");
+        assert!(matches!(
+            apply_manga_override(&conn, book_id, &json!({ "preloadCount": "lots" })),
+            Err(ShioriError::Validation(_))
+        ));
+        apply_user_preference_updates(&conn, &json!({ "book": { "fontSize": 40 } })).unwrap();
+        assert_eq!(read_user_preferences(&conn).unwrap().book.font_size, 40);
+    }
+}
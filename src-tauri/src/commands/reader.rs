@@ -1,11 +1,14 @@
-use crate::error::Result;
+use crate::error::{Result, ShioriError};
 use crate::models::{
     Annotation, AnnotationCategory, AnnotationExportData, AnnotationExportOptions,
-    AnnotationSearchResult, BookReadingStats, DailyReadingStats, ReaderSettings, ReadingGoal,
-    ReadingProgress, ReadingSession, ReadingStreak,
+    AnnotationSearchResult, BookReadingStats, ColorCount, DailyReadingStats, GoalProgress,
+    ReaderSettings, ReadingGoal, ReadingProgress, ReadingSession, ReadingStats, ReadingStreak,
 };
+use crate::services::format_adapter::FormatError;
+use crate::services::format_detection::{self, DetailedFormat};
 use crate::services::format_detector;
 use crate::services::reader_service::ReaderService;
+use crate::services::{goals_service, stats_service};
 use crate::utils::validate;
 use crate::AppState;
 use std::path::Path;
@@ -127,6 +130,54 @@ pub fn delete_annotation(id: i64, state: State<AppState>) -> Result<()> {
     ReaderService::delete_annotation(&conn, id)
 }
 
+#[tauri::command]
+pub fn get_annotations_by_color(
+    book_id: i64,
+    color: String,
+    state: State<AppState>,
+) -> Result<Vec<Annotation>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    validate::require_non_empty(&color, "color")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::get_annotations_by_color(&conn, book_id, &color)
+}
+
+#[tauri::command]
+pub fn get_color_distribution(book_id: i64, state: State<AppState>) -> Result<Vec<ColorCount>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::get_color_distribution(&conn, book_id)
+}
+
+// ==================== Bookmark Commands ====================
+
+#[tauri::command]
+pub fn get_bookmarks(book_id: i64, state: State<AppState>) -> Result<Vec<Annotation>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::get_bookmarks(&conn, book_id)
+}
+
+#[tauri::command]
+pub fn add_bookmark(
+    book_id: i64,
+    location: String,
+    label: Option<String>,
+    state: State<AppState>,
+) -> Result<Annotation> {
+    validate::require_positive_id(book_id, "book_id")?;
+    validate::require_non_empty(&location, "location")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::add_bookmark(&conn, book_id, &location, label.as_deref())
+}
+
+#[tauri::command]
+pub fn delete_bookmark(id: i64, state: State<AppState>) -> Result<()> {
+    validate::require_positive_id(id, "id")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::delete_bookmark(&conn, id)
+}
+
 #[tauri::command]
 pub fn get_annotation_categories(state: State<AppState>) -> Result<Vec<AnnotationCategory>> {
     let conn = state.db.get_connection()?;
@@ -195,6 +246,18 @@ pub fn search_annotations_global(
     )
 }
 
+/// Find highlights and notes matching `query` in any book, best matches first
+#[tauri::command]
+pub fn search_all_annotations(
+    query: String,
+    limit: Option<i64>,
+    state: State<AppState>,
+) -> Result<Vec<AnnotationSearchResult>> {
+    validate::require_non_empty(&query, "query")?;
+    let conn = state.db.get_connection()?;
+    ReaderService::search_annotations(&conn, &query, limit.unwrap_or(50).clamp(1, 500))
+}
+
 #[tauri::command]
 pub fn get_all_annotations(
     book_id: Option<i64>,
@@ -292,8 +355,16 @@ pub fn get_reader_startup_data(book_id: i64, state: State<AppState>) -> Result<R
 // ==================== Book Access Command ====================
 
 #[tauri::command]
-pub fn get_book_file_path(book_id: i64, state: State<AppState>) -> Result<String> {
+pub fn get_book_file_path(
+    book_id: i64,
+    format: Option<String>,
+    state: State<AppState>,
+) -> Result<String> {
     validate::require_positive_id(book_id, "book_id")?;
+    // A specific format opens that file; otherwise the primary one
+    if let Some(format) = format.filter(|f| !f.trim().is_empty()) {
+        return crate::services::library_service::get_book_format_path(&state.db, book_id, &format);
+    }
     let conn = state.db.get_connection()?;
     let file_path: String = conn.query_row(
         "SELECT file_path FROM books WHERE id = ?1",
@@ -332,6 +403,19 @@ pub async fn detect_book_format(path: String) -> Result<String> {
     format_detector::detect_format(Path::new(&path)).await
 }
 
+/// Detect a file's format and report how it was identified and how
+/// confident the match is
+#[tauri::command]
+pub async fn detect_format_detailed(path: String) -> Result<DetailedFormat> {
+    validate::require_safe_path(&path, "path")?;
+    format_detection::detect_format_detailed(Path::new(&path))
+        .await
+        .map_err(|e| match e {
+            FormatError::Io(e) => ShioriError::Io(e),
+            other => ShioriError::InvalidFormat(other.to_string()),
+        })
+}
+
 #[tauri::command]
 pub async fn validate_book_file(path: String, format: String) -> Result<bool> {
     validate::require_safe_path(&path, "path")?;
@@ -396,6 +480,17 @@ pub fn get_reading_streak(state: State<AppState>) -> Result<ReadingStreak> {
     ReaderService::get_reading_streak(&conn)
 }
 
+/// Aggregated reading activity between two `YYYY-MM-DD` dates (inclusive)
+#[tauri::command]
+pub fn get_reading_stats(
+    from_date: String,
+    to_date: String,
+    state: State<AppState>,
+) -> Result<ReadingStats> {
+    let conn = state.db.get_connection()?;
+    stats_service::get_reading_stats(&conn, &from_date, &to_date)
+}
+
 #[tauri::command]
 pub fn get_reading_goal(state: State<AppState>) -> Result<ReadingGoal> {
     let conn = state.db.get_connection()?;
@@ -412,6 +507,19 @@ pub fn update_reading_goal(
     ReaderService::update_reading_goal(&conn, daily_minutes_target, yearly_books_target)
 }
 
+/// Set a "N books per week/month/year" goal on the active reading goal
+#[tauri::command]
+pub fn set_goal(period: String, target: i32, state: State<AppState>) -> Result<GoalProgress> {
+    let conn = state.db.get_connection()?;
+    goals_service::set_goal(&conn, &period, target)
+}
+
+#[tauri::command]
+pub fn get_goal_progress(state: State<AppState>) -> Result<Option<GoalProgress>> {
+    let conn = state.db.get_connection()?;
+    goals_service::get_goal_progress(&conn)
+}
+
 #[tauri::command]
 pub fn get_today_reading_time(state: State<AppState>) -> Result<i64> {
     let conn = state.db.get_connection()?;
@@ -1,8 +1,14 @@
+use crate::commands::preferences::resolve_book_css;
+use crate::db::Database;
 use crate::error::Result;
 use crate::services::cache::CacheStats;
-use crate::services::renderer::{BookMetadata, Chapter, SearchResult, TocEntry};
-use crate::services::rendering_service::RenderingService;
+use crate::services::library_service;
+use crate::services::reader_service::TtsSegment;
+use crate::services::renderer::{BookMetadata, Chapter, SearchOptions, SearchResult, TocEntry};
+use crate::services::rendering_service::{ReadingEstimate, RenderingService};
 use crate::utils::validate;
+use crate::AppState;
+use serde::Serialize;
 use std::sync::Arc;
 use tauri::State;
 
@@ -28,7 +34,9 @@ pub async fn open_book_renderer(
     book_id: i64,
     path: String,
     format: String,
+    password: Option<String>,
     state: State<'_, RenderingState>,
+    app_state: State<'_, AppState>,
 ) -> Result<BookMetadata> {
     validate::require_positive_id(book_id, "book_id")?;
     validate::require_safe_path(&path, "path")?;
@@ -40,17 +48,20 @@ pub async fn open_book_renderer(
     println!("format: {}", format);
 
     let service = state.service.clone();
-    let result = tokio::task::spawn_blocking(move || service.open_book(book_id, &path, &format))
-        .await
-        .unwrap_or_else(|e| {
-            Err(crate::error::ShioriError::Other(format!(
-                "Task panicked: {}",
-                e
-            )))
-        });
+    let result = tokio::task::spawn_blocking(move || {
+        service.open_book(book_id, &path, &format, password.as_deref())
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(crate::error::ShioriError::Other(format!(
+            "Task panicked: {}",
+            e
+        )))
+    });
 
     match &result {
         Ok(metadata) => {
+            library_service::mark_book_opened_in_background(app_state.db.clone(), book_id);
             println!("SUCCESS");
             println!("title: {}", metadata.title);
             println!("chapters: {}", metadata.total_chapters);
@@ -90,11 +101,26 @@ pub async fn get_book_toc(book_id: i64, state: State<'_, RenderingState>) -> Res
         })
 }
 
+/// Custom CSS for a book; a failed lookup only costs the styling, not the chapter
+fn book_css(db: &Database, book_id: i64) -> String {
+    db.get_connection()
+        .and_then(|conn| resolve_book_css(&conn, book_id, None))
+        .unwrap_or_else(|e| {
+            log::warn!(
+                "[Rendering] Custom CSS lookup failed for book {}: {}",
+                book_id,
+                e
+            );
+            String::new()
+        })
+}
+
 #[tauri::command]
 pub async fn get_book_chapter(
     book_id: i64,
     chapter_index: usize,
     state: State<'_, RenderingState>,
+    app_state: State<'_, AppState>,
 ) -> Result<Chapter> {
     validate::require_positive_id(book_id, "book_id")?;
     println!(
@@ -102,21 +128,134 @@ pub async fn get_book_chapter(
         book_id, chapter_index
     );
     let service = state.service.clone();
-    let result = tokio::task::spawn_blocking(move || service.get_chapter(book_id, chapter_index))
+    let db = app_state.db.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let css = book_css(&db, book_id);
+        service.get_chapter(book_id, chapter_index, &css)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(crate::error::ShioriError::Other(format!(
+            "Task panicked: {}",
+            e
+        )))
+    });
+
+    match &result {
+        Ok(chapter) => println!("[get_book_chapter] Got chapter: {}", chapter.title),
+        Err(e) => println!("[get_book_chapter] Error: {}", e),
+    }
+
+    result
+}
+
+/// Total words and reading time across every chapter of an open book
+#[tauri::command]
+pub async fn get_book_reading_estimate(
+    book_id: i64,
+    state: State<'_, RenderingState>,
+) -> Result<ReadingEstimate> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let service = state.service.clone();
+    tokio::task::spawn_blocking(move || service.get_reading_estimate(book_id))
         .await
         .unwrap_or_else(|e| {
             Err(crate::error::ShioriError::Other(format!(
                 "Task panicked: {}",
                 e
             )))
-        });
+        })
+}
 
-    match &result {
-        Ok(chapter) => println!("[get_book_chapter] Got chapter: {}", chapter.title),
-        Err(e) => println!("[get_book_chapter] Error: {}", e),
+/// Whole-book progress percentage for a CFI in an open EPUB, independent
+/// of how the reader paginates
+#[tauri::command]
+pub async fn compute_epub_progress(
+    book_id: i64,
+    cfi: String,
+    state: State<'_, RenderingState>,
+) -> Result<f64> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let service = state.service.clone();
+    tokio::task::spawn_blocking(move || service.compute_epub_progress(book_id, &cfi))
+        .await
+        .unwrap_or_else(|e| {
+            Err(crate::error::ShioriError::Other(format!(
+                "Task panicked: {}",
+                e
+            )))
+        })
+}
+
+/// Plain-text sentences of a chapter with offsets, for text-to-speech
+#[tauri::command]
+pub async fn get_tts_segments(
+    book_id: i64,
+    chapter_index: usize,
+    state: State<'_, RenderingState>,
+) -> Result<Vec<TtsSegment>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let service = state.service.clone();
+    tokio::task::spawn_blocking(move || service.get_tts_segments(book_id, chapter_index))
+        .await
+        .unwrap_or_else(|e| {
+            Err(crate::error::ShioriError::Other(format!(
+                "Task panicked: {}",
+                e
+            )))
+        })
+}
+
+/// Set the words-per-minute used for chapter and book reading estimates
+#[tauri::command]
+pub async fn set_reading_speed(wpm: u32, state: State<'_, RenderingState>) -> Result<()> {
+    if !(50..=2000).contains(&wpm) {
+        return Err(crate::error::ShioriError::Validation(
+            "wpm must be between 50 and 2000".to_string(),
+        ));
     }
+    state.service.set_words_per_minute(wpm);
+    Ok(())
+}
 
-    result
+/// A chapter as it renders with custom CSS applied
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterCssPreview {
+    /// Global and book CSS, in the order they are injected
+    pub applied_css: String,
+    pub chapter: Chapter,
+}
+
+/// Preview a chapter with the book's custom CSS. Passing `css` previews an
+/// unsaved edit in place of the stored book override.
+#[tauri::command]
+pub async fn preview_chapter_css(
+    book_id: i64,
+    chapter_index: usize,
+    css: Option<String>,
+    state: State<'_, RenderingState>,
+    app_state: State<'_, AppState>,
+) -> Result<ChapterCssPreview> {
+    validate::require_positive_id(book_id, "book_id")?;
+    let service = state.service.clone();
+    let db = app_state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = db.get_connection()?;
+        let applied_css = resolve_book_css(&conn, book_id, css.as_deref())?;
+        let chapter = service.get_chapter(book_id, chapter_index, &applied_css)?;
+        Ok(ChapterCssPreview {
+            applied_css,
+            chapter,
+        })
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(crate::error::ShioriError::Other(format!(
+            "Task panicked: {}",
+            e
+        )))
+    })
 }
 
 #[tauri::command]
@@ -140,13 +279,16 @@ pub async fn get_book_chapter_count(
 pub async fn search_in_book(
     book_id: i64,
     query: String,
+    options: Option<SearchOptions>,
     state: State<'_, RenderingState>,
 ) -> Result<Vec<SearchResult>> {
     validate::require_positive_id(book_id, "book_id")?;
     validate::require_non_empty(&query, "query")?;
     validate::require_max_length(&query, 500, "query")?;
+    let mut options = options.unwrap_or_default();
+    options.context_chars = options.context_chars.min(200);
     let service = state.service.clone();
-    tokio::task::spawn_blocking(move || service.search_book(book_id, &query))
+    tokio::task::spawn_blocking(move || service.search_book(book_id, &query, &options))
         .await
         .unwrap_or_else(|e| {
             Err(crate::error::ShioriError::Other(format!(
@@ -182,6 +324,18 @@ pub fn get_renderer_cache_stats(state: State<RenderingState>) -> Result<CacheSta
     Ok(state.service.get_cache_stats())
 }
 
+/// Resize the renderer cache at runtime; shrinking evicts the oldest items
+#[tauri::command]
+pub fn set_renderer_cache_size(mb: usize, state: State<RenderingState>) -> Result<CacheStats> {
+    if !(16..=4096).contains(&mb) {
+        return Err(crate::error::ShioriError::Validation(
+            "mb must be between 16 and 4096".to_string(),
+        ));
+    }
+    state.service.resize_cache(mb);
+    Ok(state.service.get_cache_stats())
+}
+
 #[tauri::command]
 pub fn clear_renderer_cache(state: State<RenderingState>) -> Result<()> {
     state.service.clear_all_caches();
@@ -207,6 +361,30 @@ pub async fn render_pdf_page(
         })
 }
 
+/// Render pages `start..=end` as PNGs, e.g. for a thumbnail strip. Pages
+/// are capped at `max_dimension` pixels (default 2048) on their longer side.
+#[tauri::command]
+pub async fn render_pdf_pages(
+    book_id: i64,
+    start: usize,
+    end: usize,
+    scale: f32,
+    max_dimension: Option<u32>,
+    state: State<'_, RenderingState>,
+) -> Result<Vec<(usize, Vec<u8>)>> {
+    validate::require_positive_id(book_id, "book_id")?;
+    if !(scale > 0.0 && scale <= 8.0) {
+        return Err(crate::error::ShioriError::Validation(
+            "scale must be greater than 0 and at most 8".to_string(),
+        ));
+    }
+    let max_dimension = max_dimension.unwrap_or(2048).clamp(64, 8192);
+    state
+        .service
+        .render_page_range(book_id, start, end, scale, max_dimension)
+        .await
+}
+
 #[tauri::command]
 pub async fn get_pdf_page_dimensions(
     book_id: i64,
@@ -3,7 +3,9 @@ use tauri::State;
 
 use crate::error::ShioriError;
 use crate::services::rss_scheduler::RssScheduler;
-use crate::services::rss_service::{DailyEpubOptions, RssArticle, RssFeed, RssService};
+use crate::services::rss_service::{
+    ArticleCleanupReport, DailyEpubOptions, RssArticle, RssFeed, RssService,
+};
 use crate::utils::validate;
 
 /// Add a new RSS feed
@@ -166,6 +168,17 @@ pub async fn mark_all_rss_articles_read(
         .map_err(|e| ShioriError::Other(e.to_string()))
 }
 
+/// Apply the article retention settings now instead of waiting for the scheduler
+#[tauri::command]
+pub async fn cleanup_rss_articles(
+    service: State<'_, Arc<RssService>>,
+    include_epub_linked: Option<bool>,
+) -> crate::error::Result<ArticleCleanupReport> {
+    service
+        .cleanup_old_articles(include_epub_linked.unwrap_or(false))
+        .map_err(|e| ShioriError::Other(e.to_string()))
+}
+
 /// Generate daily EPUB from unread articles
 #[tauri::command]
 pub async fn generate_daily_epub(
@@ -1,7 +1,8 @@
 use crate::services::search_service;
+use crate::utils::validate;
 use crate::{
     error::Result,
-    models::{SearchQuery, SearchResult},
+    models::{RankedSearchResult, SearchQuery, SearchResult},
     AppState,
 };
 use tauri::State;
@@ -11,3 +12,23 @@ pub fn search_books(state: State<AppState>, query: SearchQuery) -> Result<Search
     let db = &state.db;
     search_service::search(db, query)
 }
+
+/// Full-text search ranked by relevance, with highlighted snippets
+#[tauri::command]
+pub fn search_books_ranked(
+    state: State<AppState>,
+    query: String,
+    format: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<RankedSearchResult>> {
+    validate::require_non_empty(&query, "query")?;
+    validate::require_max_length(&query, search_service::MAX_RANKED_QUERY_LEN, "query")?;
+    search_service::search_ranked(
+        &state.db,
+        &query,
+        format.as_deref(),
+        limit.unwrap_or(50),
+        offset.unwrap_or(0),
+    )
+}
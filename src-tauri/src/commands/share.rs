@@ -2,8 +2,11 @@ use std::sync::Arc;
 use tauri::State;
 
 use crate::error::{Result, ShioriError};
+use crate::services::api_service::{self, ApiSettings};
+use crate::services::opds_service::{self, OpdsSettings};
 use crate::services::share_service::{Share, ShareOptions, ShareResponse, ShareService};
 use crate::utils::validate;
+use crate::AppState;
 
 /// Create a share for a book
 #[tauri::command]
@@ -13,6 +16,7 @@ pub async fn create_book_share(
     password: Option<String>,
     expires_in_hours: Option<i64>,
     max_downloads: Option<i32>,
+    deliver_format: Option<String>,
 ) -> Result<ShareResponse> {
     validate::require_positive_id(book_id, "book_id")?;
     if let Some(hours) = expires_in_hours {
@@ -35,6 +39,7 @@ pub async fn create_book_share(
         password,
         expires_in_hours,
         max_accesses: max_downloads,
+        deliver_format,
     };
 
     let share = service
@@ -148,3 +153,90 @@ pub async fn cleanup_expired_shares(
         .cleanup_expired_shares()
         .map_err(|e| ShioriError::Other(e.to_string()))
 }
+
+/// OPDS catalog settings. The password is never returned.
+#[tauri::command]
+pub fn get_opds_settings(state: State<'_, AppState>) -> Result<OpdsSettings> {
+    opds_service::load_opds_settings(&state.db)
+}
+
+/// Save the OPDS catalog settings and restart the share server if it is
+/// running so the change applies immediately. Omit `password` to keep the
+/// stored one, or pass an empty string to disable basic-auth.
+#[tauri::command]
+pub async fn update_opds_settings(
+    state: State<'_, AppState>,
+    service: State<'_, Arc<tokio::sync::Mutex<ShareService>>>,
+    enabled: bool,
+    username: String,
+    password: Option<String>,
+) -> Result<OpdsSettings> {
+    let settings =
+        opds_service::save_opds_settings(&state.db, enabled, &username, password.as_deref())?;
+
+    let mut service = service.lock().await;
+    if service.is_running() {
+        service
+            .stop_server()
+            .await
+            .map_err(|e| ShioriError::Other(e.to_string()))?;
+        service
+            .start_server()
+            .await
+            .map_err(|e| ShioriError::Other(e.to_string()))?;
+    }
+    Ok(settings)
+}
+
+/// Local HTTP API settings, including the access token
+#[tauri::command]
+pub fn get_api_settings(state: State<'_, AppState>) -> Result<ApiSettings> {
+    api_service::load_api_settings(&state.db)
+}
+
+/// Save the local HTTP API settings and start, stop or rebind the API server
+/// to match them
+#[tauri::command]
+pub async fn update_api_settings(
+    state: State<'_, AppState>,
+    service: State<'_, Arc<tokio::sync::Mutex<ShareService>>>,
+    enabled: bool,
+    allow_lan: bool,
+    port: u16,
+) -> Result<ApiSettings> {
+    let settings = api_service::save_api_settings(&state.db, enabled, allow_lan, port)?;
+    restart_api_server(&service).await?;
+    Ok(settings)
+}
+
+/// Issue a new API token; integrations using the old one stop working
+#[tauri::command]
+pub async fn regenerate_api_token(
+    state: State<'_, AppState>,
+    service: State<'_, Arc<tokio::sync::Mutex<ShareService>>>,
+) -> Result<ApiSettings> {
+    let settings = api_service::regenerate_api_token(&state.db)?;
+    restart_api_server(&service).await?;
+    Ok(settings)
+}
+
+/// Check if the local HTTP API is running
+#[tauri::command]
+pub async fn is_api_server_running(
+    service: State<'_, Arc<tokio::sync::Mutex<ShareService>>>,
+) -> Result<bool> {
+    let service = service.lock().await;
+    Ok(service.is_api_running())
+}
+
+async fn restart_api_server(service: &Arc<tokio::sync::Mutex<ShareService>>) -> Result<()> {
+    let mut service = service.lock().await;
+    service
+        .stop_api_server()
+        .await
+        .map_err(|e| ShioriError::Other(e.to_string()))?;
+    service
+        .start_api_server()
+        .await
+        .map_err(|e| ShioriError::Other(e.to_string()))
+}
@@ -1,6 +1,10 @@
-use crate::services::tag_service;
+use crate::services::tag_service::{self, TagFilterMode};
 use crate::utils::validate;
-use crate::{error::Result, models::Tag, AppState};
+use crate::{
+    error::Result,
+    models::{Book, Tag, TagNode, TagRule},
+    AppState,
+};
 use tauri::State;
 
 #[tauri::command]
@@ -9,6 +13,42 @@ pub fn get_tags(state: State<AppState>) -> Result<Vec<Tag>> {
     tag_service::get_all_tags(db)
 }
 
+/// Tags nested by path ("Fiction/Sci-Fi" under "Fiction")
+#[tauri::command]
+pub fn get_tag_tree(state: State<AppState>) -> Result<Vec<TagNode>> {
+    let db = &state.db;
+    tag_service::get_tag_tree(db)
+}
+
+#[tauri::command]
+pub fn get_books_by_tag(
+    state: State<AppState>,
+    tag_id: i64,
+    include_descendants: bool,
+) -> Result<Vec<Book>> {
+    validate::require_positive_id(tag_id, "tag_id")?;
+    let db = &state.db;
+    tag_service::get_books_by_tag(db, tag_id, include_descendants)
+}
+
+/// Books matching several tags; `mode` is "any" (OR) or "all" (AND)
+#[tauri::command]
+pub fn get_books_by_tags(
+    state: State<AppState>,
+    tag_ids: Vec<i64>,
+    mode: TagFilterMode,
+    domain: Option<String>,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<Book>> {
+    validate::require_non_empty_vec(&tag_ids, "tag_ids")?;
+    if let Some(domain) = &domain {
+        validate::require_one_of(domain, &["books", "manga", "comics", "all"], "domain")?;
+    }
+    let db = &state.db;
+    tag_service::get_books_by_tags(db, &tag_ids, mode, domain.as_deref(), limit, offset)
+}
+
 #[tauri::command]
 pub fn get_book_tag_ids(book_id: i64, state: State<AppState>) -> Result<Vec<i64>> {
     let db = &state.db;
@@ -41,3 +81,75 @@ pub fn remove_tag_from_book(state: State<AppState>, book_id: i64, tag_id: i64) -
     let db = &state.db;
     tag_service::remove_tag_from_book(db, book_id, tag_id)
 }
+
+/// Tag every book in `book_ids` in one transaction
+#[tauri::command]
+pub fn add_tag_to_books(state: State<AppState>, tag_id: i64, book_ids: Vec<i64>) -> Result<usize> {
+    validate::require_positive_id(tag_id, "tag_id")?;
+    let db = &state.db;
+    tag_service::add_tag_to_books(db, tag_id, &book_ids)
+}
+
+#[tauri::command]
+pub fn remove_tag_from_books(
+    state: State<AppState>,
+    tag_id: i64,
+    book_ids: Vec<i64>,
+) -> Result<usize> {
+    validate::require_positive_id(tag_id, "tag_id")?;
+    let db = &state.db;
+    tag_service::remove_tag_from_books(db, tag_id, &book_ids)
+}
+
+/// Give every uncolored tag a color derived from its name
+#[tauri::command]
+pub fn auto_assign_tag_colors(state: State<AppState>) -> Result<usize> {
+    let db = &state.db;
+    tag_service::auto_assign_colors(db)
+}
+
+/// Fold `from_id` into `into_id`, deleting `from_id`
+#[tauri::command]
+pub fn merge_tags(state: State<AppState>, from_id: i64, into_id: i64) -> Result<()> {
+    validate::require_positive_id(from_id, "from_id")?;
+    validate::require_positive_id(into_id, "into_id")?;
+    let db = &state.db;
+    tag_service::merge_tags(db, from_id, into_id)
+}
+
+#[tauri::command]
+pub fn list_tag_rules(state: State<AppState>) -> Result<Vec<TagRule>> {
+    let db = &state.db;
+    tag_service::list_tag_rules(db)
+}
+
+/// Create a rule, or update it when `rule.id` is set
+#[tauri::command]
+pub fn set_tag_rule(state: State<AppState>, rule: TagRule) -> Result<i64> {
+    validate::require_non_empty(&rule.tag_name, "tag_name")?;
+    validate::require_max_length(&rule.tag_name, 200, "tag_name")?;
+    validate::require_max_length(&rule.value, 200, "value")?;
+    let db = &state.db;
+    tag_service::set_tag_rule(db, rule)
+}
+
+#[tauri::command]
+pub fn delete_tag_rule(state: State<AppState>, id: i64) -> Result<()> {
+    validate::require_positive_id(id, "id")?;
+    let db = &state.db;
+    tag_service::delete_tag_rule(db, id)
+}
+
+/// Apply the tag rules to books imported before they existed
+#[tauri::command]
+pub async fn apply_tag_rules_to_library(state: State<'_, AppState>) -> Result<usize> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || tag_service::apply_tag_rules_to_library(&db))
+        .await
+        .unwrap_or_else(|e| {
+            Err(crate::error::ShioriError::Other(format!(
+                "Task panicked: {}",
+                e
+            )))
+        })
+}
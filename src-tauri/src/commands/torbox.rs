@@ -343,6 +343,7 @@ async fn finalize_import_from_target(
                 &app_state.db,
                 vec![path_str.clone()],
                 &app_state.covers_dir,
+                library_service::ImportPolicy::default(),
             )?
         };
 
@@ -577,6 +578,8 @@ pub async fn send_to_torbox(
             success: vec![imported_path],
             failed: vec![],
             duplicates: vec![],
+            merged: vec![],
+            replaced: vec![],
         },
     })
 }
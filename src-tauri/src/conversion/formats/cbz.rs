@@ -9,6 +9,7 @@ use std::path::Path;
 use crate::conversion::epub_builder::comic_stylesheet;
 use crate::conversion::error::ConversionError;
 use crate::conversion::oeb::{OebBook, OebChapter, OebImage};
+use crate::utils::natural_sort;
 
 // Image file extensions we consider valid comic pages
 const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "bmp", "tiff", "tif"];
@@ -40,7 +41,7 @@ pub fn parse(path: &Path) -> Result<OebBook, ConversionError> {
         .collect();
 
     // Natural sort: page_001 < page_002 < page_010
-    image_names.sort_by(|a, b| natord::compare(a, b));
+    image_names.sort_by(|a, b| natural_sort::compare(a, b));
 
     if image_names.is_empty() {
         return Err(ConversionError::EmptyContent);
@@ -132,7 +133,7 @@ pub fn parse_image_dir(dir: &Path) -> Result<OebBook, ConversionError> {
     image_paths.sort_by(|a, b| {
         let na = a.file_name().and_then(|x| x.to_str()).unwrap_or("");
         let nb = b.file_name().and_then(|x| x.to_str()).unwrap_or("");
-        natord::compare(na, nb)
+        natural_sort::compare(na, nb)
     });
 
     if image_paths.is_empty() {
@@ -81,7 +81,18 @@ impl SourceFormat {
 /// Bridge ConversionError → FormatError for ConversionEngine compatibility.
 impl From<ConversionError> for crate::services::format_adapter::FormatError {
     fn from(e: ConversionError) -> Self {
-        crate::services::format_adapter::FormatError::ConversionError(e.to_string())
+        use crate::services::format_adapter::FormatError;
+        match e {
+            ConversionError::IoError(e) => FormatError::Io(e),
+            ConversionError::UnsupportedFormat(ext) => FormatError::UnsupportedFormat(ext),
+            ConversionError::EmptyContent => FormatError::EmptyOutput(e.to_string()),
+            ConversionError::MissingDependency(dep) => FormatError::DependencyMissing(dep),
+            ConversionError::ParseError { .. }
+            | ConversionError::ZipError(_)
+            | ConversionError::InvalidFormat(_)
+            | ConversionError::Xml(_) => FormatError::CorruptSource(e.to_string()),
+            _ => FormatError::ConversionError(e.to_string()),
+        }
     }
 }
 
@@ -103,7 +114,15 @@ pub async fn convert_to_epub(
     match format {
         SourceFormat::Mobi | SourceFormat::Azw3 => mobi::convert(source_path, output_path).await,
         SourceFormat::Pdf => pdf::convert(source_path, output_path, progress_cb).await,
-        SourceFormat::Txt => txt::convert(source_path, output_path).await,
+        SourceFormat::Txt => {
+            txt::convert_with(
+                source_path,
+                output_path,
+                &txt::ChapterSplit::default(),
+                progress_cb,
+            )
+            .await
+        }
         SourceFormat::Fb2 => fb2::convert(source_path, output_path).await,
         SourceFormat::Docx => docx::convert(source_path, output_path).await,
     }
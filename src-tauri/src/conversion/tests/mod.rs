@@ -80,4 +80,60 @@ pub mod tests {
             "Expected UnsupportedFormat error"
         );
     }
+
+    #[tokio::test]
+    async fn test_txt_heading_threshold_controls_chapter_splits() {
+        use crate::conversion::txt::{parse_with, ChapterSplit};
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("novel.txt");
+        std::fs::write(
+            &source,
+            "Chapter 1\n\nIt begins.\n\nIV\n\nA bare numeral heading.\n\n\
+             Chapter 2 The Storm\n\nRain fell.\n",
+        )
+        .unwrap();
+
+        let titles = |split: ChapterSplit| {
+            let source = source.clone();
+            async move {
+                parse_with(&source, &split)
+                    .await
+                    .unwrap()
+                    .chapters
+                    .into_iter()
+                    .map(|c| c.title.unwrap())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            titles(ChapterSplit::default()).await,
+            ["Chapter 1", "IV", "Chapter 2 The Storm"]
+        );
+        assert_eq!(titles(ChapterSplit::Auto(4)).await, ["Chapter 1", "IV"]);
+        assert_eq!(titles(ChapterSplit::Auto(5)).await, ["Chapter 1"]);
+    }
+
+    #[tokio::test]
+    async fn test_txt_year_inside_paragraph_is_not_a_heading() {
+        use crate::conversion::txt::parse_with;
+
+        // No blank lines, so the text is unwrapped line by line and the
+        // year ends up as a paragraph of its own
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("memoir.txt");
+        std::fs::write(
+            &source,
+            "Chapter 1\n\
+             The war ended in\n\
+             1945\n\
+             and the town was slowly rebuilt.\n",
+        )
+        .unwrap();
+
+        let book = parse_with(&source, &Default::default()).await.unwrap();
+        let titles: Vec<_> = book.chapters.iter().map(|c| c.title.as_deref()).collect();
+        assert_eq!(titles, [Some("Chapter 1")]);
+        assert!(book.chapters[0].html.contains("<p>1945</p>"));
+    }
 }
@@ -6,14 +6,39 @@
 /// - Unformatted: hard line breaks everywhere, needs unwrapping
 ///
 /// Also: encoding detection, chapter detection, scene breaks, smart quotes.
+use std::collections::HashSet;
 use std::path::Path;
 
 use super::oeb::{OebBook, OebChapter};
 use super::utils;
 use super::ConversionError;
+use crate::services::epub_builder::{heading_score, DEFAULT_HEADING_THRESHOLD};
+
+/// How TXT → EPUB picks chapter boundaries
+#[derive(Debug, Clone)]
+pub enum ChapterSplit {
+    /// Paragraphs whose heading score (see `epub_builder::heading_score`)
+    /// reaches the threshold, e.g. "Chapter 1", "IV" or all-caps titles
+    Auto(u8),
+    /// Paragraphs whose whole text matches the pattern start a chapter
+    Pattern(regex::Regex),
+    /// The whole text becomes one chapter
+    None,
+}
+
+impl Default for ChapterSplit {
+    fn default() -> Self {
+        Self::Auto(DEFAULT_HEADING_THRESHOLD)
+    }
+}
 
 /// Parse a TXT file into an OebBook.
 pub async fn parse(source: &Path) -> Result<OebBook, ConversionError> {
+    parse_with(source, &ChapterSplit::default()).await
+}
+
+/// Parse a TXT file into an OebBook, splitting chapters as `split` says.
+pub async fn parse_with(source: &Path, split: &ChapterSplit) -> Result<OebBook, ConversionError> {
     let raw = tokio::fs::read(source).await?;
     let text = utils::decode_text(&raw)?;
     let text = utils::normalize_line_endings(&text);
@@ -26,15 +51,26 @@ pub async fn parse(source: &Path) -> Result<OebBook, ConversionError> {
         source.display()
     );
 
+    // Headings are judged on the source lines, where the blank lines
+    // around them are still visible
+    let headings = match split {
+        ChapterSplit::Auto(threshold) => heading_lines(&text, *threshold),
+        _ => HashSet::new(),
+    };
+
     // Convert to HTML based on mode
     let html = match mode {
         TextMode::Markdown => markdown_to_html(&text),
-        TextMode::Formatted => formatted_to_html(&text),
-        TextMode::Unformatted => unformatted_to_html(&text),
+        TextMode::Formatted => formatted_to_html(&text, &headings),
+        TextMode::Unformatted => unformatted_to_html(&text, &headings),
     };
 
     // Split into chapters
-    let chapters = split_into_chapters(&html);
+    let chapters = match split {
+        ChapterSplit::Auto(threshold) => split_into_chapters(&html, mode, *threshold),
+        ChapterSplit::Pattern(pattern) => split_on_pattern(&html, pattern),
+        ChapterSplit::None => vec![("Full Text".to_string(), html)],
+    };
 
     // Infer title from filename
     let title = source
@@ -129,13 +165,22 @@ fn markdown_to_html(text: &str) -> String {
 // FORMATTED TEXT (blank-line paragraphs)
 // ──────────────────────────────────────────────────────────────────────────
 
-fn formatted_to_html(text: &str) -> String {
+fn formatted_to_html(text: &str, headings: &HashSet<usize>) -> String {
     let text = utils::smart_quotes(text);
     let mut html = String::new();
     let mut para = String::new();
 
-    for line in text.lines() {
-        if line.trim().is_empty() {
+    for (i, line) in text.lines().enumerate() {
+        if headings.contains(&i) {
+            if !para.trim().is_empty() {
+                html.push_str(&format!(
+                    "  <p>{}</p>\n",
+                    super::oeb::escape_xml(para.trim())
+                ));
+                para.clear();
+            }
+            push_heading(&mut html, line);
+        } else if line.trim().is_empty() {
             if !para.trim().is_empty() {
                 if utils::is_scene_break(para.trim()) {
                     html.push_str("  <hr class=\"scene-break\"/>\n");
@@ -168,7 +213,7 @@ fn formatted_to_html(text: &str) -> String {
 // UNFORMATTED TEXT (hard line breaks — needs unwrapping)
 // ──────────────────────────────────────────────────────────────────────────
 
-fn unformatted_to_html(text: &str) -> String {
+fn unformatted_to_html(text: &str, headings: &HashSet<usize>) -> String {
     let text = utils::smart_quotes(text);
     let lines: Vec<&str> = text.lines().collect();
 
@@ -191,6 +236,18 @@ fn unformatted_to_html(text: &str) -> String {
     for (i, line) in lines.iter().enumerate() {
         let trimmed = line.trim();
 
+        if headings.contains(&i) {
+            if !para.trim().is_empty() {
+                html.push_str(&format!(
+                    "  <p>{}</p>\n",
+                    super::oeb::escape_xml(para.trim())
+                ));
+                para.clear();
+            }
+            push_heading(&mut html, line);
+            continue;
+        }
+
         if trimmed.is_empty() {
             if !para.trim().is_empty() {
                 html.push_str(&format!(
@@ -261,17 +318,63 @@ fn unformatted_to_html(text: &str) -> String {
 // CHAPTER SPLITTING
 // ──────────────────────────────────────────────────────────────────────────
 
-/// Split HTML content into chapters based on heading detection.
-fn split_into_chapters(html: &str) -> Vec<(String, String)> {
-    static CHAPTER_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
-        regex::Regex::new(
-        r"(?im)^\s*<p>((?:chapter|part|book|prologue|epilogue|introduction|appendix|preface)\s*[\d\w]*\.?\s*)</p>\s*$"
-    ).unwrap()
-    });
+/// Indices of the lines of `text` that score at least `threshold` as
+/// headings, judged with the blank lines actually around them
+fn heading_lines(text: &str, threshold: u8) -> HashSet<usize> {
+    let lines: Vec<&str> = text.lines().collect();
+    let is_blank = |i: Option<usize>| {
+        i.and_then(|i| lines.get(i))
+            .map_or(true, |l| l.trim().is_empty())
+    };
+    (0..lines.len())
+        .filter(|&i| {
+            heading_score(lines[i], is_blank(i.checked_sub(1)), is_blank(Some(i + 1)))
+                >= threshold.max(1)
+        })
+        .collect()
+}
+
+/// A line `heading_lines` picked, as its own marked paragraph
+fn push_heading(html: &mut String, line: &str) {
+    html.push_str(&format!(
+        "  <p class=\"heading\">{}</p>\n",
+        super::oeb::escape_xml(line.trim())
+    ));
+}
+
+/// Split HTML content into chapters at heading paragraphs. Plain text has
+/// them marked while its paragraphs are built (see `heading_lines`).
+/// Markdown paragraphs are blocks between blank lines, so a one-line
+/// paragraph there counts as set apart.
+fn split_into_chapters(html: &str, mode: TextMode, threshold: u8) -> Vec<(String, String)> {
+    match mode {
+        TextMode::Markdown => split_at_paragraphs(html, |text, _| {
+            heading_score(text, true, true) >= threshold.max(1)
+        }),
+        TextMode::Formatted | TextMode::Unformatted => {
+            split_at_paragraphs(html, |_, marked| marked)
+        }
+    }
+}
 
-    // Also match all-caps lines that look like chapter titles
-    static CAPS_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
-        regex::Regex::new(r"(?m)^\s*<p>([A-Z][A-Z\s\d]{2,58})</p>\s*$").unwrap()
+/// Split HTML content into chapters at paragraphs matching `pattern`.
+fn split_on_pattern(html: &str, pattern: &regex::Regex) -> Vec<(String, String)> {
+    split_at_paragraphs(html, |text, _| {
+        pattern
+            .find(text)
+            .is_some_and(|m| m.start() == 0 && m.end() == text.len())
+    })
+}
+
+/// Split HTML content into chapters at the `<p>` paragraphs `is_heading`
+/// accepts, given their text and whether they are marked as headings;
+/// each becomes its chapter's title and `<h2>`.
+fn split_at_paragraphs(
+    html: &str,
+    is_heading: impl Fn(&str, bool) -> bool,
+) -> Vec<(String, String)> {
+    static PARAGRAPH_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r#"^\s*<p( class="heading")?>(.*)</p>\s*$"#).unwrap()
     });
 
     let mut chapters: Vec<(String, String)> = Vec::new();
@@ -279,37 +382,23 @@ fn split_into_chapters(html: &str) -> Vec<(String, String)> {
     let mut current_body = String::new();
 
     for line in html.lines() {
-        if let Some(cap) = CHAPTER_RE.captures(line) {
-            // Save previous chapter
-            if !current_body.trim().is_empty() {
-                chapters.push((current_title.clone(), current_body.trim().to_string()));
-            }
-            current_title = cap[1].trim().to_string();
-            current_body.clear();
-            current_body.push_str(&format!(
-                "  <h2>{}</h2>\n",
-                super::oeb::escape_xml(&current_title)
-            ));
-        } else if let Some(cap) = CAPS_RE.captures(line) {
-            let candidate = cap[1].trim();
-            // Must not be a regular sentence fragment — require surrounded by context
-            if candidate.len() < 60 && candidate.split_whitespace().count() <= 8 {
+        let heading = PARAGRAPH_RE
+            .captures(line)
+            .filter(|cap| is_heading(cap[2].trim(), cap.get(1).is_some()))
+            .map(|cap| cap[2].trim().to_string());
+        match heading {
+            Some(title) => {
                 if !current_body.trim().is_empty() {
                     chapters.push((current_title.clone(), current_body.trim().to_string()));
                 }
-                current_title = titlecase(candidate);
                 current_body.clear();
-                current_body.push_str(&format!(
-                    "  <h2>{}</h2>\n",
-                    super::oeb::escape_xml(&current_title)
-                ));
-                continue;
+                current_body.push_str(&format!("  <h2>{}</h2>\n", title));
+                current_title = title;
+            }
+            None => {
+                current_body.push_str(line);
+                current_body.push('\n');
             }
-            current_body.push_str(line);
-            current_body.push('\n');
-        } else {
-            current_body.push_str(line);
-            current_body.push('\n');
         }
     }
 
@@ -326,7 +415,25 @@ fn split_into_chapters(html: &str) -> Vec<(String, String)> {
 
 /// Convert a TXT file to EPUB 3 (Legacy wrapper for ConversionEngine).
 pub async fn convert(source: &Path, output: &Path) -> Result<super::EpubOutput, ConversionError> {
-    let mut book = parse(source).await?;
+    convert_with(source, output, &ChapterSplit::default(), None).await
+}
+
+/// Convert a TXT file to EPUB 3 with an explicit chapter split strategy.
+pub async fn convert_with(
+    source: &Path,
+    output: &Path,
+    split: &ChapterSplit,
+    progress_cb: Option<&(dyn Fn(u8, &str) + Send + Sync)>,
+) -> Result<super::EpubOutput, ConversionError> {
+    let report = |percent: u8, stage: &str| {
+        if let Some(cb) = progress_cb {
+            cb(percent, stage);
+        }
+    };
+
+    report(20, "Splitting chapters...");
+    let mut book = parse_with(source, split).await?;
+    report(60, "Building EPUB...");
     book.sanitize_html();
     super::epub_builder::build_epub(&book, output)?;
 
@@ -339,21 +446,3 @@ pub async fn convert(source: &Path, output: &Path) -> Result<super::EpubOutput,
         warnings: vec![],
     })
 }
-
-/// Convert "ALL CAPS TITLE" to "All Caps Title"
-fn titlecase(s: &str) -> String {
-    s.split_whitespace()
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(c) => {
-                    let mut s = c.to_uppercase().to_string();
-                    s.extend(chars.map(|c| c.to_lowercase().next().unwrap_or(c)));
-                    s
-                }
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" ")
-}
@@ -8,6 +8,91 @@
 use rusqlite::{Connection, Result};
 use sha2::{Digest, Sha256};
 
+/// Newest schema version this build knows how to migrate to. Bump it
+/// together with each new migration.
+pub const LATEST_VERSION: i32 = 59;
+
+/// Columns of the books_fts index, in declaration order
+const FTS_COLUMNS: [&str; 6] = [
+    "title",
+    "authors",
+    "publisher",
+    "description",
+    "tags",
+    "isbn",
+];
+
+/// Whether the book row `{b}` belongs in `books_fts_cjk`: its language is
+/// Chinese, Japanese or Korean, or its title contains kana, CJK ideographs
+/// or Hangul (covers libraries imported before language detection).
+const CJK_BOOK_SQL: &str =
+    "(lower({b}.language) IN ('ja', 'zh', 'ko', 'jpn', 'zho', 'chi', 'kor', 'cmn', 'yue')
+     OR lower({b}.language) LIKE 'ja-%' OR lower({b}.language) LIKE 'zh-%'
+     OR lower({b}.language) LIKE 'ko-%'
+     OR {b}.title GLOB '*[\u{3040}-\u{30FF}\u{3400}-\u{9FFF}\u{AC00}-\u{D7AF}]*')";
+
+/// Tags of the book row `{b}` as the search indexes hold them. Nested tags
+/// are indexed by their leaf name only; a top-level tag keeps its whole name
+/// even if it contains '/' ("AC/DC").
+const BOOK_TAGS_SQL: &str = "(SELECT GROUP_CONCAT(COALESCE(
+            (SELECT substr(t.name, length(p.name) + 2) FROM tags p
+             WHERE p.id = t.parent_id AND substr(t.name, 1, length(p.name) + 1) = p.name || '/'),
+            t.name), ' ')
+        FROM tags t
+        JOIN books_tags bt ON t.id = bt.tag_id
+        WHERE bt.book_id = {b}.id)";
+
+/// `books_fts` values for the book row `{b}`: id, title, authors,
+/// publisher, description, tags, isbn
+const FTS_ROW_SQL: &str = "SELECT {b}.id, {b}.title,
+       (SELECT GROUP_CONCAT(a.name, ' ') FROM authors a
+        JOIN books_authors ba ON a.id = ba.author_id
+        WHERE ba.book_id = {b}.id),
+       {b}.publisher,
+       {b}.notes,
+       {tags},
+       {b}.isbn";
+
+/// `books_fts_cjk` values for the book row `{b}`: id, title, authors, tags
+const CJK_ROW_SQL: &str = "SELECT {b}.id, {b}.title,
+       (SELECT GROUP_CONCAT(a.name, ' ') FROM authors a
+        JOIN books_authors ba ON a.id = ba.author_id
+        WHERE ba.book_id = {b}.id),
+       {tags}";
+
+/// Fill in one of the `*_ROW_SQL` templates for the row alias `alias`
+fn index_row_sql(template: &str, alias: &str) -> String {
+    template
+        .replace("{tags}", BOOK_TAGS_SQL)
+        .replace("{b}", alias)
+}
+
+/// `INSERT INTO books_fts` of the books in `from_where`, a
+/// `FROM books b WHERE ...` clause
+pub(crate) fn fts_index_insert(from_where: &str) -> String {
+    format!(
+        "INSERT INTO books_fts(rowid, title, authors, publisher, description, tags, isbn) {} {}",
+        index_row_sql(FTS_ROW_SQL, "b"),
+        from_where
+    )
+}
+
+/// SQL condition selecting the CJK-indexed books, for the row alias `alias`
+pub(crate) fn cjk_book_condition(alias: &str) -> String {
+    CJK_BOOK_SQL.replace("{b}", alias)
+}
+
+/// `INSERT INTO books_fts_cjk` of the CJK books among `from_where`, a
+/// `FROM books b WHERE ...` clause
+pub(crate) fn cjk_index_insert(from_where: &str) -> String {
+    format!(
+        "INSERT INTO books_fts_cjk(rowid, title, authors, tags) {} {} AND {}",
+        index_row_sql(CJK_ROW_SQL, "b"),
+        from_where,
+        cjk_book_condition("b")
+    )
+}
+
 pub struct MigrationManager<'a> {
     conn: &'a Connection,
 }
@@ -141,7 +226,76 @@ impl<'a> MigrationManager<'a> {
         if current_version < 39 {
             self.run_in_savepoint("v39", |mgr| mgr.migrate_to_v39())?;
         }
+        if current_version < 40 {
+            self.run_in_savepoint("v40", |mgr| mgr.migrate_to_v40())?;
+        }
+        if current_version < 41 {
+            self.run_in_savepoint("v41", |mgr| mgr.migrate_to_v41())?;
+        }
+        if current_version < 42 {
+            self.run_in_savepoint("v42", |mgr| mgr.migrate_to_v42())?;
+        }
+
+        if current_version < 43 {
+            self.run_in_savepoint("v43", |mgr| mgr.migrate_to_v43())?;
+        }
+        if current_version < 44 {
+            self.run_in_savepoint("v44", |mgr| mgr.migrate_to_v44())?;
+        }
+        if current_version < 45 {
+            self.run_in_savepoint("v45", |mgr| mgr.migrate_to_v45())?;
+        }
+        if current_version < 46 {
+            self.run_in_savepoint("v46", |mgr| mgr.migrate_to_v46())?;
+        }
+        if current_version < 47 {
+            self.run_in_savepoint("v47", |mgr| mgr.migrate_to_v47())?;
+        }
+        if current_version < 48 {
+            self.run_in_savepoint("v48", |mgr| mgr.migrate_to_v48())?;
+        }
+        if current_version < 49 {
+            self.run_in_savepoint("v49", |mgr| mgr.migrate_to_v49())?;
+        }
+        if current_version < 50 {
+            self.run_in_savepoint("v50", |mgr| mgr.migrate_to_v50())?;
+        }
+
+        if current_version < 51 {
+            self.run_in_savepoint("v51", |mgr| mgr.migrate_to_v51())?;
+        }
+
+        if current_version < 52 {
+            self.run_in_savepoint("v52", |mgr| mgr.migrate_to_v52())?;
+        }
+
+        if current_version < 53 {
+            self.run_in_savepoint("v53", |mgr| mgr.migrate_to_v53())?;
+        }
 
+        if current_version < 54 {
+            self.run_in_savepoint("v54", |mgr| mgr.migrate_to_v54())?;
+        }
+
+        if current_version < 55 {
+            self.run_in_savepoint("v55", |mgr| mgr.migrate_to_v55())?;
+        }
+
+        if current_version < 56 {
+            self.run_in_savepoint("v56", |mgr| mgr.migrate_to_v56())?;
+        }
+
+        if current_version < 57 {
+            self.run_in_savepoint("v57", |mgr| mgr.migrate_to_v57())?;
+        }
+
+        if current_version < 58 {
+            self.run_in_savepoint("v58", |mgr| mgr.migrate_to_v58())?;
+        }
+
+        if current_version < 59 {
+            self.run_in_savepoint("v59", |mgr| mgr.migrate_to_v59())?;
+        }
 
         // Always ensure the FTS table has the correct schema.
         // Previous buggy code in initialize_schema would drop and recreate
@@ -181,25 +335,100 @@ impl<'a> MigrationManager<'a> {
     }
 
     /// Ensure FTS5 table has the correct 6-column schema.
-    /// If it exists with wrong columns, drop and recreate it.
+    /// If it exists with wrong columns, drop and recreate it; if only some
+    /// rows of it or books_fts_cjk are missing or stale, re-index just those.
     fn ensure_fts_schema(&self) -> Result<()> {
-        // Check if books_fts exists and has the right columns
-        let has_publisher: bool = {
-            let mut stmt = self.conn.prepare(
-                "SELECT COUNT(*) FROM pragma_table_info('books_fts') WHERE name = 'publisher'",
-            )?;
-            let count: i32 = stmt.query_row([], |row| row.get(0))?;
-            count > 0
-        };
+        if !self.fts_schema_ok()? {
+            log::info!("[Migration] Recreating FTS5 table with correct schema");
+            self.rebuild_fts()?;
+        }
+        if !self.is_fts_consistent()? {
+            let repaired = self.reindex_fts_gaps()?;
+            log::info!("[Migration] Re-indexed {} out-of-sync FTS rows", repaired);
+        }
+        Ok(())
+    }
 
-        let fts_exists = self.table_exists("books_fts")?;
+    /// True when books_fts holds exactly one row per book and books_fts_cjk
+    /// exactly one per CJK book, compared by rowid. Assumes the schema is
+    /// already correct (see `fts_schema_ok`).
+    pub(crate) fn is_fts_consistent(&self) -> Result<bool> {
+        let out_of_sync: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM books WHERE id NOT IN (SELECT rowid FROM books_fts))
+                 OR EXISTS(SELECT 1 FROM books_fts WHERE rowid NOT IN (SELECT id FROM books))",
+            [],
+            |row| row.get(0),
+        )?;
+        if out_of_sync || !self.table_exists("books_fts_cjk")? {
+            return Ok(!out_of_sync);
+        }
+
+        let cjk_out_of_sync: bool = self.conn.query_row(
+            &format!(
+                "SELECT EXISTS(SELECT 1 FROM books b
+                               WHERE {cond} AND b.id NOT IN (SELECT rowid FROM books_fts_cjk))
+                     OR EXISTS(SELECT 1 FROM books_fts_cjk
+                               WHERE rowid NOT IN (SELECT b.id FROM books b WHERE {cond}))",
+                cond = cjk_book_condition("b")
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(!cjk_out_of_sync)
+    }
 
-        if !fts_exists || !has_publisher {
-            log::info!("[Migration] Recreating FTS5 table with correct schema");
+    /// Drop books_fts and books_fts_cjk rows whose book is gone (or, for the
+    /// CJK index, no longer CJK) and index books that have no row, leaving
+    /// every other row alone. Returns how many rows changed.
+    pub(crate) fn reindex_fts_gaps(&self) -> Result<usize> {
+        let stale = self.conn.execute(
+            "DELETE FROM books_fts WHERE rowid NOT IN (SELECT id FROM books)",
+            [],
+        )?;
+        let missing = self.conn.execute(
+            &fts_index_insert("FROM books b WHERE b.id NOT IN (SELECT rowid FROM books_fts)"),
+            [],
+        )?;
+        if !self.table_exists("books_fts_cjk")? {
+            return Ok(stale + missing);
+        }
 
-            // Drop old table and all triggers
-            self.conn.execute_batch(
-                r#"
+        let cjk_stale = self.conn.execute(
+            &format!(
+                "DELETE FROM books_fts_cjk
+                 WHERE rowid NOT IN (SELECT b.id FROM books b WHERE {})",
+                cjk_book_condition("b")
+            ),
+            [],
+        )?;
+        let cjk_missing = self.conn.execute(
+            &cjk_index_insert("FROM books b WHERE b.id NOT IN (SELECT rowid FROM books_fts_cjk)"),
+            [],
+        )?;
+        Ok(stale + missing + cjk_stale + cjk_missing)
+    }
+
+    /// True when books_fts exists with exactly the expected columns
+    pub(crate) fn fts_schema_ok(&self) -> Result<bool> {
+        if !self.table_exists("books_fts")? {
+            return Ok(false);
+        }
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM pragma_table_info('books_fts')")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(columns == FTS_COLUMNS)
+    }
+
+    /// Drop books_fts and its triggers, recreate them and re-index every book.
+    /// Nested tags are indexed by their leaf name only; a top-level tag keeps
+    /// its whole name even if it contains '/' ("AC/DC").
+    pub(crate) fn rebuild_fts(&self) -> Result<()> {
+        // Drop old table and all triggers
+        self.conn.execute_batch(
+            r#"
                 DROP TRIGGER IF EXISTS books_fts_insert;
                 DROP TRIGGER IF EXISTS books_fts_update;
                 DROP TRIGGER IF EXISTS books_fts_delete;
@@ -208,11 +437,11 @@ impl<'a> MigrationManager<'a> {
                 DROP TRIGGER IF EXISTS books_au;
                 DROP TABLE IF EXISTS books_fts;
             "#,
-            )?;
+        )?;
 
-            // Create with correct schema
-            self.conn.execute_batch(
-                r#"
+        // Create with correct schema
+        self.conn.execute_batch(&format!(
+            r#"
                 CREATE VIRTUAL TABLE books_fts USING fts5(
                     title,
                     authors,
@@ -225,16 +454,7 @@ impl<'a> MigrationManager<'a> {
 
                 CREATE TRIGGER books_ai AFTER INSERT ON books BEGIN
                     INSERT INTO books_fts(rowid, title, authors, publisher, description, tags, isbn)
-                    SELECT new.id, new.title, 
-                           (SELECT GROUP_CONCAT(a.name, ' ') FROM authors a 
-                            JOIN books_authors ba ON a.id = ba.author_id 
-                            WHERE ba.book_id = new.id),
-                           new.publisher,
-                           new.notes,
-                           (SELECT GROUP_CONCAT(t.name, ' ') FROM tags t 
-                            JOIN books_tags bt ON t.id = bt.tag_id 
-                            WHERE bt.book_id = new.id),
-                           new.isbn;
+                    {row};
                 END;
                 
                 CREATE TRIGGER books_ad AFTER DELETE ON books BEGIN
@@ -244,39 +464,55 @@ impl<'a> MigrationManager<'a> {
                 CREATE TRIGGER books_au AFTER UPDATE ON books BEGIN
                     DELETE FROM books_fts WHERE rowid = old.id;
                     INSERT INTO books_fts(rowid, title, authors, publisher, description, tags, isbn)
-                    SELECT new.id, new.title, 
-                           (SELECT GROUP_CONCAT(a.name, ' ') FROM authors a 
-                            JOIN books_authors ba ON a.id = ba.author_id 
-                            WHERE ba.book_id = new.id),
-                           new.publisher,
-                           new.notes,
-                           (SELECT GROUP_CONCAT(t.name, ' ') FROM tags t 
-                            JOIN books_tags bt ON t.id = bt.tag_id 
-                            WHERE bt.book_id = new.id),
-                           new.isbn;
+                    {row};
                 END;
             "#,
-            )?;
+            row = index_row_sql(FTS_ROW_SQL, "new")
+        ))?;
 
-            // Re-index existing books
-            self.conn.execute_batch(
-                r#"
-                INSERT INTO books_fts(rowid, title, authors, publisher, description, tags, isbn)
-                SELECT b.id, b.title,
-                       (SELECT GROUP_CONCAT(a.name, ' ') FROM authors a 
-                        JOIN books_authors ba ON a.id = ba.author_id 
-                        WHERE ba.book_id = b.id),
-                       b.publisher,
-                       b.notes,
-                       (SELECT GROUP_CONCAT(t.name, ' ') FROM tags t 
-                        JOIN books_tags bt ON t.id = bt.tag_id 
-                        WHERE bt.book_id = b.id),
-                       b.isbn
-                FROM books b;
-            "#,
-            )?;
-        }
+        // Re-index existing books
+        self.conn.execute(&fts_index_insert("FROM books b"), [])?;
+
+        Ok(())
+    }
+
+    /// Drop books_fts_cjk and its triggers, recreate them and re-index the
+    /// CJK books. The trigram tokenizer matches any substring of three or
+    /// more characters, which unicode61 cannot do for unspaced scripts.
+    pub(crate) fn rebuild_cjk_fts(&self) -> Result<()> {
+        self.conn.execute_batch(&format!(
+            r#"
+            DROP TRIGGER IF EXISTS books_cjk_ai;
+            DROP TRIGGER IF EXISTS books_cjk_ad;
+            DROP TRIGGER IF EXISTS books_cjk_au;
+            DROP TABLE IF EXISTS books_fts_cjk;
+
+            CREATE VIRTUAL TABLE books_fts_cjk USING fts5(
+                title,
+                authors,
+                tags,
+                tokenize='trigram'
+            );
+
+            CREATE TRIGGER books_cjk_ai AFTER INSERT ON books WHEN {cond} BEGIN
+                INSERT INTO books_fts_cjk(rowid, title, authors, tags) {row};
+            END;
+
+            CREATE TRIGGER books_cjk_ad AFTER DELETE ON books BEGIN
+                DELETE FROM books_fts_cjk WHERE rowid = old.id;
+            END;
+
+            CREATE TRIGGER books_cjk_au AFTER UPDATE ON books BEGIN
+                DELETE FROM books_fts_cjk WHERE rowid = old.id;
+                INSERT INTO books_fts_cjk(rowid, title, authors, tags) {row} WHERE {cond};
+            END;
+        "#,
+            cond = cjk_book_condition("new"),
+            row = index_row_sql(CJK_ROW_SQL, "new")
+        ))?;
 
+        self.conn
+            .execute(&cjk_index_insert("FROM books b WHERE 1"), [])?;
         Ok(())
     }
 
@@ -1740,6 +1976,9 @@ impl<'a> MigrationManager<'a> {
     }
 
     /// Rollback to a specific version (for development/testing)
+    ///
+    /// Only versions with a down-migration can be undone; if any version
+    /// between the current one and the target lacks one, nothing is changed.
     #[allow(dead_code)]
     pub fn rollback_to(&self, target_version: i32) -> Result<()> {
         let current_version = self.get_schema_version()?;
@@ -1749,15 +1988,216 @@ impl<'a> MigrationManager<'a> {
             return Ok(());
         }
 
+        if let Some(version) = (target_version + 1..=current_version)
+            .rev()
+            .find(|v| !matches!(v, 8 | 9))
+        {
+            return Err(Self::rollback_error(format!(
+                "no down-migration for v{}",
+                version
+            )));
+        }
+
         log::warn!(
             "[Migration] Rolling back from v{} to v{}",
             current_version,
             target_version
         );
 
-        // Note: Rollback logic would go here
-        // For now, we only support forward migrations
+        for version in (target_version + 1..=current_version).rev() {
+            let name = format!("down_v{}", version);
+            match version {
+                9 => self.run_in_savepoint(&name, |mgr| mgr.rollback_v9())?,
+                8 => self.run_in_savepoint(&name, |mgr| mgr.rollback_v8())?,
+                _ => unreachable!("checked above"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rollback_error(message: String) -> rusqlite::Error {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some(message),
+        )
+    }
+
+    /// Mark `version` as no longer applied
+    fn unrecord_migration(&self, version: i32) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM schema_migrations WHERE version = ?", [version])?;
+        self.set_schema_version(version - 1)
+    }
+
+    /// Remove columns that were added with `ALTER TABLE ... ADD COLUMN`.
+    /// SQLite before 3.35 has no DROP COLUMN, so the table is rebuilt from its
+    /// stored definition minus those columns and the rows copied across. This
+    /// keeps the table's own constraints; its indexes and triggers are dropped
+    /// with the old copy and must be recreated by the caller. Not for tables
+    /// other tables reference, since those references follow the rename.
+    fn drop_added_columns(&self, table: &str, columns: &[&str]) -> Result<()> {
+        let mut present = Vec::new();
+        for column in columns {
+            if self.column_exists(table, column)? {
+                present.push(*column);
+            }
+        }
+        if present.is_empty() {
+            return Ok(());
+        }
+
+        let mut create_sql: String = self.conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type='table' AND name=?",
+            [table],
+            |row| row.get(0),
+        )?;
+
+        for column in &present {
+            // Added columns are appended to the stored CREATE TABLE as `, name def`
+            let definition =
+                regex::Regex::new(&format!(r#"(?i),\s*"?{}"?\s[^,)]*"#, regex::escape(column)))
+                    .map_err(|e| Self::rollback_error(e.to_string()))?;
+            if definition.find(&create_sql).is_none() {
+                return Err(Self::rollback_error(format!(
+                    "cannot find definition of {}.{}",
+                    table, column
+                )));
+            }
+            create_sql = definition.replace(&create_sql, "").into_owned();
+        }
+
+        let kept = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({})", table))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|name| !columns.contains(&name.as_str()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.conn.execute_batch(&format!(
+            r#"
+            ALTER TABLE {table} RENAME TO _{table}_old;
+            {create_sql};
+            INSERT INTO {table} ({kept}) SELECT {kept} FROM _{table}_old;
+            DROP TABLE _{table}_old;
+            "#
+        ))
+    }
+
+    /// Undo v9: restore the v3 RSS column names
+    fn rollback_v9(&self) -> Result<()> {
+        log::info!("[Migration] Reverting v9: RSS schema fix");
+
+        let feeds_fixed = self.table_exists("rss_feeds")?
+            && self.column_exists("rss_feeds", "check_interval_hours")?
+            && !self.column_exists("rss_feeds", "fetch_interval_hours")?;
+        let articles_fixed = self.table_exists("rss_articles")?
+            && self.column_exists("rss_articles", "url")?
+            && !self.column_exists("rss_articles", "link")?;
+
+        if feeds_fixed != articles_fixed {
+            return Err(Self::rollback_error(
+                "RSS tables are only partly on the v9 schema".to_string(),
+            ));
+        }
+
+        // Both tables are rebuilt together: renaming rss_feeds repoints the
+        // articles' foreign key at the old copy, so the old articles must be
+        // gone before that copy is dropped or the cascade would empty them.
+        if feeds_fixed {
+            self.conn.execute_batch(
+                r#"
+                ALTER TABLE rss_articles RENAME TO _rss_articles_v9;
+                ALTER TABLE rss_feeds RENAME TO _rss_feeds_v9;
+
+                CREATE TABLE rss_feeds (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    url TEXT NOT NULL UNIQUE,
+                    title TEXT,
+                    description TEXT,
+                    last_fetched TEXT,
+                    last_success TEXT,
+                    fetch_interval_hours INTEGER DEFAULT 12,
+                    failure_count INTEGER DEFAULT 0,
+                    is_active INTEGER DEFAULT 1,
+                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );
+
+                CREATE TABLE rss_articles (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    feed_id INTEGER NOT NULL,
+                    title TEXT NOT NULL,
+                    author TEXT,
+                    link TEXT,
+                    content TEXT NOT NULL DEFAULT '',
+                    published_at TEXT,
+                    guid TEXT NOT NULL UNIQUE,
+                    is_read INTEGER DEFAULT 0,
+                    epub_book_id INTEGER,
+                    fetched_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (feed_id) REFERENCES rss_feeds(id) ON DELETE CASCADE,
+                    FOREIGN KEY (epub_book_id) REFERENCES books(id) ON DELETE SET NULL
+                );
+
+                INSERT INTO rss_feeds (id, url, title, description, last_fetched, last_success,
+                                       fetch_interval_hours, failure_count, is_active, created_at)
+                SELECT id, url, title, description, last_checked, next_check,
+                       check_interval_hours, failure_count, is_active, created_at
+                FROM _rss_feeds_v9;
+
+                INSERT INTO rss_articles (id, feed_id, title, author, link, content,
+                                          published_at, guid, is_read, epub_book_id, fetched_at)
+                SELECT id, feed_id, title, author, url, content,
+                       published, guid, is_read, epub_book_id, created_at
+                FROM _rss_articles_v9;
+
+                DROP TABLE _rss_articles_v9;
+                DROP TABLE _rss_feeds_v9;
+
+                CREATE INDEX IF NOT EXISTS idx_rss_articles_feed ON rss_articles(feed_id);
+                CREATE INDEX IF NOT EXISTS idx_rss_articles_guid ON rss_articles(guid);
+                CREATE INDEX IF NOT EXISTS idx_rss_feeds_active ON rss_feeds(is_active) WHERE is_active = 1;
+                "#,
+            )?;
+        }
+
+        self.unrecord_migration(9)?;
+        log::info!("[Migration] v9 reverted");
+        Ok(())
+    }
+
+    /// Undo v8: drop doodles and the reader enhancement preferences
+    fn rollback_v8(&self) -> Result<()> {
+        log::info!("[Migration] Reverting v8: Doodles & Reader Enhancement Preferences");
+
+        self.conn.execute_batch("DROP TABLE IF EXISTS doodles;")?;
+
+        self.drop_added_columns(
+            "user_preferences",
+            &[
+                "page_flip_enabled",
+                "page_flip_speed",
+                "paper_theme_enabled",
+                "paper_texture_intensity",
+                "doodle_enabled",
+                "adaptive_mode",
+            ],
+        )?;
+        self.conn.execute_batch(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS user_preferences_update
+            AFTER UPDATE ON user_preferences
+            BEGIN
+                UPDATE user_preferences SET updated_at = CURRENT_TIMESTAMP WHERE id = 1;
+            END;
+            "#,
+        )?;
 
+        self.unrecord_migration(8)?;
+        log::info!("[Migration] v8 reverted");
         Ok(())
     }
 
@@ -2212,7 +2652,442 @@ impl<'a> MigrationManager<'a> {
         Ok(())
     }
 
+    /// Migration v40: Low-confidence metadata matches awaiting user confirmation
+    fn migrate_to_v40(&self) -> Result<()> {
+        log::info!("[Migration] Applying v40: pending_metadata_matches");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS pending_metadata_matches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                book_id INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                query_title TEXT,
+                confidence REAL NOT NULL,
+                metadata_json TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (book_id) REFERENCES books(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_pending_metadata_book ON pending_metadata_matches(book_id);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v40_pending_metadata_matches");
+        self.record_migration(40, "pending_metadata_matches", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v41: Path + mtime index of scanned folders for incremental re-scans
+    fn migrate_to_v41(&self) -> Result<()> {
+        log::info!("[Migration] Applying v41: scanned_files");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS scanned_files (
+                file_path TEXT PRIMARY KEY,
+                folder_path TEXT NOT NULL,
+                modified_time INTEGER NOT NULL,
+                file_size INTEGER NOT NULL,
+                scanned_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_scanned_files_folder ON scanned_files(folder_path);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v41_scanned_files");
+        self.record_migration(41, "scanned_files", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v42: Add managed_library to library_settings
+    fn migrate_to_v42(&self) -> Result<()> {
+        log::info!("[Migration] Applying v42: Add managed_library to library_settings");
+
+        if !self.column_exists("library_settings", "managed_library")? {
+            self.conn.execute(
+                "ALTER TABLE library_settings ADD COLUMN managed_library INTEGER DEFAULT 0",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v42_managed_library");
+        self.record_migration(42, "managed_library", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v43: Remember whether a conversion job requested OCR
+    fn migrate_to_v43(&self) -> Result<()> {
+        log::info!("[Migration] Applying v43: Add ocr flag to conversion_jobs");
+
+        if !self.column_exists("conversion_jobs", "ocr")? {
+            self.conn.execute(
+                "ALTER TABLE conversion_jobs ADD COLUMN ocr INTEGER DEFAULT 0",
+                [],
+            )?;
+        }
 
+        let hash = Self::calculate_checksum("v43_conversion_ocr");
+        self.record_migration(43, "conversion_ocr", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v44: Nested tags. `tags.name` keeps the full path
+    /// ("Fiction/Sci-Fi") and `parent_id` links each tag to its parent.
+    fn migrate_to_v44(&self) -> Result<()> {
+        log::info!("[Migration] Applying v44: Add parent_id to tags");
+
+        if !self.column_exists("tags", "parent_id")? {
+            self.conn.execute(
+                "ALTER TABLE tags ADD COLUMN parent_id INTEGER REFERENCES tags(id) ON DELETE SET NULL",
+                [],
+            )?;
+        }
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent_id)",
+            [],
+        )?;
+
+        // Re-create the FTS triggers so they index leaf tag names
+        self.rebuild_fts()?;
+
+        let hash = Self::calculate_checksum("v44_nested_tags");
+        self.record_migration(44, "nested_tags", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v45: Auto-tagging rules applied on import
+    fn migrate_to_v45(&self) -> Result<()> {
+        log::info!("[Migration] Applying v45: tag_rules");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS tag_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                field TEXT NOT NULL,
+                operator TEXT NOT NULL,
+                value TEXT NOT NULL DEFAULT '',
+                tag_name TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v45_tag_rules");
+        self.record_migration(45, "tag_rules", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v46: User-defined theme palettes. `user_preferences.theme`
+    /// has been free text since v29, so it can name one of these directly.
+    fn migrate_to_v46(&self) -> Result<()> {
+        log::info!("[Migration] Applying v46: custom_themes");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS custom_themes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                palette_json TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v46_custom_themes");
+        self.record_migration(46, "custom_themes", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v47: Proxy, timeout and user agent for online services.
+    /// A single row, like `user_preferences`.
+    fn migrate_to_v47(&self) -> Result<()> {
+        log::info!("[Migration] Applying v47: network_settings");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS network_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                proxy_url TEXT,
+                timeout_secs INTEGER NOT NULL DEFAULT 30,
+                user_agent TEXT,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT OR IGNORE INTO network_settings (id) VALUES (1);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v47_network_settings");
+        self.record_migration(47, "network_settings", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v48: HTTP cache validators for RSS feeds, sent back as
+    /// `If-None-Match` / `If-Modified-Since` on the next fetch
+    fn migrate_to_v48(&self) -> Result<()> {
+        log::info!("[Migration] Applying v48: rss_feeds etag/last_modified");
+
+        for column in ["etag", "last_modified"] {
+            if !self.column_exists("rss_feeds", column)? {
+                self.conn.execute(
+                    &format!("ALTER TABLE rss_feeds ADD COLUMN {} TEXT", column),
+                    [],
+                )?;
+            }
+        }
+
+        let hash = Self::calculate_checksum("v48_rss_cache_validators");
+        self.record_migration(48, "rss_cache_validators", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v49: Add comicvine_api_key to user_preferences
+    fn migrate_to_v49(&self) -> Result<()> {
+        log::info!("[Migration] Applying v49: Add comicvine_api_key to user_preferences");
+
+        if !self.column_exists("user_preferences", "comicvine_api_key")? {
+            self.conn.execute(
+                "ALTER TABLE user_preferences ADD COLUMN comicvine_api_key TEXT",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v49_add_comicvine_api_key");
+        self.record_migration(49, "add_comicvine_api_key", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v50: SMTP account used to email books (e.g. to a Kindle)
+    fn migrate_to_v50(&self) -> Result<()> {
+        log::info!("[Migration] Applying v50: smtp_settings");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS smtp_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                host TEXT NOT NULL DEFAULT '',
+                port INTEGER NOT NULL DEFAULT 587,
+                security TEXT NOT NULL DEFAULT 'starttls'
+                    CHECK (security IN ('tls', 'starttls', 'none')),
+                username TEXT NOT NULL DEFAULT '',
+                password TEXT,
+                from_address TEXT NOT NULL DEFAULT '',
+                max_attachment_mb INTEGER NOT NULL DEFAULT 25,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT OR IGNORE INTO smtp_settings (id) VALUES (1);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v50_smtp_settings");
+        self.record_migration(50, "smtp_settings", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v51: OPDS catalog toggle and optional basic-auth credentials
+    fn migrate_to_v51(&self) -> Result<()> {
+        log::info!("[Migration] Applying v51: opds_settings");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS opds_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                enabled INTEGER NOT NULL DEFAULT 0,
+                username TEXT NOT NULL DEFAULT '',
+                password_hash TEXT,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT OR IGNORE INTO opds_settings (id) VALUES (1);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v51_opds_settings");
+        self.record_migration(51, "opds_settings", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v52: local HTTP API toggle, bind scope and access token
+    fn migrate_to_v52(&self) -> Result<()> {
+        log::info!("[Migration] Applying v52: api_settings");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                enabled INTEGER NOT NULL DEFAULT 0,
+                allow_lan INTEGER NOT NULL DEFAULT 0,
+                port INTEGER NOT NULL DEFAULT 8765,
+                token TEXT,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT OR IGNORE INTO api_settings (id) VALUES (1);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v52_api_settings");
+        self.record_migration(52, "api_settings", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v53: cap on conversion progress events per job per second
+    fn migrate_to_v53(&self) -> Result<()> {
+        log::info!("[Migration] Applying v53: conversion progress event rate");
+
+        if !self.column_exists("conversion_settings", "progress_events_per_second")? {
+            self.conn.execute(
+                "ALTER TABLE conversion_settings ADD COLUMN progress_events_per_second INTEGER DEFAULT 4 CHECK(progress_events_per_second BETWEEN 1 AND 60)",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v53_conversion_progress_rate");
+        self.record_migration(53, "conversion_progress_rate", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v54: per-job converter options (from conversion profiles)
+    fn migrate_to_v54(&self) -> Result<()> {
+        log::info!("[Migration] Applying v54: conversion job options");
+
+        if !self.column_exists("conversion_jobs", "options_json")? {
+            self.conn.execute(
+                "ALTER TABLE conversion_jobs ADD COLUMN options_json TEXT NOT NULL DEFAULT '{}'",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v54_conversion_job_options");
+        self.record_migration(54, "conversion_job_options", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v55: trigram-tokenized search index for CJK books
+    fn migrate_to_v55(&self) -> Result<()> {
+        log::info!("[Migration] Applying v55: CJK search index");
+
+        self.rebuild_cjk_fts()?;
+
+        let hash = Self::calculate_checksum("v55_cjk_search_index");
+        self.record_migration(55, "cjk_search_index", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v56: Add series_autofill to library_settings
+    fn migrate_to_v56(&self) -> Result<()> {
+        log::info!("[Migration] Applying v56: Add series_autofill to library_settings");
+
+        if !self.column_exists("library_settings", "series_autofill")? {
+            self.conn.execute(
+                "ALTER TABLE library_settings ADD COLUMN series_autofill INTEGER DEFAULT 0",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v56_series_autofill");
+        self.record_migration(56, "series_autofill", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v57: Books goal with a week/month/year period on reading_goals
+    fn migrate_to_v57(&self) -> Result<()> {
+        log::info!("[Migration] Applying v57: Add books goal period to reading_goals");
+
+        if !self.column_exists("reading_goals", "books_target")? {
+            self.conn.execute(
+                "ALTER TABLE reading_goals ADD COLUMN books_target INTEGER DEFAULT NULL",
+                [],
+            )?;
+            // Carry an existing yearly target over as a yearly goal
+            self.conn.execute(
+                "UPDATE reading_goals SET books_target = yearly_books_target",
+                [],
+            )?;
+        }
+        if !self.column_exists("reading_goals", "books_period")? {
+            self.conn.execute(
+                "ALTER TABLE reading_goals ADD COLUMN books_period TEXT NOT NULL DEFAULT 'year'",
+                [],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v57_books_goal_period");
+        self.record_migration(57, "books_goal_period", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v58: Allow DjVu files in book_formats. SQLite can't alter a
+    /// CHECK constraint, so the table is rebuilt with the same rows.
+    fn migrate_to_v58(&self) -> Result<()> {
+        log::info!("[Migration] Applying v58: Allow djvu in book_formats");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE book_formats_v58 (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                book_id INTEGER NOT NULL,
+                format TEXT NOT NULL CHECK(format IN (
+                    'epub', 'pdf', 'mobi', 'azw3', 'fb2',
+                    'docx', 'txt', 'html', 'cbz', 'cbr', 'djvu'
+                )),
+                file_path TEXT NOT NULL,
+                file_size INTEGER NOT NULL,
+                file_hash TEXT NOT NULL UNIQUE,
+                page_count INTEGER,
+                word_count INTEGER,
+                is_primary INTEGER DEFAULT 0,
+                added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (book_id) REFERENCES books(id) ON DELETE CASCADE
+            );
+
+            INSERT INTO book_formats_v58 (id, book_id, format, file_path, file_size, file_hash,
+                                          page_count, word_count, is_primary, added_at)
+            SELECT id, book_id, format, file_path, file_size, file_hash,
+                   page_count, word_count, is_primary, added_at
+            FROM book_formats;
+
+            DROP TABLE book_formats;
+            ALTER TABLE book_formats_v58 RENAME TO book_formats;
+
+            CREATE INDEX IF NOT EXISTS idx_book_formats_book_id ON book_formats(book_id);
+            CREATE INDEX IF NOT EXISTS idx_book_formats_format ON book_formats(format);
+            CREATE INDEX IF NOT EXISTS idx_book_formats_hash ON book_formats(file_hash);
+            CREATE INDEX IF NOT EXISTS idx_book_formats_primary ON book_formats(book_id, is_primary);
+            "#,
+        )?;
+
+        let hash = Self::calculate_checksum("v58_book_formats_djvu");
+        self.record_migration(58, "book_formats_djvu", &hash)?;
+        Ok(())
+    }
+
+    /// Migration v59: Fill in missing author sort names. Duplicate detection
+    /// compares normalized names, and older imports left sort_name empty.
+    fn migrate_to_v59(&self) -> Result<()> {
+        log::info!("[Migration] Applying v59: Backfill author sort names");
+
+        let authors = self
+            .conn
+            .prepare(
+                "SELECT id, name FROM authors WHERE sort_name IS NULL OR trim(sort_name) = ''",
+            )?
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        for (id, name) in authors {
+            self.conn.execute(
+                "UPDATE authors SET sort_name = ?1 WHERE id = ?2",
+                rusqlite::params![crate::services::author_service::normalize_name(&name), id],
+            )?;
+        }
+
+        let hash = Self::calculate_checksum("v59_author_sort_names");
+        self.record_migration(59, "author_sort_names", &hash)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -2235,6 +3110,157 @@ mod tests {
         assert!(version >= 1);
     }
 
+    #[test]
+    fn test_v59_backfills_missing_author_sort_names() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE authors (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 name TEXT NOT NULL UNIQUE,
+                 sort_name TEXT
+             );
+             INSERT INTO authors (name, sort_name) VALUES
+                 ('J.R.R. Tolkien', NULL),
+                 ('Ursula K. Le Guin', ''),
+                 ('Jane Austen', 'Kept, As Is');",
+        )
+        .unwrap();
+
+        let migrator = MigrationManager::new(&conn);
+        migrator.ensure_migrations_table().unwrap();
+        migrator
+            .run_in_savepoint("v59", |m| m.migrate_to_v59())
+            .unwrap();
+
+        let sort_names: Vec<String> = conn
+            .prepare("SELECT sort_name FROM authors ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            sort_names,
+            vec!["Tolkien, J. R. R.", "Le Guin, Ursula K.", "Kept, As Is"]
+        );
+    }
+
+    #[test]
+    fn test_rollback_v9_then_v8() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Just the `books` columns the early migrations read
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             CREATE TABLE books (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 title TEXT NOT NULL,
+                 file_path TEXT NOT NULL UNIQUE,
+                 file_format TEXT NOT NULL,
+                 file_size INTEGER,
+                 file_hash TEXT,
+                 page_count INTEGER,
+                 word_count INTEGER,
+                 publisher TEXT,
+                 isbn TEXT,
+                 notes TEXT,
+                 added_date TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+             );",
+        )
+        .unwrap();
+
+        let migrator = MigrationManager::new(&conn);
+        migrator.ensure_migrations_table().unwrap();
+        migrator
+            .run_in_savepoint("v2", |m| m.migrate_to_v2())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v3", |m| m.migrate_to_v3())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v4", |m| m.migrate_to_v4())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v5", |m| m.migrate_to_v5())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v6", |m| m.migrate_to_v6())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v7", |m| m.migrate_to_v7())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v8", |m| m.migrate_to_v8())
+            .unwrap();
+        migrator
+            .run_in_savepoint("v9", |m| m.migrate_to_v9())
+            .unwrap();
+        assert_eq!(migrator.get_schema_version().unwrap(), 9);
+
+        conn.execute_batch(
+            "INSERT INTO rss_feeds (url, title, check_interval_hours)
+                 VALUES ('https://example.com/feed', 'Feed', 6);
+             INSERT INTO rss_articles (feed_id, title, url, guid)
+                 VALUES (1, 'Post', 'https://example.com/post', 'g1');
+             UPDATE user_preferences SET ui_scale = 1.25, page_flip_speed = 250 WHERE id = 1;",
+        )
+        .unwrap();
+
+        migrator.rollback_to(8).unwrap();
+        assert_eq!(migrator.get_schema_version().unwrap(), 8);
+        assert!(migrator
+            .column_exists("rss_feeds", "fetch_interval_hours")
+            .unwrap());
+        assert!(!migrator
+            .column_exists("rss_feeds", "check_interval_hours")
+            .unwrap());
+        assert!(migrator.column_exists("rss_articles", "link").unwrap());
+        assert!(!migrator.column_exists("rss_articles", "summary").unwrap());
+        let (interval, link): (i64, String) = conn
+            .query_row(
+                "SELECT f.fetch_interval_hours, a.link
+                 FROM rss_articles a JOIN rss_feeds f ON f.id = a.feed_id",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(interval, 6);
+        assert_eq!(link, "https://example.com/post");
+        let v9_rows: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM schema_migrations WHERE version = 9",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(v9_rows, 0);
+
+        migrator.rollback_to(7).unwrap();
+        assert_eq!(migrator.get_schema_version().unwrap(), 7);
+        assert!(!migrator.table_exists("doodles").unwrap());
+        assert!(!migrator
+            .column_exists("user_preferences", "page_flip_speed")
+            .unwrap());
+        assert!(!migrator
+            .column_exists("user_preferences", "adaptive_mode")
+            .unwrap());
+        let ui_scale: f64 = conn
+            .query_row(
+                "SELECT ui_scale FROM user_preferences WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(ui_scale, 1.25);
+        // The rebuilt table keeps its constraints
+        assert!(conn
+            .execute("INSERT INTO user_preferences (id) VALUES (2)", [])
+            .is_err());
+
+        // Older versions have no down-migration; nothing changes
+        assert!(migrator.rollback_to(5).is_err());
+        assert_eq!(migrator.get_schema_version().unwrap(), 7);
+    }
+
     #[test]
     fn test_checksum_calculation() {
         let sql = "CREATE TABLE test (id INTEGER);";
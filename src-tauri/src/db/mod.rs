@@ -1,10 +1,40 @@
-use crate::error::Result;
+use crate::error::{Result, ShioriError};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use std::path::Path;
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 pub mod migrations;
 
+/// Result of [`Database::integrity_check`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseHealthReport {
+    /// Messages from `PRAGMA integrity_check`, empty when it reports "ok"
+    pub integrity_errors: Vec<String>,
+    /// One entry per row reported by `PRAGMA foreign_key_check`
+    pub foreign_key_violations: Vec<String>,
+    /// Whether books_fts exists with the expected columns
+    pub search_index_ok: bool,
+}
+
+impl DatabaseHealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.search_index_ok
+    }
+}
+
+/// Result of [`Database::compact`]. Sizes cover the main file and its WAL.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub freed_bytes: u64,
+}
+
 #[derive(Clone)]
 pub struct Database {
     pool: Pool<SqliteConnectionManager>,
@@ -49,15 +79,98 @@ impl Database {
         Ok(())
     }
 
+    /// The `performance_mode` preference, "standard" when unset
+    pub fn performance_mode(&self) -> String {
+        self.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT performance_mode FROM user_preferences WHERE id = 1",
+                    [],
+                    |row| row.get::<_, String>(0),
+                )
+                .ok()
+            })
+            .unwrap_or_else(|| "standard".to_string())
+    }
+
+    /// Pages the manga reader may preload ahead (`manga_preload_count`)
+    pub fn manga_preload_count(&self) -> usize {
+        self.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT manga_preload_count FROM user_preferences WHERE id = 1",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .ok()
+            })
+            .map_or(3, |count| count.clamp(0, 10) as usize)
+    }
+
+    /// Comic Vine API key, if the user has entered one
+    pub fn comicvine_api_key(&self) -> Option<String> {
+        self.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT comicvine_api_key FROM user_preferences WHERE id = 1",
+                    [],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .ok()
+            })
+            .flatten()
+            .filter(|key| !key.trim().is_empty())
+    }
+
+    /// Proxy, timeout and user agent for online services; defaults if unset
+    pub fn network_settings(&self) -> crate::utils::http::NetworkSettings {
+        self.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT proxy_url, timeout_secs, user_agent FROM network_settings WHERE id = 1",
+                    [],
+                    |row| {
+                        Ok(crate::utils::http::NetworkSettings {
+                            proxy_url: row.get(0)?,
+                            timeout_secs: row.get::<_, i64>(1)?.max(0) as u64,
+                            user_agent: row.get(2)?,
+                        })
+                    },
+                )
+                .ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save_network_settings(
+        &self,
+        settings: &crate::utils::http::NetworkSettings,
+    ) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT INTO network_settings (id, proxy_url, timeout_secs, user_agent, updated_at)
+             VALUES (1, ?1, ?2, ?3, CURRENT_TIMESTAMP)
+             ON CONFLICT(id) DO UPDATE SET
+                proxy_url = excluded.proxy_url,
+                timeout_secs = excluded.timeout_secs,
+                user_agent = excluded.user_agent,
+                updated_at = excluded.updated_at",
+            rusqlite::params![
+                settings.proxy_url,
+                settings.timeout_secs as i64,
+                settings.user_agent
+            ],
+        )?;
+        Ok(())
+    }
+
     fn apply_performance_pragmas(&self) -> Result<()> {
         let conn = self.get_connection()?;
-        let perf_mode: String = conn
-            .query_row(
-                "SELECT performance_mode FROM user_preferences WHERE id = 1",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or_else(|_| "standard".to_string());
+        let perf_mode = self.performance_mode();
 
         match perf_mode.as_str() {
             "large_library" => {
@@ -361,10 +474,188 @@ impl Database {
         Ok(())
     }
 
+    /// Run SQLite's integrity and foreign key checks and verify the search
+    /// index schema. Problems are reported, not repaired.
+    pub fn integrity_check(&self) -> Result<DatabaseHealthReport> {
+        let conn = self.get_connection()?;
+
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter(|msg| !matches!(msg.as_deref(), Ok("ok")))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+        let foreign_key_violations = stmt
+            .query_map([], |row| {
+                let table: String = row.get(0)?;
+                let rowid: Option<i64> = row.get(1)?;
+                let parent: String = row.get(2)?;
+                Ok(match rowid {
+                    Some(rowid) => format!("{} row {} references missing {}", table, rowid, parent),
+                    None => format!("{} references missing {}", table, parent),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let manager = migrations::MigrationManager::new(&conn);
+        let search_index_ok = manager.fts_schema_ok()? && manager.is_fts_consistent()?;
+
+        Ok(DatabaseHealthReport {
+            integrity_errors,
+            foreign_key_violations,
+            search_index_ok,
+        })
+    }
+
+    /// Drop and repopulate books_fts and books_fts_cjk from the books table
+    pub fn rebuild_search_index(&self) -> Result<()> {
+        let conn = self.get_connection()?;
+        let tx = conn.unchecked_transaction()?;
+        let manager = migrations::MigrationManager::new(&tx);
+        manager.rebuild_fts()?;
+        manager.rebuild_cjk_fts()?;
+        tx.commit()?;
+        log::info!("[Database] Rebuilt search index");
+        Ok(())
+    }
+
+    /// Copy the WAL into the main file and truncate it to zero bytes.
+    ///
+    /// Waits up to the busy timeout for readers on other pooled connections.
+    /// Returns false if they were still active and the WAL could only be
+    /// partially checkpointed; that is harmless and the next checkpoint
+    /// picks up the rest.
+    pub fn checkpoint(&self) -> Result<bool> {
+        let conn = self.get_connection()?;
+        let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        Ok(busy == 0)
+    }
+
+    /// Rebuild the database file so pages freed by deletes are released.
+    ///
+    /// VACUUM cannot run inside a transaction, so it gets its own pooled
+    /// connection in autocommit mode. It holds the write lock throughout:
+    /// writers on other connections wait on the busy timeout, and if one
+    /// holds the lock for longer VACUUM fails with SQLITE_BUSY without
+    /// changing anything. The freed space only leaves the disk once the WAL
+    /// is checkpointed, see [`Database::compact`].
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.get_connection()?;
+        if !conn.is_autocommit() {
+            return Err(ShioriError::Other(
+                "Cannot vacuum while a transaction is open".to_string(),
+            ));
+        }
+        conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+
+    /// Vacuum, then checkpoint so the file actually shrinks
+    pub fn compact(&self) -> Result<CompactReport> {
+        let size_before = self.disk_size()?;
+        self.vacuum()?;
+        if !self.checkpoint()? {
+            log::warn!("[Database] WAL checkpoint incomplete, database is busy");
+        }
+        let size_after = self.disk_size()?;
+        log::info!(
+            "[Database] Compacted from {} to {} bytes",
+            size_before,
+            size_after
+        );
+        Ok(CompactReport {
+            size_before,
+            size_after,
+            freed_bytes: size_before.saturating_sub(size_after),
+        })
+    }
+
+    /// Bytes on disk for the database file plus its `-wal` file
+    fn disk_size(&self) -> Result<u64> {
+        let conn = self.get_connection()?;
+        let path = PathBuf::from(conn.path().unwrap_or_default());
+        let mut wal = path.clone().into_os_string();
+        wal.push("-wal");
+        let file_size = |p: &Path| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+        Ok(file_size(&path) + file_size(Path::new(&wal)))
+    }
+
+    /// Copy the live database to `path` with SQLite's online backup API.
+    /// Safe while other connections read and write; the copy is a consistent
+    /// snapshot including anything still in the WAL.
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// Replace the live database with the one at `path`, then migrate it to
+    /// the current schema. The source is validated first and nothing is
+    /// touched if it isn't a Shiori library this build can open.
+    pub fn restore_from(&self, path: &Path) -> Result<()> {
+        Self::check_restorable(path)?;
+
+        let mut conn = self.get_connection()?;
+        conn.restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        drop(conn);
+
+        // Backups from older releases are brought up to date
+        self.run_migrations()?;
+        log::info!("[Database] Restored library from {}", path.display());
+        Ok(())
+    }
+
+    fn check_restorable(path: &Path) -> Result<()> {
+        let invalid = |e: rusqlite::Error| {
+            ShioriError::Validation(format!(
+                "{} is not a Shiori library database: {}",
+                path.display(),
+                e
+            ))
+        };
+        let src =
+            Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(invalid)?;
+
+        let has_migrations: bool = src
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(invalid)?;
+        if !has_migrations {
+            return Err(ShioriError::Validation(format!(
+                "{} is not a Shiori library database",
+                path.display()
+            )));
+        }
+
+        let user_version: i32 = src
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(invalid)?;
+        let recorded: i32 = src
+            .query_row(
+                "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(invalid)?;
+        let version = user_version.max(recorded);
+        if version > migrations::LATEST_VERSION {
+            return Err(ShioriError::Validation(format!(
+                "Backup uses schema v{} but this version of Shiori supports up to v{}; update Shiori before restoring it",
+                version,
+                migrations::LATEST_VERSION
+            )));
+        }
+        Ok(())
+    }
+
     pub fn get_connection(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>> {
         self.pool
             .get()
-            .map_err(|e| crate::error::ShioriError::Other(e.to_string()))
+            .map_err(|e| ShioriError::Other(e.to_string()))
     }
 }
 
@@ -405,4 +696,232 @@ mod tests {
         let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
         assert_eq!(journal_mode.to_lowercase(), "wal");
     }
+
+    #[test]
+    fn test_integrity_check_detects_malformed_search_index() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("health.db")).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Dune', '/tmp/dune.epub', 'epub', 'u-1')",
+                [],
+            )
+            .unwrap();
+        }
+        assert!(db.integrity_check().unwrap().is_healthy());
+
+        // An older three-column index, as left by a partially applied upgrade
+        db.get_connection()
+            .unwrap()
+            .execute_batch(
+                "DROP TRIGGER books_ai; DROP TRIGGER books_ad; DROP TRIGGER books_au;
+                 DROP TABLE books_fts;
+                 CREATE VIRTUAL TABLE books_fts USING fts5(title, authors, tags);",
+            )
+            .unwrap();
+        let report = db.integrity_check().unwrap();
+        assert!(!report.search_index_ok);
+        assert!(!report.is_healthy());
+        assert!(report.integrity_errors.is_empty());
+
+        db.rebuild_search_index().unwrap();
+        assert!(db.integrity_check().unwrap().is_healthy());
+        let conn = db.get_connection().unwrap();
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH 'dune'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_integrity_check_detects_missing_search_index() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("health.db")).unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute_batch("DROP TABLE books_fts;")
+            .unwrap();
+        assert!(!db.integrity_check().unwrap().search_index_ok);
+    }
+
+    #[test]
+    fn test_out_of_sync_search_rows_are_repaired_individually() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fts.db");
+        let db = Database::new(&path).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute_batch(
+                "INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Dune', '/tmp/dune.epub', 'epub', 'u-1');
+                 INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Emma', '/tmp/emma.epub', 'epub', 'u-2');
+                 DELETE FROM books_fts WHERE rowid = (SELECT id FROM books WHERE uuid = 'u-2');
+                 UPDATE books_fts SET description = 'marker'
+                  WHERE rowid = (SELECT id FROM books WHERE uuid = 'u-1');",
+            )
+            .unwrap();
+        }
+        assert!(!db.integrity_check().unwrap().search_index_ok);
+        drop(db);
+
+        // Reopening runs ensure_fts_schema, which should only add Emma back
+        let db = Database::new(&path).unwrap();
+        assert!(db.integrity_check().unwrap().is_healthy());
+        let conn = db.get_connection().unwrap();
+        let count = |query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH ?1",
+                [query],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(count("emma"), 1);
+        // A full rebuild would have reset Dune's row from `books`
+        assert_eq!(count("description:marker"), 1);
+    }
+
+    #[test]
+    fn test_search_rows_are_compared_by_rowid() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fts.db");
+        let db = Database::new(&path).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute_batch(
+                "INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Dune', '/tmp/dune.epub', 'epub', 'u-1');
+                 INSERT INTO books (title, file_path, file_format, uuid) VALUES ('ノルウェイの森', '/tmp/mori.epub', 'epub', 'u-2');
+                 DELETE FROM books_fts WHERE rowid = (SELECT id FROM books WHERE uuid = 'u-1');
+                 INSERT INTO books_fts(rowid, title) VALUES (9999, 'orphan');",
+            )
+            .unwrap();
+        }
+        // Same row count, different rows
+        assert!(!db.integrity_check().unwrap().search_index_ok);
+        drop(db);
+        let db = Database::new(&path).unwrap();
+        assert!(db.integrity_check().unwrap().is_healthy());
+
+        // A missing CJK row is found and restored too
+        db.get_connection()
+            .unwrap()
+            .execute("DELETE FROM books_fts_cjk", [])
+            .unwrap();
+        assert!(!db.integrity_check().unwrap().search_index_ok);
+        drop(db);
+        let db = Database::new(&path).unwrap();
+        assert!(db.integrity_check().unwrap().is_healthy());
+        let conn = db.get_connection().unwrap();
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM books_fts_cjk WHERE books_fts_cjk MATCH 'ェイの'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_compact_releases_deleted_space() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("compact.db")).unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute_batch(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+                 INSERT INTO books (title, file_path, file_format, uuid, notes)
+                 SELECT 'Book ' || i, '/tmp/book' || i, 'cbz', 'uuid-' || i, hex(randomblob(1000))
+                 FROM n;
+                 DELETE FROM books;",
+            )
+            .unwrap();
+        assert!(db.checkpoint().unwrap());
+
+        let report = db.compact().unwrap();
+        assert!(report.size_after < report.size_before);
+        assert_eq!(report.freed_bytes, report.size_before - report.size_after);
+        // Thousands of 2 KB rows should account for megabytes
+        assert!(report.freed_bytes > 1_000_000);
+        let wal = dir.path().join("compact.db-wal");
+        assert_eq!(std::fs::metadata(wal).map(|m| m.len()).unwrap_or(0), 0);
+    }
+
+    #[test]
+    fn test_backup_restore_round_trip() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("live.db")).unwrap();
+        let insert = "INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Dune', '/tmp/dune.epub', 'epub', 'u-1')";
+        db.get_connection().unwrap().execute(insert, []).unwrap();
+
+        let backup = dir.path().join("backup.db");
+        db.backup_to(&backup).unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute("DELETE FROM books", [])
+            .unwrap();
+
+        db.restore_from(&backup).unwrap();
+        let conn = db.get_connection().unwrap();
+        let title: String = conn
+            .query_row("SELECT title FROM books", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(title, "Dune");
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH 'dune'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_restore_rejects_foreign_and_newer_databases() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("live.db")).unwrap();
+
+        let foreign = dir.path().join("foreign.db");
+        Connection::open(&foreign)
+            .unwrap()
+            .execute_batch("CREATE TABLE notes (body TEXT);")
+            .unwrap();
+        assert!(matches!(
+            db.restore_from(&foreign),
+            Err(ShioriError::Validation(_))
+        ));
+
+        let newer = dir.path().join("newer.db");
+        db.backup_to(&newer).unwrap();
+        Connection::open(&newer)
+            .unwrap()
+            .pragma_update(None, "user_version", migrations::LATEST_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            db.restore_from(&newer),
+            Err(ShioriError::Validation(_))
+        ));
+
+        // The live database was left alone
+        assert!(db.integrity_check().unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_latest_version_matches_migrations() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("live.db")).unwrap();
+        let recorded: i32 = db
+            .get_connection()
+            .unwrap()
+            .query_row("SELECT MAX(version) FROM schema_migrations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(recorded, migrations::LATEST_VERSION);
+    }
 }
@@ -52,6 +52,12 @@ pub enum ShioriError {
     #[error("Corrupted PDF file: {details}")]
     CorruptedPdf { path: String, details: String },
 
+    #[error("PDF is password protected: {path}")]
+    PasswordRequired { path: String },
+
+    #[error("Incorrect PDF password: {path}")]
+    IncorrectPassword { path: String },
+
     #[error("Unsupported book format: {format}")]
     UnsupportedFormat { format: String, path: String },
 
@@ -108,6 +114,12 @@ impl ShioriError {
             Self::CorruptedPdf { path: _, details: _ } => {
                 "This PDF file appears to be corrupted or incomplete.".to_string()
             }
+            Self::PasswordRequired { path: _ } => {
+                "This PDF is password protected. Enter the password to open it.".to_string()
+            }
+            Self::IncorrectPassword { path: _ } => {
+                "The password is incorrect. Please try again.".to_string()
+            }
             Self::UnsupportedFormat { format, path: _ } => {
                 format!("The '{}' format is not currently supported.", format)
             }
@@ -218,6 +230,8 @@ impl ShioriError {
             Self::DuplicateBook(_) => "duplicate",
             Self::InvalidOperation(_) => "invalid_operation",
             Self::FileNotFound { .. } => "file_not_found",
+            Self::PasswordRequired { .. } => "password_required",
+            Self::IncorrectPassword { .. } => "incorrect_password",
             Self::FilePermissionDenied { .. } => "permission",
             Self::CorruptedEpub { .. }
             | Self::EpubParseFailed { .. }
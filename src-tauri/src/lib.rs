@@ -18,6 +18,7 @@ use std::sync::Arc;
 use tauri::Manager;
 
 use services::{
+    auto_scan_scheduler::AutoScanScheduler,
     book_metadata_service::BookMetadataService,
     conversion_engine::ConversionEngine,
     cover_service::CoverService,
@@ -25,6 +26,7 @@ use services::{
     manga_metadata_service::MangaMetadataService,
     online::{
         anilist::AniListProvider,
+        comicvine::ComicVineProvider,
         openlibrary::OpenLibraryProvider,
         worker::{MetadataJob, MetadataWorker},
     },
@@ -45,6 +47,10 @@ pub struct AppState {
 
 pub struct MetadataState {
     pub sender: tokio::sync::mpsc::Sender<MetadataJob>,
+    /// Shared with the worker; see `MetadataWorker::cancelled`
+    pub cancelled: Arc<dashmap::DashSet<i64>>,
+    /// Shared with the worker; see `MetadataWorker::queued`
+    pub queued: Arc<dashmap::DashMap<i64, usize>>,
 }
 
 pub struct ActiveDownloads {
@@ -253,6 +259,11 @@ pub fn run() {
             let db_path = app_dir.join("library.db");
             let database = db::Database::new(&db_path)?;
 
+            // Online services share one HTTP client built from network_settings
+            if let Err(e) = utils::http::apply(&database.network_settings()) {
+                log::warn!("Ignoring invalid network settings: {}", e);
+            }
+
             #[allow(unused_assignments, unused_variables)]
             let mut is_transparent = false;
             let mut is_first_time = true;
@@ -493,18 +504,28 @@ pub fn run() {
                 }
             });
 
-            // Initialize rendering service with 100MB cache
-            app.manage(commands::rendering::RenderingState::new(100));
+            // Initialize rendering service; cache size follows the performance mode
+            app.manage(commands::rendering::RenderingState::new(
+                services::cache::cache_size_for_performance_mode(&database.performance_mode()),
+            ));
 
-            // Initialize manga reader service
-            app.manage(commands::manga::MangaState::new());
+            // Initialize manga reader service; its page cache budget follows the performance mode
+            app.manage(commands::manga::MangaState::new(
+                services::manga_service::page_cache_budget_for_performance_mode(
+                    &database.performance_mode(),
+                ),
+            ));
 
             // Initialize v2.0 services
             let storage_path = app_dir.join("storage");
             std::fs::create_dir_all(&storage_path)?;
 
-            let mut conversion_engine = ConversionEngine::new(4, app.handle().clone());
+            let worker_count = ConversionEngine::stored_worker_count(&database);
+            let mut conversion_engine = ConversionEngine::new(worker_count, app.handle().clone());
             conversion_engine.set_database(database.clone());
+            conversion_engine
+                .set_progress_rate(ConversionEngine::stored_progress_rate(&database))
+                .ok();
             let conversion_engine = Arc::new(conversion_engine);
             if let Ok(conn) = database.get_connection() {
                 conversion_engine.restore_from_db(&conn);
@@ -514,7 +535,7 @@ pub fn run() {
 
             // Cover service
             let cover_service = Arc::new(CoverService::new(storage_path.clone())?);
-            app.manage(cover_service);
+            app.manage(Arc::clone(&cover_service));
 
             // RSS service
             let rss_service = Arc::new(RssService::new(database.clone(), storage_path.clone())?);
@@ -542,19 +563,26 @@ pub fn run() {
             });
 
             // Share service
-            let share_service = Arc::new(tokio::sync::Mutex::new(ShareService::new(
-                database.clone(),
-                storage_path.clone(),
-                Some(8080),
-            )));
+            let mut share_service =
+                ShareService::new(database.clone(), storage_path.clone(), Some(8080));
+            share_service.set_cover_service(cover_service);
+            let share_service = Arc::new(tokio::sync::Mutex::new(share_service));
+            let api_share_service = share_service.clone();
+            tauri::async_runtime::spawn(async move {
+                // No-op unless the local HTTP API is enabled in settings
+                if let Err(e) = api_share_service.lock().await.start_api_server().await {
+                    log::error!("Local API server failed to start: {}", e);
+                }
+            });
             app.manage(share_service);
 
             // Metadata enrichment services (v2.1)
             let manga_metadata_service = Arc::new(MangaMetadataService::new()?);
             app.manage(manga_metadata_service);
 
-            let book_metadata_service = Arc::new(BookMetadataService::new()?);
-            app.manage(book_metadata_service);
+            let mut book_metadata_service = BookMetadataService::new()?;
+            book_metadata_service.set_database(database.clone());
+            app.manage(Arc::new(book_metadata_service));
 
             // Sync service
             let sync_service = Arc::new(tokio::sync::Mutex::new(SyncService::new(
@@ -566,6 +594,9 @@ pub fn run() {
             // Online Metadata Enrichment Worker
             let (mut metadata_worker, metadata_rx) = MetadataWorker::new(database.clone());
 
+            // The worker tries providers that support an item in this order, so
+            // with an API key comics go to Comic Vine first and AniList second
+            metadata_worker.add_provider(Arc::new(ComicVineProvider::new(database.clone())));
             if let Ok(anilist) = AniListProvider::new() {
                 metadata_worker.add_provider(Arc::new(anilist));
             }
@@ -573,12 +604,17 @@ pub fn run() {
                 metadata_worker.add_provider(Arc::new(ol));
             }
 
+            // Prune expired metadata_cache rows now and periodically
+            services::online::cache::spawn_cleanup_task(database.clone());
+
             let metadata_job_sender = metadata_worker.sender.clone();
             metadata_worker.set_app_handle(app.handle().clone());
             metadata_worker.start(metadata_rx);
 
             app.manage(MetadataState {
                 sender: metadata_job_sender,
+                cancelled: metadata_worker.cancelled.clone(),
+                queued: metadata_worker.queued.clone(),
             });
 
             let folder_watch_service =
@@ -587,6 +623,24 @@ pub fn run() {
                 folder_watch_service,
             ));
 
+            // Keeps library_settings.auto_scan_folders imported while auto-scan is on
+            let scan_handle = app.handle().clone();
+            let auto_scan = Arc::new(AutoScanScheduler::new(
+                database.clone(),
+                covers_dir.clone(),
+                move |report| {
+                    use tauri::Emitter;
+                    let _ = scan_handle.emit("library:scan_complete", report);
+                    if report.added > 0 || report.updated > 0 {
+                        let _ = scan_handle.emit("library-updated", ());
+                    }
+                },
+            ));
+            if let Err(e) = auto_scan.start() {
+                log::error!("Auto-scan failed to start: {}", e);
+            }
+            app.manage(auto_scan);
+
             #[cfg(not(any(target_os = "android", target_os = "ios")))]
             {
                 let piper_service = Arc::new(tokio::sync::Mutex::new(services::piper_service::PiperService::new(app.handle().clone())));
@@ -595,7 +649,7 @@ pub fn run() {
 
             log::info!("Shiori v2.0 initialized with database at {:?}", db_path);
             log::info!("Storage path: {:?}", storage_path);
-            log::info!("Conversion engine: 4 workers");
+            log::info!("Conversion engine: {} workers", worker_count);
             log::info!("RSS scheduler: enabled (daily EPUB at 6 AM)");
             log::info!("Share server: ready (port 8080)");
             log::info!("Metadata APIs: AniList (manga) + Open Library (books)");
@@ -616,8 +670,18 @@ pub fn run() {
             }
         })
         .invoke_handler(crate::generate_shiori_handlers!())
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Put running conversions back in the queue so they resume on next start
+                if let Some(engine) = app.try_state::<Arc<ConversionEngine>>() {
+                    tauri::async_runtime::block_on(
+                        engine.shutdown(std::time::Duration::from_secs(3)),
+                    );
+                }
+            }
+        });
 }
 
 /// Security Fix: SSRF Prevention
@@ -125,6 +125,18 @@ pub struct Tag {
     pub color: Option<String>,
 }
 
+/// A tag and its sub-tags. `name` is the full path ("Fiction/Sci-Fi"),
+/// `label` the last segment ("Sci-Fi").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagNode {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    pub children: Vec<TagNode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub books: Vec<Book>,
@@ -132,6 +144,16 @@ pub struct SearchResult {
     pub query: String,
 }
 
+/// A single relevance-ranked full-text hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedSearchResult {
+    pub book: Book,
+    /// FTS5 `bm25()` score; lower is more relevant
+    pub rank: f64,
+    /// Matched terms wrapped in `<mark>`, from the description or title
+    pub snippet: String,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub query: Option<String>,
@@ -156,11 +178,54 @@ pub struct SearchQuery {
     pub offset: Option<i64>,
 }
 
+/// Books that look like copies of each other, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub reason: String,
+    pub book_ids: Vec<i64>,
+}
+
+/// An author row and how many books credit it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorBookCount {
+    pub id: i64,
+    pub name: String,
+    pub book_count: i64,
+}
+
+/// Author rows that are spellings of the same person
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateAuthorGroup {
+    /// Canonical "Family, Given" name
+    pub sort_name: String,
+    pub authors: Vec<AuthorBookCount>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
     pub success: Vec<String>,
     pub failed: Vec<(String, String)>, // (path, error_message)
     pub duplicates: Vec<String>,
+    /// Files attached to an existing book as an additional format
+    #[serde(default)]
+    pub merged: Vec<String>,
+    /// Files that took the place of an existing book's file and metadata
+    #[serde(default)]
+    pub replaced: Vec<String>,
+}
+
+/// Outcome of an incremental folder re-scan
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RescanResult {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Previously scanned files no longer on disk (reported, not removed)
+    pub missing: Vec<String>,
+    pub failed: Vec<(String, String)>, // (path, error_message)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -177,6 +242,35 @@ pub struct Metadata {
     pub series_index: Option<f64>,
 }
 
+/// A series with its books in reading order, or a single standalone book
+/// when `series` is `None`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesGroup {
+    pub series: Option<String>,
+    pub books: Vec<Book>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesSummary {
+    pub name: String,
+    pub book_count: i64,
+    pub cover_paths: Vec<String>,
+}
+
+/// A book together with its `reading_progress` row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookWithProgress {
+    pub book: Book,
+    pub progress_percent: f64,
+    pub current_location: String,
+    pub current_page: Option<i32>,
+    pub total_pages: Option<i32>,
+    pub last_read: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReadingProgress {
@@ -218,6 +312,14 @@ pub struct AnnotationCategory {
     pub created_at: String,
 }
 
+/// Number of annotations sharing a (lowercased) highlight color
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorCount {
+    pub color: String,
+    pub count: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnnotationSearchResult {
     pub annotation: Annotation,
@@ -281,6 +383,17 @@ pub struct SmartRule {
     pub match_type: String, // "all" or "any"
 }
 
+/// Auto-tagging rule: books matching the condition get `tag_name` added
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRule {
+    pub id: Option<i64>,
+    pub field: String, // "publisher", "language", "series", "pubdate_year", "file_format", "always"
+    pub operator: String, // "equals", "contains", "is_not_empty", "between"
+    pub value: String, // "1990-1999" for "between"
+    pub tag_name: String,
+    pub enabled: bool,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionWithBooks {
@@ -357,6 +470,61 @@ pub struct BookReadingStats {
     pub average_session_minutes: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorReadingCount {
+    pub name: String,
+    pub books_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyReadingStats {
+    /// `YYYY-MM`
+    pub month: String,
+    pub books_finished: i64,
+    pub pages_read: i64,
+    pub total_seconds: i64,
+    pub annotations_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingStats {
+    pub from_date: String,
+    pub to_date: String,
+    pub books_finished: i64,
+    pub pages_read: i64,
+    pub total_seconds: i64,
+    pub annotations_count: i64,
+    pub most_read_author: Option<AuthorReadingCount>,
+    pub reading_days: i32,
+    pub longest_streak: i32,
+    pub months: Vec<MonthlyReadingStats>,
+}
+
+/// Whether finished books are keeping up with a goal's even pace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalPace {
+    Ahead,
+    OnTrack,
+    Behind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProgress {
+    /// `week`, `month` or `year`
+    pub period: String,
+    pub target: i32,
+    /// First and last day of the current period, `YYYY-MM-DD`
+    pub period_start: String,
+    pub period_end: String,
+    pub books_finished: i64,
+    /// `books_finished / target`, not capped at 1.0
+    pub completion_ratio: f64,
+    /// Books an even pace would have finished by the end of today
+    pub expected_by_now: f64,
+    pub pace: GoalPace,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MangaSeries {
     pub id: Option<i64>,
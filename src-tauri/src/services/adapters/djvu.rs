@@ -0,0 +1,154 @@
+/// DjVu Format Adapter
+///
+/// Page count and geometry come from parsing the IFF container directly.
+/// Title/author and the first-page cover need djvulibre (`djvused`, `ddjvu`);
+/// without it those fall back to the filename and a generated cover.
+use async_trait::async_trait;
+use std::path::Path;
+use tokio::fs;
+
+use crate::services::djvu_document::{self, DjvuDocument};
+use crate::services::format_adapter::*;
+
+/// Longest edge of the rendered first-page cover
+const COVER_MAX_DIMENSION: u32 = 800;
+
+pub struct DjvuFormatAdapter;
+
+impl DjvuFormatAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DjvuFormatAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl BookFormatAdapter for DjvuFormatAdapter {
+    fn format_id(&self) -> &str {
+        "djvu"
+    }
+
+    async fn validate(&self, path: &Path) -> FormatResult<ValidationResult> {
+        let file_size = fs::metadata(path).await?.len();
+
+        let doc = match DjvuDocument::open(path) {
+            Ok(doc) => doc,
+            Err(e) => {
+                let mut result = ValidationResult::invalid(e.to_string());
+                result.file_size = file_size;
+                return Ok(result);
+            }
+        };
+
+        let mut result = ValidationResult::valid(file_size);
+        result.page_count = Some(doc.page_count() as u32);
+        if !djvu_document::is_available() {
+            result
+                .warnings
+                .push("djvulibre is not installed; pages cannot be rendered".to_string());
+        }
+        Ok(result)
+    }
+
+    async fn extract_metadata(&self, path: &Path) -> FormatResult<BookMetadata> {
+        let doc = DjvuDocument::open(path)
+            .map_err(|e| FormatError::MetadataError(format!("Failed to load DjVu: {}", e)))?;
+        let file_size = fs::metadata(path).await?.len();
+
+        let mut book_meta = BookMetadata {
+            file_format: "djvu".to_string(),
+            file_size,
+            page_count: Some(doc.page_count() as u32),
+            ..Default::default()
+        };
+
+        // Embedded metadata is optional; missing tools are not an error here
+        if let Ok(meta) = djvu_document::read_metadata(path) {
+            if let Some(title) = meta.get("title") {
+                book_meta.title = title.clone();
+            }
+            if let Some(author) = meta.get("author") {
+                book_meta.authors = vec![author.clone()];
+            }
+            book_meta.publisher = meta.get("publisher").cloned();
+            book_meta.description = meta.get("note").cloned();
+        }
+
+        if book_meta.title == "Unknown" {
+            if let Some(filename) = path.file_stem() {
+                book_meta.title = filename.to_string_lossy().to_string();
+            }
+        }
+
+        Ok(book_meta)
+    }
+
+    async fn extract_cover(&self, path: &Path) -> FormatResult<Option<CoverImage>> {
+        if !djvu_document::is_available() {
+            return Ok(None);
+        }
+
+        let png = djvu_document::render_page_png(path, 0, COVER_MAX_DIMENSION, COVER_MAX_DIMENSION)
+            .map_err(|e| FormatError::CoverError(e.to_string()))?;
+        let mut cover = CoverImage::from_bytes(&png)?;
+        cover.format = ImageFormat::Png;
+        Ok(Some(cover))
+    }
+
+    fn can_convert_to(&self, _target: &str) -> bool {
+        // Scanned pages have no reflowable text to convert
+        false
+    }
+
+    async fn convert_to(
+        &self,
+        _source: &Path,
+        _target: &Path,
+        target_format: &str,
+    ) -> FormatResult<ConversionResult> {
+        Err(FormatError::ConversionNotSupported {
+            from: "djvu".to_string(),
+            to: target_format.to_string(),
+        })
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            supports_toc: false,
+            supports_images: true,
+            supports_text_reflow: false,
+            supports_annotations: false,
+            supports_metadata: true,
+            is_readable: true,
+            supports_search: true, // Through the hidden text layer, when present
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_id() {
+        let adapter = DjvuFormatAdapter::new();
+        assert_eq!(adapter.format_id(), "djvu");
+        assert!(!adapter.can_convert_to("epub"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_non_djvu() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fake.djvu");
+        std::fs::write(&path, b"%PDF-1.7 not a djvu").unwrap();
+
+        let result = DjvuFormatAdapter::new().validate(&path).await.unwrap();
+        assert!(!result.is_valid);
+        assert_eq!(result.file_size, 19);
+    }
+}
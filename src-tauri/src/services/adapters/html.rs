@@ -7,11 +7,32 @@ use async_trait::async_trait;
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
+use crate::conversion::oeb::escape_xml;
 use crate::services::format_adapter::*;
 
+/// Elements that are written self-closing in XHTML
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// An HTML page rearranged for packaging as an EPUB
+#[derive(Debug, Default)]
+pub struct HtmlEpubParts {
+    /// `href`s of local `<link rel="stylesheet">`s, in document order
+    pub stylesheet_links: Vec<String>,
+    /// Contents of `<style>` blocks
+    pub inline_styles: Vec<String>,
+    /// Local image `src`s and the path each was rewritten to inside the EPUB
+    pub images: Vec<(String, String)>,
+    /// (title, XHTML body) per chapter
+    pub chapters: Vec<(String, String)>,
+}
+
 pub struct HtmlFormatAdapter;
 
 impl HtmlFormatAdapter {
@@ -110,6 +131,176 @@ impl HtmlFormatAdapter {
 
         count
     }
+
+    /// True for relative references that point next to the HTML file;
+    /// remote (`http:`, `//host`), `data:` and fragment URLs are not local
+    pub fn is_local_reference(url: &str) -> bool {
+        let url = url.trim();
+        !url.is_empty() && !url.starts_with('#') && !url.starts_with("//") && !url.contains(':')
+    }
+
+    /// Resolve a local reference against the directory of the HTML file
+    pub fn resolve_reference(base_dir: &Path, url: &str) -> PathBuf {
+        let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+        let decoded = urlencoding::decode(path)
+            .map(|p| p.into_owned())
+            .unwrap_or_else(|_| path.to_string());
+        base_dir.join(decoded)
+    }
+
+    /// Split an HTML page into chapters at its top-level `<h1>`s, collecting
+    /// the stylesheets and local images it references. Local image sources
+    /// are rewritten to `images/` paths; remote ones are left untouched.
+    pub fn split_for_epub(html: &str, fallback_title: &str) -> FormatResult<HtmlEpubParts> {
+        let dom = parse_document(RcDom::default(), Default::default())
+            .from_utf8()
+            .read_from(&mut html.as_bytes())
+            .map_err(|e| FormatError::CorruptSource(format!("Failed to parse HTML: {}", e)))?;
+
+        let mut parts = HtmlEpubParts::default();
+        Self::collect_resources(&dom.document, &mut parts);
+        let image_map: HashMap<&str, &str> = parts
+            .images
+            .iter()
+            .map(|(src, href)| (src.as_str(), href.as_str()))
+            .collect();
+
+        let mut title = fallback_title.to_string();
+        let mut body_html = String::new();
+        let mut has_content = false;
+        if let Some(body) = Self::find_element(&dom.document, "body") {
+            for child in body.children.borrow().iter() {
+                let is_h1 =
+                    matches!(&child.data, NodeData::Element { name, .. } if &*name.local == "h1");
+                if is_h1 {
+                    if has_content {
+                        parts.chapters.push((title, std::mem::take(&mut body_html)));
+                    }
+                    body_html.clear();
+                    let heading = Self::extract_text(child)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    title = if heading.is_empty() {
+                        fallback_title.to_string()
+                    } else {
+                        heading
+                    };
+                    has_content = true;
+                } else if !has_content {
+                    has_content = !Self::extract_text(child).trim().is_empty()
+                        || Self::find_element(child, "img").is_some();
+                }
+                Self::write_xhtml(child, &image_map, &mut body_html);
+            }
+        }
+        if has_content {
+            parts.chapters.push((title, body_html));
+        }
+
+        Ok(parts)
+    }
+
+    fn collect_resources(handle: &Handle, parts: &mut HtmlEpubParts) {
+        if let NodeData::Element { name, attrs, .. } = &handle.data {
+            let attrs = attrs.borrow();
+            let attr = |key: &str| {
+                attrs
+                    .iter()
+                    .find(|a| &*a.name.local == key)
+                    .map(|a| a.value.to_string())
+            };
+            match &*name.local {
+                "link" => {
+                    let is_stylesheet = attr("rel").is_some_and(|rel| {
+                        rel.split_whitespace()
+                            .any(|r| r.eq_ignore_ascii_case("stylesheet"))
+                    });
+                    if let Some(href) = attr("href").filter(|h| Self::is_local_reference(h)) {
+                        if is_stylesheet {
+                            parts.stylesheet_links.push(href);
+                        }
+                    }
+                }
+                "style" => parts.inline_styles.push(Self::extract_text(handle)),
+                "img" => {
+                    if let Some(src) = attr("src").filter(|s| Self::is_local_reference(s)) {
+                        if !parts.images.iter().any(|(seen, _)| *seen == src) {
+                            let ext = Path::new(src.split(['?', '#']).next().unwrap_or_default())
+                                .extension()
+                                .map(|e| e.to_string_lossy().to_lowercase())
+                                .unwrap_or_else(|| "img".to_string());
+                            let href = format!("images/image{:03}.{}", parts.images.len() + 1, ext);
+                            parts.images.push((src, href));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for child in handle.children.borrow().iter() {
+            Self::collect_resources(child, parts);
+        }
+    }
+
+    fn find_element(handle: &Handle, tag: &str) -> Option<Handle> {
+        if let NodeData::Element { name, .. } = &handle.data {
+            if &*name.local == tag {
+                return Some(handle.clone());
+            }
+        }
+        handle
+            .children
+            .borrow()
+            .iter()
+            .find_map(|child| Self::find_element(child, tag))
+    }
+
+    /// Serialize a node as well-formed XHTML, dropping scripts and event
+    /// handlers and rewriting image sources through `images`
+    fn write_xhtml(handle: &Handle, images: &HashMap<&str, &str>, out: &mut String) {
+        match &handle.data {
+            NodeData::Text { contents } => out.push_str(&escape_xml(&contents.borrow())),
+            NodeData::Element { name, attrs, .. } => {
+                let tag = &*name.local;
+                if matches!(
+                    tag,
+                    "script" | "noscript" | "style" | "link" | "meta" | "template"
+                ) {
+                    return;
+                }
+
+                out.push('<');
+                out.push_str(tag);
+                for attr in attrs.borrow().iter() {
+                    let key = &*attr.name.local;
+                    if key.starts_with("on") {
+                        continue;
+                    }
+                    let value: &str = &attr.value;
+                    let value = match (tag, key) {
+                        ("img", "src") => images.get(value).copied().unwrap_or(value),
+                        _ => value,
+                    };
+                    out.push_str(&format!(" {}=\"{}\"", key, escape_xml(value)));
+                }
+
+                if VOID_ELEMENTS.contains(&tag) {
+                    out.push_str("/>");
+                    return;
+                }
+                out.push('>');
+                for child in handle.children.borrow().iter() {
+                    Self::write_xhtml(child, images, out);
+                }
+                out.push_str("</");
+                out.push_str(tag);
+                out.push('>');
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Default for HtmlFormatAdapter {
@@ -314,4 +505,43 @@ mod tests {
         assert!(adapter.can_convert_to("txt"));
         assert!(!adapter.can_convert_to("pdf"));
     }
+
+    #[test]
+    fn test_split_for_epub() {
+        let html = r#"<html><head><title>Doc</title>
+            <link rel="stylesheet" href="css/book.css">
+            <link rel="stylesheet" href="https://cdn.example.com/x.css">
+            <style>p { color: red; }</style></head>
+            <body><p>Preface text</p>
+            <h1>One</h1><p class="lead">A<br>B <img src="img/a%20b.png?v=2"></p>
+            <script>alert(1)</script>
+            <h1>Two</h1><p onclick="x()"><img src="img/a%20b.png?v=2"><img src="http://example.com/c.jpg"></p>
+            </body></html>"#;
+        let parts = HtmlFormatAdapter::split_for_epub(html, "Doc").unwrap();
+
+        assert_eq!(parts.stylesheet_links, ["css/book.css"]);
+        assert_eq!(parts.inline_styles, ["p { color: red; }"]);
+        assert_eq!(
+            parts.images,
+            [(
+                "img/a%20b.png?v=2".to_string(),
+                "images/image001.png".to_string()
+            )]
+        );
+
+        let titles: Vec<&str> = parts.chapters.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(titles, ["Doc", "One", "Two"]);
+        let one = &parts.chapters[1].1;
+        assert!(one.contains(r#"<p class="lead">A<br/>B <img src="images/image001.png"/></p>"#));
+        assert!(!one.contains("alert"));
+        let two = &parts.chapters[2].1;
+        assert!(two.contains(
+            r#"<p><img src="images/image001.png"/><img src="http://example.com/c.jpg"/></p>"#
+        ));
+
+        assert_eq!(
+            HtmlFormatAdapter::resolve_reference(Path::new("/books"), "img/a%20b.png?v=2"),
+            Path::new("/books/img/a b.png")
+        );
+    }
 }
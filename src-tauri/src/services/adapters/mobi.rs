@@ -61,6 +61,94 @@ impl MobiFormatAdapter {
             FormatError::ConversionError(format!("Failed to read MOBI content: {}", e))
         })
     }
+
+    /// Extract `(title, text)` chapters from a MOBI file, split at page breaks
+    /// and `<h1>`/`<h2>` headings. Books without breaks become one chapter
+    /// named after the book.
+    pub async fn extract_chapters(path: &Path) -> FormatResult<Vec<(String, String)>> {
+        let file_data = fs::read(path).await?;
+        let m = Mobi::from_read(&mut &file_data[..])
+            .map_err(|e| FormatError::ConversionError(format!("Failed to parse MOBI: {}", e)))?;
+        let html = m.content_as_string_lossy().map_err(|e| {
+            FormatError::ConversionError(format!("Failed to read MOBI content: {}", e))
+        })?;
+        Ok(Self::split_chapters(&html, &m.title()))
+    }
+
+    /// Split MOBI HTML into plain-text chapters. Breaks are `<mbp:pagebreak/>`
+    /// markers and the start of every `<h1>`/`<h2>`; a heading opening a
+    /// chapter becomes its title.
+    pub fn split_chapters(html: &str, fallback_title: &str) -> Vec<(String, String)> {
+        static BREAK_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+            regex::Regex::new(r"(?is)<mbp:pagebreak\s*/?>|<h[12][\s>]").unwrap()
+        });
+        static HEADING_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+            regex::Regex::new(r"(?is)^\s*<h[12][^>]*>(.*?)</h[12]\s*>").unwrap()
+        });
+
+        let mut cuts: Vec<usize> = BREAK_RE.find_iter(html).map(|m| m.start()).collect();
+        cuts.insert(0, 0);
+        cuts.push(html.len());
+
+        let mut chapters: Vec<(Option<String>, String)> = Vec::new();
+        for window in cuts.windows(2) {
+            let piece = &html[window[0]..window[1]];
+            // Drop the page-break marker itself; headings stay in the piece
+            let piece = if piece
+                .get(..4)
+                .is_some_and(|p| p.eq_ignore_ascii_case("<mbp"))
+            {
+                piece.split_once('>').map(|(_, rest)| rest).unwrap_or("")
+            } else {
+                piece
+            };
+
+            let (title, body) = match HEADING_RE.captures(piece) {
+                Some(cap) => {
+                    let title = crate::conversion::utils::strip_html_tags(&cap[1])
+                        .trim()
+                        .to_string();
+                    let body = &piece[cap.get(0).map_or(0, |m| m.end())..];
+                    (Some(title).filter(|t| !t.is_empty()), body)
+                }
+                None => (None, piece),
+            };
+
+            let text = Self::html_to_text(body);
+            if !text.is_empty() {
+                chapters.push((title, text));
+            }
+        }
+
+        // No real breaks: keep the whole book as one chapter
+        if chapters.is_empty() {
+            return vec![(fallback_title.to_string(), Self::html_to_text(html))];
+        }
+        if chapters.len() == 1 && chapters[0].0.is_none() {
+            return vec![(fallback_title.to_string(), chapters.remove(0).1)];
+        }
+
+        chapters
+            .into_iter()
+            .enumerate()
+            .map(|(i, (title, text))| (title.unwrap_or_else(|| format!("Chapter {}", i + 1)), text))
+            .collect()
+    }
+
+    /// Flatten MOBI HTML to paragraphs separated by blank lines
+    fn html_to_text(html: &str) -> String {
+        static BLOCK_END_RE: once_cell::sync::Lazy<regex::Regex> =
+            once_cell::sync::Lazy::new(|| {
+                regex::Regex::new(r"(?i)</p\s*>|</div\s*>|<br\s*/?>|</h[1-6]\s*>").unwrap()
+            });
+        let marked = BLOCK_END_RE.replace_all(html, "\n\n");
+        crate::conversion::utils::strip_html_tags(&marked)
+            .split("\n\n")
+            .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
 }
 
 impl Default for MobiFormatAdapter {
@@ -232,6 +320,94 @@ mod tests {
         }
     }
 
+    /// Minimal uncompressed MOBI: PalmDB header, record 0 (PalmDOC + MOBI
+    /// headers + title) and a single text record.
+    fn build_mobi(title: &str, html: &str) -> Vec<u8> {
+        const MOBI_HEADER_LEN: u32 = 232;
+        let text = html.as_bytes();
+
+        let mut rec0 = vec![0u8; 16 + MOBI_HEADER_LEN as usize];
+        let put16 =
+            |buf: &mut [u8], at: usize, v: u16| buf[at..at + 2].copy_from_slice(&v.to_be_bytes());
+        let put32 =
+            |buf: &mut [u8], at: usize, v: u32| buf[at..at + 4].copy_from_slice(&v.to_be_bytes());
+        // PalmDOC header: no compression, one text record
+        put16(&mut rec0, 0, 1);
+        put32(&mut rec0, 4, text.len() as u32);
+        put16(&mut rec0, 8, 1);
+        put16(&mut rec0, 10, 4096);
+        // MOBI header
+        rec0[16..20].copy_from_slice(b"MOBI");
+        put32(&mut rec0, 20, MOBI_HEADER_LEN);
+        put32(&mut rec0, 24, 2);
+        put32(&mut rec0, 28, 65001);
+        put32(&mut rec0, 36, 6);
+        for at in (40..80).step_by(4) {
+            put32(&mut rec0, at, u32::MAX);
+        }
+        put32(&mut rec0, 80, 2);
+        let title_offset = rec0.len() as u32;
+        put32(&mut rec0, 84, title_offset);
+        put32(&mut rec0, 88, title.len() as u32);
+        put32(&mut rec0, 92, 9);
+        put32(&mut rec0, 104, 6);
+        put32(&mut rec0, 108, u32::MAX);
+        put32(&mut rec0, 168, u32::MAX);
+        put16(&mut rec0, 192, 1);
+        put16(&mut rec0, 194, 1);
+        rec0.extend_from_slice(title.as_bytes());
+        rec0.extend_from_slice(&[0, 0, 0, 0]);
+
+        let mut out = vec![0u8; 78];
+        out[..title.len().min(31)].copy_from_slice(&title.as_bytes()[..title.len().min(31)]);
+        out[60..64].copy_from_slice(b"BOOK");
+        out[64..68].copy_from_slice(b"MOBI");
+        out[76..78].copy_from_slice(&2u16.to_be_bytes());
+        let rec0_offset = 78 + 2 * 8 + 2;
+        let rec1_offset = rec0_offset + rec0.len();
+        for (i, offset) in [rec0_offset, rec1_offset].into_iter().enumerate() {
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&[0, 0, 0, i as u8]);
+        }
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&rec0);
+        out.extend_from_slice(text);
+        out
+    }
+
+    #[test]
+    fn test_split_chapters() {
+        let html = "<html><body><p>Title page</p><mbp:pagebreak/><h1>Arrival</h1><p>The ship landed.</p><p>Dust rose.</p><h2 class=\"c\">Departure</h2><p>They left.</p></body></html>";
+        let chapters = MobiFormatAdapter::split_chapters(html, "Book");
+        let titles: Vec<&str> = chapters.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(titles, vec!["Chapter 1", "Arrival", "Departure"]);
+        assert_eq!(chapters[1].1, "The ship landed.\n\nDust rose.");
+
+        let flat = MobiFormatAdapter::split_chapters("<p>Just text.</p>", "Book");
+        assert_eq!(flat, vec![("Book".to_string(), "Just text.".to_string())]);
+
+        // A piece whose fourth byte falls inside a character
+        let cjk = MobiFormatAdapter::split_chapters("日本語の本", "Book");
+        assert_eq!(cjk, vec![("Book".to_string(), "日本語の本".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_extract_chapters_from_mobi() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("two.mobi");
+        let html = "<html><body><h1>One</h1><p>First chapter.</p><mbp:pagebreak/><h1>Two</h1><p>Second chapter.</p></body></html>";
+        std::fs::write(&path, build_mobi("Two Headings", html)).unwrap();
+
+        let chapters = MobiFormatAdapter::extract_chapters(&path).await.unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                ("One".to_string(), "First chapter.".to_string()),
+                ("Two".to_string(), "Second chapter.".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_format_id() {
         let adapter = MobiFormatAdapter::new();
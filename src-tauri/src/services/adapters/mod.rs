@@ -1,3 +1,4 @@
+pub mod djvu;
 pub mod docx;
 pub mod fb2;
 pub mod html;
@@ -10,6 +11,7 @@ pub mod pdf;
 /// cover extraction, and conversion.
 pub mod txt;
 
+pub use djvu::DjvuFormatAdapter;
 pub use docx::DocxFormatAdapter;
 pub use fb2::Fb2FormatAdapter;
 pub use html::HtmlFormatAdapter;
@@ -0,0 +1,430 @@
+//! Token-protected JSON API over the library for community integrations.
+//! Served by [`ShareService`](crate::services::share_service::ShareService)
+//! on its own listener, bound to localhost unless LAN access is enabled.
+
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use rand::Rng;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::db::Database;
+use crate::error::{Result, ShioriError};
+use crate::models::{Book, SearchQuery, SearchResult};
+use crate::services::{library_service, search_service};
+use crate::utils::validate;
+
+/// Page size used when a request does not pass `limit`
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Largest page a single request may ask for
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Local API configuration. The token is returned so the user can copy it
+/// into integrations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub allow_lan: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl ApiSettings {
+    /// Loopback only unless LAN access was opted into
+    pub fn bind_addr(&self) -> SocketAddr {
+        let ip = if self.allow_lan {
+            [0, 0, 0, 0]
+        } else {
+            [127, 0, 0, 1]
+        };
+        SocketAddr::from((ip, self.port))
+    }
+}
+
+fn generate_token() -> String {
+    rand::rngs::OsRng
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
+
+/// Load the API settings, generating the access token on first use
+pub fn load_api_settings(db: &Database) -> Result<ApiSettings> {
+    let conn = db.get_connection()?;
+    let (enabled, allow_lan, port, token) = conn.query_row(
+        "SELECT enabled, allow_lan, port, token FROM api_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok((
+                row.get::<_, bool>(0)?,
+                row.get::<_, bool>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    )?;
+
+    let token = match token.filter(|t| !t.is_empty()) {
+        Some(token) => token,
+        None => {
+            let token = generate_token();
+            conn.execute(
+                "UPDATE api_settings SET token = ?1 WHERE id = 1",
+                params![token],
+            )?;
+            token
+        }
+    };
+
+    Ok(ApiSettings {
+        enabled,
+        allow_lan,
+        port: port.clamp(1, u16::MAX as i64) as u16,
+        token,
+    })
+}
+
+pub fn save_api_settings(
+    db: &Database,
+    enabled: bool,
+    allow_lan: bool,
+    port: u16,
+) -> Result<ApiSettings> {
+    validate::require_in_range(port as i64, 1024, u16::MAX as i64, "port")?;
+
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE api_settings
+         SET enabled = ?1, allow_lan = ?2, port = ?3, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        params![enabled, allow_lan, port],
+    )?;
+    drop(conn);
+
+    load_api_settings(db)
+}
+
+/// Replace the access token, invalidating every integration using the old one
+pub fn regenerate_api_token(db: &Database) -> Result<ApiSettings> {
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE api_settings SET token = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        params![generate_token()],
+    )?;
+    drop(conn);
+
+    load_api_settings(db)
+}
+
+#[derive(Clone)]
+struct ApiState {
+    db: Database,
+    storage_path: PathBuf,
+    token: String,
+}
+
+/// Build the `/api` routes guarded by `token`, expected as
+/// `Authorization: Bearer <token>`
+pub fn router(db: Database, storage_path: PathBuf, token: String) -> Router {
+    let state = ApiState {
+        db,
+        storage_path,
+        token,
+    };
+
+    Router::new()
+        .route("/api/books", get(handle_list_books))
+        .route("/api/books/:id", get(handle_get_book))
+        .route("/api/books/:id/cover", get(handle_book_cover))
+        .route("/api/search", get(handle_search))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// JSON error body: `{"error": "..."}`
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<ShioriError> for ApiError {
+    fn from(err: ShioriError) -> Self {
+        let status = match err {
+            ShioriError::BookNotFound(_) => StatusCode::NOT_FOUND,
+            ShioriError::Validation(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, err.to_string())
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if bearer_matches(request.headers(), &state.token) {
+        next.run(request).await
+    } else {
+        let mut response = ApiError(
+            StatusCode::UNAUTHORIZED,
+            "Invalid or missing API token".to_string(),
+        )
+        .into_response();
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+        response
+    }
+}
+
+fn bearer_matches(headers: &HeaderMap, token: &str) -> bool {
+    let Some(supplied) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    // Compare without short-circuiting so timing does not leak the prefix
+    let (a, b) = (supplied.trim().as_bytes(), token.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
+}
+
+/// One page of `GET /api/books`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookPage {
+    pub books: Vec<Book>,
+    pub total: i64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+async fn handle_list_books(
+    State(state): State<ApiState>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult<Json<BookPage>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+
+    let books = library_service::get_all_books(
+        &state.db,
+        limit,
+        offset,
+        query.sort_by.as_deref(),
+        query.sort_order.as_deref(),
+    )?;
+    let total = library_service::get_total_books(&state.db)?;
+
+    Ok(Json(BookPage {
+        books,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+async fn handle_get_book(
+    State(state): State<ApiState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Book>> {
+    let book = library_service::get_book_by_id(&state.db, id)?;
+    if book.in_trash {
+        return Err(ShioriError::BookNotFound(id.to_string()).into());
+    }
+    Ok(Json(book))
+}
+
+async fn handle_book_cover(
+    State(state): State<ApiState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Response> {
+    let book = library_service::get_book_by_id(&state.db, id)?;
+    if book.in_trash {
+        return Err(ShioriError::BookNotFound(id.to_string()).into());
+    }
+    let not_found = || ApiError(StatusCode::NOT_FOUND, "Cover not available".to_string());
+    let cover_path = book.cover_path.as_deref().ok_or_else(not_found)?;
+    let bytes = tokio::fs::read(state.storage_path.join(cover_path))
+        .await
+        .map_err(|_| not_found())?;
+    let mime = mime_guess::from_path(cover_path).first_or_octet_stream();
+
+    Ok(([(header::CONTENT_TYPE, mime.to_string())], bytes).into_response())
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+async fn handle_search(
+    State(state): State<ApiState>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Json<SearchResult>> {
+    let q = params.q.unwrap_or_default();
+    if q.trim().is_empty() {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "Query parameter 'q' is required".to_string(),
+        ));
+    }
+
+    let query = SearchQuery {
+        query: Some(q),
+        limit: Some(
+            params
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE) as i64,
+        ),
+        offset: Some(params.offset.unwrap_or(0) as i64),
+        ..Default::default()
+    };
+    Ok(Json(search_service::search(&state.db, query)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "test-token";
+
+    fn insert_book(db: &Database, uuid: &str, title: &str) {
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (uuid, title, file_path, file_format) VALUES (?1, ?2, ?3, 'epub')",
+            params![uuid, title, format!("/books/{}.epub", uuid)],
+        )
+        .unwrap();
+        library_service::refresh_fts_row(&conn, conn.last_insert_rowid()).unwrap();
+    }
+
+    async fn get_json(
+        app: Router,
+        uri: &str,
+        token: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = axum::http::Request::get(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let response = app
+            .oneshot(request.body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn test_app() -> (tempfile::TempDir, Router) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        insert_book(&db, "u1", "The Left Hand of Darkness");
+        insert_book(&db, "u2", "A Wizard of Earthsea");
+        insert_book(&db, "u3", "The Dispossessed");
+        let app = router(db, dir.path().to_path_buf(), TOKEN.to_string());
+        (dir, app)
+    }
+
+    #[tokio::test]
+    async fn test_list_books_returns_page_of_books() {
+        let (_dir, app) = test_app();
+
+        let (status, body) = get_json(app.clone(), "/api/books", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["error"].is_string());
+
+        let (status, body) = get_json(
+            app,
+            "/api/books?limit=2&offset=0&sort_by=title&sort_order=asc",
+            Some(TOKEN),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["limit"], 2);
+        assert_eq!(body["offset"], 0);
+        let books = body["books"].as_array().unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books[0]["title"], "A Wizard of Earthsea");
+        assert!(books[0]["id"].is_i64());
+        assert_eq!(books[0]["file_format"], "epub");
+        assert!(books[0]["authors"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_matching_books() {
+        let (_dir, app) = test_app();
+
+        let (status, body) = get_json(app.clone(), "/api/search?q=earthsea", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["query"], "earthsea");
+        assert_eq!(body["total"], 1);
+        assert_eq!(body["books"][0]["uuid"], "u2");
+
+        let (status, body) = get_json(app, "/api/search", Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_trashed_book_cover_is_not_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        insert_book(&db, "u1", "The Lathe of Heaven");
+        std::fs::write(dir.path().join("u1.jpg"), b"jpeg").unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute("UPDATE books SET cover_path = 'u1.jpg'", [])
+            .unwrap();
+        let app = router(db.clone(), dir.path().to_path_buf(), TOKEN.to_string());
+
+        let request = || {
+            axum::http::Request::get("/api/books/1/cover")
+                .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        db.get_connection()
+            .unwrap()
+            .execute("UPDATE books SET in_trash = 1", [])
+            .unwrap();
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,331 @@
+//! Author name normalization and merging of duplicate author rows.
+//!
+//! Imports spell the same person several ways ("Tolkien, J.R.R.",
+//! "J. R. R. Tolkien"). `normalize_name` turns any of them into one
+//! "Family, Given" sort name, and authors whose sort names match are
+//! reported as duplicates that `merge_authors` can fold together.
+
+use crate::db::Database;
+use crate::error::{Result, ShioriError};
+use crate::models::{AuthorBookCount, DuplicateAuthorGroup};
+use crate::services::library_service;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+
+/// Generational and academic suffixes kept after the given names
+const NAME_SUFFIXES: [&str; 7] = ["jr", "sr", "ii", "iii", "iv", "phd", "md"];
+
+/// Lowercase words that belong to the family name ("Le Guin", "van Gogh")
+const FAMILY_PARTICLES: [&str; 12] = [
+    "de", "del", "della", "der", "di", "du", "da", "la", "le", "van", "von", "st.",
+];
+
+fn is_suffix(word: &str) -> bool {
+    let word = word.trim().replace('.', "").to_lowercase();
+    NAME_SUFFIXES.contains(&word.as_str())
+}
+
+fn is_particle(word: &str) -> bool {
+    FAMILY_PARTICLES.contains(&word.to_lowercase().as_str())
+}
+
+/// "J.R.R." / "J.R.R" / "j" become "J. R. R." / "J. R. R." / "J."; other words are kept
+fn normalize_initials(word: &str) -> String {
+    let letters: Vec<&str> = word.split('.').filter(|p| !p.is_empty()).collect();
+    let is_initials = !letters.is_empty()
+        && (word.contains('.') || word.chars().count() == 1)
+        && letters
+            .iter()
+            .all(|l| l.chars().count() == 1 && l.chars().all(char::is_alphabetic));
+    if !is_initials {
+        return word.to_string();
+    }
+    letters
+        .iter()
+        .map(|l| format!("{}.", l.to_uppercase()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_words(words: &str) -> String {
+    words
+        .split_whitespace()
+        .map(normalize_initials)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split "Given Names Family" into (given, family)
+fn split_natural_order(name: &str) -> (String, String) {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let Some((last, rest)) = words.split_last() else {
+        return (String::new(), String::new());
+    };
+    let particles = rest.iter().rev().take_while(|w| is_particle(w)).count();
+    // Keep at least one given name: "De Niro" alone is a family name
+    let particles = particles.min(rest.len().saturating_sub(1));
+    let (given, family_prefix) = rest.split_at(rest.len() - particles);
+    let mut family: Vec<&str> = family_prefix.to_vec();
+    family.push(last);
+    (given.join(" "), family.join(" "))
+}
+
+/// Canonical "Family, Given[, Suffix]" sort name for an author as written in
+/// either order. Whitespace is collapsed and initials are spelled "J. R. R.";
+/// single names ("Plato") are returned as they are.
+pub fn normalize_name(raw: &str) -> String {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let (given, family, suffix) = match collapsed.split_once(',') {
+        // "Martin Luther King, Jr." is natural order with a suffix
+        Some((name, rest)) if is_suffix(rest) => {
+            let (given, family) = split_natural_order(name);
+            (given, family, Some(rest.trim().to_string()))
+        }
+        // "Tolkien, J.R.R." or "King, Martin Luther, Jr."
+        Some((family, rest)) => match rest.rsplit_once(',') {
+            Some((given, suffix)) if is_suffix(suffix) => (
+                given.trim().to_string(),
+                family.trim().to_string(),
+                Some(suffix.trim().to_string()),
+            ),
+            _ => (rest.trim().to_string(), family.trim().to_string(), None),
+        },
+        None => {
+            let mut words: Vec<&str> = collapsed.split_whitespace().collect();
+            let suffix = match words.last() {
+                Some(last) if words.len() > 2 && is_suffix(last) => words.pop(),
+                _ => None,
+            };
+            let (given, family) = split_natural_order(&words.join(" "));
+            (given, family, suffix.map(str::to_string))
+        }
+    };
+
+    let given = normalize_words(&given);
+    let mut sort_name = if given.is_empty() {
+        family
+    } else if family.is_empty() {
+        given
+    } else {
+        format!("{}, {}", family, given)
+    };
+    if let Some(suffix) = suffix.filter(|s| !s.is_empty()) {
+        sort_name = format!("{}, {}", sort_name, suffix);
+    }
+    sort_name
+}
+
+/// Lowercased sort name without punctuation, so every spelling of the
+/// same person groups together while "Elton John" and "John Elton" don't
+fn duplicate_key(name: &str) -> String {
+    normalize_name(name)
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Authors whose names normalize to the same person. Within a group the
+/// author with the most books comes first; groups are ordered by sort name.
+pub fn find_duplicate_authors(db: &Database) -> Result<Vec<DuplicateAuthorGroup>> {
+    let conn = db.get_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT a.id, a.name, COUNT(ba.book_id)
+         FROM authors a
+         LEFT JOIN books_authors ba ON ba.author_id = a.id
+         GROUP BY a.id
+         ORDER BY a.id",
+    )?;
+    let authors = stmt
+        .query_map([], |row| {
+            Ok(AuthorBookCount {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                book_count: row.get(2)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut by_key: BTreeMap<String, Vec<AuthorBookCount>> = BTreeMap::new();
+    for author in authors {
+        let key = duplicate_key(&author.name);
+        if !key.is_empty() {
+            by_key.entry(key).or_default().push(author);
+        }
+    }
+
+    let mut groups: Vec<DuplicateAuthorGroup> = by_key
+        .into_values()
+        .filter(|authors| authors.len() > 1)
+        .map(|mut authors| {
+            authors.sort_by(|a, b| b.book_count.cmp(&a.book_count).then(a.id.cmp(&b.id)));
+            DuplicateAuthorGroup {
+                sort_name: normalize_name(&authors[0].name),
+                authors,
+            }
+        })
+        .collect();
+    groups.sort_by_key(|group| group.sort_name.to_lowercase());
+    Ok(groups)
+}
+
+fn author_name(conn: &Connection, id: i64) -> Result<String> {
+    conn.query_row("SELECT name FROM authors WHERE id = ?1", [id], |row| {
+        row.get(0)
+    })
+    .optional()?
+    .ok_or_else(|| ShioriError::Validation(format!("No author with id {}", id)))
+}
+
+/// Move every book of author `from_id` onto `into_id` and delete `from_id`.
+/// Books already credited to both keep a single credit. Returns the number
+/// of books `from_id` was credited on.
+pub fn merge_authors(db: &Database, from_id: i64, into_id: i64) -> Result<usize> {
+    if from_id == into_id {
+        return Err(ShioriError::Validation(
+            "Cannot merge an author into itself".to_string(),
+        ));
+    }
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    author_name(&tx, from_id)?;
+    let into_name = author_name(&tx, into_id)?;
+
+    let book_ids: Vec<i64> = tx
+        .prepare("SELECT book_id FROM books_authors WHERE author_id = ?1")?
+        .query_map([from_id], |row| row.get(0))?
+        .collect::<std::result::Result<_, _>>()?;
+
+    // (book_id, author_id) is the primary key, so a book credited to both
+    // keeps its existing credit for `into_id`
+    tx.execute(
+        "INSERT OR IGNORE INTO books_authors (book_id, author_id, author_order)
+         SELECT book_id, ?2, author_order FROM books_authors WHERE author_id = ?1",
+        params![from_id, into_id],
+    )?;
+    tx.execute("DELETE FROM books_authors WHERE author_id = ?1", [from_id])?;
+    tx.execute("DELETE FROM authors WHERE id = ?1", [from_id])?;
+    tx.execute(
+        "UPDATE authors SET sort_name = ?2 WHERE id = ?1 AND sort_name IS NULL",
+        params![into_id, normalize_name(&into_name)],
+    )?;
+    library_service::refresh_fts_rows(&tx, &book_ids)?;
+    tx.commit()?;
+
+    log::info!(
+        "[merge_authors] Merged author {} into {} across {} book(s)",
+        from_id,
+        into_id,
+        book_ids.len()
+    );
+    Ok(book_ids.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_normalize_name_inverts_and_tidies() {
+        assert_eq!(normalize_name("Tolkien, J.R.R."), "Tolkien, J. R. R.");
+        assert_eq!(normalize_name("J.R.R. Tolkien"), "Tolkien, J. R. R.");
+        assert_eq!(normalize_name("  J. R. R.   Tolkien "), "Tolkien, J. R. R.");
+        assert_eq!(normalize_name("Austen, Jane"), "Austen, Jane");
+        assert_eq!(normalize_name("Ursula K. Le Guin"), "Le Guin, Ursula K.");
+        assert_eq!(
+            normalize_name("Martin Luther King, Jr."),
+            "King, Martin Luther, Jr."
+        );
+        assert_eq!(
+            normalize_name("King, Martin Luther, Jr."),
+            "King, Martin Luther, Jr."
+        );
+        assert_eq!(normalize_name("Plato"), "Plato");
+        assert_eq!(normalize_name(""), "");
+
+        assert_eq!(
+            duplicate_key("tolkien, j.r.r."),
+            duplicate_key("J. R. R. Tolkien")
+        );
+        assert_ne!(duplicate_key("Elton John"), duplicate_key("John Elton"));
+        assert_ne!(duplicate_key("Jane Austen"), duplicate_key("Jane Auster"));
+    }
+
+    #[test]
+    fn test_merge_authors_consolidates_duplicate_rows() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("authors.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        for (title, authors) in [
+            ("The Hobbit", vec!["Tolkien, J.R.R."]),
+            ("The Silmarillion", vec!["J.R.R. Tolkien"]),
+            // Credited under both spellings
+            ("Letters", vec!["Tolkien, J.R.R.", "J.R.R. Tolkien"]),
+            ("Emma", vec!["Jane Austen"]),
+        ] {
+            conn.execute(
+                "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+                params![title, format!("/tmp/{}.epub", title)],
+            )
+            .unwrap();
+            let book_id = conn.last_insert_rowid();
+            for author in authors {
+                conn.execute("INSERT OR IGNORE INTO authors (name) VALUES (?1)", [author])
+                    .unwrap();
+                conn.execute(
+                    "INSERT INTO books_authors (book_id, author_id)
+                     SELECT ?1, id FROM authors WHERE name = ?2",
+                    params![book_id, author],
+                )
+                .unwrap();
+            }
+        }
+        let id_of = |name: &str| -> i64 {
+            conn.query_row("SELECT id FROM authors WHERE name = ?1", [name], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        let (inverted, natural) = (id_of("Tolkien, J.R.R."), id_of("J.R.R. Tolkien"));
+
+        let groups = find_duplicate_authors(&db).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].sort_name, "Tolkien, J. R. R.");
+        let ids: Vec<i64> = groups[0].authors.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![inverted, natural]);
+
+        assert!(merge_authors(&db, inverted, inverted).is_err());
+        assert_eq!(merge_authors(&db, natural, inverted).unwrap(), 2);
+
+        let credits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM books_authors WHERE author_id = ?1",
+                [inverted],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(credits, 3);
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM authors WHERE name LIKE '%Tolkien%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 1);
+        let sort_name: Option<String> = conn
+            .query_row(
+                "SELECT sort_name FROM authors WHERE id = ?1",
+                [inverted],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(sort_name.as_deref(), Some("Tolkien, J. R. R."));
+        assert!(find_duplicate_authors(&db).unwrap().is_empty());
+    }
+}
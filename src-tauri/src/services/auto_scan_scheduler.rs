@@ -0,0 +1,441 @@
+//! Background re-scan of the folders listed in `library_settings.auto_scan_folders`.
+//!
+//! While `user_preferences.auto_scan_enabled` is on, every configured folder
+//! is run through `rescan_folder` at startup, whenever a filesystem watcher
+//! reports a change under it, and on a fixed interval as a fallback for
+//! folders the watcher can't follow (network shares, watch limits). The
+//! settings are re-read before every scan, so changes apply on the next run.
+
+use crate::db::Database;
+use crate::error::{Result, ShioriError};
+use crate::services::{folder_watch, library_service};
+use crate::utils::validate;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, FileIdMap};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often folders are re-scanned without any watcher event
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Quiet period before a burst of file events triggers a scan
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// A folder to keep scanned, optionally limited to one domain
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AutoScanFolder {
+    pub path: String,
+    /// "books", "manga" or "comics"; `None` files each book by its extension
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+/// Entries may be stored as a bare path or as a folder object
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StoredFolder {
+    Path(String),
+    Folder(AutoScanFolder),
+}
+
+/// Totals of one auto-scan pass, emitted as `library:scan_complete`
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoScanReport {
+    pub folders: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub missing: usize,
+    pub failed: usize,
+}
+
+pub fn get_auto_scan_folders(db: &Database) -> Result<Vec<AutoScanFolder>> {
+    let conn = db.get_connection()?;
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT auto_scan_folders FROM library_settings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+    let stored: Vec<StoredFolder> = match json.as_deref() {
+        Some(json) if !json.trim().is_empty() => serde_json::from_str(json).map_err(|e| {
+            ShioriError::Validation(format!("Invalid auto_scan_folders setting: {}", e))
+        })?,
+        _ => Vec::new(),
+    };
+    Ok(stored
+        .into_iter()
+        .map(|folder| match folder {
+            StoredFolder::Path(path) => AutoScanFolder { path, domain: None },
+            StoredFolder::Folder(folder) => folder,
+        })
+        .collect())
+}
+
+pub fn set_auto_scan_folders(db: &Database, folders: &[AutoScanFolder]) -> Result<()> {
+    for folder in folders {
+        validate::require_safe_path(&folder.path, "auto-scan folder")?;
+        if folder_watch::is_system_directory(&folder.path) {
+            return Err(ShioriError::Validation(format!(
+                "Refusing to auto-scan system directory {}",
+                folder.path
+            )));
+        }
+        if let Some(domain) = folder.domain.as_deref() {
+            if !["books", "manga", "comics"].contains(&domain) {
+                return Err(ShioriError::Validation(format!(
+                    "Folder domain must be books, manga or comics, got '{}'",
+                    domain
+                )));
+            }
+        }
+    }
+    let json = serde_json::to_string(folders)
+        .map_err(|e| ShioriError::Other(format!("Failed to store auto-scan folders: {}", e)))?;
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE library_settings SET auto_scan_folders = ?1, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        [json],
+    )?;
+    Ok(())
+}
+
+fn auto_scan_enabled(db: &Database) -> Result<bool> {
+    let conn = db.get_connection()?;
+    let enabled: Option<bool> = conn
+        .query_row(
+            "SELECT auto_scan_enabled FROM user_preferences WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+    Ok(enabled.unwrap_or(true))
+}
+
+/// Folders to scan right now: none while auto-scan is off
+fn active_folders(db: &Database) -> Vec<AutoScanFolder> {
+    let folders = auto_scan_enabled(db).and_then(|enabled| {
+        if enabled {
+            get_auto_scan_folders(db)
+        } else {
+            Ok(Vec::new())
+        }
+    });
+    match folders {
+        Ok(folders) => folders,
+        Err(e) => {
+            log::warn!("[auto_scan] Failed to read settings: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Run `rescan_folder` over each folder and add up the results. Folders
+/// that are missing or fail are logged and counted as failed.
+pub fn scan_folders(
+    db: &Database,
+    folders: &[AutoScanFolder],
+    covers_dir: &Path,
+) -> AutoScanReport {
+    let mut report = AutoScanReport::default();
+    for folder in folders {
+        if !Path::new(&folder.path).is_dir() {
+            log::warn!("[auto_scan] Skipping missing folder {}", folder.path);
+            report.failed += 1;
+            continue;
+        }
+        match library_service::rescan_folder_in_domain(
+            db,
+            &folder.path,
+            folder.domain.as_deref(),
+            covers_dir,
+        ) {
+            Ok(result) => {
+                report.folders += 1;
+                report.added += result.added;
+                report.updated += result.updated;
+                report.unchanged += result.unchanged;
+                report.missing += result.missing.len();
+                report.failed += result.failed.len();
+            }
+            Err(e) => {
+                log::error!("[auto_scan] Failed to scan {}: {}", folder.path, e);
+                report.failed += 1;
+            }
+        }
+    }
+    report
+}
+
+enum Signal {
+    /// A watched folder changed, or a rescan was requested
+    Scan,
+    Stop,
+}
+
+type ScanCallback = Arc<dyn Fn(&AutoScanReport) + Send + Sync>;
+
+/// Watch `folders` and send `Signal::Scan` when files under them change.
+/// Returns `None` when nothing could be watched, leaving polling to cover it.
+fn watch_folders(
+    folders: &[AutoScanFolder],
+    signals: Sender<Signal>,
+) -> Option<Debouncer<RecommendedWatcher, FileIdMap>> {
+    if folders.is_empty() {
+        return None;
+    }
+    let on_events = move |result: DebounceEventResult| {
+        let changed = result.is_ok_and(|events| {
+            events.iter().any(|event| {
+                matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                )
+            })
+        });
+        if changed {
+            let _ = signals.send(Signal::Scan);
+        }
+    };
+    let mut debouncer = match new_debouncer(WATCH_DEBOUNCE, None, on_events) {
+        Ok(debouncer) => debouncer,
+        Err(e) => {
+            log::warn!("[auto_scan] File watching unavailable, polling only: {}", e);
+            return None;
+        }
+    };
+
+    let mut watching = 0;
+    for folder in folders {
+        match debouncer
+            .watcher()
+            .watch(Path::new(&folder.path), RecursiveMode::Recursive)
+        {
+            Ok(()) => watching += 1,
+            Err(e) => log::warn!(
+                "[auto_scan] Can't watch {}, polling it instead: {}",
+                folder.path,
+                e
+            ),
+        }
+    }
+    (watching > 0).then_some(debouncer)
+}
+
+/// Keeps the configured auto-scan folders imported in the background
+pub struct AutoScanScheduler {
+    db: Database,
+    covers_dir: PathBuf,
+    poll_interval: Duration,
+    on_complete: ScanCallback,
+    signals: Mutex<Option<Sender<Signal>>>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AutoScanScheduler {
+    /// `on_complete` runs on the scheduler thread after every scan pass
+    pub fn new(
+        db: Database,
+        covers_dir: PathBuf,
+        on_complete: impl Fn(&AutoScanReport) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            db,
+            covers_dir,
+            poll_interval: POLL_INTERVAL,
+            on_complete: Arc::new(on_complete),
+            signals: Mutex::new(None),
+            worker: Mutex::new(None),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Start the background thread; the first scan runs immediately
+    pub fn start(&self) -> Result<()> {
+        let mut signals = self.signals.lock();
+        if signals.is_some() {
+            return Ok(());
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher_sender = sender.clone();
+        let db = self.db.clone();
+        let covers_dir = self.covers_dir.clone();
+        let poll_interval = self.poll_interval;
+        let on_complete = Arc::clone(&self.on_complete);
+
+        let worker = std::thread::Builder::new()
+            .name("auto-scan".to_string())
+            .spawn(move || {
+                let mut watched: Vec<AutoScanFolder> = Vec::new();
+                let mut _watcher = None;
+                loop {
+                    let folders = active_folders(&db);
+                    if folders != watched {
+                        _watcher = watch_folders(&folders, watcher_sender.clone());
+                        watched = folders.clone();
+                    }
+                    if !folders.is_empty() {
+                        let report = scan_folders(&db, &folders, &covers_dir);
+                        log::info!(
+                            "[auto_scan] Scanned {} folder(s): {} added, {} updated",
+                            report.folders,
+                            report.added,
+                            report.updated
+                        );
+                        on_complete(&report);
+                    }
+
+                    match receiver.recv_timeout(poll_interval) {
+                        Ok(Signal::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                        Ok(Signal::Scan) | Err(RecvTimeoutError::Timeout) => {}
+                    }
+                    // One scan covers every change that queued up meanwhile
+                    while let Ok(signal) = receiver.try_recv() {
+                        if let Signal::Stop = signal {
+                            return;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| ShioriError::Other(format!("Failed to start auto-scan: {}", e)))?;
+
+        *signals = Some(sender);
+        *self.worker.lock() = Some(worker);
+        log::info!(
+            "[auto_scan] Started (polling every {}s)",
+            self.poll_interval.as_secs()
+        );
+        Ok(())
+    }
+
+    /// Re-read the settings and scan now, e.g. after the folder list changed
+    pub fn trigger(&self) {
+        if let Some(sender) = self.signals.lock().as_ref() {
+            let _ = sender.send(Signal::Scan);
+        }
+    }
+
+    /// Stop the background thread, waiting for a running scan to finish
+    pub fn stop(&self) {
+        if let Some(sender) = self.signals.lock().take() {
+            let _ = sender.send(Signal::Stop);
+        }
+        if let Some(worker) = self.worker.lock().take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for AutoScanScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_folders_accept_bare_paths_and_objects() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("scan.db")).unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute(
+                r#"UPDATE library_settings
+                   SET auto_scan_folders = '["/books", {"path": "/comics", "domain": "manga"}]'
+                   WHERE id = 1"#,
+                [],
+            )
+            .unwrap();
+
+        let folders = get_auto_scan_folders(&db).unwrap();
+        assert_eq!(
+            folders,
+            vec![
+                AutoScanFolder {
+                    path: "/books".to_string(),
+                    domain: None
+                },
+                AutoScanFolder {
+                    path: "/comics".to_string(),
+                    domain: Some("manga".to_string())
+                },
+            ]
+        );
+
+        let bad = AutoScanFolder {
+            path: "/books".to_string(),
+            domain: Some("music".to_string()),
+        };
+        assert!(set_auto_scan_folders(&db, &[bad]).is_err());
+        for system_dir in ["/", "/usr/", "/etc/books"] {
+            let folder = AutoScanFolder {
+                path: system_dir.to_string(),
+                domain: None,
+            };
+            assert!(set_auto_scan_folders(&db, &[folder]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_new_file_in_watched_folder_is_imported() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("scan.db")).unwrap();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+        let inbox = dir.path().join("inbox");
+        std::fs::create_dir_all(&inbox).unwrap();
+        set_auto_scan_folders(
+            &db,
+            &[AutoScanFolder {
+                path: inbox.to_string_lossy().to_string(),
+                domain: Some("books".to_string()),
+            }],
+        )
+        .unwrap();
+
+        let (reports, received) = mpsc::channel();
+        let reports = Mutex::new(reports);
+        // Polling is pushed out of the way so only the watcher can trigger the rescan
+        let scheduler = AutoScanScheduler::new(db.clone(), covers_dir, move |report| {
+            let _ = reports.lock().send(report.clone());
+        })
+        .with_poll_interval(Duration::from_secs(3600));
+        scheduler.start().unwrap();
+
+        let initial = received.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(initial.folders, 1);
+        assert_eq!(initial.added, 0);
+
+        std::fs::write(inbox.join("Dune.txt"), "the spice must flow").unwrap();
+        // Not a book, so the books-only folder leaves it alone
+        std::fs::write(inbox.join("Chapter 1.cbz"), "not a book").unwrap();
+
+        let after = received.recv_timeout(Duration::from_secs(30)).unwrap();
+        assert_eq!(after.added, 1);
+        assert_eq!(after.failed, 0);
+        scheduler.stop();
+
+        let titles: Vec<String> = library_service::get_all_books(&db, 10, 0, None, None)
+            .unwrap()
+            .into_iter()
+            .map(|book| book.title)
+            .collect();
+        assert_eq!(titles, vec!["Dune".to_string()]);
+    }
+}
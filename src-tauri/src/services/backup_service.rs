@@ -36,6 +36,17 @@ pub struct RestoreInfo {
     pub frontend_settings: Option<String>,
 }
 
+/// Result of [`backup_library`] and [`restore_library`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibrarySnapshotInfo {
+    pub book_count: usize,
+    pub files_copied: usize,
+    pub total_size_bytes: u64,
+}
+
+/// App data folders copied next to the database by [`backup_library`]
+const LIBRARY_DIRS: [&str; 2] = ["covers", "storage/rss"];
+
 /// Create a complete backup of library database, covers, and optionally book files
 pub fn create_backup(
     db: &Database,
@@ -331,3 +342,129 @@ pub fn get_backup_info(backup_path: &Path) -> Result<BackupInfo> {
 
     Ok(backup_info)
 }
+
+/// Snapshot the live database into `backup_dir/library.db`, next to copies
+/// of the covers and RSS folders. Unlike [`create_backup`] this is a plain
+/// directory and the database is copied with SQLite's online backup API.
+pub fn backup_library(
+    db: &Database,
+    app_data_dir: &Path,
+    backup_dir: &Path,
+) -> Result<LibrarySnapshotInfo> {
+    fs::create_dir_all(backup_dir)?;
+
+    // Back up beside the target and swap it in, so an earlier backup in the
+    // same folder survives a failure
+    let db_path = backup_dir.join("library.db");
+    let temp_path = backup_dir.join("library.db.partial");
+    db.backup_to(&temp_path)?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(backup_dir.join(format!("library.db{}", suffix)));
+    }
+    fs::rename(&temp_path, &db_path)?;
+
+    let mut info = LibrarySnapshotInfo {
+        book_count: count_books(db)?,
+        files_copied: 0,
+        total_size_bytes: fs::metadata(&db_path)?.len(),
+    };
+    for dir in LIBRARY_DIRS {
+        copy_dir(&app_data_dir.join(dir), &backup_dir.join(dir), &mut info)?;
+    }
+    Ok(info)
+}
+
+/// Replace the live library with one written by [`backup_library`]
+pub fn restore_library(
+    db: &Database,
+    app_data_dir: &Path,
+    backup_dir: &Path,
+) -> Result<LibrarySnapshotInfo> {
+    let db_path = backup_dir.join("library.db");
+    if !db_path.is_file() {
+        return Err(ShioriError::FileNotFound {
+            path: db_path.display().to_string(),
+        });
+    }
+    db.restore_from(&db_path)?;
+
+    let mut info = LibrarySnapshotInfo {
+        book_count: count_books(db)?,
+        files_copied: 0,
+        total_size_bytes: fs::metadata(&db_path)?.len(),
+    };
+    for dir in LIBRARY_DIRS {
+        copy_dir(&backup_dir.join(dir), &app_data_dir.join(dir), &mut info)?;
+    }
+    Ok(info)
+}
+
+fn count_books(db: &Database) -> Result<usize> {
+    let conn = db.get_connection()?;
+    Ok(conn.query_row("SELECT COUNT(*) FROM books", [], |row| row.get(0))?)
+}
+
+/// Copy every file under `src` into `dst`, overwriting existing files.
+/// A missing `src` is not an error.
+fn copy_dir(src: &Path, dst: &Path, info: &mut LibrarySnapshotInfo) -> Result<()> {
+    if !src.is_dir() {
+        return Ok(());
+    }
+    for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(src) else {
+            continue;
+        };
+        let target = dst.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        info.total_size_bytes += fs::copy(entry.path(), &target)?;
+        info.files_copied += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_library_backup_round_trip() {
+        let app_dir = tempdir().unwrap();
+        let backup_dir = tempdir().unwrap();
+        let db = Database::new(app_dir.path().join("library.db")).unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO books (title, file_path, file_format, uuid) VALUES ('Dune', '/tmp/dune.epub', 'epub', 'u-1')",
+                [],
+            )
+            .unwrap();
+        fs::create_dir_all(app_dir.path().join("covers")).unwrap();
+        fs::write(app_dir.path().join("covers/u-1.jpg"), b"cover").unwrap();
+        fs::create_dir_all(app_dir.path().join("storage/rss")).unwrap();
+        fs::write(app_dir.path().join("storage/rss/feed.epub"), b"rss").unwrap();
+
+        let info = backup_library(&db, app_dir.path(), backup_dir.path()).unwrap();
+        assert_eq!(info.book_count, 1);
+        assert_eq!(info.files_copied, 2);
+        assert!(backup_dir.path().join("storage/rss/feed.epub").is_file());
+
+        db.get_connection()
+            .unwrap()
+            .execute("DELETE FROM books", [])
+            .unwrap();
+        fs::remove_dir_all(app_dir.path().join("covers")).unwrap();
+
+        let restored = restore_library(&db, app_dir.path(), backup_dir.path()).unwrap();
+        assert_eq!(restored.book_count, 1);
+        assert_eq!(
+            fs::read(app_dir.path().join("covers/u-1.jpg")).unwrap(),
+            b"cover"
+        );
+    }
+}
@@ -8,10 +8,12 @@
 /// - Subjects/genres
 /// - Publication dates
 /// - ISBN information
+use crate::db::Database;
 use crate::error::{Result, ShioriError};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use crate::utils::{http, isbn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
 
 // ═══════════════════════════════════════════════════════════
 // API TYPES
@@ -126,32 +128,106 @@ struct LanguageRef {
 // ═══════════════════════════════════════════════════════════
 
 pub struct BookMetadataService {
-    client: Client,
     base_url: String,
     covers_url: String,
+    db: Option<Database>,
+    cache_ttl_days: i64,
 }
 
+/// Provider name used for rows in the `metadata_cache` table
+const CACHE_PROVIDER: &str = "openlibrary";
+/// Default lifetime of cached Open Library responses
+const DEFAULT_CACHE_TTL_DAYS: i64 = 30;
+
 /// Maximum response body size for JSON/API responses (2 MB)
 const MAX_JSON_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
 /// Maximum response body size for cover image downloads (10 MB)
-#[allow(dead_code)]
-const MAX_IMAGE_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+pub(crate) const MAX_IMAGE_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
 
 impl BookMetadataService {
     pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Shiori/0.1.0 (https://github.com/yourusername/shiori)")
-            .build()
-            .map_err(|e| ShioriError::Other(format!("Failed to create HTTP client: {}", e)))?;
-
         Ok(Self {
-            client,
             base_url: "https://openlibrary.org".to_string(),
             covers_url: "https://covers.openlibrary.org".to_string(),
+            db: None,
+            cache_ttl_days: DEFAULT_CACHE_TTL_DAYS,
         })
     }
 
+    /// Set the database used for the `metadata_cache` lookup cache
+    pub fn set_database(&mut self, db: Database) {
+        self.db = Some(db);
+    }
+
+    /// Set how long cached responses stay valid (default 30 days)
+    #[allow(dead_code)]
+    pub fn set_cache_ttl_days(&mut self, days: i64) {
+        self.cache_ttl_days = days.max(0);
+    }
+
+    // ═══════════════════════════════════════════════════════════
+    // LOCAL CACHE
+    // ═══════════════════════════════════════════════════════════
+
+    fn query_hash(key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Return a non-expired cached response for `key`, or run `fetch` and cache
+    /// its result. Empty results (`None`, `[]`) are not cached so a later lookup
+    /// can still find newly catalogued books.
+    async fn cached<T, F, Fut>(&self, key: &str, fetch: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let db = match &self.db {
+            Some(db) => db,
+            None => return fetch().await,
+        };
+        let query_hash = Self::query_hash(key);
+
+        if let Ok(conn) = db.get_connection() {
+            let cached: Option<String> = conn
+                .query_row(
+                    "SELECT response_json FROM metadata_cache
+                     WHERE provider = ?1 AND query_hash = ?2 AND expires_at > CURRENT_TIMESTAMP",
+                    rusqlite::params![CACHE_PROVIDER, query_hash],
+                    |row| row.get(0),
+                )
+                .ok();
+            if let Some(value) = cached.and_then(|json| serde_json::from_str::<T>(&json).ok()) {
+                log::info!("[BookMetadataService] Cache HIT for '{}'", key);
+                return Ok(value);
+            }
+        }
+
+        let value = fetch().await?;
+
+        let json = serde_json::to_string(&value).unwrap_or_default();
+        if !json.is_empty() && json != "null" && json != "[]" {
+            if let Ok(conn) = db.get_connection() {
+                if let Err(e) = conn.execute(
+                    "INSERT OR REPLACE INTO metadata_cache (provider, query_hash, response_json, expires_at)
+                     VALUES (?1, ?2, ?3, datetime('now', ?4))",
+                    rusqlite::params![
+                        CACHE_PROVIDER,
+                        query_hash,
+                        json,
+                        format!("+{} days", self.cache_ttl_days)
+                    ],
+                ) {
+                    log::warn!("[BookMetadataService] Failed to cache '{}': {}", key, e);
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
     /// Read a response body as JSON with a size limit to prevent memory exhaustion.
     async fn bounded_json<T: serde::de::DeserializeOwned>(
         response: reqwest::Response,
@@ -164,7 +240,7 @@ impl BookMetadataService {
     }
 
     /// Read a response body with a size limit to prevent memory exhaustion.
-    async fn bounded_bytes(
+    pub(crate) async fn bounded_bytes(
         response: reqwest::Response,
         max_bytes: usize,
         context: &str,
@@ -199,6 +275,11 @@ impl BookMetadataService {
         title: &str,
         author: Option<&str>,
     ) -> Result<Vec<BookMetadata>> {
+        let key = format!("search:{}|{}", title.trim(), author.unwrap_or("").trim());
+        self.cached(&key, || self.fetch_search(title, author)).await
+    }
+
+    async fn fetch_search(&self, title: &str, author: Option<&str>) -> Result<Vec<BookMetadata>> {
         log::info!("[BookMetadataService] Searching for: '{}'", title);
 
         let mut query_parts = vec![format!("title:{}", title)];
@@ -214,7 +295,7 @@ impl BookMetadataService {
         );
 
         let response =
-            self.client.get(&url).send().await.map_err(|e| {
+            http::client().get(&url).send().await.map_err(|e| {
                 ShioriError::Other(format!("Open Library API request failed: {}", e))
             })?;
 
@@ -243,13 +324,22 @@ impl BookMetadataService {
         Ok(metadata)
     }
 
-    /// Search by ISBN (most accurate)
+    /// Search by ISBN (most accurate). ISBN-10s are looked up by their
+    /// ISBN-13 so both forms share one cache entry.
     pub async fn search_by_isbn(&self, isbn: &str) -> Result<Option<BookMetadata>> {
+        let isbn13 = isbn::normalize(isbn)
+            .map(|normalized| normalized.isbn13)
+            .ok_or_else(|| ShioriError::Validation(format!("Invalid ISBN: {}", isbn)))?;
+        let key = format!("isbn:{}", isbn13);
+        self.cached(&key, || self.fetch_by_isbn(&isbn13)).await
+    }
+
+    async fn fetch_by_isbn(&self, isbn: &str) -> Result<Option<BookMetadata>> {
         log::info!("[BookMetadataService] Searching by ISBN: {}", isbn);
 
         let url = format!("{}/isbn/{}.json", self.base_url, isbn);
 
-        let response = self.client.get(&url).send().await;
+        let response = http::client().get(&url).send().await;
 
         match response {
             Ok(resp) if resp.status().is_success() => {
@@ -275,6 +365,12 @@ impl BookMetadataService {
     /// Get detailed book metadata by Open Library ID
     #[allow(dead_code)]
     pub async fn get_book_by_id(&self, ol_id: &str) -> Result<BookMetadata> {
+        let key = format!("id:{}", ol_id);
+        self.cached(&key, || self.fetch_book_by_id(ol_id)).await
+    }
+
+    #[allow(dead_code)]
+    async fn fetch_book_by_id(&self, ol_id: &str) -> Result<BookMetadata> {
         log::info!("[BookMetadataService] Fetching book: {}", ol_id);
 
         // Determine if it's a work or edition ID
@@ -284,8 +380,7 @@ impl BookMetadataService {
             format!("{}/books/{}.json", self.base_url, ol_id)
         };
 
-        let response = self
-            .client
+        let response = http::client()
             .get(&url)
             .send()
             .await
@@ -313,8 +408,7 @@ impl BookMetadataService {
         let url = format!("{}/b/id/{}-{}.jpg", self.covers_url, cover_id, size);
         log::info!("[BookMetadataService] Downloading cover from: {}", url);
 
-        let response = self
-            .client
+        let response = http::client()
             .get(&url)
             .send()
             .await
@@ -460,7 +554,7 @@ impl BookMetadataService {
             // key is like "/authors/OL123A"
             let url = format!("{}{}.json", self.base_url, key);
 
-            match self.client.get(&url).send().await {
+            match http::client().get(&url).send().await {
                 Ok(resp) if resp.status().is_success() => {
                     if let Ok(detail) = Self::bounded_json::<AuthorDetailResponse>(
                         resp,
@@ -549,3 +643,58 @@ impl Default for BookMetadataService {
         Self::new().expect("Failed to create BookMetadataService")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_isbn_lookup_uses_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/isbn/9780141036144.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "title": "Nineteen Eighty-Four",
+                "publishers": ["Penguin"],
+                "isbn_13": ["9780141036144"]
+            })))
+            .expect(1) // the second lookup must not reach the network
+            .mount(&server)
+            .await;
+
+        let temp_dir = std::env::temp_dir().join(format!(
+            "shiori-test-metadata-cache-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .subsec_nanos()
+        ));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db = Database::new(temp_dir.join("test.db")).unwrap();
+
+        let mut service = BookMetadataService::new().unwrap();
+        service.base_url = server.uri();
+        service.set_database(db.clone());
+
+        let first = service.search_by_isbn("978-0141036144").await.unwrap().unwrap();
+        let second = service.search_by_isbn("9780141036144").await.unwrap().unwrap();
+        // The ISBN-10 form of the same book is served from the same entry
+        let third = service.search_by_isbn("0141036141").await.unwrap().unwrap();
+        assert_eq!(first.title, "Nineteen Eighty-Four");
+        assert_eq!(second.title, first.title);
+        assert_eq!(third.title, first.title);
+
+        let rows: i64 = db
+            .get_connection()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM metadata_cache WHERE provider = 'openlibrary'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+}
@@ -30,16 +30,42 @@ pub enum CacheItemType {
     Resource,
 }
 
+/// Renderer cache size for a `performance_mode` preference value
+pub fn cache_size_for_performance_mode(mode: &str) -> usize {
+    match mode {
+        "large_library" => 256,
+        "low_memory" => 32,
+        _ => 100,
+    }
+}
+
 /// Internal state protected by a single mutex to prevent deadlocks
 struct CacheState {
     lru: LruCache<CacheKey, CachedContent>,
     current_size_bytes: usize,
+    max_size_bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl CacheState {
+    /// Drop least-recently-used items until `incoming` more bytes fit
+    fn evict_to_fit(&mut self, incoming: usize) {
+        while self.current_size_bytes + incoming > self.max_size_bytes {
+            if let Some((_, evicted)) = self.lru.pop_lru() {
+                self.current_size_bytes -= BookCache::estimate_content_size(&evicted);
+                self.evictions += 1;
+            } else {
+                break; // Cache is empty
+            }
+        }
+    }
 }
 
 /// In-memory LRU cache for book content
 pub struct BookCache {
     state: Mutex<CacheState>,
-    max_size_bytes: usize,
 }
 
 impl BookCache {
@@ -52,15 +78,31 @@ impl BookCache {
             state: Mutex::new(CacheState {
                 lru: LruCache::new(capacity),
                 current_size_bytes: 0,
+                max_size_bytes,
+                hits: 0,
+                misses: 0,
+                evictions: 0,
             }),
-            max_size_bytes,
         }
     }
 
     /// Get an item from the cache
     pub fn get(&self, key: &CacheKey) -> Option<CachedContent> {
         let mut state = self.state.lock().unwrap();
-        state.lru.get(key).cloned()
+        let found = state.lru.get(key).cloned();
+        if found.is_some() {
+            state.hits += 1;
+        } else {
+            state.misses += 1;
+        }
+        found
+    }
+
+    /// Change the size limit, evicting the oldest items if the cache no longer fits
+    pub fn resize(&self, new_size_mb: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.max_size_bytes = new_size_mb * 1024 * 1024;
+        state.evict_to_fit(0);
     }
 
     /// Put an item into the cache
@@ -69,18 +111,15 @@ impl BookCache {
         let mut state = self.state.lock().unwrap();
 
         // Evict oldest items until we have space
-        while state.current_size_bytes + content_size > self.max_size_bytes {
-            if let Some((_, evicted)) = state.lru.pop_lru() {
-                state.current_size_bytes -= Self::estimate_content_size(&evicted);
-            } else {
-                break; // Cache is empty
-            }
-        }
+        state.evict_to_fit(content_size);
 
-        // Add new item
-        if let Some(old_content) = state.lru.put(key, content) {
-            // Replace existing item — subtract old size
+        // Add new item; `push` also hands back whatever the item cap pushed out
+        if let Some((old_key, old_content)) = state.lru.push(key.clone(), content) {
+            // Replaced or evicted item — subtract old size
             state.current_size_bytes -= Self::estimate_content_size(&old_content);
+            if old_key != key {
+                state.evictions += 1;
+            }
         }
         state.current_size_bytes += content_size;
     }
@@ -120,13 +159,23 @@ impl BookCache {
     /// Get current cache statistics
     pub fn stats(&self) -> CacheStats {
         let state = self.state.lock().unwrap();
+        let lookups = state.hits + state.misses;
 
         CacheStats {
             item_count: state.lru.len(),
             size_bytes: state.current_size_bytes,
-            max_size_bytes: self.max_size_bytes,
-            utilization_percent: (state.current_size_bytes as f64 / self.max_size_bytes as f64
+            max_size_bytes: state.max_size_bytes,
+            utilization_percent: (state.current_size_bytes as f64
+                / state.max_size_bytes.max(1) as f64
                 * 100.0) as u32,
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                state.hits as f64 / lookups as f64
+            },
         }
     }
 
@@ -152,6 +201,12 @@ pub struct CacheStats {
     pub size_bytes: usize,
     pub max_size_bytes: usize,
     pub utilization_percent: u32,
+    pub hits: u64,
+    pub misses: u64,
+    /// Items dropped to make room, including those dropped by `resize`
+    pub evictions: u64,
+    /// Fraction of lookups served from cache, 0.0–1.0
+    pub hit_rate: f64,
 }
 
 /// Global cache instance manager
@@ -210,6 +265,57 @@ mod tests {
 
         let stats = cache.stats();
         assert!(stats.size_bytes <= stats.max_size_bytes);
+        assert!(stats.evictions > 0);
+    }
+
+    #[test]
+    fn test_resize_evicts_oldest() {
+        let cache = BookCache::new(2); // 2 MB
+        let key = |index| CacheKey {
+            book_id: 1,
+            item_type: CacheItemType::Chapter,
+            index,
+        };
+
+        // Four ~400KB chapters fit in 2 MB
+        for i in 0..4 {
+            cache.put(key(i), CachedContent::Text("x".repeat(400 * 1024)));
+        }
+        assert_eq!(cache.stats().evictions, 0);
+
+        cache.resize(1);
+        let stats = cache.stats();
+        assert_eq!(stats.max_size_bytes, 1024 * 1024);
+        assert!(stats.size_bytes <= stats.max_size_bytes);
+        assert_eq!(stats.item_count, 2);
+        assert_eq!(stats.evictions, 2);
+        assert!(cache.get(&key(0)).is_none());
+        assert!(cache.get(&key(3)).is_some());
+
+        // Growing again evicts nothing
+        cache.resize(4);
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_hit_rate() {
+        let cache = BookCache::new(1);
+        let key = CacheKey {
+            book_id: 1,
+            item_type: CacheItemType::Chapter,
+            index: 0,
+        };
+        assert_eq!(cache.stats().hit_rate, 0.0);
+
+        cache.get(&key);
+        cache.put(key.clone(), CachedContent::Text("a".to_string()));
+        cache.get(&key);
+        cache.get(&key);
+        cache.get(&key);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert_eq!(stats.hit_rate, 0.75);
     }
 
     #[test]
@@ -0,0 +1,625 @@
+/// CBR (RAR) comic archive access.
+///
+/// RAR4 and RAR5 headers are parsed natively to list pages, detect solid
+/// compression and spot password protection without extra software. Page
+/// data is decompressed by the system `unrar` tool, the same way CBR
+/// conversion does. Everything here is blocking and meant to run inside
+/// `spawn_blocking`.
+use crate::error::{Result, ShioriError};
+use crate::utils::natural_sort;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+const RAR4_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x00";
+const RAR5_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x01\x00";
+
+/// One file or directory entry from the archive headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RarEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub encrypted: bool,
+}
+
+/// Everything the headers tell us about an archive
+#[derive(Debug, Clone, Default)]
+pub struct RarListing {
+    pub solid: bool,
+    /// Headers themselves are encrypted, so no entries could be listed
+    pub encrypted_headers: bool,
+    pub entries: Vec<RarEntry>,
+}
+
+impl RarListing {
+    fn is_password_protected(&self) -> bool {
+        self.encrypted_headers || self.entries.iter().any(|e| e.encrypted)
+    }
+
+    /// Image entries from every folder, in natural reading order
+    fn sorted_pages(&self) -> Vec<String> {
+        let mut pages: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| !e.is_dir && is_page_image(&e.name))
+            .map(|e| e.name.clone())
+            .collect();
+        pages.sort_by(|a, b| natural_sort::compare(a, b));
+        pages
+    }
+}
+
+/// An opened CBR with its page list resolved
+pub struct CbrArchive {
+    path: PathBuf,
+    pages: Vec<String>,
+    solid: bool,
+    /// Solid archives can only be decoded front to back, so the first page
+    /// request unpacks the whole archive here and later pages read from disk.
+    extracted: Mutex<Option<tempfile::TempDir>>,
+}
+
+impl CbrArchive {
+    pub fn open(path: &Path) -> Result<Self> {
+        let listing = Self::validate(path)?;
+        crate::services::manga_service::warn_undecodable_pages(
+            &path.display().to_string(),
+            listing
+                .entries
+                .iter()
+                .filter(|e| !e.is_dir)
+                .map(|e| e.name.as_str()),
+        );
+        let pages = listing.sorted_pages();
+        if pages.is_empty() {
+            return Err(ShioriError::InvalidFormat(
+                "No image files found in CBR archive".to_string(),
+            ));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            pages,
+            solid: listing.solid,
+            extracted: Mutex::new(None),
+        })
+    }
+
+    /// Read the archive headers, failing when the archive or any of its
+    /// entries is encrypted. Unlike PDFs there is no password prompt for
+    /// these, so the error is `UnsupportedFeature` rather than
+    /// `PasswordRequired`.
+    pub fn validate(path: &Path) -> Result<RarListing> {
+        let mut file = std::fs::File::open(path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                ShioriError::FileNotFound {
+                    path: path.display().to_string(),
+                }
+            } else {
+                ShioriError::Io(e)
+            }
+        })?;
+        let listing = read_listing(&mut file)?;
+        if listing.is_password_protected() {
+            return Err(ShioriError::UnsupportedFeature(
+                "This CBR archive is password protected. Remove the password (e.g. re-pack it as CBZ) to read it.".to_string(),
+            ));
+        }
+        Ok(listing)
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn page_names(&self) -> &[String] {
+        &self.pages
+    }
+
+    pub fn is_solid(&self) -> bool {
+        self.solid
+    }
+
+    pub fn get_page(&self, index: usize) -> Result<Vec<u8>> {
+        let name = self.pages.get(index).ok_or_else(|| {
+            ShioriError::Other(format!(
+                "Page index {} out of range (total: {})",
+                index,
+                self.pages.len()
+            ))
+        })?;
+
+        if !self.solid {
+            return run_unrar(&[
+                "p".as_ref(),
+                "-inul".as_ref(),
+                "-p-".as_ref(),
+                self.path.as_os_str(),
+                name.as_ref(),
+            ]);
+        }
+
+        let mut extracted = self.extracted.lock().unwrap();
+        if extracted.is_none() {
+            let dir = tempfile::tempdir()?;
+            let mut target = dir.path().as_os_str().to_owned();
+            target.push(std::path::MAIN_SEPARATOR_STR);
+            run_unrar(&[
+                "x".as_ref(),
+                "-y".as_ref(),
+                "-inul".as_ref(),
+                "-p-".as_ref(),
+                self.path.as_os_str(),
+                target.as_os_str(),
+            ])?;
+            *extracted = Some(dir);
+        }
+        let dir = extracted.as_ref().expect("extracted above");
+        std::fs::read(dir.path().join(name)).map_err(|e| {
+            ShioriError::Other(format!("Page '{}' missing after extraction: {}", name, e))
+        })
+    }
+}
+
+fn run_unrar(args: &[&std::ffi::OsStr]) -> Result<Vec<u8>> {
+    let output = Command::new("unrar")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| {
+            ShioriError::UnsupportedFeature(
+                "The 'unrar' command was not found. Install unrar to open CBR files (e.g. 'pacman -S unrar' on Arch Linux or 'sudo apt install unrar' on Ubuntu).".to_string(),
+            )
+        })?;
+    if !output.status.success() {
+        return Err(ShioriError::Other(format!(
+            "unrar exited with error: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn is_page_image(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let file_name = lower.rsplit('/').next().unwrap_or(&lower);
+    if file_name.starts_with('.') || lower.starts_with("__macosx/") {
+        return false;
+    }
+    crate::services::manga_service::is_image_file(name)
+}
+
+/// Parse the archive headers, skipping over compressed data
+pub fn read_listing<R: Read + Seek>(reader: &mut R) -> Result<RarListing> {
+    let mut signature = [0u8; 8];
+    let read = read_up_to(reader, &mut signature)?;
+    if signature[..read].starts_with(RAR5_SIGNATURE) {
+        read_rar5(reader)
+    } else if signature[..read].starts_with(RAR4_SIGNATURE) {
+        reader.seek(SeekFrom::Start(RAR4_SIGNATURE.len() as u64))?;
+        read_rar4(reader)
+    } else {
+        Err(ShioriError::InvalidFormat(
+            "Invalid CBR/RAR file".to_string(),
+        ))
+    }
+}
+
+fn malformed(what: &str) -> ShioriError {
+    ShioriError::InvalidFormat(format!("Malformed RAR archive: {}", what))
+}
+
+/// Seek past an entry's packed data, rejecting sizes a seek can't express
+fn skip_data<R: Seek>(reader: &mut R, data_size: u64) -> Result<()> {
+    let offset = i64::try_from(data_size).map_err(|_| malformed("data size out of range"))?;
+    reader.seek(SeekFrom::Current(offset))?;
+    Ok(())
+}
+
+/// Fill as much of `buf` as the stream allows, returning the byte count
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn normalize_name(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw).replace('\\', "/")
+}
+
+// ─── RAR 1.5–4.x ───────────────────────────────────────
+
+const RAR4_MAIN_HEAD: u8 = 0x73;
+const RAR4_FILE_HEAD: u8 = 0x74;
+const RAR4_END_HEAD: u8 = 0x7b;
+
+fn read_rar4<R: Read + Seek>(reader: &mut R) -> Result<RarListing> {
+    let mut listing = RarListing::default();
+    loop {
+        // CRC(2) TYPE(1) FLAGS(2) SIZE(2)
+        let mut base = [0u8; 7];
+        if read_up_to(reader, &mut base)? < base.len() {
+            break;
+        }
+        let head_type = base[2];
+        let flags = u16::from_le_bytes([base[3], base[4]]);
+        let head_size = u16::from_le_bytes([base[5], base[6]]) as usize;
+        if head_size < base.len() {
+            return Err(malformed("header shorter than its fixed fields"));
+        }
+        let mut body = vec![0u8; head_size - base.len()];
+        reader
+            .read_exact(&mut body)
+            .map_err(|_| malformed("truncated header"))?;
+
+        // LONG_BLOCK: a 4-byte data size follows the base fields
+        let mut data_size = if flags & 0x8000 != 0 && body.len() >= 4 {
+            u32::from_le_bytes([body[0], body[1], body[2], body[3]]) as u64
+        } else {
+            0
+        };
+
+        match head_type {
+            RAR4_MAIN_HEAD => {
+                listing.solid = flags & 0x0008 != 0;
+                if flags & 0x0080 != 0 {
+                    listing.encrypted_headers = true;
+                    return Ok(listing);
+                }
+            }
+            RAR4_FILE_HEAD => {
+                // PACK(4) UNP(4) OS(1) CRC(4) TIME(4) VER(1) METHOD(1) NAME_SIZE(2) ATTR(4)
+                if body.len() < 25 {
+                    return Err(malformed("truncated file header"));
+                }
+                data_size = u32::from_le_bytes([body[0], body[1], body[2], body[3]]) as u64;
+                let name_size = u16::from_le_bytes([body[19], body[20]]) as usize;
+                let mut name_start = 25;
+                if flags & 0x0100 != 0 {
+                    // LARGE: high halves of the packed and unpacked sizes
+                    let high = body
+                        .get(25..29)
+                        .ok_or_else(|| malformed("truncated file header"))?;
+                    data_size |=
+                        (u32::from_le_bytes([high[0], high[1], high[2], high[3]]) as u64) << 32;
+                    name_start += 8;
+                }
+                let raw_name = body
+                    .get(name_start..name_start + name_size)
+                    .ok_or_else(|| malformed("file name runs past header"))?;
+                // Unicode names store an ASCII form, a NUL and an encoded form
+                let raw_name = raw_name.split(|&b| b == 0).next().unwrap_or(raw_name);
+                listing.entries.push(RarEntry {
+                    name: normalize_name(raw_name),
+                    is_dir: flags & 0x00e0 == 0x00e0,
+                    encrypted: flags & 0x0004 != 0,
+                });
+            }
+            RAR4_END_HEAD => break,
+            _ => {}
+        }
+        skip_data(reader, data_size)?;
+    }
+    Ok(listing)
+}
+
+// ─── RAR 5.x ───────────────────────────────────────────
+
+const RAR5_MAIN_HEAD: u64 = 1;
+const RAR5_FILE_HEAD: u64 = 2;
+const RAR5_ENCRYPTION_HEAD: u64 = 4;
+const RAR5_END_HEAD: u64 = 5;
+
+/// Cursor over one RAR5 header's bytes
+struct Rar5Fields<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Rar5Fields<'a> {
+    /// Variable-length integer: 7 bits per byte, high bit continues
+    fn vint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| malformed("truncated header"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(malformed("integer too long"))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or_else(|| malformed("field runs past header"))?;
+        let slice = self
+            .data
+            .get(self.pos..end)
+            .ok_or_else(|| malformed("field runs past header"))?;
+        self.pos = end;
+        Ok(slice)
+    }
+}
+
+fn read_rar5<R: Read + Seek>(reader: &mut R) -> Result<RarListing> {
+    let mut listing = RarListing::default();
+    loop {
+        let mut crc = [0u8; 4];
+        if read_up_to(reader, &mut crc)? < crc.len() {
+            break;
+        }
+        // Header size is itself a vint, read byte by byte from the stream
+        let mut header_size = 0u64;
+        for shift in (0..21).step_by(7) {
+            let mut byte = [0u8; 1];
+            reader
+                .read_exact(&mut byte)
+                .map_err(|_| malformed("truncated header"))?;
+            header_size |= ((byte[0] & 0x7f) as u64) << shift;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut header = vec![0u8; header_size as usize];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| malformed("truncated header"))?;
+
+        let mut fields = Rar5Fields {
+            data: &header,
+            pos: 0,
+        };
+        let head_type = fields.vint()?;
+        let head_flags = fields.vint()?;
+        let extra_size = if head_flags & 0x0001 != 0 {
+            fields.vint()? as usize
+        } else {
+            0
+        };
+        let data_size = if head_flags & 0x0002 != 0 {
+            fields.vint()?
+        } else {
+            0
+        };
+
+        match head_type {
+            RAR5_MAIN_HEAD => {
+                let archive_flags = fields.vint()?;
+                listing.solid = archive_flags & 0x0004 != 0;
+            }
+            RAR5_ENCRYPTION_HEAD => {
+                listing.encrypted_headers = true;
+                return Ok(listing);
+            }
+            RAR5_FILE_HEAD => {
+                let file_flags = fields.vint()?;
+                let _unpacked_size = fields.vint()?;
+                let _attributes = fields.vint()?;
+                if file_flags & 0x0002 != 0 {
+                    fields.bytes(4)?; // mtime
+                }
+                if file_flags & 0x0004 != 0 {
+                    fields.bytes(4)?; // data CRC32
+                }
+                let _compression = fields.vint()?;
+                let _host_os = fields.vint()?;
+                let name_len = fields.vint()? as usize;
+                let name = normalize_name(fields.bytes(name_len)?);
+
+                let extra_start = header
+                    .len()
+                    .checked_sub(extra_size)
+                    .ok_or_else(|| malformed("extra area larger than header"))?;
+                let mut extra = Rar5Fields {
+                    data: &header[extra_start..],
+                    pos: 0,
+                };
+                let mut encrypted = false;
+                while extra.pos < extra.data.len() {
+                    let record_size = extra.vint()? as usize;
+                    if record_size == 0 {
+                        return Err(malformed("empty extra record"));
+                    }
+                    let record_start = extra.pos;
+                    // Record type 1 is the file encryption record
+                    if extra.vint()? == 1 {
+                        encrypted = true;
+                    }
+                    extra.pos = record_start
+                        .checked_add(record_size)
+                        .ok_or_else(|| malformed("extra record runs past header"))?;
+                }
+
+                listing.entries.push(RarEntry {
+                    name,
+                    is_dir: file_flags & 0x0001 != 0,
+                    encrypted,
+                });
+            }
+            RAR5_END_HEAD => break,
+            _ => {}
+        }
+        skip_data(reader, data_size)?;
+    }
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn vint(mut value: u64, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    /// Append a RAR5 header block (zero CRC) followed by `data`
+    fn rar5_block(out: &mut Vec<u8>, head_type: u64, body: &[u8], extra: &[u8], data: &[u8]) {
+        let mut header = Vec::new();
+        vint(head_type, &mut header);
+        let mut flags = 0;
+        if !extra.is_empty() {
+            flags |= 0x0001;
+        }
+        if !data.is_empty() {
+            flags |= 0x0002;
+        }
+        vint(flags, &mut header);
+        if !extra.is_empty() {
+            vint(extra.len() as u64, &mut header);
+        }
+        if !data.is_empty() {
+            vint(data.len() as u64, &mut header);
+        }
+        header.extend_from_slice(body);
+        header.extend_from_slice(extra);
+
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        vint(header.len() as u64, out);
+        out.extend_from_slice(&header);
+        out.extend_from_slice(data);
+    }
+
+    fn rar5_file(out: &mut Vec<u8>, name: &str, is_dir: bool, encrypted: bool) {
+        let mut body = Vec::new();
+        vint(if is_dir { 0x0001 } else { 0 }, &mut body);
+        vint(3, &mut body); // unpacked size
+        vint(0, &mut body); // attributes
+        vint(0, &mut body); // compression info
+        vint(1, &mut body); // host OS: Unix
+        vint(name.len() as u64, &mut body);
+        body.extend_from_slice(name.as_bytes());
+
+        let mut extra = Vec::new();
+        if encrypted {
+            let record = [1u8, 0, 0];
+            vint(record.len() as u64, &mut extra);
+            extra.extend_from_slice(&record);
+        }
+        let data: &[u8] = if is_dir { &[] } else { b"img" };
+        rar5_block(out, RAR5_FILE_HEAD, &body, &extra, data);
+    }
+
+    fn rar5_archive(solid: bool, files: &[(&str, bool)], encrypted: bool) -> Vec<u8> {
+        let mut out = RAR5_SIGNATURE.to_vec();
+        let mut main = Vec::new();
+        vint(if solid { 0x0004 } else { 0 }, &mut main);
+        rar5_block(&mut out, RAR5_MAIN_HEAD, &main, &[], &[]);
+        for &(name, is_dir) in files {
+            rar5_file(&mut out, name, is_dir, encrypted && !is_dir);
+        }
+        rar5_block(&mut out, RAR5_END_HEAD, &[0], &[], &[]);
+        out
+    }
+
+    #[test]
+    fn test_nested_pages_are_listed_in_natural_order() {
+        let data = rar5_archive(
+            true,
+            &[
+                ("Chapter 2", true),
+                ("Chapter 2/page10.jpg", false),
+                ("Chapter 2/page2.jpg", false),
+                ("Chapter 10/page1.png", false),
+                ("Chapter 1/page1.jpg", false),
+                ("Chapter 1/notes.txt", false),
+                ("__MACOSX/Chapter 1/._page1.jpg", false),
+                ("ComicInfo.xml", false),
+            ],
+            false,
+        );
+        let listing = read_listing(&mut Cursor::new(data)).unwrap();
+
+        assert!(listing.solid);
+        assert!(!listing.is_password_protected());
+        assert_eq!(
+            listing.sorted_pages(),
+            vec![
+                "Chapter 1/page1.jpg",
+                "Chapter 2/page2.jpg",
+                "Chapter 2/page10.jpg",
+                "Chapter 10/page1.png",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rar4_headers_and_password_detection() {
+        let mut data = RAR4_SIGNATURE.to_vec();
+        // Main header: solid flag, 13 bytes total
+        data.extend_from_slice(&[0, 0, RAR4_MAIN_HEAD, 0x08, 0x00, 13, 0]);
+        data.extend_from_slice(&[0; 6]);
+        for (name, flags) in [("vol\\p10.jpg", 0x8000u16), ("vol\\p9.jpg", 0x8004)] {
+            let head_size = 32 + name.len() as u16;
+            data.extend_from_slice(&[0, 0, RAR4_FILE_HEAD]);
+            data.extend_from_slice(&flags.to_le_bytes());
+            data.extend_from_slice(&head_size.to_le_bytes());
+            data.extend_from_slice(&3u32.to_le_bytes()); // packed
+            data.extend_from_slice(&3u32.to_le_bytes()); // unpacked
+            data.extend_from_slice(&[0; 11]); // os, crc, time, version, method
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0; 4]); // attributes
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(b"img");
+        }
+        data.extend_from_slice(&[0, 0, RAR4_END_HEAD, 0, 0x40, 7, 0]);
+
+        let listing = read_listing(&mut Cursor::new(data)).unwrap();
+        assert!(listing.solid);
+        assert_eq!(listing.sorted_pages(), vec!["vol/p9.jpg", "vol/p10.jpg"]);
+        assert!(listing.is_password_protected());
+
+        let encrypted = rar5_archive(false, &[("p1.jpg", false)], true);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locked.cbr");
+        std::fs::write(&path, encrypted).unwrap();
+        assert!(matches!(
+            CbrArchive::open(&path),
+            Err(ShioriError::UnsupportedFeature(msg)) if msg.contains("password protected")
+        ));
+    }
+
+    #[test]
+    fn test_oversized_fields_are_rejected() {
+        let mut fields = Rar5Fields {
+            data: b"abc",
+            pos: 1,
+        };
+        assert!(fields.bytes(usize::MAX).is_err());
+        assert_eq!(fields.bytes(2).unwrap(), b"bc");
+
+        // A data size past i64::MAX can't be skipped with a relative seek
+        let mut data = RAR5_SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        vint(9, &mut header); // unknown block type, skipped by size
+        vint(0x0002, &mut header);
+        vint(u64::MAX, &mut header);
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        vint(header.len() as u64, &mut data);
+        data.extend_from_slice(&header);
+        assert!(matches!(
+            read_listing(&mut Cursor::new(data)),
+            Err(ShioriError::InvalidFormat(_))
+        ));
+    }
+}
@@ -2,10 +2,27 @@ use crate::error::Result;
 use crate::models::{Book, Collection, SmartRule};
 use chrono::Utc;
 use rusqlite::{params, Connection, Row};
+use serde::Deserialize;
 use serde_json;
 
 pub struct CollectionService;
 
+/// Smart rules written as `{ "all": [...] }` or `{ "any": [...] }` groups
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SmartRuleGroup {
+    all: Option<Vec<SmartCondition>>,
+    any: Option<Vec<SmartCondition>>,
+}
+
+/// One condition of a rule group, e.g. `{"field":"rating","op":">=","value":4}`
+#[derive(Debug, Deserialize)]
+struct SmartCondition {
+    field: String,
+    op: String,
+    value: serde_json::Value,
+}
+
 impl CollectionService {
     // ==================== Collection CRUD ====================
 
@@ -190,7 +207,51 @@ impl CollectionService {
         Ok(())
     }
 
-    pub fn get_collection_books(conn: &Connection, collection_id: i64) -> Result<Vec<Book>> {
+    /// Books in a collection. With `include_descendants`, books from every
+    /// child collection (recursively) are merged in, deduplicated by book id.
+    pub fn get_collection_books(
+        conn: &Connection,
+        collection_id: i64,
+        include_descendants: bool,
+    ) -> Result<Vec<Book>> {
+        if !include_descendants {
+            return Self::get_direct_collection_books(conn, collection_id);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut books = Vec::new();
+        for id in Self::get_descendant_collection_ids(conn, collection_id)? {
+            for book in Self::get_direct_collection_books(conn, id)? {
+                if seen.insert(book.id) {
+                    books.push(book);
+                }
+            }
+        }
+        Ok(books)
+    }
+
+    /// The collection itself followed by all of its descendants. `UNION`
+    /// discards rows already produced, so a cycle in `parent_id` terminates.
+    fn get_descendant_collection_ids(conn: &Connection, collection_id: i64) -> Result<Vec<i64>> {
+        // Make sure the root exists so a bad id surfaces as an error
+        Self::get_collection(conn, collection_id)?;
+
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE descendants(id) AS (
+                 SELECT ?1
+                 UNION
+                 SELECT c.id FROM collections c
+                 JOIN descendants d ON c.parent_id = d.id
+             )
+             SELECT id FROM descendants",
+        )?;
+        let ids = stmt
+            .query_map(params![collection_id], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    fn get_direct_collection_books(conn: &Connection, collection_id: i64) -> Result<Vec<Book>> {
         // Check if smart collection
         let collection = Self::get_collection(conn, collection_id)?;
 
@@ -267,10 +328,64 @@ impl CollectionService {
         Ok(books)
     }
 
+    /// Parse `smart_rules` in either the legacy `SmartRule[]` form or the
+    /// grouped `{ "all" | "any": [{ field, op, value }] }` form. Grouped
+    /// conditions are normalized onto the legacy field/operator names.
+    fn parse_smart_rules(rules_json: &str) -> Result<Vec<SmartRule>> {
+        let invalid = |msg: String| {
+            crate::error::ShioriError::InvalidOperation(format!("Invalid smart rules: {}", msg))
+        };
+
+        if let Ok(rules) = serde_json::from_str::<Vec<SmartRule>>(rules_json) {
+            return Ok(rules);
+        }
+
+        let group: SmartRuleGroup =
+            serde_json::from_str(rules_json).map_err(|e| invalid(e.to_string()))?;
+        let (match_type, conditions) = match (group.all, group.any) {
+            (Some(all), None) => ("all", all),
+            (None, Some(any)) => ("any", any),
+            _ => {
+                return Err(invalid(
+                    "expected exactly one of \"all\" or \"any\"".to_string(),
+                ))
+            }
+        };
+
+        conditions
+            .into_iter()
+            .map(|cond| {
+                let field = match cond.field.as_str() {
+                    "file_format" => "format".to_string(),
+                    "rating" | "series" | "added_date" | "author" | "tag" => cond.field.clone(),
+                    other => return Err(invalid(format!("unsupported field '{}'", other))),
+                };
+                let operator = match (field.as_str(), cond.op.as_str()) {
+                    // Legacy tag "contains" is an exact name match; grouped rules mean substring
+                    ("tag", "contains") => "name_contains",
+                    (_, "=") => "equals",
+                    (_, "!=") => "not_equals",
+                    (_, ">=") => "at_least",
+                    (_, "<=") => "at_most",
+                    (_, "contains") => "contains",
+                    (_, other) => return Err(invalid(format!("unsupported operator '{}'", other))),
+                };
+                let value = match cond.value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                Ok(SmartRule {
+                    field,
+                    operator: operator.to_string(),
+                    value,
+                    match_type: match_type.to_string(),
+                })
+            })
+            .collect()
+    }
+
     fn get_books_by_smart_rules(conn: &Connection, rules_json: &str) -> Result<Vec<Book>> {
-        let rules: Vec<SmartRule> = serde_json::from_str(rules_json).map_err(|e| {
-            crate::error::ShioriError::InvalidOperation(format!("Invalid smart rules: {}", e))
-        })?;
+        let rules = Self::parse_smart_rules(rules_json)?;
 
         if rules.is_empty() {
             return Ok(Vec::new());
@@ -294,6 +409,10 @@ impl CollectionService {
                     params_vec.push(Box::new(rule.value.clone()));
                     "b.file_format != ?".to_string()
                 }
+                ("format", "contains") => {
+                    params_vec.push(Box::new(format!("%{}%", rule.value)));
+                    "b.file_format LIKE ?".to_string()
+                }
                 ("rating", "equals") => {
                     if let Ok(val) = rule.value.parse::<i32>() {
                         params_vec.push(Box::new(val));
@@ -318,6 +437,30 @@ impl CollectionService {
                         continue;
                     }
                 }
+                ("rating", "not_equals") => {
+                    if let Ok(val) = rule.value.parse::<i32>() {
+                        params_vec.push(Box::new(val));
+                        "COALESCE(b.rating, 0) != ?".to_string()
+                    } else {
+                        continue;
+                    }
+                }
+                ("rating", "at_least") => {
+                    if let Ok(val) = rule.value.parse::<i32>() {
+                        params_vec.push(Box::new(val));
+                        "b.rating >= ?".to_string()
+                    } else {
+                        continue;
+                    }
+                }
+                ("rating", "at_most") => {
+                    if let Ok(val) = rule.value.parse::<i32>() {
+                        params_vec.push(Box::new(val));
+                        "COALESCE(b.rating, 0) <= ?".to_string()
+                    } else {
+                        continue;
+                    }
+                }
                 ("rating", "is_empty") => "b.rating IS NULL".to_string(),
                 ("series", "equals") => {
                     params_vec.push(Box::new(rule.value.clone()));
@@ -327,6 +470,10 @@ impl CollectionService {
                     params_vec.push(Box::new(format!("%{}%", rule.value)));
                     "b.series LIKE ?".to_string()
                 }
+                ("series", "not_equals") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "COALESCE(b.series, '') != ?".to_string()
+                }
                 ("series", "is_empty") => "b.series IS NULL".to_string(),
                 ("series", "is_not_empty") => "b.series IS NOT NULL".to_string(),
                 ("title", "contains") => {
@@ -393,6 +540,18 @@ impl CollectionService {
                     params_vec.push(Box::new(rule.value.clone()));
                     "EXISTS (SELECT 1 FROM books_tags bt JOIN tags t ON bt.tag_id = t.id WHERE bt.book_id = b.id AND t.name = ?)".to_string()
                 }
+                ("tag", "equals") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "EXISTS (SELECT 1 FROM books_tags bt JOIN tags t ON bt.tag_id = t.id WHERE bt.book_id = b.id AND t.name = ?)".to_string()
+                }
+                ("tag", "not_equals") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "NOT EXISTS (SELECT 1 FROM books_tags bt JOIN tags t ON bt.tag_id = t.id WHERE bt.book_id = b.id AND t.name = ?)".to_string()
+                }
+                ("tag", "name_contains") => {
+                    params_vec.push(Box::new(format!("%{}%", rule.value)));
+                    "EXISTS (SELECT 1 FROM books_tags bt JOIN tags t ON bt.tag_id = t.id WHERE bt.book_id = b.id AND t.name LIKE ?)".to_string()
+                }
                 ("tag", "is_empty") => {
                     "NOT EXISTS (SELECT 1 FROM books_tags WHERE book_id = b.id)".to_string()
                 }
@@ -423,6 +582,22 @@ impl CollectionService {
                         continue;
                     }
                 }
+                ("added_date", "equals") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "date(b.added_date) = date(?)".to_string()
+                }
+                ("added_date", "not_equals") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "date(b.added_date) != date(?)".to_string()
+                }
+                ("added_date", "at_least") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "date(b.added_date) >= date(?)".to_string()
+                }
+                ("added_date", "at_most") => {
+                    params_vec.push(Box::new(rule.value.clone()));
+                    "date(b.added_date) <= date(?)".to_string()
+                }
                 _ => continue,
             };
 
@@ -434,7 +609,11 @@ impl CollectionService {
         }
 
         let connector = if match_type == "any" { " OR " } else { " AND " };
-        let where_sql = where_clauses.join(connector);
+        let where_sql = where_clauses
+            .iter()
+            .map(|clause| format!("({})", clause))
+            .collect::<Vec<_>>()
+            .join(connector);
 
         let query = format!(
             "SELECT b.id, b.uuid, b.title, b.sort_title, b.isbn, b.isbn13, b.publisher,
@@ -724,3 +903,142 @@ impl CollectionService {
         Ok(books.len() as i64)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> (crate::db::Database, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::Database::new(&dir.path().join("collections.db")).unwrap();
+        (db, dir)
+    }
+
+    fn insert_book(conn: &Connection, uuid: &str, title: &str, series: Option<&str>, rating: i32) {
+        conn.execute(
+            "INSERT INTO books (uuid, title, file_path, file_format, series, rating)
+             VALUES (?1, ?2, ?3, 'epub', ?4, ?5)",
+            params![uuid, title, format!("/books/{}.epub", uuid), series, rating],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_smart_collection_all_rules() {
+        let (db, _dir) = setup_db();
+        let conn = db.get_connection().unwrap();
+        insert_book(&conn, "u1", "Dune", Some("Dune"), 5);
+        insert_book(&conn, "u2", "Dune Messiah", Some("Dune"), 3);
+        insert_book(&conn, "u3", "Hyperion", Some("Hyperion Cantos"), 5);
+
+        let rules = r#"{ "all": [
+            {"field": "rating", "op": ">=", "value": 4},
+            {"field": "series", "op": "=", "value": "Dune"}
+        ] }"#;
+        let collection = CollectionService::create_collection(
+            &conn,
+            "Best of Dune",
+            None,
+            None,
+            true,
+            Some(rules),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let books =
+            CollectionService::get_collection_books(&conn, collection.id.unwrap(), false).unwrap();
+        let titles: Vec<_> = books.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, vec!["Dune"]);
+    }
+
+    #[test]
+    fn test_parse_smart_rules_formats() {
+        let legacy =
+            r#"[{"field":"rating","operator":"greater_than","value":"3","match_type":"any"}]"#;
+        let rules = CollectionService::parse_smart_rules(legacy).unwrap();
+        assert_eq!(rules[0].operator, "greater_than");
+
+        let grouped = r#"{"any":[{"field":"file_format","op":"!=","value":"pdf"},{"field":"tag","op":"contains","value":"sci"}]}"#;
+        let rules = CollectionService::parse_smart_rules(grouped).unwrap();
+        assert_eq!(rules[0].field, "format");
+        assert_eq!(rules[0].operator, "not_equals");
+        assert_eq!(rules[1].operator, "name_contains");
+        assert!(rules.iter().all(|r| r.match_type == "any"));
+
+        assert!(CollectionService::parse_smart_rules(
+            r#"{"all":[{"field":"isbn","op":"=","value":"1"}]}"#
+        )
+        .is_err());
+        assert!(CollectionService::parse_smart_rules(
+            r#"{"all":[{"field":"rating","op":"~","value":1}]}"#
+        )
+        .is_err());
+    }
+
+    fn create_plain(conn: &Connection, name: &str, parent_id: Option<i64>) -> i64 {
+        CollectionService::create_collection(
+            conn, name, None, parent_id, false, None, None, None, None,
+        )
+        .unwrap()
+        .id
+        .unwrap()
+    }
+
+    fn book_id(conn: &Connection, uuid: &str) -> i64 {
+        conn.query_row(
+            "SELECT id FROM books WHERE uuid = ?1",
+            params![uuid],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collection_books_include_descendants() {
+        let (db, _dir) = setup_db();
+        let conn = db.get_connection().unwrap();
+        insert_book(&conn, "u1", "Dune", Some("Dune"), 5);
+        insert_book(&conn, "u2", "Dune Messiah", Some("Dune"), 3);
+        insert_book(&conn, "u3", "Hyperion", Some("Hyperion Cantos"), 5);
+
+        let root = create_plain(&conn, "Sci-Fi", None);
+        let child = create_plain(&conn, "Space Opera", Some(root));
+        let grandchild = create_plain(&conn, "Desert Planets", Some(child));
+
+        let (b1, b2, b3) = (
+            book_id(&conn, "u1"),
+            book_id(&conn, "u2"),
+            book_id(&conn, "u3"),
+        );
+        CollectionService::add_books_to_collection(&conn, root, vec![b1]).unwrap();
+        CollectionService::add_books_to_collection(&conn, child, vec![b3]).unwrap();
+        CollectionService::add_books_to_collection(&conn, grandchild, vec![b1, b2]).unwrap();
+
+        let direct = CollectionService::get_collection_books(&conn, root, false).unwrap();
+        assert_eq!(direct.len(), 1);
+
+        let all = CollectionService::get_collection_books(&conn, root, true).unwrap();
+        let mut ids: Vec<_> = all.iter().map(|b| b.id.unwrap()).collect();
+        ids.sort();
+        assert_eq!(ids, {
+            let mut expected = vec![b1, b2, b3];
+            expected.sort();
+            expected
+        });
+
+        let mid = CollectionService::get_collection_books(&conn, child, true).unwrap();
+        assert_eq!(mid.len(), 3);
+
+        // A parent_id cycle must not recurse forever
+        conn.execute(
+            "UPDATE collections SET parent_id = ?1 WHERE id = ?2",
+            params![grandchild, root],
+        )
+        .unwrap();
+        let cyclic = CollectionService::get_collection_books(&conn, child, true).unwrap();
+        assert_eq!(cyclic.len(), 3);
+    }
+}
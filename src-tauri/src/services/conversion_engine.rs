@@ -7,6 +7,7 @@
 /// - Soft cancellation via DashSet
 /// - Unified capability matrix
 use chrono::{DateTime, Utc};
+use dashmap::mapref::entry::Entry;
 use dashmap::{DashMap, DashSet};
 use printpdf::*;
 use serde::{Deserialize, Serialize};
@@ -14,8 +15,9 @@ use std::collections::VecDeque;
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::Emitter;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -62,6 +64,16 @@ pub enum ConversionStatus {
     Cancelled,
 }
 
+impl ConversionStatus {
+    /// Completed, Failed and Cancelled jobs never change state again
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ConversionStatus::Completed | ConversionStatus::Failed | ConversionStatus::Cancelled
+        )
+    }
+}
+
 impl std::fmt::Display for ConversionStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -88,6 +100,314 @@ pub struct ConversionJob {
     pub created_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// OCR scanned PDFs whose text layer is missing (PDF → TXT/EPUB only)
+    #[serde(default)]
+    pub ocr: bool,
+    /// Converter settings, usually taken from a conversion profile
+    #[serde(default)]
+    pub options: ConversionOptions,
+}
+
+impl ConversionJob {
+    /// Reset a job that was interrupted mid-conversion so it runs from scratch
+    fn requeue(&mut self) {
+        self.status = ConversionStatus::Queued;
+        self.progress = 0.0;
+        self.error = None;
+        self.started_at = None;
+    }
+}
+
+/// Paper size for natively rendered PDF output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfPageSize {
+    #[default]
+    A4,
+    Letter,
+    A5,
+}
+
+impl PdfPageSize {
+    /// Width and height of the page
+    fn dimensions(self) -> (Mm, Mm) {
+        match self {
+            PdfPageSize::A4 => (Mm(210.0), Mm(297.0)),
+            PdfPageSize::Letter => (Mm(215.9), Mm(279.4)),
+            PdfPageSize::A5 => (Mm(148.0), Mm(210.0)),
+        }
+    }
+}
+
+/// Builtin typeface for natively rendered PDFs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfFont {
+    #[default]
+    Times,
+    Helvetica,
+    Courier,
+}
+
+impl PdfFont {
+    fn regular(self) -> BuiltinFont {
+        match self {
+            PdfFont::Times => BuiltinFont::TimesRoman,
+            PdfFont::Helvetica => BuiltinFont::Helvetica,
+            PdfFont::Courier => BuiltinFont::Courier,
+        }
+    }
+
+    fn bold(self) -> BuiltinFont {
+        match self {
+            PdfFont::Times => BuiltinFont::TimesBold,
+            PdfFont::Helvetica => BuiltinFont::HelveticaBold,
+            PdfFont::Courier => BuiltinFont::CourierBold,
+        }
+    }
+
+    /// Average glyph advance as a fraction of the font size, used to wrap
+    /// lines without measuring every glyph
+    fn average_advance(self) -> f32 {
+        match self {
+            PdfFont::Times => 0.515,
+            PdfFont::Helvetica => 0.55,
+            PdfFont::Courier => 0.6,
+        }
+    }
+}
+
+/// Page geometry and type for natively rendered PDFs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfOutputOptions {
+    pub page_size: PdfPageSize,
+    /// Applied to all four sides
+    pub margin_mm: f32,
+    /// In points
+    pub font_size: f32,
+    pub font: PdfFont,
+}
+
+impl Default for PdfOutputOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PdfPageSize::A4,
+            margin_mm: 15.0,
+            font_size: 11.0,
+            font: PdfFont::Times,
+        }
+    }
+}
+
+impl PdfOutputOptions {
+    const FONT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 6.0..=36.0;
+    /// Widest margin that still leaves room for text on an A5 page
+    const MAX_MARGIN_MM: f32 = 50.0;
+
+    fn validate(&self) -> FormatResult<()> {
+        if !Self::FONT_SIZE_RANGE.contains(&self.font_size) {
+            return Err(FormatError::ValidationError(format!(
+                "PDF font size must be between {} and {} pt",
+                Self::FONT_SIZE_RANGE.start(),
+                Self::FONT_SIZE_RANGE.end()
+            )));
+        }
+        if !(0.0..=Self::MAX_MARGIN_MM).contains(&self.margin_mm) {
+            return Err(FormatError::ValidationError(format!(
+                "PDF margin must be between 0 and {} mm",
+                Self::MAX_MARGIN_MM
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Chapter boundaries for TXT → EPUB output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChapterSplitMode {
+    /// Heading heuristics, or `chapter_pattern` when one is set
+    #[default]
+    Auto,
+    /// Keep the whole text as a single chapter
+    None,
+}
+
+/// Converter settings a job carries; stored as a profile's `options_json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversionOptions {
+    /// Layout of natively rendered PDFs (TXT, FB2, DOCX and EPUB → PDF)
+    #[serde(flatten)]
+    pub pdf: PdfOutputOptions,
+    pub chapter_split: ChapterSplitMode,
+    /// Regex a whole paragraph must match to start a chapter (TXT → EPUB)
+    pub chapter_pattern: Option<String>,
+    /// Heading score (1–5) a paragraph needs to start a chapter when no
+    /// pattern is set; lower splits more eagerly. Defaults to
+    /// `epub_builder::DEFAULT_HEADING_THRESHOLD`
+    pub heading_threshold: Option<u8>,
+}
+
+impl ConversionOptions {
+    fn validate(&self) -> FormatResult<()> {
+        self.pdf.validate()?;
+        self.txt_chapter_split().map(|_| ())
+    }
+
+    /// Highest score `epub_builder::heading_score` gives
+    const MAX_HEADING_THRESHOLD: u8 = 5;
+
+    fn txt_chapter_split(&self) -> FormatResult<crate::conversion::txt::ChapterSplit> {
+        use crate::conversion::txt::ChapterSplit;
+        use crate::services::epub_builder::DEFAULT_HEADING_THRESHOLD;
+
+        let threshold = self.heading_threshold.unwrap_or(DEFAULT_HEADING_THRESHOLD);
+        if !(1..=Self::MAX_HEADING_THRESHOLD).contains(&threshold) {
+            return Err(FormatError::ValidationError(format!(
+                "Heading threshold must be between 1 and {}",
+                Self::MAX_HEADING_THRESHOLD
+            )));
+        }
+        let pattern = self
+            .chapter_pattern
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
+        match (self.chapter_split, pattern) {
+            (ChapterSplitMode::None, _) => Ok(ChapterSplit::None),
+            (ChapterSplitMode::Auto, Some(pattern)) => regex::Regex::new(pattern)
+                .map(ChapterSplit::Pattern)
+                .map_err(|e| {
+                    FormatError::ValidationError(format!("Invalid chapter pattern: {}", e))
+                }),
+            (ChapterSplitMode::Auto, None) => Ok(ChapterSplit::Auto(threshold)),
+        }
+    }
+}
+
+/// A named conversion preset from `conversion_profiles`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionProfile {
+    pub id: i64,
+    pub name: String,
+    pub source_format: String,
+    pub target_format: String,
+    pub options: ConversionOptions,
+    pub created_at: String,
+}
+
+fn profile_from_row(row: &rusqlite::Row) -> rusqlite::Result<ConversionProfile> {
+    let options_json: Option<String> = row.get(4)?;
+    // Missing fields take their defaults, but options that don't parse at all
+    // fail rather than converting with settings the user never chose
+    let options = match options_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        None => ConversionOptions::default(),
+    };
+    Ok(ConversionProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        source_format: row.get(2)?,
+        target_format: row.get(3)?,
+        options,
+        created_at: row.get(5)?,
+    })
+}
+
+pub fn create_conversion_profile(
+    conn: &rusqlite::Connection,
+    name: &str,
+    source_format: &str,
+    target_format: &str,
+    options: ConversionOptions,
+) -> crate::error::Result<ConversionProfile> {
+    use crate::error::ShioriError;
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ShioriError::Validation(
+            "Profile name must not be empty".to_string(),
+        ));
+    }
+    let source_format = source_format.trim().to_lowercase();
+    let target_format = target_format.trim().to_lowercase();
+    if !can_convert(&source_format, &target_format) {
+        return Err(ShioriError::Validation(format!(
+            "Cannot convert {} to {}",
+            source_format, target_format
+        )));
+    }
+    options
+        .validate()
+        .map_err(|e| ShioriError::Validation(e.to_string()))?;
+
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM conversion_profiles WHERE name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(ShioriError::Validation(format!(
+            "A conversion profile named '{}' already exists",
+            name
+        )));
+    }
+
+    conn.execute(
+        "INSERT INTO conversion_profiles (name, source_format, target_format, options_json)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![
+            name,
+            source_format,
+            target_format,
+            serde_json::to_string(&options)?
+        ],
+    )?;
+    load_conversion_profile(conn, name)
+}
+
+pub fn load_conversion_profile(
+    conn: &rusqlite::Connection,
+    name: &str,
+) -> crate::error::Result<ConversionProfile> {
+    use rusqlite::OptionalExtension;
+
+    conn.query_row(
+        "SELECT id, name, source_format, target_format, options_json, created_at
+         FROM conversion_profiles WHERE name = ?1",
+        [name.trim()],
+        profile_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| {
+        crate::error::ShioriError::Validation(format!("No conversion profile named '{}'", name))
+    })
+}
+
+pub fn list_conversion_profiles(
+    conn: &rusqlite::Connection,
+) -> crate::error::Result<Vec<ConversionProfile>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, source_format, target_format, options_json, created_at
+         FROM conversion_profiles ORDER BY name COLLATE NOCASE",
+    )?;
+    let profiles = stmt
+        .query_map([], profile_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(profiles)
+}
+
+/// Returns false when no profile had that id
+pub fn delete_conversion_profile(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> crate::error::Result<bool> {
+    let deleted = conn.execute("DELETE FROM conversion_profiles WHERE id = ?1", [id])?;
+    Ok(deleted > 0)
 }
 
 // ──────────────────────────────────────────────────────────────────────────
@@ -96,31 +416,271 @@ pub struct ConversionJob {
 
 type Queue = VecDeque<String>; // job IDs
 
+/// Allowed worker counts, matching the CHECK on `conversion_settings.worker_thread_count`
+pub const WORKER_COUNT_RANGE: std::ops::RangeInclusive<usize> = 1..=8;
+
+/// Worker bookkeeping shared with the workers themselves. `running` counts
+/// workers that haven't exited yet, including ones still finishing a job
+/// after the target was lowered.
+#[derive(Debug, Default)]
+struct WorkerPool {
+    target: usize,
+    running: usize,
+    next_id: usize,
+    started: bool,
+}
+
+/// Allowed progress event rates, matching the CHECK on
+/// `conversion_settings.progress_events_per_second`
+pub const PROGRESS_RATE_RANGE: std::ops::RangeInclusive<u32> = 1..=60;
+
+/// Coalesces `conversion:progress` events so a job emits at most `rate`
+/// progress updates per second. The newest coalesced update goes out when
+/// the window ends, so the last value before a pause is never lost. Status
+/// changes, including the terminal Completed/Failed/Cancelled states, always
+/// go through immediately.
+struct ProgressThrottle {
+    rate: AtomicU32,
+    /// Per job: the last event sent and any update held back since
+    last_emit: DashMap<String, LastEmit>,
+}
+
+struct LastEmit {
+    at: Instant,
+    status: ConversionStatus,
+    /// Newest coalesced update, sent when the window ends
+    pending: Option<ConversionJob>,
+    /// A flush for `pending` is already scheduled
+    flush_scheduled: bool,
+}
+
+/// What `ProgressThrottle` decided for one update
+#[derive(Debug, PartialEq)]
+enum Throttled {
+    /// Send the update now
+    Emit,
+    /// Held back; schedule a flush after the delay to send it
+    Defer(Duration),
+    /// Held back, replacing an update an earlier flush will send
+    Coalesced,
+}
+
+impl ProgressThrottle {
+    fn new(rate: u32) -> Self {
+        let (min, max) = PROGRESS_RATE_RANGE.into_inner();
+        Self {
+            rate: AtomicU32::new(rate.clamp(min, max)),
+            last_emit: DashMap::new(),
+        }
+    }
+
+    fn set_rate(&self, rate: u32) {
+        let (min, max) = PROGRESS_RATE_RANGE.into_inner();
+        self.rate.store(rate.clamp(min, max), Ordering::Relaxed);
+    }
+
+    fn rate(&self) -> u32 {
+        self.rate.load(Ordering::Relaxed)
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_millis(1000 / self.rate().max(1) as u64)
+    }
+
+    /// Decide what happens to `job`'s update arriving at `now`
+    fn offer_at(&self, job: &ConversionJob, now: Instant) -> Throttled {
+        if job.status.is_terminal() {
+            self.forget(&job.id);
+            return Throttled::Emit;
+        }
+
+        let interval = self.interval();
+        match self.last_emit.entry(job.id.clone()) {
+            Entry::Occupied(mut last) => {
+                let last = last.get_mut();
+                if last.status == job.status && now.duration_since(last.at) < interval {
+                    last.pending = Some(job.clone());
+                    if last.flush_scheduled {
+                        return Throttled::Coalesced;
+                    }
+                    last.flush_scheduled = true;
+                    return Throttled::Defer(interval - now.duration_since(last.at));
+                }
+                last.at = now;
+                last.status = job.status.clone();
+                last.pending = None;
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(LastEmit {
+                    at: now,
+                    status: job.status.clone(),
+                    pending: None,
+                    flush_scheduled: false,
+                });
+            }
+        }
+        Throttled::Emit
+    }
+
+    /// Send a job's held-back update through `emit` once its window has
+    /// ended. Returns how much longer to wait when it hasn't yet. `emit` runs
+    /// under the job's entry, so a terminal event can't overtake it.
+    fn flush_at(
+        &self,
+        job_id: &str,
+        now: Instant,
+        emit: impl FnOnce(&ConversionJob),
+    ) -> Option<Duration> {
+        let mut last = self.last_emit.get_mut(job_id)?;
+        if last.pending.is_some() {
+            let due = last.at + self.interval();
+            if now < due {
+                return Some(due - now);
+            }
+            if let Some(job) = last.pending.take() {
+                emit(&job);
+            }
+            last.at = now;
+        }
+        last.flush_scheduled = false;
+        None
+    }
+
+    /// Drop a job's state once it finished, failed, was cancelled or requeued
+    fn forget(&self, job_id: &str) {
+        self.last_emit.remove(job_id);
+    }
+}
+
+/// Emit `conversion:progress` for `job` unless the throttle coalesces it, in
+/// which case the newest held-back update is sent when the window ends
+fn emit_job_progress(
+    handle: &tauri::AppHandle,
+    throttle: &Arc<ProgressThrottle>,
+    job: &ConversionJob,
+) {
+    match throttle.offer_at(job, Instant::now()) {
+        Throttled::Emit => send_progress(handle, job),
+        Throttled::Coalesced => {}
+        Throttled::Defer(delay) => {
+            let handle = handle.clone();
+            let throttle = Arc::clone(throttle);
+            let job_id = job.id.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut delay = delay;
+                loop {
+                    tokio::time::sleep(delay).await;
+                    match throttle
+                        .flush_at(&job_id, Instant::now(), |job| send_progress(&handle, job))
+                    {
+                        Some(remaining) => delay = remaining,
+                        None => break,
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn send_progress(handle: &tauri::AppHandle, job: &ConversionJob) {
+    if let Err(e) = handle.emit("conversion:progress", job) {
+        log::warn!("[ConversionEngine] Failed to emit progress event: {}", e);
+    }
+}
+
 pub struct ConversionEngine {
     queue: Arc<Mutex<Queue>>,
     tracker: Arc<DashMap<String, ConversionJob>>,
+    progress_throttle: Arc<ProgressThrottle>,
     cancelled: Arc<DashSet<String>>,
+    // Jobs stopped by shutdown rather than the user; they go back to Queued
+    interrupted: Arc<DashSet<String>>,
     shutdown: Arc<Mutex<bool>>,
-    worker_count: usize,
-    workers_started: std::sync::Mutex<bool>,
+    workers: Arc<std::sync::Mutex<WorkerPool>>,
     app_handle: tauri::AppHandle,
     db: Option<Database>,
 }
 
 impl ConversionEngine {
     pub fn new(worker_count: usize, app_handle: tauri::AppHandle) -> Self {
+        let (min, max) = WORKER_COUNT_RANGE.into_inner();
         Self {
             queue: Arc::new(Mutex::new(VecDeque::new())),
             tracker: Arc::new(DashMap::new()),
+            progress_throttle: Arc::new(ProgressThrottle::new(4)),
             cancelled: Arc::new(DashSet::new()),
+            interrupted: Arc::new(DashSet::new()),
             shutdown: Arc::new(Mutex::new(false)),
-            worker_count,
-            workers_started: std::sync::Mutex::new(false),
+            workers: Arc::new(std::sync::Mutex::new(WorkerPool {
+                target: worker_count.clamp(min, max),
+                ..Default::default()
+            })),
             app_handle,
             db: None,
         }
     }
 
+    /// `conversion_settings.worker_thread_count`, or the schema default of 2
+    /// when it can't be read
+    pub fn stored_worker_count(db: &Database) -> usize {
+        db.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT worker_thread_count FROM conversion_settings WHERE id = 1",
+                    [],
+                    |row| row.get::<_, Option<i64>>(0),
+                )
+                .ok()
+                .flatten()
+            })
+            .map(|n| {
+                let (min, max) = WORKER_COUNT_RANGE.into_inner();
+                (n.max(0) as usize).clamp(min, max)
+            })
+            .unwrap_or(2)
+    }
+
+    /// `conversion_settings.progress_events_per_second`, or the schema
+    /// default of 4 when it can't be read
+    pub fn stored_progress_rate(db: &Database) -> u32 {
+        db.get_connection()
+            .ok()
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT progress_events_per_second FROM conversion_settings WHERE id = 1",
+                    [],
+                    |row| row.get::<_, Option<i64>>(0),
+                )
+                .ok()
+                .flatten()
+            })
+            .map(|n| {
+                let (min, max) = PROGRESS_RATE_RANGE.into_inner();
+                (n.max(0) as u32).clamp(min, max)
+            })
+            .unwrap_or(4)
+    }
+
+    pub fn progress_rate(&self) -> u32 {
+        self.progress_throttle.rate()
+    }
+
+    /// Limit each job to `rate` progress events per second; status changes
+    /// are never held back
+    pub fn set_progress_rate(&self, rate: u32) -> FormatResult<()> {
+        if !PROGRESS_RATE_RANGE.contains(&rate) {
+            return Err(FormatError::ValidationError(format!(
+                "Progress event rate must be between {} and {}, got {}",
+                PROGRESS_RATE_RANGE.start(),
+                PROGRESS_RATE_RANGE.end(),
+                rate
+            )));
+        }
+        self.progress_throttle.set_rate(rate);
+        Ok(())
+    }
+
     /// Set the database pool for job persistence
     pub fn set_database(&mut self, db: Database) {
         self.db = Some(db);
@@ -129,21 +689,90 @@ impl ConversionEngine {
     // ── Worker management ─────────────────────────────────────────────────
 
     fn ensure_workers(&self) {
-        let mut started = self.workers_started.lock().unwrap();
-        if !*started {
-            for id in 0..self.worker_count {
-                let queue = self.queue.clone();
-                let tracker = self.tracker.clone();
-                let cancelled = self.cancelled.clone();
-                let shutdown = self.shutdown.clone();
-                let handle = self.app_handle.clone();
-                let db = self.db.clone();
-                tokio::spawn(async move {
-                    Self::worker_loop(id, queue, tracker, cancelled, shutdown, handle, db).await;
-                });
-            }
-            *started = true;
-            log::info!("[ConversionEngine] {} workers started", self.worker_count);
+        let mut pool = self.workers.lock().unwrap();
+        if !pool.started {
+            pool.started = true;
+            self.spawn_workers(&mut pool);
+            log::info!("[ConversionEngine] {} workers started", pool.target);
+        }
+    }
+
+    /// Spawn workers until `target` are running
+    fn spawn_workers(&self, pool: &mut WorkerPool) {
+        while pool.running < pool.target {
+            let id = pool.next_id;
+            pool.next_id += 1;
+            pool.running += 1;
+
+            let queue = self.queue.clone();
+            let tracker = self.tracker.clone();
+            let throttle = self.progress_throttle.clone();
+            let cancelled = self.cancelled.clone();
+            let interrupted = self.interrupted.clone();
+            let shutdown = self.shutdown.clone();
+            let workers = self.workers.clone();
+            let handle = self.app_handle.clone();
+            let db = self.db.clone();
+            tokio::spawn(async move {
+                Self::worker_loop(
+                    id,
+                    queue,
+                    tracker,
+                    throttle,
+                    cancelled,
+                    interrupted,
+                    shutdown,
+                    workers,
+                    handle,
+                    db,
+                )
+                .await;
+            });
+        }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.lock().unwrap().target
+    }
+
+    /// Change the number of workers at runtime. Extra workers are spawned
+    /// right away; surplus ones finish their current job and exit the next
+    /// time they look for work. Before the first job is submitted this only
+    /// records the count.
+    pub fn set_worker_count(&self, count: usize) -> FormatResult<()> {
+        if !WORKER_COUNT_RANGE.contains(&count) {
+            return Err(FormatError::ValidationError(format!(
+                "Worker count must be between {} and {}, got {}",
+                WORKER_COUNT_RANGE.start(),
+                WORKER_COUNT_RANGE.end(),
+                count
+            )));
+        }
+
+        let mut pool = self.workers.lock().unwrap();
+        let previous = pool.target;
+        pool.target = count;
+        if pool.started {
+            self.spawn_workers(&mut pool);
+        }
+        log::info!(
+            "[ConversionEngine] Worker count changed from {} to {}",
+            previous,
+            count
+        );
+        Ok(())
+    }
+
+    /// Called by a worker between jobs: true if it should exit because the
+    /// pool is above its target. The running count drops as it decides, so
+    /// exactly the surplus retires.
+    fn retire_if_surplus(workers: &std::sync::Mutex<WorkerPool>) -> bool {
+        let mut pool = workers.lock().unwrap();
+        if pool.running > pool.target {
+            pool.running -= 1;
+            true
+        } else {
+            false
         }
     }
 
@@ -155,9 +784,74 @@ impl ConversionEngine {
         target_format: &str,
         output_dir: Option<PathBuf>,
         book_id: Option<i64>,
+        ocr: bool,
+        options: ConversionOptions,
+    ) -> FormatResult<String> {
+        self.ensure_workers();
+        let job =
+            Self::prepare_job(&source, target_format, output_dir, book_id, ocr, options).await?;
+        Ok(self.enqueue(job).await)
+    }
+
+    /// Convert `source` with the target format and options of a saved
+    /// conversion profile. The output lands next to the source.
+    pub async fn submit_with_profile(
+        &self,
+        source: PathBuf,
+        profile_name: &str,
+        book_id: Option<i64>,
     ) -> FormatResult<String> {
+        let db = self.db.as_ref().ok_or_else(|| {
+            FormatError::ConversionError("Conversion profiles need a database".to_string())
+        })?;
         self.ensure_workers();
+        let job = Self::prepare_profile_job(db, &source, profile_name, book_id).await?;
+        Ok(self.enqueue(job).await)
+    }
+
+    /// Build the job a profile describes for `source`, checking that the
+    /// profile applies to the source's format
+    async fn prepare_profile_job(
+        db: &Database,
+        source: &Path,
+        profile_name: &str,
+        book_id: Option<i64>,
+    ) -> FormatResult<ConversionJob> {
+        let profile = db
+            .get_connection()
+            .map_err(|e| FormatError::ConversionError(e.to_string()))
+            .and_then(|conn| {
+                load_conversion_profile(&conn, profile_name)
+                    .map_err(|e| FormatError::ValidationError(e.to_string()))
+            })?;
+
+        let job = Self::prepare_job(
+            source,
+            &profile.target_format,
+            None,
+            book_id,
+            false,
+            profile.options,
+        )
+        .await?;
+        if job.source_format != profile.source_format {
+            return Err(FormatError::ValidationError(format!(
+                "Profile '{}' converts {} files, but this book is {}",
+                profile.name, profile.source_format, job.source_format
+            )));
+        }
+        Ok(job)
+    }
 
+    /// Validate a conversion request and build its Queued job
+    async fn prepare_job(
+        source: &Path,
+        target_format: &str,
+        output_dir: Option<PathBuf>,
+        book_id: Option<i64>,
+        ocr: bool,
+        options: ConversionOptions,
+    ) -> FormatResult<ConversionJob> {
         // Verify source file exists before queueing
         if !source.exists() {
             return Err(FormatError::ConversionError(format!(
@@ -166,7 +860,7 @@ impl ConversionEngine {
             )));
         }
 
-        let fmt_info = detect_format(&source).await?;
+        let fmt_info = detect_format(source).await?;
         let source_format = fmt_info.format.clone();
 
         if !can_convert(&source_format, target_format) {
@@ -175,6 +869,7 @@ impl ConversionEngine {
                 to: target_format.to_string(),
             });
         }
+        options.validate()?;
 
         let target_path = if let Some(dir) = output_dir {
             let stem = source
@@ -186,9 +881,8 @@ impl ConversionEngine {
             source.with_extension(target_format)
         };
 
-        let job_id = Uuid::new_v4().to_string();
-        let job = ConversionJob {
-            id: job_id.clone(),
+        Ok(ConversionJob {
+            id: Uuid::new_v4().to_string(),
             book_id,
             source_path: source.to_string_lossy().to_string(),
             target_path: target_path.to_string_lossy().to_string(),
@@ -200,8 +894,14 @@ impl ConversionEngine {
             created_at: Utc::now(),
             started_at: None,
             completed_at: None,
-        };
+            ocr,
+            options,
+        })
+    }
 
+    /// Track, queue and persist a prepared job; returns its id
+    async fn enqueue(&self, job: ConversionJob) -> String {
+        let job_id = job.id.clone();
         self.tracker.insert(job_id.clone(), job.clone());
         self.queue.lock().await.push_back(job_id.clone());
 
@@ -220,7 +920,7 @@ impl ConversionEngine {
             job.source_format,
             job.target_format
         );
-        Ok(job_id)
+        job_id
     }
 
     pub fn get_job_status(&self, job_id: &str) -> Option<ConversionJob> {
@@ -249,43 +949,64 @@ impl ConversionEngine {
         ))
     }
 
-    #[allow(dead_code)]
-    pub async fn shutdown(&self) {
+    /// Stop the workers before the app exits. Running conversions are
+    /// interrupted at their next cancellation check and put back to Queued;
+    /// any still running after `timeout` are requeued in the DB anyway, so
+    /// `restore_from_db` resumes each one once on the next start.
+    pub async fn shutdown(&self, timeout: Duration) {
         *self.shutdown.lock().await = true;
         log::info!("[ConversionEngine] Shutdown signal sent");
+
+        for job in self.tracker.iter() {
+            if job.status == ConversionStatus::Processing {
+                self.interrupted.insert(job.id.clone());
+                self.cancelled.insert(job.id.clone());
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline
+            && self
+                .tracker
+                .iter()
+                .any(|job| job.status == ConversionStatus::Processing)
+        {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let conn = self.db.as_ref().and_then(|db| db.get_connection().ok());
+        let stuck = Self::requeue_interrupted(&self.tracker, conn.as_deref());
+        if stuck > 0 {
+            log::warn!(
+                "[ConversionEngine] {} jobs still running at exit were requeued",
+                stuck
+            );
+        }
+    }
+
+    /// Requeue every job still marked Processing, persisting each one
+    fn requeue_interrupted(
+        tracker: &DashMap<String, ConversionJob>,
+        conn: Option<&rusqlite::Connection>,
+    ) -> usize {
+        let mut count = 0;
+        for mut job in tracker.iter_mut() {
+            if job.status != ConversionStatus::Processing {
+                continue;
+            }
+            job.requeue();
+            if let Some(conn) = conn {
+                Self::persist_job(job.value(), conn);
+            }
+            count += 1;
+        }
+        count
     }
 
     // ── Restore jobs from DB on startup ──────────────────────────────────
 
     pub fn restore_from_db(&self, conn: &rusqlite::Connection) {
-        fn load_jobs(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<ConversionJob>> {
-            let mut stmt = conn.prepare(
-                "SELECT id, book_id, source_path, target_path, source_format, target_format,
-                        status, progress, error_message, created_at
-                 FROM conversion_jobs
-                 WHERE status IN ('Queued', 'Processing')
-                 ORDER BY created_at ASC",
-            )?;
-            let rows = stmt.query_map([], |row| {
-                Ok(ConversionJob {
-                    id: row.get(0)?,
-                    book_id: row.get(1)?,
-                    source_path: row.get(2)?,
-                    target_path: row.get(3)?,
-                    source_format: row.get(4)?,
-                    target_format: row.get(5)?,
-                    status: ConversionStatus::Queued, // always re-queue
-                    progress: 0.0,
-                    error: None,
-                    created_at: Utc::now(),
-                    started_at: None,
-                    completed_at: None,
-                })
-            })?;
-            rows.collect()
-        }
-
-        match load_jobs(conn) {
+        match load_pending_jobs(conn) {
             Ok(jobs) => {
                 log::info!("[ConversionEngine] Restoring {} jobs from DB", jobs.len());
                 let rt_handle = tauri::async_runtime::handle();
@@ -322,9 +1043,7 @@ impl ConversionEngine {
     // ── Event emission ────────────────────────────────────────────────────
 
     fn emit_progress(&self, job: &ConversionJob) {
-        if let Err(e) = self.app_handle.emit("conversion:progress", job) {
-            log::warn!("[ConversionEngine] Failed to emit progress event: {}", e);
-        }
+        emit_job_progress(&self.app_handle, &self.progress_throttle, job);
     }
 
     // ── DB persistence ────────────────────────────────────────────────────
@@ -334,8 +1053,8 @@ impl ConversionEngine {
         if let Err(e) = conn.execute(
             "INSERT OR REPLACE INTO conversion_jobs
              (id, book_id, source_path, target_path, source_format, target_format,
-              status, progress, error_message, updated_at)
-             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,CURRENT_TIMESTAMP)",
+              status, progress, error_message, ocr, options_json, updated_at)
+             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,CURRENT_TIMESTAMP)",
             rusqlite::params![
                 job.id,
                 job.book_id,
@@ -346,6 +1065,8 @@ impl ConversionEngine {
                 status_str,
                 job.progress,
                 job.error,
+                job.ocr,
+                serde_json::to_string(&job.options).unwrap_or_else(|_| "{}".to_string()),
             ],
         ) {
             log::error!(
@@ -362,8 +1083,11 @@ impl ConversionEngine {
         worker_id: usize,
         queue: Arc<Mutex<Queue>>,
         tracker: Arc<DashMap<String, ConversionJob>>,
+        throttle: Arc<ProgressThrottle>,
         cancelled: Arc<DashSet<String>>,
+        interrupted: Arc<DashSet<String>>,
         shutdown: Arc<Mutex<bool>>,
+        workers: Arc<std::sync::Mutex<WorkerPool>>,
         handle: tauri::AppHandle,
         db: Option<Database>,
     ) {
@@ -381,6 +1105,14 @@ impl ConversionEngine {
         loop {
             if *shutdown.lock().await {
                 log::info!("[ConversionWorker-{}] Shutting down", worker_id);
+                workers.lock().unwrap().running -= 1;
+                break;
+            }
+            if Self::retire_if_surplus(&workers) {
+                log::info!(
+                    "[ConversionWorker-{}] Stopping, worker count lowered",
+                    worker_id
+                );
                 break;
             }
 
@@ -408,7 +1140,7 @@ impl ConversionEngine {
                     j.status = ConversionStatus::Processing;
                     j.started_at = Some(Utc::now());
                     j.progress = 5.0;
-                    handle.emit("conversion:progress", j.value()).ok();
+                    emit_job_progress(&handle, &throttle, j.value());
                     persist(j.value());
                 }
 
@@ -417,6 +1149,7 @@ impl ConversionEngine {
                 let target = PathBuf::from(&job.target_path);
                 let cb_handle = handle.clone();
                 let cb_tracker = tracker.clone();
+                let cb_throttle = throttle.clone();
                 let cb_job_id = job_id.clone();
                 let cb_db = db.clone();
                 
@@ -424,10 +1157,15 @@ impl ConversionEngine {
                 
                 let progress_cb = std::sync::Arc::new(move |pct: u8, _msg: &str| {
                     if let Some(mut j) = cb_tracker.get_mut(&cb_job_id) {
+                        // Cancelled jobs report until their next cancellation
+                        // check; the Cancelled event already went out
+                        if j.status.is_terminal() {
+                            return;
+                        }
                         j.progress = pct as f32;
                         
-                        // Emit event frequently to frontend
-                        let _ = cb_handle.emit("conversion:progress", j.value());
+                        // Coalesced to the configured events per second
+                        emit_job_progress(&cb_handle, &cb_throttle, j.value());
                         
                         // Throttle database persistence to max once per second
                         let now = chrono::Utc::now().timestamp_millis();
@@ -452,6 +1190,8 @@ impl ConversionEngine {
                     &job_id,
                     db.as_ref(),
                     Some(progress_cb),
+                    job.ocr,
+                    &job.options,
                 )
                 .await;
 
@@ -474,6 +1214,17 @@ impl ConversionEngine {
                                 )
                                 .ok();
                         }
+                        Err(_) if interrupted.contains(&job_id) => {
+                            // Stopped by shutdown: drop the partial output so
+                            // the next start redoes the job cleanly
+                            j.requeue();
+                            let _ = std::fs::remove_file(&target);
+                            log::info!(
+                                "[ConversionWorker-{}] Job {} interrupted, requeued",
+                                worker_id,
+                                job_id
+                            );
+                        }
                         Err(e) => {
                             if cancelled.contains(&job_id) {
                                 j.status = ConversionStatus::Cancelled;
@@ -492,14 +1243,17 @@ impl ConversionEngine {
                                         "conversion:error",
                                         serde_json::json!({
                                             "job_id": job_id,
-                                            "error": e.to_string(),
+                                            "error_code": e.code(),
+                                            "error_message": e.to_string(),
                                         }),
                                     )
                                     .ok();
                             }
                         }
                     }
-                    handle.emit("conversion:progress", j.value()).ok();
+                    emit_job_progress(&handle, &throttle, j.value());
+                    // Requeued jobs aren't terminal, so drop their state here too
+                    throttle.forget(&job_id);
                     persist(j.value());
                 }
             } else {
@@ -536,14 +1290,16 @@ impl ConversionEngine {
         target_fmt: &str,
         source: &Path,
         target: &Path,
-        cancelled: &DashSet<String>,
+        cancelled: &Arc<DashSet<String>>,
         job_id: &str,
         db: Option<&Database>,
         progress_cb: Option<std::sync::Arc<dyn Fn(u8, &str) + Send + Sync>>,
+        ocr: bool,
+        options: &ConversionOptions,
     ) -> FormatResult<()> {
         let check_cancel = || -> FormatResult<()> {
             if cancelled.contains(job_id) {
-                Err(FormatError::ConversionError("Cancelled".to_string()))
+                Err(FormatError::Cancelled)
             } else {
                 Ok(())
             }
@@ -551,6 +1307,30 @@ impl ConversionEngine {
 
         check_cancel()?;
 
+        // Scanned PDFs: use the direct text path so OCR can fill in a missing text layer
+        if ocr && source_fmt == "pdf" && matches!(target_fmt, "txt" | "epub") {
+            let cancelled = cancelled.clone();
+            let job_id = job_id.to_string();
+            let ocr_ctx = OcrContext {
+                cancel_check: Arc::new(move || cancelled.contains(&job_id)),
+                progress_cb: progress_cb.clone(),
+            };
+            return if target_fmt == "txt" {
+                Self::pdf_to_txt(source, target, Some(ocr_ctx)).await
+            } else {
+                Self::pdf_to_epub(source, target, Some(ocr_ctx)).await
+            };
+        }
+
+        // HTML keeps its markup, stylesheets and images rather than going
+        // through the plain-text pipeline
+        if target_fmt == "epub" && source_fmt == "html" {
+            if let Some(cb) = &progress_cb {
+                cb(10, "Converting with native engine...");
+            }
+            return Self::html_to_epub(source, target).await;
+        }
+
         if target_fmt == "epub" {
             if let Some((calibre_first, profile)) = Self::epub_policy_for_source(source_fmt) {
                 if calibre_first {
@@ -570,7 +1350,7 @@ impl ConversionEngine {
                         {
                             Ok(()) => return Ok(()),
                             Err(CalibreError::Cancelled) => {
-                                return Err(FormatError::ConversionError("Cancelled".to_string()))
+                                return Err(FormatError::Cancelled)
                             }
                             Err(err @ CalibreError::Disabled)
                             | Err(err @ CalibreError::NotFound)
@@ -599,10 +1379,8 @@ impl ConversionEngine {
                         cb(10, "Converting with native engine...");
                     }
 
-                    let res = crate::conversion::convert_to_epub(source, target, rust_fmt, progress_cb.as_deref())
-                        .await
-                        .map(|_| ())
-                        .map_err(|e| e.into());
+                    let res = Self::source_to_epub(source, target, rust_fmt, options, progress_cb.as_deref())
+                        .await;
                         
                     if let Some(cb) = &progress_cb {
                         cb(100, "Finalizing...");
@@ -616,15 +1394,14 @@ impl ConversionEngine {
                     cb(10, "Converting with native engine...");
                 }
                 
-                match crate::conversion::convert_to_epub(
+                match Self::source_to_epub(
                     source,
                     target,
                     crate::conversion::SourceFormat::Txt,
+                    options,
                     progress_cb.as_deref(),
                 )
                 .await
-                .map(|_| ())
-                .map_err(|e| e.into())
                 {
                     Ok(()) => return Ok(()),
                     Err(rust_err) => {
@@ -644,9 +1421,7 @@ impl ConversionEngine {
                             {
                                 Ok(()) => return Ok(()),
                                 Err(CalibreError::Cancelled) => {
-                                    return Err(FormatError::ConversionError(
-                                        "Cancelled".to_string(),
-                                    ))
+                                    return Err(FormatError::Cancelled)
                                 }
                                 Err(err @ CalibreError::Disabled)
                                 | Err(err @ CalibreError::NotFound)
@@ -668,6 +1443,19 @@ impl ConversionEngine {
             }
         }
 
+        // Text-like sources go straight to PDF, skipping the EPUB intermediate;
+        // DOCX this way also keeps its bold runs
+        if target_fmt == "pdf" && matches!(source_fmt, "docx" | "txt" | "fb2") {
+            if let Some(cb) = &progress_cb {
+                cb(10, "Converting with native engine...");
+            }
+            return match source_fmt {
+                "docx" => Self::docx_to_pdf(source, target, options.pdf).await,
+                "txt" => Self::txt_to_pdf(source, target, options.pdf).await,
+                _ => Self::fb2_to_pdf(source, target, options.pdf).await,
+            };
+        }
+
         // ── Native N-to-N Pipeline ──
         // Strategy: Source -> EPUB -> Target
         let temp_dir = std::env::temp_dir();
@@ -678,9 +1466,8 @@ impl ConversionEngine {
             let src_format = crate::conversion::SourceFormat::from_extension(source_fmt)
                 .unwrap_or(crate::conversion::SourceFormat::Txt);
             
-            crate::conversion::convert_to_epub(source, &intermediate_epub, src_format, progress_cb.as_deref())
-                .await
-                .map_err(|e| FormatError::ConversionError(e.to_string()))?;
+            Self::source_to_epub(source, &intermediate_epub, src_format, options, progress_cb.as_deref())
+                .await?;
         } else {
             tokio::fs::copy(source, &intermediate_epub).await?;
         }
@@ -695,7 +1482,7 @@ impl ConversionEngine {
         }
 
         let res = match target_fmt {
-            "pdf" => Self::epub_to_pdf(&intermediate_epub, target).await,
+            "pdf" => Self::epub_to_pdf(&intermediate_epub, target, options.pdf).await,
             "txt" => Self::epub_to_txt(&intermediate_epub, target).await,
             "docx" => Self::epub_to_docx(&intermediate_epub, target).await,
             "mobi" | "azw3" => Self::epub_to_mobi(&intermediate_epub, target).await,
@@ -710,6 +1497,29 @@ impl ConversionEngine {
         res
     }
 
+    /// Native conversion to EPUB; TXT sources honour the job's chapter split
+    async fn source_to_epub(
+        source: &Path,
+        target: &Path,
+        format: crate::conversion::SourceFormat,
+        options: &ConversionOptions,
+        progress_cb: Option<&(dyn Fn(u8, &str) + Send + Sync)>,
+    ) -> FormatResult<()> {
+        use crate::conversion::txt;
+
+        if matches!(format, crate::conversion::SourceFormat::Txt) {
+            let split = options.txt_chapter_split()?;
+            return txt::convert_with(source, target, &split, progress_cb)
+                .await
+                .map(|_| ())
+                .map_err(FormatError::from);
+        }
+        crate::conversion::convert_to_epub(source, target, format, progress_cb)
+            .await
+            .map(|_| ())
+            .map_err(FormatError::from)
+    }
+
     // ──────────────────────────────────────────────────────────────────────
     // EPUB EXPORT PIPELINE
     // ──────────────────────────────────────────────────────────────────────
@@ -721,21 +1531,13 @@ impl ConversionEngine {
         tokio::task::spawn_blocking(move || -> FormatResult<()> {
             use ::epub::doc::EpubDoc;
             let mut doc = EpubDoc::new(&source_clone)
-                .map_err(|e| FormatError::ConversionError(format!("Failed to open EPUB: {}", e)))?;
+                .map_err(|e| FormatError::CorruptSource(format!("Failed to open EPUB: {}", e)))?;
             
             let mut full_text = String::new();
             while doc.go_next() {
                 if let Some((content_bytes, _mime_type)) = doc.get_current() {
                     let html = String::from_utf8_lossy(&content_bytes);
-                    let text = html
-                        .replace("<br>", "\n")
-                        .replace("<br/>", "\n")
-                        .replace("<p>", "\n")
-                        .replace("</p>", "\n");
-                    static HTML_TAG_RE: once_cell::sync::Lazy<regex::Regex> =
-                        once_cell::sync::Lazy::new(|| regex::Regex::new(r"<[^>]*>").unwrap());
-                    let clean = HTML_TAG_RE.replace_all(&text, "");
-                    full_text.push_str(&clean);
+                    full_text.push_str(&epub_html_to_text(&html));
                     full_text.push('\n');
                 }
             }
@@ -791,6 +1593,13 @@ impl ConversionEngine {
         let metadata = adapter.extract_metadata(source).await?;
         let content_bytes = tokio::fs::read(source).await?;
         let content = String::from_utf8_lossy(&content_bytes).into_owned();
+        let parts = HtmlFormatAdapter::split_for_epub(&content, &metadata.title)?;
+        if parts.chapters.is_empty() {
+            return Err(FormatError::EmptyOutput(
+                "HTML document has no body content".to_string(),
+            ));
+        }
+
         let mut builder = EpubBuilder::new();
         builder = builder.metadata(EpubMetadata {
             title: metadata.title.clone(),
@@ -799,7 +1608,39 @@ impl ConversionEngine {
             description: metadata.description.clone(),
             ..Default::default()
         });
-        builder.add_chapter(metadata.title.clone(), content);
+
+        // Resources are resolved next to the HTML file; missing ones are skipped
+        let base_dir = source.parent().unwrap_or_else(|| Path::new("."));
+        for href in &parts.stylesheet_links {
+            let path = HtmlFormatAdapter::resolve_reference(base_dir, href);
+            match tokio::fs::read(&path).await {
+                Ok(css) => builder.append_stylesheet(&String::from_utf8_lossy(&css)),
+                Err(e) => log::warn!("[Conversion] Skipping stylesheet {}: {}", path.display(), e),
+            }
+        }
+        for css in &parts.inline_styles {
+            builder.append_stylesheet(css);
+        }
+        for (src, href) in &parts.images {
+            let path = HtmlFormatAdapter::resolve_reference(base_dir, src);
+            match tokio::fs::read(&path).await {
+                Ok(data) => {
+                    let media_type = crate::conversion::utils::detect_image_format(&data)
+                        .map(|(mime, _)| mime.to_string())
+                        .unwrap_or_else(|| {
+                            mime_guess::from_path(&path)
+                                .first_or_octet_stream()
+                                .to_string()
+                        });
+                    builder.add_image(href.clone(), media_type, data);
+                }
+                Err(e) => log::warn!("[Conversion] Skipping image {}: {}", path.display(), e),
+            }
+        }
+
+        for (title, html) in parts.chapters {
+            builder.add_html_chapter(title, html);
+        }
         builder.generate(target).await?;
         log::info!("[Conversion] HTML → EPUB: {}", target.display());
         Ok(())
@@ -824,7 +1665,7 @@ impl ConversionEngine {
     async fn mobi_to_epub(source: &Path, target: &Path) -> FormatResult<()> {
         let adapter = MobiFormatAdapter::new();
         let metadata = adapter.extract_metadata(source).await?;
-        let content = MobiFormatAdapter::extract_content(source).await?;
+        let chapters = MobiFormatAdapter::extract_chapters(source).await?;
         let mut builder = EpubBuilder::new();
         builder = builder.metadata(EpubMetadata {
             title: metadata.title.clone(),
@@ -835,7 +1676,9 @@ impl ConversionEngine {
             isbn: metadata.isbn.clone(),
             ..Default::default()
         });
-        builder.add_chapter(metadata.title.clone(), content);
+        for (title, content) in chapters {
+            builder.add_chapter(title, content);
+        }
         builder.generate(target).await?;
         log::info!("[Conversion] MOBI → EPUB: {}", target.display());
         Ok(())
@@ -859,22 +1702,14 @@ impl ConversionEngine {
 
         let content = tokio::task::spawn_blocking(move || -> FormatResult<String> {
             let doc = docx_rs::read_docx(&file_data)
-                .map_err(|e| FormatError::ConversionError(format!("DOCX parse failed: {}", e)))?;
+                .map_err(|e| FormatError::CorruptSource(format!("DOCX parse failed: {}", e)))?;
             let mut content = String::new();
-            for child in &doc.document.children {
-                if let docx_rs::DocumentChild::Paragraph(para) = child {
-                    for child in &para.children {
-                        if let docx_rs::ParagraphChild::Run(run) = child {
-                            for child in &run.children {
-                                if let docx_rs::RunChild::Text(t) = child {
-                                    content.push_str(&t.text);
-                                    content.push(' ');
-                                }
-                            }
-                        }
-                    }
-                    content.push_str("\n\n");
+            for runs in docx_paragraph_runs(&doc) {
+                for (text, _bold) in runs {
+                    content.push_str(&text);
+                    content.push(' ');
                 }
+                content.push_str("\n\n");
             }
             Ok(content)
         })
@@ -901,24 +1736,8 @@ impl ConversionEngine {
 
         let content = tokio::task::spawn_blocking(move || -> FormatResult<String> {
             let doc = docx_rs::read_docx(&file_data)
-                .map_err(|e| FormatError::ConversionError(format!("DOCX parse failed: {}", e)))?;
-            let mut content = String::new();
-            for child in &doc.document.children {
-                if let docx_rs::DocumentChild::Paragraph(para) = child {
-                    for child in &para.children {
-                        if let docx_rs::ParagraphChild::Run(run) = child {
-                            for child in &run.children {
-                                if let docx_rs::RunChild::Text(t) = child {
-                                    content.push_str(&t.text);
-                                    content.push(' ');
-                                }
-                            }
-                        }
-                    }
-                    content.push('\n');
-                }
-            }
-            Ok(content)
+                .map_err(|e| FormatError::CorruptSource(format!("DOCX parse failed: {}", e)))?;
+            Ok(crate::services::docx_adapter::DocxAdapter::plain_text(&doc))
         })
         .await
         .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
@@ -927,6 +1746,58 @@ impl ConversionEngine {
         Ok(())
     }
 
+    /// DOCX → PDF: paragraphs laid out directly with printpdf, bold runs in
+    /// the bold face of the chosen font
+    async fn docx_to_pdf(source: &Path, target: &Path, pdf: PdfOutputOptions) -> FormatResult<()> {
+        let file_data = tokio::fs::read(source).await?;
+        let title = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Untitled".to_string());
+        let target_clone = target.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> FormatResult<()> {
+            let doc = docx_rs::read_docx(&file_data)
+                .map_err(|e| FormatError::CorruptSource(format!("DOCX parse failed: {}", e)))?;
+
+            let (width, height) = pdf.page_size.dimensions();
+            let (pdf_doc, page1, layer1) = PdfDocument::new(&title, width, height, "Layer 1");
+            let regular = pdf_doc
+                .add_builtin_font(pdf.font.regular())
+                .map_err(|e| FormatError::ConversionError(format!("Font error: {}", e)))?;
+            let bold = pdf_doc
+                .add_builtin_font(pdf.font.bold())
+                .map_err(|e| FormatError::ConversionError(format!("Font error: {}", e)))?;
+
+            let mut pager = PdfPager::new(&pdf_doc, page1, layer1, pdf);
+            for runs in docx_paragraph_runs(&doc) {
+                // Empty paragraphs are the document's own blank lines
+                if runs.iter().all(|(text, _)| text.trim().is_empty()) {
+                    pager.advance(pager.line_height);
+                    continue;
+                }
+                for line in wrap_runs(&runs, pager.max_chars) {
+                    pager.write_runs(&line, &regular, &bold);
+                    pager.advance(pager.line_height);
+                }
+                // Blank line between paragraphs
+                pager.advance(pager.line_height);
+            }
+
+            let file = File::create(&target_clone)?;
+            let mut w = BufWriter::new(file);
+            pdf_doc
+                .save(&mut w)
+                .map_err(|e| FormatError::ConversionError(format!("PDF save failed: {}", e)))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
+
+        log::info!("[Conversion] DOCX → PDF: {}", target.display());
+        Ok(())
+    }
+
     async fn fb2_to_epub(source: &Path, target: &Path) -> FormatResult<()> {
         let adapter = Fb2FormatAdapter::new();
         let metadata = adapter.extract_metadata(source).await?;
@@ -958,15 +1829,52 @@ impl ConversionEngine {
         Ok(())
     }
 
+    async fn fb2_to_pdf(source: &Path, target: &Path, pdf: PdfOutputOptions) -> FormatResult<()> {
+        let content_bytes = tokio::fs::read(source).await?;
+        let target_clone = target.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let content = String::from_utf8_lossy(&content_bytes);
+            render_text_to_pdf(
+                &Fb2FormatAdapter::extract_text(&content),
+                &target_clone,
+                pdf,
+            )
+        })
+        .await
+        .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
+        log::info!("[Conversion] FB2 → PDF: {}", target.display());
+        Ok(())
+    }
+
+    async fn txt_to_pdf(source: &Path, target: &Path, pdf: PdfOutputOptions) -> FormatResult<()> {
+        let content_bytes = tokio::fs::read(source).await?;
+        let target_clone = target.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            render_text_to_pdf(
+                &String::from_utf8_lossy(&content_bytes),
+                &target_clone,
+                pdf,
+            )
+        })
+        .await
+        .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
+        log::info!("[Conversion] TXT → PDF: {}", target.display());
+        Ok(())
+    }
+
     /// PDF → EPUB: chapter detection via heading heuristic
-    async fn pdf_to_epub(source: &Path, target: &Path) -> FormatResult<()> {
+    async fn pdf_to_epub(
+        source: &Path,
+        target: &Path,
+        ocr: Option<OcrContext>,
+    ) -> FormatResult<()> {
         let adapter = PdfFormatAdapter::new();
         let metadata = adapter.extract_metadata(source).await?;
         let source_path = source.to_path_buf();
 
         let chapters =
             tokio::task::spawn_blocking(move || -> FormatResult<Vec<(String, String)>> {
-                let text = PdfFormatAdapter::extract_content(&source_path)?;
+                let text = pdf_text_with_ocr(&source_path, ocr.as_ref())?;
                 let text = Self::sanitize_mojibake(&text);
                 Ok(Self::detect_pdf_chapters(&text))
             })
@@ -1068,10 +1976,10 @@ impl ConversionEngine {
         cleaned
     }
 
-    async fn pdf_to_txt(source: &Path, target: &Path) -> FormatResult<()> {
+    async fn pdf_to_txt(source: &Path, target: &Path, ocr: Option<OcrContext>) -> FormatResult<()> {
         let source_path = source.to_path_buf();
         let mut text = tokio::task::spawn_blocking(move || -> FormatResult<String> {
-            PdfFormatAdapter::extract_content(&source_path)
+            pdf_text_with_ocr(&source_path, ocr.as_ref())
         })
         .await
         .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
@@ -1084,32 +1992,11 @@ impl ConversionEngine {
     }
 
     /// EPUB → PDF: text + embedded images via printpdf
-    async fn epub_to_pdf(source: &Path, target: &Path) -> FormatResult<()> {
+    async fn epub_to_pdf(source: &Path, target: &Path, pdf: PdfOutputOptions) -> FormatResult<()> {
         use ::epub::doc::EpubDoc;
 
         let mut doc = EpubDoc::new(source)
-            .map_err(|e| FormatError::ConversionError(format!("Failed to open EPUB: {}", e)))?;
-
-        let title = source
-            .file_stem()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Untitled".to_string());
-
-        let (pdf_doc, page1, layer1) = PdfDocument::new(&title, Mm(210.0), Mm(297.0), "Layer 1");
-        let font = pdf_doc
-            .add_builtin_font(BuiltinFont::TimesRoman)
-            .map_err(|e| FormatError::ConversionError(format!("Font error: {}", e)))?;
-
-        let mut current_layer = pdf_doc.get_page(page1).get_layer(layer1);
-        let mut current_y = Mm(280.0);
-        let left_margin = Mm(15.0);
-        let font_size = 11.0;
-        let line_height = Mm(5.0);
-        let page_bottom = Mm(20.0);
-
-        // new_page helper: creates a new PDF page and returns layer + initial y
-        // Note: printpdf's add_page / get_page are on the PdfDocument value,
-        // not a reference. We inline the call each time instead of a closure.
+            .map_err(|e| FormatError::CorruptSource(format!("Failed to open EPUB: {}", e)))?;
 
         // Helper: strip HTML tags
         let strip_html = |html: &str| -> String {
@@ -1121,73 +2008,31 @@ impl ConversionEngine {
                 .replace("</h1>", "\n\n")
                 .replace("</h2>", "\n\n")
                 .replace("</h3>", "\n");
-            static HTML_TAG_RE: once_cell::sync::Lazy<regex::Regex> =
-                once_cell::sync::Lazy::new(|| regex::Regex::new(r"<[^>]*>").unwrap());
             HTML_TAG_RE.replace_all(&t, "").to_string()
         };
 
         let num_chapters = doc.get_num_chapters();
-        let mut i = 0;
+        let mut text = String::new();
 
-        while i < num_chapters {
+        for i in 0..num_chapters {
             let _ = doc.set_current_chapter(i);
 
-            // Try to embed images from current chapter resources
             // Note: image extraction from EPUB is limited by the epub crate's API.
             // Images referenced in <img src="..."> cannot be fetched per-chapter easily
             // without a full HTML parser + resource map. We render text faithfully here.
             // For full image support, a headless browser pipeline is required.
 
             if let Some((content, _)) = doc.get_current_str() {
-                let text = strip_html(&content);
-                for line in text.lines() {
-                    let max_chars = 90usize;
-                    let chars: Vec<char> = line.chars().collect();
-                    if chars.is_empty() {
-                        current_y -= line_height * 0.5;
-                    } else {
-                        for chunk in chars.chunks(max_chars) {
-                            let s: String = chunk.iter().collect();
-                            if !s.trim().is_empty() {
-                                current_layer.use_text(
-                                    &s,
-                                    font_size,
-                                    left_margin,
-                                    current_y,
-                                    &font,
-                                );
-                                current_y -= line_height;
-                            }
-                            if current_y < page_bottom {
-                                let (new_p, new_l) =
-                                    pdf_doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                                current_layer = pdf_doc.get_page(new_p).get_layer(new_l);
-                                current_y = Mm(280.0);
-                            }
-                        }
-                    }
-                    if current_y < page_bottom {
-                        let (new_p, new_l) = pdf_doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                        current_layer = pdf_doc.get_page(new_p).get_layer(new_l);
-                        current_y = Mm(280.0);
-                    }
-                }
-                // Chapter separator
-                current_y -= line_height * 2.0;
-                if current_y < page_bottom {
-                    let (new_p, new_l) = pdf_doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                    current_layer = pdf_doc.get_page(new_p).get_layer(new_l);
-                    current_y = Mm(280.0);
-                }
+                text.push_str(&strip_html(&content));
+                // Chapter separator: four blank lines = two line heights
+                text.push_str("\n\n\n\n\n");
             }
-            i += 1;
         }
 
-        let file = File::create(target)?;
-        let mut w = BufWriter::new(file);
-        pdf_doc
-            .save(&mut w)
-            .map_err(|e| FormatError::ConversionError(format!("PDF save failed: {}", e)))?;
+        let target_clone = target.to_path_buf();
+        tokio::task::spawn_blocking(move || render_text_to_pdf(&text, &target_clone, pdf))
+            .await
+            .map_err(|e| FormatError::ConversionError(format!("Task Join Error: {}", e)))??;
 
         log::info!("[Conversion] EPUB → PDF: {}", target.display());
         Ok(())
@@ -1205,7 +2050,7 @@ impl ConversionEngine {
         db: Option<&Database>,
         progress_cb: Option<std::sync::Arc<dyn Fn(u8, &str) + Send + Sync>>,
     ) -> FormatResult<()> {
-        let dummy_cancelled = DashSet::new();
+        let dummy_cancelled = Arc::new(DashSet::new());
         let dummy_job_id = "direct";
         Self::execute_conversion(
             source_format,
@@ -1216,6 +2061,8 @@ impl ConversionEngine {
             dummy_job_id,
             db,
             progress_cb,
+            false,
+            &ConversionOptions::default(),
         )
         .await
     }
@@ -1227,6 +2074,339 @@ impl Default for ConversionEngine {
     }
 }
 
+/// Jobs left Queued or Processing by a previous run, oldest first, all reset
+/// to Queued
+fn load_pending_jobs(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<ConversionJob>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, book_id, source_path, target_path, source_format, target_format,
+                status, progress, error_message, created_at, ocr, options_json
+         FROM conversion_jobs
+         WHERE status IN ('Queued', 'Processing')
+         ORDER BY created_at ASC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ConversionJob {
+            id: row.get(0)?,
+            book_id: row.get(1)?,
+            source_path: row.get(2)?,
+            target_path: row.get(3)?,
+            source_format: row.get(4)?,
+            target_format: row.get(5)?,
+            status: ConversionStatus::Queued, // always re-queue
+            progress: 0.0,
+            error: None,
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            ocr: row.get::<_, Option<bool>>(10)?.unwrap_or(false),
+            options: row
+                .get::<_, Option<String>>(11)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+        })
+    })?;
+    rows.collect()
+}
+
+/// Cancellation and progress hooks for the OCR fallback of a running job
+#[derive(Clone)]
+struct OcrContext {
+    cancel_check: Arc<dyn Fn() -> bool + Send + Sync>,
+    progress_cb: Option<Arc<dyn Fn(u8, &str) + Send + Sync>>,
+}
+
+/// PDF text layer, with an OCR pass when `ocr` is given and the layer is too
+/// sparse. If the OCR tools are missing or fail, whatever text layer exists
+/// is returned instead. Blocking; call from `spawn_blocking`.
+fn pdf_text_with_ocr(source: &Path, ocr: Option<&OcrContext>) -> FormatResult<String> {
+    use crate::services::ocr_service::{self, OcrError};
+
+    let extracted = PdfFormatAdapter::extract_content(source);
+    let Some(ctx) = ocr else {
+        return require_text(extracted?);
+    };
+    // Image-only PDFs may make the text extractor fail outright
+    let text = extracted.unwrap_or_else(|e| {
+        log::warn!("[Conversion] PDF text layer unreadable, trying OCR: {}", e);
+        String::new()
+    });
+
+    let page_count = lopdf::Document::load(source)
+        .map(|doc| doc.get_pages().len())
+        .unwrap_or(1);
+    if !ocr_service::needs_ocr(&text, page_count) {
+        return Ok(text);
+    }
+
+    let on_page = |done: usize, total: usize| {
+        if let Some(cb) = &ctx.progress_cb {
+            let pct = 10 + (done * 80 / total.max(1)) as u8;
+            cb(pct, &format!("OCR page {}/{}", done, total));
+        }
+    };
+    match ocr_service::ocr_pdf(source, page_count, || (ctx.cancel_check)(), on_page) {
+        Ok(ocr_text) if !ocr_text.trim().is_empty() => Ok(ocr_text),
+        Ok(_) => require_text(text),
+        Err(OcrError::Cancelled) => Err(FormatError::Cancelled),
+        Err(e @ OcrError::Unavailable) if text.trim().is_empty() => {
+            Err(FormatError::DependencyMissing(e.to_string()))
+        }
+        Err(e) => {
+            log::warn!("[Conversion] OCR unavailable, using text layer only: {}", e);
+            require_text(text)
+        }
+    }
+}
+
+/// Rejects PDFs whose extracted text is blank, which almost always means a
+/// scanned document without a text layer
+fn require_text(text: String) -> FormatResult<String> {
+    if text.trim().is_empty() {
+        return Err(FormatError::EmptyOutput(
+            "the PDF has no text layer".to_string(),
+        ));
+    }
+    Ok(text)
+}
+
+/// Text cursor shared by the PDF writers. Tracks the current layer and
+/// baseline, and starts a new page once the bottom margin is crossed.
+/// Line width and height follow the job's `PdfOutputOptions`.
+struct PdfPager<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    y: Mm,
+    options: PdfOutputOptions,
+    /// Characters per line at the font size within the margins; 90 for
+    /// 11 pt Times on A4 with 15 mm margins
+    max_chars: usize,
+    line_height: Mm,
+}
+
+impl<'a> PdfPager<'a> {
+    const MM_PER_PT: f32 = 25.4 / 72.0;
+    /// Baseline-to-baseline distance as a multiple of the font size
+    const LEADING: f32 = 1.3;
+
+    fn new(
+        doc: &'a PdfDocumentReference,
+        page: PdfPageIndex,
+        layer: PdfLayerIndex,
+        options: PdfOutputOptions,
+    ) -> Self {
+        let (width, _) = options.page_size.dimensions();
+        let font_mm = options.font_size * Self::MM_PER_PT;
+        let text_width = width.0 - 2.0 * options.margin_mm;
+        let advance = font_mm * options.font.average_advance();
+        Self {
+            doc,
+            layer: doc.get_page(page).get_layer(layer),
+            y: Self::top(&options),
+            options,
+            max_chars: ((text_width / advance) as usize).max(1),
+            line_height: Mm(font_mm * Self::LEADING),
+        }
+    }
+
+    /// First baseline: one font height below the top margin
+    fn top(options: &PdfOutputOptions) -> Mm {
+        let (_, height) = options.page_size.dimensions();
+        height - Mm(options.margin_mm + options.font_size * Self::MM_PER_PT)
+    }
+
+    /// Move the baseline down, breaking to a new page past the bottom margin
+    fn advance(&mut self, dy: Mm) {
+        self.y -= dy;
+        if self.y < Mm(self.options.margin_mm) {
+            let (width, height) = self.options.page_size.dimensions();
+            let (page, layer) = self.doc.add_page(width, height, "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = Self::top(&self.options);
+        }
+    }
+
+    fn write_line(&self, text: &str, font: &IndirectFontRef) {
+        self.layer.use_text(
+            text,
+            self.options.font_size,
+            Mm(self.options.margin_mm),
+            self.y,
+            font,
+        );
+    }
+
+    /// Write one line made of (text, bold) segments; the PDF text cursor
+    /// advances by real glyph widths so mixed fonts sit flush.
+    fn write_runs(
+        &self,
+        runs: &[(String, bool)],
+        regular: &IndirectFontRef,
+        bold: &IndirectFontRef,
+    ) {
+        self.layer.begin_text_section();
+        self.layer
+            .set_text_cursor(Mm(self.options.margin_mm), self.y);
+        for (text, is_bold) in runs {
+            let font = if *is_bold { bold } else { regular };
+            self.layer.set_font(font, self.options.font_size);
+            self.layer.write_text(text.clone(), font);
+        }
+        self.layer.end_text_section();
+    }
+}
+
+/// Lay plain text out per `options` (Times Roman 11 pt on A4 by default).
+/// Long lines wrap at the pager's line width; each empty line advances half
+/// a line height. Shared by every text → PDF path.
+fn render_text_to_pdf(text: &str, target: &Path, options: PdfOutputOptions) -> FormatResult<()> {
+    let title = target
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let (width, height) = options.page_size.dimensions();
+    let (pdf_doc, page1, layer1) = PdfDocument::new(&title, width, height, "Layer 1");
+    let font = pdf_doc
+        .add_builtin_font(options.font.regular())
+        .map_err(|e| FormatError::ConversionError(format!("Font error: {}", e)))?;
+
+    let mut pager = PdfPager::new(&pdf_doc, page1, layer1, options);
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            pager.advance(pager.line_height * 0.5);
+            continue;
+        }
+        for chunk in chars.chunks(pager.max_chars) {
+            let s: String = chunk.iter().collect();
+            if !s.trim().is_empty() {
+                pager.write_line(&s, &font);
+                pager.advance(pager.line_height);
+            }
+        }
+    }
+
+    let file = File::create(target)?;
+    let mut w = BufWriter::new(file);
+    pdf_doc
+        .save(&mut w)
+        .map_err(|e| FormatError::ConversionError(format!("PDF save failed: {}", e)))?;
+    Ok(())
+}
+
+/// Walk a DOCX body into paragraphs of (text, bold) runs
+fn docx_paragraph_runs(doc: &docx_rs::Docx) -> Vec<Vec<(String, bool)>> {
+    let mut paragraphs = Vec::new();
+    for child in &doc.document.children {
+        if let docx_rs::DocumentChild::Paragraph(para) = child {
+            let mut runs = Vec::new();
+            for child in &para.children {
+                if let docx_rs::ParagraphChild::Run(run) = child {
+                    let is_bold = run.run_property.bold.is_some();
+                    for child in &run.children {
+                        if let docx_rs::RunChild::Text(t) = child {
+                            runs.push((t.text.clone(), is_bold));
+                        }
+                    }
+                }
+            }
+            paragraphs.push(runs);
+        }
+    }
+    paragraphs
+}
+
+/// Greedy word wrap over styled runs. Adjacent words with the same weight are
+/// merged into one segment; words longer than a line are split.
+fn wrap_runs(runs: &[(String, bool)], max_chars: usize) -> Vec<Vec<(String, bool)>> {
+    let mut lines: Vec<Vec<(String, bool)>> = Vec::new();
+    let mut line: Vec<(String, bool)> = Vec::new();
+    let mut line_len = 0usize;
+
+    let push_word = |line: &mut Vec<(String, bool)>, word: &str, bold: bool| match line.last_mut() {
+        Some((text, b)) if *b == bold => text.push_str(word),
+        _ => line.push((word.to_string(), bold)),
+    };
+
+    for (text, bold) in runs {
+        for word in text.split_inclusive(' ') {
+            let chars: Vec<char> = word.chars().collect();
+            for piece in chars.chunks(max_chars) {
+                let piece: String = piece.iter().collect();
+                let visible = piece.trim_end().chars().count();
+                if line_len > 0 && line_len + visible > max_chars {
+                    lines.push(std::mem::take(&mut line));
+                    line_len = 0;
+                }
+                if line_len == 0 && piece.trim().is_empty() {
+                    continue;
+                }
+                push_word(&mut line, &piece, *bold);
+                line_len += piece.chars().count();
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+static HTML_TAG_RE: once_cell::sync::Lazy<regex::Regex> =
+    once_cell::sync::Lazy::new(|| regex::Regex::new(r"<[^>]*>").unwrap());
+
+static HTML_HEADING_RE: once_cell::sync::Lazy<regex::Regex> =
+    once_cell::sync::Lazy::new(|| regex::Regex::new(r"(?is)<h[1-3][^>]*>(.*?)</h[1-3]>").unwrap());
+
+/// Flatten one EPUB content document to plain text, one paragraph per block
+fn epub_html_to_text(html: &str) -> String {
+    let text = html
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<p>", "\n")
+        .replace("</p>", "\n");
+    HTML_TAG_RE.replace_all(&text, "").into_owned()
+}
+
+/// Extract `(title, text)` chapters from a TXT or EPUB file, using the same
+/// splitting the TXT/EPUB converters apply. Other formats are unsupported.
+pub(crate) fn extract_chapters(source: &Path, format: &str) -> FormatResult<Vec<(String, String)>> {
+    match format.to_lowercase().as_str() {
+        "txt" => {
+            let raw = std::fs::read(source)?;
+            Ok(split_text_into_chapters(&String::from_utf8_lossy(&raw)))
+        }
+        "epub" => {
+            use ::epub::doc::EpubDoc;
+            let mut doc = EpubDoc::new(source)
+                .map_err(|e| FormatError::CorruptSource(format!("Failed to open EPUB: {}", e)))?;
+
+            let mut chapters = Vec::new();
+            loop {
+                if let Some((content_bytes, _mime_type)) = doc.get_current() {
+                    let html = String::from_utf8_lossy(&content_bytes);
+                    let text = crate::conversion::utils::decode_html_entities(
+                        epub_html_to_text(&html).trim(),
+                    );
+                    if !text.trim().is_empty() {
+                        let title = HTML_HEADING_RE
+                            .captures(&html)
+                            .map(|c| HTML_TAG_RE.replace_all(&c[1], "").trim().to_string())
+                            .filter(|t| !t.is_empty())
+                            .unwrap_or_else(|| format!("Section {}", chapters.len() + 1));
+                        chapters.push((title, text));
+                    }
+                }
+                if !doc.go_next() {
+                    break;
+                }
+            }
+            Ok(chapters)
+        }
+        other => Err(FormatError::UnsupportedFormat(other.to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1237,10 +2417,461 @@ mod tests {
         assert!(can_convert("pdf", "epub"));
         assert!(can_convert("mobi", "epub"));
         assert!(can_convert("txt", "epub"));
+        assert!(can_convert("docx", "pdf"));
+        assert!(can_convert("txt", "pdf"));
+        assert!(can_convert("fb2", "pdf"));
         // assert!(!can_convert("epub", "mobi")); // mobi conversion seems supported now
         assert!(!can_convert("cbz", "epub")); // manga, not books
     }
 
+    #[test]
+    fn test_surplus_workers_retire_down_to_target() {
+        let workers = std::sync::Mutex::new(WorkerPool {
+            target: 2,
+            running: 4,
+            next_id: 4,
+            started: true,
+        });
+        let retired = (0..4)
+            .filter(|_| ConversionEngine::retire_if_surplus(&workers))
+            .count();
+        assert_eq!(retired, 2);
+        assert_eq!(workers.lock().unwrap().running, 2);
+
+        // Once at or under the target nobody else retires
+        workers.lock().unwrap().target = 3;
+        assert!(!ConversionEngine::retire_if_surplus(&workers));
+    }
+
+    #[test]
+    fn test_interrupted_job_requeued_once_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("library.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        let job = |id: &str, status: ConversionStatus| ConversionJob {
+            id: id.to_string(),
+            book_id: None,
+            source_path: format!("/tmp/{}.mobi", id),
+            target_path: format!("/tmp/{}.epub", id),
+            source_format: "mobi".to_string(),
+            target_format: "epub".to_string(),
+            status,
+            progress: 40.0,
+            error: None,
+            created_at: Utc::now(),
+            started_at: Some(Utc::now()),
+            completed_at: None,
+            ocr: false,
+            options: ConversionOptions::default(),
+        };
+        // Shut down while one job is mid-conversion and another already finished
+        let tracker = DashMap::new();
+        for job in [
+            job("running", ConversionStatus::Processing),
+            job("done", ConversionStatus::Completed),
+        ] {
+            ConversionEngine::persist_job(&job, &conn);
+            tracker.insert(job.id.clone(), job);
+        }
+
+        assert_eq!(
+            ConversionEngine::requeue_interrupted(&tracker, Some(&conn)),
+            1
+        );
+        assert_eq!(
+            tracker.get("running").unwrap().status,
+            ConversionStatus::Queued
+        );
+        let (status, progress): (String, f32) = conn
+            .query_row(
+                "SELECT status, progress FROM conversion_jobs WHERE id = 'running'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((status.as_str(), progress), ("Queued", 0.0));
+
+        // Nothing is left Processing, so a second pass changes nothing
+        assert_eq!(
+            ConversionEngine::requeue_interrupted(&tracker, Some(&conn)),
+            0
+        );
+
+        let pending = load_pending_jobs(&conn).unwrap();
+        let ids: Vec<_> = pending.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(ids, ["running"]);
+    }
+
+    #[test]
+    fn test_progress_throttle_coalesces_but_keeps_terminal_events() {
+        let throttle = ProgressThrottle::new(4);
+        let mut job = ConversionJob {
+            id: "job".to_string(),
+            book_id: None,
+            source_path: "/tmp/job.txt".to_string(),
+            target_path: "/tmp/job.epub".to_string(),
+            source_format: "txt".to_string(),
+            target_format: "epub".to_string(),
+            status: ConversionStatus::Processing,
+            progress: 0.0,
+            error: None,
+            created_at: Utc::now(),
+            started_at: Some(Utc::now()),
+            completed_at: None,
+            ocr: false,
+            options: ConversionOptions::default(),
+        };
+
+        // 100 updates over 500 ms at 4/s: one every 250 ms gets through, and
+        // only the first held-back update asks for a flush
+        let start = Instant::now();
+        let decisions: Vec<Throttled> = (0..100)
+            .map(|i| {
+                job.progress = i as f32;
+                throttle.offer_at(&job, start + Duration::from_millis(i * 5))
+            })
+            .collect();
+        let count = |wanted: fn(&Throttled) -> bool| decisions.iter().filter(|d| wanted(d)).count();
+        assert_eq!(count(|d| *d == Throttled::Emit), 2);
+        assert_eq!(decisions[1], Throttled::Defer(Duration::from_millis(245)));
+        assert_eq!(count(|d| matches!(d, Throttled::Defer(_))), 1);
+
+        // The flush waits out the window, then sends the newest value once
+        let mut flushed = Vec::new();
+        let early = throttle.flush_at(&job.id, start + Duration::from_millis(300), |j| {
+            flushed.push(j.progress)
+        });
+        assert_eq!(early, Some(Duration::from_millis(200)));
+        assert!(flushed.is_empty());
+        let done = throttle.flush_at(&job.id, start + Duration::from_millis(500), |j| {
+            flushed.push(j.progress)
+        });
+        assert_eq!(done, None);
+        assert_eq!(flushed, vec![99.0]);
+        assert_eq!(
+            throttle.flush_at(&job.id, start + Duration::from_millis(900), |_| panic!(
+                "sent twice"
+            )),
+            None
+        );
+
+        // Completion right after a coalesced update is never dropped, and
+        // clears the job's state so a late flush sends nothing
+        job.progress = 99.5;
+        assert!(matches!(
+            throttle.offer_at(&job, start + Duration::from_millis(501)),
+            Throttled::Defer(_)
+        ));
+        job.status = ConversionStatus::Completed;
+        job.progress = 100.0;
+        assert_eq!(
+            throttle.offer_at(&job, start + Duration::from_millis(502)),
+            Throttled::Emit
+        );
+        assert!(throttle.last_emit.is_empty());
+        assert_eq!(
+            throttle.flush_at(&job.id, start + Duration::from_millis(900), |_| panic!(
+                "stale"
+            )),
+            None
+        );
+
+        // A status change inside the window also goes through
+        job.id = "other".to_string();
+        job.status = ConversionStatus::Queued;
+        assert_eq!(throttle.offer_at(&job, start), Throttled::Emit);
+        job.status = ConversionStatus::Processing;
+        assert_eq!(
+            throttle.offer_at(&job, start + Duration::from_millis(1)),
+            Throttled::Emit
+        );
+        assert!(matches!(
+            throttle.offer_at(&job, start + Duration::from_millis(2)),
+            Throttled::Defer(_)
+        ));
+
+        // Requeued and cancelled jobs are forgotten
+        throttle.forget(&job.id);
+        assert!(throttle.last_emit.is_empty());
+    }
+
+    #[test]
+    fn test_structured_error_codes() {
+        use crate::conversion::ConversionError;
+        use crate::services::format_adapter::ErrorDetails;
+
+        let empty: FormatError = ConversionError::EmptyContent.into();
+        assert_eq!(empty.code(), "empty_output");
+        let parse: FormatError = ConversionError::ParseError {
+            format: "MOBI".into(),
+            detail: "bad header".into(),
+        }
+        .into();
+        assert_eq!(parse.code(), "corrupt_source");
+        let tool: FormatError = ConversionError::MissingDependency("pdftohtml".into()).into();
+        assert_eq!(tool.code(), "dependency_missing");
+
+        assert_eq!(
+            require_text("  \n ".into()).unwrap_err().code(),
+            "empty_output"
+        );
+        assert!(ErrorDetails::for_code("empty_output")
+            .remediation
+            .contains("OCR"));
+        assert_eq!(
+            ErrorDetails::for_code("nonsense").title,
+            "Conversion failed"
+        );
+    }
+
+    #[test]
+    fn test_wrap_runs_keeps_weight_and_width() {
+        let runs = vec![
+            ("Party A ".to_string(), false),
+            ("shall pay".to_string(), true),
+            (" the full amount".to_string(), false),
+        ];
+        let lines = wrap_runs(&runs, 20);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            vec![
+                ("Party A ".to_string(), false),
+                ("shall pay".to_string(), true),
+                (" ".to_string(), false),
+            ]
+        );
+        assert_eq!(lines[1], vec![("the full amount".to_string(), false)]);
+
+        let long = wrap_runs(&[("x".repeat(45), false)], 20);
+        assert_eq!(long.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_docx_to_pdf() {
+        use docx_rs::{Docx, Paragraph, Run};
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("contract.docx");
+        let target = dir.path().join("contract.pdf");
+        let file = File::create(&source).unwrap();
+        Docx::new()
+            .add_paragraph(
+                Paragraph::new()
+                    .add_run(Run::new().add_text("Agreement").bold())
+                    .add_run(Run::new().add_text(" between the parties.")),
+            )
+            .add_paragraph(Paragraph::new())
+            .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Signed.")))
+            .build()
+            .pack(file)
+            .unwrap();
+
+        ConversionEngine::convert_direct(&source, &target, "docx", "pdf", None, None)
+            .await
+            .unwrap();
+        let bytes = std::fs::read(&target).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn test_render_text_to_pdf_multi_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("long.pdf");
+        // ~52 lines fit on a page; 200 lines plus wrapping spans several pages
+        let text = (0..200)
+            .map(|i| format!("Line {} {}", i, "lorem ipsum ".repeat(10)))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        render_text_to_pdf(&text, &target, PdfOutputOptions::default()).unwrap();
+
+        let bytes = std::fs::read(&target).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+        let pdf = lopdf::Document::load_mem(&bytes).unwrap();
+        assert!(pdf.get_pages().len() > 1);
+    }
+
+    #[test]
+    fn test_pdf_output_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = (0..300)
+            .map(|i| format!("Line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let page_count = |options: PdfOutputOptions, name: &str| {
+            let target = dir.path().join(name);
+            render_text_to_pdf(&text, &target, options).unwrap();
+            let bytes = std::fs::read(&target).unwrap();
+            assert!(bytes.starts_with(b"%PDF"));
+            lopdf::Document::load_mem(&bytes).unwrap().get_pages().len()
+        };
+
+        let letter = PdfOutputOptions {
+            page_size: PdfPageSize::Letter,
+            font: PdfFont::Helvetica,
+            ..Default::default()
+        };
+        assert!(page_count(letter, "letter.pdf") > 1);
+
+        // Same lines at 8 pt: more per page, so fewer pages
+        let default_pages = page_count(PdfOutputOptions::default(), "default.pdf");
+        let small = PdfOutputOptions {
+            font_size: 8.0,
+            ..Default::default()
+        };
+        assert!(page_count(small, "small.pdf") < default_pages);
+
+        let tiny = PdfOutputOptions {
+            font_size: 2.0,
+            ..Default::default()
+        };
+        assert!(tiny.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_txt_and_fb2_to_pdf() {
+        let dir = tempfile::tempdir().unwrap();
+        let txt = dir.path().join("notes.txt");
+        std::fs::write(&txt, "First paragraph.\n\nSecond paragraph.").unwrap();
+        let fb2 = dir.path().join("story.fb2");
+        std::fs::write(
+            &fb2,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0">
+  <body><section><title><p>One</p></title><p>Once upon a time.</p></section></body>
+</FictionBook>"#,
+        )
+        .unwrap();
+
+        for (source, fmt) in [(&txt, "txt"), (&fb2, "fb2")] {
+            let target = dir.path().join(format!("{}.pdf", fmt));
+            ConversionEngine::convert_direct(source, &target, fmt, "pdf", None, None)
+                .await
+                .unwrap();
+            assert!(std::fs::read(&target).unwrap().starts_with(b"%PDF"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profile_job_uses_profile_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("library.db")).unwrap();
+        let options = ConversionOptions {
+            pdf: PdfOutputOptions {
+                page_size: PdfPageSize::Letter,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        {
+            let conn = db.get_connection().unwrap();
+            create_conversion_profile(&conn, "Letter PDF", "TXT", "pdf", options.clone()).unwrap();
+            assert!(
+                create_conversion_profile(&conn, "Letter PDF", "txt", "pdf", options.clone())
+                    .is_err()
+            );
+            assert_eq!(list_conversion_profiles(&conn).unwrap().len(), 1);
+        }
+
+        let source = dir.path().join("notes.txt");
+        std::fs::write(&source, "First paragraph.\n\nSecond paragraph.").unwrap();
+        let job = ConversionEngine::prepare_profile_job(&db, &source, "Letter PDF", None)
+            .await
+            .unwrap();
+        assert_eq!(job.target_format, "pdf");
+        assert!(
+            ConversionEngine::prepare_profile_job(&db, &source, "Missing", None)
+                .await
+                .is_err()
+        );
+
+        // A profile whose options no longer parse is refused, not defaulted
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO conversion_profiles (name, source_format, target_format, options_json)
+                 VALUES ('Broken', 'txt', 'pdf', '{not json')",
+                [],
+            )
+            .unwrap();
+        assert!(
+            ConversionEngine::prepare_profile_job(&db, &source, "Broken", None)
+                .await
+                .is_err()
+        );
+
+        // Queue it as `enqueue` does and read it back as a restart would, so
+        // the options run from their persisted `options_json`
+        let conn = db.get_connection().unwrap();
+        ConversionEngine::persist_job(&job, &conn);
+        let job = load_pending_jobs(&conn)
+            .unwrap()
+            .into_iter()
+            .find(|queued| queued.id == job.id)
+            .unwrap();
+        assert_eq!(job.options, options);
+
+        let target = PathBuf::from(&job.target_path);
+        ConversionEngine::execute_conversion(
+            &job.source_format,
+            &job.target_format,
+            &source,
+            &target,
+            &Arc::new(DashSet::new()),
+            &job.id,
+            None,
+            None,
+            job.ocr,
+            &job.options,
+        )
+        .await
+        .unwrap();
+
+        // US Letter is 612 × 792 pt; A4 would be 595 × 842
+        let pdf = lopdf::Document::load(&target).unwrap();
+        let page_id = *pdf.get_pages().values().next().unwrap();
+        let media_box = pdf
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"MediaBox")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_float().unwrap())
+            .collect::<Vec<_>>();
+        assert!((media_box[2] - 612.0).abs() < 1.0);
+        assert!((media_box[3] - 792.0).abs() < 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_txt_pattern_split_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("notes.txt");
+        let target = dir.path().join("notes.epub");
+        std::fs::write(&source, "Part A\n\nFirst.\n\nPart B\n\nSecond.").unwrap();
+        let options = ConversionOptions {
+            chapter_pattern: Some(r"Part \w".to_string()),
+            ..Default::default()
+        };
+
+        let stages = std::sync::Mutex::new(Vec::new());
+        let record = |percent: u8, _: &str| stages.lock().unwrap().push(percent);
+        ConversionEngine::source_to_epub(
+            &source,
+            &target,
+            crate::conversion::SourceFormat::Txt,
+            &options,
+            Some(&record),
+        )
+        .await
+        .unwrap();
+        assert!(target.exists());
+        assert_eq!(*stages.lock().unwrap(), [20, 60]);
+    }
+
     #[test]
     fn test_pdf_chapter_detection() {
         let text = "Introduction\nSome intro text here.\n\nChapter 1 The Beginning\n\nOnce upon a time\n\nCHAPTER 2 THE MIDDLE\n\nAnd then things happened.";
@@ -7,6 +7,8 @@ use image::{DynamicImage, Rgba, RgbaImage};
 use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_text_mut};
 use imageproc::rect::Rect;
 use lru::LruCache;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
@@ -15,7 +17,10 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::error::ShioriError;
+use crate::services::book_metadata_service::{BookMetadataService, MAX_IMAGE_RESPONSE_BYTES};
 use crate::services::format_adapter::{BookMetadata, CoverImage, FormatResult};
+use crate::utils::{http, validate};
 
 const THUMBNAIL_WIDTH: u32 = 200;
 const THUMBNAIL_HEIGHT: u32 = 300;
@@ -32,6 +37,56 @@ pub struct CoverSet {
     pub full: PathBuf,      // Original resolution
 }
 
+/// Where a user-chosen cover comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CoverSource {
+    File { path: String },
+    Url { url: String },
+}
+
+/// Read a custom cover's bytes, capped at the same size as metadata cover
+/// downloads. The bytes are not checked to be an image here.
+pub async fn load_cover_source(source: &CoverSource) -> crate::error::Result<Vec<u8>> {
+    match source {
+        CoverSource::File { path } => {
+            validate::require_safe_path(path, "cover path")?;
+            let size = tokio::fs::metadata(path)
+                .await
+                .map_err(|_| ShioriError::FileNotFound { path: path.clone() })?
+                .len();
+            if size as usize > MAX_IMAGE_RESPONSE_BYTES {
+                return Err(ShioriError::Validation(format!(
+                    "Cover image is too large: {} bytes (max {})",
+                    size, MAX_IMAGE_RESPONSE_BYTES
+                )));
+            }
+            Ok(tokio::fs::read(path).await?)
+        }
+        CoverSource::Url { url } => {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(ShioriError::Validation(format!(
+                    "Cover URL must start with http:// or https://: {}",
+                    url
+                )));
+            }
+            let response = http::client()
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| ShioriError::Other(format!("Failed to download cover: {}", e)))?;
+            if !response.status().is_success() {
+                return Err(ShioriError::Other(format!(
+                    "Failed to download cover: HTTP {}",
+                    response.status()
+                )));
+            }
+            BookMetadataService::bounded_bytes(response, MAX_IMAGE_RESPONSE_BYTES, "cover image")
+                .await
+        }
+    }
+}
+
 /// Color scheme for generated covers
 #[derive(Debug, Clone)]
 struct ColorScheme {
@@ -112,12 +167,7 @@ impl CoverGenerator {
 
     /// Generate color scheme from seed string
     fn generate_color_scheme(&self, seed: &str) -> ColorScheme {
-        let mut hasher = DefaultHasher::new();
-        seed.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        // Generate hue from hash
-        let hue = (hash % 360) as f32;
+        let hue = seed_hue(seed);
         let saturation = 0.7;
         let lightness = 0.5;
 
@@ -143,25 +193,8 @@ impl CoverGenerator {
 
     /// Convert HSL to RGBA
     fn hsl_to_rgba(&self, h: f32, s: f32, l: f32) -> Rgba<u8> {
-        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-        let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-        let m = l - c / 2.0;
-
-        let (r, g, b) = match h as u32 {
-            0..=59 => (c, x, 0.0),
-            60..=119 => (x, c, 0.0),
-            120..=179 => (0.0, c, x),
-            180..=239 => (0.0, x, c),
-            240..=299 => (x, 0.0, c),
-            _ => (c, 0.0, x),
-        };
-
-        Rgba([
-            ((r + m) * 255.0) as u8,
-            ((g + m) * 255.0) as u8,
-            ((b + m) * 255.0) as u8,
-            255,
-        ])
+        let [r, g, b] = hsl_to_rgb(h, s, l);
+        Rgba([r, g, b, 255])
     }
 
     /// Check if color is dark
@@ -473,6 +506,37 @@ impl CoverGenerator {
     }
 }
 
+/// Hue in degrees derived from a hash of `seed`. Tag colors are stored, so
+/// this uses SHA-256 rather than `DefaultHasher`, whose output may change
+/// between Rust releases.
+pub(crate) fn seed_hue(seed: &str) -> f32 {
+    let digest = Sha256::digest(seed.as_bytes());
+    let prefix: [u8; 8] = digest[..8].try_into().expect("SHA-256 is 32 bytes");
+    (u64::from_be_bytes(prefix) % 360) as f32
+}
+
+/// Convert HSL (hue in degrees, saturation and lightness in 0..=1) to RGB
+pub(crate) fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [u8; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r, g, b) = match h as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    [
+        ((r + m) * 255.0) as u8,
+        ((g + m) * 255.0) as u8,
+        ((b + m) * 255.0) as u8,
+    ]
+}
+
 impl Default for CoverGenerator {
     fn default() -> Self {
         Self::new_with_fallback()
@@ -525,11 +589,24 @@ impl CoverService {
         self.process_and_store(book_id, image).await
     }
 
-    /// Process cover image and create all resolutions
-    async fn process_and_store(&self, book_id: Uuid, image: CoverImage) -> FormatResult<CoverSet> {
+    /// Replace a book's cover with user-supplied image bytes. Nothing is
+    /// written unless the bytes decode as an image.
+    pub async fn store_custom_cover(&self, book_id: Uuid, bytes: &[u8]) -> FormatResult<CoverSet> {
+        let image = image::load_from_memory(bytes)?;
+        self.process_and_store(book_id, CoverImage::new(image))
+            .await
+    }
+
+    /// Process cover image and create all resolutions. Needs no runtime, so
+    /// blocking import code can drive it with `futures::executor::block_on`.
+    pub(crate) async fn process_and_store(
+        &self,
+        book_id: Uuid,
+        image: CoverImage,
+    ) -> FormatResult<CoverSet> {
         // Create cover directory
         let cover_dir = self.storage_path.join(book_id.to_string());
-        tokio::fs::create_dir_all(&cover_dir).await?;
+        std::fs::create_dir_all(&cover_dir)?;
 
         // Generate three resolutions
         let thumb = image::imageops::resize(
@@ -585,6 +662,57 @@ impl CoverService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_seed_hue_is_stable() {
+        assert_eq!(seed_hue("sci-fi"), 77.0);
+    }
+
+    #[tokio::test]
+    async fn test_custom_cover_from_local_png() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let png_path = temp_dir.path().join("cover.png");
+        RgbaImage::from_pixel(60, 90, Rgba([200, 40, 40, 255]))
+            .save(&png_path)
+            .unwrap();
+
+        let source = CoverSource::File {
+            path: png_path.to_string_lossy().to_string(),
+        };
+        let bytes = load_cover_source(&source).await.unwrap();
+
+        let service = CoverService::new(temp_dir.path().join("covers")).unwrap();
+        let book_id = Uuid::new_v4();
+        let cover_set = service.store_custom_cover(book_id, &bytes).await.unwrap();
+
+        for (path, width) in [
+            (&cover_set.thumbnail, THUMBNAIL_WIDTH),
+            (&cover_set.medium, MEDIUM_WIDTH),
+            (&cover_set.full, 60),
+        ] {
+            assert_eq!(image::open(path).unwrap().width(), width);
+        }
+
+        // Garbage is rejected before anything is written
+        let other = Uuid::new_v4();
+        assert!(service
+            .store_custom_cover(other, b"not an image")
+            .await
+            .is_err());
+        assert!(!temp_dir
+            .path()
+            .join("covers")
+            .join(other.to_string())
+            .exists());
+
+        let traversal = CoverSource::File {
+            path: format!("{}/../cover.png", temp_dir.path().display()),
+        };
+        assert!(matches!(
+            load_cover_source(&traversal).await,
+            Err(ShioriError::Validation(_))
+        ));
+    }
+
     #[test]
     fn test_color_scheme_generation() {
         let generator = CoverGenerator::new_with_fallback();
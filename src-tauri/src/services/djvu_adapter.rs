@@ -0,0 +1,246 @@
+use crate::error::{Result, ShioriError};
+use crate::services::djvu_document::{self, DjvuDocument, DjvuError, PageInfo};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Paginated reader for DjVu. Each page is a chapter holding its text layer;
+/// the page itself is rasterized on demand through `render_page`.
+pub struct DjvuAdapter {
+    path: PathBuf,
+    metadata: Option<BookMetadata>,
+    pages: Vec<PageInfo>,
+    // Text layers fetched so far, one slot per page
+    texts: Mutex<Vec<Option<String>>>,
+}
+
+impl DjvuAdapter {
+    pub fn new() -> Self {
+        Self {
+            path: PathBuf::new(),
+            metadata: None,
+            pages: Vec::new(),
+            texts: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn map_error(&self, e: DjvuError) -> ShioriError {
+        match e {
+            DjvuError::Unavailable => {
+                ShioriError::UnsupportedFeature("Install djvulibre to view DjVu pages".to_string())
+            }
+            DjvuError::Io(e) => ShioriError::Io(e),
+            other => ShioriError::InvalidFormat(format!("{}: {}", self.path.display(), other)),
+        }
+    }
+
+    /// Text layer of a page; empty when the page has none or the tools are missing
+    fn page_text(&self, index: usize) -> String {
+        if let Some(Some(text)) = self.texts.lock().unwrap().get(index) {
+            return text.clone();
+        }
+
+        let text = djvu_document::page_text(&self.path, index).unwrap_or_else(|e| {
+            log::warn!("[DjvuAdapter] No text for page {}: {}", index + 1, e);
+            String::new()
+        });
+        if let Some(slot) = self.texts.lock().unwrap().get_mut(index) {
+            *slot = Some(text.clone());
+        }
+        text
+    }
+
+    /// djvutxt separates columns, regions and paragraphs with control
+    /// characters; each becomes a `<p>`, with its lines joined
+    fn text_to_html(text: &str, index: usize) -> String {
+        let normalized = text.replace(
+            |c: char| matches!(c, '\x0b' | '\x0c' | '\x1d' | '\x1f'),
+            "\n\n",
+        );
+        let mut html = String::from("<div class=\"djvu-page\">\n");
+        let mut has_text = false;
+        for para in normalized.split("\n\n") {
+            let joined = para.split_whitespace().collect::<Vec<_>>().join(" ");
+            if joined.is_empty() {
+                continue;
+            }
+            has_text = true;
+            html.push_str("<p>");
+            html.push_str(
+                &joined
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;"),
+            );
+            html.push_str("</p>\n");
+        }
+        if !has_text {
+            html.push_str(&format!("<p>Page {}</p>\n", index + 1));
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+unsafe impl Send for DjvuAdapter {}
+unsafe impl Sync for DjvuAdapter {}
+
+#[async_trait]
+impl BookReaderAdapter for DjvuAdapter {
+    async fn load(&mut self, path: &str) -> Result<()> {
+        self.path = PathBuf::from(path);
+        let path_buf = self.path.clone();
+        let doc = tauri::async_runtime::spawn_blocking(move || DjvuDocument::open(&path_buf))
+            .await
+            .map_err(|e| ShioriError::Other(format!("Task spawn failed: {:?}", e)))?
+            .map_err(|e| self.map_error(e))?;
+
+        let path_buf = self.path.clone();
+        let meta =
+            tauri::async_runtime::spawn_blocking(move || djvu_document::read_metadata(&path_buf))
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .unwrap_or_default();
+
+        let title = meta.get("title").cloned().unwrap_or_else(|| {
+            self.path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown Document".to_string())
+        });
+
+        self.metadata = Some(BookMetadata {
+            title,
+            author: meta.get("author").cloned(),
+            total_chapters: doc.page_count(),
+            total_pages: Some(doc.page_count()),
+            format: "djvu".to_string(),
+        });
+        *self.texts.lock().unwrap() = vec![None; doc.page_count()];
+        self.pages = doc.pages;
+
+        Ok(())
+    }
+
+    fn get_metadata(&self) -> Result<BookMetadata> {
+        self.metadata
+            .clone()
+            .ok_or_else(|| ShioriError::Other("Metadata not loaded".to_string()))
+    }
+
+    fn get_toc(&self) -> Result<Vec<TocEntry>> {
+        let toc: Vec<TocEntry> = (0..self.pages.len())
+            .step_by(10)
+            .map(|i| TocEntry {
+                label: format!("Page {}", i + 1),
+                location: format!("page:{}", i + 1),
+                level: 0,
+                children: Vec::new(),
+            })
+            .collect();
+        Ok(toc)
+    }
+
+    fn get_chapter(&self, index: usize) -> Result<Chapter> {
+        if index >= self.pages.len() {
+            return Err(ShioriError::ChapterReadFailed {
+                chapter_index: index,
+                cause: "Page index out of bounds".to_string(),
+            });
+        }
+        Ok(Chapter {
+            index,
+            title: format!("Page {}", index + 1),
+            content: Self::text_to_html(&self.page_text(index), index),
+            location: format!("page:{}", index + 1),
+            word_count: 0,
+            estimated_minutes: 0.0,
+        })
+    }
+
+    fn chapter_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
+        let mut results = Vec::new();
+        for page_num in 0..self.pages.len() {
+            results.extend(matcher.search_chapter(
+                &self.page_text(page_num),
+                page_num,
+                format!("Page {}", page_num + 1),
+                format!("page:{}", page_num + 1),
+            ));
+        }
+        Ok(results)
+    }
+
+    fn get_resource(&self, _path: &str) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn get_resource_mime(&self, _path: &str) -> Result<String> {
+        Ok("application/octet-stream".to_string())
+    }
+
+    fn supports_pagination(&self) -> bool {
+        true
+    }
+
+    fn supports_images(&self) -> bool {
+        true
+    }
+
+    async fn render_page(&self, page_number: usize, scale: f32) -> Result<Vec<u8>> {
+        let (width, height) = self.get_page_dimensions(page_number)?;
+        let scale = scale.clamp(0.1, 8.0);
+        let path = self.path.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            djvu_document::render_page_png(
+                &path,
+                page_number,
+                (width * scale).ceil() as u32,
+                (height * scale).ceil() as u32,
+            )
+        })
+        .await
+        .map_err(|e| ShioriError::Other(format!("Task spawn failed: {:?}", e)))?
+        .map_err(|e| self.map_error(e))
+    }
+
+    fn get_page_dimensions(&self, page_number: usize) -> Result<(f32, f32)> {
+        self.pages
+            .get(page_number)
+            .map(PageInfo::size_points)
+            .ok_or_else(|| ShioriError::ChapterReadFailed {
+                chapter_index: page_number,
+                cause: "Page index out of bounds".to_string(),
+            })
+    }
+
+    fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_layer_paragraphs() {
+        let html = DjvuAdapter::text_to_html("Fish & chips\nare <good>\x1fSecond\x0c", 0);
+        assert_eq!(
+            html,
+            "<div class=\"djvu-page\">\n<p>Fish &amp; chips are &lt;good&gt;</p>\n<p>Second</p>\n</div>\n"
+        );
+        assert!(DjvuAdapter::text_to_html(" \n", 4).contains("<p>Page 5</p>"));
+    }
+}
@@ -0,0 +1,356 @@
+/// DjVu document access.
+///
+/// The IFF85 container is parsed natively for page count and geometry, so
+/// import and validation work without extra software. Rasterizing pages,
+/// reading the hidden text layer and metadata shell out to djvulibre's
+/// `ddjvu`, `djvutxt` and `djvused`, the same way OCR drives tesseract.
+/// Everything here is blocking and meant to run inside `spawn_blocking`.
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+/// Resolution assumed when a page's INFO chunk has none (djvulibre's default)
+const DEFAULT_DPI: u16 = 300;
+
+#[derive(Debug, Error)]
+pub enum DjvuError {
+    #[error("Not a DjVu file")]
+    NotDjvu,
+
+    #[error("Malformed DjVu file: {0}")]
+    Malformed(String),
+
+    #[error("Indirect (multi-file) DjVu documents are not supported")]
+    Indirect,
+
+    #[error("DjVu tools (djvulibre) are not installed")]
+    Unavailable,
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("DjVu rendering failed: {0}")]
+    Failed(String),
+}
+
+/// Geometry of a single page, from its INFO chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageInfo {
+    pub width: u16,
+    pub height: u16,
+    pub dpi: u16,
+}
+
+impl PageInfo {
+    /// Page size in points (1/72 inch), matching PDF page dimensions
+    pub fn size_points(&self) -> (f32, f32) {
+        let dpi = self.dpi as f32;
+        (
+            self.width as f32 * 72.0 / dpi,
+            self.height as f32 * 72.0 / dpi,
+        )
+    }
+}
+
+/// Page structure of a single-page or bundled multi-page DjVu file
+#[derive(Debug, Clone)]
+pub struct DjvuDocument {
+    pub pages: Vec<PageInfo>,
+}
+
+impl DjvuDocument {
+    pub fn open(path: &Path) -> Result<Self, DjvuError> {
+        Self::parse(&std::fs::read(path)?)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, DjvuError> {
+        let body = data.strip_prefix(b"AT&T").ok_or(DjvuError::NotDjvu)?;
+        let (id, form, _) = next_chunk(body)?.ok_or(DjvuError::NotDjvu)?;
+        if id != b"FORM" || form.len() < 4 {
+            return Err(DjvuError::NotDjvu);
+        }
+
+        let (kind, children) = form.split_at(4);
+        let pages = match kind {
+            b"DJVU" => vec![page_info(children)?],
+            b"DJVM" => {
+                let mut pages = Vec::new();
+                let mut bundled = true;
+                let mut rest = children;
+                while let Some((id, chunk, next)) = next_chunk(rest)? {
+                    if id == b"DIRM" {
+                        // High bit of the first byte marks a bundled document
+                        bundled = chunk.first().is_some_and(|flags| flags & 0x80 != 0);
+                    } else if id == b"FORM" && chunk.starts_with(b"DJVU") {
+                        pages.push(page_info(&chunk[4..])?);
+                    }
+                    rest = next;
+                }
+                if !bundled {
+                    return Err(DjvuError::Indirect);
+                }
+                pages
+            }
+            _ => return Err(DjvuError::NotDjvu),
+        };
+
+        if pages.is_empty() {
+            return Err(DjvuError::Malformed("document has no pages".to_string()));
+        }
+        Ok(Self { pages })
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+/// Split the next chunk off `data`: `(id, contents, remainder)`.
+/// Chunks are padded to an even length.
+fn next_chunk(data: &[u8]) -> Result<Option<(&[u8], &[u8], &[u8])>, DjvuError> {
+    if data.len() < 8 {
+        return Ok(None);
+    }
+    let id = &data[..4];
+    let len = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let end = 8usize
+        .checked_add(len)
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| {
+            DjvuError::Malformed(format!(
+                "{} chunk runs past end of file",
+                String::from_utf8_lossy(id)
+            ))
+        })?;
+    let next = (end + (len & 1)).min(data.len());
+    Ok(Some((id, &data[8..end], &data[next..])))
+}
+
+/// Read the INFO chunk among a page FORM's children
+fn page_info(children: &[u8]) -> Result<PageInfo, DjvuError> {
+    let mut rest = children;
+    while let Some((id, chunk, next)) = next_chunk(rest)? {
+        if id == b"INFO" && chunk.len() >= 4 {
+            let width = u16::from_be_bytes([chunk[0], chunk[1]]);
+            let height = u16::from_be_bytes([chunk[2], chunk[3]]);
+            // Resolution is the one little-endian field in the format
+            let dpi = match chunk.get(6..8) {
+                Some(&[lo, hi]) => u16::from_le_bytes([lo, hi]),
+                _ => 0,
+            };
+            return Ok(PageInfo {
+                width,
+                height,
+                dpi: if (25..=6000).contains(&dpi) {
+                    dpi
+                } else {
+                    DEFAULT_DPI
+                },
+            });
+        }
+        rest = next;
+    }
+    Err(DjvuError::Malformed("page without INFO chunk".to_string()))
+}
+
+/// True when djvulibre's command-line tools can be launched
+pub fn is_available() -> bool {
+    Command::new("ddjvu")
+        .arg("--help")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Missing executables mean djvulibre isn't installed
+fn spawn_error(e: std::io::Error) -> DjvuError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        DjvuError::Unavailable
+    } else {
+        DjvuError::Io(e)
+    }
+}
+
+/// Rasterize page `index` (0-based) to PNG, fitted inside `max_width` x
+/// `max_height` pixels with the aspect ratio preserved
+pub fn render_page_png(
+    path: &Path,
+    index: usize,
+    max_width: u32,
+    max_height: u32,
+) -> Result<Vec<u8>, DjvuError> {
+    let work_dir = tempfile::tempdir()?;
+    let output = work_dir.path().join("page.pnm");
+
+    let status = Command::new("ddjvu")
+        .arg("-format=pnm")
+        .arg(format!("-page={}", index + 1))
+        .arg(format!("-size={}x{}", max_width.max(1), max_height.max(1)))
+        .arg(path)
+        .arg(&output)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(spawn_error)?;
+    if !status.success() {
+        return Err(DjvuError::Failed(format!(
+            "ddjvu exited with {} on page {}",
+            status,
+            index + 1
+        )));
+    }
+
+    // ddjvu writes PBM, PGM or PPM depending on the page's color depth
+    let image = image::ImageReader::open(&output)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| DjvuError::Failed(e.to_string()))?;
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| DjvuError::Failed(e.to_string()))?;
+    Ok(png)
+}
+
+/// Hidden text layer of page `index` (0-based); empty for pure scans
+pub fn page_text(path: &Path, index: usize) -> Result<String, DjvuError> {
+    let output = Command::new("djvutxt")
+        .arg(format!("--page={}", index + 1))
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(spawn_error)?;
+    if !output.status.success() {
+        return Err(DjvuError::Failed(format!(
+            "djvutxt exited with {} on page {}",
+            output.status,
+            index + 1
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Document-level metadata (`title`, `author`, ...) as stored by `djvused`
+pub fn read_metadata(path: &Path) -> Result<HashMap<String, String>, DjvuError> {
+    let output = Command::new("djvused")
+        .args(["-u", "-e", "print-meta"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(spawn_error)?;
+    if !output.status.success() {
+        return Err(DjvuError::Failed(format!(
+            "djvused exited with {}",
+            output.status
+        )));
+    }
+    Ok(parse_meta(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `print-meta` output: one `key "value"` pair per line
+fn parse_meta(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(char::is_whitespace)?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value)
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\");
+            (!value.trim().is_empty()).then(|| (key.to_lowercase(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    fn page(width: u16, height: u16, dpi: u16) -> Vec<u8> {
+        let mut info = Vec::new();
+        info.extend_from_slice(&width.to_be_bytes());
+        info.extend_from_slice(&height.to_be_bytes());
+        info.extend_from_slice(&[24, 0]);
+        info.extend_from_slice(&dpi.to_le_bytes());
+        info.extend_from_slice(&[22, 1]);
+        let mut form = b"DJVU".to_vec();
+        form.extend(chunk(b"INFO", &info));
+        // Odd-length chunk to exercise padding
+        form.extend(chunk(b"Sjbz", &[0; 3]));
+        chunk(b"FORM", &form)
+    }
+
+    fn file(kind: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut form = kind.to_vec();
+        form.extend_from_slice(children);
+        let mut out = b"AT&T".to_vec();
+        out.extend(chunk(b"FORM", &form));
+        out
+    }
+
+    #[test]
+    fn test_parse_single_page() {
+        let data = file(b"DJVU", &page(2550, 3300, 300)[12..]);
+        let doc = DjvuDocument::parse(&data).unwrap();
+        assert_eq!(doc.page_count(), 1);
+        assert_eq!(
+            doc.pages[0],
+            PageInfo {
+                width: 2550,
+                height: 3300,
+                dpi: 300
+            }
+        );
+        assert_eq!(doc.pages[0].size_points(), (612.0, 792.0));
+    }
+
+    #[test]
+    fn test_parse_bundled_and_indirect() {
+        let mut children = chunk(b"DIRM", &[0x81, 0, 2]);
+        children.extend(page(1000, 1500, 150));
+        children.extend(page(1000, 1500, 0));
+        let doc = DjvuDocument::parse(&file(b"DJVM", &children)).unwrap();
+        assert_eq!(doc.page_count(), 2);
+        assert_eq!(doc.pages[1].dpi, DEFAULT_DPI);
+
+        let indirect = file(b"DJVM", &chunk(b"DIRM", &[0x01, 0, 2]));
+        assert!(matches!(
+            DjvuDocument::parse(&indirect),
+            Err(DjvuError::Indirect)
+        ));
+        assert!(matches!(
+            DjvuDocument::parse(b"%PDF-1.7"),
+            Err(DjvuError::NotDjvu)
+        ));
+        let truncated = &file(b"DJVU", &page(10, 10, 300)[12..])[..20];
+        assert!(matches!(
+            DjvuDocument::parse(truncated),
+            Err(DjvuError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_meta() {
+        let meta =
+            parse_meta("title\t\"The \\\"Best\\\" Book\"\nauthor\t\"Jane Doe\"\nyear\t\"\"\n");
+        assert_eq!(meta.get("title").unwrap(), "The \"Best\" Book");
+        assert_eq!(meta.get("author").unwrap(), "Jane Doe");
+        assert!(!meta.contains_key("year"));
+    }
+}
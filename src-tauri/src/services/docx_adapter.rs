@@ -1,5 +1,7 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use docx_rs::*;
 use std::fs;
@@ -111,6 +113,27 @@ impl DocxAdapter {
 
         (html, toc)
     }
+
+    /// Plain text of every paragraph, one paragraph per line
+    pub(crate) fn plain_text(doc: &Docx) -> String {
+        let mut content = String::new();
+        for child in &doc.document.children {
+            if let DocumentChild::Paragraph(para) = child {
+                for child in &para.children {
+                    if let ParagraphChild::Run(run) = child {
+                        for child in &run.children {
+                            if let RunChild::Text(t) = child {
+                                content.push_str(&t.text);
+                                content.push(' ');
+                            }
+                        }
+                    }
+                }
+                content.push('\n');
+            }
+        }
+        content
+    }
 }
 
 unsafe impl Send for DocxAdapter {}
@@ -171,6 +194,8 @@ impl BookReaderAdapter for DocxAdapter {
             title: "Document".to_string(),
             content: self.html_content.clone(),
             location: "docx:start".to_string(),
+            word_count: 0,
+            estimated_minutes: 0.0,
         })
     }
 
@@ -178,45 +203,19 @@ impl BookReaderAdapter for DocxAdapter {
         1
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
-        let content_lower = self.html_content.to_lowercase();
-        let mut results = Vec::new();
-
-        let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-        if !matches.is_empty() {
-            let first_match_pos = matches[0].0;
-
-            // Safely slice using character boundaries to avoid panics on multi-byte UTF-8
-            let char_indices: Vec<(usize, char)> = self.html_content.char_indices().collect();
-            let char_idx = char_indices
-                .iter()
-                .position(|&(b_idx, _)| b_idx >= first_match_pos)
-                .unwrap_or(0);
-            let start_char_idx = char_idx.saturating_sub(50);
-            let end_char_idx = (char_idx + query.chars().count() + 50).min(char_indices.len());
-            let start_byte = char_indices
-                .get(start_char_idx)
-                .map(|&(b, _)| b)
-                .unwrap_or(0);
-            let end_byte = if end_char_idx >= char_indices.len() {
-                self.html_content.len()
-            } else {
-                char_indices[end_char_idx].0
-            };
-
-            let snippet = format!("...{}...", &self.html_content[start_byte..end_byte]);
-
-            results.push(SearchResult {
-                chapter_index: 0,
-                chapter_title: "Document".to_string(),
-                snippet,
-                location: "docx:start".to_string(),
-                match_count: matches.len(),
-            });
-        }
-
-        Ok(results)
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
+        Ok(matcher
+            .search_chapter(
+                &self.html_content,
+                0,
+                "Document".to_string(),
+                "docx:start".to_string(),
+            )
+            .into_iter()
+            .collect())
     }
 
     fn get_resource(&self, _path: &str) -> Result<Vec<u8>> {
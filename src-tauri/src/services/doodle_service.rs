@@ -0,0 +1,483 @@
+//! Doodle overlays: rendering saved strokes to a standalone SVG or PNG, and
+//! copying or moving them between pages and books.
+//!
+//! Strokes are stored by the reader as JSON: points are `[x%, y%, pressure]`
+//! relative to the page and `width` is in pixels at the exported size.
+//! Eraser strokes mask out pen strokes, as in the reader's SVG layer.
+
+use crate::error::{Result, ShioriError};
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::draw_filled_circle_mut;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::io::Cursor;
+
+/// Eraser strokes are drawn this many times wider than their `width`
+const ERASER_WIDTH_FACTOR: f32 = 3.0;
+
+/// Largest `strokes_json` a single page's doodle may hold
+pub const MAX_DOODLE_BYTES: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoodleFormat {
+    Svg,
+    Png,
+}
+
+impl DoodleFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            _ => Err(ShioriError::Validation(format!(
+                "Unsupported doodle export format: {}",
+                format
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Stroke {
+    #[serde(default)]
+    tool: String,
+    #[serde(default)]
+    color: String,
+    width: f32,
+    points: Vec<[f32; 3]>,
+}
+
+impl Stroke {
+    fn is_eraser(&self) -> bool {
+        self.tool == "eraser"
+    }
+}
+
+/// Render `strokes_json` at `width` x `height` pixels. An empty stroke list
+/// gives a fully transparent overlay of that size.
+pub fn render(
+    strokes_json: &str,
+    format: DoodleFormat,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>> {
+    let strokes: Vec<Stroke> = if strokes_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(strokes_json)?
+    };
+
+    match format {
+        DoodleFormat::Svg => Ok(render_svg(&strokes, width, height).into_bytes()),
+        DoodleFormat::Png => render_png(&strokes, width, height),
+    }
+}
+
+fn path_data(points: &[[f32; 3]]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, [x, y, _])| format!("{} {} {}", if i == 0 { "M" } else { "L" }, x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render_svg(strokes: &[Stroke], width: u32, height: u32) -> String {
+    let path = |stroke: &Stroke, color: &str, stroke_width: f32| {
+        format!(
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\" vector-effect=\"non-scaling-stroke\"/>",
+            path_data(&stroke.points),
+            escape_attr(color),
+            stroke_width
+        )
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 100 100\" preserveAspectRatio=\"none\">",
+        width, height
+    );
+    svg.push_str("<defs><mask id=\"eraser-mask\"><rect x=\"0\" y=\"0\" width=\"100\" height=\"100\" fill=\"white\"/>");
+    for stroke in strokes.iter().filter(|s| s.is_eraser()) {
+        svg.push_str(&path(stroke, "black", stroke.width * ERASER_WIDTH_FACTOR));
+    }
+    svg.push_str("</mask></defs><g mask=\"url(#eraser-mask)\">");
+    for stroke in strokes.iter().filter(|s| !s.is_eraser()) {
+        svg.push_str(&path(stroke, &stroke.color, stroke.width));
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
+
+/// `#rgb` / `#rrggbb` (optionally `#rrggbbaa`); anything else is black
+fn parse_color(color: &str) -> Rgba<u8> {
+    let hex = color.trim().trim_start_matches('#');
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    let parsed = match hex.len() {
+        3 => hex
+            .chars()
+            .map(|c| channel(&c.to_string()).map(|v| v * 17))
+            .collect::<Option<Vec<_>>>()
+            .map(|c| [c[0], c[1], c[2], 255]),
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| hex.get(i..i + 2).and_then(channel))
+            .collect::<Option<Vec<_>>>()
+            .map(|c| [c[0], c[1], c[2], c.get(3).copied().unwrap_or(255)]),
+        _ => None,
+    };
+    Rgba(parsed.unwrap_or([0, 0, 0, 255]))
+}
+
+/// Stamp filled circles along each segment to draw a round-capped line
+fn draw_stroke(canvas: &mut RgbaImage, points: &[[f32; 3]], stroke_width: f32, color: Rgba<u8>) {
+    let (w, h) = (canvas.width() as f32, canvas.height() as f32);
+    let radius = (stroke_width / 2.0).max(0.5);
+    let step = (radius / 2.0).max(0.5);
+    let to_px = |[x, y, _]: [f32; 3]| (x / 100.0 * w, y / 100.0 * h);
+
+    let mut stamp = |x: f32, y: f32| {
+        draw_filled_circle_mut(
+            canvas,
+            (x.round() as i32, y.round() as i32),
+            radius.round() as i32,
+            color,
+        );
+    };
+
+    let mut pixels = points.iter().copied().map(to_px);
+    let Some(mut prev) = pixels.next() else {
+        return;
+    };
+    stamp(prev.0, prev.1);
+    for next in pixels {
+        let distance = ((next.0 - prev.0).powi(2) + (next.1 - prev.1).powi(2)).sqrt();
+        let steps = (distance / step).ceil().max(1.0) as usize;
+        for i in 1..=steps {
+            let t = i as f32 / steps as f32;
+            stamp(
+                prev.0 + (next.0 - prev.0) * t,
+                prev.1 + (next.1 - prev.1) * t,
+            );
+        }
+        prev = next;
+    }
+}
+
+fn render_png(strokes: &[Stroke], width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut canvas = RgbaImage::new(width, height);
+
+    for stroke in strokes.iter().filter(|s| !s.is_eraser()) {
+        draw_stroke(
+            &mut canvas,
+            &stroke.points,
+            stroke.width,
+            parse_color(&stroke.color),
+        );
+    }
+    // Drawing with a transparent color replaces pixels rather than blending,
+    // which clears whatever the erasers cover
+    for stroke in strokes.iter().filter(|s| s.is_eraser()) {
+        draw_stroke(
+            &mut canvas,
+            &stroke.points,
+            stroke.width * ERASER_WIDTH_FACTOR,
+            Rgba([0, 0, 0, 0]),
+        );
+    }
+
+    let mut png = Vec::new();
+    canvas
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| ShioriError::Other(format!("Failed to encode doodle PNG: {}", e)))?;
+    Ok(png)
+}
+
+/// Pages of `book_id` that have a doodle, numeric page numbers in order
+pub fn list_doodle_pages(conn: &Connection, book_id: i64) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT page_number FROM doodles WHERE book_id = ?1
+         ORDER BY CAST(page_number AS INTEGER), page_number",
+    )?;
+    let pages = stmt
+        .query_map([book_id], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    Ok(pages)
+}
+
+/// Append the strokes of `incoming` to `existing`, skipping strokes whose
+/// `id` is already present so repeated copies do not duplicate them
+fn merge_strokes(existing: &str, incoming: &str) -> Result<String> {
+    let mut strokes: Vec<serde_json::Value> = serde_json::from_str(existing)?;
+    let incoming: Vec<serde_json::Value> = serde_json::from_str(incoming)?;
+    let ids: HashSet<String> = strokes
+        .iter()
+        .filter_map(|s| s.get("id").and_then(|id| id.as_str()).map(str::to_string))
+        .collect();
+    strokes.extend(
+        incoming
+            .into_iter()
+            .filter(|s| match s.get("id").and_then(|id| id.as_str()) {
+                Some(id) => !ids.contains(id),
+                None => true,
+            }),
+    );
+    Ok(serde_json::to_string(&strokes)?)
+}
+
+fn doodle_strokes(conn: &Connection, book_id: i64, page: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT strokes_json FROM doodles WHERE book_id = ?1 AND page_number = ?2",
+            params![book_id, page],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Write `strokes_json` to a page, merging with or replacing what is there
+pub(crate) fn put_strokes(
+    conn: &Connection,
+    book_id: i64,
+    page: &str,
+    strokes_json: &str,
+    overwrite: bool,
+) -> Result<()> {
+    let existing = doodle_strokes(conn, book_id, page)?;
+    let strokes = match &existing {
+        Some(existing) if !overwrite => merge_strokes(existing, strokes_json)?,
+        _ => strokes_json.to_string(),
+    };
+    if strokes.len() > MAX_DOODLE_BYTES {
+        return Err(ShioriError::Validation(format!(
+            "Doodle for page {} would exceed the 5MB limit",
+            page
+        )));
+    }
+
+    if existing.is_some() {
+        conn.execute(
+            "UPDATE doodles SET strokes_json = ?1, updated_at = CURRENT_TIMESTAMP
+             WHERE book_id = ?2 AND page_number = ?3",
+            params![strokes, book_id, page],
+        )?;
+    } else {
+        conn.execute(
+            "INSERT INTO doodles (book_id, page_number, strokes_json) VALUES (?1, ?2, ?3)",
+            params![book_id, page, strokes],
+        )?;
+    }
+    Ok(())
+}
+
+/// Copy every doodle of `from_book_id` to the same pages of `to_book_id`.
+/// Pages that already have a doodle are merged, or replaced when
+/// `overwrite` is set. Returns the number of pages copied.
+pub fn copy_doodles(
+    conn: &Connection,
+    from_book_id: i64,
+    to_book_id: i64,
+    overwrite: bool,
+) -> Result<usize> {
+    if from_book_id == to_book_id {
+        return Err(ShioriError::Validation(
+            "Cannot copy doodles onto the same book".to_string(),
+        ));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let doodles = tx
+        .prepare("SELECT page_number, strokes_json FROM doodles WHERE book_id = ?1")?
+        .query_map([from_book_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (page, strokes) in &doodles {
+        put_strokes(&tx, to_book_id, page, strokes, overwrite)?;
+    }
+    tx.commit()?;
+    Ok(doodles.len())
+}
+
+/// Move a book's doodle from one page to another. If the target page has a
+/// doodle the two are merged, or the target is replaced when `overwrite` is
+/// set.
+pub fn move_doodle(
+    conn: &Connection,
+    book_id: i64,
+    from_page: &str,
+    to_page: &str,
+    overwrite: bool,
+) -> Result<()> {
+    if from_page == to_page {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let strokes = doodle_strokes(&tx, book_id, from_page)?
+        .ok_or_else(|| ShioriError::Validation(format!("No doodle on page {}", from_page)))?;
+    // Free the source row first so the (book_id, page_number) index never
+    // sees two rows for the target page
+    tx.execute(
+        "DELETE FROM doodles WHERE book_id = ?1 AND page_number = ?2",
+        params![book_id, from_page],
+    )?;
+    put_strokes(&tx, book_id, to_page, &strokes, overwrite)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use tempfile::tempdir;
+
+    fn add_book(conn: &Connection, uuid: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+            params![uuid, format!("/tmp/{}.epub", uuid)],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn stroke(id: &str) -> String {
+        format!(
+            r##"{{"id":"{}","tool":"pen","color":"#000","width":2,"points":[[1,1,1]]}}"##,
+            id
+        )
+    }
+
+    fn stroke_ids(conn: &Connection, book_id: i64, page: &str) -> Vec<String> {
+        let json = doodle_strokes(conn, book_id, page).unwrap().unwrap();
+        let strokes: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        strokes
+            .iter()
+            .map(|s| s["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_copy_doodles_creates_and_merges_rows() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("doodles.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        let old = add_book(&conn, "old");
+        let new = add_book(&conn, "new");
+        for (book, page, ids) in [
+            (old, "1", vec!["a"]),
+            (old, "10", vec!["b"]),
+            (new, "1", vec!["c"]),
+        ] {
+            let strokes = ids
+                .iter()
+                .map(|id| stroke(id))
+                .collect::<Vec<_>>()
+                .join(",");
+            put_strokes(&conn, book, page, &format!("[{}]", strokes), true).unwrap();
+        }
+
+        assert_eq!(copy_doodles(&conn, old, new, false).unwrap(), 2);
+        assert_eq!(list_doodle_pages(&conn, new).unwrap(), vec!["1", "10"]);
+        assert_eq!(stroke_ids(&conn, new, "1"), vec!["c", "a"]);
+        // Copying again does not duplicate merged strokes
+        copy_doodles(&conn, old, new, false).unwrap();
+        assert_eq!(stroke_ids(&conn, new, "1"), vec!["c", "a"]);
+
+        copy_doodles(&conn, old, new, true).unwrap();
+        assert_eq!(stroke_ids(&conn, new, "1"), vec!["a"]);
+        assert_eq!(list_doodle_pages(&conn, old).unwrap().len(), 2);
+        assert!(copy_doodles(&conn, old, old, true).is_err());
+    }
+
+    #[test]
+    fn test_move_doodle_respects_unique_page() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("doodles.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        let book = add_book(&conn, "book");
+        put_strokes(&conn, book, "2", &format!("[{}]", stroke("a")), true).unwrap();
+        put_strokes(&conn, book, "3", &format!("[{}]", stroke("b")), true).unwrap();
+        put_strokes(&conn, book, "4", &format!("[{}]", stroke("c")), true).unwrap();
+
+        move_doodle(&conn, book, "2", "5", false).unwrap();
+        assert_eq!(list_doodle_pages(&conn, book).unwrap(), vec!["3", "4", "5"]);
+
+        // Onto an occupied page: merge keeps both, overwrite keeps the source
+        move_doodle(&conn, book, "3", "5", false).unwrap();
+        assert_eq!(stroke_ids(&conn, book, "5"), vec!["a", "b"]);
+        move_doodle(&conn, book, "4", "5", true).unwrap();
+        assert_eq!(stroke_ids(&conn, book, "5"), vec!["c"]);
+        assert_eq!(list_doodle_pages(&conn, book).unwrap(), vec!["5"]);
+
+        assert!(move_doodle(&conn, book, "9", "1", false).is_err());
+    }
+
+    #[test]
+    fn test_merged_doodle_respects_size_limit() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("doodles.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        let book = add_book(&conn, "book");
+        let big = |id: &str| {
+            let points = vec!["[1,1,1]"; MAX_DOODLE_BYTES / 2 / 8].join(",");
+            format!(r#"[{{"id":"{}","tool":"pen","points":[{}]}}]"#, id, points)
+        };
+        put_strokes(&conn, book, "1", &big("a"), true).unwrap();
+        put_strokes(&conn, book, "2", &big("b"), true).unwrap();
+
+        // Each half fits, the merged page does not
+        assert!(move_doodle(&conn, book, "1", "2", false).is_err());
+        assert_eq!(stroke_ids(&conn, book, "2"), vec!["b"]);
+        assert_eq!(list_doodle_pages(&conn, book).unwrap(), vec!["1", "2"]);
+    }
+
+    const TWO_STROKES: &str = r##"[
+        {"id":"a","tool":"pen","color":"#ff0000","width":4,"points":[[10,10,0.5],[90,90,0.5]],"timestamp":1},
+        {"id":"b","tool":"pen","color":"#00f","width":2,"points":[[10,90,0.5],[90,10,0.5]],"timestamp":2}
+    ]"##;
+
+    #[test]
+    fn test_two_stroke_doodle_renders_png() {
+        let png = render(TWO_STROKES, DoodleFormat::Png, 200, 100).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (200, 100));
+
+        let painted = image.pixels().filter(|p| p.0[3] > 0).count();
+        assert!(painted > 0);
+        assert_eq!(image.get_pixel(20, 10).0, [255, 0, 0, 255]);
+        // Right edge, away from both strokes
+        assert_eq!(image.get_pixel(199, 50).0[3], 0);
+    }
+
+    #[test]
+    fn test_empty_and_erased_doodles() {
+        let png = render("[]", DoodleFormat::Png, 30, 40).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (30, 40));
+        assert!(image.pixels().all(|p| p.0[3] == 0));
+
+        let erased = r##"[
+            {"tool":"pen","color":"#000000","width":4,"points":[[0,50,1],[100,50,1]]},
+            {"tool":"eraser","color":"#000000","width":20,"points":[[0,50,1],[100,50,1]]}
+        ]"##;
+        let png = render(erased, DoodleFormat::Png, 100, 100).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert!(image.pixels().all(|p| p.0[3] == 0));
+
+        let svg =
+            String::from_utf8(render(TWO_STROKES, DoodleFormat::Svg, 200, 100).unwrap()).unwrap();
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.contains("d=\"M 10 10 L 90 90\""));
+        assert!(render("not json", DoodleFormat::Svg, 10, 10).is_err());
+    }
+}
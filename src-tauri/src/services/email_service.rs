@@ -0,0 +1,541 @@
+//! Email delivery of library books, e.g. to a Kindle's personal document
+//! address.
+//!
+//! The SMTP account lives in the `smtp_settings` table and its password in
+//! the OS credential store (the table only holds it on mobile, or until a
+//! password saved by an older version is moved over). The password is
+//! write-only from the frontend's point of view: [`SmtpSettings`] only says
+//! whether one is stored, and it is read back just before connecting.
+
+use crate::db::Database;
+use crate::error::{Result, ShioriError};
+use crate::services::conversion_engine::{self, ConversionEngine};
+use crate::services::library_service;
+use crate::utils::{http, secrets, validate};
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_MAX_ATTACHMENT_MB: u64 = 25;
+
+/// Upper bound for the configurable cap; mail providers reject far less
+pub const MAX_ATTACHMENT_MB_LIMIT: u64 = 100;
+
+/// Credential store entry holding the SMTP password
+const SMTP_SECRET: &str = "smtp-password";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Implicit TLS, usually port 465
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    StartTls,
+    /// Unencrypted, only sensible for a local relay
+    None,
+}
+
+impl SmtpSecurity {
+    fn as_str(self) -> &'static str {
+        match self {
+            SmtpSecurity::Tls => "tls",
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::None => "none",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "tls" => SmtpSecurity::Tls,
+            "none" => SmtpSecurity::None,
+            _ => SmtpSecurity::StartTls,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub from_address: String,
+    pub max_attachment_mb: u64,
+    /// Whether a password is stored. Ignored when saving.
+    #[serde(default)]
+    pub has_password: bool,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: String::new(),
+            from_address: String::new(),
+            max_attachment_mb: DEFAULT_MAX_ATTACHMENT_MB,
+            has_password: false,
+        }
+    }
+}
+
+pub fn load_smtp_settings(db: &Database) -> Result<SmtpSettings> {
+    let conn = db.get_connection()?;
+    let settings = conn.query_row(
+        "SELECT host, port, security, username, from_address, max_attachment_mb,
+                password IS NOT NULL AND password != ''
+         FROM smtp_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(SmtpSettings {
+                host: row.get(0)?,
+                port: row.get::<_, i64>(1)?.clamp(1, u16::MAX as i64) as u16,
+                security: SmtpSecurity::parse(&row.get::<_, String>(2)?),
+                username: row.get(3)?,
+                from_address: row.get(4)?,
+                max_attachment_mb: row.get::<_, i64>(5)?.max(1) as u64,
+                has_password: row.get(6)?,
+            })
+        },
+    )?;
+    drop(conn);
+
+    let has_password = settings.has_password
+        || (secrets::AVAILABLE
+            && secrets::get(SMTP_SECRET)
+                .unwrap_or_else(|e| {
+                    log::warn!("[Email] Could not read the SMTP password: {}", e);
+                    None
+                })
+                .is_some());
+    Ok(SmtpSettings {
+        has_password,
+        ..settings
+    })
+}
+
+/// Save the SMTP account. `password` of `None` keeps the stored one and an
+/// empty string removes it.
+pub fn save_smtp_settings(
+    db: &Database,
+    settings: &SmtpSettings,
+    password: Option<&str>,
+) -> Result<SmtpSettings> {
+    if !settings.from_address.trim().is_empty() {
+        validate::require_valid_email(settings.from_address.trim(), "from_address")?;
+    }
+    validate::require_in_range(
+        settings.max_attachment_mb as i64,
+        1,
+        MAX_ATTACHMENT_MB_LIMIT as i64,
+        "max_attachment_mb",
+    )?;
+    if settings.port == 0 {
+        return Err(ShioriError::Validation(
+            "SMTP port must be between 1 and 65535".to_string(),
+        ));
+    }
+
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE smtp_settings
+         SET host = ?1, port = ?2, security = ?3, username = ?4, from_address = ?5,
+             max_attachment_mb = ?6, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        params![
+            settings.host.trim(),
+            settings.port,
+            settings.security.as_str(),
+            settings.username.trim(),
+            settings.from_address.trim(),
+            settings.max_attachment_mb as i64,
+        ],
+    )?;
+    if let Some(password) = password {
+        if secrets::AVAILABLE {
+            secrets::set(SMTP_SECRET, Some(password).filter(|p| !p.is_empty()))?;
+            conn.execute("UPDATE smtp_settings SET password = NULL WHERE id = 1", [])?;
+        } else {
+            conn.execute(
+                "UPDATE smtp_settings SET password = NULLIF(?1, '') WHERE id = 1",
+                params![password],
+            )?;
+        }
+    }
+    drop(conn);
+
+    load_smtp_settings(db)
+}
+
+fn smtp_password(db: &Database) -> Result<Option<String>> {
+    let conn = db.get_connection()?;
+    let stored: Option<String> = conn.query_row(
+        "SELECT password FROM smtp_settings WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )?;
+    let stored = stored.filter(|p| !p.is_empty());
+    if !secrets::AVAILABLE {
+        return Ok(stored);
+    }
+
+    match stored {
+        // Saved before the credential store was used; move it over
+        Some(password) => {
+            match secrets::set(SMTP_SECRET, Some(&password)) {
+                Ok(()) => {
+                    conn.execute("UPDATE smtp_settings SET password = NULL WHERE id = 1", [])?;
+                }
+                Err(e) => log::warn!("[Email] Could not move the SMTP password: {}", e),
+            }
+            Ok(Some(password))
+        }
+        None => secrets::get(SMTP_SECRET),
+    }
+}
+
+/// A message with a single file attached
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub attachment_name: String,
+    pub attachment_mime: String,
+    pub attachment: Vec<u8>,
+}
+
+#[async_trait]
+pub trait MailTransport: Send + Sync {
+    async fn send(&self, email: OutgoingEmail) -> Result<()>;
+}
+
+/// Delivers through the configured SMTP server
+pub struct SmtpMailTransport {
+    settings: SmtpSettings,
+    password: Option<String>,
+}
+
+impl SmtpMailTransport {
+    pub fn from_db(db: &Database) -> Result<Self> {
+        let settings = load_smtp_settings(db)?;
+        if settings.host.is_empty() || settings.from_address.is_empty() {
+            return Err(ShioriError::Validation(
+                "Set up an SMTP server and sender address before sending books".to_string(),
+            ));
+        }
+        Ok(Self {
+            settings,
+            password: smtp_password(db)?,
+        })
+    }
+}
+
+#[async_trait]
+impl MailTransport for SmtpMailTransport {
+    async fn send(&self, email: OutgoingEmail) -> Result<()> {
+        let mailbox = |address: &str, field: &str| {
+            address.parse::<Mailbox>().map_err(|e| {
+                ShioriError::Validation(format!("Invalid {} '{}': {}", field, address, e))
+            })
+        };
+        let content_type = ContentType::parse(&email.attachment_mime)
+            .or_else(|_| ContentType::parse("application/octet-stream"))
+            .map_err(|e| ShioriError::Other(e.to_string()))?;
+
+        let message = Message::builder()
+            .from(mailbox(&self.settings.from_address, "sender address")?)
+            .to(mailbox(&email.to, "recipient address")?)
+            .subject(email.subject)
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(email.body))
+                    .singlepart(
+                        Attachment::new(email.attachment_name).body(email.attachment, content_type),
+                    ),
+            )
+            .map_err(|e| ShioriError::Other(format!("Failed to build email: {}", e)))?;
+
+        let host = self.settings.host.as_str();
+        let builder = match self.settings.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+        }
+        .map_err(|e| ShioriError::Other(format!("Invalid SMTP server '{}': {}", host, e)))?;
+
+        let mut builder = builder
+            .port(self.settings.port)
+            .timeout(Some(Duration::from_secs(http::DEFAULT_TIMEOUT_SECS)));
+        if let Some(password) = &self.password {
+            builder = builder.credentials(Credentials::new(
+                self.settings.username.clone(),
+                password.clone(),
+            ));
+        }
+
+        builder
+            .build()
+            .send(message)
+            .await
+            .map_err(|e| ShioriError::Other(format!("Failed to send email: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// What was sent by [`send_book`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentBook {
+    pub to_address: String,
+    pub file_name: String,
+    pub format: String,
+    pub size_bytes: u64,
+}
+
+/// Email a book over the configured SMTP account, converting it to
+/// `convert_to` first when that differs from the stored format
+pub async fn send_book(
+    db: &Database,
+    book_id: i64,
+    to_address: &str,
+    convert_to: Option<&str>,
+) -> Result<SentBook> {
+    let transport = SmtpMailTransport::from_db(db)?;
+    send_book_with(db, &transport, book_id, to_address, convert_to).await
+}
+
+pub async fn send_book_with(
+    db: &Database,
+    transport: &dyn MailTransport,
+    book_id: i64,
+    to_address: &str,
+    convert_to: Option<&str>,
+) -> Result<SentBook> {
+    let to_address = to_address.trim();
+    validate::require_valid_email(to_address, "to_address")?;
+    let max_mb = load_smtp_settings(db)?.max_attachment_mb;
+
+    let book = library_service::get_book_by_id(db, book_id)?;
+    let source = PathBuf::from(&book.file_path);
+    if !source.exists() {
+        return Err(ShioriError::FileNotFound {
+            path: book.file_path.clone(),
+        });
+    }
+
+    let source_format = book.file_format.to_lowercase();
+    let format = convert_to
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| source_format.clone());
+
+    // Keeps the converted copy alive until it has been read
+    let conversion_dir = tempfile::tempdir()?;
+    let file = if format == source_format {
+        source
+    } else {
+        convert(&source, &source_format, &format, conversion_dir.path(), db).await?
+    };
+
+    let size_bytes = std::fs::metadata(&file)?.len();
+    let size_mb = size_bytes.div_ceil(1024 * 1024);
+    if size_bytes > max_mb * 1024 * 1024 {
+        return Err(ShioriError::FileSizeLimitExceeded { size_mb, max_mb });
+    }
+
+    let file_name = format!("{}.{}", attachment_stem(&book.title), format);
+    let email = OutgoingEmail {
+        to: to_address.to_string(),
+        subject: book.title.clone(),
+        body: format!("\"{}\", sent from Shiori.", book.title),
+        attachment_name: file_name.clone(),
+        attachment_mime: attachment_mime(&format),
+        attachment: tokio::fs::read(&file).await?,
+    };
+    transport.send(email).await?;
+
+    log::info!(
+        "[Email] Sent book {} as {} ({} bytes)",
+        book_id,
+        file_name,
+        size_bytes
+    );
+    Ok(SentBook {
+        to_address: to_address.to_string(),
+        file_name,
+        format,
+        size_bytes,
+    })
+}
+
+async fn convert(
+    source: &Path,
+    source_format: &str,
+    target_format: &str,
+    dir: &Path,
+    db: &Database,
+) -> Result<PathBuf> {
+    if !conversion_engine::can_convert(source_format, target_format) {
+        return Err(ShioriError::UnsupportedFeature(format!(
+            "Cannot convert {} to {}",
+            source_format, target_format
+        )));
+    }
+    let target = dir.join(format!("book.{}", target_format));
+    ConversionEngine::convert_direct(
+        source,
+        &target,
+        source_format,
+        target_format,
+        Some(db),
+        None,
+    )
+    .await
+    .map_err(|e| ShioriError::Other(format!("Conversion failed: {}", e)))?;
+    Ok(target)
+}
+
+/// Title reduced to characters that survive every mail client's file name handling
+fn attachment_stem(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = stem.trim();
+    if stem.is_empty() {
+        "book".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+fn attachment_mime(format: &str) -> String {
+    match format {
+        "epub" => "application/epub+zip",
+        "mobi" | "azw3" => "application/x-mobipocket-ebook",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => "application/octet-stream",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<OutgoingEmail>>,
+    }
+
+    #[async_trait]
+    impl MailTransport for RecordingTransport {
+        async fn send(&self, email: OutgoingEmail) -> Result<()> {
+            self.sent.lock().unwrap().push(email);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_book_attaches_file_for_recipient() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let book_path = temp_dir.path().join("dune.epub");
+        std::fs::write(&book_path, b"epub bytes").unwrap();
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO books (id, uuid, title, file_path, file_format)
+                 VALUES (1, 'u-1', 'Dune: Messiah', ?1, 'epub')",
+                params![book_path.to_string_lossy()],
+            )
+            .unwrap();
+
+        let saved = save_smtp_settings(
+            &db,
+            &SmtpSettings {
+                host: "smtp.example.com".to_string(),
+                from_address: "me@example.com".to_string(),
+                max_attachment_mb: 1,
+                ..Default::default()
+            },
+            Some("secret"),
+        )
+        .unwrap();
+        assert!(saved.has_password);
+        assert!(!serde_json::to_string(&saved).unwrap().contains("secret"));
+        let column = || -> Option<String> {
+            db.get_connection()
+                .unwrap()
+                .query_row(
+                    "SELECT password FROM smtp_settings WHERE id = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(column(), None);
+        assert_eq!(smtp_password(&db).unwrap().as_deref(), Some("secret"));
+
+        // A password saved by an older version moves to the credential store
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "UPDATE smtp_settings SET password = 'legacy' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+        assert_eq!(smtp_password(&db).unwrap().as_deref(), Some("legacy"));
+        assert_eq!(column(), None);
+        assert_eq!(
+            secrets::get(SMTP_SECRET).unwrap().as_deref(),
+            Some("legacy")
+        );
+
+        let transport = RecordingTransport::default();
+        let sent = send_book_with(&db, &transport, 1, " reader@kindle.com ", None)
+            .await
+            .unwrap();
+        assert_eq!(sent.file_name, "Dune_ Messiah.epub");
+        assert_eq!(sent.size_bytes, 10);
+
+        let emails = transport.sent.lock().unwrap().clone();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].to, "reader@kindle.com");
+        assert_eq!(emails[0].subject, "Dune: Messiah");
+        assert_eq!(emails[0].attachment_name, "Dune_ Messiah.epub");
+        assert_eq!(emails[0].attachment_mime, "application/epub+zip");
+        assert_eq!(emails[0].attachment, b"epub bytes");
+
+        // Bad recipients and oversized files never reach the transport
+        assert!(send_book_with(&db, &transport, 1, "not-an-address", None)
+            .await
+            .is_err());
+        std::fs::write(&book_path, vec![0u8; 1024 * 1024 + 1]).unwrap();
+        assert!(matches!(
+            send_book_with(&db, &transport, 1, "reader@kindle.com", None).await,
+            Err(ShioriError::FileSizeLimitExceeded { max_mb: 1, .. })
+        ));
+        assert_eq!(transport.sent.lock().unwrap().len(), 1);
+    }
+}
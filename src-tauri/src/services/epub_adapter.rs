@@ -1,8 +1,18 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use epub::doc::EpubDoc;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
 use std::sync::RwLock;
+use zip::write::{SimpleFileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
 
 pub struct EpubAdapter {
     doc: Option<RwLock<EpubDoc<std::io::BufReader<std::fs::File>>>>,
@@ -78,6 +88,13 @@ impl EpubAdapter {
         Ok(())
     }
 
+    /// Package path of spine item `index`, e.g. `OEBPS/ch0001.xhtml`
+    pub fn chapter_path(&self, index: usize) -> Option<String> {
+        let doc = self.doc.as_ref()?.read().ok()?;
+        let item = doc.resources.get(&doc.spine.get(index)?.idref)?;
+        Some(item.path.to_string_lossy().replace('\\', "/"))
+    }
+
     fn load_metadata(&mut self) -> Result<()> {
         let doc_ref = self
             .doc
@@ -200,6 +217,8 @@ impl BookReaderAdapter for EpubAdapter {
             title,
             content,
             location: format!("epubcfi(/{})", index),
+            word_count: 0,
+            estimated_minutes: 0.0,
         })
     }
 
@@ -215,8 +234,10 @@ impl BookReaderAdapter for EpubAdapter {
         }
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
         let mut results = Vec::new();
 
         let doc_ref = self
@@ -261,45 +282,7 @@ impl BookReaderAdapter for EpubAdapter {
                 .unwrap_or_else(|| format!("Chapter {}", i + 1));
 
             let content = strip_html_tags(&raw_content);
-            let content_lower = content.to_lowercase();
-            let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-
-            if !matches.is_empty() {
-                // Get snippet around first match
-                let first_match_pos = matches[0].0;
-
-                // Safely slice strings using character boundaries to avoid panics on emoji/unicode
-                let char_indices: Vec<(usize, char)> = content.char_indices().collect();
-
-                // Find the index in our char array that corresponds to the byte position
-                let char_idx = char_indices
-                    .iter()
-                    .position(|&(b_idx, _)| b_idx >= first_match_pos)
-                    .unwrap_or(0);
-
-                let start_char_idx = char_idx.saturating_sub(50);
-                let end_char_idx = (char_idx + query.chars().count() + 50).min(char_indices.len());
-
-                let start_byte = char_indices
-                    .get(start_char_idx)
-                    .map(|&(b, _)| b)
-                    .unwrap_or(0);
-                let end_byte = if end_char_idx >= char_indices.len() {
-                    content.len()
-                } else {
-                    char_indices[end_char_idx].0
-                };
-
-                let snippet = format!("...{}...", &content[start_byte..end_byte]);
-
-                results.push(SearchResult {
-                    chapter_index: i,
-                    chapter_title: title,
-                    snippet,
-                    location: format!("epubcfi(/{})", i),
-                    match_count: matches.len(),
-                });
-            }
+            results.extend(matcher.search_chapter(&content, i, title, format!("epubcfi(/{})", i)));
         }
 
         Ok(results)
@@ -460,6 +443,218 @@ impl BookReaderAdapter for EpubAdapter {
     }
 }
 
+/// Fields to rewrite in an EPUB's package document. `None` (or only blank
+/// values) keeps whatever the file already has.
+#[derive(Debug, Clone, Default)]
+pub struct EpubMetadataUpdate {
+    pub title: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub publisher: Option<String>,
+    pub language: Option<String>,
+    pub date: Option<String>,
+}
+
+impl EpubMetadataUpdate {
+    /// `(dc element, new values)` for every field being replaced
+    fn replacements(&self) -> Vec<(&'static str, Vec<&str>)> {
+        fn single(value: &Option<String>) -> Vec<&str> {
+            value.iter().map(String::as_str).collect()
+        }
+        [
+            ("dc:title", single(&self.title)),
+            (
+                "dc:creator",
+                self.authors.iter().flatten().map(String::as_str).collect(),
+            ),
+            ("dc:publisher", single(&self.publisher)),
+            ("dc:language", single(&self.language)),
+            ("dc:date", single(&self.date)),
+        ]
+        .into_iter()
+        .map(|(tag, values): (&'static str, Vec<&str>)| {
+            let values: Vec<&str> = values
+                .into_iter()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .collect();
+            (tag, values)
+        })
+        .filter(|(_, values)| !values.is_empty())
+        .collect()
+    }
+}
+
+fn opf_error(e: impl std::fmt::Display) -> ShioriError {
+    ShioriError::InvalidFormat(format!("Invalid EPUB package document: {}", e))
+}
+
+fn read_zip_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<String> {
+    let mut content = String::new();
+    archive.by_name(name)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Location of the OPF package document, from `META-INF/container.xml`
+fn opf_path(container_xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(container_xml);
+    loop {
+        match reader.read_event().map_err(opf_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"rootfile" => {
+                if let Some(attr) = e.try_get_attribute("full-path").map_err(opf_error)? {
+                    return Ok(attr
+                        .decode_and_unescape_value(reader.decoder())
+                        .map_err(opf_error)?
+                        .into_owned());
+                }
+            }
+            Event::Eof => return Err(opf_error("container.xml has no rootfile")),
+            _ => {}
+        }
+    }
+}
+
+/// Replace the requested `dc:` elements at the top of `<metadata>`, leaving
+/// the rest of the document byte-for-byte intact
+fn rewrite_opf_metadata(opf: &str, update: &EpubMetadataUpdate) -> Result<String> {
+    let replacements = update.replacements();
+    let replaced = |e: &BytesStart| {
+        replacements
+            .iter()
+            .any(|(tag, _)| tag.as_bytes() == e.name().as_ref())
+    };
+
+    // First pass: ids of the elements being dropped, so EPUB 3
+    // `<meta refines="#id">` entries that describe them are dropped too
+    let mut dropped_ids = HashSet::new();
+    let mut reader = Reader::from_str(opf);
+    loop {
+        match reader.read_event().map_err(opf_error)? {
+            Event::Start(e) | Event::Empty(e) if replaced(&e) => {
+                if let Some(id) = e.try_get_attribute("id").map_err(opf_error)? {
+                    let id = id
+                        .decode_and_unescape_value(reader.decoder())
+                        .map_err(opf_error)?;
+                    dropped_ids.insert(format!("#{}", id));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let decoder = reader.decoder();
+    let dropped = |e: &BytesStart| -> Result<bool> {
+        if replaced(e) {
+            return Ok(true);
+        }
+        if e.local_name().as_ref() != b"meta" {
+            return Ok(false);
+        }
+        Ok(match e.try_get_attribute("refines").map_err(opf_error)? {
+            Some(attr) => dropped_ids.contains(
+                attr.decode_and_unescape_value(decoder)
+                    .map_err(opf_error)?
+                    .as_ref(),
+            ),
+            None => false,
+        })
+    };
+
+    let mut reader = Reader::from_str(opf);
+    let mut writer = Writer::new(Vec::new());
+    let mut skip_depth = 0usize;
+    loop {
+        let event = reader.read_event().map_err(opf_error)?;
+        if skip_depth > 0 {
+            match event {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_) => skip_depth -= 1,
+                Event::Eof => break,
+                _ => {}
+            }
+            continue;
+        }
+        match &event {
+            Event::Start(e) if dropped(e)? => {
+                skip_depth = 1;
+                continue;
+            }
+            Event::Empty(e) if dropped(e)? => continue,
+            Event::Eof => break,
+            _ => {}
+        }
+
+        let opens_metadata =
+            matches!(&event, Event::Start(e) if e.local_name().as_ref() == b"metadata");
+        writer.write_event(event).map_err(opf_error)?;
+        if opens_metadata {
+            for (tag, values) in &replacements {
+                for value in values {
+                    writer
+                        .write_event(Event::Text(BytesText::new("\n    ")))
+                        .map_err(opf_error)?;
+                    writer
+                        .write_event(Event::Start(BytesStart::new(*tag)))
+                        .map_err(opf_error)?;
+                    writer
+                        .write_event(Event::Text(BytesText::new(value)))
+                        .map_err(opf_error)?;
+                    writer
+                        .write_event(Event::End(BytesEnd::new(*tag)))
+                        .map_err(opf_error)?;
+                }
+            }
+        }
+    }
+    String::from_utf8(writer.into_inner()).map_err(opf_error)
+}
+
+/// Rewrite title, authors and the other fields in `update` inside the EPUB
+/// at `path`. The new archive is built in a temp file beside the original
+/// and renamed over it, so a failure never leaves a half-written book.
+pub fn write_metadata(path: &str, update: &EpubMetadataUpdate) -> Result<()> {
+    let path = Path::new(path);
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let opf_path = opf_path(&read_zip_entry(&mut archive, "META-INF/container.xml")?)?;
+    let opf = rewrite_opf_metadata(&read_zip_entry(&mut archive, &opf_path)?, update)?;
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    {
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        let mut zip = ZipWriter::new(std::io::BufWriter::new(temp.as_file_mut()));
+        // OCF requires an uncompressed `mimetype` as the very first entry
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/epub+zip")?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i)?;
+            let name = entry.name().to_string();
+            if name == "mimetype" {
+                continue;
+            }
+            if name == opf_path {
+                drop(entry);
+                zip.start_file(name, deflated)?;
+                zip.write_all(opf.as_bytes())?;
+            } else {
+                // Copied still compressed, so untouched entries are not
+                // re-encoded
+                zip.raw_copy_file(entry)?;
+            }
+        }
+        zip.finish()?.flush()?;
+    }
+    temp.as_file().sync_all()?;
+    // NamedTempFile is created owner-only; keep the book's original mode
+    std::fs::set_permissions(temp.path(), std::fs::metadata(path)?.permissions())?;
+    temp.persist(path).map_err(|e| ShioriError::Io(e.error))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,4 +664,125 @@ mod tests {
         let adapter = EpubAdapter::new();
         assert_eq!(adapter.chapter_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_search_context_windows() {
+        use crate::services::epub_builder::EpubBuilder;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fox.epub");
+        let mut builder = EpubBuilder::new();
+        builder.add_chapter(
+            "Opening".to_string(),
+            "The quick brown fox jumps over the lazy dog.".to_string(),
+        );
+        builder.add_chapter(
+            "Harbor".to_string(),
+            "Foxes are clever. A fox named Rust guarded the harbor.".to_string(),
+        );
+        builder.write_to(&path).unwrap();
+
+        let mut adapter = EpubAdapter::new();
+        adapter.load(path.to_str().unwrap()).await.unwrap();
+        let options = SearchOptions {
+            context_chars: 10,
+            ..Default::default()
+        };
+
+        let results = adapter.search("fox", &options).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].chapter_index < results[1].chapter_index);
+        assert_eq!(results[0].before, "ick brown ");
+        assert_eq!(results[0].matched, "fox");
+        assert_eq!(results[0].after, " jumps ove");
+        assert_eq!(results[0].snippet, "...ick brown fox jumps ove...");
+        assert_eq!(results[1].match_count, 2);
+        assert_eq!(results[1].matched, "Fox");
+
+        let whole_word = SearchOptions {
+            whole_word: true,
+            ..options.clone()
+        };
+        let results = adapter.search("fox", &whole_word).unwrap();
+        assert_eq!(results[1].match_count, 1);
+        assert_eq!(results[1].before, "clever. A ");
+        assert_eq!(results[1].after, " named Rus");
+
+        let case_sensitive = SearchOptions {
+            case_sensitive: true,
+            ..options
+        };
+        let results = adapter.search("Fox", &case_sensitive).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].after, "es are cle");
+
+        assert!(adapter
+            .search("  ", &SearchOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_metadata_round_trip() {
+        use crate::services::epub_builder::{EpubBuilder, EpubMetadata};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.epub");
+        let mut builder = EpubBuilder::new().metadata(EpubMetadata {
+            title: "Old Title".to_string(),
+            authors: vec!["Old Author".to_string()],
+            ..EpubMetadata::default()
+        });
+        builder.add_chapter("One".to_string(), "Chapter text.".to_string());
+        builder.write_to(&path).unwrap();
+        let path_str = path.to_str().unwrap();
+
+        write_metadata(
+            path_str,
+            &EpubMetadataUpdate {
+                title: Some("New & Improved".to_string()),
+                authors: Some(vec!["Ada Lovelace".to_string()]),
+                language: Some("  ".to_string()),
+                ..EpubMetadataUpdate::default()
+            },
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let first = archive.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), CompressionMethod::Stored);
+        drop(first);
+        let opf = read_zip_entry(&mut archive, "OEBPS/content.opf").unwrap();
+        assert!(!opf.contains("Old Title"));
+        assert!(!opf.contains("Old Author"));
+        // A blank language is ignored rather than written
+        assert!(opf.contains("<dc:language>en</dc:language>"));
+
+        let mut adapter = EpubAdapter::new();
+        adapter.load(path_str).await.unwrap();
+        let metadata = adapter.get_metadata().unwrap();
+        assert_eq!(metadata.title, "New & Improved");
+        assert_eq!(metadata.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(adapter.chapter_count(), 1);
+    }
+
+    #[test]
+    fn test_rewrite_opf_drops_refinements_of_replaced_elements() {
+        let opf = r##"<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:creator id="c1">Old</dc:creator>
+    <meta refines="#c1" property="role">aut</meta>
+    <meta property="dcterms:modified">2020</meta>
+  </metadata></package>"##;
+        let update = EpubMetadataUpdate {
+            authors: Some(vec!["A".to_string(), "B".to_string()]),
+            ..EpubMetadataUpdate::default()
+        };
+        let rewritten = rewrite_opf_metadata(opf, &update).unwrap();
+        assert!(rewritten.contains("<dc:creator>A</dc:creator>"));
+        assert!(rewritten.contains("<dc:creator>B</dc:creator>"));
+        assert!(!rewritten.contains("Old"));
+        assert!(!rewritten.contains("refines"));
+        assert!(rewritten.contains("dcterms:modified"));
+    }
 }
@@ -14,6 +14,18 @@ pub struct Chapter {
     pub id: String,
     pub title: String,
     pub content: String,
+    /// Top-level section this chapter is grouped under in the TOC
+    pub section: Option<String>,
+    /// `content` is an XHTML body fragment rather than plain text
+    pub is_html: bool,
+}
+
+/// Image bundled into the EPUB, referenced from chapters by `href`
+#[derive(Debug, Clone)]
+pub struct EpubImage {
+    pub href: String,
+    pub media_type: String,
+    pub data: Vec<u8>,
 }
 
 /// EPUB metadata builder
@@ -48,6 +60,8 @@ pub struct EpubBuilder {
     chapters: Vec<Chapter>,
     stylesheet: Option<String>,
     cover_image: Option<Vec<u8>>,
+    images: Vec<EpubImage>,
+    current_section: Option<String>,
 }
 
 impl EpubBuilder {
@@ -58,6 +72,8 @@ impl EpubBuilder {
             chapters: Vec::new(),
             stylesheet: Some(Self::default_stylesheet()),
             cover_image: None,
+            images: Vec::new(),
+            current_section: None,
         }
     }
 
@@ -70,7 +86,40 @@ impl EpubBuilder {
     /// Add a chapter
     pub fn add_chapter(&mut self, title: String, content: String) {
         let id = format!("ch{:04}", self.chapters.len() + 1);
-        self.chapters.push(Chapter { id, title, content });
+        self.chapters.push(Chapter {
+            id,
+            title,
+            content,
+            section: self.current_section.clone(),
+            is_html: false,
+        });
+    }
+
+    /// Add a chapter whose content is already XHTML (the inside of `<body>`).
+    /// It is written as-is, without a generated title heading.
+    pub fn add_html_chapter(&mut self, title: String, html: String) {
+        let id = format!("ch{:04}", self.chapters.len() + 1);
+        self.chapters.push(Chapter {
+            id,
+            title,
+            content: html,
+            section: self.current_section.clone(),
+            is_html: true,
+        });
+    }
+
+    /// Bundle an image at `href` (relative to the package, e.g. `images/a.png`)
+    pub fn add_image(&mut self, href: String, media_type: String, data: Vec<u8>) {
+        self.images.push(EpubImage {
+            href,
+            media_type,
+            data,
+        });
+    }
+
+    /// Start a top-level section; chapters added afterwards nest under it in the TOC
+    pub fn begin_section(&mut self, title: String) {
+        self.current_section = Some(title);
     }
 
     /// Set custom stylesheet
@@ -80,6 +129,15 @@ impl EpubBuilder {
         self
     }
 
+    /// Append CSS after the current stylesheet so its rules take precedence
+    pub fn append_stylesheet(&mut self, css: &str) {
+        let stylesheet = self.stylesheet.get_or_insert_with(String::new);
+        if !stylesheet.is_empty() {
+            stylesheet.push_str("\n\n");
+        }
+        stylesheet.push_str(css);
+    }
+
     /// Set cover image
     #[allow(dead_code)]
     pub fn cover_image(mut self, image_data: Vec<u8>) -> Self {
@@ -94,6 +152,26 @@ impl EpubBuilder {
         Ok(())
     }
 
+    /// Generate EPUB file synchronously, for callers already off the async runtime
+    pub fn write_to(&self, output_path: &Path) -> FormatResult<()> {
+        let zip_data = self.build_zip()?;
+        std::fs::write(output_path, zip_data)?;
+        Ok(())
+    }
+
+    /// Group consecutive chapters by section, preserving order
+    fn toc_groups(&self) -> Vec<(Option<&str>, Vec<(usize, &Chapter)>)> {
+        let mut groups: Vec<(Option<&str>, Vec<(usize, &Chapter)>)> = Vec::new();
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            let section = chapter.section.as_deref();
+            match groups.last_mut() {
+                Some((last, members)) if *last == section => members.push((i, chapter)),
+                _ => groups.push((section, vec![(i, chapter)])),
+            }
+        }
+        groups
+    }
+
     /// Build EPUB as ZIP bytes
     fn build_zip(&self) -> FormatResult<Vec<u8>> {
         let mut buffer = Cursor::new(Vec::new());
@@ -150,7 +228,15 @@ impl EpubBuilder {
                 .map_err(|e| FormatError::ConversionError(e.to_string()))?;
         }
 
-        // 8. Chapter files
+        // 8. Bundled images
+        for image in &self.images {
+            zip.start_file(format!("OEBPS/{}", image.href), options)
+                .map_err(|e| FormatError::ConversionError(e.to_string()))?;
+            zip.write_all(&image.data)
+                .map_err(|e| FormatError::ConversionError(e.to_string()))?;
+        }
+
+        // 9. Chapter files
         for chapter in &self.chapters {
             let filename = format!("OEBPS/{}.xhtml", chapter.id);
             zip.start_file(&filename, options)
@@ -239,6 +325,21 @@ impl EpubBuilder {
             ""
         };
 
+        let image_items = self
+            .images
+            .iter()
+            .enumerate()
+            .map(|(i, image)| {
+                format!(
+                    r#"    <item id="image{:04}" href="{}" media-type="{}"/>"#,
+                    i + 1,
+                    Self::escape_xml(&image.href),
+                    Self::escape_xml(&image.media_type)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
         format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="uid">
@@ -255,6 +356,7 @@ impl EpubBuilder {
     <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
     <item id="stylesheet" href="stylesheet.css" media-type="text/css"/>
 {}
+{}
 {}
   </manifest>
   <spine toc="ncx">
@@ -268,6 +370,7 @@ impl EpubBuilder {
             chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
             optional_meta,
             cover_item,
+            image_items,
             manifest_items,
             spine_items
         )
@@ -275,31 +378,64 @@ impl EpubBuilder {
 
     /// Generate toc.ncx (EPUB 2 navigation)
     fn toc_ncx(&self) -> String {
+        let nav_point = |i: usize, ch: &Chapter, indent: &str| {
+            format!(
+                r#"{indent}<navPoint id="{}" playOrder="{}">
+{indent}  <navLabel><text>{}</text></navLabel>
+{indent}  <content src="{}.xhtml"/>
+{indent}</navPoint>"#,
+                ch.id,
+                i + 1,
+                Self::escape_xml(&ch.title),
+                ch.id,
+                indent = indent
+            )
+        };
+
+        let has_sections = self.chapters.iter().any(|ch| ch.section.is_some());
+        let mut play_order = self.chapters.len();
         let nav_points = self
-            .chapters
-            .iter()
-            .enumerate()
-            .map(|(i, ch)| {
-                format!(
-                    r#"    <navPoint id="{}" playOrder="{}">
+            .toc_groups()
+            .into_iter()
+            .map(|(section, members)| match section {
+                Some(section) => {
+                    // Section entries point at their first chapter and take
+                    // play orders after the chapters so they stay unique
+                    play_order += 1;
+                    let children = members
+                        .iter()
+                        .map(|(i, ch)| nav_point(*i, ch, "      "))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    format!(
+                        r#"    <navPoint id="section{}" playOrder="{}">
       <navLabel><text>{}</text></navLabel>
       <content src="{}.xhtml"/>
+{}
     </navPoint>"#,
-                    ch.id,
-                    i + 1,
-                    Self::escape_xml(&ch.title),
-                    ch.id
-                )
+                        members[0].0 + 1,
+                        play_order,
+                        Self::escape_xml(section),
+                        members[0].1.id,
+                        children
+                    )
+                }
+                None => members
+                    .iter()
+                    .map(|(i, ch)| nav_point(*i, ch, "    "))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
             })
             .collect::<Vec<_>>()
             .join("\n");
+        let depth = if has_sections { 2 } else { 1 };
 
         format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
   <head>
     <meta name="dtb:uid" content="urn:uuid:{}"/>
-    <meta name="dtb:depth" content="1"/>
+    <meta name="dtb:depth" content="{}"/>
     <meta name="dtb:totalPageCount" content="0"/>
     <meta name="dtb:maxPageNumber" content="0"/>
   </head>
@@ -309,6 +445,7 @@ impl EpubBuilder {
   </navMap>
 </ncx>"#,
             Uuid::new_v4(),
+            depth,
             Self::escape_xml(&self.metadata.title),
             nav_points
         )
@@ -316,15 +453,41 @@ impl EpubBuilder {
 
     /// Generate nav.xhtml (EPUB 3 navigation)
     fn nav_xhtml(&self) -> String {
+        let nav_item = |ch: &Chapter, indent: &str| {
+            format!(
+                r#"{}<li><a href="{}.xhtml">{}</a></li>"#,
+                indent,
+                ch.id,
+                Self::escape_xml(&ch.title)
+            )
+        };
+
         let nav_items = self
-            .chapters
-            .iter()
-            .map(|ch| {
-                format!(
-                    r#"        <li><a href="{}.xhtml">{}</a></li>"#,
-                    ch.id,
-                    Self::escape_xml(&ch.title)
-                )
+            .toc_groups()
+            .into_iter()
+            .map(|(section, members)| match section {
+                Some(section) => {
+                    let children = members
+                        .iter()
+                        .map(|(_, ch)| nav_item(ch, "            "))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    format!(
+                        r#"        <li><a href="{}.xhtml">{}</a>
+          <ol>
+{}
+          </ol>
+        </li>"#,
+                        members[0].1.id,
+                        Self::escape_xml(section),
+                        children
+                    )
+                }
+                None => members
+                    .iter()
+                    .map(|(_, ch)| nav_item(ch, "        "))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
             })
             .collect::<Vec<_>>()
             .join("\n");
@@ -351,6 +514,24 @@ impl EpubBuilder {
 
     /// Generate chapter XHTML
     fn chapter_xhtml(&self, chapter: &Chapter) -> String {
+        if chapter.is_html {
+            return format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml">
+  <head>
+    <title>{}</title>
+    <link rel="stylesheet" type="text/css" href="stylesheet.css"/>
+  </head>
+  <body>
+{}
+  </body>
+</html>"#,
+                Self::escape_xml(&chapter.title),
+                chapter.content
+            );
+        }
+
         format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE html>
@@ -439,36 +620,38 @@ impl Default for EpubBuilder {
     }
 }
 
+/// Heading score `split_text_into_chapters` requires to start a chapter
+pub const DEFAULT_HEADING_THRESHOLD: u8 = 3;
+
 /// Helper function to split text into chapters
 pub fn split_text_into_chapters(text: &str) -> Vec<(String, String)> {
+    split_text_into_chapters_with(text, DEFAULT_HEADING_THRESHOLD)
+}
+
+/// Split text into chapters at lines whose `heading_score` reaches
+/// `threshold`. Lower thresholds split more eagerly; text before the first
+/// heading becomes "Chapter 1", and text with no headings is one chapter.
+pub fn split_text_into_chapters_with(text: &str, threshold: u8) -> Vec<(String, String)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let is_blank = |i: Option<usize>| {
+        i.and_then(|i| lines.get(i))
+            .map_or(true, |l| l.trim().is_empty())
+    };
+
     let mut chapters = Vec::new();
     let mut current_title = "Chapter 1".to_string();
     let mut current_content = String::new();
-    let mut chapter_num = 1;
-
-    for line in text.lines() {
-        let trimmed = line.trim();
-
-        // Detect chapter markers
-        if trimmed.to_lowercase().starts_with("chapter ")
-            || trimmed.to_lowercase().starts_with("part ")
-            || (trimmed.len() < 50
-                && trimmed
-                    .chars()
-                    .all(|c| c.is_uppercase() || c.is_whitespace() || c.is_numeric()))
-        {
+
+    for (i, line) in lines.iter().enumerate() {
+        let blank_before = is_blank(i.checked_sub(1));
+        let blank_after = is_blank(Some(i + 1));
+
+        if heading_score(line, blank_before, blank_after) >= threshold.max(1) {
             // Save previous chapter
             if !current_content.trim().is_empty() {
-                chapters.push((current_title.clone(), current_content.trim().to_string()));
+                chapters.push((current_title, current_content.trim().to_string()));
             }
-
-            // Start new chapter
-            chapter_num += 1;
-            current_title = if trimmed.is_empty() {
-                format!("Chapter {}", chapter_num)
-            } else {
-                trimmed.to_string()
-            };
+            current_title = line.split_whitespace().collect::<Vec<_>>().join(" ");
             current_content.clear();
         } else {
             current_content.push_str(line);
@@ -489,6 +672,150 @@ pub fn split_text_into_chapters(text: &str) -> Vec<(String, String)> {
     chapters
 }
 
+/// How heading-like a line is, 0–5. The form of the line scores up to 3
+/// ("Chapter One" 3, "12." or "IV" 2, an all-caps title 1 or 2 if indented
+/// like a centred title); a blank line before and after add 1 each.
+pub(crate) fn heading_score(line: &str, blank_before: bool, blank_after: bool) -> u8 {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.chars().count() > 60 || trimmed.ends_with([',', ';', '?', '!'])
+    {
+        return 0;
+    }
+
+    let form = keyword_heading_score(trimmed)
+        .or_else(|| bare_number_score(trimmed))
+        .unwrap_or_else(|| caps_title_score(line));
+    if form == 0 {
+        return 0;
+    }
+    form + u8::from(blank_before) + u8::from(blank_after)
+}
+
+/// "Chapter 3", "CHAPTER ONE", "Part IV: The Return", "Book Twenty-One"
+fn keyword_heading_score(trimmed: &str) -> Option<u8> {
+    const KEYWORDS: [&str; 4] = ["chapter", "part", "book", "section"];
+
+    let lower = trimmed.to_lowercase();
+    if ["prologue", "epilogue", "preface", "introduction"]
+        .contains(&lower.trim_end_matches(['.', ':']))
+    {
+        return Some(3);
+    }
+    let (keyword, rest) = lower.split_once(char::is_whitespace)?;
+    if !KEYWORDS.contains(&keyword) {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let numeral_len = numeral_prefix_len(rest)?;
+    let after = rest[numeral_len..].trim_start();
+    Some(match after.chars().next() {
+        // "Chapter 3" / "Chapter 3: Title"
+        None | Some('.' | ':' | '-' | '—' | '–') => 3,
+        // "Chapter 3 The Storm" reads like prose unless set apart
+        Some(_) => 1,
+    })
+}
+
+/// "7", "7.", "IV", "XII." alone on a line
+fn bare_number_score(trimmed: &str) -> Option<u8> {
+    let number = trimmed.trim_end_matches(['.', ':']);
+    let is_digits =
+        !number.is_empty() && number.len() <= 4 && number.chars().all(|c| c.is_ascii_digit());
+    let is_roman = number.chars().all(|c| c.is_ascii_uppercase()) && is_roman_numeral(number);
+    (is_digits || is_roman).then_some(2)
+}
+
+/// Short all-caps lines, scored higher when indented like a centred title
+fn caps_title_score(line: &str) -> u8 {
+    let trimmed = line.trim();
+    let has_letters = trimmed.chars().any(char::is_alphabetic);
+    let all_caps = trimmed
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .all(char::is_uppercase);
+    if !has_letters || !all_caps || trimmed.split_whitespace().count() > 8 {
+        return 0;
+    }
+    let indent = line.len() - line.trim_start().len();
+    if indent >= 4 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Byte length of the leading numeral in `rest` (lowercase): digits, a Roman
+/// numeral, or spelled-out number words such as "twenty-one"
+fn numeral_prefix_len(rest: &str) -> Option<usize> {
+    const NUMBER_WORDS: [&str; 28] = [
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+        "twenty",
+        "thirty",
+        "forty",
+        "fifty",
+        "sixty",
+        "seventy",
+        "eighty",
+        "ninety",
+        "hundred",
+    ];
+
+    let token_end = rest
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or(rest.len());
+    let token = &rest[..token_end];
+    if token.is_empty() {
+        return None;
+    }
+    if token.chars().all(|c| c.is_ascii_digit()) || is_roman_numeral(&token.to_ascii_uppercase()) {
+        return Some(token_end);
+    }
+
+    // Spelled out, possibly compound: "twenty-one", "one hundred"
+    let mut end = 0;
+    let mut pos = 0;
+    loop {
+        let word_end = rest[pos..]
+            .find(|c: char| !c.is_alphabetic())
+            .map_or(rest.len(), |i| pos + i);
+        if !NUMBER_WORDS.contains(&&rest[pos..word_end]) {
+            break;
+        }
+        end = word_end;
+        match rest[end..].chars().next() {
+            Some(' ' | '-') => pos = end + 1,
+            _ => break,
+        }
+    }
+    (end > 0).then_some(end)
+}
+
+fn is_roman_numeral(s: &str) -> bool {
+    static ROMAN_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"^M{0,3}(CM|CD|D?C{0,3})(XC|XL|L?X{0,3})(IX|IV|V?I{0,3})$").unwrap()
+    });
+    !s.is_empty() && s.len() <= 12 && ROMAN_RE.is_match(s)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -514,6 +841,54 @@ mod tests {
         assert_eq!(builder.chapters.len(), 2);
     }
 
+    #[test]
+    fn test_sections_nest_in_toc() {
+        let mut builder = EpubBuilder::new();
+        builder.add_chapter("Preface".to_string(), "Intro.".to_string());
+        builder.begin_section("Book One".to_string());
+        builder.add_chapter("Chapter 1".to_string(), "One.".to_string());
+        builder.add_chapter("Chapter 2".to_string(), "Two.".to_string());
+
+        let groups = builder.toc_groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, None);
+        assert_eq!(groups[1].0, Some("Book One"));
+        assert_eq!(groups[1].1.len(), 2);
+
+        let nav = builder.nav_xhtml();
+        assert!(nav.contains(r#"<li><a href="ch0002.xhtml">Book One</a>"#));
+        assert!(builder
+            .toc_ncx()
+            .contains(r#"<meta name="dtb:depth" content="2"/>"#));
+    }
+
+    #[test]
+    fn test_html_chapters_and_images() {
+        let mut builder = EpubBuilder::new();
+        builder.add_html_chapter(
+            "One".to_string(),
+            r#"<h1>One</h1><p><img src="images/image001.png"/></p>"#.to_string(),
+        );
+        builder.add_image(
+            "images/image001.png".to_string(),
+            "image/png".to_string(),
+            vec![0x89, b'P', b'N', b'G'],
+        );
+        builder.append_stylesheet(".lead { font-weight: bold; }");
+
+        let xhtml = builder.chapter_xhtml(&builder.chapters[0]);
+        assert!(xhtml.contains(r#"<h1>One</h1><p><img src="images/image001.png"/></p>"#));
+        assert_eq!(xhtml.matches("<h1>").count(), 1);
+        assert!(builder.content_opf().contains(
+            r#"<item id="image0001" href="images/image001.png" media-type="image/png"/>"#
+        ));
+        assert!(builder
+            .stylesheet
+            .as_deref()
+            .unwrap()
+            .ends_with("}\n\n.lead { font-weight: bold; }"));
+    }
+
     #[test]
     fn test_xml_escape() {
         let text = r#"Test & <html> "quotes" 'apostrophe'"#;
@@ -537,4 +912,56 @@ This is the second chapter."#;
         assert_eq!(chapters[0].0, "Chapter 1");
         assert_eq!(chapters[1].0, "Chapter 2");
     }
+
+    #[test]
+    fn test_chapter_splitting_roman_parts() {
+        let text = "Part I\n\nThe first part begins here.\nIt goes on.\n\n\
+                    Part II\n\nThe second part.\n\n\
+                    IV\n\nA bare numeral heading.\n\n\
+                    I went to the shop.";
+
+        let chapters = split_text_into_chapters(text);
+        let titles: Vec<&str> = chapters.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(titles, ["Part I", "Part II", "IV"]);
+        assert_eq!(chapters[0].1, "The first part begins here.\nIt goes on.");
+        assert!(chapters[2].1.ends_with("I went to the shop."));
+    }
+
+    #[test]
+    fn test_chapter_splitting_spelled_out() {
+        let text = "Title page text.\n\n\
+                    CHAPTER ONE\n\nIt was a dark night.\n\
+                    Chapter two was mentioned in passing, though.\n\n\
+                    Chapter Twenty-One: The End\n\nFin.";
+
+        let chapters = split_text_into_chapters(text);
+        let titles: Vec<&str> = chapters.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Chapter 1", "CHAPTER ONE", "Chapter Twenty-One: The End"]
+        );
+        assert_eq!(chapters[0].1, "Title page text.");
+        assert!(chapters[1].1.contains("mentioned in passing"));
+        assert_eq!(chapters[2].1, "Fin.");
+    }
+
+    #[test]
+    fn test_chapter_splitting_numeric_headings() {
+        let text = "1.\n\nOpening lines.\n\n2.\n\nMore lines.\nThe year was\n1999\nand so on.";
+
+        let chapters = split_text_into_chapters(text);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(
+            chapters[0],
+            ("1.".to_string(), "Opening lines.".to_string())
+        );
+        assert!(chapters[1].1.contains("1999"));
+
+        // A lenient threshold accepts the inline number too
+        assert_eq!(split_text_into_chapters_with(text, 2).len(), 3);
+
+        // No headings at all: one chapter
+        let plain = split_text_into_chapters("just some prose\nwith two lines");
+        assert_eq!(plain.len(), 1);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::db::Database;
 use crate::error::Result;
-use rusqlite::params;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Write;
@@ -143,7 +145,7 @@ fn get_authors_string(conn: &rusqlite::Connection, book_id: i64) -> Result<Strin
         "SELECT a.name FROM authors a 
          JOIN books_authors ba ON a.id = ba.author_id 
          WHERE ba.book_id = ?1
-         ORDER BY ba.id",
+         ORDER BY ba.author_order",
     )?;
 
     let authors: Vec<String> = stmt
@@ -185,7 +187,7 @@ fn get_collections_string(conn: &rusqlite::Connection, book_id: i64) -> Result<S
 
 fn get_reading_progress(conn: &rusqlite::Connection, book_id: i64) -> Result<Option<f64>> {
     match conn.query_row(
-        "SELECT progress FROM reading_progress WHERE book_id = ?1 ORDER BY last_read DESC LIMIT 1",
+        "SELECT progress_percent FROM reading_progress WHERE book_id = ?1 ORDER BY last_read DESC LIMIT 1",
         params![book_id],
         |row| row.get(0),
     ) {
@@ -305,3 +307,1533 @@ fn export_as_markdown(books: &[ExportedBook], file_path: &str) -> Result<()> {
     file.write_all(content.as_bytes())?;
     Ok(())
 }
+
+const CATALOG_COLUMNS: [&str; 12] = [
+    "title",
+    "authors",
+    "series",
+    "series_index",
+    "isbn",
+    "publisher",
+    "pubdate",
+    "rating",
+    "file_format",
+    "file_size",
+    "tags",
+    "added_date",
+];
+
+/// Write one record per library book to `file_path` as CSV or JSON, returning
+/// the number of books written. Books are loaded in pages so the whole
+/// catalog never sits in memory at once.
+pub fn export_catalog(db: &Database, format: ExportFormat, file_path: &str) -> Result<usize> {
+    use crate::services::library_service;
+
+    let ids: Vec<i64> = {
+        let conn = db.get_connection()?;
+        let mut stmt =
+            conn.prepare("SELECT id FROM books WHERE in_trash = 0 ORDER BY title, id")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        ids
+    };
+
+    match format {
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_path(file_path)?;
+            wtr.write_record(CATALOG_COLUMNS)?;
+            for chunk in ids.chunks(500) {
+                for book in library_service::get_books_by_ids(db, chunk)? {
+                    let authors = book
+                        .authors
+                        .iter()
+                        .map(|a| a.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let tags = book
+                        .tags
+                        .iter()
+                        .map(|t| t.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    wtr.write_record([
+                        book.title.as_str(),
+                        authors.as_str(),
+                        book.series.as_deref().unwrap_or(""),
+                        book.series_index
+                            .map(|v| v.to_string())
+                            .unwrap_or_default()
+                            .as_str(),
+                        book.isbn.as_deref().unwrap_or(""),
+                        book.publisher.as_deref().unwrap_or(""),
+                        book.pubdate.as_deref().unwrap_or(""),
+                        book.rating
+                            .map(|v| v.to_string())
+                            .unwrap_or_default()
+                            .as_str(),
+                        book.file_format.as_str(),
+                        book.file_size
+                            .map(|v| v.to_string())
+                            .unwrap_or_default()
+                            .as_str(),
+                        tags.as_str(),
+                        book.added_date.as_str(),
+                    ])?;
+                }
+            }
+            wtr.flush()?;
+        }
+        ExportFormat::Json => {
+            let mut out = std::io::BufWriter::new(File::create(file_path)?);
+            out.write_all(b"[")?;
+            let mut first = true;
+            for chunk in ids.chunks(500) {
+                for book in library_service::get_books_by_ids(db, chunk)? {
+                    if !first {
+                        out.write_all(b",")?;
+                    }
+                    first = false;
+                    out.write_all(b"\n  ")?;
+                    serde_json::to_writer(&mut out, &book)?;
+                }
+            }
+            out.write_all(b"\n]\n")?;
+            out.flush()?;
+        }
+        ExportFormat::Markdown => {
+            return Err(crate::error::ShioriError::InvalidOperation(
+                "Catalog export supports csv or json".to_string(),
+            ))
+        }
+    }
+
+    Ok(ids.len())
+}
+
+/// Portable reading state, keyed by book UUID so it can move between libraries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingStateExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub progress: Vec<ExportedProgress>,
+    pub annotations: Vec<ExportedAnnotation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedProgress {
+    pub book_uuid: String,
+    pub current_location: String,
+    pub progress_percent: f64,
+    pub current_page: Option<i32>,
+    pub total_pages: Option<i32>,
+    pub cfi_location: Option<String>,
+    pub last_read: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedAnnotation {
+    pub book_uuid: String,
+    pub annotation_type: String,
+    pub location: String,
+    pub cfi_range: Option<String>,
+    pub selected_text: Option<String>,
+    pub note_content: Option<String>,
+    pub color: Option<String>,
+    pub chapter_title: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingStateImportReport {
+    pub progress_updated: usize,
+    pub annotations_added: usize,
+    pub annotations_updated: usize,
+    /// UUIDs referenced by the export that do not exist in this library
+    pub skipped_books: Vec<String>,
+}
+
+const READING_STATE_VERSION: u32 = 1;
+
+pub fn export_reading_state(db: &Database) -> Result<String> {
+    let conn = db.get_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.uuid, rp.current_location, rp.progress_percent, rp.current_page,
+                rp.total_pages, rp.cfi_location, rp.last_read
+         FROM reading_progress rp
+         JOIN books b ON b.id = rp.book_id
+         ORDER BY b.uuid",
+    )?;
+    let progress = stmt
+        .query_map([], |row| {
+            Ok(ExportedProgress {
+                book_uuid: row.get(0)?,
+                current_location: row.get(1)?,
+                progress_percent: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+                current_page: row.get(3)?,
+                total_pages: row.get(4)?,
+                cfi_location: row.get(5)?,
+                last_read: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.uuid, a.type, a.location, a.cfi_range, a.selected_text, a.note_content,
+                a.color, a.chapter_title, a.created_at, a.updated_at
+         FROM annotations a
+         JOIN books b ON b.id = a.book_id
+         ORDER BY b.uuid, a.created_at",
+    )?;
+    let annotations = stmt
+        .query_map([], |row| {
+            Ok(ExportedAnnotation {
+                book_uuid: row.get(0)?,
+                annotation_type: row.get(1)?,
+                location: row.get(2)?,
+                cfi_range: row.get(3)?,
+                selected_text: row.get(4)?,
+                note_content: row.get(5)?,
+                color: row.get(6)?,
+                chapter_title: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let export = ReadingStateExport {
+        version: READING_STATE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        progress,
+        annotations,
+    };
+
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+/// Merge an exported reading state into this library. Books are matched by
+/// UUID; on conflict the newer `last_read` / `updated_at` wins. Annotations
+/// have no portable id, so they are matched on type, location and CFI range.
+pub fn import_reading_state(db: &Database, json: &str) -> Result<ReadingStateImportReport> {
+    let export: ReadingStateExport = serde_json::from_str(json).map_err(|e| {
+        crate::error::ShioriError::Validation(format!("Invalid reading state: {}", e))
+    })?;
+    if export.version > READING_STATE_VERSION {
+        return Err(crate::error::ShioriError::Validation(format!(
+            "Unsupported reading state version {}",
+            export.version
+        )));
+    }
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    let mut report = ReadingStateImportReport::default();
+    let mut book_ids: std::collections::HashMap<String, Option<i64>> =
+        std::collections::HashMap::new();
+
+    let mut resolve = |tx: &rusqlite::Transaction, uuid: &str| -> Result<Option<i64>> {
+        if let Some(id) = book_ids.get(uuid) {
+            return Ok(*id);
+        }
+        let id = tx
+            .query_row(
+                "SELECT id FROM books WHERE uuid = ?1",
+                params![uuid],
+                |row| row.get(0),
+            )
+            .optional()?;
+        book_ids.insert(uuid.to_string(), id);
+        Ok(id)
+    };
+
+    for prog in &export.progress {
+        let Some(book_id) = resolve(&tx, &prog.book_uuid)? else {
+            continue;
+        };
+        let current: Option<String> = tx
+            .query_row(
+                "SELECT last_read FROM reading_progress WHERE book_id = ?1",
+                params![book_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if current.is_some_and(|current| !is_newer(&prog.last_read, &current)) {
+            continue;
+        }
+        let changed = tx.execute(
+            "INSERT INTO reading_progress
+                 (book_id, current_location, progress_percent, current_page, total_pages, cfi_location, last_read)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(book_id) DO UPDATE SET
+                 current_location = excluded.current_location,
+                 progress_percent = excluded.progress_percent,
+                 current_page = excluded.current_page,
+                 total_pages = excluded.total_pages,
+                 cfi_location = excluded.cfi_location,
+                 last_read = excluded.last_read",
+            params![
+                book_id,
+                prog.current_location,
+                prog.progress_percent,
+                prog.current_page,
+                prog.total_pages,
+                prog.cfi_location,
+                prog.last_read
+            ],
+        )?;
+        report.progress_updated += changed;
+    }
+
+    for ann in &export.annotations {
+        let Some(book_id) = resolve(&tx, &ann.book_uuid)? else {
+            continue;
+        };
+        let existing: Option<(i64, String)> = tx
+            .query_row(
+                "SELECT id, updated_at FROM annotations
+                 WHERE book_id = ?1 AND type = ?2 AND location = ?3
+                   AND COALESCE(cfi_range, '') = COALESCE(?4, '')",
+                params![book_id, ann.annotation_type, ann.location, ann.cfi_range],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        match existing {
+            Some((id, updated_at)) => {
+                if is_newer(&ann.updated_at, &updated_at) {
+                    tx.execute(
+                        "UPDATE annotations
+                         SET selected_text = ?1, note_content = ?2, color = ?3,
+                             chapter_title = ?4, updated_at = ?5
+                         WHERE id = ?6",
+                        params![
+                            ann.selected_text,
+                            ann.note_content,
+                            ann.color,
+                            ann.chapter_title,
+                            ann.updated_at,
+                            id
+                        ],
+                    )?;
+                    report.annotations_updated += 1;
+                }
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO annotations
+                         (book_id, type, location, cfi_range, selected_text, note_content,
+                          color, chapter_title, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, COALESCE(?7, '#FFEB3B'), ?8, ?9, ?10)",
+                    params![
+                        book_id,
+                        ann.annotation_type,
+                        ann.location,
+                        ann.cfi_range,
+                        ann.selected_text,
+                        ann.note_content,
+                        ann.color,
+                        ann.chapter_title,
+                        ann.created_at,
+                        ann.updated_at
+                    ],
+                )?;
+                report.annotations_added += 1;
+            }
+        }
+    }
+
+    tx.commit()?;
+
+    let mut skipped: Vec<String> = book_ids
+        .into_iter()
+        .filter(|(_, id)| id.is_none())
+        .map(|(uuid, _)| uuid)
+        .collect();
+    skipped.sort();
+    report.skipped_books = skipped;
+
+    Ok(report)
+}
+
+/// Whether timestamp `incoming` is later than `current`. Library timestamps
+/// are either RFC 3339 or SQLite's `YYYY-MM-DD HH:MM:SS` (UTC), which do not
+/// compare correctly as strings; unparseable values fall back to that.
+fn is_newer(incoming: &str, current: &str) -> bool {
+    fn parse(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+            return Some(dt.with_timezone(&chrono::Utc));
+        }
+        chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|dt| dt.and_utc())
+    }
+
+    match (parse(incoming), parse(current)) {
+        (Some(a), Some(b)) => a > b,
+        _ => incoming > current,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationExportFormat {
+    Markdown,
+    Csv,
+}
+
+struct AnnotationRow {
+    id: i64,
+    annotation_type: String,
+    location: String,
+    cfi_range: Option<String>,
+    selected_text: Option<String>,
+    note_content: Option<String>,
+    color: Option<String>,
+    chapter_title: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Render every annotation of a book as a standalone Markdown or CSV document.
+/// A book without annotations still yields a valid document.
+pub fn export_annotations(
+    db: &Database,
+    book_id: i64,
+    format: AnnotationExportFormat,
+) -> Result<String> {
+    let conn = db.get_connection()?;
+
+    let title: String = conn
+        .query_row(
+            "SELECT title FROM books WHERE id = ?1",
+            params![book_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| crate::error::ShioriError::BookNotFound(book_id.to_string()))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, type, location, cfi_range, selected_text, note_content, color,
+                chapter_title, created_at, updated_at
+         FROM annotations
+         WHERE book_id = ?1
+         ORDER BY created_at, id",
+    )?;
+    let mut annotations = stmt
+        .query_map(params![book_id], |row| {
+            Ok(AnnotationRow {
+                id: row.get(0)?,
+                annotation_type: row.get(1)?,
+                location: row.get(2)?,
+                cfi_range: row.get(3)?,
+                selected_text: row.get(4)?,
+                note_content: row.get(5)?,
+                color: row.get(6)?,
+                chapter_title: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    // Stable, so equal positions keep their creation order
+    annotations.sort_by(|a, b| {
+        cmp_location(&a.location, &b.location).then_with(|| {
+            cmp_location(
+                a.cfi_range.as_deref().unwrap_or(""),
+                b.cfi_range.as_deref().unwrap_or(""),
+            )
+        })
+    });
+
+    match format {
+        AnnotationExportFormat::Markdown => Ok(annotations_to_markdown(&title, &annotations)),
+        AnnotationExportFormat::Csv => annotations_to_csv(book_id, &annotations),
+    }
+}
+
+/// Compare reading positions with digit runs taken by value, so CFI step
+/// "/10" sorts after "/2" and "chapter-10" after "chapter-9"
+fn cmp_location(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (x, y) = match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => (x, y),
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            // Compare without leading zeros: longer is larger, then digit by digit
+            let a_num = a[..a_end].trim_start_matches('0');
+            let b_num = b[..b_end].trim_start_matches('0');
+            let ord = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            a = &a[a_end..];
+            b = &b[b_end..];
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            a = &a[x.len_utf8()..];
+            b = &b[y.len_utf8()..];
+        }
+    }
+}
+
+fn annotations_to_markdown(title: &str, annotations: &[AnnotationRow]) -> String {
+    let mut content = format!("# Annotations: {}\n\n", title);
+    if annotations.is_empty() {
+        content.push_str("_No annotations._\n");
+        return content;
+    }
+
+    let mut current_location: Option<&str> = None;
+    for ann in annotations {
+        if current_location != Some(ann.location.as_str()) {
+            current_location = Some(ann.location.as_str());
+            let heading = ann.chapter_title.as_deref().unwrap_or(&ann.location);
+            content.push_str(&format!("## {}\n\n", heading));
+        }
+
+        let text = ann.selected_text.as_deref().unwrap_or("").trim();
+        let note = ann
+            .note_content
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty());
+        match ann.annotation_type.as_str() {
+            "highlight" => {
+                for line in text.lines() {
+                    content.push_str(&format!("> {}\n", line));
+                }
+                if let Some(color) = &ann.color {
+                    content.push_str(&format!(">\n> _Color: {}_\n", color));
+                }
+                content.push('\n');
+                if let Some(note) = note {
+                    content.push_str(&format!("- {}\n\n", note));
+                }
+            }
+            "note" => {
+                let label = if text.is_empty() { "Note" } else { text };
+                content.push_str(&format!("- {}\n", label));
+                if let Some(note) = note {
+                    content.push_str(&format!("  - {}\n", note));
+                }
+                content.push('\n');
+            }
+            _ => {
+                content.push_str(&format!("- Bookmark: {}\n\n", ann.location));
+            }
+        }
+    }
+
+    content
+}
+
+fn annotations_to_csv(book_id: i64, annotations: &[AnnotationRow]) -> Result<String> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record([
+        "id",
+        "book_id",
+        "type",
+        "location",
+        "cfi_range",
+        "selected_text",
+        "note_content",
+        "color",
+        "chapter_title",
+        "created_at",
+        "updated_at",
+    ])?;
+
+    for ann in annotations {
+        wtr.write_record([
+            ann.id.to_string().as_str(),
+            book_id.to_string().as_str(),
+            ann.annotation_type.as_str(),
+            ann.location.as_str(),
+            ann.cfi_range.as_deref().unwrap_or(""),
+            ann.selected_text.as_deref().unwrap_or(""),
+            ann.note_content.as_deref().unwrap_or(""),
+            ann.color.as_deref().unwrap_or(""),
+            ann.chapter_title.as_deref().unwrap_or(""),
+            ann.created_at.as_str(),
+            ann.updated_at.as_str(),
+        ])?;
+    }
+
+    let bytes = wtr
+        .into_inner()
+        .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| crate::error::ShioriError::Other(e.to_string()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnthologyExport {
+    pub file_path: String,
+    pub included: Vec<String>,
+    /// Titles of members whose format cannot be merged (pdf, cbz, ...)
+    pub skipped: Vec<String>,
+}
+
+/// Merge every EPUB/TXT book of a collection into one EPUB, one top-level
+/// section per source book. The file is written into `output_dir`.
+pub fn export_collection_as_anthology(
+    db: &Database,
+    collection_id: i64,
+    output_dir: &std::path::Path,
+) -> Result<AnthologyExport> {
+    use crate::services::collection_service::CollectionService;
+    use crate::services::conversion_engine::extract_chapters;
+    use crate::services::epub_builder::{EpubBuilder, EpubMetadata};
+
+    let (collection, books) = {
+        let conn = db.get_connection()?;
+        let collection = CollectionService::get_collection(&conn, collection_id)?;
+        let books = CollectionService::get_collection_books(&conn, collection_id, false)?;
+        (collection, books)
+    };
+
+    let mut builder = EpubBuilder::new().metadata(EpubMetadata {
+        title: collection.name.clone(),
+        description: collection.description.clone(),
+        ..Default::default()
+    });
+    let mut included = Vec::new();
+    let mut skipped = Vec::new();
+
+    for book in &books {
+        let chapters = match book.file_format.to_lowercase().as_str() {
+            "epub" | "txt" => {
+                extract_chapters(std::path::Path::new(&book.file_path), &book.file_format)
+            }
+            _ => {
+                skipped.push(book.title.clone());
+                continue;
+            }
+        };
+        match chapters {
+            Ok(chapters) if !chapters.is_empty() => {
+                builder.begin_section(book.title.clone());
+                for (title, content) in chapters {
+                    builder.add_chapter(title, content);
+                }
+                included.push(book.title.clone());
+            }
+            Ok(_) => skipped.push(book.title.clone()),
+            Err(e) => {
+                log::warn!("[Anthology] Skipping '{}': {}", book.title, e);
+                skipped.push(book.title.clone());
+            }
+        }
+    }
+
+    if included.is_empty() {
+        return Err(crate::error::ShioriError::InvalidOperation(format!(
+            "Collection '{}' has no EPUB or TXT books to merge",
+            collection.name
+        )));
+    }
+
+    std::fs::create_dir_all(output_dir)?;
+    let output_path = output_dir.join(format!(
+        "{} (Anthology).epub",
+        safe_file_name(&collection.name)
+    ));
+    builder
+        .write_to(&output_path)
+        .map_err(|e| crate::error::ShioriError::Other(e.to_string()))?;
+
+    Ok(AnthologyExport {
+        file_path: output_path.to_string_lossy().to_string(),
+        included,
+        skipped,
+    })
+}
+
+/// `name` with everything but letters, digits, spaces and dashes replaced
+fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    name.trim().to_string()
+}
+
+/// Inlined images past this size make the export slow to open in a browser
+const HTML_EXPORT_IMAGE_WARN_BYTES: usize = 20 * 1024 * 1024;
+
+const HTML_EXPORT_CSS: &str = "body { max-width: 42em; margin: 0 auto; padding: 1em 1.5em; font-family: Georgia, serif; line-height: 1.6; }
+img, svg { max-width: 100%; height: auto; }
+#toc ol { padding-left: 1.5em; }
+section.chapter { margin-top: 3em; }";
+
+static BODY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<body[^>]*>(.*)</body>").unwrap());
+static STYLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<style[^>]*>(.*?)</style>").unwrap());
+static LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap());
+static HREF_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\bhref\s*=\s*["']([^"']+)["']"#).unwrap());
+/// `<img src>` and SVG `<image href>` / `xlink:href`
+static IMAGE_REF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)(<img\b[^>]*?\bsrc\s*=\s*|<image\b[^>]*?\b(?:xlink:)?href\s*=\s*)(["'])([^"']+)["']"#)
+        .unwrap()
+});
+/// `url(...)` in CSS, quoted or not
+static CSS_URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)url\(\s*(["']?)([^"')]+?)["']?\s*\)"#).unwrap());
+/// `id` attributes inside a tag
+static ID_ATTR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?is)(<[^>]*?\sid\s*=\s*)(["'])([^"']*)["']"#).unwrap());
+/// `<a href>` link targets
+static ANCHOR_HREF_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?is)(<a\b[^>]*?\shref\s*=\s*)(["'])([^"']*)["']"#).unwrap());
+static HEADING_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<h[1-3][^>]*>(.*?)</h[1-3]>").unwrap());
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]+>").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HtmlExport {
+    pub file_path: String,
+    pub size_bytes: u64,
+    /// Set when inlined images make the file unusually large
+    pub warning: Option<String>,
+}
+
+/// One `<section>` of the exported document
+struct HtmlSection {
+    title: String,
+    body: String,
+}
+
+/// Write a book as one self-contained HTML file in `output_dir`: EPUB
+/// chapters with their images inlined as data URIs and stylesheets in a
+/// `<style>` block, or the text of a TXT/DOCX book, behind a linked table
+/// of contents.
+pub async fn export_book_as_html(
+    db: &Database,
+    book_id: i64,
+    output_dir: &std::path::Path,
+) -> Result<HtmlExport> {
+    use crate::error::ShioriError;
+
+    let (title, authors, file_path, format, language) = {
+        let conn = db.get_connection()?;
+        let (title, file_path, format, language): (String, String, String, Option<String>) = conn
+            .query_row(
+                "SELECT title, file_path, file_format, language FROM books WHERE id = ?1",
+                params![book_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()?
+            .ok_or_else(|| ShioriError::BookNotFound(book_id.to_string()))?;
+        let authors = get_authors_string(&conn, book_id)?;
+        (title, authors, file_path, format.to_lowercase(), language)
+    };
+
+    let body = match format.as_str() {
+        "epub" => epub_html_body(&file_path).await?,
+        "txt" => HtmlBody::text(
+            crate::services::conversion_engine::extract_chapters(
+                std::path::Path::new(&file_path),
+                "txt",
+            )
+            .map_err(|e| ShioriError::Other(e.to_string()))?,
+        ),
+        "docx" => {
+            let bytes = std::fs::read(&file_path)?;
+            let doc = docx_rs::read_docx(&bytes)
+                .map_err(|e| ShioriError::InvalidFormat(format!("Failed to read DOCX: {}", e)))?;
+            let text = crate::services::docx_adapter::DocxAdapter::plain_text(&doc);
+            HtmlBody::text(vec![(title.clone(), text)])
+        }
+        other => {
+            return Err(ShioriError::InvalidOperation(format!(
+                "HTML export supports EPUB, TXT and DOCX books, not {}",
+                other
+            )))
+        }
+    };
+    if body.sections.is_empty() {
+        return Err(ShioriError::InvalidOperation(format!(
+            "'{}' has no text to export",
+            title
+        )));
+    }
+
+    let html = render_html_document(
+        &title,
+        &authors,
+        language.as_deref().unwrap_or("en"),
+        &body.css,
+        &body.sections,
+    );
+    std::fs::create_dir_all(output_dir)?;
+    let output_path = output_dir.join(format!("{}.html", safe_file_name(&title)));
+    std::fs::write(&output_path, &html)?;
+
+    let warning = (body.image_bytes > HTML_EXPORT_IMAGE_WARN_BYTES).then(|| {
+        let message = format!(
+            "{} inlined images add {:.1} MB; the file may be slow to open in a browser",
+            body.image_count,
+            body.image_bytes as f64 / (1024.0 * 1024.0)
+        );
+        log::warn!("[HtmlExport] '{}': {}", title, message);
+        message
+    });
+    Ok(HtmlExport {
+        file_path: output_path.to_string_lossy().to_string(),
+        size_bytes: html.len() as u64,
+        warning,
+    })
+}
+
+/// Sections of the exported document plus what they pull in
+struct HtmlBody {
+    sections: Vec<HtmlSection>,
+    css: Vec<String>,
+    image_count: usize,
+    image_bytes: usize,
+}
+
+impl HtmlBody {
+    /// Plain-text `(title, text)` chapters
+    fn text(chapters: Vec<(String, String)>) -> Self {
+        Self {
+            sections: chapters
+                .into_iter()
+                .map(|(title, text)| HtmlSection {
+                    title,
+                    body: text_to_paragraphs(&text),
+                })
+                .collect(),
+            css: Vec::new(),
+            image_count: 0,
+            image_bytes: 0,
+        }
+    }
+}
+
+/// Read every spine item through the EPUB renderer, keeping the body markup
+/// with images and CSS `url()` resources swapped for data URIs and
+/// collecting the stylesheets it uses. Element ids are prefixed with their
+/// section's id so chapters can't collide, and links between chapters
+/// become in-page anchors.
+async fn epub_html_body(path: &str) -> Result<HtmlBody> {
+    use crate::services::epub_adapter::EpubAdapter;
+    use crate::services::renderer::BookReaderAdapter;
+
+    let mut adapter = EpubAdapter::new();
+    adapter.load(path).await?;
+
+    let mut inlined = HtmlBody {
+        sections: Vec::new(),
+        css: Vec::new(),
+        image_count: 0,
+        image_bytes: 0,
+    };
+    let mut seen_stylesheets = std::collections::HashSet::new();
+    // Data URIs by reference, so a resource shared by chapters is read once
+    let mut resources: std::collections::HashMap<String, Option<String>> =
+        std::collections::HashMap::new();
+    // (chapter file name, body) of each non-empty chapter, in section order
+    let mut chapters: Vec<(String, String)> = Vec::new();
+
+    for index in 0..adapter.chapter_count() {
+        let chapter = adapter.get_chapter(index)?;
+        let content = chapter.content;
+
+        for link in LINK_RE.find_iter(&content) {
+            let link = link.as_str();
+            if !link.to_lowercase().contains("stylesheet") {
+                continue;
+            }
+            let Some(href) = HREF_RE.captures(link).map(|c| c[1].to_string()) else {
+                continue;
+            };
+            if seen_stylesheets.insert(href.clone()) {
+                match adapter.get_resource(&href) {
+                    Ok(bytes) => {
+                        let css = inline_css_urls(&String::from_utf8_lossy(&bytes), |src| {
+                            resource_data_uri(&adapter, &mut resources, &mut inlined, src)
+                        });
+                        inlined.css.push(css);
+                    }
+                    Err(e) => log::warn!("[HtmlExport] Missing stylesheet {}: {}", href, e),
+                }
+            }
+        }
+        for style in STYLE_RE.captures_iter(&content) {
+            let style = style[1].to_string();
+            if seen_stylesheets.insert(style.clone()) {
+                let css = inline_css_urls(&style, |src| {
+                    resource_data_uri(&adapter, &mut resources, &mut inlined, src)
+                });
+                inlined.css.push(css);
+            }
+        }
+
+        let body = BODY_RE
+            .captures(&content)
+            .map(|c| c[1].to_string())
+            .unwrap_or_else(|| content.clone());
+        if body.trim().is_empty() {
+            continue;
+        }
+        let body = IMAGE_REF_RE.replace_all(&body, |caps: &regex::Captures| {
+            let src = &caps[3];
+            if src.starts_with("data:") || src.contains("://") {
+                return caps[0].to_string();
+            }
+            match resource_data_uri(&adapter, &mut resources, &mut inlined, src) {
+                Some(uri) => format!("{}{}{}{}", &caps[1], &caps[2], uri, &caps[2]),
+                None => caps[0].to_string(),
+            }
+        });
+        let file = adapter
+            .chapter_path(index)
+            .map(|path| resource_file_name(&path).to_string())
+            .unwrap_or_default();
+        chapters.push((file, body.into_owned()));
+    }
+
+    let section_of: std::collections::HashMap<String, usize> = chapters
+        .iter()
+        .enumerate()
+        .filter(|(_, (file, _))| !file.is_empty())
+        .map(|(i, (file, _))| (file.clone(), i + 1))
+        .collect();
+    for (i, (_, body)) in chapters.into_iter().enumerate() {
+        let body = scope_anchors(&body, i + 1, &section_of);
+        let title = HEADING_RE
+            .captures(&body)
+            .map(|c| TAG_RE.replace_all(&c[1], "").trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| format!("Chapter {}", i + 1));
+        inlined.sections.push(HtmlSection {
+            title: crate::conversion::utils::decode_html_entities(&title),
+            body,
+        });
+    }
+    Ok(inlined)
+}
+
+/// Data URI for the EPUB resource `src`, read once per distinct reference
+fn resource_data_uri(
+    adapter: &crate::services::epub_adapter::EpubAdapter,
+    cache: &mut std::collections::HashMap<String, Option<String>>,
+    inlined: &mut HtmlBody,
+    src: &str,
+) -> Option<String> {
+    use crate::services::renderer::BookReaderAdapter;
+    use base64::Engine;
+
+    cache
+        .entry(src.to_string())
+        .or_insert_with(|| {
+            let bytes = adapter.get_resource(src).ok()?;
+            inlined.image_count += 1;
+            inlined.image_bytes += bytes.len();
+            Some(format!(
+                "data:{};base64,{}",
+                resource_mime(src),
+                base64::engine::general_purpose::STANDARD.encode(&bytes)
+            ))
+        })
+        .clone()
+}
+
+/// Replace the local `url()` references of `css` with what `data_uri`
+/// returns for them; ones it can't resolve are left as they are
+fn inline_css_urls(css: &str, mut data_uri: impl FnMut(&str) -> Option<String>) -> String {
+    CSS_URL_RE
+        .replace_all(css, |caps: &regex::Captures| {
+            let src = caps[2].trim();
+            if src.starts_with("data:") || src.starts_with('#') || src.contains("://") {
+                return caps[0].to_string();
+            }
+            match data_uri(src) {
+                Some(uri) => format!("url(\"{}\")", uri),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Last path component of an EPUB reference, without query or fragment
+fn resource_file_name(href: &str) -> &str {
+    let path = href.split(['?', '#']).next().unwrap_or(href);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Prefix the ids of section `section` with its own id, and point links at
+/// other chapters (`ch2.xhtml#x`) or within this one (`#x`) at the matching
+/// anchors of the single page. Links to files that aren't chapters are kept.
+fn scope_anchors(
+    body: &str,
+    section: usize,
+    section_of: &std::collections::HashMap<String, usize>,
+) -> String {
+    let body = ID_ATTR_RE.replace_all(body, |caps: &regex::Captures| {
+        format!(
+            "{}{}chapter-{}-{}{}",
+            &caps[1], &caps[2], section, &caps[3], &caps[2]
+        )
+    });
+    ANCHOR_HREF_RE
+        .replace_all(&body, |caps: &regex::Captures| {
+            let href = &caps[3];
+            if href.contains("://") || href.starts_with("mailto:") || href.starts_with("data:") {
+                return caps[0].to_string();
+            }
+            let (file, fragment) = href.split_once('#').unwrap_or((href, ""));
+            let target = if file.is_empty() {
+                Some(section)
+            } else {
+                section_of.get(resource_file_name(file)).copied()
+            };
+            match (target, fragment) {
+                (Some(target), "") => {
+                    format!("{}{}#chapter-{}{}", &caps[1], &caps[2], target, &caps[2])
+                }
+                (Some(target), fragment) => format!(
+                    "{}{}#chapter-{}-{}{}",
+                    &caps[1], &caps[2], target, fragment, &caps[2]
+                ),
+                (None, _) => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+fn resource_mime(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path.split(['?', '#']).next().unwrap_or(path))
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    match extension.as_deref() {
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        _ => "image/png",
+    }
+}
+
+/// Plain text as `<p>` paragraphs, one per blank-line separated block
+fn text_to_paragraphs(text: &str) -> String {
+    use crate::conversion::oeb::escape_xml;
+
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("<p>{}</p>", escape_xml(p).replace('\n', "<br>\n")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_html_document(
+    title: &str,
+    authors: &str,
+    language: &str,
+    css: &[String],
+    sections: &[HtmlSection],
+) -> String {
+    use crate::conversion::oeb::escape_xml;
+    use std::fmt::Write as _;
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}\n",
+        escape_xml(language),
+        escape_xml(title),
+        HTML_EXPORT_CSS
+    );
+    for sheet in css {
+        // A stray closing tag in book CSS must not end the block early
+        html.push_str(&sheet.replace("</style", "<\\/style"));
+        html.push('\n');
+    }
+    let _ = write!(
+        html,
+        "</style>\n</head>\n<body>\n<header>\n<h1>{}</h1>\n",
+        escape_xml(title)
+    );
+    if !authors.is_empty() {
+        let _ = writeln!(html, "<p class=\"author\">{}</p>", escape_xml(authors));
+    }
+    html.push_str("</header>\n<nav id=\"toc\">\n<h2>Contents</h2>\n<ol>\n");
+    for (i, section) in sections.iter().enumerate() {
+        let _ = writeln!(
+            html,
+            "<li><a href=\"#chapter-{}\">{}</a></li>",
+            i + 1,
+            escape_xml(&section.title)
+        );
+    }
+    html.push_str("</ol>\n</nav>\n");
+    for (i, section) in sections.iter().enumerate() {
+        let _ = write!(
+            html,
+            "<section class=\"chapter\" id=\"chapter-{}\">\n{}\n</section>\n",
+            i + 1,
+            section.body
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn setup_db(dir: &std::path::Path, name: &str) -> Database {
+        Database::new(&dir.join(name)).unwrap()
+    }
+
+    fn insert_book(db: &Database, uuid: &str) -> i64 {
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (uuid, title, file_path, file_format) VALUES (?1, ?1, ?2, 'epub')",
+            params![uuid, format!("/books/{}.epub", uuid)],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn test_reading_state_round_trip() {
+        let dir = tempdir().unwrap();
+        let source = setup_db(dir.path(), "source.db");
+        let target = setup_db(dir.path(), "target.db");
+
+        let a = insert_book(&source, "book-a");
+        let b = insert_book(&source, "book-b");
+        {
+            let conn = source.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'chapter-5', 42.5, '2026-03-02T10:00:00Z')",
+                params![a],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'chapter-1', 5.0, '2026-03-02T10:00:00Z')",
+                params![b],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO annotations (book_id, type, location, selected_text, note_content, created_at, updated_at)
+                 VALUES (?1, 'note', 'chapter-5', 'spice', 'remember this', '2026-03-02T10:00:00Z', '2026-03-02T10:00:00Z')",
+                params![a],
+            )
+            .unwrap();
+        }
+
+        // The target only has book-a, with older progress than the export
+        let target_a = insert_book(&target, "book-a");
+        target
+            .get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'chapter-2', 10.0, '2026-03-01T10:00:00Z')",
+                params![target_a],
+            )
+            .unwrap();
+
+        let json = export_reading_state(&source).unwrap();
+        let report = import_reading_state(&target, &json).unwrap();
+        assert_eq!(report.progress_updated, 1);
+        assert_eq!(report.annotations_added, 1);
+        assert_eq!(report.skipped_books, vec!["book-b".to_string()]);
+
+        let conn = target.get_connection().unwrap();
+        let (location, percent): (String, f64) = conn
+            .query_row(
+                "SELECT current_location, progress_percent FROM reading_progress WHERE book_id = ?1",
+                params![target_a],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(location, "chapter-5");
+        assert_eq!(percent, 42.5);
+
+        // Importing the same state again is a no-op
+        let again = import_reading_state(&target, &json).unwrap();
+        assert_eq!(again.progress_updated, 0);
+        assert_eq!(again.annotations_added, 0);
+        assert_eq!(again.annotations_updated, 0);
+
+        let note: String = conn
+            .query_row(
+                "SELECT note_content FROM annotations WHERE book_id = ?1",
+                params![target_a],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(note, "remember this");
+    }
+
+    #[test]
+    fn test_reading_state_compares_mixed_timestamp_formats() {
+        assert!(is_newer("2026-03-02T10:00:00Z", "2026-03-02 09:59:59"));
+        assert!(!is_newer(
+            "2026-03-02T09:00:00+00:00",
+            "2026-03-02 09:30:00"
+        ));
+        // As strings, 'T' sorts after ' ' and this would look newer
+        assert!(!is_newer("2026-03-02T08:00:00Z", "2026-03-02 10:00:00"));
+
+        let dir = tempdir().unwrap();
+        let source = setup_db(dir.path(), "source.db");
+        let target = setup_db(dir.path(), "target.db");
+        let a = insert_book(&source, "book-a");
+        source
+            .get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'chapter-5', 42.5, '2026-03-02T08:00:00Z')",
+                params![a],
+            )
+            .unwrap();
+        let target_a = insert_book(&target, "book-a");
+        target
+            .get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'chapter-7', 60.0, '2026-03-02 10:00:00')",
+                params![target_a],
+            )
+            .unwrap();
+
+        let json = export_reading_state(&source).unwrap();
+        let report = import_reading_state(&target, &json).unwrap();
+        assert_eq!(report.progress_updated, 0);
+        let location: String = target
+            .get_connection()
+            .unwrap()
+            .query_row(
+                "SELECT current_location FROM reading_progress WHERE book_id = ?1",
+                params![target_a],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(location, "chapter-7");
+    }
+
+    fn seed_annotations(db: &Database, book_id: i64) {
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO annotations (book_id, type, location, selected_text, color)
+             VALUES (?1, 'highlight', 'chapter-2', 'Fear is the mind-killer.', '#FFEB3B')",
+            params![book_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO annotations (book_id, type, location, selected_text, color)
+             VALUES (?1, 'highlight', 'chapter-1', 'A beginning is a very delicate time.', '#4CAF50')",
+            params![book_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO annotations (book_id, type, location, selected_text, note_content)
+             VALUES (?1, 'note', 'chapter-2', 'Litany', 'Recited by Paul, \"twice\"')",
+            params![book_id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_export_annotations_markdown() {
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "annotations.db");
+        let book_id = insert_book(&db, "dune");
+        seed_annotations(&db, book_id);
+
+        let md = export_annotations(&db, book_id, AnnotationExportFormat::Markdown).unwrap();
+        assert!(md.starts_with("# Annotations: dune\n"));
+        let ch1 = md.find("## chapter-1").unwrap();
+        let ch2 = md.find("## chapter-2").unwrap();
+        assert!(ch1 < ch2);
+        assert_eq!(md.matches("## chapter-2").count(), 1);
+        assert!(md.contains("> A beginning is a very delicate time.\n"));
+        assert!(md.contains("> _Color: #4CAF50_"));
+        assert!(md.contains("- Litany\n  - Recited by Paul, \"twice\"\n"));
+
+        let empty_id = insert_book(&db, "empty");
+        let empty = export_annotations(&db, empty_id, AnnotationExportFormat::Markdown).unwrap();
+        assert_eq!(empty, "# Annotations: empty\n\n_No annotations._\n");
+    }
+
+    #[test]
+    fn test_export_annotations_orders_locations_numerically() {
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "annotations.db");
+        let book_id = insert_book(&db, "dune");
+        let conn = db.get_connection().unwrap();
+        for location in [
+            "epubcfi(/6/10!/4/2)",
+            "epubcfi(/6/2!/4/10)",
+            "epubcfi(/6/2!/4/2)",
+        ] {
+            conn.execute(
+                "INSERT INTO annotations (book_id, type, location, selected_text)
+                 VALUES (?1, 'highlight', ?2, ?2)",
+                params![book_id, location],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        let md = export_annotations(&db, book_id, AnnotationExportFormat::Markdown).unwrap();
+        let first = md.find("## epubcfi(/6/2!/4/2)").unwrap();
+        let second = md.find("## epubcfi(/6/2!/4/10)").unwrap();
+        let third = md.find("## epubcfi(/6/10!/4/2)").unwrap();
+        assert!(first < second && second < third, "{md}");
+    }
+
+    #[test]
+    fn test_export_annotations_csv() {
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "annotations.db");
+        let book_id = insert_book(&db, "dune");
+        seed_annotations(&db, book_id);
+
+        let csv_text = export_annotations(&db, book_id, AnnotationExportFormat::Csv).unwrap();
+        let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
+        assert_eq!(reader.headers().unwrap().len(), 11);
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(&rows[0][2], "highlight");
+        assert_eq!(&rows[0][3], "chapter-1");
+        assert_eq!(&rows[2][2], "note");
+        assert_eq!(&rows[2][6], "Recited by Paul, \"twice\"");
+
+        let empty_id = insert_book(&db, "empty");
+        let empty = export_annotations(&db, empty_id, AnnotationExportFormat::Csv).unwrap();
+        assert_eq!(empty.lines().count(), 1);
+
+        assert!(export_annotations(&db, 9999, AnnotationExportFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn test_anthology_merges_txt_books() {
+        use crate::services::collection_service::CollectionService;
+
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "anthology.db");
+
+        let mut book_ids = Vec::new();
+        for (uuid, format, body) in [
+            (
+                "first",
+                "txt",
+                "Chapter 1\nThe first story begins.\n\nChapter 2\nIt ends.",
+            ),
+            ("second", "txt", "A single untitled story."),
+            ("scan", "pdf", ""),
+        ] {
+            let path = dir.path().join(format!("{}.{}", uuid, format));
+            std::fs::write(&path, body).unwrap();
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (uuid, title, file_path, file_format) VALUES (?1, ?1, ?2, ?3)",
+                params![uuid, path.to_string_lossy(), format],
+            )
+            .unwrap();
+            book_ids.push(conn.last_insert_rowid());
+        }
+
+        let collection_id = {
+            let conn = db.get_connection().unwrap();
+            let collection = CollectionService::create_collection(
+                &conn, "Best Of", None, None, false, None, None, None, None,
+            )
+            .unwrap();
+            let id = collection.id.unwrap();
+            CollectionService::add_books_to_collection(&conn, id, book_ids).unwrap();
+            id
+        };
+
+        let out_dir = dir.path().join("exports");
+        let export = export_collection_as_anthology(&db, collection_id, &out_dir).unwrap();
+        assert_eq!(export.included.len(), 2);
+        assert_eq!(export.skipped, vec!["scan".to_string()]);
+
+        let mut doc = ::epub::doc::EpubDoc::new(&export.file_path).unwrap();
+        assert_eq!(
+            doc.mdata("title").map(|m| m.value.clone()),
+            Some("Best Of".to_string())
+        );
+        // Two chapters from the first book, one from the second
+        assert_eq!(doc.get_num_pages(), 3);
+        assert!(doc.get_current_str().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_export_epub_as_single_html() {
+        use crate::services::epub_builder::EpubBuilder;
+
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "html.db");
+        let epub_path = dir.path().join("two.epub");
+        let mut builder = EpubBuilder::new();
+        builder.add_image(
+            "images/dot.png".to_string(),
+            "image/png".to_string(),
+            vec![0x89, b'P', b'N', b'G'],
+        );
+        builder.add_html_chapter(
+            "Arrival".to_string(),
+            "<h1 id=\"top\">Arrival</h1><p>The ship lands.</p><img src=\"images/dot.png\" alt=\"\"/>\
+             <p><a href=\"ch0002.xhtml#end\">Skip ahead</a></p>"
+                .to_string(),
+        );
+        builder.add_html_chapter(
+            "Departure".to_string(),
+            "<h1 id=\"top\">Departure</h1><p id=\"end\">The ship leaves &amp; the story ends.</p>\
+             <p><a href=\"#top\">Top</a> <a href=\"ch0001.xhtml\">Again</a></p>"
+                .to_string(),
+        );
+        builder.append_stylesheet("h1 { background: url('images/dot.png'); }");
+        builder.write_to(&epub_path).unwrap();
+
+        let book_id = {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (uuid, title, file_path, file_format) VALUES ('two', 'Two Chapters', ?1, 'epub')",
+                params![epub_path.to_string_lossy()],
+            )
+            .unwrap();
+            conn.last_insert_rowid()
+        };
+
+        let out_dir = dir.path().join("exports");
+        let export = export_book_as_html(&db, book_id, &out_dir).await.unwrap();
+        assert!(export.warning.is_none());
+        let html = std::fs::read_to_string(&export.file_path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Two Chapters</title>"));
+
+        // Every TOC link lands on a section
+        let targets: Vec<&str> = html
+            .split("<a href=\"#")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                "chapter-1",
+                "chapter-2",
+                "chapter-2-end",
+                "chapter-2-top",
+                "chapter-1"
+            ]
+        );
+        for target in &targets {
+            assert!(html.contains(&format!("id=\"{}\"", target)));
+        }
+        // Both chapters had an id "top"; each keeps its own
+        assert!(html.contains("<h1 id=\"chapter-1-top\">"));
+        assert!(html.contains(">Arrival</a>"));
+        assert!(html.contains(">Departure</a>"));
+
+        // Nothing is left pointing into the EPUB
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+        assert!(html.contains("url(\"data:image/png;base64,iVBORw==\")"));
+        assert!(!html.contains("images/dot.png"));
+        assert!(!html.contains("stylesheet.css"));
+        assert!(!html.contains(".xhtml"));
+        assert_eq!(html.matches("<style>").count(), 1);
+    }
+
+    fn insert_catalog_book(db: &Database) {
+        let id = insert_book(db, "dune-deluxe");
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "UPDATE books SET title = ?1, series = 'Dune', series_index = 1.0, rating = 5,
+                 file_size = 1024 WHERE id = ?2",
+            params!["Dune, \"Deluxe\" Edition", id],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO authors (name) VALUES ('Frank Herbert')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO books_authors (book_id, author_id) VALUES (?1, ?2)",
+            params![id, conn.last_insert_rowid()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_export_catalog_csv_escapes_fields() {
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "catalog.db");
+        insert_catalog_book(&db);
+
+        let path = dir.path().join("catalog.csv");
+        let count = export_catalog(&db, ExportFormat::Csv, path.to_str().unwrap()).unwrap();
+        assert_eq!(count, 1);
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains(r#""Dune, ""Deluxe"" Edition""#));
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(reader.headers().unwrap().len(), CATALOG_COLUMNS.len());
+        let row = reader.records().next().unwrap().unwrap();
+        assert_eq!(&row[0], "Dune, \"Deluxe\" Edition");
+        assert_eq!(&row[1], "Frank Herbert");
+        assert_eq!(&row[7], "5");
+        assert_eq!(&row[9], "1024");
+    }
+
+    #[test]
+    fn test_export_catalog_json_round_trips() {
+        let dir = tempdir().unwrap();
+        let db = setup_db(dir.path(), "catalog.db");
+        insert_catalog_book(&db);
+
+        let path = dir.path().join("catalog.json");
+        export_catalog(&db, ExportFormat::Json, path.to_str().unwrap()).unwrap();
+
+        let books: Vec<crate::models::Book> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].title, "Dune, \"Deluxe\" Edition");
+        assert_eq!(books[0].authors[0].name, "Frank Herbert");
+
+        assert!(export_catalog(&db, ExportFormat::Markdown, path.to_str().unwrap()).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
@@ -197,6 +199,8 @@ impl Fb2ReaderAdapter {
                                         current_html
                                     ),
                                     location: format!("fb2-chapter-{}", idx),
+                                    word_count: 0,
+                                    estimated_minutes: 0.0,
                                 });
                                 toc.push(TocEntry {
                                     label: title,
@@ -270,6 +274,8 @@ impl Fb2ReaderAdapter {
                                         current_html
                                     ),
                                     location: format!("fb2-chapter-{}", idx),
+                                    word_count: 0,
+                                    estimated_minutes: 0.0,
                                 });
                                 toc.push(TocEntry {
                                     label: title,
@@ -336,6 +342,8 @@ impl Fb2ReaderAdapter {
                 title: "Content".to_string(),
                 content: format!("<div class=\"fb2-content\">{}</div>", current_html),
                 location: "fb2-chapter-0".to_string(),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
             toc.push(TocEntry {
                 label: "Content".to_string(),
@@ -391,6 +399,8 @@ impl Fb2ReaderAdapter {
                     title: "Content".to_string(),
                     content: format!("<div class=\"fb2-content\">{}</div>", fallback_html),
                     location: "fb2-chapter-0".to_string(),
+                    word_count: 0,
+                    estimated_minutes: 0.0,
                 });
                 toc.push(TocEntry {
                     label: "Content".to_string(),
@@ -403,24 +413,6 @@ impl Fb2ReaderAdapter {
 
         (chapters, toc)
     }
-
-    /// UTF-8-safe search snippet extraction
-    fn safe_snippet(content: &str, match_pos: usize, query_char_len: usize) -> String {
-        let char_indices: Vec<(usize, char)> = content.char_indices().collect();
-        let char_idx = char_indices
-            .iter()
-            .position(|&(b_idx, _)| b_idx >= match_pos)
-            .unwrap_or(0);
-        let start_char = char_idx.saturating_sub(50);
-        let end_char = (char_idx + query_char_len + 50).min(char_indices.len());
-        let start_byte = char_indices.get(start_char).map(|&(b, _)| b).unwrap_or(0);
-        let end_byte = if end_char >= char_indices.len() {
-            content.len()
-        } else {
-            char_indices[end_char].0
-        };
-        format!("...{}...", &content[start_byte..end_byte])
-    }
 }
 
 unsafe impl Send for Fb2ReaderAdapter {}
@@ -489,9 +481,10 @@ impl BookReaderAdapter for Fb2ReaderAdapter {
         self.chapters.len()
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
-        let query_char_len = query.chars().count();
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
         let mut results = Vec::new();
 
         fn strip_html_tags(html: &str) -> String {
@@ -517,18 +510,12 @@ impl BookReaderAdapter for Fb2ReaderAdapter {
 
         for chapter in &self.chapters {
             let content = strip_html_tags(&chapter.content);
-            let content_lower = content.to_lowercase();
-            let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-            if !matches.is_empty() {
-                let snippet = Self::safe_snippet(&content, matches[0].0, query_char_len);
-                results.push(SearchResult {
-                    chapter_index: chapter.index,
-                    chapter_title: chapter.title.clone(),
-                    snippet,
-                    location: chapter.location.clone(),
-                    match_count: matches.len(),
-                });
-            }
+            results.extend(matcher.search_chapter(
+                &content,
+                chapter.index,
+                chapter.title.clone(),
+                chapter.location.clone(),
+            ));
         }
 
         Ok(results)
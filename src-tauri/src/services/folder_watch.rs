@@ -10,7 +10,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 const SUPPORTED_FORMATS: &[&str] = &[
-    "epub", "pdf", "mobi", "azw3", "docx", "fb2", "cbz", "cbr", "zip",
+    "epub", "pdf", "mobi", "azw3", "docx", "fb2", "djvu", "cbz", "cbr", "zip",
 ];
 
 const SYSTEM_DIRS: &[&str] = &[
@@ -31,6 +31,16 @@ const SYSTEM_DIRS: &[&str] = &[
     "C:\\Program Files (x86)",
 ];
 
+/// Whether `path` is, or is inside, a system directory that must never be
+/// watched or walked
+pub(crate) fn is_system_directory(path: &str) -> bool {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    let path = if trimmed.is_empty() { path } else { trimmed };
+    SYSTEM_DIRS
+        .iter()
+        .any(|sys_dir| path == *sys_dir || path.starts_with(&format!("{}/", sys_dir)))
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct WatchFolder {
     pub path: String,
@@ -110,7 +120,7 @@ impl FolderWatchService {
                 continue;
             }
 
-            if Self::is_system_directory(&folder_path) {
+            if is_system_directory(&folder_path) {
                 log::error!("Refusing to watch system directory: {}", folder_path);
                 continue;
             }
@@ -146,7 +156,7 @@ impl FolderWatchService {
     }
 
     pub fn add_watch_folder(&self, path: String, enabled: bool) -> Result<()> {
-        if Self::is_system_directory(&path) {
+        if is_system_directory(&path) {
             return Err(ShioriError::Other(format!(
                 "Cannot watch system directory: {}",
                 path
@@ -250,12 +260,17 @@ impl FolderWatchService {
             log::info!("Importing new file: {}", path_str);
 
             match library_service::import_single_book(db, &path_str, covers_dir) {
-                Ok(is_duplicate) => {
-                    if is_duplicate {
-                        log::info!("File is duplicate (by hash): {}", path_str);
-                    } else {
-                        log::info!("Successfully imported: {}", path_str);
-                    }
+                Ok(library_service::ImportOutcome::Duplicate { .. }) => {
+                    log::info!("File is duplicate (by hash): {}", path_str);
+                }
+                Ok(library_service::ImportOutcome::Merged { book_id }) => {
+                    log::info!("Attached {} as a format of book {}", path_str, book_id);
+                }
+                Ok(library_service::ImportOutcome::Replaced { book_id }) => {
+                    log::info!("Replaced the file of book {} with {}", book_id, path_str);
+                }
+                Ok(library_service::ImportOutcome::Imported { .. }) => {
+                    log::info!("Successfully imported: {}", path_str);
                 }
                 Err(e) => {
                     log::error!("Failed to import {}: {}", path_str, e);
@@ -273,12 +288,6 @@ impl FolderWatchService {
             .unwrap_or(false)
     }
 
-    fn is_system_directory(path: &str) -> bool {
-        SYSTEM_DIRS
-            .iter()
-            .any(|sys_dir| path == *sys_dir || path.starts_with(&format!("{}/", sys_dir)))
-    }
-
     fn file_already_imported(db: &Database, path: &str) -> Result<bool> {
         let conn = db.get_connection()?;
         let exists: bool = conn
@@ -38,6 +38,91 @@ pub enum FormatError {
 
     #[error("Conversion failed: {0}")]
     ConversionError(String),
+
+    #[error("Source file is corrupt or unreadable: {0}")]
+    CorruptSource(String),
+
+    #[error("Conversion produced no content: {0}")]
+    EmptyOutput(String),
+
+    #[error("Missing dependency: {0}")]
+    DependencyMissing(String),
+
+    #[error("Conversion cancelled")]
+    Cancelled,
+}
+
+impl FormatError {
+    /// Stable machine-readable code sent to the UI with `conversion:error`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::Image(_) => "image",
+            Self::InvalidFormat(_) => "invalid_format",
+            Self::UnsupportedFormat(_) => "unsupported_format",
+            Self::MetadataError(_) => "metadata",
+            Self::CoverError(_) => "cover",
+            Self::ValidationError(_) => "validation",
+            Self::ConversionNotSupported { .. } => "conversion_not_supported",
+            Self::ConversionError(_) => "conversion_failed",
+            Self::CorruptSource(_) => "corrupt_source",
+            Self::EmptyOutput(_) => "empty_output",
+            Self::DependencyMissing(_) => "dependency_missing",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// User-facing explanation for a [`FormatError::code`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorDetails {
+    pub error_code: String,
+    pub title: String,
+    pub remediation: String,
+}
+
+impl ErrorDetails {
+    pub fn for_code(code: &str) -> Self {
+        let (title, remediation) = match code {
+            "io" => (
+                "File could not be read or written",
+                "Check that the file still exists and that the output folder is writable.",
+            ),
+            "image" => (
+                "Image processing failed",
+                "The book contains an image that could not be decoded. Try converting without the cover or re-exporting the source.",
+            ),
+            "invalid_format" | "corrupt_source" => (
+                "The source file is damaged",
+                "Re-download or re-export the book, then try again. Opening it in its original app can confirm whether it is intact.",
+            ),
+            "unsupported_format" | "conversion_not_supported" => (
+                "This conversion is not supported",
+                "Pick a different target format, or enable Calibre in settings for wider format coverage.",
+            ),
+            "empty_output" => (
+                "No text could be extracted",
+                "The file has no text layer, which usually means it is a scanned PDF. Retry the conversion with OCR enabled.",
+            ),
+            "dependency_missing" => (
+                "A required tool is not installed",
+                "Install the missing tool (for OCR: tesseract and poppler's pdftoppm) and make sure it is on your PATH.",
+            ),
+            "cancelled" => (
+                "Conversion cancelled",
+                "The job was cancelled. Submit it again to restart the conversion.",
+            ),
+            _ => (
+                "Conversion failed",
+                "Try again, or enable Calibre in settings to use it as a fallback converter.",
+            ),
+        };
+        Self {
+            error_code: code.to_string(),
+            title: title.to_string(),
+            remediation: remediation.to_string(),
+        }
+    }
 }
 
 pub type FormatResult<T> = Result<T, FormatError>;
@@ -273,6 +358,17 @@ pub enum DetectionMethod {
     ContentInspection,
 }
 
+impl DetectionMethod {
+    /// Short name reported by `detect_format_detailed`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Extension => "extension",
+            Self::MagicBytes => "magic",
+            Self::ContentInspection => "content",
+        }
+    }
+}
+
 #[allow(dead_code)]
 impl FormatInfo {
     pub fn new(format: &str) -> Self {
@@ -288,6 +384,7 @@ impl FormatInfo {
             ),
             "txt" => ("txt", "text/plain"),
             "html" => ("html", "text/html"),
+            "djvu" => ("djvu", "image/vnd.djvu"),
             "cbz" => ("cbz", "application/vnd.comicbook+zip"),
             "cbr" => ("cbr", "application/vnd.comicbook-rar"),
             _ => ("bin", "application/octet-stream"),
@@ -325,6 +422,9 @@ impl FormatInfo {
     pub fn html() -> Self {
         Self::new("html")
     }
+    pub fn djvu() -> Self {
+        Self::new("djvu")
+    }
     pub fn cbz() -> Self {
         Self::new("cbz")
     }
@@ -5,6 +5,7 @@
 /// 2. Magic byte verification
 /// 3. Deep content inspection
 use crate::services::format_adapter::{DetectionMethod, FormatError, FormatInfo, FormatResult};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::io::Read;
 use std::path::Path;
@@ -16,8 +17,11 @@ const MAGIC_PDF: &[u8] = b"%PDF";
 const MAGIC_ZIP: &[u8] = b"PK\x03\x04";
 const MAGIC_MOBI: &[u8] = b"BOOKMOBI";
 const MAGIC_XML: &[u8] = b"<?xml";
-const MAGIC_HTML_DOCTYPE: &[u8] = b"<!DOCTYPE html";
+// HTML markers are matched against lowercased content
+const MAGIC_HTML_DOCTYPE: &[u8] = b"<!doctype html";
 const MAGIC_HTML_TAG: &[u8] = b"<html";
+const MAGIC_DJVU: &[u8] = b"AT&TFORM";
+const MAGIC_RAR: &[u8] = b"Rar!\x1a\x07";
 
 lazy_static::lazy_static! {
     static ref EXTENSION_MAP: HashMap<&'static str, &'static str> = {
@@ -35,66 +39,105 @@ lazy_static::lazy_static! {
         m.insert("html", "html");
         m.insert("htm", "html");
         m.insert("xhtml", "html");
+        m.insert("djvu", "djvu");
+        m.insert("djv", "djvu");
         m.insert("cbz", "cbz");
         m.insert("cbr", "cbr");
         m
     };
 }
 
+/// Detection result with how it was reached, for diagnosing mis-imported
+/// files (e.g. a `.txt` that is really HTML)
+#[derive(Debug, Clone, Serialize)]
+pub struct DetailedFormat {
+    pub format: String,
+    /// `extension`, `magic` or `content`
+    pub detected_by: &'static str,
+    /// 0.0–1.0: structural matches score highest, text heuristics lowest
+    pub confidence: f32,
+    pub mime_type: String,
+}
+
 /// Main format detection function
 pub async fn detect_format(path: &Path) -> FormatResult<FormatInfo> {
+    detect(path).await.map(|(info, _)| info)
+}
+
+/// Like [`detect_format`], also reporting how sure the match is
+pub async fn detect_format_detailed(path: &Path) -> FormatResult<DetailedFormat> {
+    let (info, confidence) = detect(path).await?;
+    Ok(DetailedFormat {
+        format: info.format,
+        detected_by: info.detected_by.as_str(),
+        confidence,
+        mime_type: info.mime_type,
+    })
+}
+
+fn detected(format: &str, method: DetectionMethod, confidence: f32) -> (FormatInfo, f32) {
+    let mut info = FormatInfo::new(format);
+    info.detected_by = method;
+    (info, confidence)
+}
+
+async fn detect(path: &Path) -> FormatResult<(FormatInfo, f32)> {
+    let magic = read_magic_bytes(path, 512).await?;
+    let claimed = get_extension(path).and_then(|ext| EXTENSION_MAP.get(ext.as_str()).copied());
+
+    // ZIP-based formats (EPUB, DOCX, CBZ) share one signature, so the
+    // extension can't tell them apart: the archive structure decides
+    if magic.starts_with(MAGIC_ZIP) {
+        let (mut info, confidence) = classify_zip_format(path).await?;
+        if claimed == Some(info.format.as_str()) {
+            info.detected_by = DetectionMethod::Extension;
+            return Ok((info, 0.99));
+        }
+        return Ok((info, confidence));
+    }
+
     // Stage 1: Extension check (fast path)
-    if let Some(ext) = get_extension(path) {
-        if let Some(format) = EXTENSION_MAP.get(ext.as_str()) {
-            // Stage 2: Verify with magic bytes
-            if verify_magic_bytes(path, format).await? {
-                let mut info = FormatInfo::new(format);
-                info.detected_by = DetectionMethod::Extension;
-                return Ok(info);
-            }
+    if let Some(format) = claimed {
+        // Stage 2: Verify with magic bytes
+        if verify_magic_bytes(&magic, format) {
+            let confidence = match format {
+                "txt" => 0.6,
+                "html" | "fb2" => 0.85,
+                _ => 0.95,
+            };
+            return Ok(detected(format, DetectionMethod::Extension, confidence));
         }
     }
 
     // Stage 3: Deep content inspection
-    let magic = read_magic_bytes(path, 512).await?;
-
-    // PDF check
     if magic.starts_with(MAGIC_PDF) {
-        let mut info = FormatInfo::pdf();
-        info.detected_by = DetectionMethod::MagicBytes;
-        return Ok(info);
+        return Ok(detected("pdf", DetectionMethod::MagicBytes, 0.9));
     }
-
-    // ZIP-based formats (EPUB, DOCX, CBZ)
-    if magic.starts_with(MAGIC_ZIP) {
-        let format_info = classify_zip_format(path).await?;
-        return Ok(format_info);
+    if magic.starts_with(MAGIC_DJVU) {
+        return Ok(detected("djvu", DetectionMethod::MagicBytes, 0.9));
+    }
+    if magic.starts_with(MAGIC_RAR) {
+        return Ok(detected("cbr", DetectionMethod::MagicBytes, 0.8));
     }
 
     // MOBI/AZW3 check (magic bytes at offset 60)
     if magic.len() >= 68 && &magic[60..68] == MAGIC_MOBI {
         let format_info = classify_mobi_format(path).await?;
-        return Ok(format_info);
+        return Ok((format_info, 0.85));
     }
 
     // XML-based formats (FB2, HTML)
     if magic.starts_with(MAGIC_XML) {
-        let format_info = classify_xml_format(&magic).await?;
-        return Ok(format_info);
+        return classify_xml_format(&magic).await;
     }
 
-    // HTML check
-    if magic.starts_with(MAGIC_HTML_DOCTYPE) || magic.starts_with(MAGIC_HTML_TAG) {
-        let mut info = FormatInfo::html();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+    if looks_like_html(&magic) {
+        return Ok(detected("html", DetectionMethod::ContentInspection, 0.75));
     }
 
     // Text file check (UTF-8 validation)
     if is_valid_utf8(&magic) && is_text_like(&magic) {
-        let mut info = FormatInfo::txt();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+        return Ok(detected("txt", DetectionMethod::ContentInspection, 0.5));
     }
 
     Err(FormatError::UnsupportedFormat(format!(
@@ -119,16 +162,10 @@ async fn read_magic_bytes(path: &Path, num_bytes: usize) -> FormatResult<Vec<u8>
     Ok(buffer)
 }
 
-/// Verify magic bytes for a specific format
-async fn verify_magic_bytes(path: &Path, format: &str) -> FormatResult<bool> {
-    let magic = read_magic_bytes(path, 512).await?;
-
-    let is_valid = match format {
+/// Verify magic bytes for a specific non-ZIP format
+fn verify_magic_bytes(magic: &[u8], format: &str) -> bool {
+    match format {
         "pdf" => magic.starts_with(MAGIC_PDF),
-        "epub" | "docx" | "cbz" => {
-            // ZIP-based formats
-            magic.starts_with(MAGIC_ZIP)
-        }
         "mobi" | "azw3" => {
             // MOBI magic bytes at offset 60
             magic.len() >= 68 && &magic[60..68] == MAGIC_MOBI
@@ -137,25 +174,42 @@ async fn verify_magic_bytes(path: &Path, format: &str) -> FormatResult<bool> {
             // FB2 is XML with FictionBook root
             magic.starts_with(MAGIC_XML)
         }
-        "html" => magic.starts_with(MAGIC_HTML_DOCTYPE) || magic.starts_with(MAGIC_HTML_TAG),
+        "html" => looks_like_html(magic),
+        "djvu" => magic.starts_with(MAGIC_DJVU),
+        "cbr" => magic.starts_with(MAGIC_RAR),
         "txt" => {
-            // Text files should be valid UTF-8
-            is_valid_utf8(&magic)
+            // Text files should be valid UTF-8, and not markup saved as .txt
+            is_valid_utf8(magic) && !looks_like_html(magic)
         }
         _ => false,
-    };
+    }
+}
 
-    Ok(is_valid)
+/// Whether the content opens with an HTML doctype or root tag, ignoring
+/// a BOM, leading whitespace and case
+fn looks_like_html(magic: &[u8]) -> bool {
+    let text = magic.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(magic);
+    let start = text
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    let head = text[start..].to_ascii_lowercase();
+    head.starts_with(MAGIC_HTML_DOCTYPE) || head.starts_with(MAGIC_HTML_TAG)
 }
 
 /// Classify ZIP-based formats (EPUB, DOCX, CBZ)
-async fn classify_zip_format(path: &Path) -> FormatResult<FormatInfo> {
-    use std::io::Cursor;
-
-    let file_data = tokio::fs::read(path).await?;
-    let cursor = Cursor::new(file_data);
+async fn classify_zip_format(path: &Path) -> FormatResult<(FormatInfo, f32)> {
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || classify_zip_archive(&path))
+        .await
+        .map_err(|e| std::io::Error::other(format!("Task spawn failed: {:?}", e)))?
+}
 
-    let mut archive = zip::ZipArchive::new(cursor)
+/// Blocking half of [`classify_zip_format`]
+fn classify_zip_archive(path: &Path) -> FormatResult<(FormatInfo, f32)> {
+    // Only the central directory is read, not the whole archive
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)
         .map_err(|e| FormatError::InvalidFormat(format!("Invalid ZIP: {}", e)))?;
 
     // EPUB: contains "mimetype" file with "application/epub+zip"
@@ -166,9 +220,7 @@ async fn classify_zip_format(path: &Path) -> FormatResult<FormatInfo> {
             .map_err(|e| FormatError::InvalidFormat(format!("Failed to read mimetype: {}", e)))?;
 
         if content.trim().contains("epub") {
-            let mut info = FormatInfo::epub();
-            info.detected_by = DetectionMethod::ContentInspection;
-            return Ok(info);
+            return Ok(detected("epub", DetectionMethod::ContentInspection, 0.95));
         }
     }
 
@@ -176,9 +228,12 @@ async fn classify_zip_format(path: &Path) -> FormatResult<FormatInfo> {
     if archive.by_name("[Content_Types].xml").is_ok()
         && archive.by_name("word/document.xml").is_ok()
     {
-        let mut info = FormatInfo::docx();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+        return Ok(detected("docx", DetectionMethod::ContentInspection, 0.95));
+    }
+
+    // EPUB written without its mimetype entry still has the OCF container
+    if archive.by_name("META-INF/container.xml").is_ok() {
+        return Ok(detected("epub", DetectionMethod::ContentInspection, 0.8));
     }
 
     // CBZ: contains image files (jpg, png, webp)
@@ -195,9 +250,7 @@ async fn classify_zip_format(path: &Path) -> FormatResult<FormatInfo> {
     });
 
     if has_images {
-        let mut info = FormatInfo::cbz();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+        return Ok(detected("cbz", DetectionMethod::ContentInspection, 0.8));
     }
 
     Err(FormatError::UnsupportedFormat(
@@ -225,21 +278,17 @@ async fn classify_mobi_format(path: &Path) -> FormatResult<FormatInfo> {
 }
 
 /// Classify XML-based formats (FB2, HTML)
-async fn classify_xml_format(magic: &[u8]) -> FormatResult<FormatInfo> {
+async fn classify_xml_format(magic: &[u8]) -> FormatResult<(FormatInfo, f32)> {
     let content = String::from_utf8_lossy(magic);
 
     // FB2: has <FictionBook> root element
     if content.contains("<FictionBook") || content.contains("<fictionbook") {
-        let mut info = FormatInfo::fb2();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+        return Ok(detected("fb2", DetectionMethod::ContentInspection, 0.85));
     }
 
     // Check if it's actually XHTML (treat as HTML)
     if content.contains("<html") || content.contains("<HTML") {
-        let mut info = FormatInfo::html();
-        info.detected_by = DetectionMethod::ContentInspection;
-        return Ok(info);
+        return Ok(detected("html", DetectionMethod::ContentInspection, 0.75));
     }
 
     Err(FormatError::UnsupportedFormat(
@@ -303,4 +352,69 @@ mod tests {
         assert!(is_text_like(b"Line 1\nLine 2\nLine 3"));
         assert!(!is_text_like(&[0xFF, 0xFE, 0x00, 0x01]));
     }
+
+    fn zip_with(entries: &[(&str, &[u8])], suffix: &str) -> NamedTempFile {
+        let file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file.reopen().unwrap());
+        for (name, data) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn test_zip_formats_are_told_apart_by_structure() {
+        let epub = zip_with(
+            &[
+                ("mimetype", b"application/epub+zip"),
+                ("META-INF/container.xml", b"<container/>"),
+            ],
+            ".zip",
+        );
+        let result = detect_format_detailed(epub.path()).await.unwrap();
+        assert_eq!(result.format, "epub");
+        assert_eq!(result.detected_by, "content");
+        assert_eq!(result.mime_type, "application/epub+zip");
+
+        // A DOCX renamed to .epub is still reported as DOCX
+        let docx = zip_with(
+            &[
+                ("[Content_Types].xml", b"<Types/>"),
+                ("word/document.xml", b"<w:document/>"),
+            ],
+            ".epub",
+        );
+        let result = detect_format_detailed(docx.path()).await.unwrap();
+        assert_eq!(result.format, "docx");
+        assert_eq!(result.detected_by, "content");
+
+        let named_docx = zip_with(
+            &[
+                ("[Content_Types].xml", b"<Types/>"),
+                ("word/document.xml", b"<w:document/>"),
+            ],
+            ".docx",
+        );
+        let result = detect_format_detailed(named_docx.path()).await.unwrap();
+        assert_eq!(result.detected_by, "extension");
+        assert!(result.confidence > 0.95);
+
+        let plain = zip_with(&[("notes.txt", b"hello")], ".epub");
+        assert!(detect_format_detailed(plain.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_html_saved_as_txt_is_detected_as_html() {
+        let mut file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+        file.write_all(b"\n  <!DOCTYPE HTML><html><body>Hi</body></html>")
+            .unwrap();
+
+        let result = detect_format_detailed(file.path()).await.unwrap();
+        assert_eq!(result.format, "html");
+        assert_eq!(result.detected_by, "content");
+        assert_eq!(result.mime_type, "text/html");
+    }
 }
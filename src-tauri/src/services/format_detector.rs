@@ -10,6 +10,7 @@ const MAX_FILE_SIZE_MB: u64 = 500;
 const EPUB_MAGIC: &[u8] = b"PK\x03\x04";
 const PDF_MAGIC: &[u8] = b"%PDF";
 const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const DJVU_MAGIC: &[u8] = b"AT&TFORM";
 
 /// Supported book formats
 #[derive(Debug, Clone, PartialEq)]
@@ -21,6 +22,7 @@ pub enum BookFormat {
     Cbz,
     Cbr,
     Txt,
+    Djvu,
     Audio,
 }
 
@@ -34,6 +36,7 @@ impl BookFormat {
             BookFormat::Cbz => "cbz",
             BookFormat::Cbr => "cbr",
             BookFormat::Txt => "txt",
+            BookFormat::Djvu => "djvu",
             BookFormat::Audio => "audio",
         }
     }
@@ -47,6 +50,7 @@ impl BookFormat {
             "cbz" | "zip" => Some(BookFormat::Cbz),
             "cbr" => Some(BookFormat::Cbr),
             "txt" => Some(BookFormat::Txt),
+            "djvu" | "djv" => Some(BookFormat::Djvu),
             "mp3" | "m4a" | "m4b" | "ogg" | "flac" | "wav" => Some(BookFormat::Audio),
             _ => None,
         }
@@ -132,6 +136,7 @@ fn verify_format_with_magic(path: &Path, format: &BookFormat) -> Result<bool> {
                 .count()
                 < bytes_read / 10)
         }
+        BookFormat::Djvu => Ok(buffer.starts_with(DJVU_MAGIC)),
         BookFormat::Cbr => {
             // CBR files are RAR archives
             Ok(buffer.starts_with(&[0x52, 0x61, 0x72, 0x21]))
@@ -166,6 +171,11 @@ fn detect_format_from_magic(path: &Path) -> Result<String> {
         return Ok("pdf".to_string());
     }
 
+    // Check DjVu
+    if buffer.starts_with(DJVU_MAGIC) {
+        return Ok("djvu".to_string());
+    }
+
     // Check MOBI/AZW3
     if buffer.len() >= 68 && &buffer[60..68] == b"BOOKMOBI" {
         return Ok("mobi".to_string());
@@ -211,6 +221,12 @@ fn detect_format_from_magic(path: &Path) -> Result<String> {
 
 /// Validate book file integrity based on format
 pub async fn validate_file_integrity(path: &Path, format: &str) -> Result<bool> {
+    validate_file_structure(path, format)
+}
+
+/// Blocking structural validation behind `validate_file_integrity`.
+/// Formats without a validator fail with `UnsupportedFormat`.
+pub fn validate_file_structure(path: &Path, format: &str) -> Result<bool> {
     let format_enum =
         BookFormat::from_str(format).ok_or_else(|| ShioriError::UnsupportedFormat {
             format: format.to_string(),
@@ -224,6 +240,9 @@ pub async fn validate_file_integrity(path: &Path, format: &str) -> Result<bool>
         BookFormat::Cbz => validate_cbz(path),
         BookFormat::Cbr => validate_cbr(path),
         BookFormat::Txt => validate_txt(path),
+        BookFormat::Djvu => crate::services::djvu_document::DjvuDocument::open(path)
+            .map(|_| true)
+            .map_err(|e| ShioriError::InvalidFormat(e.to_string())),
         BookFormat::Audio => Ok(true), // We trust symphonia for playback, basic structural validation is enough.
     }
 }
@@ -303,19 +322,9 @@ fn validate_cbz(path: &Path) -> Result<bool> {
 }
 
 fn validate_cbr(path: &Path) -> Result<bool> {
-    // RAR support requires external library (unrar)
-    // For now, just check magic bytes
-    let mut file = File::open(path)?;
-    let mut buffer = vec![0u8; 4];
-    file.read_exact(&mut buffer)?;
-
-    if buffer == [0x52, 0x61, 0x72, 0x21] {
-        Ok(true)
-    } else {
-        Err(ShioriError::InvalidFormat(
-            "Invalid CBR/RAR file".to_string(),
-        ))
-    }
+    // Walks the RAR headers, rejecting encrypted archives and ones with no pages
+    crate::services::cbr_archive::CbrArchive::open(path)?;
+    Ok(true)
 }
 
 fn validate_txt(path: &Path) -> Result<bool> {
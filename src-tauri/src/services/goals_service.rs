@@ -0,0 +1,262 @@
+//! "N books this week/month/year" goals and how far along they are.
+//!
+//! The goal lives on the active `reading_goals` row (`books_target`,
+//! `books_period`). A book counts as finished on the day its progress last
+//! reached 100% (or it was marked completed), using the same definition as
+//! the reading statistics. Periods are calendar periods in UTC and weeks
+//! start on Monday.
+
+use crate::error::{Result, ShioriError};
+use crate::models::{GoalPace, GoalProgress};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Finishing within this many books of the even pace counts as on track
+const PACE_TOLERANCE: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GoalPeriod {
+    Week,
+    Month,
+    Year,
+}
+
+impl GoalPeriod {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            other => Err(ShioriError::Validation(format!(
+                "Goal period must be week, month or year, got '{}'",
+                other
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+
+    /// First and last day (inclusive) of the period containing `day`
+    fn bounds(self, day: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Week => {
+                let start = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            Self::Month => {
+                let start = day.with_day(1).unwrap_or(day);
+                let next = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                };
+                (start, next.map_or(day, |n| n - Duration::days(1)))
+            }
+            Self::Year => (
+                NaiveDate::from_ymd_opt(day.year(), 1, 1).unwrap_or(day),
+                NaiveDate::from_ymd_opt(day.year(), 12, 31).unwrap_or(day),
+            ),
+        }
+    }
+}
+
+/// Set the books goal on the active reading goal, creating one if needed
+pub fn set_goal(conn: &Connection, period: &str, target: i32) -> Result<GoalProgress> {
+    let period = GoalPeriod::parse(period)?;
+    if target < 1 {
+        return Err(ShioriError::Validation(format!(
+            "Goal target must be at least 1 book, got {}",
+            target
+        )));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    // Keep the older yearly target in step so existing screens agree
+    let updated = conn.execute(
+        "UPDATE reading_goals SET books_target = ?1, books_period = ?2,
+             yearly_books_target = CASE WHEN ?2 = 'year' THEN ?1 ELSE yearly_books_target END,
+             updated_at = ?3
+         WHERE is_active = 1",
+        params![target, period.as_str(), now],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO reading_goals (books_target, books_period, yearly_books_target, is_active, created_at, updated_at)
+             VALUES (?1, ?2, CASE WHEN ?2 = 'year' THEN ?1 END, 1, ?3, ?3)",
+            params![target, period.as_str(), now],
+        )?;
+    }
+
+    progress_on(conn, period, target, Utc::now().date_naive())
+}
+
+/// Progress of the active books goal for the current period, or `None` when
+/// no goal has been set
+pub fn get_goal_progress(conn: &Connection) -> Result<Option<GoalProgress>> {
+    goal_progress_on(conn, Utc::now().date_naive())
+}
+
+fn goal_progress_on(conn: &Connection, today: NaiveDate) -> Result<Option<GoalProgress>> {
+    let goal: Option<(Option<i32>, String)> = conn
+        .query_row(
+            "SELECT books_target, books_period FROM reading_goals
+             WHERE is_active = 1 ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match goal {
+        Some((Some(target), period)) if target > 0 => {
+            progress_on(conn, GoalPeriod::parse(&period)?, target, today).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn progress_on(
+    conn: &Connection,
+    period: GoalPeriod,
+    target: i32,
+    today: NaiveDate,
+) -> Result<GoalProgress> {
+    let (start, end) = period.bounds(today);
+    let period_start = start.format(DATE_FORMAT).to_string();
+    let period_end = end.format(DATE_FORMAT).to_string();
+
+    let books_finished: i64 = conn.query_row(
+        "SELECT COUNT(*)
+         FROM reading_progress rp
+         JOIN books b ON b.id = rp.book_id
+         WHERE date(rp.last_read) BETWEEN ?1 AND ?2
+           AND (rp.progress_percent >= 100 OR b.reading_status = 'completed')",
+        params![period_start, period_end],
+        |row| row.get(0),
+    )?;
+
+    let total_days = (end - start).num_days() + 1;
+    let elapsed_days = (today - start).num_days() + 1;
+    let expected_by_now = target as f64 * elapsed_days as f64 / total_days as f64;
+    let difference = books_finished as f64 - expected_by_now;
+    let pace = if difference >= PACE_TOLERANCE {
+        GoalPace::Ahead
+    } else if difference <= -PACE_TOLERANCE {
+        GoalPace::Behind
+    } else {
+        GoalPace::OnTrack
+    };
+
+    Ok(GoalProgress {
+        period: period.as_str().to_string(),
+        target,
+        period_start,
+        period_end,
+        books_finished,
+        completion_ratio: books_finished as f64 / target as f64,
+        expected_by_now,
+        pace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use tempfile::tempdir;
+
+    fn add_finished(conn: &Connection, title: &str, percent: f64, last_read: &str) {
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+            params![title, format!("/tmp/{}.epub", title)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+             VALUES (?1, '0', ?2, ?3)",
+            params![conn.last_insert_rowid(), percent, last_read],
+        )
+        .unwrap();
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_yearly_goal_counts_books_finished_this_year() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("goals.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        assert!(goal_progress_on(&conn, date("2026-07-02"))
+            .unwrap()
+            .is_none());
+
+        add_finished(&conn, "Dune", 100.0, "2026-01-11T21:00:00+00:00");
+        add_finished(&conn, "Emma", 100.0, "2026-03-02T09:00:00+00:00");
+        add_finished(&conn, "Ubik", 100.0, "2026-06-30T22:00:00+00:00");
+        add_finished(&conn, "Half Read", 50.0, "2026-05-01T10:00:00+00:00");
+        add_finished(&conn, "Last Year", 100.0, "2025-12-31T23:00:00+00:00");
+
+        set_goal(&conn, "year", 24).unwrap();
+        let progress = goal_progress_on(&conn, date("2026-07-02"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(progress.period, "year");
+        assert_eq!(progress.period_start, "2026-01-01");
+        assert_eq!(progress.period_end, "2026-12-31");
+        assert_eq!(progress.books_finished, 3);
+        assert!((progress.completion_ratio - 0.125).abs() < 1e-9);
+        // Day 183 of 365 puts an even pace at about 12 books
+        assert!((progress.expected_by_now - 24.0 * 183.0 / 365.0).abs() < 1e-9);
+        assert_eq!(progress.pace, GoalPace::Behind);
+
+        // The older yearly target follows the books goal
+        let yearly: Option<i32> = conn
+            .query_row(
+                "SELECT yearly_books_target FROM reading_goals WHERE is_active = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(yearly, Some(24));
+
+        set_goal(&conn, "month", 1).unwrap();
+        let june = goal_progress_on(&conn, date("2026-06-30"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(june.period_start, "2026-06-01");
+        assert_eq!(june.books_finished, 1);
+        assert_eq!(june.pace, GoalPace::OnTrack);
+    }
+
+    #[test]
+    fn test_goal_periods_and_validation() {
+        assert_eq!(
+            GoalPeriod::Week.bounds(date("2026-10-18")),
+            (date("2026-10-12"), date("2026-10-18"))
+        );
+        assert_eq!(
+            GoalPeriod::Month.bounds(date("2024-02-10")),
+            (date("2024-02-01"), date("2024-02-29"))
+        );
+        assert_eq!(
+            GoalPeriod::Month.bounds(date("2026-12-31")),
+            (date("2026-12-01"), date("2026-12-31"))
+        );
+
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("goals.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        assert!(set_goal(&conn, "decade", 5).is_err());
+        assert!(set_goal(&conn, "year", 0).is_err());
+    }
+}
@@ -1,11 +1,18 @@
 use crate::db::Database;
 use crate::error::{Result, ShioriError};
-use crate::models::{Author, Book, ImportResult, Tag};
-use crate::services::metadata_service;
+use crate::models::{
+    Author, Book, DuplicateGroup, ImportResult, RescanResult, SeriesGroup, SeriesSummary, Tag,
+};
+use crate::services::epub_adapter::{self, EpubMetadataUpdate};
+use crate::services::{author_service, doodle_service, metadata_service, tag_service};
 use crate::utils::file::{calculate_file_hash, get_file_size};
+use crate::utils::isbn;
+use crate::utils::lang_detect;
 use crate::utils::validate;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use rusqlite::params;
+use regex::Regex;
+use rusqlite::{params, OptionalExtension};
 use std::collections::HashMap;
 use uuid::Uuid;
 use walkdir::WalkDir;
@@ -155,12 +162,114 @@ fn attach_authors_and_tags(conn: &rusqlite::Connection, books: &mut [Book]) -> R
     Ok(())
 }
 
-pub fn get_all_books(db: &Database, limit: u32, offset: u32) -> Result<Vec<Book>> {
+/// Fields the library may be sorted by. Sort fields are spliced into SQL, so
+/// anything outside this list is rejected.
+pub const LIBRARY_SORT_FIELDS: [&str; 5] = ["title", "added_date", "pubdate", "rating", "series"];
+
+/// A validated library sort; build with [`LibrarySort::parse`] or
+/// [`resolve_library_sort`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LibrarySort {
+    field: &'static str,
+    descending: bool,
+}
+
+impl Default for LibrarySort {
+    fn default() -> Self {
+        Self {
+            field: "added_date",
+            descending: true,
+        }
+    }
+}
+
+impl LibrarySort {
+    pub fn parse(field: &str, order: &str) -> Result<Self> {
+        let field = LIBRARY_SORT_FIELDS
+            .into_iter()
+            .find(|f| *f == field)
+            .ok_or_else(|| {
+                ShioriError::Validation(format!(
+                    "sort field must be one of {:?}, got '{}'",
+                    LIBRARY_SORT_FIELDS, field
+                ))
+            })?;
+        validate::require_one_of(order, &["asc", "desc"], "sort order")?;
+        Ok(Self {
+            field,
+            descending: order == "desc",
+        })
+    }
+
+    /// `ORDER BY` body for the `books b` alias. Missing values sort last in
+    /// either direction and `b.id` keeps paging stable.
+    fn order_by(&self) -> String {
+        let dir = if self.descending { "DESC" } else { "ASC" };
+        match self.field {
+            "title" => format!(
+                "COALESCE(b.sort_title, b.title) COLLATE NOCASE {}, b.id",
+                dir
+            ),
+            "series" => format!(
+                "b.series IS NULL, b.series COLLATE NOCASE {}, b.series_index, b.id",
+                dir
+            ),
+            field => format!("b.{0} IS NULL, b.{0} {1}, b.id", field, dir),
+        }
+    }
+}
+
+/// Sort from explicit arguments, falling back to the stored
+/// `library_settings` defaults for whichever one is omitted
+pub fn resolve_library_sort(
+    conn: &rusqlite::Connection,
+    sort_by: Option<&str>,
+    sort_order: Option<&str>,
+) -> Result<LibrarySort> {
+    if let (Some(field), Some(order)) = (sort_by, sort_order) {
+        return LibrarySort::parse(field, order);
+    }
+
+    let stored: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT default_sort_field, default_sort_order FROM library_settings WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (stored_field, stored_order) = stored.unwrap_or_default();
+    // A stale stored field falls back to the default instead of failing
+    let stored_sort = match (&stored_field, &stored_order) {
+        (Some(field), Some(order)) => LibrarySort::parse(field, order).unwrap_or_default(),
+        _ => LibrarySort::default(),
+    };
+
+    let field = match sort_by {
+        Some(field) => field,
+        None => stored_sort.field,
+    };
+    let order = match sort_order {
+        Some(order) => order,
+        None if stored_sort.descending => "desc",
+        None => "asc",
+    };
+    LibrarySort::parse(field, order)
+}
+
+pub fn get_all_books(
+    db: &Database,
+    limit: u32,
+    offset: u32,
+    sort_by: Option<&str>,
+    sort_order: Option<&str>,
+) -> Result<Vec<Book>> {
     let conn = db.get_connection()?;
+    let sort = resolve_library_sort(&conn, sort_by, sort_order)?;
 
     let sql = format!(
-        "SELECT {} FROM books b WHERE b.in_trash = 0 ORDER BY b.added_date DESC LIMIT ?1 OFFSET ?2",
-        BOOK_COLUMNS
+        "SELECT {} FROM books b WHERE b.in_trash = 0 ORDER BY {} LIMIT ?1 OFFSET ?2",
+        BOOK_COLUMNS,
+        sort.order_by()
     );
     let mut stmt = conn.prepare(&sql)?;
 
@@ -217,6 +326,126 @@ pub fn get_books_by_ids(db: &Database, ids: &[i64]) -> Result<Vec<Book>> {
     Ok(ordered)
 }
 
+/// Reading order within a series: numbered volumes first, then unnumbered
+/// ones by title
+const SERIES_BOOK_ORDER: &str =
+    "b.series_index IS NULL, b.series_index, COALESCE(b.sort_title, b.title) COLLATE NOCASE";
+
+/// Library entries where each series collapses into one group and every
+/// standalone book is its own group. Groups are ordered by series name or
+/// book title, and `limit`/`offset` page over groups rather than books.
+pub fn get_books_grouped_by_series(
+    db: &Database,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<SeriesGroup>> {
+    let conn = db.get_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT series_name, MIN(id)
+         FROM (
+             SELECT NULLIF(trim(series), '') AS series_name, id,
+                    COALESCE(sort_title, title) AS title_key
+             FROM books
+             WHERE in_trash = 0
+         )
+         GROUP BY series_name, CASE WHEN series_name IS NULL THEN id END
+         ORDER BY MIN(COALESCE(series_name, title_key)) COLLATE NOCASE, MIN(id)
+         LIMIT ?1 OFFSET ?2",
+    )?;
+    let entries: Vec<(Option<String>, i64)> = stmt
+        .query_map(params![limit, offset], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let series_names: Vec<&String> = entries.iter().filter_map(|(s, _)| s.as_ref()).collect();
+    let mut series_book_ids: HashMap<String, Vec<i64>> = HashMap::new();
+    for chunk in series_names.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT b.id, trim(b.series) FROM books b
+             WHERE b.in_trash = 0 AND trim(b.series) IN ({})
+             ORDER BY {}",
+            placeholders, SERIES_BOOK_ORDER
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(chunk.iter()), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        for (id, series) in rows {
+            series_book_ids.entry(series).or_default().push(id);
+        }
+    }
+
+    // One batched fetch for every book on the page, in group order
+    let all_ids: Vec<i64> = entries
+        .iter()
+        .flat_map(|(series, id)| match series {
+            Some(name) => series_book_ids.get(name).cloned().unwrap_or_default(),
+            None => vec![*id],
+        })
+        .collect();
+    let mut books: HashMap<i64, Book> = get_books_by_ids(db, &all_ids)?
+        .into_iter()
+        .filter_map(|b| b.id.map(|id| (id, b)))
+        .collect();
+
+    let groups = entries
+        .into_iter()
+        .map(|(series, id)| {
+            let ids = match &series {
+                Some(name) => series_book_ids.remove(name).unwrap_or_default(),
+                None => vec![id],
+            };
+            SeriesGroup {
+                series,
+                books: ids.iter().filter_map(|id| books.remove(id)).collect(),
+            }
+        })
+        .collect();
+    Ok(groups)
+}
+
+/// Distinct series with their book counts and up to four covers in reading
+/// order, for stacked-cover tiles
+pub fn get_series_list(db: &Database) -> Result<Vec<SeriesSummary>> {
+    const MAX_SERIES_COVERS: usize = 4;
+
+    let conn = db.get_connection()?;
+    let sql = format!(
+        "SELECT trim(b.series), b.cover_path FROM books b
+         WHERE b.in_trash = 0 AND NULLIF(trim(b.series), '') IS NOT NULL
+         ORDER BY trim(b.series) COLLATE NOCASE, trim(b.series), {}",
+        SERIES_BOOK_ORDER
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut series: Vec<SeriesSummary> = Vec::new();
+    for (name, cover_path) in rows {
+        if series.last().map(|s| &s.name) != Some(&name) {
+            series.push(SeriesSummary {
+                name,
+                book_count: 0,
+                cover_paths: Vec::new(),
+            });
+        }
+        let current = series.last_mut().expect("pushed above");
+        current.book_count += 1;
+        if let Some(path) = cover_path {
+            if current.cover_paths.len() < MAX_SERIES_COVERS {
+                current.cover_paths.push(path);
+            }
+        }
+    }
+    Ok(series)
+}
+
 pub fn get_total_books(db: &Database) -> Result<i64> {
     let conn = db.get_connection()?;
     let count: i64 =
@@ -316,24 +545,121 @@ pub fn add_book(db: &Database, mut book: Book) -> Result<i64> {
         }
     }
 
+    // The insert trigger ran before authors/tags existed, so index them now
+    refresh_fts_row(&tx, book_id)?;
+
     tx.commit()?;
     Ok(book_id)
 }
 
-pub fn update_book(db: &Database, book: Book) -> Result<()> {
+/// Rebuild a book's `books_fts` row from its current authors and tags
+pub fn refresh_fts_row(conn: &rusqlite::Connection, book_id: i64) -> Result<()> {
+    refresh_fts_rows(conn, &[book_id])
+}
+
+/// Rebuild the `books_fts` (and `books_fts_cjk`) rows of many books with
+/// one statement per chunk
+pub fn refresh_fts_rows(conn: &rusqlite::Connection, book_ids: &[i64]) -> Result<()> {
+    for chunk in book_ids.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        conn.execute(
+            &format!("DELETE FROM books_fts WHERE rowid IN ({})", placeholders),
+            rusqlite::params_from_iter(chunk.iter()),
+        )?;
+        conn.execute(
+            &format!(
+                "DELETE FROM books_fts_cjk WHERE rowid IN ({})",
+                placeholders
+            ),
+            rusqlite::params_from_iter(chunk.iter()),
+        )?;
+        conn.execute(
+            &crate::db::migrations::cjk_index_insert(&format!(
+                "FROM books b WHERE b.id IN ({})",
+                placeholders
+            )),
+            rusqlite::params_from_iter(chunk.iter()),
+        )?;
+        conn.execute(
+            &crate::db::migrations::fts_index_insert(&format!(
+                "FROM books b WHERE b.id IN ({})",
+                placeholders
+            )),
+            rusqlite::params_from_iter(chunk.iter()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Save edited book metadata. With `write_to_file`, the title, authors,
+/// publisher, language and publication date are also written into the
+/// book's EPUB file (other formats are rejected before anything changes).
+pub fn update_book(db: &Database, book: Book, write_to_file: bool) -> Result<()> {
     let mut conn = db.get_connection()?;
 
     let book_id = book.id.ok_or(ShioriError::Other(
         "Book ID required for update".to_string(),
     ))?;
 
+    let file_path = if write_to_file {
+        let (file_path, file_format): (String, String) = conn
+            .query_row(
+                "SELECT file_path, file_format FROM books WHERE id = ?1",
+                params![book_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| ShioriError::BookNotFound(book_id.to_string()))?;
+        if !file_format.eq_ignore_ascii_case("epub") {
+            return Err(ShioriError::UnsupportedFeature(format!(
+                "Writing metadata to {} files is not supported",
+                file_format
+            )));
+        }
+        Some(file_path)
+    } else {
+        None
+    };
+
+    let tx = conn.transaction()?;
+    write_book_rows(&tx, book_id, &book)?;
+
+    // The file is written before the commit, so a failed write rolls the
+    // row changes back instead of leaving them out of sync with the file
+    if let Some(file_path) = file_path {
+        let update = EpubMetadataUpdate {
+            title: Some(book.title.clone()),
+            authors: Some(book.authors.iter().map(|a| a.name.clone()).collect()),
+            publisher: book.publisher.clone(),
+            language: Some(book.language.clone()),
+            date: book.pubdate.clone(),
+        };
+        epub_adapter::write_metadata(&file_path, &update)?;
+
+        // The rewritten file no longer matches the stored hash and size
+        let file_hash = calculate_file_hash(&file_path)?;
+        let file_size = get_file_size(&file_path)?;
+        tx.execute(
+            "UPDATE books SET file_hash = ?1, file_size = ?2 WHERE id = ?3",
+            params![file_hash, file_size, book_id],
+        )?;
+        tx.execute(
+            "UPDATE book_formats SET file_hash = ?1, file_size = ?2 WHERE file_path = ?3",
+            params![file_hash, file_size, file_path],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Write a book's editable columns, authors and tags within `tx`
+fn write_book_rows(tx: &rusqlite::Transaction, book_id: i64, book: &Book) -> Result<()> {
     let metadata_locked_json = book
         .metadata_locked
         .as_ref()
         .and_then(|locks| serde_json::to_string(locks).ok());
 
-    let tx = conn.transaction()?;
-
     tx.execute(
         "UPDATE books SET
             title = ?1, sort_title = ?2, isbn = ?3, isbn13 = ?4, publisher = ?5,
@@ -363,7 +689,7 @@ pub fn update_book(db: &Database, book: Book) -> Result<()> {
         params![book_id],
     )?;
     for author in &book.authors {
-        let author_id = get_or_create_author_tx(&tx, &author.name)?;
+        let author_id = get_or_create_author_tx(tx, &author.name)?;
         tx.execute(
             "INSERT INTO books_authors (book_id, author_id) VALUES (?1, ?2)",
             params![book_id, author_id],
@@ -383,7 +709,6 @@ pub fn update_book(db: &Database, book: Book) -> Result<()> {
         }
     }
 
-    tx.commit()?;
     Ok(())
 }
 
@@ -594,107 +919,828 @@ pub fn cleanup_database(db: &Database, covers_dir: &std::path::Path) -> Result<(
     Ok((deleted_books as usize, deleted_covers))
 }
 
+/// Books handled per transaction (and per progress report) when rebuilding covers
+const COVER_REGENERATION_BATCH: usize = 50;
+
+/// Rebuild covers for books whose `cover_path` is null or points at a file
+/// that no longer exists. The cover is re-extracted from the book file when
+/// possible, otherwise a geometric cover is generated. `on_progress` gets
+/// `(processed, total)` after every batch. Returns how many books were fixed.
+pub fn regenerate_missing_covers(
+    db: &Database,
+    covers_dir: &std::path::Path,
+    on_progress: impl Fn(usize, usize),
+) -> Result<usize> {
+    let candidates: Vec<(i64, String, String, String, Option<String>)> = {
+        let conn = db.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT b.id, b.uuid, b.file_path, b.title, b.cover_path,
+                    (SELECT a.name FROM books_authors ba
+                     JOIN authors a ON a.id = ba.author_id
+                     WHERE ba.book_id = b.id ORDER BY ba.author_order, ba.rowid LIMIT 1)
+             FROM books b WHERE b.in_trash = 0 ORDER BY b.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?;
+        let mut candidates = Vec::new();
+        for row in rows {
+            let (id, uuid, file_path, title, cover_path, author) = row?;
+            let missing = match cover_path.as_deref() {
+                None => true,
+                // Online covers are fetched by the frontend, never stored locally
+                Some(path) if path.starts_with("http://") || path.starts_with("https://") => false,
+                Some(path) => !std::path::Path::new(path).exists(),
+            };
+            if missing {
+                candidates.push((id, uuid, file_path, title, author));
+            }
+        }
+        candidates
+    };
+
+    let total = candidates.len();
+    if total == 0 {
+        return Ok(0);
+    }
+    std::fs::create_dir_all(covers_dir)?;
+    let generator = crate::services::cover_service::CoverGenerator::new_with_fallback();
+
+    let mut regenerated = 0;
+    let mut processed = 0;
+    for batch in candidates.chunks(COVER_REGENERATION_BATCH) {
+        let covers: Vec<(i64, String)> = batch
+            .par_iter()
+            .filter_map(|(id, uuid, file_path, title, author)| {
+                let extracted = metadata_service::extract_cover(file_path, uuid, covers_dir)
+                    .ok()
+                    .flatten();
+                let cover_path = extracted.or_else(|| {
+                    let metadata = crate::services::format_adapter::BookMetadata {
+                        title: title.clone(),
+                        authors: author.iter().cloned().collect(),
+                        ..Default::default()
+                    };
+                    let cover = generator.create_geometric_cover(&metadata).ok()?;
+                    let path = covers_dir.join(format!("{}.webp", uuid));
+                    match cover.image.save(&path) {
+                        Ok(()) => Some(path.to_string_lossy().to_string()),
+                        Err(e) => {
+                            log::warn!("[covers] Failed to save cover for book {}: {}", id, e);
+                            None
+                        }
+                    }
+                })?;
+                Some((*id, cover_path))
+            })
+            .collect();
+
+        let mut conn = db.get_connection()?;
+        let tx = conn.transaction()?;
+        for (id, cover_path) in &covers {
+            tx.execute(
+                "UPDATE books SET cover_path = ?1 WHERE id = ?2",
+                params![cover_path, id],
+            )?;
+        }
+        tx.commit()?;
+
+        regenerated += covers.len();
+        processed += batch.len();
+        on_progress(processed, total);
+    }
+
+    log::info!(
+        "[covers] Regenerated {} of {} missing covers",
+        regenerated,
+        total
+    );
+    Ok(regenerated)
+}
+
+/// Books checked between progress callbacks in `validate_library`
+const INTEGRITY_SCAN_BATCH: usize = 25;
+
+/// Result of checking one book's file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum IntegrityStatus {
+    Ok,
+    Missing,
+    /// The file's hash no longer matches the one recorded at import
+    HashMismatch,
+    /// The file fails its format's structural validation
+    Corrupt,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BookIntegrity {
+    pub book_id: i64,
+    pub status: IntegrityStatus,
+    /// Why the book was flagged, when there is more to say than the status
+    pub detail: Option<String>,
+}
+
+/// Check every book's file: that it exists, that its hash matches
+/// `file_hash`, and that it passes its format's validation. Formats without
+/// a structural validator are only checked for existence and hash.
+/// `on_progress` gets `(processed, total)` after every batch.
+pub fn validate_library(
+    db: &Database,
+    on_progress: impl Fn(usize, usize),
+) -> Result<Vec<BookIntegrity>> {
+    let books: Vec<(i64, String, String, Option<String>)> = {
+        let conn = db.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_path, file_format, file_hash FROM books
+             WHERE in_trash = 0 ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let total = books.len();
+    let mut report = Vec::with_capacity(total);
+    for batch in books.chunks(INTEGRITY_SCAN_BATCH) {
+        report.par_extend(batch.par_iter().map(|(id, file_path, format, file_hash)| {
+            let (status, detail) = check_book_file(file_path, format, file_hash.as_deref());
+            BookIntegrity {
+                book_id: *id,
+                status,
+                detail,
+            }
+        }));
+        on_progress(report.len(), total);
+    }
+
+    let flagged = report
+        .iter()
+        .filter(|r| r.status != IntegrityStatus::Ok)
+        .count();
+    log::info!(
+        "[integrity] Checked {} books, {} with problems",
+        total,
+        flagged
+    );
+    Ok(report)
+}
+
+fn check_book_file(
+    file_path: &str,
+    format: &str,
+    file_hash: Option<&str>,
+) -> (IntegrityStatus, Option<String>) {
+    let path = std::path::Path::new(file_path);
+    if !path.is_file() {
+        return (IntegrityStatus::Missing, None);
+    }
+
+    if let Some(expected) = file_hash {
+        match calculate_file_hash(file_path) {
+            Ok(actual) if actual == expected => {}
+            Ok(_) => return (IntegrityStatus::HashMismatch, None),
+            Err(e) => return (IntegrityStatus::Corrupt, Some(e.to_string())),
+        }
+    }
+
+    match crate::services::format_detector::validate_file_structure(path, format) {
+        Ok(_) | Err(ShioriError::UnsupportedFormat { .. }) => (IntegrityStatus::Ok, None),
+        Err(e) => (IntegrityStatus::Corrupt, Some(e.to_string())),
+    }
+}
+
+/// Worker threads `import_books` hashes and extracts files on, for a
+/// `performance_mode` preference value
+pub fn import_workers_for_performance_mode(mode: &str) -> usize {
+    match mode {
+        "large_library" => 6,
+        "low_memory" => 2,
+        _ => 4,
+    }
+}
+
+/// Reported by `import_books_with_progress` after each file. The `import_books`
+/// command also counts the importable entries of ZIP bundles in `total`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ImportProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub path: String,
+}
+
 pub fn import_books(
     db: &Database,
     paths: Vec<String>,
     covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+) -> Result<ImportResult> {
+    import_books_with_progress(db, paths, covers_dir, policy, |_| {})
+}
+
+/// Import files in parallel. Hashing, metadata and cover extraction run on a
+/// worker pool sized by `performance_mode`; the database work for each file
+/// happens one file at a time on the calling thread, in completion order, so
+/// duplicate checks see every earlier insert and the workers never hold
+/// pooled connections.
+pub fn import_books_with_progress(
+    db: &Database,
+    paths: Vec<String>,
+    covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+    on_progress: impl Fn(&ImportProgress),
 ) -> Result<ImportResult> {
     let mut result = ImportResult {
         success: vec![],
         failed: vec![],
         duplicates: vec![],
+        merged: vec![],
+        replaced: vec![],
     };
+    let total = paths.len();
+    let mut processed = 0;
 
+    let mut candidates = Vec::with_capacity(paths.len());
     for path in paths {
-        if let Err(e) = validate::require_safe_path(&path, "import path") {
-            result.failed.push((path, e.to_string()));
-            continue;
+        let rejected = validate::require_safe_path(&path, "import path")
+            .and_then(|_| validate_domain(&path, "books"));
+        match rejected {
+            Ok(()) => candidates.push(path),
+            Err(e) => {
+                result.failed.push((path.clone(), e.to_string()));
+                processed += 1;
+                on_progress(&ImportProgress {
+                    processed,
+                    total,
+                    path,
+                });
+            }
         }
+    }
+    if candidates.is_empty() {
+        return Ok(result);
+    }
 
-        if let Err(e) = validate_domain(&path, "books") {
-            result.failed.push((path, e.to_string()));
-            continue;
-        }
+    let workers = import_workers_for_performance_mode(&db.performance_mode());
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .thread_name(|i| format!("import-{}", i))
+        .build()
+        .map_err(|e| ShioriError::Other(format!("Failed to start import workers: {}", e)))?;
+    // Bounded so workers can't run far ahead of the writer with covers in flight
+    let (sender, receiver) = std::sync::mpsc::sync_channel(workers * 2);
+
+    std::thread::scope(|scope| -> Result<()> {
+        scope.spawn(move || {
+            pool.install(|| {
+                candidates
+                    .into_par_iter()
+                    .for_each_with(sender, |sender, path| {
+                        let prepared = prepare_import(&path, covers_dir);
+                        let _ = sender.send((path, prepared));
+                    })
+            })
+        });
 
-        match import_single_book(db, &path, covers_dir) {
-            Ok(is_duplicate) => {
-                if is_duplicate {
-                    result.duplicates.push(path);
-                } else {
-                    let conn = db.get_connection()?;
-                    conn.execute(
-                        "UPDATE books SET domain = 'books' WHERE file_path = ?1",
-                        params![path],
-                    )?;
-                    result.success.push(path);
+        let written = receiver
+            .iter()
+            .try_for_each(|(path, prepared)| -> Result<()> {
+                let outcome =
+                    prepared.and_then(|prepared| store_import(db, prepared, covers_dir, policy));
+                match outcome {
+                    Ok(ImportOutcome::Duplicate { .. }) => result.duplicates.push(path.clone()),
+                    Ok(ImportOutcome::Merged { .. }) => result.merged.push(path.clone()),
+                    Ok(ImportOutcome::Replaced { .. }) => result.replaced.push(path.clone()),
+                    Ok(ImportOutcome::Imported { book_id }) => {
+                        let conn = db.get_connection()?;
+                        conn.execute(
+                            "UPDATE books SET domain = 'books' WHERE id = ?1",
+                            params![book_id],
+                        )?;
+                        result.success.push(path.clone());
+                    }
+                    Err(e) => {
+                        result.failed.push((path.clone(), e.to_string()));
+                    }
+                }
+                processed += 1;
+                on_progress(&ImportProgress {
+                    processed,
+                    total,
+                    path,
+                });
+                Ok(())
+            });
+        if written.is_err() {
+            // The workers finish their files regardless; drop the covers
+            // they extract, since nothing will store those books
+            for (_, prepared) in receiver.iter() {
+                if let Some(cover) = prepared.ok().and_then(|prepared| prepared.cover_path) {
+                    metadata_service::remove_cover(&cover);
                 }
-            }
-            Err(e) => {
-                result.failed.push((path, e.to_string()));
             }
         }
-    }
+        written
+    })?;
 
     Ok(result)
 }
 
-pub fn import_single_book(db: &Database, path: &str, covers_dir: &std::path::Path) -> Result<bool> {
-    // Extract metadata
-    let metadata = metadata_service::extract_from_file(path)?;
-
-    // Calculate file hash
-    let file_hash = calculate_file_hash(path)?;
-
-    // Check for duplicates
-    let conn = db.get_connection()?;
-    let exists: bool = conn.query_row(
-        "SELECT EXISTS(SELECT 1 FROM books WHERE (file_hash != '' AND file_hash = ?1) OR file_path = ?2)",
-        params![file_hash, path],
-        |row| row.get(0),
-    )?;
+/// Import every supported ebook inside a ZIP archive. Entries are extracted into
+/// `storage_dir/archives/<uuid>/` and imported under `policy`, as loose files
+/// are; entries that fail, duplicate an existing book, or belong to another
+/// domain are removed again so only files the library uses are kept. Each
+/// entry is reported as `<zip_path>/<entry name>`.
+pub fn import_archive(
+    db: &Database,
+    zip_path: &str,
+    domain: &str,
+    storage_dir: &std::path::Path,
+    covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+) -> Result<ImportResult> {
+    import_archive_with_progress(
+        db,
+        zip_path,
+        domain,
+        storage_dir,
+        covers_dir,
+        policy,
+        |_| {},
+    )
+}
 
-    if exists {
-        return Ok(true); // Is duplicate
+/// Number of entries `import_archive` will report for a ZIP bundle, so
+/// progress can include them up front. Unreadable archives count as none.
+pub fn count_archive_entries(zip_path: &str) -> usize {
+    let mut archive = match std::fs::File::open(zip_path)
+        .map_err(zip::result::ZipError::from)
+        .and_then(zip::ZipArchive::new)
+    {
+        Ok(archive) => archive,
+        Err(_) => return 0,
+    };
+    let mut count = 0;
+    for i in 0..archive.len() {
+        match archive.by_index(i) {
+            Ok(entry) => {
+                if !entry.is_dir() && importable_entry_name(entry.enclosed_name()).is_some() {
+                    count += 1;
+                }
+            }
+            // Reported as a failed entry
+            Err(_) => count += 1,
+        }
     }
+    count
+}
 
-    // Get file extension
-    let file_format = std::path::Path::new(path)
+/// Bare file name of an archive entry with a supported extension. Flattened
+/// so entries can't escape the extraction dir; hidden files are skipped.
+fn importable_entry_name(enclosed_name: Option<std::path::PathBuf>) -> Option<String> {
+    let name = enclosed_name
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|name| !name.starts_with('.'))?;
+    let ext = std::path::Path::new(&name)
         .extension()
         .and_then(|e| e.to_str())
-        .unwrap_or("unknown")
+        .unwrap_or("")
         .to_lowercase();
+    (BOOK_FORMATS.contains(&ext.as_str()) || MANGA_FORMATS.contains(&ext.as_str())).then_some(name)
+}
 
-    // Generate UUID for the book
-    let book_uuid = Uuid::new_v4().to_string();
+/// `import_archive`, calling `on_entry` with each entry's label once it is
+/// handled, in the order and number given by `count_archive_entries`
+pub fn import_archive_with_progress(
+    db: &Database,
+    zip_path: &str,
+    domain: &str,
+    storage_dir: &std::path::Path,
+    covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+    on_entry: impl Fn(&str),
+) -> Result<ImportResult> {
+    validate::require_safe_path(zip_path, "archive path")?;
 
-    // Extract cover image (if available)
-    let cover_path = metadata_service::extract_cover(path, &book_uuid, covers_dir)
-        .ok()
-        .flatten();
+    let mut result = ImportResult {
+        success: vec![],
+        failed: vec![],
+        duplicates: vec![],
+        merged: vec![],
+        replaced: vec![],
+    };
 
-    // Create book
-    let book = Book {
-        id: None,
-        uuid: book_uuid,
-        title: metadata
-            .title
-            .unwrap_or_else(|| "Unknown Title".to_string()),
-        sort_title: None,
-        isbn: metadata.isbn,
-        isbn13: None,
-        publisher: metadata.publisher,
-        pubdate: metadata.pubdate,
-        series: metadata.series,
-        series_index: metadata.series_index,
-        rating: None,
-        file_path: path.to_string(),
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let extract_dir = storage_dir
+        .join("archives")
+        .join(Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&extract_dir)?;
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                let label = format!("{}#{}", zip_path, i);
+                on_entry(&label);
+                result.failed.push((label, e.to_string()));
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = match importable_entry_name(entry.enclosed_name()) {
+            Some(name) => name,
+            None => continue,
+        };
+        let label = format!("{}/{}", zip_path, name);
+
+        // Keep the original name (it feeds the fallback title) unless it collides
+        let mut target = extract_dir.join(&name);
+        if target.exists() {
+            target = extract_dir.join(format!("{}_{}", i, name));
+        }
+        let target_str = target.to_string_lossy().to_string();
+
+        let outcome = validate_domain(&target_str, domain)
+            .and_then(|_| {
+                std::fs::File::create(&target)
+                    .and_then(|mut out| std::io::copy(&mut entry, &mut out))
+                    .map_err(ShioriError::from)
+            })
+            .and_then(|_| import_single_book_with_policy(db, &target_str, covers_dir, policy));
+        on_entry(&label);
+
+        match outcome {
+            Ok(ImportOutcome::Imported { book_id }) => {
+                let conn = db.get_connection()?;
+                conn.execute(
+                    "UPDATE books SET domain = ?1 WHERE id = ?2",
+                    params![domain, book_id],
+                )?;
+                // Managed mode keeps its own copy, so the extracted file isn't needed
+                let stored_path: String = conn.query_row(
+                    "SELECT file_path FROM books WHERE id = ?1",
+                    params![book_id],
+                    |row| row.get(0),
+                )?;
+                if stored_path != target_str {
+                    let _ = std::fs::remove_file(&target);
+                }
+                result.success.push(label);
+            }
+            Ok(outcome @ (ImportOutcome::Merged { .. } | ImportOutcome::Replaced { .. })) => {
+                // Managed mode attached its own copy of the entry
+                let conn = db.get_connection()?;
+                let in_use: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM books WHERE file_path = ?1)
+                         OR EXISTS(SELECT 1 FROM book_formats WHERE file_path = ?1)",
+                    [&target_str],
+                    |row| row.get(0),
+                )?;
+                if !in_use {
+                    let _ = std::fs::remove_file(&target);
+                }
+                match outcome {
+                    ImportOutcome::Merged { .. } => result.merged.push(label),
+                    _ => result.replaced.push(label),
+                }
+            }
+            Ok(ImportOutcome::Duplicate { .. }) => {
+                let _ = std::fs::remove_file(&target);
+                result.duplicates.push(label);
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&target);
+                result.failed.push((label, e.to_string()));
+            }
+        }
+    }
+
+    // Drop the extraction dir if nothing from the archive was kept
+    if std::fs::read_dir(&extract_dir)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false)
+    {
+        let _ = std::fs::remove_dir(&extract_dir);
+    }
+
+    log::info!(
+        "[import_archive] {}: {} imported, {} merged, {} duplicates, {} failed",
+        zip_path,
+        result.success.len(),
+        result.merged.len(),
+        result.duplicates.len(),
+        result.failed.len()
+    );
+    Ok(result)
+}
+
+/// What `import_single_book` did with a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// A new book was created
+    Imported { book_id: i64 },
+    /// The file (by hash or path), or a file of the same format for the
+    /// matching book, is already in the library
+    Duplicate { book_id: i64 },
+    /// The file matched an existing book and was attached as another format
+    Merged { book_id: i64 },
+    /// The file and its metadata took the place of an existing book's
+    Replaced { book_id: i64 },
+}
+
+/// What an import does with a file that is already in the library
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// Leave the existing book alone and report the file as a duplicate
+    #[default]
+    Skip,
+    /// Attach the file to the existing book as another format
+    AddFormat,
+    /// Point the existing book at the new file and refresh its metadata
+    Replace,
+}
+
+/// How `import_books` treats files that match books already in the library.
+/// Besides exact matches (same hash or path), `AddFormat` and `Replace` also
+/// match on title and first author when `duplicate_detection_mode` finds nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportPolicy {
+    pub on_duplicate: DuplicatePolicy,
+}
+
+pub fn import_single_book(
+    db: &Database,
+    path: &str,
+    covers_dir: &std::path::Path,
+) -> Result<ImportOutcome> {
+    import_single_book_with_policy(db, path, covers_dir, ImportPolicy::default())
+}
+
+pub fn import_single_book_with_policy(
+    db: &Database,
+    path: &str,
+    covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+) -> Result<ImportOutcome> {
+    let prepared = prepare_import(path, covers_dir)?;
+    store_import(db, prepared, covers_dir, policy)
+}
+
+/// Everything `store_import` needs that can be worked out from the file
+/// alone, so it can run off the database thread
+struct PreparedImport {
+    path: String,
+    metadata: crate::models::Metadata,
+    file_hash: String,
+    file_format: String,
+    file_size: i64,
+    uuid: String,
+    cover_path: Option<String>,
+    word_count: Option<i32>,
+    language: String,
+}
+
+/// Hash the file and read its metadata, cover and text statistics
+fn prepare_import(path: &str, covers_dir: &std::path::Path) -> Result<PreparedImport> {
+    let metadata = metadata_service::extract_from_file(path)?;
+    let file_hash = calculate_file_hash(path)?;
+    let file_format = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("unknown")
+        .to_lowercase();
+    let uuid = Uuid::new_v4().to_string();
+    let cover_path = metadata_service::extract_cover(path, &uuid, covers_dir)
+        .ok()
+        .flatten();
+    let text = text_stats(path, &metadata);
+    let language = metadata
+        .language
+        .clone()
+        .unwrap_or_else(|| detect_language(text.sample.as_deref()));
+
+    Ok(PreparedImport {
+        path: path.to_string(),
+        file_size: get_file_size(path)?,
+        word_count: text.word_count,
+        metadata,
+        file_hash,
         file_format,
-        file_size: Some(get_file_size(path)?),
-        file_hash: Some(file_hash),
+        uuid,
         cover_path,
+        language,
+    })
+}
+
+/// Held from the duplicate check until a file is stored. Folder watching,
+/// auto-scan and manual imports run on their own threads, and `file_hash`
+/// isn't UNIQUE, so without it two of them handed the same file could both
+/// find it missing and insert it twice.
+static IMPORT_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn import_lock() -> std::sync::MutexGuard<'static, ()> {
+    IMPORT_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Add a prepared file to the library: skipped if already present, attached
+/// to a matching book as another format, swapped in for a matching book's
+/// file, or inserted as a new book
+fn store_import(
+    db: &Database,
+    prepared: PreparedImport,
+    covers_dir: &std::path::Path,
+    policy: ImportPolicy,
+) -> Result<ImportOutcome> {
+    let _import = import_lock();
+    let PreparedImport {
+        path,
+        metadata,
+        file_hash,
+        file_format,
+        file_size,
+        uuid: book_uuid,
+        cover_path,
+        word_count,
+        language,
+    } = prepared;
+    let path = path.as_str();
+    // The cover was extracted up front; drop it if no new book ends up using it
+    let discard_cover = || {
+        if let Some(cover) = &cover_path {
+            metadata_service::remove_cover(cover);
+        }
+    };
+
+    // Check for duplicates (including files already attached as extra formats)
+    let conn = db.get_connection()?;
+    let exact: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM books WHERE (file_hash != '' AND file_hash = ?1) OR file_path = ?2
+             UNION ALL
+             SELECT book_id FROM book_formats WHERE file_hash = ?1 OR file_path = ?2
+             LIMIT 1",
+            params![file_hash, path],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let isbn = canonical_isbn(metadata.isbn.as_deref(), path);
+
+    let existing = match exact {
+        Some(book_id) if policy.on_duplicate == DuplicatePolicy::Replace => Some(book_id),
+        Some(book_id) => {
+            discard_cover();
+            return Ok(ImportOutcome::Duplicate { book_id });
+        }
+        // Same book in another format? Attach it instead of creating a duplicate entry
+        None => {
+            let by_setting = match duplicate_detection_mode(&conn)?.as_str() {
+                "isbn" => isbn
+                    .as_ref()
+                    .map(|isbn| find_book_by_isbn13(&conn, &isbn.isbn13))
+                    .transpose()?
+                    .flatten(),
+                "title" => metadata
+                    .title
+                    .as_deref()
+                    .map(|title| find_book_by_title_author(&conn, title, metadata.authors.first()))
+                    .transpose()?
+                    .flatten(),
+                _ => None,
+            };
+            match (by_setting, &metadata.title) {
+                (None, Some(title)) if policy.on_duplicate != DuplicatePolicy::Skip => {
+                    find_book_by_title_author(&conn, title, metadata.authors.first())?
+                }
+                (found, _) => found,
+            }
+        }
+    };
+
+    let managed = managed_library_enabled(&conn)?;
+
+    if let (Some(book_id), DuplicatePolicy::Replace) = (existing, policy.on_duplicate) {
+        drop(conn);
+        let replacement = ReplacementFile {
+            path,
+            file_format: &file_format,
+            file_size,
+            file_hash: &file_hash,
+            word_count,
+            language,
+            cover_path: cover_path.clone(),
+        };
+        if let Err(e) = replace_book_file(db, book_id, replacement, metadata, managed, covers_dir) {
+            discard_cover();
+            return Err(e);
+        }
+        log::info!(
+            "[import] Replaced the file of book {} with {}",
+            book_id,
+            path
+        );
+        return Ok(ImportOutcome::Replaced { book_id });
+    }
+
+    if let Some(book_id) = existing {
+        discard_cover();
+        // A book keeps one file per format
+        let has_format: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM book_formats WHERE book_id = ?1 AND format = ?2)
+                 OR EXISTS(SELECT 1 FROM books WHERE id = ?1 AND file_format = ?2)",
+            params![book_id, file_format],
+            |row| row.get(0),
+        )?;
+        if has_format {
+            return Ok(ImportOutcome::Duplicate { book_id });
+        }
+        let stored_path = if managed {
+            let uuid: String = conn.query_row(
+                "SELECT uuid FROM books WHERE id = ?1",
+                params![book_id],
+                |row| row.get(0),
+            )?;
+            copy_into_managed_storage(covers_dir, &uuid, &file_format, path)?
+        } else {
+            path.to_string()
+        };
+        if let Err(e) = attach_book_format(
+            &conn,
+            book_id,
+            &file_format,
+            &stored_path,
+            file_size,
+            &file_hash,
+            metadata.page_count,
+        ) {
+            if managed {
+                let _ = std::fs::remove_file(&stored_path);
+            }
+            return Err(e);
+        }
+        log::info!(
+            "[import] Attached {} to existing book {} as {}",
+            path,
+            book_id,
+            file_format
+        );
+        return Ok(ImportOutcome::Merged { book_id });
+    }
+
+    // Managed libraries keep their own copy so moving the source doesn't break the book
+    let stored_path = if managed {
+        copy_into_managed_storage(covers_dir, &book_uuid, &file_format, path)?
+    } else {
+        path.to_string()
+    };
+
+    let (series, series_index) = match metadata.series {
+        Some(series) => (Some(series), metadata.series_index),
+        None if series_autofill_enabled(&conn)? => match guess_series(&conn, path)? {
+            Some((series, index)) => (Some(series), Some(index)),
+            None => (None, None),
+        },
+        None => (None, None),
+    };
+    drop(conn);
+
+    // Create book
+    let book = Book {
+        id: None,
+        uuid: book_uuid,
+        title: metadata
+            .title
+            .unwrap_or_else(|| "Unknown Title".to_string()),
+        sort_title: None,
+        isbn: isbn.as_ref().and_then(|isbn| isbn.isbn10.clone()),
+        isbn13: isbn.map(|isbn| isbn.isbn13),
+        publisher: metadata.publisher,
+        pubdate: metadata.pubdate,
+        series,
+        series_index,
+        rating: None,
+        file_path: stored_path.clone(),
+        file_format,
+        file_size: Some(file_size),
+        file_hash: Some(file_hash),
+        cover_path: cover_path.clone(),
         page_count: metadata.page_count,
-        word_count: None,
-        language: metadata.language.unwrap_or_else(|| "eng".to_string()),
+        word_count,
+        language,
         added_date: chrono::Utc::now().to_rfc3339(),
         modified_date: chrono::Utc::now().to_rfc3339(),
         last_opened: None,
@@ -723,8 +1769,1295 @@ pub fn import_single_book(db: &Database, path: &str, covers_dir: &std::path::Pat
         metadata_locked: None,
     };
 
-    add_book(db, book)?;
-    Ok(false) // Not a duplicate
+    let book_id = match add_book(db, book) {
+        Ok(id) => id,
+        Err(e) => {
+            if managed {
+                let _ = std::fs::remove_file(&stored_path);
+            }
+            discard_cover();
+            return Err(e);
+        }
+    };
+    // A broken rule must not fail the import itself
+    if let Err(e) = tag_service::apply_tag_rules(db, book_id) {
+        log::warn!(
+            "[Import] Failed to apply tag rules to book {}: {}",
+            book_id,
+            e
+        );
+    }
+    Ok(ImportOutcome::Imported { book_id })
+}
+
+/// The incoming file `replace_book_file` swaps in
+struct ReplacementFile<'a> {
+    path: &'a str,
+    file_format: &'a str,
+    file_size: i64,
+    file_hash: &'a str,
+    word_count: Option<i32>,
+    language: String,
+    cover_path: Option<String>,
+}
+
+/// Point `book_id` at a new file: its primary format row and file columns
+/// follow the file, and metadata the file declares overwrites fields the
+/// user hasn't locked. Reading progress, annotations and tags are kept, as
+/// is a locked or custom cover. Metadata and file change together or not at
+/// all; a managed copy is only moved into place once the swap is committed.
+fn replace_book_file(
+    db: &Database,
+    book_id: i64,
+    file: ReplacementFile,
+    metadata: crate::models::Metadata,
+    managed: bool,
+    covers_dir: &std::path::Path,
+) -> Result<()> {
+    let mut book = get_book_by_id(db, book_id)?;
+    let is_locked = |field: &str| {
+        book.metadata_locked
+            .as_ref()
+            .and_then(|locks| locks.get(field))
+            .copied()
+            .unwrap_or(false)
+    };
+
+    let title = metadata
+        .title
+        .filter(|t| !t.trim().is_empty() && !is_locked("title"));
+    let authors = Some(metadata.authors).filter(|a| !a.is_empty() && !is_locked("authors"));
+    let publisher = metadata.publisher.filter(|_| !is_locked("publisher"));
+    let pubdate = metadata.pubdate.filter(|_| !is_locked("publish_date"));
+    let isbn = canonical_isbn(metadata.isbn.as_deref(), file.path).filter(|_| !is_locked("isbn"));
+    let series = metadata.series.filter(|_| !is_locked("series"));
+    let language = Some(file.language.clone()).filter(|_| !is_locked("language"));
+    let keep_cover = is_locked("cover") || has_custom_cover(db, book_id)?;
+    let page_count = metadata.page_count;
+
+    if let Some(title) = title {
+        book.title = title;
+    }
+    if let Some(authors) = authors {
+        book.authors = authors
+            .into_iter()
+            .map(|name| Author {
+                id: None,
+                name,
+                sort_name: None,
+                link: None,
+            })
+            .collect();
+    }
+    if publisher.is_some() {
+        book.publisher = publisher;
+    }
+    if pubdate.is_some() {
+        book.pubdate = pubdate;
+    }
+    if let Some(isbn) = isbn {
+        book.isbn = isbn.isbn10;
+        book.isbn13 = Some(isbn.isbn13);
+    }
+    if series.is_some() {
+        book.series = series;
+        book.series_index = metadata.series_index;
+    }
+    if let Some(language) = language {
+        book.language = language;
+    }
+    let old_path = book.file_path.clone();
+    let old_cover = book.cover_path.clone();
+    let new_cover = if keep_cover {
+        old_cover.clone()
+    } else {
+        file.cover_path.clone().or_else(|| old_cover.clone())
+    };
+
+    // The managed copy may share its name with the current file, so it is
+    // staged beside it and renamed once the database points at it
+    let (stored_path, staged_path) = if managed {
+        let staged = copy_into_managed_storage(
+            covers_dir,
+            &book.uuid,
+            &format!("{}.part", file.file_format),
+            file.path,
+        )?;
+        (staged.trim_end_matches(".part").to_string(), Some(staged))
+    } else {
+        (file.path.to_string(), None)
+    };
+
+    let swapped = swap_book_file(
+        db,
+        &book,
+        &file,
+        &stored_path,
+        page_count,
+        new_cover.as_deref(),
+    );
+    let dropped_paths = match swapped {
+        Ok(dropped_paths) => dropped_paths,
+        Err(e) => {
+            if let Some(staged) = &staged_path {
+                let _ = std::fs::remove_file(staged);
+            }
+            return Err(e);
+        }
+    };
+    if let Some(staged) = &staged_path {
+        std::fs::rename(staged, &stored_path)?;
+    }
+
+    // Managed copies of the old files and the unused cover are no longer referenced
+    let managed_dir = managed_books_dir(covers_dir);
+    for path in std::iter::once(&old_path).chain(&dropped_paths) {
+        if managed && path != &stored_path && std::path::Path::new(path).starts_with(&managed_dir) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    for cover in [old_cover, file.cover_path].into_iter().flatten() {
+        if Some(&cover) != new_cover.as_ref() {
+            metadata_service::remove_cover(&cover);
+        }
+    }
+    Ok(())
+}
+
+/// Whether a book's cover was set by hand (custom covers are the only ones
+/// recorded in `cover_cache`)
+fn has_custom_cover(db: &Database, book_id: i64) -> Result<bool> {
+    let conn = db.get_connection()?;
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM cover_cache WHERE book_id = ?1)",
+        [book_id],
+        |row| row.get(0),
+    )?)
+}
+
+/// Write `book`'s metadata and point it at `file` in one transaction.
+/// Returns the paths of other files of the new format that were dropped.
+fn swap_book_file(
+    db: &Database,
+    book: &Book,
+    file: &ReplacementFile,
+    stored_path: &str,
+    page_count: Option<i32>,
+    cover_path: Option<&str>,
+) -> Result<Vec<String>> {
+    let book_id = book
+        .id
+        .ok_or_else(|| ShioriError::Other("Book ID required for update".to_string()))?;
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    ensure_primary_format_row(&tx, book_id, book_id, true)?;
+    write_book_rows(&tx, book_id, book)?;
+
+    // The old primary row becomes the new file; a second file of the new
+    // format would otherwise be left behind
+    let dropped_paths = tx
+        .prepare(
+            "SELECT file_path FROM book_formats WHERE book_id = ?1 AND format = ?2 AND is_primary = 0",
+        )?
+        .query_map(params![book_id, file.file_format], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    tx.execute(
+        "DELETE FROM book_formats WHERE book_id = ?1 AND format = ?2 AND is_primary = 0",
+        params![book_id, file.file_format],
+    )?;
+    tx.execute(
+        "UPDATE book_formats SET format = ?2, file_path = ?3, file_size = ?4, file_hash = ?5,
+             page_count = ?6, word_count = ?7
+         WHERE book_id = ?1 AND is_primary = 1",
+        params![
+            book_id,
+            file.file_format,
+            stored_path,
+            file.file_size,
+            file.file_hash,
+            page_count,
+            file.word_count
+        ],
+    )?;
+    tx.execute(
+        "UPDATE books SET file_path = ?2, file_format = ?3, file_size = ?4, file_hash = ?5,
+             page_count = ?6, word_count = ?7, cover_path = ?8,
+             modified_date = CURRENT_TIMESTAMP
+         WHERE id = ?1",
+        params![
+            book_id,
+            stored_path,
+            file.file_format,
+            file.file_size,
+            file.file_hash,
+            page_count,
+            file.word_count,
+            cover_path
+        ],
+    )?;
+    refresh_fts_row(&tx, book_id)?;
+    tx.commit()?;
+    Ok(dropped_paths)
+}
+
+/// Word count, plus a text sample when the metadata doesn't declare a language
+fn text_stats(path: &str, metadata: &crate::models::Metadata) -> metadata_service::TextStats {
+    let sample_bytes = if metadata.language.is_some() {
+        0
+    } else {
+        lang_detect::SAMPLE_BYTES
+    };
+    metadata_service::text_stats(path, sample_bytes)
+}
+
+/// Language of a book that doesn't declare one, guessed from its opening text
+fn detect_language(sample: Option<&str>) -> String {
+    sample
+        .map(lang_detect::detect_or_default)
+        .unwrap_or_else(|| lang_detect::DEFAULT_LANGUAGE.to_string())
+}
+
+/// "Series #3", "Series Book 3", "Series, Vol. 3", optionally followed by " - Title"
+static SERIES_MARKED_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^(.*?[[:alpha:]].*?)[\s,]*(?:#|\b(?:book|vol\.?|volume)\s+#?)(\d{1,3}(?:\.\d+)?)(?:\s*[-–:]\s*.*)?$",
+    )
+    .unwrap()
+});
+
+/// "Series 3 - Title" or a bare "Series 3"
+static SERIES_BARE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.*?[[:alpha:]].*?)\s+(\d{1,3}(?:\.\d+)?)(?:\s*[-–:]\s*.*)?$").unwrap()
+});
+
+/// Series name and index read off a filename
+#[derive(Debug, Clone, PartialEq)]
+struct SeriesGuess {
+    name: String,
+    index: f64,
+    /// Named with "#" or "Book"; a bare number ("Catch 22") could be part of the title
+    marked: bool,
+}
+
+fn parse_series_filename(stem: &str) -> Option<SeriesGuess> {
+    let stem = stem.replace('_', " ");
+    let stem = stem.trim();
+    let (captures, marked) = match SERIES_MARKED_RE.captures(stem) {
+        Some(captures) => (captures, true),
+        None => (SERIES_BARE_RE.captures(stem)?, false),
+    };
+    let name = captures[1]
+        .trim()
+        .trim_end_matches([',', '-', '(', '['])
+        .trim()
+        .to_string();
+    let index: f64 = captures[2].parse().ok()?;
+    if name.is_empty() || index <= 0.0 {
+        return None;
+    }
+    Some(SeriesGuess {
+        name,
+        index,
+        marked,
+    })
+}
+
+/// Series for a file that doesn't declare one, from its name. A bare number
+/// is only trusted when the library already has that series, or a book
+/// titled after it; an existing series' spelling wins.
+fn guess_series(conn: &rusqlite::Connection, path: &str) -> Result<Option<(String, f64)>> {
+    let Some(guess) = std::path::Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(parse_series_filename)
+    else {
+        return Ok(None);
+    };
+    let known: Option<String> = conn
+        .query_row(
+            "SELECT series FROM books WHERE series = ?1 COLLATE NOCASE LIMIT 1",
+            [&guess.name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(series) = known {
+        return Ok(Some((series, guess.index)));
+    }
+    let titled: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM books WHERE title = ?1 COLLATE NOCASE",
+        [&guess.name],
+        |row| row.get(0),
+    )?;
+    Ok((guess.marked || titled).then_some((guess.name, guess.index)))
+}
+
+/// Whether `library_settings.series_autofill` is switched on
+fn series_autofill_enabled(conn: &rusqlite::Connection) -> Result<bool> {
+    match conn.query_row(
+        "SELECT series_autofill FROM library_settings WHERE id = 1",
+        [],
+        |row| row.get::<_, Option<bool>>(0),
+    ) {
+        Ok(enabled) => Ok(enabled.unwrap_or(false)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn set_series_autofill(db: &Database, enabled: bool) -> Result<()> {
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE library_settings SET series_autofill = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        params![enabled],
+    )?;
+    Ok(())
+}
+
+pub fn get_series_autofill(db: &Database) -> Result<bool> {
+    let conn = db.get_connection()?;
+    series_autofill_enabled(&conn)
+}
+
+/// Whether `library_settings.managed_library` is switched on
+fn managed_library_enabled(conn: &rusqlite::Connection) -> Result<bool> {
+    match conn.query_row(
+        "SELECT managed_library FROM library_settings WHERE id = 1",
+        [],
+        |row| row.get::<_, Option<bool>>(0),
+    ) {
+        Ok(enabled) => Ok(enabled.unwrap_or(false)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn set_managed_library(db: &Database, enabled: bool) -> Result<()> {
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE library_settings SET managed_library = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        params![enabled],
+    )?;
+    Ok(())
+}
+
+pub fn get_managed_library(db: &Database) -> Result<bool> {
+    let conn = db.get_connection()?;
+    managed_library_enabled(&conn)
+}
+
+/// Managed book files live in `storage/books`, next to the covers dir in app data
+fn managed_books_dir(covers_dir: &std::path::Path) -> std::path::PathBuf {
+    covers_dir
+        .parent()
+        .unwrap_or(covers_dir)
+        .join("storage")
+        .join("books")
+}
+
+fn copy_into_managed_storage(
+    covers_dir: &std::path::Path,
+    uuid: &str,
+    format: &str,
+    source: &str,
+) -> Result<String> {
+    let dir = managed_books_dir(covers_dir);
+    std::fs::create_dir_all(&dir)?;
+    let target = dir.join(format!("{}.{}", uuid, format));
+    std::fs::copy(source, &target)?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Books whose files went missing and what `relocate_missing_files` did about them
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationReport {
+    pub checked: usize,
+    /// (book_id, new file path)
+    pub relocated: Vec<(i64, String)>,
+    pub still_missing: Vec<i64>,
+}
+
+/// Find books whose `file_path` no longer exists and point them at the file's
+/// new location. Candidates are the managed copy (`storage/books/<uuid>.<ext>`)
+/// and any file with the same name under `search_dirs`; a candidate is only
+/// accepted when its hash matches the stored `file_hash`.
+pub fn relocate_missing_files(
+    db: &Database,
+    covers_dir: &std::path::Path,
+    search_dirs: &[String],
+) -> Result<RelocationReport> {
+    let conn = db.get_connection()?;
+    let mut report = RelocationReport::default();
+
+    let missing: Vec<(i64, String, String, String, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, uuid, file_path, file_format, file_hash FROM books WHERE in_trash = 0",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|(_, _, path, _, _)| !std::path::Path::new(path).exists())
+            .collect()
+    };
+    report.checked = missing.len();
+    if missing.is_empty() {
+        return Ok(report);
+    }
+
+    // Index the search dirs by file name once, only when something is missing
+    let mut by_name: HashMap<String, Vec<std::path::PathBuf>> = HashMap::new();
+    for dir in search_dirs {
+        validate::require_safe_path(dir, "search dir")?;
+        for entry in WalkDir::new(dir)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            by_name
+                .entry(entry.file_name().to_string_lossy().to_string())
+                .or_default()
+                .push(entry.into_path());
+        }
+    }
+
+    for (id, uuid, old_path, format, file_hash) in missing {
+        let mut candidates =
+            vec![managed_books_dir(covers_dir).join(format!("{}.{}", uuid, format))];
+        if let Some(name) = std::path::Path::new(&old_path).file_name() {
+            if let Some(found) = by_name.get(&*name.to_string_lossy()) {
+                candidates.extend(found.iter().cloned());
+            }
+        }
+
+        let found = candidates.into_iter().find(|candidate| {
+            let candidate = candidate.to_string_lossy();
+            match file_hash.as_deref() {
+                Some(expected) if !expected.is_empty() => calculate_file_hash(&candidate)
+                    .map(|hash| hash == expected)
+                    .unwrap_or(false),
+                _ => std::path::Path::new(&*candidate).is_file(),
+            }
+        });
+
+        match found {
+            Some(new_path) => {
+                let new_path = new_path.to_string_lossy().to_string();
+                conn.execute(
+                    "UPDATE books SET file_path = ?1 WHERE id = ?2",
+                    params![new_path, id],
+                )?;
+                log::info!("[relocate] Book {} moved: {} -> {}", id, old_path, new_path);
+                report.relocated.push((id, new_path));
+            }
+            None => report.still_missing.push(id),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Current `library_settings.duplicate_detection_mode` ('hash' when unset)
+fn duplicate_detection_mode(conn: &rusqlite::Connection) -> Result<String> {
+    match conn.query_row(
+        "SELECT duplicate_detection_mode FROM library_settings WHERE id = 1",
+        [],
+        |row| row.get::<_, Option<String>>(0),
+    ) {
+        Ok(mode) => Ok(mode.unwrap_or_else(|| "hash".to_string())),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok("hash".to_string()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Normalize an ISBN-10 or ISBN-13 to its ISBN-13 digits, rejecting bad
+/// check digits
+pub fn normalize_isbn13(raw: &str) -> Option<String> {
+    isbn::normalize(raw).map(|normalized| normalized.isbn13)
+}
+
+/// Validated ISBN from extracted metadata. An embedded ISBN with a bad
+/// check digit is dropped rather than stored.
+fn canonical_isbn(raw: Option<&str>, path: &str) -> Option<isbn::NormalizedIsbn> {
+    let raw = raw?;
+    let normalized = isbn::normalize(raw);
+    if normalized.is_none() {
+        log::warn!("[import] Ignoring invalid ISBN '{}' in {}", raw, path);
+    }
+    normalized
+}
+
+fn find_book_by_isbn13(conn: &rusqlite::Connection, isbn13: &str) -> Result<Option<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, isbn, isbn13 FROM books
+         WHERE in_trash = 0 AND (isbn IS NOT NULL OR isbn13 IS NOT NULL)",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+        ))
+    })?;
+
+    for row in rows {
+        let (id, isbn, isbn13_col) = row?;
+        let matches = [isbn, isbn13_col]
+            .iter()
+            .flatten()
+            .any(|candidate| normalize_isbn13(candidate).as_deref() == Some(isbn13));
+        if matches {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Case-insensitive title + first-author match against existing books
+fn find_book_by_title_author(
+    conn: &rusqlite::Connection,
+    title: &str,
+    first_author: Option<&String>,
+) -> Result<Option<i64>> {
+    let title = title.trim();
+    if title.is_empty() || title.eq_ignore_ascii_case("Unknown Title") {
+        return Ok(None);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT b.id,
+                (SELECT a.name FROM books_authors ba
+                 JOIN authors a ON a.id = ba.author_id
+                 WHERE ba.book_id = b.id ORDER BY ba.author_order, ba.rowid LIMIT 1)
+         FROM books b
+         WHERE b.in_trash = 0 AND LOWER(TRIM(b.title)) = LOWER(?1)
+         ORDER BY b.id",
+    )?;
+    let rows = stmt.query_map(params![title], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+
+    let wanted = first_author.map(|a| a.trim().to_lowercase());
+    for row in rows {
+        let (id, author) = row?;
+        if author.map(|a| a.trim().to_lowercase()) == wanted {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Record `source_id`'s own file as a format row of `target_id`, unless
+/// `source_id` already tracks its formats in `book_formats`. A file that
+/// can't be recorded (a format outside the table's CHECK list, no hash, or
+/// the hash of another book's file) is an error rather than left untracked;
+/// a file identical to one `target_id` already has needs no row of its own.
+fn ensure_primary_format_row(
+    conn: &rusqlite::Connection,
+    target_id: i64,
+    source_id: i64,
+    is_primary: bool,
+) -> Result<()> {
+    let tracked: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM book_formats WHERE book_id = ?1)",
+        [source_id],
+        |row| row.get(0),
+    )?;
+    if tracked {
+        return Ok(());
+    }
+
+    let (format, path, hash): (String, String, Option<String>) = conn
+        .query_row(
+            "SELECT LOWER(file_format), file_path, file_hash FROM books WHERE id = ?1",
+            [source_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| ShioriError::BookNotFound(source_id.to_string()))?;
+    if !BOOK_FORMAT_TYPES.contains(&format.as_str()) {
+        return Err(ShioriError::InvalidFormat(format!(
+            "Book {} is a {} file, which can't be stored as a book format",
+            source_id, format
+        )));
+    }
+    let hash = hash.filter(|h| !h.is_empty()).ok_or_else(|| {
+        ShioriError::Validation(format!("Book {} has no file hash for {}", source_id, path))
+    })?;
+
+    let owner: Option<i64> = conn
+        .query_row(
+            "SELECT book_id FROM book_formats WHERE file_hash = ?1",
+            [&hash],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match owner {
+        Some(owner) if owner == target_id => return Ok(()),
+        Some(owner) => {
+            return Err(ShioriError::DuplicateBook(format!(
+                "{} is the same file as one of book {}",
+                path, owner
+            )))
+        }
+        None => {}
+    }
+
+    conn.execute(
+        "INSERT INTO book_formats (book_id, format, file_path, file_size, file_hash, page_count, word_count, is_primary)
+         SELECT ?1, ?3, file_path, COALESCE(file_size, 0), file_hash, page_count, word_count, ?4
+         FROM books WHERE id = ?2",
+        params![target_id, source_id, format, is_primary],
+    )?;
+    Ok(())
+}
+
+/// Record a file as an additional format of an existing book, making sure the
+/// book's original file is present as the primary format row
+fn attach_book_format(
+    conn: &rusqlite::Connection,
+    book_id: i64,
+    format: &str,
+    path: &str,
+    file_size: i64,
+    file_hash: &str,
+    page_count: Option<i32>,
+) -> Result<()> {
+    ensure_primary_format_row(conn, book_id, book_id, true)?;
+    conn.execute(
+        "INSERT INTO book_formats (book_id, format, file_path, file_size, file_hash, page_count, is_primary)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+        params![book_id, format, path, file_size, file_hash, page_count],
+    )?;
+    Ok(())
+}
+
+/// Formats `book_formats` accepts (its CHECK constraint)
+const BOOK_FORMAT_TYPES: [&str; 11] = [
+    "epub", "pdf", "mobi", "azw3", "fb2", "docx", "txt", "html", "cbz", "cbr", "djvu",
+];
+
+/// One file of a book, from `book_formats`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BookFormat {
+    pub id: i64,
+    pub book_id: i64,
+    pub format: String,
+    pub file_path: String,
+    pub file_size: i64,
+    pub file_hash: String,
+    pub page_count: Option<i32>,
+    pub is_primary: bool,
+    pub added_at: String,
+}
+
+const BOOK_FORMAT_COLUMNS: &str =
+    "id, book_id, format, file_path, file_size, file_hash, page_count, is_primary, added_at";
+
+fn book_format_from_row(row: &rusqlite::Row) -> rusqlite::Result<BookFormat> {
+    Ok(BookFormat {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        format: row.get(2)?,
+        file_path: row.get(3)?,
+        file_size: row.get(4)?,
+        file_hash: row.get(5)?,
+        page_count: row.get(6)?,
+        is_primary: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
+        added_at: row.get(8)?,
+    })
+}
+
+fn require_book(conn: &rusqlite::Connection, book_id: i64) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM books WHERE id = ?1",
+        [book_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(ShioriError::BookNotFound(book_id.to_string()));
+    }
+    Ok(())
+}
+
+/// Every file of a book, primary first. Books imported as a single file
+/// get their format row recorded on first call.
+pub fn get_book_formats(db: &Database, book_id: i64) -> Result<Vec<BookFormat>> {
+    let conn = db.get_connection()?;
+    require_book(&conn, book_id)?;
+    // Listing still works for books whose file has no format row yet
+    if let Err(e) = ensure_primary_format_row(&conn, book_id, book_id, true) {
+        log::warn!("[get_book_formats] Book {}: {}", book_id, e);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM book_formats WHERE book_id = ?1 ORDER BY is_primary DESC, id",
+        BOOK_FORMAT_COLUMNS
+    ))?;
+    let formats = stmt
+        .query_map([book_id], book_format_from_row)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(formats)
+}
+
+/// Attach the file at `path` to a book as another format. The format comes
+/// from the extension; a book holds at most one file per format, and a file
+/// already in the library (by hash) is rejected. Managed libraries store a
+/// copy, as on import.
+pub fn add_book_format(
+    db: &Database,
+    book_id: i64,
+    path: &str,
+    covers_dir: &std::path::Path,
+) -> Result<BookFormat> {
+    let format = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .filter(|e| BOOK_FORMAT_TYPES.contains(&e.as_str()))
+        .ok_or_else(|| ShioriError::InvalidFormat(format!("Unsupported book format: {}", path)))?;
+    if !std::path::Path::new(path).is_file() {
+        return Err(ShioriError::FileNotFound {
+            path: path.to_string(),
+        });
+    }
+    let file_hash = calculate_file_hash(path)?;
+    let file_size = get_file_size(path)?;
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    require_book(&tx, book_id)?;
+    ensure_primary_format_row(&tx, book_id, book_id, true)?;
+
+    let duplicate: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM book_formats WHERE file_hash = ?1)
+             OR EXISTS(SELECT 1 FROM books WHERE file_hash != '' AND file_hash = ?1)",
+        [&file_hash],
+        |row| row.get(0),
+    )?;
+    if duplicate {
+        return Err(ShioriError::DuplicateBook(format!(
+            "{} is already in the library",
+            path
+        )));
+    }
+    let has_format: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM book_formats WHERE book_id = ?1 AND format = ?2",
+        params![book_id, format],
+        |row| row.get(0),
+    )?;
+    if has_format {
+        return Err(ShioriError::Validation(format!(
+            "Book {} already has a {} file",
+            book_id, format
+        )));
+    }
+
+    let managed = managed_library_enabled(&tx)?;
+    let stored_path = if managed {
+        let uuid: String =
+            tx.query_row("SELECT uuid FROM books WHERE id = ?1", [book_id], |row| {
+                row.get(0)
+            })?;
+        copy_into_managed_storage(covers_dir, &uuid, &format, path)?
+    } else {
+        path.to_string()
+    };
+    let page_count = metadata_service::extract_from_file(path)
+        .ok()
+        .and_then(|m| m.page_count);
+    let added = attach_book_format(
+        &tx,
+        book_id,
+        &format,
+        &stored_path,
+        file_size,
+        &file_hash,
+        page_count,
+    )
+    .and_then(|()| {
+        let added = tx.query_row(
+            &format!(
+                "SELECT {} FROM book_formats WHERE id = last_insert_rowid()",
+                BOOK_FORMAT_COLUMNS
+            ),
+            [],
+            book_format_from_row,
+        )?;
+        tx.commit()?;
+        Ok(added)
+    });
+    if added.is_err() && managed {
+        let _ = std::fs::remove_file(&stored_path);
+    }
+    added
+}
+
+/// Make a book's `format` file the one it opens with. The book row's file
+/// columns follow the primary format.
+pub fn set_primary_format(db: &Database, book_id: i64, format: &str) -> Result<()> {
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    require_book(&tx, book_id)?;
+    ensure_primary_format_row(&tx, book_id, book_id, true)?;
+
+    let format = format.trim().to_lowercase();
+    let format_id: i64 = tx
+        .query_row(
+            "SELECT id FROM book_formats WHERE book_id = ?1 AND format = ?2",
+            params![book_id, format],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            ShioriError::Validation(format!("Book {} has no {} file", book_id, format))
+        })?;
+    // The book's current file must stay reachable once its columns move on
+    let current_tracked: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM book_formats bf JOIN books b ON b.id = bf.book_id
+                       WHERE bf.book_id = ?1 AND bf.file_path = b.file_path)",
+        [book_id],
+        |row| row.get(0),
+    )?;
+    if !current_tracked {
+        return Err(ShioriError::InvalidOperation(format!(
+            "Book {}'s current file is not recorded as one of its formats",
+            book_id
+        )));
+    }
+
+    tx.execute(
+        "UPDATE book_formats SET is_primary = (id = ?1) WHERE book_id = ?2",
+        params![format_id, book_id],
+    )?;
+    tx.execute(
+        "UPDATE books SET
+            (file_path, file_format, file_size, file_hash, page_count) =
+                (SELECT file_path, format, file_size, file_hash, page_count
+                 FROM book_formats WHERE id = ?1),
+            modified_date = CURRENT_TIMESTAMP
+         WHERE id = ?2",
+        params![format_id, book_id],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Forget one file of a book. The primary format can't be removed; make
+/// another format primary first. The file itself stays on disk.
+pub fn delete_book_format(db: &Database, format_id: i64) -> Result<()> {
+    let conn = db.get_connection()?;
+    let is_primary: bool = conn
+        .query_row(
+            "SELECT COALESCE(is_primary, 0) FROM book_formats WHERE id = ?1",
+            [format_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| ShioriError::Validation(format!("No book format with id {}", format_id)))?;
+    if is_primary {
+        return Err(ShioriError::InvalidOperation(
+            "Cannot remove a book's primary format".to_string(),
+        ));
+    }
+    conn.execute("DELETE FROM book_formats WHERE id = ?1", [format_id])?;
+    Ok(())
+}
+
+/// Path of a book's file in `format`, if it has one
+pub fn get_book_format_path(db: &Database, book_id: i64, format: &str) -> Result<String> {
+    let conn = db.get_connection()?;
+    require_book(&conn, book_id)?;
+    conn.query_row(
+        "SELECT file_path FROM book_formats WHERE book_id = ?1 AND format = ?2",
+        params![book_id, format.trim().to_lowercase()],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| ShioriError::Validation(format!("Book {} has no {} file", book_id, format)))
+}
+
+/// How [`find_duplicates`] decides two books are the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateMode {
+    /// Identical file hash
+    Hash,
+    /// Same ISBN-13, ignoring hyphens and spaces
+    Isbn,
+    /// Same title and first author after lowercasing and dropping punctuation
+    TitleAuthor,
+}
+
+impl DuplicateMode {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "hash" => Ok(Self::Hash),
+            "isbn" => Ok(Self::Isbn),
+            "title_author" => Ok(Self::TitleAuthor),
+            other => Err(ShioriError::Validation(format!(
+                "Duplicate mode must be one of hash, isbn, title_author, got '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Lowercase alphanumeric words, so "The Hobbit!" and "the  hobbit" match
+fn normalize_match_key(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Groups of non-trashed books sharing a hash, ISBN-13 or title + author.
+/// Groups follow the lowest book id in each, and ids within a group ascend.
+pub fn find_duplicates(db: &Database, mode: DuplicateMode) -> Result<Vec<DuplicateGroup>> {
+    let conn = db.get_connection()?;
+    let sql = match mode {
+        DuplicateMode::Hash => {
+            "SELECT b.id, b.file_hash, NULL FROM books b WHERE b.in_trash = 0 ORDER BY b.id"
+        }
+        DuplicateMode::Isbn => {
+            "SELECT b.id, COALESCE(b.isbn13, b.isbn), NULL FROM books b
+             WHERE b.in_trash = 0 ORDER BY b.id"
+        }
+        DuplicateMode::TitleAuthor => {
+            "SELECT b.id, b.title,
+                    (SELECT a.name FROM books_authors ba
+                     JOIN authors a ON a.id = ba.author_id
+                     WHERE ba.book_id = b.id ORDER BY ba.author_order, ba.rowid LIMIT 1)
+             FROM books b WHERE b.in_trash = 0 ORDER BY b.id"
+        }
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<i64>> = HashMap::new();
+    for (id, value, author) in rows {
+        let key = match mode {
+            DuplicateMode::Hash => value.unwrap_or_default(),
+            DuplicateMode::Isbn => value
+                .as_deref()
+                .and_then(normalize_isbn13)
+                .unwrap_or_default(),
+            DuplicateMode::TitleAuthor => {
+                let title = normalize_match_key(&value.unwrap_or_default());
+                if title.is_empty() {
+                    continue;
+                }
+                format!(
+                    "{}\u{1f}{}",
+                    title,
+                    normalize_match_key(&author.unwrap_or_default())
+                )
+            }
+        };
+        if key.is_empty() {
+            continue;
+        }
+        let ids = groups.entry(key.clone()).or_default();
+        if ids.is_empty() {
+            order.push(key);
+        }
+        ids.push(id);
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|key| {
+            let book_ids = groups.remove(&key)?;
+            if book_ids.len() < 2 {
+                return None;
+            }
+            let reason = match mode {
+                DuplicateMode::Hash => "Identical file".to_string(),
+                DuplicateMode::Isbn => format!("Same ISBN-13 ({})", key),
+                DuplicateMode::TitleAuthor => "Same title and author".to_string(),
+            };
+            Some(DuplicateGroup { reason, book_ids })
+        })
+        .collect())
+}
+
+/// Fold `merge_ids` into `keep_id` in one transaction: their files become
+/// extra formats of the kept book, annotations, sessions, tags, collection
+/// memberships, doodles and per-book preference overrides move over, the
+/// most recent reading progress wins, and the merged book rows are deleted.
+/// Fails without changing anything if a merged book's file can't be kept.
+/// Returns the number of books merged.
+pub fn merge_books(db: &Database, keep_id: i64, merge_ids: &[i64]) -> Result<usize> {
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+
+    let book_exists = |id: i64| -> Result<bool> {
+        Ok(tx.query_row(
+            "SELECT COUNT(*) > 0 FROM books WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?)
+    };
+    if !book_exists(keep_id)? {
+        return Err(ShioriError::BookNotFound(keep_id.to_string()));
+    }
+
+    let mut merge_ids: Vec<i64> = merge_ids
+        .iter()
+        .copied()
+        .filter(|id| *id != keep_id)
+        .collect();
+    merge_ids.sort_unstable();
+    merge_ids.dedup();
+    for id in &merge_ids {
+        if !book_exists(*id)? {
+            return Err(ShioriError::BookNotFound(id.to_string()));
+        }
+    }
+
+    ensure_primary_format_row(&tx, keep_id, keep_id, true)?;
+    // Move every tracked file first, so a merged book whose own file is one
+    // of them is recognized as already recorded below
+    let mut untracked = Vec::new();
+    for merge_id in &merge_ids {
+        let moved = tx.execute(
+            "UPDATE book_formats SET book_id = ?1, is_primary = 0 WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        if moved == 0 {
+            untracked.push(*merge_id);
+        }
+    }
+    for merge_id in &merge_ids {
+        if untracked.contains(merge_id) {
+            ensure_primary_format_row(&tx, keep_id, *merge_id, false)?;
+        }
+        tx.execute(
+            "UPDATE annotations SET book_id = ?1 WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "UPDATE reading_sessions SET book_id = ?1 WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        // reading_progress is one row per book: keep whichever was read last
+        tx.execute(
+            "DELETE FROM reading_progress
+             WHERE book_id = ?1
+               AND last_read < (SELECT last_read FROM reading_progress WHERE book_id = ?2)",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "UPDATE OR IGNORE reading_progress SET book_id = ?1 WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO books_tags (book_id, tag_id)
+             SELECT ?1, tag_id FROM books_tags WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO collections_books (collection_id, book_id, added_at, sort_order)
+             SELECT collection_id, ?1, added_at, sort_order FROM collections_books WHERE book_id = ?2",
+            params![keep_id, merge_id],
+        )?;
+        // Per-book overrides of the kept book win over the merged one's
+        for table in ["book_preference_overrides", "manga_preference_overrides"] {
+            tx.execute(
+                &format!(
+                    "UPDATE OR IGNORE {} SET book_id = ?1 WHERE book_id = ?2",
+                    table
+                ),
+                params![keep_id, merge_id],
+            )?;
+        }
+        let doodles = tx
+            .prepare("SELECT page_number, strokes_json FROM doodles WHERE book_id = ?1")?
+            .query_map([merge_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        for (page, strokes) in &doodles {
+            doodle_service::put_strokes(&tx, keep_id, page, strokes, false)?;
+        }
+        // Remaining rows (the losing progress row, old junction rows) go
+        // with the book via ON DELETE CASCADE
+        tx.execute("DELETE FROM books WHERE id = ?1", [merge_id])?;
+    }
+
+    refresh_fts_row(&tx, keep_id)?;
+    tx.commit()?;
+    log::info!(
+        "[merge_books] Merged {} book(s) into {}",
+        merge_ids.len(),
+        keep_id
+    );
+    Ok(merge_ids.len())
+}
+
+/// Fields `bulk_update` sets on every selected book. `None` leaves a field
+/// alone; an empty series or publisher clears it, as does a rating of 0.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct BookPatch {
+    pub series: Option<String>,
+    pub publisher: Option<String>,
+    pub language: Option<String>,
+    pub rating: Option<i32>,
+    pub add_tag_ids: Vec<i64>,
+    pub remove_tag_ids: Vec<i64>,
+}
+
+/// Apply `patch` to many books in one transaction, reindexing search once
+/// at the end. Fails without changing anything if a book or tag is missing.
+/// Returns the number of books updated.
+pub fn bulk_update(db: &Database, book_ids: &[i64], patch: &BookPatch) -> Result<usize> {
+    if let Some(rating) = patch.rating {
+        if !(0..=5).contains(&rating) {
+            return Err(ShioriError::Validation(
+                "rating must be between 0 and 5".to_string(),
+            ));
+        }
+    }
+    if patch
+        .language
+        .as_deref()
+        .is_some_and(|l| l.trim().is_empty())
+    {
+        return Err(ShioriError::Validation(
+            "language must not be empty".to_string(),
+        ));
+    }
+
+    let mut book_ids = book_ids.to_vec();
+    book_ids.sort_unstable();
+    book_ids.dedup();
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+
+    for id in &book_ids {
+        let exists: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM books WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(ShioriError::BookNotFound(id.to_string()));
+        }
+    }
+    for tag_id in patch.add_tag_ids.iter().chain(&patch.remove_tag_ids) {
+        let exists: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM tags WHERE id = ?1",
+            [tag_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(ShioriError::TagNotFound(tag_id.to_string()));
+        }
+    }
+
+    let blank_to_null = |value: &str| {
+        let value = value.trim();
+        if value.is_empty() {
+            rusqlite::types::Value::Null
+        } else {
+            rusqlite::types::Value::Text(value.to_string())
+        }
+    };
+    let mut assignments: Vec<&str> = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+    if let Some(series) = &patch.series {
+        assignments.push("series = ?");
+        values.push(blank_to_null(series));
+    }
+    if let Some(publisher) = &patch.publisher {
+        assignments.push("publisher = ?");
+        values.push(blank_to_null(publisher));
+    }
+    if let Some(language) = &patch.language {
+        assignments.push("language = ?");
+        values.push(rusqlite::types::Value::Text(language.trim().to_string()));
+    }
+    if let Some(rating) = patch.rating {
+        assignments.push("rating = ?");
+        values.push(if rating == 0 {
+            rusqlite::types::Value::Null
+        } else {
+            rusqlite::types::Value::Integer(rating.into())
+        });
+    }
+    let tags_changed = !patch.add_tag_ids.is_empty() || !patch.remove_tag_ids.is_empty();
+
+    for chunk in book_ids.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        if !assignments.is_empty() || tags_changed {
+            let sql = format!(
+                "UPDATE books SET {}modified_date = CURRENT_TIMESTAMP WHERE id IN ({})",
+                assignments
+                    .iter()
+                    .map(|a| format!("{}, ", a))
+                    .collect::<String>(),
+                placeholders
+            );
+            tx.execute(
+                &sql,
+                rusqlite::params_from_iter(
+                    values
+                        .iter()
+                        .cloned()
+                        .chain(chunk.iter().map(|id| rusqlite::types::Value::Integer(*id))),
+                ),
+            )?;
+        }
+        for tag_id in &patch.add_tag_ids {
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO books_tags (book_id, tag_id)
+                     SELECT id, ? FROM books WHERE id IN ({})",
+                    placeholders
+                ),
+                rusqlite::params_from_iter(std::iter::once(tag_id).chain(chunk.iter())),
+            )?;
+        }
+        for tag_id in &patch.remove_tag_ids {
+            tx.execute(
+                &format!(
+                    "DELETE FROM books_tags WHERE tag_id = ? AND book_id IN ({})",
+                    placeholders
+                ),
+                rusqlite::params_from_iter(std::iter::once(tag_id).chain(chunk.iter())),
+            )?;
+        }
+    }
+
+    // The update trigger re-indexes books_fts before the tag rows change
+    if tags_changed {
+        refresh_fts_rows(&tx, &book_ids)?;
+    }
+    tx.commit()?;
+    log::info!("[bulk_update] Updated {} book(s)", book_ids.len());
+    Ok(book_ids.len())
 }
 
 struct PreprocessedBook {
@@ -769,6 +3102,8 @@ pub fn scan_and_import_folder(
         success: vec![],
         failed: vec![],
         duplicates: vec![],
+        merged: vec![],
+        replaced: vec![],
     };
 
     if all_paths.is_empty() {
@@ -807,11 +3142,13 @@ pub fn scan_and_import_folder(
                 },
             };
 
+            let isbn = canonical_isbn(metadata.isbn.as_deref(), &path);
             let book_uuid = Uuid::new_v4().to_string();
             let cover_path = metadata_service::extract_cover(&path, &book_uuid, covers_dir)
                 .ok()
                 .flatten();
             let file_size = get_file_size(&path).unwrap_or(0);
+            let text = text_stats(&path, &metadata);
 
             let book = Book {
                 id: None,
@@ -824,12 +3161,12 @@ pub fn scan_and_import_folder(
                         .to_string()
                 }),
                 sort_title: None,
-                isbn: metadata.isbn,
-                isbn13: None,
+                isbn: isbn.as_ref().and_then(|isbn| isbn.isbn10.clone()),
+                isbn13: isbn.map(|isbn| isbn.isbn13),
                 publisher: metadata.publisher,
                 pubdate: metadata.pubdate,
-                series: None,
-                series_index: None,
+                series: metadata.series,
+                series_index: metadata.series_index,
                 rating: None,
                 file_path: path.clone(),
                 file_format: ext_str,
@@ -837,8 +3174,10 @@ pub fn scan_and_import_folder(
                 file_hash: Some(file_hash),
                 cover_path,
                 page_count: metadata.page_count,
-                word_count: None,
-                language: metadata.language.unwrap_or_else(|| "eng".to_string()),
+                word_count: text.word_count,
+                language: metadata
+                    .language
+                    .unwrap_or_else(|| detect_language(text.sample.as_deref())),
                 added_date: chrono::Utc::now().to_rfc3339(),
                 modified_date: chrono::Utc::now().to_rfc3339(),
                 last_opened: None,
@@ -871,8 +3210,10 @@ pub fn scan_and_import_folder(
         })
         .collect();
 
+    let _import = import_lock();
     let mut conn = db.get_connection()?;
     let tx = conn.transaction()?;
+    let autofill = series_autofill_enabled(&tx)?;
 
     for res in preprocessed {
         match res {
@@ -892,6 +3233,14 @@ pub fn scan_and_import_folder(
                     if book.uuid.is_empty() {
                         book.uuid = Uuid::new_v4().to_string();
                     }
+                    // Guessed inside the transaction, so earlier volumes of
+                    // this scan count as known series
+                    if book.series.is_none() && autofill {
+                        if let Some((series, index)) = guess_series(&tx, &book.file_path)? {
+                            book.series = Some(series);
+                            book.series_index = Some(index);
+                        }
+                    }
 
                     let insert_res = tx.execute(
                         "INSERT INTO books (uuid, title, sort_title, isbn, isbn13, publisher, pubdate,
@@ -940,11 +3289,175 @@ pub fn scan_and_import_folder(
     Ok(result)
 }
 
+/// Incrementally re-scan a folder. Files whose path and modified time match the
+/// last scan are skipped without hashing; new files are imported and changed
+/// files have their hash/size refreshed. Files recorded for this folder that no
+/// longer exist on disk are reported in `missing` but left in the library.
+pub fn rescan_folder(
+    db: &Database,
+    folder_path: &str,
+    covers_dir: &std::path::Path,
+) -> Result<RescanResult> {
+    rescan_folder_in_domain(db, folder_path, None, covers_dir)
+}
+
+/// `rescan_folder` for a folder that holds only one domain ("books",
+/// "manga" or "comics"): other files are ignored and new ones are filed
+/// under that domain. `None` picks the domain from each file's extension.
+pub fn rescan_folder_in_domain(
+    db: &Database,
+    folder_path: &str,
+    folder_domain: Option<&str>,
+    covers_dir: &std::path::Path,
+) -> Result<RescanResult> {
+    let domain_formats = match folder_domain {
+        None => None,
+        Some("books") => Some(BOOK_FORMATS),
+        Some("manga") => Some(MANGA_FORMATS),
+        Some("comics") => Some(COMICS_FORMATS),
+        Some(other) => {
+            return Err(ShioriError::Validation(format!(
+                "Folder domain must be books, manga or comics, got '{}'",
+                other
+            )))
+        }
+    };
+    let mut result = RescanResult::default();
+    let conn = db.get_connection()?;
+
+    let mut known: HashMap<String, i64> = {
+        let mut stmt = conn
+            .prepare("SELECT file_path, modified_time FROM scanned_files WHERE folder_path = ?1")?;
+        let rows = stmt.query_map(params![folder_path], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    for entry in WalkDir::new(folder_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let ext_str = match entry.path().extension() {
+            Some(ext) => ext.to_string_lossy().to_lowercase(),
+            None => continue,
+        };
+        let domain = match (folder_domain, domain_formats) {
+            (Some(domain), Some(formats)) if formats.contains(&ext_str.as_str()) => domain,
+            (Some(_), _) => continue,
+            _ if BOOK_FORMATS.contains(&ext_str.as_str()) => "books",
+            _ if MANGA_FORMATS.contains(&ext_str.as_str()) => "manga",
+            _ => continue,
+        };
+        let path = match entry.path().to_str() {
+            Some(p) => p.to_string(),
+            None => continue,
+        };
+
+        let fs_meta = match entry.metadata() {
+            Ok(m) => m,
+            Err(e) => {
+                result.failed.push((path, e.to_string()));
+                continue;
+            }
+        };
+        let mtime = fs_meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let previous = known.remove(&path);
+        if previous == Some(mtime) {
+            result.unchanged += 1;
+            continue;
+        }
+
+        let book_id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM books WHERE file_path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let outcome = match book_id {
+            // Known book whose file changed on disk: refresh the stored fingerprint
+            Some(id) => calculate_file_hash(&path).and_then(|hash| {
+                conn.execute(
+                    "UPDATE books SET file_hash = ?1, file_size = ?2, word_count = ?3,
+                         modified_date = ?4
+                     WHERE id = ?5",
+                    params![
+                        hash,
+                        fs_meta.len() as i64,
+                        metadata_service::count_words(&path),
+                        chrono::Utc::now().to_rfc3339(),
+                        id
+                    ],
+                )?;
+                result.updated += 1;
+                Ok(())
+            }),
+            None => import_single_book(db, &path, covers_dir).and_then(|outcome| {
+                match outcome {
+                    ImportOutcome::Imported { book_id } => {
+                        conn.execute(
+                            "UPDATE books SET domain = ?1 WHERE id = ?2",
+                            params![domain, book_id],
+                        )?;
+                        result.added += 1;
+                    }
+                    ImportOutcome::Merged { .. } => result.added += 1,
+                    ImportOutcome::Replaced { .. } => result.updated += 1,
+                    ImportOutcome::Duplicate { .. } => result.unchanged += 1,
+                }
+                Ok(())
+            }),
+        };
+
+        match outcome {
+            Ok(()) => {
+                conn.execute(
+                    "INSERT OR REPLACE INTO scanned_files (file_path, folder_path, modified_time, file_size, scanned_at)
+                     VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                    params![path, folder_path, mtime, fs_meta.len() as i64],
+                )?;
+            }
+            Err(e) => result.failed.push((path, e.to_string())),
+        }
+    }
+
+    // Anything recorded for this folder but not seen during the walk
+    result.missing = known
+        .into_keys()
+        .filter(|path| !std::path::Path::new(path).exists())
+        .collect();
+    result.missing.sort();
+
+    log::info!(
+        "[rescan_folder] {}: {} added, {} updated, {} unchanged, {} missing",
+        folder_path,
+        result.added,
+        result.updated,
+        result.unchanged,
+        result.missing.len()
+    );
+    Ok(result)
+}
+
 // ═══════════════════════════════════════════════════════════
 // DOMAIN-SEPARATED IMPORT (Books vs Manga)
 // ═══════════════════════════════════════════════════════════
 
-const BOOK_FORMATS: &[&str] = &["epub", "pdf", "mobi", "azw3", "fb2", "txt", "docx", "html"];
+const BOOK_FORMATS: &[&str] = &[
+    "epub", "pdf", "mobi", "azw3", "fb2", "txt", "docx", "html", "djvu",
+];
 const MANGA_FORMATS: &[&str] = &["cbz", "cbr", "zip"];
 const COMICS_FORMATS: &[&str] = &["cbz", "cbr", "zip"];
 
@@ -1014,6 +3527,8 @@ pub fn import_manga(
         success: vec![],
         failed: vec![],
         duplicates: vec![],
+        merged: vec![],
+        replaced: vec![],
     };
 
     for path in paths {
@@ -1028,17 +3543,16 @@ pub fn import_manga(
         }
 
         match import_single_book(db, &path, covers_dir) {
-            Ok(is_duplicate) => {
-                if is_duplicate {
-                    result.duplicates.push(path);
-                } else {
-                    let conn = db.get_connection()?;
-                    conn.execute(
-                        "UPDATE books SET domain = 'manga' WHERE file_path = ?1",
-                        params![path],
-                    )?;
-                    result.success.push(path);
-                }
+            Ok(ImportOutcome::Duplicate { .. }) => result.duplicates.push(path),
+            Ok(ImportOutcome::Merged { .. }) => result.merged.push(path),
+            Ok(ImportOutcome::Replaced { .. }) => result.replaced.push(path),
+            Ok(ImportOutcome::Imported { book_id }) => {
+                let conn = db.get_connection()?;
+                conn.execute(
+                    "UPDATE books SET domain = 'manga' WHERE id = ?1",
+                    params![book_id],
+                )?;
+                result.success.push(path);
             }
             Err(e) => {
                 result.failed.push((path, e.to_string()));
@@ -1087,6 +3601,8 @@ pub fn import_comics(
         success: vec![],
         failed: vec![],
         duplicates: vec![],
+        merged: vec![],
+        replaced: vec![],
     };
 
     for path in paths {
@@ -1101,17 +3617,16 @@ pub fn import_comics(
         }
 
         match import_single_book(db, &path, covers_dir) {
-            Ok(is_duplicate) => {
-                if is_duplicate {
-                    result.duplicates.push(path);
-                } else {
-                    let conn = db.get_connection()?;
-                    conn.execute(
-                        "UPDATE books SET domain = 'comics' WHERE file_path = ?1",
-                        params![path],
-                    )?;
-                    result.success.push(path);
-                }
+            Ok(ImportOutcome::Duplicate { .. }) => result.duplicates.push(path),
+            Ok(ImportOutcome::Merged { .. }) => result.merged.push(path),
+            Ok(ImportOutcome::Replaced { .. }) => result.replaced.push(path),
+            Ok(ImportOutcome::Imported { book_id }) => {
+                let conn = db.get_connection()?;
+                conn.execute(
+                    "UPDATE books SET domain = 'comics' WHERE id = ?1",
+                    params![book_id],
+                )?;
+                result.success.push(path);
             }
             Err(e) => {
                 result.failed.push((path, e.to_string()));
@@ -1160,8 +3675,11 @@ pub fn get_books_by_domain(
     domain: &str,
     limit: u32,
     offset: u32,
+    sort_by: Option<&str>,
+    sort_order: Option<&str>,
 ) -> Result<Vec<Book>> {
     let conn = db.get_connection()?;
+    let sort = resolve_library_sort(&conn, sort_by, sort_order)?;
 
     let where_clause = match domain {
         "books" => "WHERE b.domain = 'books' AND b.in_trash = 0",
@@ -1171,8 +3689,10 @@ pub fn get_books_by_domain(
     };
 
     let sql = format!(
-        "SELECT {} FROM books b {} ORDER BY b.added_date DESC LIMIT ?1 OFFSET ?2",
-        BOOK_COLUMNS, where_clause
+        "SELECT {} FROM books b {} ORDER BY {} LIMIT ?1 OFFSET ?2",
+        BOOK_COLUMNS,
+        where_clause,
+        sort.order_by()
     );
 
     let mut stmt = conn.prepare(&sql)?;
@@ -1257,7 +3777,10 @@ fn get_or_create_author_impl(conn: &rusqlite::Connection, name: &str) -> Result<
         Ok(id) => Ok(id),
         Err(rusqlite::Error::QueryReturnedNoRows) => {
             // Create new author
-            conn.execute("INSERT INTO authors (name) VALUES (?1)", params![name])?;
+            conn.execute(
+                "INSERT INTO authors (name, sort_name) VALUES (?1, ?2)",
+                params![name, author_service::normalize_name(name)],
+            )?;
             Ok(conn.last_insert_rowid())
         }
         Err(e) => Err(e.into()),
@@ -1294,6 +3817,12 @@ pub fn reset_database(db: &Database) -> Result<()> {
     tx.execute("PRAGMA foreign_keys = ON", [])?;
 
     tx.commit()?;
+    drop(conn);
+
+    // Fold the deletes into the main file so the WAL doesn't keep the old data around
+    if !db.checkpoint()? {
+        log::warn!("[reset_database] WAL checkpoint incomplete, database is busy");
+    }
     log::info!("[reset_database] Database has been reset successfully.");
     Ok(())
 }
@@ -1354,6 +3883,118 @@ pub fn get_reading_history(
     Ok(books)
 }
 
+/// Books started but not finished (`0 < progress < 100`), most recently read first
+pub fn get_continue_reading(
+    db: &Database,
+    limit: u32,
+) -> Result<Vec<crate::models::BookWithProgress>> {
+    get_books_with_progress(
+        db,
+        "rp.progress_percent > 0 AND rp.progress_percent < 100",
+        limit,
+    )
+}
+
+/// Books read to 100%, most recently finished first
+pub fn get_recently_finished(
+    db: &Database,
+    limit: u32,
+) -> Result<Vec<crate::models::BookWithProgress>> {
+    get_books_with_progress(db, "rp.progress_percent >= 100", limit)
+}
+
+/// Stamp `books.last_opened` with the current time
+pub fn mark_book_opened(db: &Database, book_id: i64) -> Result<()> {
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE books SET last_opened = ?1 WHERE id = ?2",
+        params![chrono::Utc::now().to_rfc3339(), book_id],
+    )?;
+    Ok(())
+}
+
+/// Fire-and-forget [`mark_book_opened`] so the open path never waits on the
+/// write; failures are only logged
+pub fn mark_book_opened_in_background(db: Database, book_id: i64) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = mark_book_opened(&db, book_id) {
+            log::warn!("Failed to update last_opened for book {}: {}", book_id, e);
+        }
+    });
+}
+
+/// Books by `last_opened`, most recent first, excluding trashed books
+pub fn get_recently_opened(db: &Database, limit: u32) -> Result<Vec<Book>> {
+    let conn = db.get_connection()?;
+    let sql = format!(
+        "SELECT {} FROM books b
+         WHERE b.last_opened IS NOT NULL AND b.in_trash = 0
+         ORDER BY b.last_opened DESC
+         LIMIT ?1",
+        BOOK_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut books: Vec<Book> = stmt
+        .query_map(params![limit], book_from_row)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    attach_authors_and_tags(&conn, &mut books)?;
+    Ok(books)
+}
+
+fn get_books_with_progress(
+    db: &Database,
+    progress_filter: &str,
+    limit: u32,
+) -> Result<Vec<crate::models::BookWithProgress>> {
+    let conn = db.get_connection()?;
+    let sql = format!(
+        "SELECT {}, rp.progress_percent, rp.current_location, rp.current_page,
+                rp.total_pages, rp.last_read
+         FROM books b
+         JOIN reading_progress rp ON b.id = rp.book_id
+         WHERE b.in_trash = 0 AND {}
+         ORDER BY rp.last_read DESC
+         LIMIT ?1",
+        BOOK_COLUMNS, progress_filter
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map(params![limit], |row| {
+            Ok((
+                book_from_row(row)?,
+                row.get::<_, Option<f64>>(34)?.unwrap_or(0.0),
+                row.get::<_, String>(35)?,
+                row.get::<_, Option<i32>>(36)?,
+                row.get::<_, Option<i32>>(37)?,
+                row.get::<_, String>(38)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let (mut books, progress): (Vec<Book>, Vec<_>) = rows
+        .into_iter()
+        .map(|(book, pct, loc, page, total, last)| (book, (pct, loc, page, total, last)))
+        .unzip();
+    attach_authors_and_tags(&conn, &mut books)?;
+
+    Ok(books
+        .into_iter()
+        .zip(progress)
+        .map(
+            |(book, (progress_percent, current_location, current_page, total_pages, last_read))| {
+                crate::models::BookWithProgress {
+                    book,
+                    progress_percent,
+                    current_location,
+                    current_page,
+                    total_pages,
+                    last_read,
+                }
+            },
+        )
+        .collect())
+}
+
 const BOOK_SUMMARY_COLUMNS: &str =
     "b.id, b.uuid, b.title, b.sort_title, b.file_path, b.file_format, b.file_size,
      b.cover_path, b.added_date, b.is_favorite, b.reading_status, b.domain,
@@ -1644,11 +4285,24 @@ mod tests {
             link: None,
         });
 
-        update_book(&db, fetched_book.clone()).expect("Failed to update book");
+        update_book(&db, fetched_book.clone(), false).expect("Failed to update book");
+
+        let updated_book = get_book_by_id(&db, id).unwrap();
+        assert_eq!(updated_book.title, "Updated Title");
+        assert_eq!(updated_book.authors.len(), 3);
+    }
+
+    #[test]
+    fn test_update_book_rolls_back_when_file_write_fails() {
+        let (db, _dir) = setup_test_db();
+        // The test book's EPUB path doesn't exist, so the write-back fails
+        let id = add_book(&db, create_test_book()).unwrap();
+
+        let mut edited = get_book_by_id(&db, id).unwrap();
+        edited.title = "Updated Title".to_string();
+        assert!(update_book(&db, edited, true).is_err());
 
-        let updated_book = get_book_by_id(&db, id).unwrap();
-        assert_eq!(updated_book.title, "Updated Title");
-        assert_eq!(updated_book.authors.len(), 3);
+        assert_eq!(get_book_by_id(&db, id).unwrap().title, "Test Book");
     }
 
     #[test]
@@ -1749,4 +4403,1124 @@ mod tests {
             .expect("missing path should be recorded as a failed entry, not abort the batch");
         assert!(!missing_failure.1.is_empty());
     }
+
+    #[test]
+    fn test_normalize_isbn13() {
+        assert_eq!(
+            normalize_isbn13("0-261-10334-2").as_deref(),
+            Some("9780261103344")
+        );
+        assert_eq!(
+            normalize_isbn13("978-0-261-10334-4").as_deref(),
+            Some("9780261103344")
+        );
+        assert_eq!(
+            normalize_isbn13("080442957X").as_deref(),
+            Some("9780804429573")
+        );
+        assert_eq!(normalize_isbn13("12345"), None);
+    }
+
+    #[test]
+    fn test_title_mode_merges_other_format() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let txt_path = dir.path().join("Dune.txt");
+        let html_path = dir.path().join("Dune.html");
+        std::fs::write(&txt_path, b"plain text edition").unwrap();
+        std::fs::write(&html_path, b"<p>html edition</p>").unwrap();
+        let txt_path = txt_path.to_string_lossy().to_string();
+        let html_path = html_path.to_string_lossy().to_string();
+
+        // Default 'hash' mode keeps both as separate books
+        let first = import_single_book(&db, &txt_path, &covers_dir).unwrap();
+        assert!(matches!(first, ImportOutcome::Imported { .. }));
+
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "UPDATE library_settings SET duplicate_detection_mode = 'title' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+
+        let result = import_books(
+            &db,
+            vec![html_path.clone()],
+            &covers_dir,
+            ImportPolicy::default(),
+        )
+        .unwrap();
+        assert!(result.success.is_empty());
+        assert_eq!(result.merged, vec![html_path.clone()]);
+        assert_eq!(get_total_books(&db).unwrap(), 1);
+
+        let conn = db.get_connection().unwrap();
+        let formats: Vec<(String, i64)> = conn
+            .prepare("SELECT format, is_primary FROM book_formats ORDER BY is_primary DESC")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            formats,
+            vec![("txt".to_string(), 1), ("html".to_string(), 0)]
+        );
+
+        // Re-importing the attached file is reported as a plain duplicate
+        assert!(matches!(
+            import_single_book(&db, &html_path, &covers_dir).unwrap(),
+            ImportOutcome::Duplicate { .. }
+        ));
+    }
+
+    #[test]
+    fn test_import_policy_for_files_already_in_library() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let original = dir.path().join("Dune.txt");
+        std::fs::write(&original, b"the spice must flow").unwrap();
+        let moved_dir = dir.path().join("moved");
+        std::fs::create_dir_all(&moved_dir).unwrap();
+        let moved = moved_dir.join("Dune.txt");
+        std::fs::copy(&original, &moved).unwrap();
+        let html = dir.path().join("Dune.html");
+        std::fs::write(&html, b"<p>the spice must flow</p>").unwrap();
+        let (original, moved, html) = (
+            original.to_string_lossy().to_string(),
+            moved.to_string_lossy().to_string(),
+            html.to_string_lossy().to_string(),
+        );
+
+        let book_id = match import_single_book(&db, &original, &covers_dir).unwrap() {
+            ImportOutcome::Imported { book_id } => book_id,
+            other => panic!("expected a new book, got {:?}", other),
+        };
+        let policy = |on_duplicate| ImportPolicy { on_duplicate };
+
+        // Skip (the default) reports the same file at a new path and leaves the book alone
+        let skipped = import_books(
+            &db,
+            vec![moved.clone()],
+            &covers_dir,
+            ImportPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(skipped.duplicates, vec![moved.clone()]);
+        assert_eq!(get_book_by_id(&db, book_id).unwrap().file_path, original);
+
+        // Skip doesn't match on title either: another format becomes its own book
+        assert_eq!(
+            import_single_book_with_policy(&db, &html, &covers_dir, ImportPolicy::default())
+                .unwrap(),
+            ImportOutcome::Imported {
+                book_id: book_id + 1
+            }
+        );
+        delete_book(&db, book_id + 1).unwrap();
+        permanent_delete_book(&db, book_id + 1).unwrap();
+
+        // AddFormat attaches a same-titled file to the existing book
+        let added = import_books(
+            &db,
+            vec![html.clone()],
+            &covers_dir,
+            policy(DuplicatePolicy::AddFormat),
+        )
+        .unwrap();
+        assert_eq!(added.merged, vec![html.clone()]);
+        assert_eq!(get_total_books(&db).unwrap(), 1);
+        let formats = get_book_formats(&db, book_id).unwrap();
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[1].file_path, html);
+
+        // ...but a file with the same hash has nothing new to attach
+        let again = import_books(
+            &db,
+            vec![moved.clone()],
+            &covers_dir,
+            policy(DuplicatePolicy::AddFormat),
+        )
+        .unwrap();
+        assert_eq!(again.duplicates, vec![moved.clone()]);
+
+        // Replace points the book at the new file and keeps its other formats
+        let replaced = import_books(
+            &db,
+            vec![moved.clone()],
+            &covers_dir,
+            policy(DuplicatePolicy::Replace),
+        )
+        .unwrap();
+        assert_eq!(replaced.replaced, vec![moved.clone()]);
+        assert_eq!(get_total_books(&db).unwrap(), 1);
+        let book = get_book_by_id(&db, book_id).unwrap();
+        assert_eq!(book.file_path, moved);
+        assert_eq!(book.title, "Dune");
+        let formats = get_book_formats(&db, book_id).unwrap();
+        assert_eq!(formats.len(), 2);
+        assert!(formats[0].is_primary);
+        assert_eq!(formats[0].file_path, moved);
+
+        // A swap that can't complete leaves the book's metadata as it was.
+        // Without a hash the current file can't be kept as a format row.
+        let mut unhashed = create_test_book();
+        unhashed.title = "Roadside Picnic".to_string();
+        unhashed.authors = Vec::new();
+        unhashed.tags = Vec::new();
+        unhashed.language = "rus".to_string();
+        unhashed.file_path = "/dummy/path/Roadside Picnic.epub".to_string();
+        unhashed.file_hash = None;
+        let unhashed_id = add_book(&db, unhashed).unwrap();
+        let picnic = dir.path().join("Roadside Picnic.txt");
+        std::fs::write(&picnic, b"The Zone was left behind by visitors.").unwrap();
+        let failed = import_books(
+            &db,
+            vec![picnic.to_string_lossy().to_string()],
+            &covers_dir,
+            policy(DuplicatePolicy::Replace),
+        )
+        .unwrap();
+        assert_eq!(failed.failed.len(), 1);
+        let book = get_book_by_id(&db, unhashed_id).unwrap();
+        assert_eq!(book.file_format, "epub");
+        assert_eq!(book.language, "rus");
+
+        // A DjVu file is kept as a format row like any other
+        let mut djvu = create_test_book();
+        djvu.title = "Solaris".to_string();
+        djvu.authors = Vec::new();
+        djvu.tags = Vec::new();
+        djvu.file_path = "/dummy/path/Solaris.djvu".to_string();
+        djvu.file_format = "djvu".to_string();
+        djvu.file_hash = Some("hash-solaris".to_string());
+        let djvu_id = add_book(&db, djvu).unwrap();
+        let solaris = dir.path().join("Solaris.txt");
+        std::fs::write(&solaris, b"The ocean of the planet was alive and thinking.").unwrap();
+        let solaris = solaris.to_string_lossy().to_string();
+        let replaced = import_books(
+            &db,
+            vec![solaris.clone()],
+            &covers_dir,
+            policy(DuplicatePolicy::Replace),
+        )
+        .unwrap();
+        assert_eq!(replaced.replaced, vec![solaris.clone()]);
+        let book = get_book_by_id(&db, djvu_id).unwrap();
+        assert_eq!(book.file_format, "txt");
+        assert_eq!(book.file_path, solaris);
+        let formats = get_book_formats(&db, djvu_id).unwrap();
+        assert_eq!(formats.len(), 1);
+        assert_eq!(formats[0].format, "txt");
+        assert!(formats[0].is_primary);
+    }
+
+    #[test]
+    fn test_import_counts_words_for_text() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let txt_path = dir.path().join("Notes.txt");
+        std::fs::write(&txt_path, "one two  three\nfour\tfive\n").unwrap();
+        let cbz_path = dir.path().join("Chapter.cbz");
+        std::fs::write(&cbz_path, b"fake cbz bytes").unwrap();
+
+        let result = import_books(
+            &db,
+            vec![txt_path.to_string_lossy().to_string()],
+            &covers_dir,
+            ImportPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(result.success.len(), 1);
+        let manga = import_manga(
+            &db,
+            vec![cbz_path.to_string_lossy().to_string()],
+            &covers_dir,
+        )
+        .unwrap();
+        assert_eq!(manga.success.len(), 1);
+
+        let conn = db.get_connection().unwrap();
+        let word_count = |format: &str| -> Option<i32> {
+            conn.query_row(
+                "SELECT word_count FROM books WHERE file_format = ?1",
+                params![format],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(word_count("txt"), Some(5));
+        assert_eq!(word_count("cbz"), None);
+    }
+
+    #[test]
+    fn test_import_detects_language_of_text() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let txt_path = dir.path().join("Roman.txt");
+        std::fs::write(
+            &txt_path,
+            "Longtemps, je me suis couché de bonne heure. Parfois, à peine ma bougie éteinte, \
+             mes yeux se fermaient si vite que je n'avais pas le temps de me dire : je m'endors.",
+        )
+        .unwrap();
+
+        let outcome = import_single_book(&db, &txt_path.to_string_lossy(), &covers_dir).unwrap();
+        let ImportOutcome::Imported { book_id } = outcome else {
+            panic!("expected a new book, got {:?}", outcome);
+        };
+        let language: String = db
+            .get_connection()
+            .unwrap()
+            .query_row(
+                "SELECT language FROM books WHERE id = ?1",
+                params![book_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(language, "fra");
+    }
+
+    #[test]
+    fn test_import_books_concurrently_counts_each_file_once() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let mut paths = Vec::new();
+        for i in 0..12 {
+            let path = dir.path().join(format!("book-{}.txt", i));
+            std::fs::write(&path, format!("Book number {} begins here.", i)).unwrap();
+            paths.push(path.to_string_lossy().to_string());
+        }
+        // Same contents as book-0, so only one of the two can be inserted
+        let copy = dir.path().join("copy-of-book-0.txt");
+        std::fs::copy(&paths[0], &copy).unwrap();
+        paths.push(copy.to_string_lossy().to_string());
+        paths.push(dir.path().join("notes.xyz").to_string_lossy().to_string());
+
+        let progress = std::cell::RefCell::new(Vec::new());
+        let result = import_books_with_progress(
+            &db,
+            paths.clone(),
+            &covers_dir,
+            ImportPolicy::default(),
+            |p| progress.borrow_mut().push((p.processed, p.total)),
+        )
+        .unwrap();
+
+        assert_eq!(result.success.len(), 12);
+        assert_eq!(result.duplicates.len(), 1);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].0.ends_with("notes.xyz"));
+        let mut reported: Vec<&String> = result
+            .success
+            .iter()
+            .chain(&result.duplicates)
+            .chain(result.failed.iter().map(|(path, _)| path))
+            .collect();
+        reported.sort();
+        reported.dedup();
+        assert_eq!(reported.len(), paths.len());
+
+        let conn = db.get_connection().unwrap();
+        let (books, hashes): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), COUNT(DISTINCT file_hash) FROM books",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((books, hashes), (12, 12));
+
+        // The rejected .xyz is reported first, so the count starts at one
+        let progress = progress.into_inner();
+        assert_eq!(progress.len(), 14);
+        assert_eq!(progress.first(), Some(&(1, 14)));
+        assert_eq!(progress.last(), Some(&(14, 14)));
+    }
+
+    #[tokio::test]
+    async fn test_import_pdf_renders_first_page_as_cover() {
+        use crate::services::conversion_engine::ConversionEngine;
+
+        if !crate::services::pdf_adapter::is_available() {
+            eprintln!("skipping: poppler's pdftoppm is not on PATH");
+            return;
+        }
+
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        let txt = dir.path().join("report.txt");
+        std::fs::write(&txt, "Annual Report\n\nNo pictures here, only text.").unwrap();
+        let pdf = dir.path().join("report.pdf");
+        ConversionEngine::convert_direct(&txt, &pdf, "txt", "pdf", None, None)
+            .await
+            .unwrap();
+
+        let outcome = import_single_book(&db, &pdf.to_string_lossy(), &covers_dir).unwrap();
+        let ImportOutcome::Imported { book_id } = outcome else {
+            panic!("expected a new book, got {:?}", outcome);
+        };
+        let book = get_book_by_id(&db, book_id).unwrap();
+        let cover =
+            std::path::PathBuf::from(book.cover_path.expect("a text-only PDF still gets a cover"));
+        // A CoverService set rendered from the A4 page, not generated
+        assert_eq!(cover, covers_dir.join(&book.uuid).join("medium.webp"));
+        let full = image::open(cover.with_file_name("full.webp")).unwrap();
+        assert_eq!(full.height(), 800);
+        assert!(full.width() < full.height());
+    }
+
+    #[test]
+    fn test_parse_series_filename_patterns() {
+        let guess = |stem: &str| parse_series_filename(stem).map(|g| (g.name, g.index, g.marked));
+        assert_eq!(
+            guess("Dune 2 - Messiah"),
+            Some(("Dune".to_string(), 2.0, false))
+        );
+        assert_eq!(
+            guess("The Expanse #3 - Abaddon's Gate"),
+            Some(("The Expanse".to_string(), 3.0, true))
+        );
+        assert_eq!(
+            guess("Mistborn, Book 1"),
+            Some(("Mistborn".to_string(), 1.0, true))
+        );
+        assert_eq!(
+            guess("Discworld_Vol. 4.5"),
+            Some(("Discworld".to_string(), 4.5, true))
+        );
+        assert_eq!(guess("1984"), None);
+        assert_eq!(guess("A Tale of Two Cities"), None);
+    }
+
+    #[test]
+    fn test_import_fills_series_from_filename_when_enabled() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+        add_titled_book(&db, "Dune", "Frank Herbert", "epub");
+        let import = |name: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("{}: the desert planet, once more.", name)).unwrap();
+            let outcome = import_single_book(&db, &path.to_string_lossy(), &covers_dir).unwrap();
+            let ImportOutcome::Imported { book_id } = outcome else {
+                panic!("expected a new book, got {:?}", outcome);
+            };
+            let book = get_book_by_id(&db, book_id).unwrap();
+            (book.series, book.series_index)
+        };
+
+        // Off by default
+        assert_eq!(import("Dune 3 - Children.txt"), (None, None));
+
+        set_series_autofill(&db, true).unwrap();
+        // A bare number is confirmed by the existing "Dune"
+        assert_eq!(
+            import("Dune 2 - Messiah.txt"),
+            (Some("Dune".to_string()), Some(2.0))
+        );
+        // Nothing in the library backs this one up, so it's left alone
+        assert_eq!(import("Catch 22.txt"), (None, None));
+
+        // Folder scans fill it in the same way
+        let folder = dir.path().join("scan");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("Dune 4 - God Emperor.txt"), "the worm").unwrap();
+        let scanned = scan_and_import_folder(&db, &folder.to_string_lossy(), &covers_dir).unwrap();
+        assert_eq!(scanned.success.len(), 1);
+        let series: (Option<String>, Option<f64>) = db
+            .get_connection()
+            .unwrap()
+            .query_row(
+                "SELECT series, series_index FROM books WHERE file_path = ?1",
+                [&scanned.success[0]],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(series, (Some("Dune".to_string()), Some(4.0)));
+    }
+
+    #[test]
+    fn test_rescan_folder_is_incremental() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        let library = dir.path().join("library");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+        std::fs::create_dir_all(&library).unwrap();
+
+        let first = library.join("First.txt");
+        let second = library.join("Second.txt");
+        std::fs::write(&first, "first book").unwrap();
+        std::fs::write(&second, "second book").unwrap();
+        let folder = library.to_string_lossy().to_string();
+
+        let initial = rescan_folder(&db, &folder, &covers_dir).unwrap();
+        assert_eq!(
+            (initial.added, initial.updated, initial.unchanged),
+            (2, 0, 0)
+        );
+
+        let again = rescan_folder(&db, &folder, &covers_dir).unwrap();
+        assert_eq!((again.added, again.updated, again.unchanged), (0, 0, 2));
+
+        // Rewrite one file with a different mtime, delete the other, add a third
+        std::fs::write(&first, "first book, revised edition").unwrap();
+        let file = std::fs::File::options().write(true).open(&first).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        std::fs::remove_file(&second).unwrap();
+        std::fs::write(library.join("Third.txt"), "third book").unwrap();
+
+        let changed = rescan_folder(&db, &folder, &covers_dir).unwrap();
+        assert_eq!(
+            (changed.added, changed.updated, changed.unchanged),
+            (1, 1, 0)
+        );
+        assert_eq!(changed.missing, vec![second.to_string_lossy().to_string()]);
+
+        // Missing files are only reported, never removed from the library
+        assert_eq!(get_total_books(&db).unwrap(), 3);
+        let word_count: Option<i32> = db
+            .get_connection()
+            .unwrap()
+            .query_row(
+                "SELECT word_count FROM books WHERE file_path = ?1",
+                params![first.to_string_lossy().to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(word_count, Some(4));
+    }
+
+    #[test]
+    fn test_import_archive_extracts_books_only() {
+        use std::io::Write;
+
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        let storage_dir = dir.path().join("storage");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let zip_path = dir.path().join("bundle.zip");
+        {
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            for (name, body) in [
+                ("nested/Alpha.txt", "alpha text"),
+                ("Beta.html", "<p>beta</p>"),
+                ("Chapter 1.cbz", "not an ebook"),
+                ("readme.md", "ignored"),
+            ] {
+                writer.start_file(name, options).unwrap();
+                writer.write_all(body.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        let zip_str = zip_path.to_string_lossy().to_string();
+
+        let result = import_archive(
+            &db,
+            &zip_str,
+            "books",
+            &storage_dir,
+            &covers_dir,
+            ImportPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(result.success.len(), 2, "failed: {:?}", result.failed);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].0.ends_with("Chapter 1.cbz"));
+        assert_eq!(get_total_books(&db).unwrap(), 2);
+
+        // Only the imported files remain in managed storage
+        let archive_dirs: Vec<_> = std::fs::read_dir(storage_dir.join("archives"))
+            .unwrap()
+            .collect();
+        assert_eq!(archive_dirs.len(), 1);
+        let kept = std::fs::read_dir(archive_dirs[0].as_ref().unwrap().path())
+            .unwrap()
+            .count();
+        assert_eq!(kept, 2);
+
+        // Importing the same archive again only reports duplicates and leaves nothing behind
+        let reported = std::cell::RefCell::new(Vec::new());
+        let again = import_archive_with_progress(
+            &db,
+            &zip_str,
+            "books",
+            &storage_dir,
+            &covers_dir,
+            ImportPolicy::default(),
+            |label| reported.borrow_mut().push(label.to_string()),
+        )
+        .unwrap();
+        assert_eq!(again.duplicates.len(), 2);
+
+        // Progress covers exactly the entries counted up front, readme excluded
+        assert_eq!(count_archive_entries(&zip_str), 3);
+        let reported = reported.into_inner();
+        assert_eq!(reported.len(), 3);
+        assert!(reported.iter().all(|label| !label.ends_with("readme.md")));
+        assert_eq!(
+            count_archive_entries(&dir.path().join("missing.zip").to_string_lossy()),
+            0
+        );
+        assert_eq!(
+            std::fs::read_dir(storage_dir.join("archives"))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_managed_library_copies_and_relocates() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+        set_managed_library(&db, true).unwrap();
+
+        let source = dir.path().join("Managed.txt");
+        std::fs::write(&source, "managed copy").unwrap();
+        let outcome = import_single_book(&db, &source.to_string_lossy(), &covers_dir).unwrap();
+        let book_id = match outcome {
+            ImportOutcome::Imported { book_id } => book_id,
+            other => panic!("unexpected outcome {:?}", other),
+        };
+
+        let book = get_book_by_id(&db, book_id).unwrap();
+        let expected = dir
+            .path()
+            .join("storage")
+            .join("books")
+            .join(format!("{}.txt", book.uuid));
+        assert_eq!(book.file_path, expected.to_string_lossy());
+
+        // Removing the source no longer affects the library
+        std::fs::remove_file(&source).unwrap();
+        assert!(std::path::Path::new(&book.file_path).exists());
+
+        // A moved unmanaged book is found again by name + hash
+        set_managed_library(&db, false).unwrap();
+        let original = dir.path().join("Loose.txt");
+        std::fs::write(&original, "loose book").unwrap();
+        import_single_book(&db, &original.to_string_lossy(), &covers_dir).unwrap();
+        let moved_dir = dir.path().join("moved");
+        std::fs::create_dir_all(&moved_dir).unwrap();
+        let moved = moved_dir.join("Loose.txt");
+        std::fs::rename(&original, &moved).unwrap();
+
+        let report =
+            relocate_missing_files(&db, &covers_dir, &[moved_dir.to_string_lossy().to_string()])
+                .unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.relocated.len(), 1);
+        assert_eq!(report.relocated[0].1, moved.to_string_lossy());
+        assert!(report.still_missing.is_empty());
+    }
+
+    #[test]
+    fn test_continue_reading_and_recently_finished() {
+        let (db, _dir) = setup_test_db();
+        let mut ids = Vec::new();
+        for i in 0..4 {
+            let mut book = create_test_book();
+            book.title = format!("Book {}", i);
+            book.file_path = format!("/dummy/path/progress{}.epub", i);
+            book.file_hash = Some(format!("progresshash{}", i));
+            ids.push(add_book(&db, book).unwrap());
+        }
+
+        let conn = db.get_connection().unwrap();
+        for (book_id, percent, last_read) in [
+            (ids[0], 0.0, "2024-01-04 10:00:00"),
+            (ids[1], 42.5, "2024-01-01 10:00:00"),
+            (ids[2], 80.0, "2024-01-03 10:00:00"),
+            (ids[3], 100.0, "2024-01-02 10:00:00"),
+        ] {
+            conn.execute(
+                "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES (?1, 'loc', ?2, ?3)",
+                params![book_id, percent, last_read],
+            )
+            .unwrap();
+        }
+
+        let continuing = get_continue_reading(&db, 10).unwrap();
+        let titles: Vec<_> = continuing.iter().map(|b| b.book.title.as_str()).collect();
+        assert_eq!(titles, vec!["Book 2", "Book 1"]);
+        assert_eq!(continuing[1].progress_percent, 42.5);
+        assert_eq!(continuing[0].book.authors.len(), 2);
+
+        let finished = get_recently_finished(&db, 10).unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].book.id, Some(ids[3]));
+
+        assert_eq!(get_continue_reading(&db, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_mark_book_opened_orders_recently_opened() {
+        let (db, _dir) = setup_test_db();
+        let first = add_book(&db, create_test_book()).unwrap();
+        let mut other = create_test_book();
+        other.file_path = "/dummy/path/other.epub".to_string();
+        other.file_hash = Some("otherhash".to_string());
+        let second = add_book(&db, other).unwrap();
+
+        assert!(get_recently_opened(&db, 10).unwrap().is_empty());
+
+        mark_book_opened(&db, second).unwrap();
+        mark_book_opened(&db, first).unwrap();
+        assert!(get_book_by_id(&db, first).unwrap().last_opened.is_some());
+
+        let recent = get_recently_opened(&db, 10).unwrap();
+        let ids: Vec<i64> = recent.iter().filter_map(|b| b.id).collect();
+        assert_eq!(ids, vec![first, second]);
+        assert_eq!(get_recently_opened(&db, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_books_grouped_by_series() {
+        let (db, _dir) = setup_test_db();
+        let add = |title: &str, series: Option<&str>, index: Option<f64>| {
+            let mut book = create_test_book();
+            book.title = title.to_string();
+            book.series = series.map(str::to_string);
+            book.series_index = index;
+            book.file_path = format!("/dummy/path/{}.epub", title);
+            book.file_hash = Some(format!("hash-{}", title));
+            book.cover_path = Some(format!("/covers/{}.jpg", title));
+            add_book(&db, book).unwrap()
+        };
+        let second = add("Saga Two", Some("Test Saga"), Some(2.0));
+        let extra = add("Saga Extra", Some("Test Saga"), None);
+        let first = add("Saga One", Some("Test Saga"), Some(1.0));
+        let standalone = add("Alpha Standalone", None, None);
+
+        let groups = get_books_grouped_by_series(&db, 10, 0).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].series, None);
+        assert_eq!(groups[0].books[0].id, Some(standalone));
+        assert_eq!(groups[1].series.as_deref(), Some("Test Saga"));
+        let ids: Vec<Option<i64>> = groups[1].books.iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![Some(first), Some(second), Some(extra)]);
+
+        // Pages count groups, not books
+        let page = get_books_grouped_by_series(&db, 1, 1).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].books.len(), 3);
+
+        let series = get_series_list(&db).unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].name, "Test Saga");
+        assert_eq!(series[0].book_count, 3);
+        assert_eq!(
+            series[0].cover_paths,
+            vec![
+                "/covers/Saga One.jpg",
+                "/covers/Saga Two.jpg",
+                "/covers/Saga Extra.jpg"
+            ]
+        );
+    }
+
+    fn add_sortable_book(db: &Database, title: &str, rating: Option<i32>) -> i64 {
+        let mut book = create_test_book();
+        book.title = title.to_string();
+        book.rating = rating;
+        book.file_path = format!("/dummy/path/{}.epub", title);
+        book.file_hash = Some(format!("hash-{}", title));
+        add_book(db, book).unwrap()
+    }
+
+    fn titles(books: &[Book]) -> Vec<&str> {
+        books.iter().map(|b| b.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_get_all_books_sorting() {
+        let (db, _dir) = setup_test_db();
+        add_sortable_book(&db, "banana", Some(3));
+        add_sortable_book(&db, "Apple", Some(5));
+        add_sortable_book(&db, "cherry", None);
+        add_sortable_book(&db, "Date", Some(1));
+
+        let by_title = get_all_books(&db, 10, 0, Some("title"), Some("asc")).unwrap();
+        assert_eq!(titles(&by_title), vec!["Apple", "banana", "cherry", "Date"]);
+
+        // Unrated books sort last
+        let by_rating = get_all_books(&db, 10, 0, Some("rating"), Some("desc")).unwrap();
+        assert_eq!(
+            titles(&by_rating),
+            vec!["Apple", "banana", "Date", "cherry"]
+        );
+
+        // Omitted arguments fall back to the stored library settings
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "UPDATE library_settings SET default_sort_field = 'title', default_sort_order = 'desc' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+        let stored = get_books_by_domain(&db, "all", 10, 0, None, None).unwrap();
+        assert_eq!(titles(&stored), vec!["Date", "cherry", "banana", "Apple"]);
+        let flipped = get_all_books(&db, 10, 0, None, Some("asc")).unwrap();
+        assert_eq!(titles(&flipped), vec!["Apple", "banana", "cherry", "Date"]);
+    }
+
+    #[test]
+    fn test_get_all_books_rejects_unknown_sort_fields() {
+        let (db, _dir) = setup_test_db();
+        for (field, order) in [
+            ("title; DROP TABLE books", "asc"),
+            ("file_path", "asc"),
+            ("title", "sideways"),
+        ] {
+            assert!(matches!(
+                get_all_books(&db, 10, 0, Some(field), Some(order)),
+                Err(ShioriError::Validation(_))
+            ));
+        }
+    }
+
+    fn add_titled_book(db: &Database, title: &str, author: &str, format: &str) -> i64 {
+        let mut book = create_test_book();
+        book.title = title.to_string();
+        book.authors = vec![Author {
+            id: None,
+            name: author.to_string(),
+            sort_name: None,
+            link: None,
+        }];
+        book.tags = Vec::new();
+        book.file_format = format.to_string();
+        book.file_path = format!("/dummy/path/{}-{}.{}", title, author, format);
+        book.file_hash = Some(format!("hash-{}-{}-{}", title, author, format));
+        add_book(db, book).unwrap()
+    }
+
+    #[test]
+    fn test_find_duplicates_by_title_and_author() {
+        let (db, _dir) = setup_test_db();
+        let first = add_titled_book(&db, "The Hobbit", "J.R.R. Tolkien", "epub");
+        add_titled_book(&db, "The Silmarillion", "J.R.R. Tolkien", "epub");
+        let second = add_titled_book(&db, "the hobbit!", "j.r.r.  tolkien", "pdf");
+        add_titled_book(&db, "The Hobbit", "Someone Else", "epub");
+
+        let groups = find_duplicates(&db, DuplicateMode::TitleAuthor).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].book_ids, vec![first, second]);
+
+        assert!(find_duplicates(&db, DuplicateMode::Hash)
+            .unwrap()
+            .is_empty());
+        assert!(DuplicateMode::parse("fuzzy").is_err());
+    }
+
+    #[test]
+    fn test_bulk_update_sets_series_and_tag_atomically() {
+        let (db, _dir) = setup_test_db();
+        let ids = [
+            add_titled_book(&db, "Dune", "Frank Herbert", "epub"),
+            add_titled_book(&db, "Dune Messiah", "Frank Herbert", "epub"),
+            add_titled_book(&db, "Children of Dune", "Frank Herbert", "epub"),
+        ];
+        let tag_id = {
+            let conn = db.get_connection().unwrap();
+            conn.execute("INSERT INTO tags (name) VALUES ('arrakis')", [])
+                .unwrap();
+            conn.last_insert_rowid()
+        };
+
+        let patch = BookPatch {
+            series: Some("Dune Chronicles".to_string()),
+            add_tag_ids: vec![tag_id],
+            ..Default::default()
+        };
+        assert_eq!(bulk_update(&db, &ids, &patch).unwrap(), 3);
+        for id in ids {
+            let book = get_book_by_id(&db, id).unwrap();
+            assert_eq!(book.series.as_deref(), Some("Dune Chronicles"));
+            assert_eq!(book.tags.len(), 1);
+            assert_eq!(book.tags[0].name, "arrakis");
+            // Fields absent from the patch are untouched
+            assert_eq!(book.authors[0].name, "Frank Herbert");
+        }
+        let conn = db.get_connection().unwrap();
+        let tagged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH 'tags:arrakis'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tagged, 3);
+
+        // One missing book rolls the whole batch back
+        let patch = BookPatch {
+            publisher: Some("Ace".to_string()),
+            remove_tag_ids: vec![tag_id],
+            ..Default::default()
+        };
+        assert!(matches!(
+            bulk_update(&db, &[ids[0], 9999], &patch),
+            Err(ShioriError::BookNotFound(_))
+        ));
+        let book = get_book_by_id(&db, ids[0]).unwrap();
+        assert_eq!(book.publisher.as_deref(), Some("Test Publisher"));
+        assert_eq!(book.tags.len(), 1);
+    }
+
+    #[test]
+    fn test_book_formats_add_switch_primary_and_reject_duplicates() {
+        let (db, dir) = setup_test_db();
+        let book_id = add_titled_book(&db, "Emma", "Jane Austen", "epub");
+        let other_id = add_titled_book(&db, "Persuasion", "Jane Austen", "epub");
+        let txt = dir.path().join("emma.txt");
+        std::fs::write(&txt, "Emma Woodhouse, handsome, clever, and rich.").unwrap();
+        let txt = txt.to_string_lossy().to_string();
+
+        let added = add_book_format(&db, book_id, &txt, dir.path()).unwrap();
+        assert_eq!(added.format, "txt");
+        assert!(!added.is_primary);
+        let formats = get_book_formats(&db, book_id).unwrap();
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[0].format, "epub");
+        assert!(formats[0].is_primary);
+
+        set_primary_format(&db, book_id, "txt").unwrap();
+        let book = get_book_by_id(&db, book_id).unwrap();
+        assert_eq!(book.file_format, "txt");
+        assert_eq!(book.file_path, txt);
+        let formats = get_book_formats(&db, book_id).unwrap();
+        assert_eq!(formats[0].format, "txt");
+        assert_eq!(formats.iter().filter(|f| f.is_primary).count(), 1);
+        assert_eq!(
+            get_book_format_path(&db, book_id, "epub").unwrap(),
+            "/dummy/path/Emma-Jane Austen.epub"
+        );
+
+        // The same file can't be attached to another book
+        assert!(matches!(
+            add_book_format(&db, other_id, &txt, dir.path()),
+            Err(ShioriError::DuplicateBook(_))
+        ));
+        // and the primary format can't be dropped
+        assert!(matches!(
+            delete_book_format(&db, formats[0].id),
+            Err(ShioriError::InvalidOperation(_))
+        ));
+        delete_book_format(&db, formats[1].id).unwrap();
+        assert_eq!(get_book_formats(&db, book_id).unwrap().len(), 1);
+
+        // A book whose own file can't get a format row is left untouched
+        let zip_id = add_titled_book(&db, "Emma", "Jane Austen", "zip");
+        let notes = dir.path().join("emma-notes.txt");
+        std::fs::write(&notes, "Notes on Emma.").unwrap();
+        assert!(matches!(
+            add_book_format(&db, zip_id, &notes.to_string_lossy(), dir.path()),
+            Err(ShioriError::InvalidFormat(_))
+        ));
+        assert!(set_primary_format(&db, zip_id, "txt").is_err());
+        assert_eq!(get_book_by_id(&db, zip_id).unwrap().file_format, "zip");
+        assert!(get_book_formats(&db, zip_id).unwrap().is_empty());
+
+        // DjVu books keep their file as the primary format
+        let djvu_id = add_titled_book(&db, "Mansfield Park", "Jane Austen", "djvu");
+        let notes = dir.path().join("mansfield-notes.txt");
+        std::fs::write(&notes, "Notes on Mansfield Park.").unwrap();
+        let notes = notes.to_string_lossy().to_string();
+        add_book_format(&db, djvu_id, &notes, dir.path()).unwrap();
+        let formats = get_book_formats(&db, djvu_id).unwrap();
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[0].format, "djvu");
+        assert!(formats[0].is_primary);
+        set_primary_format(&db, djvu_id, "txt").unwrap();
+        assert_eq!(get_book_by_id(&db, djvu_id).unwrap().file_path, notes);
+        assert_eq!(
+            get_book_format_path(&db, djvu_id, "djvu").unwrap(),
+            "/dummy/path/Mansfield Park-Jane Austen.djvu"
+        );
+    }
+
+    #[test]
+    fn test_merge_books_consolidates_into_kept_book() {
+        let (db, _dir) = setup_test_db();
+        let keep = add_titled_book(&db, "Dune", "Frank Herbert", "epub");
+        let merged = add_titled_book(&db, "Dune", "Frank Herbert", "pdf");
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute_batch(&format!(
+                "INSERT INTO tags (name) VALUES ('classic');
+                 INSERT INTO books_tags (book_id, tag_id) SELECT {merged}, id FROM tags WHERE name = 'classic';
+                 INSERT INTO annotations (book_id, type, location) VALUES ({merged}, 'highlight', 'ch1');
+                 INSERT INTO doodles (book_id, page_number, strokes_json) VALUES ({merged}, '3', '[]');
+                 INSERT INTO book_preference_overrides (book_id, font_size) VALUES ({merged}, 22);
+                 INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+                 VALUES ({keep}, 'a', 10, '2026-01-01T00:00:00+00:00'),
+                        ({merged}, 'b', 60, '2026-02-01T00:00:00+00:00');",
+            ))
+            .unwrap();
+        }
+
+        assert_eq!(merge_books(&db, keep, &[merged, keep]).unwrap(), 1);
+
+        assert!(get_book_by_id(&db, merged).is_err());
+        let kept = get_book_by_id(&db, keep).unwrap();
+        assert_eq!(kept.tags.len(), 1);
+        assert_eq!(kept.tags[0].name, "classic");
+
+        let conn = db.get_connection().unwrap();
+        let formats: Vec<(String, bool)> = conn
+            .prepare("SELECT format, is_primary FROM book_formats WHERE book_id = ?1 ORDER BY id")
+            .unwrap()
+            .query_map([keep], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            formats,
+            vec![("epub".to_string(), true), ("pdf".to_string(), false)]
+        );
+        let annotations: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM annotations WHERE book_id = ?1",
+                [keep],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(annotations, 1);
+        let progress: f64 = conn
+            .query_row(
+                "SELECT progress_percent FROM reading_progress WHERE book_id = ?1",
+                [keep],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(progress, 60.0);
+        let moved: (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM doodles WHERE book_id = ?1),
+                        (SELECT COUNT(*) FROM book_preference_overrides WHERE book_id = ?1)",
+                [keep],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(moved, (1, 1));
+
+        assert!(merge_books(&db, keep, &[9999]).is_err());
+
+        // A file book_formats can't hold stops the merge instead of being dropped
+        let zip = add_titled_book(&db, "Dune", "Frank Herbert", "zip");
+        assert!(matches!(
+            merge_books(&db, keep, &[zip]),
+            Err(ShioriError::InvalidFormat(_))
+        ));
+        assert!(get_book_by_id(&db, zip).is_ok());
+
+        // A DjVu copy is carried over as another format
+        let djvu = add_titled_book(&db, "Dune", "Frank Herbert", "djvu");
+        assert_eq!(merge_books(&db, keep, &[djvu]).unwrap(), 1);
+        assert!(get_book_by_id(&db, djvu).is_err());
+        assert_eq!(
+            get_book_format_path(&db, keep, "djvu").unwrap(),
+            "/dummy/path/Dune-Frank Herbert.djvu"
+        );
+    }
+
+    #[test]
+    fn test_regenerate_missing_covers() {
+        let (db, dir) = setup_test_db();
+        let covers_dir = dir.path().join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+        let intact_cover = covers_dir.join("intact.webp");
+        std::fs::write(&intact_cover, b"cover").unwrap();
+
+        let add = |title: &str, cover_path: String| {
+            let mut book = create_test_book();
+            book.title = title.to_string();
+            book.file_path = format!("/dummy/path/{}.epub", title);
+            book.file_hash = Some(format!("hash-{}", title));
+            book.cover_path = Some(cover_path);
+            add_book(&db, book).unwrap()
+        };
+        let broken = add("Broken", "/deleted/cover.jpg".to_string());
+        let intact = add("Intact", intact_cover.to_string_lossy().to_string());
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let regenerated = regenerate_missing_covers(&db, &covers_dir, |done, total| {
+            progress.lock().unwrap().push((done, total))
+        })
+        .unwrap();
+        assert_eq!(regenerated, 1);
+        assert_eq!(*progress.lock().unwrap(), vec![(1, 1)]);
+
+        let broken_book = get_book_by_id(&db, broken).unwrap();
+        let new_cover = broken_book.cover_path.unwrap();
+        assert!(new_cover.ends_with(&format!("{}.webp", broken_book.uuid)));
+        assert!(image::open(&new_cover).is_ok());
+        assert_eq!(
+            get_book_by_id(&db, intact).unwrap().cover_path,
+            Some(intact_cover.to_string_lossy().to_string())
+        );
+
+        // Nothing left to fix on a second run
+        assert_eq!(
+            regenerate_missing_covers(&db, &covers_dir, |_, _| {}).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_validate_library_flags_missing_and_changed_files() {
+        let (db, dir) = setup_test_db();
+        let add = |name: &str, format: &str, contents: Option<&[u8]>, hash: Option<String>| {
+            let path = dir.path().join(name);
+            if let Some(contents) = contents {
+                std::fs::write(&path, contents).unwrap();
+            }
+            let mut book = create_test_book();
+            book.title = name.to_string();
+            book.file_path = path.to_string_lossy().to_string();
+            book.file_format = format.to_string();
+            book.file_hash = hash.or_else(|| calculate_file_hash(&book.file_path).ok());
+            add_book(&db, book).unwrap()
+        };
+
+        let intact = add("intact.txt", "txt", Some(b"plain text"), None);
+        let missing = add("gone.epub", "epub", None, Some("hash-gone".to_string()));
+        let changed = add(
+            "changed.txt",
+            "txt",
+            Some(b"edited since import"),
+            Some("hash-at-import".to_string()),
+        );
+        let corrupt = add("broken.pdf", "pdf", Some(b"not a pdf at all"), None);
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let report = validate_library(&db, |done, total| {
+            progress.lock().unwrap().push((done, total))
+        })
+        .unwrap();
+        assert_eq!(*progress.lock().unwrap(), vec![(4, 4)]);
+
+        let status_of = |id: i64| report.iter().find(|r| r.book_id == id).unwrap().status;
+        assert_eq!(status_of(intact), IntegrityStatus::Ok);
+        assert_eq!(status_of(missing), IntegrityStatus::Missing);
+        assert_eq!(status_of(changed), IntegrityStatus::HashMismatch);
+        assert_eq!(status_of(corrupt), IntegrityStatus::Corrupt);
+    }
 }
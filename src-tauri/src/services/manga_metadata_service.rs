@@ -8,9 +8,10 @@
 /// - Community ratings
 /// - Publication information
 use crate::error::{Result, ShioriError};
-use reqwest::Client;
+use crate::utils::http;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
 
 // ═══════════════════════════════════════════════════════════
 // API TYPES
@@ -32,8 +33,16 @@ pub struct MangaMetadata {
     pub status: String, // FINISHED, RELEASING, NOT_YET_RELEASED, etc.
     pub start_year: Option<i32>,
     pub authors: Vec<String>,
+    /// AniList media format (MANGA, ONE_SHOT, NOVEL, ...)
+    #[serde(default)]
+    pub format: Option<String>,
 }
 
+/// AniList formats treated as manga
+const MANGA_FORMATS: &[&str] = &["MANGA", "ONE_SHOT"];
+/// AniList format used for light novels (still `type: MANGA` on AniList)
+const NOVEL_FORMATS: &[&str] = &["NOVEL"];
+
 #[derive(Debug, Serialize)]
 struct GraphQLQuery {
     query: String,
@@ -74,6 +83,7 @@ struct MediaData {
     #[serde(rename = "startDate")]
     start_date: DateData,
     staff: Option<StaffConnection>,
+    format: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -121,7 +131,6 @@ struct StaffName {
 // ═══════════════════════════════════════════════════════════
 
 pub struct MangaMetadataService {
-    client: Client,
     api_url: String,
 }
 
@@ -133,14 +142,7 @@ const MAX_IMAGE_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
 
 impl MangaMetadataService {
     pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Shiori/0.1.0")
-            .build()
-            .map_err(|e| ShioriError::Other(format!("Failed to create HTTP client: {}", e)))?;
-
         Ok(Self {
-            client,
             api_url: "https://graphql.anilist.co".to_string(),
         })
     }
@@ -188,13 +190,59 @@ impl MangaMetadataService {
 
     /// Search for manga by title
     pub async fn search_manga(&self, title: &str, include_nsfw: bool) -> Result<Vec<MangaMetadata>> {
-        log::info!("[MangaMetadataService] Searching for: '{}', nsfw: {}", title, include_nsfw);
+        self.search_media(title, include_nsfw, MANGA_FORMATS).await
+    }
+
+    /// Search for light novels by title (AniList `type: MANGA`, `format: NOVEL`)
+    pub async fn search_light_novel(
+        &self,
+        title: &str,
+        include_nsfw: bool,
+    ) -> Result<Vec<MangaMetadata>> {
+        self.search_media(title, include_nsfw, NOVEL_FORMATS).await
+    }
+
+    /// Search both light novels and manga. A title that exists as both
+    /// (e.g. a novel and its adaptation) yields both entries, novels first.
+    pub async fn search_light_novel_or_manga(
+        &self,
+        title: &str,
+        include_nsfw: bool,
+    ) -> Result<Vec<MangaMetadata>> {
+        let (novels, manga) = tokio::try_join!(
+            self.search_light_novel(title, include_nsfw),
+            self.search_manga(title, include_nsfw)
+        )?;
+
+        let mut results = novels;
+        for entry in manga {
+            if !results.iter().any(|r| r.anilist_id == entry.anilist_id) {
+                results.push(entry);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Search AniList `MANGA`-type media restricted to the given formats
+    async fn search_media(
+        &self,
+        title: &str,
+        include_nsfw: bool,
+        formats: &[&str],
+    ) -> Result<Vec<MangaMetadata>> {
+        log::info!(
+            "[MangaMetadataService] Searching for: '{}', nsfw: {}, formats: {:?}",
+            title,
+            include_nsfw,
+            formats
+        );
 
         let query = r#"
-            query ($search: String, $isAdult: Boolean) {
+            query ($search: String, $isAdult: Boolean, $formats: [MediaFormat]) {
                 Page(page: 1, perPage: 5) {
-                    media(search: $search, type: MANGA, format_in: [MANGA, ONE_SHOT], sort: SEARCH_MATCH, isAdult: $isAdult) {
+                    media(search: $search, type: MANGA, format_in: $formats, sort: SEARCH_MATCH, isAdult: $isAdult) {
                         id
+                        format
                         title {
                             romaji
                             english
@@ -229,7 +277,8 @@ impl MangaMetadataService {
         "#;
 
         let mut variables = serde_json::json!({
-            "search": title
+            "search": title,
+            "formats": formats,
         });
 
         if !include_nsfw {
@@ -241,8 +290,7 @@ impl MangaMetadataService {
             variables,
         };
 
-        let response = self
-            .client
+        let response = http::client()
             .post(&self.api_url)
             .json(&payload)
             .send()
@@ -287,8 +335,9 @@ impl MangaMetadataService {
 
         let query = r#"
             query ($id: Int) {
-                Media(id: $id, type: MANGA, format_in: [MANGA, ONE_SHOT]) {
+                Media(id: $id, type: MANGA, format_in: [MANGA, ONE_SHOT, NOVEL]) {
                     id
+                    format
                     title {
                         romaji
                         english
@@ -330,8 +379,7 @@ impl MangaMetadataService {
             variables,
         };
 
-        let response = self
-            .client
+        let response = http::client()
             .post(&self.api_url)
             .json(&payload)
             .send()
@@ -365,8 +413,7 @@ impl MangaMetadataService {
     pub async fn download_cover(&self, url: &str) -> Result<Vec<u8>> {
         log::info!("[MangaMetadataService] Downloading cover from: {}", url);
 
-        let response = self
-            .client
+        let response = http::client()
             .get(url)
             .send()
             .await
@@ -421,6 +468,7 @@ impl MangaMetadataService {
             status: media.status,
             start_year: media.start_date.year,
             authors,
+            format: media.format,
         }
     }
 }
@@ -438,7 +486,7 @@ impl Default for MangaMetadataService {
 /// Strip all HTML tags from a string, converting block-level elements to newlines.
 /// Handles AniList descriptions which contain <br>, <p>, <i>, <b>, <em>, <strong>,
 /// <ul>, <li>, <a href="...">, and other common HTML tags.
-fn strip_html_tags(html: &str) -> String {
+pub(crate) fn strip_html_tags(html: &str) -> String {
     // First pass: convert block-level and line-break tags to newlines
     let with_newlines = html
         .replace("<br>", "\n")
@@ -474,53 +522,107 @@ fn strip_html_tags(html: &str) -> String {
 // TITLE PARSING
 // ═══════════════════════════════════════════════════════════
 
+/// Structured information parsed from a manga archive filename
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedMangaFilename {
+    pub series: String,
+    pub volume: Option<u32>,
+    pub chapter: Option<f32>,
+    pub year: Option<u32>,
+    pub title: Option<String>,
+}
+
+static YEAR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[\(\[]((?:19|20)\d{2})[\)\]]").expect("valid year regex"));
+static BRACKET_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[[^\]]*\]|\([^)]*\)|\{[^}]*\}").expect("valid bracket regex")
+});
+static VOLUME_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:^|[\s-])(?:vol(?:ume)?\.?\s*|v)(\d{1,4})\b").expect("valid volume regex")
+});
+static CHAPTER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:^|[\s-])(?:ch(?:apter)?\.?\s*|c|#)(\d{1,4}(?:\.\d+)?)\b")
+        .expect("valid chapter regex")
+});
+/// Bare trailing chapter numbers: "Series - 110" or zero-padded "Series 045"
+static TRAILING_CHAPTER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\s-\s*(\d{1,4}(?:\.\d+)?)|\s(0\d{1,3}(?:\.\d+)?))\s*$")
+        .expect("valid trailing chapter regex")
+});
+
+/// Parse a manga filename into series, volume, chapter, year and chapter title.
+/// Handles:
+/// - "SeriesName v03 c015 (2021).cbz" → volume 3, chapter 15, year 2021
+/// - "[Group] Series Vol.3 Ch. 15.5 - Title.cbz" → decimal chapters and titles
+/// - "Series_Vol_05.cbz", "Series - 110.cbz", "Series 045.cbz"
+pub fn parse_manga_filename(filename: &str) -> ParsedMangaFilename {
+    let lower = filename.to_lowercase();
+    let stem = [".cbz", ".cbr", ".cb7", ".zip", ".rar"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| &filename[..filename.len() - ext.len()])
+        .unwrap_or(filename);
+
+    let text = stem.replace('_', " ");
+    let year = YEAR_RE
+        .captures(&text)
+        .and_then(|c| c[1].parse::<u32>().ok());
+
+    // Drop scanlation groups, years and tags like (Digital) / [MangaPlus]
+    let text = BRACKET_RE.replace_all(&text, " ").to_string();
+
+    let volume = VOLUME_RE.captures(&text);
+    let chapter = CHAPTER_RE
+        .captures(&text)
+        .or_else(|| volume.is_none().then(|| TRAILING_CHAPTER_RE.captures(&text)).flatten());
+
+    let spans: Vec<(usize, usize)> = volume
+        .iter()
+        .chain(chapter.iter())
+        .map(|c| {
+            let m = c.get(0).unwrap();
+            (m.start(), m.end())
+        })
+        .collect();
+    let series_end = spans.iter().map(|s| s.0).min().unwrap_or(text.len());
+    let tokens_end = spans.iter().map(|s| s.1).max().unwrap_or(text.len());
+
+    let clean = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_matches(|c: char| c == '-' || c == ':' || c == '.' || c.is_whitespace())
+            .to_string()
+    };
+
+    let series = clean(&text[..series_end]);
+    let title = Some(clean(&text[tokens_end..])).filter(|t| !t.is_empty());
+
+    ParsedMangaFilename {
+        series,
+        volume: volume.and_then(|c| c[1].parse().ok()),
+        chapter: chapter.and_then(|c| {
+            c.get(1)
+                .or_else(|| c.get(2))
+                .and_then(|m| m.as_str().parse().ok())
+        }),
+        year,
+        title,
+    }
+}
+
 /// Parse manga title from filename
 /// Handles various naming conventions:
 /// - "One Piece v103.cbz" → "One Piece"
 /// - "[Group] Series Name Ch.123.cbz" → "Series Name"
 /// - "SeriesName_Vol_05.cbz" → "SeriesName"
 pub fn parse_manga_title(filename: &str) -> String {
-    let mut title = filename
-        .trim_end_matches(".cbz")
-        .trim_end_matches(".cbr")
-        .trim_end_matches(".zip")
-        .to_string();
-
-    // Remove group tags: [Group] or (Group)
-    if let Some(_start) = title.find('[') {
-        if let Some(end) = title.find(']') {
-            title = title[end + 1..].trim().to_string();
-        }
-    }
-    if let Some(start) = title.find('(') {
-        if let Some(end) = title.find(')') {
-            title = title[..start].to_string() + &title[end + 1..];
-        }
-    }
-
-    // Remove volume/chapter indicators (case insensitive)
-    let patterns = [
-        r" v\d+",
-        r" vol\.?\s*\d+",
-        r" volume\s*\d+",
-        r" ch\.?\s*\d+",
-        r" chapter\s*\d+",
-        r"_v\d+",
-        r"_vol_\d+",
-        r"_ch_\d+",
-    ];
-
-    for pattern in &patterns {
-        if let Ok(re) = regex::Regex::new(&format!("(?i){}", pattern)) {
-            title = re.replace_all(&title, "").to_string();
-        }
+    let parsed = parse_manga_filename(filename);
+    if parsed.series.is_empty() {
+        // Nothing but volume/chapter markers: fall back to the raw name
+        return parsed.title.unwrap_or_else(|| filename.trim().to_string());
     }
-
-    // Replace underscores and multiple spaces
-    title = title.replace('_', " ");
-    title = title.split_whitespace().collect::<Vec<_>>().join(" ");
-
-    title.trim().to_string()
+    parsed.series
 }
 
 #[cfg(test)]
@@ -567,4 +669,39 @@ mod tests {
             "Vinland Saga"
         );
     }
+
+    #[test]
+    fn test_parse_manga_filename_conventions() {
+        // (filename, series, volume, chapter, year, title)
+        type Case = (&'static str, &'static str, Option<u32>, Option<f32>, Option<u32>, Option<&'static str>);
+        let cases: &[Case] = &[
+            ("SeriesName v03 c015 (2021).cbz", "SeriesName", Some(3), Some(15.0), Some(2021), None),
+            ("[Group] Attack on Titan Ch.139.cbz", "Attack on Titan", None, Some(139.0), None, None),
+            ("One Piece v103.cbz", "One Piece", Some(103), None, None, None),
+            ("Berserk_Vol_41.cbz", "Berserk", Some(41), None, None, None),
+            ("Chainsaw Man - Ch. 15.5.cbz", "Chainsaw Man", None, Some(15.5), None, None),
+            (
+                "Vinland Saga Vol.3 Ch.20 - The Journey [Scans] (2008).cbr",
+                "Vinland Saga",
+                Some(3),
+                Some(20.0),
+                Some(2008),
+                Some("The Journey"),
+            ),
+            ("(Digital) Vinland Saga Volume 12.cbz", "Vinland Saga", Some(12), None, None, None),
+            ("Kaguya-sama wa Kokurasetai c123 [MangaPlus].cbz", "Kaguya-sama wa Kokurasetai", None, Some(123.0), None, None),
+            ("Dorohedoro 045.cbz", "Dorohedoro", None, Some(45.0), None, None),
+            ("Solo Leveling - 110.cbz", "Solo Leveling", None, Some(110.0), None, None),
+            ("Yotsuba&! v01 (2016) (Digital) (LuCaZ).cbz", "Yotsuba&!", Some(1), None, Some(2016), None),
+        ];
+
+        for (filename, series, volume, chapter, year, title) in cases {
+            let parsed = parse_manga_filename(filename);
+            assert_eq!(parsed.series, *series, "series for {}", filename);
+            assert_eq!(parsed.volume, *volume, "volume for {}", filename);
+            assert_eq!(parsed.chapter, *chapter, "chapter for {}", filename);
+            assert_eq!(parsed.year, *year, "year for {}", filename);
+            assert_eq!(parsed.title.as_deref(), *title, "title for {}", filename);
+        }
+    }
 }
@@ -3,10 +3,13 @@
 /// Thread-safe service for extracting and caching manga page images.
 /// Uses natural sort for page ordering and optional image downscaling.
 use crate::error::{Result, ShioriError};
+use crate::services::cbr_archive::CbrArchive;
+use crate::utils::natural_sort;
 use image::GenericImageView;
-use std::collections::HashMap;
+use lru::LruCache;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{Cursor, Read};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use zip::ZipArchive;
 
 // ═══════════════════════════════════════════════════════════
@@ -24,6 +27,36 @@ pub struct MangaMetadata {
     pub page_dimensions: Vec<(u32, u32)>,
 }
 
+/// Page order for two-page spreads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl ReadingDirection {
+    /// Parse the `manga_direction` preference value (`ltr` or `rtl`)
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "ltr" => Ok(Self::LeftToRight),
+            "rtl" => Ok(Self::RightToLeft),
+            other => Err(ShioriError::Validation(format!(
+                "Reading direction must be 'ltr' or 'rtl', got '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// One screen of double-page mode. `left` and `right` are screen positions,
+/// already swapped for right-to-left reading.
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MangaSpread {
+    Single { page: usize },
+    Pair { left: usize, right: usize },
+}
+
 #[allow(dead_code)]
 struct OpenManga {
     file_path: String,
@@ -31,6 +64,8 @@ struct OpenManga {
     /// `try_clone()` instead of re-opening from the filesystem path.
     file_handle: std::fs::File,
     sorted_pages: Vec<String>,
+    /// Set for CBR files; page bytes come from here instead of the ZIP
+    rar: Option<Arc<CbrArchive>>,
     page_dimensions: Vec<(u32, u32)>,
     title: String,
     has_comic_info: bool,
@@ -39,58 +74,328 @@ struct OpenManga {
     writer: Option<String>,
 }
 
-/// LRU-ish page cache entry
-struct CachedPage {
-    data: Vec<u8>,
-    last_access: std::time::Instant,
+/// Cache key: (book id, page index, max dimension)
+type PageKey = (i64, usize, u32);
+
+/// Page cache budget for a `performance_mode` preference value
+pub fn page_cache_budget_for_performance_mode(mode: &str) -> usize {
+    match mode {
+        "large_library" => 400 * 1024 * 1024,
+        "low_memory" => 48 * 1024 * 1024,
+        _ => 200 * 1024 * 1024,
+    }
+}
+
+/// Page cache usage, as reported by `get_manga_cache_stats`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MangaCacheStats {
+    pub used_bytes: usize,
+    pub budget_bytes: usize,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub hit_rate: f64,
+}
+
+/// Decoded pages, least recently used first out once `budget_bytes` is hit
+struct PageCache {
+    lru: LruCache<PageKey, Vec<u8>>,
+    used_bytes: usize,
+    budget_bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl PageCache {
+    fn new(budget_bytes: usize) -> Self {
+        Self {
+            lru: LruCache::unbounded(),
+            used_bytes: 0,
+            budget_bytes,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn get(&mut self, key: &PageKey) -> Option<Vec<u8>> {
+        match self.lru.get(key) {
+            Some(data) => {
+                self.hits += 1;
+                Some(data.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: PageKey, data: Vec<u8>) {
+        // A page bigger than the whole budget would only flush everything else
+        if data.len() > self.budget_bytes {
+            return;
+        }
+        if let Some(old) = self.lru.pop(&key) {
+            self.used_bytes -= old.len();
+        }
+        self.evict_to_fit(data.len());
+        self.used_bytes += data.len();
+        self.lru.put(key, data);
+    }
+
+    /// Drop least-recently-used pages until `incoming` more bytes fit
+    fn evict_to_fit(&mut self, incoming: usize) {
+        while self.used_bytes + incoming > self.budget_bytes {
+            let Some((_, evicted)) = self.lru.pop_lru() else {
+                break;
+            };
+            self.used_bytes -= evicted.len();
+            self.evictions += 1;
+        }
+    }
+
+    fn set_budget(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.evict_to_fit(0);
+    }
+
+    fn remove_book(&mut self, book_id: i64) {
+        let keys: Vec<PageKey> = self
+            .lru
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|key| key.0 == book_id)
+            .collect();
+        for key in keys {
+            if let Some(data) = self.lru.pop(&key) {
+                self.used_bytes -= data.len();
+            }
+        }
+    }
+
+    fn stats(&self) -> MangaCacheStats {
+        let lookups = self.hits + self.misses;
+        MangaCacheStats {
+            used_bytes: self.used_bytes,
+            budget_bytes: self.budget_bytes,
+            entries: self.lru.len(),
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                self.hits as f64 / lookups as f64
+            },
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════
-// NATURAL SORT
+// SPREADS
 // ═══════════════════════════════════════════════════════════
 
-/// Generate a sort key that handles embedded numbers naturally.
-/// "page2.jpg" < "page10.jpg" (unlike lexicographic sort)
-fn natural_sort_key(s: &str) -> Vec<NaturalChunk> {
-    let mut chunks = Vec::new();
-    let mut chars = s.chars().peekable();
-
-    while chars.peek().is_some() {
-        if chars.peek().map_or(false, |c| c.is_ascii_digit()) {
-            // Collect digit run
-            let mut num_str = String::new();
-            while chars.peek().map_or(false, |c| c.is_ascii_digit()) {
-                num_str.push(chars.next().unwrap());
+/// Pair consecutive portrait pages. A landscape page is already a full
+/// spread, so it stands alone and pairing restarts after it; an unpaired
+/// portrait page before it (or at the end) is shown on its own.
+fn pair_spreads(dimensions: &[(u32, u32)], direction: ReadingDirection) -> Vec<MangaSpread> {
+    let mut spreads = Vec::new();
+    let mut pending: Option<usize> = None;
+    for (page, &(width, height)) in dimensions.iter().enumerate() {
+        if width > height {
+            if let Some(first) = pending.take() {
+                spreads.push(MangaSpread::Single { page: first });
             }
-            let num: u64 = num_str.parse().unwrap_or(0);
-            chunks.push(NaturalChunk::Number(num));
-        } else {
-            // Collect non-digit run (case-insensitive)
-            let mut text = String::new();
-            while chars.peek().map_or(false, |c| !c.is_ascii_digit()) {
-                text.push(chars.next().unwrap().to_ascii_lowercase());
-            }
-            chunks.push(NaturalChunk::Text(text));
+            spreads.push(MangaSpread::Single { page });
+            continue;
+        }
+        match pending.take() {
+            None => pending = Some(page),
+            Some(first) => spreads.push(match direction {
+                ReadingDirection::LeftToRight => MangaSpread::Pair {
+                    left: first,
+                    right: page,
+                },
+                ReadingDirection::RightToLeft => MangaSpread::Pair {
+                    left: page,
+                    right: first,
+                },
+            }),
         }
     }
-    chunks
+    if let Some(page) = pending {
+        spreads.push(MangaSpread::Single { page });
+    }
+    spreads
+}
+
+// ═══════════════════════════════════════════════════════════
+// LONG STRIP
+// ═══════════════════════════════════════════════════════════
+
+/// A page's place in the long-strip column, in column pixels
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct StripPage {
+    pub index: usize,
+    pub width: u32,
+    pub height: u32,
+    pub offset_y: u64,
+}
+
+/// Stack pages top to bottom, each scaled to `column_width` wide
+fn strip_layout(dimensions: &[(u32, u32)], column_width: u32) -> Vec<StripPage> {
+    let mut offset_y = 0u64;
+    dimensions
+        .iter()
+        .enumerate()
+        .map(|(index, &(width, height))| {
+            let scaled_height = if width == 0 {
+                height
+            } else {
+                (height as f64 * column_width as f64 / width as f64).round() as u32
+            };
+            let page = StripPage {
+                index,
+                width: column_width,
+                height: scaled_height,
+                offset_y,
+            };
+            offset_y += scaled_height as u64;
+            page
+        })
+        .collect()
+}
+
+/// Indices of pages overlapping the viewport `[scroll_y, scroll_y + viewport_height)`
+/// followed by up to `ahead` pages below it
+fn strip_pages_to_load(
+    manifest: &[StripPage],
+    scroll_y: u64,
+    viewport_height: u64,
+    ahead: usize,
+) -> Vec<usize> {
+    let bottom = scroll_y.saturating_add(viewport_height);
+    let first = manifest.partition_point(|page| page.offset_y + page.height as u64 <= scroll_y);
+    let visible_end = manifest.partition_point(|page| page.offset_y < bottom);
+    let end = visible_end
+        .max(first)
+        .saturating_add(ahead)
+        .min(manifest.len());
+    (first..end).collect()
+}
+
+/// Image size from its header, falling back to a full decode
+fn image_dimensions(buf: &[u8]) -> Option<(u32, u32)> {
+    // Try header-only decode first (fast — doesn't load full pixel data)
+    let reader = image::ImageReader::new(Cursor::new(buf))
+        .with_guessed_format()
+        .ok()?;
+    if let Ok((w, h)) = reader.into_dimensions() {
+        return Some((w, h));
+    }
+
+    // Fallback: full decode
+    let img = image::load_from_memory(buf).ok()?;
+    Some(img.dimensions())
+}
+
+/// Downscale a page to fit `max_dimension` (0 keeps the original size) and
+/// make sure the webview can show it: JPEG, PNG and GIF pass through, other
+/// formats (WebP, AVIF, BMP) are transcoded to PNG.
+fn prepare_page(image_bytes: Vec<u8>, max_dimension: u32) -> Result<Vec<u8>> {
+    let format = match image::guess_format(&image_bytes) {
+        Ok(format) => format,
+        // Nothing to resize, so hand unrecognised bytes to the webview as-is
+        Err(_) if max_dimension == 0 => return Ok(image_bytes),
+        Err(e) => return Err(ShioriError::Other(e.to_string())),
+    };
+    let passthrough = matches!(
+        format,
+        image::ImageFormat::Jpeg | image::ImageFormat::Png | image::ImageFormat::Gif
+    );
+    if passthrough && max_dimension == 0 {
+        return Ok(image_bytes);
+    }
+
+    let img = image::load_from_memory_with_format(&image_bytes, format)
+        .map_err(|e| ShioriError::Other(format!("Failed to decode {:?} page: {}", format, e)))?;
+
+    let mut out_bytes = Vec::new();
+    if max_dimension == 0 || (img.width() <= max_dimension && img.height() <= max_dimension) {
+        if passthrough {
+            return Ok(image_bytes);
+        }
+        img.write_to(&mut Cursor::new(&mut out_bytes), image::ImageFormat::Png)
+            .map_err(|e| ShioriError::Other(e.to_string()))?;
+        return Ok(out_bytes);
+    }
+
+    let resized = img.resize(
+        max_dimension,
+        max_dimension,
+        image::imageops::FilterType::Lanczos3,
+    );
+
+    // JPEG has no alpha channel, so flatten transparent WebP/PNG pages first
+    image::DynamicImage::ImageRgb8(resized.to_rgb8())
+        .write_to(&mut Cursor::new(&mut out_bytes), image::ImageFormat::Jpeg)
+        .map_err(|e| ShioriError::Other(e.to_string()))?;
+
+    Ok(out_bytes)
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum NaturalChunk {
-    Text(String),
-    Number(u64),
+/// Extensions of images that can appear as comic pages
+const PAGE_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp", "avif"];
+
+/// Page formats the decode path can read. AVIF decoding needs the
+/// `avif-decode` feature (links the system dav1d library).
+pub fn supported_image_formats() -> &'static [&'static str] {
+    if cfg!(feature = "avif-decode") {
+        PAGE_IMAGE_EXTENSIONS
+    } else {
+        &PAGE_IMAGE_EXTENSIONS[..PAGE_IMAGE_EXTENSIONS.len() - 1]
+    }
 }
 
-/// Check if filename is an image (matches CbzFormatAdapter::is_image_file)
-fn is_image_file(filename: &str) -> bool {
-    let lower = filename.to_lowercase();
-    lower.ends_with(".jpg")
-        || lower.ends_with(".jpeg")
-        || lower.ends_with(".png")
-        || lower.ends_with(".gif")
-        || lower.ends_with(".webp")
-        || lower.ends_with(".bmp")
+/// Check if filename is a page image we can decode. Images in formats this
+/// build can't decode are skipped rather than failing the archive.
+pub(crate) fn is_image_file(filename: &str) -> bool {
+    filename
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| supported_image_formats().contains(&ext.to_lowercase().as_str()))
+}
+
+/// Log one warning per format for the pages of a volume that this build
+/// can't decode, returning how many were skipped
+pub(crate) fn warn_undecodable_pages<'a>(
+    volume: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> usize {
+    let mut skipped: BTreeMap<String, usize> = BTreeMap::new();
+    for name in names {
+        let Some((_, ext)) = name.rsplit_once('.') else {
+            continue;
+        };
+        let ext = ext.to_lowercase();
+        if PAGE_IMAGE_EXTENSIONS.contains(&ext.as_str())
+            && !supported_image_formats().contains(&ext.as_str())
+        {
+            *skipped.entry(ext).or_default() += 1;
+        }
+    }
+    for (ext, count) in &skipped {
+        log::warn!(
+            "[manga] Skipping {} {} page(s) in '{}': {} decoding is not enabled in this build",
+            count,
+            ext,
+            volume,
+            ext
+        );
+    }
+    skipped.values().sum()
 }
 
 // ═══════════════════════════════════════════════════════════
@@ -99,21 +404,26 @@ fn is_image_file(filename: &str) -> bool {
 
 pub struct MangaService {
     open_books: Mutex<HashMap<i64, OpenManga>>,
-    page_cache: Mutex<HashMap<(i64, usize, u32), CachedPage>>,
-    max_cache_entries: usize,
-    max_cache_bytes: usize,
+    page_cache: Mutex<PageCache>,
 }
 
 impl MangaService {
-    pub fn new() -> Self {
+    pub fn new(cache_budget_bytes: usize) -> Self {
         Self {
             open_books: Mutex::new(HashMap::new()),
-            page_cache: Mutex::new(HashMap::new()),
-            max_cache_entries: 100,
-            max_cache_bytes: 200 * 1024 * 1024, // 200MB
+            page_cache: Mutex::new(PageCache::new(cache_budget_bytes)),
         }
     }
 
+    /// Change the page cache budget; shrinking evicts the oldest pages
+    pub fn set_cache_budget(&self, budget_bytes: usize) {
+        self.page_cache.lock().unwrap().set_budget(budget_bytes);
+    }
+
+    pub fn cache_stats(&self) -> MangaCacheStats {
+        self.page_cache.lock().unwrap().stats()
+    }
+
     /// Open a manga archive and prepare it for reading
     pub fn open(&self, book_id: i64, path: &str) -> Result<MangaMetadata> {
         println!("\n=== OPEN_MANGA ===");
@@ -135,27 +445,46 @@ impl MangaService {
             .try_clone()
             .map_err(|e| ShioriError::Other(format!("Failed to clone file handle: {}", e)))?;
 
-        let mut archive = ZipArchive::new(file_for_archive)
-            .map_err(|e| ShioriError::InvalidFormat(format!("Invalid CBZ/ZIP file: {}", e)))?;
+        let is_rar = {
+            let mut magic = [0u8; 4];
+            let mut probe = &file_for_archive;
+            probe.read_exact(&mut magic).is_ok() && magic == *b"Rar!"
+        };
 
-        // Collect and naturally sort image filenames
-        let mut image_files: Vec<String> = Vec::new();
-        for i in 0..archive.len() {
-            if let Ok(file) = archive.by_index(i) {
-                let name = file.name().to_string();
-                if is_image_file(&name) {
-                    image_files.push(name);
+        let (image_files, rar, (has_comic_info, series, volume, writer)) = if is_rar {
+            // RAR pages (including those in subfolders) arrive naturally sorted
+            let rar = CbrArchive::open(std::path::Path::new(path))?;
+            let image_files = rar.page_names().to_vec();
+            (image_files, Some(Arc::new(rar)), (false, None, None, None))
+        } else {
+            let mut archive = ZipArchive::new(file_for_archive)
+                .map_err(|e| ShioriError::InvalidFormat(format!("Invalid CBZ/ZIP file: {}", e)))?;
+
+            warn_undecodable_pages(path, archive.file_names());
+
+            // Collect and naturally sort image filenames
+            let mut image_files: Vec<String> = Vec::new();
+            for i in 0..archive.len() {
+                if let Ok(file) = archive.by_index(i) {
+                    let name = file.name().to_string();
+                    if is_image_file(&name) {
+                        image_files.push(name);
+                    }
                 }
             }
-        }
 
-        image_files.sort_by(|a, b| natural_sort_key(a).cmp(&natural_sort_key(b)));
+            image_files.sort_by(|a, b| natural_sort::compare(a, b));
 
-        if image_files.is_empty() {
-            return Err(ShioriError::InvalidFormat(
-                "No image files found in manga archive".to_string(),
-            ));
-        }
+            if image_files.is_empty() {
+                return Err(ShioriError::InvalidFormat(
+                    "No image files found in manga archive".to_string(),
+                ));
+            }
+
+            // Try to parse ComicInfo.xml
+            let comic_info = Self::try_parse_comic_info(&mut archive);
+            (image_files, None, comic_info)
+        };
 
         let page_count = image_files.len();
         println!("Found {} pages", page_count);
@@ -171,9 +500,6 @@ impl MangaService {
             .map(|s| s.to_string_lossy().replace('_', " "))
             .unwrap_or_else(|| "Unknown Manga".to_string());
 
-        // Try to parse ComicInfo.xml
-        let (has_comic_info, series, volume, writer) = Self::try_parse_comic_info(&mut archive);
-
         let metadata = MangaMetadata {
             title: title.clone(),
             page_count,
@@ -189,6 +515,7 @@ impl MangaService {
             file_path: path.to_string(),
             file_handle: file,
             sorted_pages: image_files,
+            rar,
             page_dimensions,
             title,
             has_comic_info,
@@ -214,15 +541,11 @@ impl MangaService {
     ) -> Result<Vec<u8>> {
         // Check cache first
         let cache_key = (book_id, page_index, max_dimension);
-        {
-            let mut cache = self.page_cache.lock().unwrap();
-            if let Some(entry) = cache.get_mut(&cache_key) {
-                entry.last_access = std::time::Instant::now();
-                return Ok(entry.data.clone());
-            }
+        if let Some(data) = self.page_cache.lock().unwrap().get(&cache_key) {
+            return Ok(data);
         }
 
-        let (file_path, page_name) = {
+        let (file_path, page_name, rar) = {
             let books = self.open_books.lock().unwrap();
             let manga = books
                 .get(&book_id)
@@ -240,11 +563,14 @@ impl MangaService {
             let file_path = manga.file_path.clone();
             let page_name = manga.sorted_pages[page_index].clone();
 
-            (file_path, page_name)
+            (file_path, page_name, manga.rar.clone())
         };
 
         // Extract image bytes from ZIP (CPU intensive for large zips, use spawn_blocking)
         let image_bytes = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+            if let Some(rar) = rar {
+                return rar.get_page(page_index);
+            }
             let file = std::fs::File::open(&file_path)
                 .map_err(|e| ShioriError::Other(format!("Failed to open archive: {}", e)))?;
             let mut archive = ZipArchive::new(file).map_err(|e| {
@@ -265,44 +591,16 @@ impl MangaService {
         .map_err(|e| ShioriError::Other(format!("Task Join Error: {}", e)))??;
 
         // Optionally downscale (Also in the blocking task to avoid dropping frames)
-        let result_bytes = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
-            if max_dimension > 0 {
-                let reader = image::ImageReader::new(Cursor::new(&image_bytes))
-                    .with_guessed_format()
-                    .map_err(|e| ShioriError::Other(e.to_string()))?;
-
-                let img = reader
-                    .decode()
-                    .map_err(|e| ShioriError::Other(e.to_string()))?;
-
-                let width = img.width();
-                let height = img.height();
-
-                if width <= max_dimension && height <= max_dimension {
-                    return Ok(image_bytes);
-                }
-
-                let resized = img.resize(
-                    max_dimension,
-                    max_dimension,
-                    image::imageops::FilterType::Lanczos3,
-                );
-
-                let mut out_bytes = Vec::new();
-                resized
-                    .write_to(&mut Cursor::new(&mut out_bytes), image::ImageFormat::Jpeg)
-                    .map_err(|e| ShioriError::Other(e.to_string()))?;
-
-                Ok(out_bytes)
-            } else {
-                Ok(image_bytes)
-            }
-        })
-        .await
-        .map_err(|e| ShioriError::Other(format!("Task Join Error: {}", e)))??;
+        let result_bytes =
+            tokio::task::spawn_blocking(move || prepare_page(image_bytes, max_dimension))
+                .await
+                .map_err(|e| ShioriError::Other(format!("Task Join Error: {}", e)))??;
 
         // Cache the result
-        self.cache_page(cache_key, &result_bytes);
+        self.page_cache
+            .lock()
+            .unwrap()
+            .insert(cache_key, result_bytes.clone());
 
         Ok(result_bytes)
     }
@@ -316,12 +614,9 @@ impl MangaService {
     ) -> Result<()> {
         for &idx in page_indices {
             let cache_key = (book_id, idx, max_dimension);
-            // Skip if already cached
-            {
-                let cache = self.page_cache.lock().unwrap();
-                if cache.contains_key(&cache_key) {
-                    continue;
-                }
+            // Skip if already cached (without counting a lookup)
+            if self.page_cache.lock().unwrap().lru.contains(&cache_key) {
+                continue;
             }
             // Load the page (which also caches it)
             let _ = self.get_page(book_id, idx, max_dimension).await;
@@ -331,44 +626,63 @@ impl MangaService {
 
     /// Get page dimensions for given indices.
     /// Lazily computes real dimensions from the archive, caching them for future calls.
+    /// Pages are read without holding the open-books lock, since a CBR page
+    /// is extracted by a separate unrar process.
     pub fn get_page_dimensions(
         &self,
         book_id: i64,
         page_indices: &[usize],
     ) -> Result<Vec<(u32, u32)>> {
-        let mut books = self.open_books.lock().unwrap();
-        let manga = books
-            .get_mut(&book_id)
-            .ok_or_else(|| ShioriError::BookNotFound(format!("Manga {} not open", book_id)))?;
-
         // Check which pages still have placeholder dimensions and need resolving
         let placeholder = (800u32, 1200u32);
-        let needs_resolve: Vec<usize> = page_indices
-            .iter()
-            .copied()
-            .filter(|&idx| {
-                idx < manga.page_dimensions.len() && manga.page_dimensions[idx] == placeholder
-            })
-            .collect();
+        let (file_path, rar, needs_resolve) = {
+            let books = self.open_books.lock().unwrap();
+            let manga = books
+                .get(&book_id)
+                .ok_or_else(|| ShioriError::BookNotFound(format!("Manga {} not open", book_id)))?;
+            let needs_resolve: Vec<(usize, String)> = page_indices
+                .iter()
+                .copied()
+                .filter(|&idx| {
+                    idx < manga.page_dimensions.len()
+                        && idx < manga.sorted_pages.len()
+                        && manga.page_dimensions[idx] == placeholder
+                })
+                .map(|idx| (idx, manga.sorted_pages[idx].clone()))
+                .collect();
+            (manga.file_path.clone(), manga.rar.clone(), needs_resolve)
+        };
 
+        let mut resolved = Vec::with_capacity(needs_resolve.len());
         if !needs_resolve.is_empty() {
-            // Open fresh file handle for dimension reading to avoid concurrent access issues
-            let file_path = manga.file_path.clone();
-            if let Ok(file) = std::fs::File::open(&file_path) {
+            if let Some(rar) = rar {
+                for (idx, _) in &needs_resolve {
+                    if let Some(dims) = rar.get_page(*idx).ok().and_then(|b| image_dimensions(&b)) {
+                        resolved.push((*idx, dims));
+                    }
+                }
+            } else if let Ok(file) = std::fs::File::open(&file_path) {
+                // Fresh file handle so page reads don't share the reader's archive
                 if let Ok(mut archive) = ZipArchive::new(file) {
-                    for &idx in &needs_resolve {
-                        if idx < manga.sorted_pages.len() {
-                            let page_name = &manga.sorted_pages[idx];
-                            if let Some(dims) = Self::read_image_dimensions(&mut archive, page_name)
-                            {
-                                manga.page_dimensions[idx] = dims;
-                            }
+                    for (idx, page_name) in &needs_resolve {
+                        if let Some(dims) = Self::read_image_dimensions(&mut archive, page_name) {
+                            resolved.push((*idx, dims));
                         }
                     }
                 }
             }
         }
 
+        let mut books = self.open_books.lock().unwrap();
+        let manga = books
+            .get_mut(&book_id)
+            .ok_or_else(|| ShioriError::BookNotFound(format!("Manga {} not open", book_id)))?;
+        for (idx, dims) in resolved {
+            if idx < manga.page_dimensions.len() {
+                manga.page_dimensions[idx] = dims;
+            }
+        }
+
         let mut dims = Vec::with_capacity(page_indices.len());
         for &idx in page_indices {
             if idx < manga.page_dimensions.len() {
@@ -380,6 +694,61 @@ impl MangaService {
         Ok(dims)
     }
 
+    /// Group all pages into double-page spreads. Resolves every page's real
+    /// dimensions, so the first call on a large archive reads each header.
+    pub fn get_spreads(
+        &self,
+        book_id: i64,
+        direction: ReadingDirection,
+    ) -> Result<Vec<MangaSpread>> {
+        let page_count = self
+            .open_books
+            .lock()
+            .unwrap()
+            .get(&book_id)
+            .map(|manga| manga.sorted_pages.len())
+            .ok_or_else(|| ShioriError::BookNotFound(format!("Manga {} not open", book_id)))?;
+        if page_count == 0 {
+            return Ok(Vec::new());
+        }
+        let indices: Vec<usize> = (0..page_count).collect();
+        let dimensions = self.get_page_dimensions(book_id, &indices)?;
+        Ok(pair_spreads(&dimensions, direction))
+    }
+
+    /// Layout of every page in long-strip mode at `column_width`, so the
+    /// frontend can size the whole scroll area up front
+    pub fn get_strip_manifest(&self, book_id: i64, column_width: u32) -> Result<Vec<StripPage>> {
+        let page_count = self
+            .open_books
+            .lock()
+            .unwrap()
+            .get(&book_id)
+            .map(|manga| manga.sorted_pages.len())
+            .ok_or_else(|| ShioriError::BookNotFound(format!("Manga {} not open", book_id)))?;
+        let indices: Vec<usize> = (0..page_count).collect();
+        let dimensions = self.get_page_dimensions(book_id, &indices)?;
+        Ok(strip_layout(&dimensions, column_width))
+    }
+
+    /// Decode the pages visible at `scroll_y` plus `ahead` pages below into
+    /// the cache at `max_dimension` (the size `get_page` will be asked for).
+    /// Returns the page indices that were requested.
+    pub async fn prefetch_strip(
+        &self,
+        book_id: i64,
+        column_width: u32,
+        scroll_y: u64,
+        viewport_height: u64,
+        max_dimension: u32,
+        ahead: usize,
+    ) -> Result<Vec<usize>> {
+        let manifest = self.get_strip_manifest(book_id, column_width)?;
+        let pages = strip_pages_to_load(&manifest, scroll_y, viewport_height, ahead);
+        self.preload_pages(book_id, &pages, max_dimension).await?;
+        Ok(pages)
+    }
+
     /// Close a manga and free all associated resources
     pub fn close(&self, book_id: i64) {
         println!("[MangaService] Closing manga {}", book_id);
@@ -388,8 +757,7 @@ impl MangaService {
         self.open_books.lock().unwrap().remove(&book_id);
 
         // Evict all cache entries for this book
-        let mut cache = self.page_cache.lock().unwrap();
-        cache.retain(|key, _| key.0 != book_id);
+        self.page_cache.lock().unwrap().remove_book(book_id);
 
         println!("[MangaService] Manga {} closed", book_id);
     }
@@ -405,60 +773,7 @@ impl MangaService {
         let mut file = archive.by_name(filename).ok()?;
         let mut buf = Vec::new();
         file.read_to_end(&mut buf).ok()?;
-
-        // Try header-only decode first (fast — doesn't load full pixel data)
-        let reader = image::ImageReader::new(Cursor::new(&buf))
-            .with_guessed_format()
-            .ok()?;
-        if let Ok((w, h)) = reader.into_dimensions() {
-            return Some((w, h));
-        }
-
-        // Fallback: full decode
-        let img = image::load_from_memory(&buf).ok()?;
-        Some(img.dimensions())
-    }
-
-    /// Cache a page, evicting LRU entries if over limits
-    fn cache_page(&self, key: (i64, usize, u32), data: &[u8]) {
-        let mut cache = self.page_cache.lock().unwrap();
-
-        // Evict if over entry limit
-        while cache.len() >= self.max_cache_entries {
-            self.evict_oldest(&mut cache);
-        }
-
-        // Evict if over byte limit
-        let total_bytes: usize = cache.values().map(|e| e.data.len()).sum();
-        if total_bytes + data.len() > self.max_cache_bytes {
-            // Evict until we have room
-            let mut current_bytes = total_bytes;
-            while current_bytes + data.len() > self.max_cache_bytes && !cache.is_empty() {
-                if let Some(evicted_size) = self.evict_oldest(&mut cache) {
-                    current_bytes -= evicted_size;
-                } else {
-                    break;
-                }
-            }
-        }
-
-        cache.insert(
-            key,
-            CachedPage {
-                data: data.to_vec(),
-                last_access: std::time::Instant::now(),
-            },
-        );
-    }
-
-    /// Evict the oldest cache entry, returns size of evicted entry
-    fn evict_oldest(&self, cache: &mut HashMap<(i64, usize, u32), CachedPage>) -> Option<usize> {
-        let oldest_key = cache
-            .iter()
-            .min_by_key(|(_, v)| v.last_access)
-            .map(|(k, _)| *k)?;
-
-        cache.remove(&oldest_key).map(|e| e.data.len())
+        image_dimensions(&buf)
     }
 
     /// Try to parse ComicInfo.xml from the archive
@@ -499,3 +814,140 @@ impl MangaService {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_layout_accumulates_offsets() {
+        // Portrait, double-width spread, tiny strip, and a 1:2 tall page
+        let pages = [(800, 1200), (1600, 1200), (400, 100), (1000, 2000)];
+        let manifest = strip_layout(&pages, 400);
+        let layout: Vec<(u32, u64)> = manifest.iter().map(|p| (p.height, p.offset_y)).collect();
+        assert_eq!(layout, vec![(600, 0), (300, 600), (100, 900), (800, 1000)]);
+        assert!(manifest.iter().all(|p| p.width == 400));
+
+        // Viewport 650..1050 touches pages 1-3; nothing exists past page 3
+        assert_eq!(strip_pages_to_load(&manifest, 650, 400, 2), vec![1, 2, 3]);
+        // Viewport 0..600 is exactly page 0, then two pages ahead
+        assert_eq!(strip_pages_to_load(&manifest, 0, 600, 2), vec![0, 1, 2]);
+        assert!(strip_pages_to_load(&manifest, 5000, 400, 2).is_empty());
+        // A viewport running past u64::MAX must not overflow
+        assert_eq!(strip_pages_to_load(&manifest, 900, u64::MAX, 0), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_page_cache_evicts_oldest_over_budget() {
+        let mut cache = PageCache::new(300);
+        cache.insert((1, 0, 0), vec![0; 100]);
+        cache.insert((1, 1, 0), vec![0; 100]);
+        cache.insert((1, 2, 0), vec![0; 100]);
+        // Touch page 0 so page 1 becomes the oldest
+        assert!(cache.get(&(1, 0, 0)).is_some());
+
+        cache.insert((1, 3, 0), vec![0; 100]);
+        assert!(cache.get(&(1, 1, 0)).is_none());
+        assert!(cache.get(&(1, 0, 0)).is_some());
+        assert!(cache.get(&(1, 3, 0)).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.used_bytes, 300);
+        assert_eq!(stats.entries, 3);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+
+        // Larger than the whole budget: not cached, nothing evicted
+        cache.insert((1, 4, 0), vec![0; 301]);
+        assert_eq!(cache.stats().entries, 3);
+
+        cache.set_budget(150);
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.stats().used_bytes, 100);
+    }
+
+    #[test]
+    fn test_wide_page_stands_alone_and_resets_pairing() {
+        const PORTRAIT: (u32, u32) = (800, 1200);
+        const WIDE: (u32, u32) = (1600, 1200);
+        // 0-1 pair, 2 is left over when the wide page 3 arrives, 4-5 pair
+        let pages = [
+            PORTRAIT, PORTRAIT, PORTRAIT, WIDE, PORTRAIT, PORTRAIT, PORTRAIT,
+        ];
+
+        assert_eq!(
+            pair_spreads(&pages, ReadingDirection::LeftToRight),
+            vec![
+                MangaSpread::Pair { left: 0, right: 1 },
+                MangaSpread::Single { page: 2 },
+                MangaSpread::Single { page: 3 },
+                MangaSpread::Pair { left: 4, right: 5 },
+                MangaSpread::Single { page: 6 },
+            ]
+        );
+        assert_eq!(
+            pair_spreads(&pages, ReadingDirection::RightToLeft),
+            vec![
+                MangaSpread::Pair { left: 1, right: 0 },
+                MangaSpread::Single { page: 2 },
+                MangaSpread::Single { page: 3 },
+                MangaSpread::Pair { left: 5, right: 4 },
+                MangaSpread::Single { page: 6 },
+            ]
+        );
+        assert!(ReadingDirection::parse("up").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webp_page_is_served_as_png() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut webp = Vec::new();
+        image::RgbaImage::from_pixel(4, 6, image::Rgba([200, 40, 40, 128]))
+            .write_to(&mut Cursor::new(&mut webp), image::ImageFormat::WebP)
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("webp.cbz");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        for (name, data) in [
+            ("001.webp", webp.as_slice()),
+            ("002.avif", b"not decoded".as_slice()),
+            ("notes.txt", b"hi".as_slice()),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let service = MangaService::new(1 << 20);
+        let metadata = service.open(1, path.to_str().unwrap()).unwrap();
+        let expected_pages = if cfg!(feature = "avif-decode") { 2 } else { 1 };
+        assert_eq!(metadata.page_count, expected_pages);
+        assert_eq!(
+            warn_undecodable_pages(
+                "webp.cbz",
+                ["001.webp", "002.avif", "003.AVIF", "notes.txt"]
+            ),
+            if cfg!(feature = "avif-decode") { 0 } else { 2 }
+        );
+
+        // Unrecognised bytes pass through untouched unless they need resizing
+        assert_eq!(prepare_page(b"??".to_vec(), 0).unwrap(), b"??");
+        assert!(prepare_page(b"??".to_vec(), 100).is_err());
+
+        let page = service.get_page(1, 0, 0).await.unwrap();
+        assert!(page.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let decoded = image::load_from_memory(&page).unwrap();
+        assert_eq!(decoded.dimensions(), (4, 6));
+
+        // Downscaled pages come back as JPEG even with an alpha channel
+        let small = service.get_page(1, 0, 3).await.unwrap();
+        assert_eq!(
+            image::guess_format(&small).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use pulldown_cmark::{html, Options, Parser};
 use regex::Regex;
@@ -57,6 +59,8 @@ impl MarkdownReaderAdapter {
                 title: "Content".to_string(),
                 content: format!("<div class=\"md-chapter\">{}</div>", html_content),
                 location: "md-chapter-0".to_string(),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
             toc.push(TocEntry {
                 label: "Content".to_string(),
@@ -77,6 +81,8 @@ impl MarkdownReaderAdapter {
                     title: "Introduction".to_string(),
                     content: format!("<div class=\"md-chapter\">{}</div>", preamble),
                     location: format!("md-chapter-{}", idx),
+                    word_count: 0,
+                    estimated_minutes: 0.0,
                 });
                 toc.push(TocEntry {
                     label: "Introduction".to_string(),
@@ -112,6 +118,8 @@ impl MarkdownReaderAdapter {
                 },
                 content: format!("<div class=\"md-chapter\">{}</div>", section_content),
                 location: format!("md-chapter-{}", idx),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
 
             toc.push(TocEntry {
@@ -128,23 +136,6 @@ impl MarkdownReaderAdapter {
 
         (chapters, toc)
     }
-
-    fn safe_snippet(content: &str, match_pos: usize, query_char_len: usize) -> String {
-        let char_indices: Vec<(usize, char)> = content.char_indices().collect();
-        let char_idx = char_indices
-            .iter()
-            .position(|&(b_idx, _)| b_idx >= match_pos)
-            .unwrap_or(0);
-        let start_char = char_idx.saturating_sub(50);
-        let end_char = (char_idx + query_char_len + 50).min(char_indices.len());
-        let start_byte = char_indices.get(start_char).map(|&(b, _)| b).unwrap_or(0);
-        let end_byte = if end_char >= char_indices.len() {
-            content.len()
-        } else {
-            char_indices[end_char].0
-        };
-        format!("...{}...", &content[start_byte..end_byte])
-    }
 }
 
 unsafe impl Send for MarkdownReaderAdapter {}
@@ -205,24 +196,19 @@ impl BookReaderAdapter for MarkdownReaderAdapter {
         self.chapters.len()
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
-        let query_char_len = query.chars().count();
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
         let mut results = Vec::new();
 
         for chapter in &self.chapters {
-            let content_lower = chapter.content.to_lowercase();
-            let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-            if !matches.is_empty() {
-                let snippet = Self::safe_snippet(&chapter.content, matches[0].0, query_char_len);
-                results.push(SearchResult {
-                    chapter_index: chapter.index,
-                    chapter_title: chapter.title.clone(),
-                    snippet,
-                    location: chapter.location.clone(),
-                    match_count: matches.len(),
-                });
-            }
+            results.extend(matcher.search_chapter(
+                &chapter.content,
+                chapter.index,
+                chapter.title.clone(),
+                chapter.location.clone(),
+            ));
         }
 
         Ok(results)
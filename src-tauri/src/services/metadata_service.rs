@@ -19,10 +19,172 @@ pub fn extract_from_file(file_path: &str) -> Result<Metadata> {
         "mobi" | "azw3" => extract_mobi_metadata(file_path),
         "fb2" => extract_fb2_metadata(file_path),
         "docx" => extract_docx_metadata(file_path),
+        "djvu" => extract_djvu_metadata(file_path),
         _ => Ok(Metadata::default_from_filename(path)),
     }
 }
 
+/// Word count and opening text of a book, gathered in one read of its text
+#[derive(Debug, Default)]
+pub struct TextStats {
+    pub word_count: Option<i32>,
+    pub sample: Option<String>,
+}
+
+/// Count words in the book's text. Only EPUB, TXT and DOCX are counted;
+/// other formats (PDF, comics) return `None` since the result is unreliable.
+pub fn count_words(file_path: &str) -> Option<i32> {
+    text_stats(file_path, 0).word_count
+}
+
+/// `count_words` plus up to `sample_bytes` of text from the start, for
+/// language detection. Formats whose words are counted are read once for
+/// both; the others only yield a sample (see `sample_text`).
+pub fn text_stats(file_path: &str, sample_bytes: usize) -> TextStats {
+    let Some(text) = countable_text(file_path) else {
+        return TextStats {
+            word_count: None,
+            sample: sample_text(file_path, sample_bytes),
+        };
+    };
+
+    let word_count = i32::try_from(text.split_whitespace().count()).ok();
+    let sample = (sample_bytes > 0).then(|| {
+        let mut sample = text;
+        truncate_to(&mut sample, sample_bytes);
+        sample
+    });
+    TextStats { word_count, sample }
+}
+
+/// Full plain text of the formats `count_words` trusts
+fn countable_text(file_path: &str) -> Option<String> {
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())?
+        .to_lowercase();
+
+    match extension.as_str() {
+        "txt" => {
+            let bytes = fs::read(file_path).ok()?;
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        "epub" => {
+            let mut doc = epub::doc::EpubDoc::new(file_path).ok()?;
+            let mut text = String::new();
+            loop {
+                if let Some((content, _mime)) = doc.get_current() {
+                    // Pad tags so adjacent block elements don't merge words
+                    let html = String::from_utf8_lossy(&content).replace('>', "> ");
+                    text.push_str(&crate::conversion::utils::strip_html_tags(&html));
+                    text.push(' ');
+                }
+                if !doc.go_next() {
+                    break;
+                }
+            }
+            Some(text)
+        }
+        "docx" => {
+            let bytes = fs::read(file_path).ok()?;
+            let doc = docx_rs::read_docx(&bytes).ok()?;
+            Some(crate::services::docx_adapter::DocxAdapter::plain_text(&doc))
+        }
+        _ => None,
+    }
+}
+
+/// PDF pages read for a text sample; front matter is often image-only
+const SAMPLE_PDF_PAGES: u32 = 10;
+
+/// Up to `max_bytes` of the book's text from its start, for language
+/// detection. Covers every text format: the ones `count_words` reads plus
+/// PDF, MOBI/AZW3, FB2 and HTML. Comics and DjVu have no text to sample.
+pub fn sample_text(file_path: &str, max_bytes: usize) -> Option<String> {
+    if max_bytes == 0 {
+        return None;
+    }
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())?
+        .to_lowercase();
+
+    let mut text = match extension.as_str() {
+        "txt" => {
+            let mut buf = Vec::with_capacity(max_bytes);
+            fs::File::open(file_path)
+                .ok()?
+                .take(max_bytes as u64)
+                .read_to_end(&mut buf)
+                .ok()?;
+            String::from_utf8_lossy(&buf).into_owned()
+        }
+        "epub" => {
+            let mut doc = epub::doc::EpubDoc::new(file_path).ok()?;
+            let mut text = String::new();
+            // Title and copyright pages are short; keep reading until the sample fills
+            while text.len() < max_bytes {
+                if let Some((content, _mime)) = doc.get_current() {
+                    let html = String::from_utf8_lossy(&content).replace('>', "> ");
+                    text.push_str(&crate::conversion::utils::strip_html_tags(&html));
+                    text.push(' ');
+                }
+                if !doc.go_next() {
+                    break;
+                }
+            }
+            text
+        }
+        "docx" => {
+            let bytes = fs::read(file_path).ok()?;
+            let doc = docx_rs::read_docx(&bytes).ok()?;
+            crate::services::docx_adapter::DocxAdapter::plain_text(&doc)
+        }
+        "pdf" => {
+            let doc = lopdf::Document::load(file_path).ok()?;
+            let pages: Vec<u32> = doc
+                .get_pages()
+                .keys()
+                .copied()
+                .take(SAMPLE_PDF_PAGES as usize)
+                .collect();
+            doc.extract_text(&pages).ok()?
+        }
+        "mobi" | "azw3" => {
+            let bytes = fs::read(file_path).ok()?;
+            let mobi = mobi::Mobi::from_read(&mut &bytes[..]).ok()?;
+            let html = mobi.content_as_string_lossy().ok()?.replace('>', "> ");
+            crate::conversion::utils::strip_html_tags(&html)
+        }
+        "fb2" => {
+            let bytes = fs::read(file_path).ok()?;
+            crate::services::adapters::fb2::Fb2FormatAdapter::extract_text(
+                &String::from_utf8_lossy(&bytes),
+            )
+        }
+        "html" | "htm" | "xhtml" => {
+            let bytes = fs::read(file_path).ok()?;
+            let html = String::from_utf8_lossy(&bytes).replace('>', "> ");
+            crate::conversion::utils::strip_html_tags(&html)
+        }
+        _ => return None,
+    };
+
+    truncate_to(&mut text, max_bytes);
+    Some(text)
+}
+
+/// Cut `text` to at most `max_bytes`, on a character boundary
+fn truncate_to(text: &mut String, max_bytes: usize) {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+}
+
 pub fn extract_cover(
     file_path: &str,
     book_uuid: &str,
@@ -38,8 +200,14 @@ pub fn extract_cover(
     let raw_cover = match extension.as_str() {
         "epub" => extract_epub_cover(file_path, book_uuid, covers_dir),
         "cbz" | "cbr" | "zip" => extract_cbz_cover(file_path, book_uuid, covers_dir),
-        "pdf" => extract_pdf_cover(file_path, book_uuid, covers_dir),
+        "pdf" => match extract_pdf_cover(file_path, book_uuid, covers_dir) {
+            Ok(Some(cover)) => Ok(Some(cover)),
+            // No embedded image to use; the first page itself makes the cover.
+            // CoverService already sized it, so skip the thumbnailing below
+            _ => return render_pdf_cover(file_path, book_uuid, covers_dir),
+        },
         "mobi" | "azw3" => extract_mobi_cover(file_path, book_uuid, covers_dir),
+        "djvu" => extract_djvu_cover(file_path, book_uuid, covers_dir),
         _ => return Ok(None),
     }?;
 
@@ -222,6 +390,99 @@ fn detect_image_format(data: &[u8]) -> Option<(&'static str, usize)> {
     None
 }
 
+/// Render the first page as the cover; needs djvulibre, so a missing
+/// install just means no cover
+fn extract_djvu_cover(
+    file_path: &str,
+    book_uuid: &str,
+    covers_dir: &Path,
+) -> Result<Option<String>> {
+    use crate::services::djvu_document;
+
+    let png = match djvu_document::render_page_png(Path::new(file_path), 0, 600, 800) {
+        Ok(png) => png,
+        Err(e) => {
+            log::warn!("[extract_djvu_cover] No cover for {}: {}", file_path, e);
+            return Ok(None);
+        }
+    };
+
+    fs::create_dir_all(covers_dir).map_err(|e| {
+        ShioriError::MetadataExtraction(format!("Failed to create covers dir: {}", e))
+    })?;
+    let cover_path = covers_dir.join(format!("{}.png", book_uuid));
+    fs::write(&cover_path, &png).map_err(|e| {
+        ShioriError::MetadataExtraction(format!("Failed to write cover data: {}", e))
+    })?;
+    Ok(Some(cover_path.to_string_lossy().to_string()))
+}
+
+/// Longer side, in pixels, of a cover rendered from a PDF's first page
+const PDF_COVER_LONG_SIDE: u32 = 800;
+
+/// Render a PDF's first page with `PdfAdapter` and store it as a
+/// `CoverService` cover set under `covers_dir/<uuid>/`; the medium size
+/// becomes the book's cover. Encrypted PDFs have no password at import time
+/// and fall back to a generated cover.
+fn render_pdf_cover(file_path: &str, book_uuid: &str, covers_dir: &Path) -> Result<Option<String>> {
+    use crate::services::cover_service::CoverService;
+    use crate::services::format_adapter::CoverImage;
+    use crate::services::pdf_adapter::PdfAdapter;
+    use crate::services::renderer::BookReaderAdapter;
+
+    let rendered = futures::executor::block_on(async {
+        let mut adapter = PdfAdapter::new();
+        adapter.load_with_password(file_path, None).await?;
+        let (width, height) = adapter.get_page_dimensions(0)?;
+        let scale = PDF_COVER_LONG_SIDE as f32 / width.max(height).max(1.0);
+        adapter.render_page(0, scale).await
+    })
+    .and_then(|png| {
+        image::load_from_memory(&png).map_err(|e| {
+            ShioriError::MetadataExtraction(format!("Unreadable rendered page: {}", e))
+        })
+    });
+    let page = match rendered {
+        Ok(page) => page,
+        Err(e) => {
+            log::warn!("[render_pdf_cover] No cover for {}: {}", file_path, e);
+            return Ok(None);
+        }
+    };
+    let book_id = uuid::Uuid::parse_str(book_uuid)
+        .map_err(|e| ShioriError::MetadataExtraction(format!("Invalid book UUID: {}", e)))?;
+
+    let stored = CoverService::new(covers_dir.to_path_buf()).and_then(|service| {
+        futures::executor::block_on(service.process_and_store(book_id, CoverImage::new(page)))
+    });
+    match stored {
+        Ok(cover_set) => Ok(Some(cover_set.medium.to_string_lossy().to_string())),
+        Err(e) => {
+            log::warn!(
+                "[render_pdf_cover] Failed to store cover for {}: {}",
+                file_path,
+                e
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Delete an extracted cover. Covers stored by `CoverService` are a set of
+/// sizes in their own directory, which goes with them.
+pub fn remove_cover(cover_path: &str) {
+    let cover = Path::new(cover_path);
+    let _ = fs::remove_file(cover);
+    if cover.file_name().is_some_and(|name| name == "medium.webp") {
+        if let Some(dir) = cover.parent() {
+            for size in ["thumb.webp", "full.webp"] {
+                let _ = fs::remove_file(dir.join(size));
+            }
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
 fn extract_mobi_cover(
     file_path: &str,
     book_uuid: &str,
@@ -447,7 +708,7 @@ fn extract_cbz_cover(
     }
 
     // Sort by natural order (page1.jpg < page10.jpg)
-    image_files.sort_by(|a, b| natord::compare(&a.1, &b.1));
+    image_files.sort_by(|a, b| crate::utils::natural_sort::compare(&a.1, &b.1));
 
     // Get the first image (cover)
     let first_image_idx = image_files[0].0;
@@ -641,6 +902,32 @@ fn extract_epub_metadata(file_path: &str) -> Result<Metadata> {
     Ok(metadata)
 }
 
+fn extract_djvu_metadata(file_path: &str) -> Result<Metadata> {
+    use crate::services::djvu_document::{self, DjvuDocument};
+
+    let path = Path::new(file_path);
+    let doc = DjvuDocument::open(path)
+        .map_err(|e| ShioriError::MetadataExtraction(format!("Failed to parse DjVu: {}", e)))?;
+
+    let mut metadata = Metadata::default_from_filename(path);
+    metadata.page_count = Some(doc.page_count() as i32);
+
+    // Embedded metadata needs djvused; without it the filename is used
+    if let Ok(meta) = djvu_document::read_metadata(path) {
+        if let Some(title) = meta.get("title") {
+            metadata.title = Some(title.clone());
+        }
+        if let Some(author) = meta.get("author") {
+            metadata.authors = vec![author.clone()];
+        }
+        metadata.publisher = meta.get("publisher").cloned();
+        metadata.pubdate = meta.get("year").cloned();
+        metadata.description = meta.get("note").cloned();
+    }
+
+    Ok(metadata)
+}
+
 fn extract_pdf_metadata(file_path: &str) -> Result<Metadata> {
     let doc = lopdf::Document::load(file_path)
         .map_err(|e| ShioriError::MetadataExtraction(format!("Failed to parse PDF: {}", e)))?;
@@ -787,7 +1074,7 @@ fn extract_mobi_metadata(file_path: &str) -> Result<Metadata> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_mobi_cover_record_candidates;
+    use super::{parse_mobi_cover_record_candidates, text_stats};
 
     #[test]
     fn parses_cover_candidates_from_exth_and_first_image() {
@@ -871,6 +1158,32 @@ mod tests {
         assert!(candidates.contains(&2));
         assert!(candidates.contains(&7));
     }
+
+    #[test]
+    fn text_stats_counts_words_and_samples_other_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let txt = dir.path().join("book.txt");
+        std::fs::write(&txt, "one two three four").unwrap();
+        let stats = text_stats(&txt.to_string_lossy(), 7);
+        assert_eq!(stats.word_count, Some(4));
+        assert_eq!(stats.sample.as_deref(), Some("one two"));
+        assert_eq!(text_stats(&txt.to_string_lossy(), 0).sample, None);
+
+        // HTML has no trusted word count but still yields a sample
+        let html = dir.path().join("page.html");
+        std::fs::write(
+            &html,
+            "<html><body><p>Bonjour</p><p>le monde</p></body></html>",
+        )
+        .unwrap();
+        let stats = text_stats(&html.to_string_lossy(), 100);
+        assert_eq!(stats.word_count, None);
+        let sample = stats.sample.unwrap();
+        assert_eq!(
+            sample.split_whitespace().collect::<Vec<_>>(),
+            ["Bonjour", "le", "monde"]
+        );
+    }
 }
 
 fn extract_fb2_metadata(file_path: &str) -> Result<Metadata> {
@@ -1130,7 +1443,8 @@ impl Metadata {
             isbn: None,
             publisher: None,
             pubdate: None,
-            language: Some("eng".to_string()),
+            // Nothing declares it; the importer detects it from the text
+            language: None,
             description: None,
             page_count: None,
             series,
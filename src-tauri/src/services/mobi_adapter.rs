@@ -1,5 +1,7 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use mobi::Mobi;
 use regex::Regex;
@@ -410,6 +412,8 @@ impl MobiAdapter {
                 title,
                 content,
                 location: format!("mobi-chapter-{}", chapters.len()),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
         }
 
@@ -496,6 +500,8 @@ impl MobiAdapter {
                     .unwrap_or(fallback_title),
                 content: html_chunk,
                 location: format!("mobi-chapter-{}", chapters.len()),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
         }
 
@@ -505,12 +511,61 @@ impl MobiAdapter {
                 title: "Content".to_string(),
                 content: Self::text_to_html(&plain),
                 location: "mobi-chapter-0".to_string(),
+                word_count: 0,
+                estimated_minutes: 0.0,
             });
         }
 
         chapters
     }
 
+    /// MOBI6 text: decode the text records every way we know, keep the most
+    /// readable result and split it on headings and page breaks
+    fn legacy_chapters(
+        file_data: &[u8],
+        m: &Mobi,
+        image_map: &HashMap<String, String>,
+    ) -> Result<Vec<Chapter>> {
+        // ── Multi-strategy content extraction ──
+        // Try all methods and pick the best result using readability scoring.
+        let mut candidates: Vec<String> = Vec::new();
+
+        // Strategy 1: Custom PDB record extraction (handles compression, extra bytes, encoding)
+        if let Some(content) = Self::extract_html_from_records(file_data) {
+            if !content.trim().is_empty() {
+                candidates.push(content);
+            }
+        }
+
+        // Strategy 2: mobi crate strict UTF-8
+        if let Ok(content) = m.content_as_string() {
+            if !content.trim().is_empty() {
+                candidates.push(content);
+            }
+        }
+
+        // Strategy 3: mobi crate lossy (replacement chars for invalid bytes)
+        if let Ok(content) = m.content_as_string_lossy() {
+            if !content.trim().is_empty() {
+                candidates.push(content);
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(ShioriError::Other(
+                "Failed to extract any readable content from this MOBI file. It may be DRM-protected or corrupted.".to_string()
+            ));
+        }
+
+        // Pick the best candidate by readability score
+        let html = Self::pick_best_decoded_candidate(candidates)
+            .unwrap_or_else(|| "<p>Unable to decode MOBI content.</p>".to_string());
+
+        let normalized_html = Self::normalize_mobi_content(&html);
+        let normalized_html = Self::inline_mobi_images(&normalized_html, image_map);
+        Ok(Self::split_into_chapters(&normalized_html))
+    }
+
     fn read_be_u16(data: &[u8], offset: usize) -> Option<u16> {
         data.get(offset..offset + 2)
             .map(|b| u16::from_be_bytes([b[0], b[1]]))
@@ -1090,79 +1145,332 @@ impl MobiAdapter {
             })
             .to_string()
     }
+}
 
-    fn build_snippet(content: &str, first_match_pos: usize, query_char_count: usize) -> String {
-        let char_indices: Vec<(usize, char)> = content.char_indices().collect();
-        let char_idx = char_indices
-            .iter()
-            .position(|&(b_idx, _)| b_idx >= first_match_pos)
-            .unwrap_or(0);
-        let start_char_idx = char_idx.saturating_sub(50);
-        let end_char_idx = (char_idx + query_char_count + 50).min(char_indices.len());
-        let start_byte = char_indices
-            .get(start_char_idx)
-            .map(|&(b, _)| b)
-            .unwrap_or(0);
-        let end_byte = if end_char_idx >= char_indices.len() {
-            content.len()
-        } else {
-            char_indices[end_char_idx].0
-        };
+// ─── KF8 (AZW3) ─────────────────────────────────────────────────────────────
+//
+// KF8 stores each XHTML file as a skeleton (the page shell) followed by the
+// fragments that are spliced into it. The skeleton and fragment INDX indexes
+// say where every piece starts, so books keep their real file structure
+// instead of being re-split on headings.
 
-        format!("...{}...", &content[start_byte..end_byte])
+/// Index fields in the KF8 header use this for "absent"
+const KF8_NULL_INDEX: u32 = 0xFFFF_FFFF;
+
+impl MobiAdapter {
+    fn pdb_record<'a>(data: &'a [u8], offsets: &[usize], idx: usize) -> Option<&'a [u8]> {
+        let start = *offsets.get(idx)?;
+        let end = offsets.get(idx + 1).copied().unwrap_or(data.len());
+        data.get(start..end)
     }
-}
 
-unsafe impl Send for MobiAdapter {}
-unsafe impl Sync for MobiAdapter {}
+    /// Record index of the KF8 header: 0 for KF8-only files, or the record
+    /// after the BOUNDARY marker (EXTH 121) in combined MOBI6/KF8 files
+    fn find_kf8_start(data: &[u8], offsets: &[usize]) -> Option<usize> {
+        let record0 = *offsets.first()?;
+        if data.get(record0 + 0x10..record0 + 0x14) != Some(b"MOBI") {
+            return None;
+        }
+        if Self::read_be_u32(data, record0 + 0x24)? == 8 {
+            return Some(0);
+        }
 
-#[async_trait]
-impl BookReaderAdapter for MobiAdapter {
-    async fn load(&mut self, path: &str) -> Result<()> {
-        let file_data = fs::read(path).map_err(|e| ShioriError::Io(e))?;
+        let boundary = Self::read_exth_u32(data, record0, 121)?;
+        if boundary == KF8_NULL_INDEX {
+            return None;
+        }
+        let kf8_start = boundary as usize;
+        let marker = Self::pdb_record(data, offsets, kf8_start.checked_sub(1)?)?;
+        (marker.starts_with(b"BOUNDARY") && kf8_start < offsets.len()).then_some(kf8_start)
+    }
 
-        let m = Mobi::from_read(&mut &file_data[..])
-            .map_err(|e| ShioriError::Other(format!("Invalid MOBI file: {}", e)))?;
+    fn read_exth_u32(data: &[u8], record0: usize, wanted: u32) -> Option<u32> {
+        if Self::read_be_u32(data, record0 + 0x80)? & 0x40 == 0 {
+            return None;
+        }
+        let header_len = Self::read_be_u32(data, record0 + 0x14)? as usize;
+        let exth = record0 + 0x10 + header_len;
+        if data.get(exth..exth + 4) != Some(b"EXTH") {
+            return None;
+        }
 
-        // ── Multi-strategy content extraction ──
-        // Try all methods and pick the best result using readability scoring.
-        let mut candidates: Vec<String> = Vec::new();
+        let count = Self::read_be_u32(data, exth + 8)?;
+        let mut pos = exth + 12;
+        for _ in 0..count {
+            let kind = Self::read_be_u32(data, pos)?;
+            let len = Self::read_be_u32(data, pos + 4)? as usize;
+            if len < 8 {
+                return None;
+            }
+            if kind == wanted && len >= 12 {
+                return Self::read_be_u32(data, pos + 8);
+            }
+            pos += len;
+        }
+        None
+    }
 
-        // Strategy 1: Custom PDB record extraction (handles compression, extra bytes, encoding)
-        if let Some(content) = Self::extract_html_from_records(&file_data) {
-            if !content.trim().is_empty() {
-                candidates.push(content);
+    /// Decompressed text of the KF8 section whose header is record `start`
+    fn kf8_text(data: &[u8], offsets: &[usize], start: usize) -> Option<Vec<u8>> {
+        let header = *offsets.get(start)?;
+        let compression = Self::read_be_u16(data, header)?;
+        let text_length = Self::read_be_u32(data, header + 4)? as usize;
+        let record_count = Self::read_be_u16(data, header + 8)? as usize;
+        let header_len = Self::read_be_u32(data, header + 0x14)? as usize;
+        let extra_flags = if header_len >= 0xE4 {
+            Self::read_be_u16(data, header + 0xF2).unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut text = Vec::with_capacity(text_length);
+        for idx in start + 1..=start + record_count {
+            let record =
+                Self::trim_record_extra_bytes(Self::pdb_record(data, offsets, idx)?, extra_flags);
+            match compression {
+                1 => text.extend_from_slice(&record),
+                2 => text.extend(Self::palm_doc_decompress(&record)?),
+                // HUFF/CDIC is only used by store books, which are DRM-protected anyway
+                _ => return None,
+            }
+        }
+        text.truncate(text_length);
+
+        // Flow 0 is the XHTML; later flows (CSS, SVG) are appended after it
+        let fdst = Self::read_be_u32(data, header + 0xC0)?;
+        if fdst != KF8_NULL_INDEX {
+            if let Some(record) = Self::pdb_record(data, offsets, start + fdst as usize) {
+                if record.starts_with(b"FDST") && Self::read_be_u32(record, 8)? > 0 {
+                    let flow_end = Self::read_be_u32(record, 16)? as usize;
+                    text.truncate(flow_end);
+                }
+            }
+        }
+        Some(text)
+    }
+
+    /// Forward-encoded variable width integer (high bit marks the last byte)
+    fn read_forward_varint(data: &[u8]) -> Option<(u32, usize)> {
+        let mut value = 0u32;
+        for (i, &b) in data.iter().enumerate().take(5) {
+            value = (value << 7) | (b & 0x7F) as u32;
+            if b & 0x80 != 0 {
+                return Some((value, i + 1));
             }
         }
+        None
+    }
 
-        // Strategy 2: mobi crate strict UTF-8
-        if let Ok(content) = m.content_as_string() {
-            if !content.trim().is_empty() {
-                candidates.push(content);
+    /// Decode an index entry's tag values as described by the TAGX table
+    fn decode_kf8_tags(
+        tagx: &[[u8; 4]],
+        control: &[u8],
+        mut data: &[u8],
+    ) -> Option<HashMap<u8, Vec<u32>>> {
+        // (tag, values per entry, value count, or byte length when `is_bytes`)
+        let mut present = Vec::new();
+        let mut control_idx = 0;
+        for &[tag, per_entry, mask, end_flag] in tagx {
+            if end_flag == 1 {
+                control_idx += 1;
+                continue;
+            }
+            let value = control.get(control_idx)? & mask;
+            if value == 0 {
+                continue;
+            }
+            if value == mask && mask.count_ones() > 1 {
+                let (len, used) = Self::read_forward_varint(data)?;
+                data = &data[used..];
+                present.push((tag, per_entry, len as usize, true));
+            } else {
+                let count = (value >> mask.trailing_zeros()) as usize;
+                present.push((tag, per_entry, count, false));
             }
         }
 
-        // Strategy 3: mobi crate lossy (replacement chars for invalid bytes)
-        if let Ok(content) = m.content_as_string_lossy() {
-            if !content.trim().is_empty() {
-                candidates.push(content);
+        let mut tags = HashMap::new();
+        for (tag, per_entry, amount, is_bytes) in present {
+            let mut values = Vec::new();
+            let mut consumed = 0;
+            while (is_bytes && consumed < amount)
+                || (!is_bytes && values.len() < amount * per_entry as usize)
+            {
+                let (value, used) = Self::read_forward_varint(data)?;
+                data = &data[used..];
+                consumed += used;
+                values.push(value);
             }
+            tags.insert(tag, values);
         }
+        Some(tags)
+    }
 
-        if candidates.is_empty() {
-            return Err(ShioriError::Other(
-                "Failed to extract any readable content from this MOBI file. It may be DRM-protected or corrupted.".to_string()
-            ));
+    /// Entries of the INDX index starting at record `header_idx`, in order,
+    /// as (label, tag values)
+    fn read_kf8_index(
+        data: &[u8],
+        offsets: &[usize],
+        header_idx: usize,
+    ) -> Option<Vec<(String, HashMap<u8, Vec<u32>>)>> {
+        let header = Self::pdb_record(data, offsets, header_idx)?;
+        if !header.starts_with(b"INDX") {
+            return None;
         }
+        let header_len = Self::read_be_u32(header, 4)? as usize;
+        let record_count = Self::read_be_u32(header, 24)? as usize;
 
-        // Pick the best candidate by readability score
-        let html = Self::pick_best_decoded_candidate(candidates)
-            .unwrap_or_else(|| "<p>Unable to decode MOBI content.</p>".to_string());
+        let tagx = header.get(header_len..)?;
+        if !tagx.starts_with(b"TAGX") {
+            return None;
+        }
+        let tagx_len = Self::read_be_u32(tagx, 4)? as usize;
+        let control_bytes = Self::read_be_u32(tagx, 8)? as usize;
+        let tag_table: Vec<[u8; 4]> = tagx
+            .get(12..tagx_len)?
+            .chunks_exact(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect();
+
+        let mut entries = Vec::new();
+        for idx in header_idx + 1..=header_idx + record_count {
+            let record = Self::pdb_record(data, offsets, idx)?;
+            if !record.starts_with(b"INDX") {
+                return None;
+            }
+            let idxt = Self::read_be_u32(record, 20)? as usize;
+            let count = Self::read_be_u32(record, 24)? as usize;
+            if record.get(idxt..idxt + 4) != Some(b"IDXT") {
+                return None;
+            }
+
+            let positions = (0..count)
+                .map(|i| Self::read_be_u16(record, idxt + 4 + 2 * i).map(usize::from))
+                .collect::<Option<Vec<_>>>()?;
+            for (i, &pos) in positions.iter().enumerate() {
+                let end = positions.get(i + 1).copied().unwrap_or(idxt);
+                let entry = record.get(pos..end)?;
+                let label_len = *entry.first()? as usize;
+                let label = String::from_utf8_lossy(entry.get(1..1 + label_len)?).into_owned();
+                let rest = entry.get(1 + label_len..)?;
+                let tags = Self::decode_kf8_tags(
+                    &tag_table,
+                    rest.get(..control_bytes)?,
+                    rest.get(control_bytes..)?,
+                )?;
+                entries.push((label, tags));
+            }
+        }
+        Some(entries)
+    }
+
+    /// Rebuild each KF8 part (one XHTML file) by splicing its fragments into
+    /// its skeleton. `None` for MOBI6-only books or a damaged index.
+    fn extract_kf8_parts(data: &[u8]) -> Option<Vec<String>> {
+        let offsets = Self::parse_pdb_record_offsets(data)?;
+        let start = Self::find_kf8_start(data, &offsets)?;
+        let header = offsets[start];
+        let text = Self::kf8_text(data, &offsets, start)?;
+
+        let index_record = |field: usize| -> Option<usize> {
+            let idx = Self::read_be_u32(data, header + field)?;
+            (idx != KF8_NULL_INDEX).then_some(start + idx as usize)
+        };
+        let skeletons = Self::read_kf8_index(data, &offsets, index_record(0xFC)?)?;
+        let mut fragments = match index_record(0xF8) {
+            Some(idx) => Self::read_kf8_index(data, &offsets, idx)?,
+            None => Vec::new(),
+        }
+        .into_iter();
+
+        let mut parts = Vec::with_capacity(skeletons.len());
+        for (_, tags) in skeletons {
+            let fragment_count = *tags.get(&1)?.first()? as usize;
+            let position = tags.get(&6)?;
+            let (skel_start, skel_len) = (*position.first()? as usize, *position.get(1)? as usize);
+
+            let mut cursor = skel_start + skel_len;
+            let mut part = text.get(skel_start..cursor)?.to_vec();
+            for _ in 0..fragment_count {
+                // Fragment labels are their absolute insert position
+                let (label, tags) = fragments.next()?;
+                let insert_at = label
+                    .trim()
+                    .parse::<usize>()
+                    .ok()?
+                    .checked_sub(skel_start)?;
+                let length = *tags.get(&6)?.get(1)? as usize;
+                let fragment = text.get(cursor..cursor + length)?;
+                if insert_at > part.len() {
+                    return None;
+                }
+                part.splice(insert_at..insert_at, fragment.iter().copied());
+                cursor += length;
+            }
+            parts.push(String::from_utf8_lossy(&part).into_owned());
+        }
+
+        (!parts.is_empty()).then_some(parts)
+    }
+
+    /// One chapter per KF8 part, titled by its first heading. Only the body
+    /// is kept; each part's head just repeats the book title and stylesheet.
+    fn kf8_chapters(data: &[u8], image_map: &HashMap<String, String>) -> Option<Vec<Chapter>> {
+        let body_re = Regex::new(r"(?is)<body[^>]*>(.*)</body>").ok()?;
+        let heading_re = Regex::new(r"(?is)<h[1-6][^>]*>(.*?)</h[1-6]>").ok()?;
+        let chapters: Vec<Chapter> = Self::extract_kf8_parts(data)?
+            .iter()
+            .map(|part| {
+                let body = body_re
+                    .captures(part)
+                    .and_then(|cap| cap.get(1))
+                    .map_or(part.as_str(), |m| m.as_str());
+                Self::inline_mobi_images(&Self::normalize_mobi_content(body), image_map)
+            })
+            .filter(|html| !Self::strip_html_tags(html).is_empty() || html.contains("<img"))
+            .enumerate()
+            .map(|(index, content)| {
+                let title = heading_re
+                    .captures(&content)
+                    .and_then(|cap| cap.get(1))
+                    .map(|m| Self::strip_html_tags(m.as_str()))
+                    .filter(|t| !t.is_empty())
+                    .unwrap_or_else(|| format!("Chapter {}", index + 1));
+                Chapter {
+                    index,
+                    title,
+                    content,
+                    location: format!("mobi-chapter-{}", index),
+                    word_count: 0,
+                    estimated_minutes: 0.0,
+                }
+            })
+            .collect();
+
+        (!chapters.is_empty()).then_some(chapters)
+    }
+}
+
+unsafe impl Send for MobiAdapter {}
+unsafe impl Sync for MobiAdapter {}
+
+#[async_trait]
+impl BookReaderAdapter for MobiAdapter {
+    async fn load(&mut self, path: &str) -> Result<()> {
+        let file_data = fs::read(path).map_err(|e| ShioriError::Io(e))?;
+
+        let m = Mobi::from_read(&mut &file_data[..])
+            .map_err(|e| ShioriError::Other(format!("Invalid MOBI file: {}", e)))?;
 
         let image_map = Self::build_mobi_image_map(&file_data);
-        let normalized_html = Self::normalize_mobi_content(&html);
-        let normalized_html = Self::inline_mobi_images(&normalized_html, &image_map);
-        self.chapters = Self::split_into_chapters(&normalized_html);
+
+        // KF8 (AZW3) books keep their real part structure; MOBI6 text is the fallback
+        let kf8_chapters = Self::kf8_chapters(&file_data, &image_map);
+        let is_kf8 = kf8_chapters.is_some();
+        self.chapters = match kf8_chapters {
+            Some(chapters) => chapters,
+            None => Self::legacy_chapters(&file_data, &m, &image_map)?,
+        };
         self.path = path.to_string();
 
         let title = {
@@ -1192,7 +1500,7 @@ impl BookReaderAdapter for MobiAdapter {
             author,
             total_chapters: self.chapters.len(),
             total_pages: None,
-            format: "mobi".to_string(),
+            format: if is_kf8 { "azw3" } else { "mobi" }.to_string(),
         });
 
         Ok(())
@@ -1234,12 +1542,10 @@ impl BookReaderAdapter for MobiAdapter {
         self.chapters.len()
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        if query.trim().is_empty() {
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
             return Ok(Vec::new());
-        }
-
-        let query_lower = query.to_lowercase();
+        };
         let mut results = Vec::new();
 
         fn strip_html_tags(html: &str) -> String {
@@ -1265,22 +1571,12 @@ impl BookReaderAdapter for MobiAdapter {
 
         for chapter in &self.chapters {
             let content = strip_html_tags(&chapter.content);
-            let content_lower = content.to_lowercase();
-            let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-            if matches.is_empty() {
-                continue;
-            }
-
-            let first_match_pos = matches[0].0;
-            let snippet = Self::build_snippet(&content, first_match_pos, query.chars().count());
-
-            results.push(SearchResult {
-                chapter_index: chapter.index,
-                chapter_title: chapter.title.clone(),
-                snippet,
-                location: chapter.location.clone(),
-                match_count: matches.len(),
-            });
+            results.extend(matcher.search_chapter(
+                &content,
+                chapter.index,
+                chapter.title.clone(),
+                chapter.location.clone(),
+            ));
         }
 
         Ok(results)
@@ -1526,4 +1822,32 @@ mod tests {
                 .unwrap_or_default();
         assert_eq!(chosen, clean);
     }
+
+    #[test]
+    fn kf8_parts_become_chapters() {
+        use crate::services::renderer::BookReaderAdapter;
+
+        // KF8-only book: three parts, the second built from two fragments,
+        // with a CSS flow after the XHTML
+        let data = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/kf8_sample.azw3"
+        ))
+        .unwrap();
+        let offsets = MobiAdapter::parse_pdb_record_offsets(&data).unwrap();
+        assert_eq!(MobiAdapter::find_kf8_start(&data, &offsets), Some(0));
+
+        let mut adapter = MobiAdapter::new();
+        adapter.chapters = MobiAdapter::kf8_chapters(&data, &HashMap::new()).unwrap();
+        let toc = adapter.get_toc().unwrap();
+        let labels: Vec<&str> = toc.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, ["Chapter One", "Chapter Two", "Chapter Three"]);
+
+        let second = adapter.get_chapter(1).unwrap();
+        assert!(second
+            .content
+            .contains("<h1>Chapter Two</h1><p>Second fragment."));
+        assert!(!second.content.contains("<title>"));
+        assert!(!adapter.chapters[2].content.contains("color: red"));
+    }
 }
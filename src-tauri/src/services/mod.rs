@@ -1,10 +1,15 @@
+pub mod author_service;
+pub mod auto_scan_scheduler;
 pub mod cache;
 pub mod collection_service;
+pub mod djvu_adapter;
 pub mod docx_adapter;
+pub mod doodle_service;
 pub mod epub_adapter;
 pub mod export_service;
 pub mod fb2_reader_adapter;
 pub mod format_detector;
+pub mod goals_service;
 pub mod html_reader_adapter;
 pub mod library_service;
 pub mod markdown_reader_adapter;
@@ -15,19 +20,26 @@ pub mod reader_service;
 pub mod renderer;
 pub mod rendering_service;
 pub mod search_service;
+pub mod stats_service;
 pub mod tag_service;
+pub mod theme_service;
 pub mod txt_reader_adapter;
 
 // New v2.0 services
 pub mod adapters;
+pub mod api_service;
 pub mod calibre_service;
+pub mod cbr_archive;
 pub mod conversion_engine;
 pub mod cover_service;
+pub mod djvu_document;
 pub mod epub_builder;
 pub mod format_adapter;
 pub mod format_detection;
 pub mod manga_service;
+pub mod ocr_service;
 pub mod rss_scheduler;
+pub mod opds_service;
 pub mod rss_service;
 pub mod share_service;
 
@@ -39,6 +51,9 @@ pub mod online;
 // Backup/restore service
 pub mod backup_service;
 
+// Email delivery service
+pub mod email_service;
+
 // Translation/dictionary service
 pub mod translation_service;
 
@@ -0,0 +1,128 @@
+/// OCR fallback for scanned (image-only) PDFs.
+///
+/// Pages are rasterized with poppler's `pdftoppm` and recognized with the
+/// `tesseract` CLI, the same way Calibre is driven as an external tool.
+/// Everything here is blocking and meant to run inside `spawn_blocking`.
+use std::path::Path;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+/// Rasterization resolution; 300 DPI is tesseract's recommended input
+const OCR_DPI: &str = "300";
+
+/// Below this many non-whitespace characters per page the text layer is
+/// considered missing and OCR is attempted
+pub const MIN_CHARS_PER_PAGE: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum OcrError {
+    #[error("OCR tools (tesseract, pdftoppm) are not installed")]
+    Unavailable,
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("OCR failed: {0}")]
+    Failed(String),
+
+    #[error("OCR cancelled")]
+    Cancelled,
+}
+
+/// Whether `text` is too sparse for a document of `page_count` pages
+pub fn needs_ocr(text: &str, page_count: usize) -> bool {
+    let chars = text.chars().filter(|c| !c.is_whitespace()).count();
+    chars < MIN_CHARS_PER_PAGE * page_count.max(1)
+}
+
+/// True when both `tesseract` and `pdftoppm` can be launched
+pub fn is_available() -> bool {
+    let runs = |exe: &str, arg: &str| {
+        Command::new(exe)
+            .arg(arg)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    };
+    runs("tesseract", "--version") && runs("pdftoppm", "-v")
+}
+
+/// OCR every page of `pdf_path`. `cancel_check` is polled before each page and
+/// `on_page(done, total)` is called after each one.
+pub fn ocr_pdf(
+    pdf_path: &Path,
+    page_count: usize,
+    cancel_check: impl Fn() -> bool,
+    on_page: impl Fn(usize, usize),
+) -> Result<String, OcrError> {
+    if !is_available() {
+        return Err(OcrError::Unavailable);
+    }
+
+    let work_dir = tempfile::tempdir()?;
+    let mut text = String::new();
+
+    for page in 1..=page_count {
+        if cancel_check() {
+            return Err(OcrError::Cancelled);
+        }
+
+        let prefix = work_dir.path().join(format!("page-{}", page));
+        let page_str = page.to_string();
+        let status = Command::new("pdftoppm")
+            .args(["-r", OCR_DPI, "-gray", "-png", "-singlefile"])
+            .args(["-f", &page_str, "-l", &page_str])
+            .arg(pdf_path)
+            .arg(&prefix)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(OcrError::Failed(format!(
+                "pdftoppm exited with {} on page {}",
+                status, page
+            )));
+        }
+
+        let image = prefix.with_extension("png");
+        let output = Command::new("tesseract")
+            .arg(&image)
+            .arg("stdout")
+            .stderr(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(OcrError::Failed(format!(
+                "tesseract exited with {} on page {}",
+                output.status, page
+            )));
+        }
+        let _ = std::fs::remove_file(&image);
+
+        let page_text = String::from_utf8_lossy(&output.stdout);
+        let page_text = page_text.trim();
+        if !page_text.is_empty() {
+            if !text.is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str(page_text);
+        }
+        on_page(page, page_count);
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_ocr_threshold() {
+        assert!(needs_ocr("", 3));
+        assert!(needs_ocr("  \n\n  page 1  \n", 1));
+        assert!(!needs_ocr(&"word ".repeat(40), 1));
+        // Sparse text spread over many pages still counts as scanned
+        assert!(needs_ocr(&"word ".repeat(40), 20));
+    }
+}
@@ -1,8 +1,7 @@
-use super::provider::{FetchedMetadata, MetadataError, MetadataProvider, MetadataQuery};
+use super::provider::{FetchedMetadata, ItemType, MetadataError, MetadataProvider, MetadataQuery};
+use crate::utils::http;
 use async_trait::async_trait;
-use reqwest::Client;
 use serde::Deserialize;
-use std::time::Duration;
 
 pub async fn fetch_cover_by_title(client: &reqwest::Client, title: &str) -> Option<String> {
     let trimmed = title.trim();
@@ -42,23 +41,135 @@ pub async fn fetch_cover_by_title(client: &reqwest::Client, title: &str) -> Opti
 }
 
 pub struct AniListProvider {
-    client: Client,
     api_url: String,
 }
 
 impl AniListProvider {
     pub fn new() -> Result<Self, MetadataError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Shiori/0.1.0")
-            .build()
-            .map_err(MetadataError::RequestFailed)?;
-
         Ok(Self {
-            client,
             api_url: "https://graphql.anilist.co".to_string(),
         })
     }
+
+    /// Search AniList light novels (`type: MANGA`, `format: NOVEL`)
+    pub async fn search_light_novel(
+        &self,
+        title: &str,
+    ) -> Result<Option<FetchedMetadata>, MetadataError> {
+        self.search_media(title, "[NOVEL]").await
+    }
+
+    /// Search AniList `MANGA`-type media restricted to a GraphQL `format_in` list
+    async fn search_media(
+        &self,
+        title: &str,
+        formats: &str,
+    ) -> Result<Option<FetchedMetadata>, MetadataError> {
+        let graphql_query = format!(
+            r#"
+            query ($search: String) {{
+                Page(page: 1, perPage: 1) {{
+                    media(search: $search, type: MANGA, format_in: {}, sort: SEARCH_MATCH) {{
+                        id
+                        format
+                        title {{ romaji english }}
+                        description
+                        coverImage {{ extraLarge }}
+                        genres
+                        volumes
+                        chapters
+                        staff(perPage: 5) {{
+                            edges {{
+                                role
+                                node {{ name {{ full }} }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        "#,
+            formats
+        );
+
+        let variables = serde_json::json!({ "search": title });
+        let payload = serde_json::json!({
+            "query": graphql_query,
+            "variables": variables
+        });
+
+        let response = http::client()
+            .post(&self.api_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(MetadataError::RequestFailed)?;
+
+        if response.status() == 429 {
+            return Err(MetadataError::RateLimited { retry_after: 60 });
+        } else if !response.status().is_success() {
+            return Err(MetadataError::ParseFailed(format!(
+                "AniList API error: {}",
+                response.status()
+            )));
+        }
+
+        let result: GraphQLResponse = response
+            .json()
+            .await
+            .map_err(|e| MetadataError::ParseFailed(e.to_string()))?;
+
+        let media = match result
+            .data
+            .and_then(|d| d.page)
+            .and_then(|p| p.media.into_iter().next())
+        {
+            Some(media) => media,
+            None => return Ok(None),
+        };
+
+        let is_novel = media.format.as_deref() == Some("NOVEL");
+        let mut authors = Vec::new();
+        if let Some(staff_conn) = media.staff {
+            for edge in staff_conn.edges {
+                // Novels credit "Story" and "Illustration"; only the writer is an author
+                let is_author = if is_novel {
+                    edge.role.contains("Story") || edge.role.contains("Author")
+                } else {
+                    edge.role.contains("Story") || edge.role.contains("Art")
+                };
+                if is_author {
+                    authors.push(edge.node.name.full);
+                }
+            }
+        }
+
+        // Remove HTML tags from description
+        let description = media.description.map(|desc| {
+            desc.replace("<br>", "\n")
+                .replace("<br/>", "\n")
+                .replace("<i>", "")
+                .replace("</i>", "")
+                .replace("<b>", "")
+                .replace("</b>", "")
+        });
+
+        // For novels, volumes are the published books; chapters are rarely tracked
+        Ok(Some(FetchedMetadata {
+            provider_id: Some(self.name().to_string()),
+            title: Some(media.title.english.unwrap_or(media.title.romaji)),
+            authors,
+            description,
+            cover_url: media.cover_image.large.or(media.cover_image.extra_large),
+            genres: media.genres,
+            extra_data: Some(serde_json::json!({
+                "anilist_id": media.id,
+                "format": media.format,
+                "volumes": media.volumes,
+                "chapters": if is_novel { None } else { media.chapters },
+            })),
+            confidence: None,
+        }))
+    }
 }
 
 // Minimal GraphQL types needed for AniList search
@@ -87,6 +198,12 @@ struct MediaData {
     cover_image: CoverImageData,
     genres: Vec<String>,
     staff: Option<StaffConnection>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    volumes: Option<i32>,
+    #[serde(default)]
+    chapters: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -133,6 +250,14 @@ impl MetadataProvider for AniListProvider {
         is_manga // AniList only supports manga properly
     }
 
+    fn supports_item(&self, item_type: ItemType) -> bool {
+        // Comics land here too when Comic Vine has no API key or no match
+        matches!(
+            item_type,
+            ItemType::Manga | ItemType::LightNovel | ItemType::Comic
+        )
+    }
+
     async fn fetch_metadata(
         &self,
         query: &MetadataQuery,
@@ -142,95 +267,33 @@ impl MetadataProvider for AniListProvider {
             _ => return Ok(None), // ISBN not supported by AniList effectively
         };
 
-        let graphql_query = r#"
-            query ($search: String) {
-                Page(page: 1, perPage: 1) {
-                    media(search: $search, type: MANGA, format_in: [MANGA, ONE_SHOT], sort: SEARCH_MATCH) {
-                        id
-                        title { romaji english }
-                        description
-                        coverImage { extraLarge }
-                        genres
-                        staff(perPage: 5) {
-                            edges {
-                                role
-                                node { name { full } }
-                            }
-                        }
-                    }
-                }
-            }
-        "#;
-
-        let variables = serde_json::json!({ "search": title_to_search });
-        let payload = serde_json::json!({
-            "query": graphql_query,
-            "variables": variables
-        });
-
-        let response = self
-            .client
-            .post(&self.api_url)
-            .json(&payload)
-            .send()
+        self.search_media(title_to_search, "[MANGA, ONE_SHOT]")
             .await
-            .map_err(MetadataError::RequestFailed)?;
+    }
 
-        if response.status() == 429 {
-            return Err(MetadataError::RateLimited { retry_after: 60 });
-        } else if !response.status().is_success() {
-            return Err(MetadataError::ParseFailed(format!(
-                "AniList API error: {}",
-                response.status()
-            )));
+    async fn fetch_for_item(
+        &self,
+        item_type: ItemType,
+        query: &MetadataQuery,
+    ) -> Result<Option<FetchedMetadata>, MetadataError> {
+        if item_type != ItemType::LightNovel {
+            return self.fetch_metadata(query).await;
         }
 
-        let result: GraphQLResponse = response
-            .json()
-            .await
-            .map_err(|e| MetadataError::ParseFailed(e.to_string()))?;
+        let title_to_search = match query {
+            MetadataQuery::Title(t) | MetadataQuery::TitleAuthor { title: t, .. } => t,
+            _ => return Ok(None),
+        };
 
-        if let Some(data) = result.data {
-            if let Some(page) = data.page {
-                if let Some(media) = page.media.into_iter().next() {
-                    let mut authors = Vec::new();
-                    if let Some(staff_conn) = media.staff {
-                        for edge in staff_conn.edges {
-                            if edge.role.contains("Story") || edge.role.contains("Art") {
-                                authors.push(edge.node.name.full);
-                            }
-                        }
-                    }
-
-                    // Remove HTML tags from description
-                    let description = media.description.map(|desc| {
-                        desc.replace("<br>", "\n")
-                            .replace("<br/>", "\n")
-                            .replace("<i>", "")
-                            .replace("</i>", "")
-                            .replace("<b>", "")
-                            .replace("</b>", "")
-                    });
-
-                    return Ok(Some(FetchedMetadata {
-                        provider_id: Some(self.name().to_string()),
-                        title: Some(media.title.english.unwrap_or(media.title.romaji)),
-                        authors,
-                        description,
-                        cover_url: media.cover_image.large.or(media.cover_image.extra_large),
-                        genres: media.genres,
-                        extra_data: Some(serde_json::json!({"anilist_id": media.id})),
-                    }));
-                }
-            }
+        // Fall back to the manga entry when the novel isn't catalogued
+        match self.search_light_novel(title_to_search).await? {
+            Some(metadata) => Ok(Some(metadata)),
+            None => self.fetch_metadata(query).await,
         }
-
-        Ok(None)
     }
 
     async fn fetch_cover(&self, cover_url: &str) -> Result<Vec<u8>, MetadataError> {
-        let response = self
-            .client
+        let response = http::client()
             .get(cover_url)
             .send()
             .await
@@ -0,0 +1,159 @@
+//! Housekeeping for the `metadata_cache` table shared by the online
+//! providers and `BookMetadataService`.
+
+use crate::db::Database;
+use crate::error::Result;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Rows kept after expiry cleanup; the oldest beyond this are evicted
+pub const MAX_CACHE_ROWS: usize = 10_000;
+
+/// How often the background task prunes the cache after the startup run
+pub const CLEANUP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCacheStats {
+    pub provider: String,
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataCacheStats {
+    pub rows: usize,
+    /// Total size of the cached `response_json` payloads
+    pub bytes: u64,
+    pub expired_rows: usize,
+    pub providers: Vec<ProviderCacheStats>,
+}
+
+/// Delete expired rows, then evict the oldest rows above `max_rows`.
+/// Returns the number of rows removed.
+pub fn cleanup_metadata_cache(db: &Database, max_rows: usize) -> Result<usize> {
+    let conn = db.get_connection()?;
+
+    let expired = conn.execute(
+        "DELETE FROM metadata_cache WHERE expires_at <= CURRENT_TIMESTAMP",
+        [],
+    )?;
+
+    let evicted = conn.execute(
+        "DELETE FROM metadata_cache WHERE id IN (
+             SELECT id FROM metadata_cache ORDER BY created_at ASC, id ASC
+             LIMIT MAX(0, (SELECT COUNT(*) FROM metadata_cache) - ?1)
+         )",
+        rusqlite::params![max_rows as i64],
+    )?;
+
+    if expired + evicted > 0 {
+        log::info!(
+            "[MetadataCache] Removed {} expired and {} excess entries",
+            expired,
+            evicted
+        );
+    }
+    Ok(expired + evicted)
+}
+
+pub fn get_metadata_cache_stats(db: &Database) -> Result<MetadataCacheStats> {
+    let conn = db.get_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT provider, COUNT(*), COALESCE(SUM(LENGTH(CAST(response_json AS BLOB))), 0),
+                SUM(expires_at <= CURRENT_TIMESTAMP)
+         FROM metadata_cache GROUP BY provider ORDER BY provider",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            ProviderCacheStats {
+                provider: row.get(0)?,
+                rows: row.get::<_, i64>(1)? as usize,
+                bytes: row.get::<_, i64>(2)? as u64,
+            },
+            row.get::<_, i64>(3)? as usize,
+        ))
+    })?;
+
+    let mut stats = MetadataCacheStats::default();
+    for row in rows {
+        let (provider, expired) = row?;
+        stats.rows += provider.rows;
+        stats.bytes += provider.bytes;
+        stats.expired_rows += expired;
+        stats.providers.push(provider);
+    }
+    Ok(stats)
+}
+
+/// Prune the cache now and then every [`CLEANUP_INTERVAL`]
+pub fn spawn_cleanup_task(db: Database) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let db_for_cleanup = db.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                cleanup_metadata_cache(&db_for_cleanup, MAX_CACHE_ROWS)
+            })
+            .await;
+            match result {
+                Ok(Err(e)) => log::warn!("[MetadataCache] Cleanup failed: {}", e),
+                Err(e) => log::warn!("[MetadataCache] Cleanup task panicked: {}", e),
+                Ok(Ok(_)) => {}
+            }
+            tokio::time::sleep(CLEANUP_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(db: &Database, provider: &str, hash: &str, expires: &str) {
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "INSERT INTO metadata_cache (provider, query_hash, response_json, expires_at)
+                 VALUES (?1, ?2, '{\"title\":\"x\"}', datetime('now', ?3))",
+                rusqlite::params![provider, hash, expires],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_removes_only_expired_rows_and_caps_size() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+
+        insert(&db, "anilist", "expired", "-1 day");
+        insert(&db, "openlibrary", "fresh", "+7 days");
+
+        let stats = get_metadata_cache_stats(&db).unwrap();
+        assert_eq!(stats.rows, 2);
+        assert_eq!(stats.expired_rows, 1);
+        assert_eq!(stats.bytes, 2 * "{\"title\":\"x\"}".len() as u64);
+        assert_eq!(stats.providers.len(), 2);
+
+        assert_eq!(cleanup_metadata_cache(&db, MAX_CACHE_ROWS).unwrap(), 1);
+        let stats = get_metadata_cache_stats(&db).unwrap();
+        assert_eq!(stats.rows, 1);
+        assert_eq!(stats.providers[0].provider, "openlibrary");
+
+        // Over the cap the oldest rows go first
+        insert(&db, "openlibrary", "newer", "+7 days");
+        insert(&db, "openlibrary", "newest", "+7 days");
+        assert_eq!(cleanup_metadata_cache(&db, 2).unwrap(), 1);
+        let conn = db.get_connection().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT query_hash FROM metadata_cache ORDER BY id")
+            .unwrap();
+        let kept: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(kept, vec!["newer", "newest"]);
+    }
+}
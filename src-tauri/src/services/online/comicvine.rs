@@ -0,0 +1,371 @@
+use super::provider::{FetchedMetadata, ItemType, MetadataError, MetadataProvider, MetadataQuery};
+use crate::db::Database;
+use crate::services::manga_metadata_service::strip_html_tags;
+use crate::utils::http;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Comic Vine asks API clients to stay around one request per second
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Volumes considered per search before picking the closest title
+const SEARCH_LIMIT: usize = 5;
+
+/// Issues listed in `extra_data`; Comic Vine caps a page at 100
+const ISSUE_LIMIT: usize = 100;
+
+/// `status_code` Comic Vine returns once the hourly limit is spent
+const STATUS_RATE_LIMITED: i32 = 107;
+
+pub struct ComicVineProvider {
+    api_url: String,
+    /// The API key is read from preferences per request, so a changed key
+    /// applies without a restart
+    db: Database,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl ComicVineProvider {
+    pub fn new(db: Database) -> Self {
+        Self {
+            api_url: "https://comicvine.gamespot.com/api".to_string(),
+            db,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    fn api_key(&self) -> Option<String> {
+        self.db
+            .comicvine_api_key()
+            .map(|key| key.trim().to_string())
+    }
+
+    /// Wait until at least [`MIN_REQUEST_INTERVAL`] has passed since the last call
+    async fn throttle(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(previous) = *last_request {
+            let elapsed = previous.elapsed();
+            if elapsed < MIN_REQUEST_INTERVAL {
+                tokio::time::sleep(MIN_REQUEST_INTERVAL - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    async fn get<T: DeserializeOwned + Default>(
+        &self,
+        resource: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, MetadataError> {
+        let api_key = self.api_key().ok_or_else(|| {
+            MetadataError::ParseFailed("Comic Vine API key is not set".to_string())
+        })?;
+        self.throttle().await;
+
+        // The key is part of the query string; keep the URL out of errors and logs
+        let response = http::client()
+            .get(format!("{}/{}/", self.api_url, resource))
+            .query(&[("api_key", api_key.as_str()), ("format", "json")])
+            .query(params)
+            .send()
+            .await
+            .map_err(|e| MetadataError::RequestFailed(e.without_url()))?;
+
+        // Comic Vine answers 420 when requests come in too fast
+        if matches!(response.status().as_u16(), 420 | 429) {
+            return Err(MetadataError::RateLimited { retry_after: 60 });
+        } else if !response.status().is_success() {
+            return Err(MetadataError::ParseFailed(format!(
+                "Comic Vine API error: {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| MetadataError::ParseFailed(e.without_url().to_string()))?;
+
+        match body.status_code {
+            1 => Ok(body.results),
+            STATUS_RATE_LIMITED => Err(MetadataError::RateLimited { retry_after: 60 }),
+            _ => Err(MetadataError::ParseFailed(format!(
+                "Comic Vine API error: {}",
+                body.error
+            ))),
+        }
+    }
+
+    async fn search_volume(&self, title: &str) -> Result<Option<Volume>, MetadataError> {
+        let volumes: Vec<Volume> = self
+            .get(
+                "search",
+                &[
+                    ("query", title.to_string()),
+                    ("resources", "volume".to_string()),
+                    ("limit", SEARCH_LIMIT.to_string()),
+                    (
+                        "field_list",
+                        "id,name,deck,description,image,publisher,start_year,count_of_issues,site_detail_url"
+                            .to_string(),
+                    ),
+                ],
+            )
+            .await?;
+
+        Ok(best_volume(title, volumes))
+    }
+
+    async fn volume_issues(&self, volume_id: i64) -> Result<Vec<Issue>, MetadataError> {
+        self.get(
+            "issues",
+            &[
+                ("filter", format!("volume:{}", volume_id)),
+                ("sort", "cover_date:asc".to_string()),
+                ("limit", ISSUE_LIMIT.to_string()),
+                (
+                    "field_list",
+                    "id,name,issue_number,cover_date,image".to_string(),
+                ),
+            ],
+        )
+        .await
+    }
+}
+
+/// The search result whose name is closest to the queried title
+fn best_volume(title: &str, volumes: Vec<Volume>) -> Option<Volume> {
+    let wanted = title.to_lowercase();
+    volumes.into_iter().max_by(|a, b| {
+        let score = |v: &Volume| strsim::normalized_levenshtein(&wanted, &v.name.to_lowercase());
+        score(a).total_cmp(&score(b))
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    #[serde(default)]
+    error: String,
+    status_code: i32,
+    #[serde(default)]
+    results: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct Volume {
+    id: i64,
+    name: String,
+    deck: Option<String>,
+    description: Option<String>,
+    image: Option<ImageData>,
+    publisher: Option<Publisher>,
+    start_year: Option<String>,
+    count_of_issues: Option<i64>,
+    site_detail_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    id: i64,
+    name: Option<String>,
+    issue_number: Option<String>,
+    cover_date: Option<String>,
+    image: Option<ImageData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageData {
+    original_url: Option<String>,
+    super_url: Option<String>,
+    medium_url: Option<String>,
+}
+
+impl ImageData {
+    /// Largest available size, skipping Comic Vine's "no cover" placeholder
+    fn best_url(&self) -> Option<String> {
+        self.original_url
+            .as_ref()
+            .or(self.super_url.as_ref())
+            .or(self.medium_url.as_ref())
+            .filter(|url| !url.contains("blank"))
+            .cloned()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Publisher {
+    name: String,
+}
+
+#[async_trait]
+impl MetadataProvider for ComicVineProvider {
+    fn name(&self) -> &'static str {
+        "comicvine"
+    }
+
+    fn supports_media(&self, _is_manga: bool) -> bool {
+        false // Comic Vine only catalogues western comics, see `supports_item`
+    }
+
+    fn supports_item(&self, item_type: ItemType) -> bool {
+        item_type == ItemType::Comic && self.api_key().is_some()
+    }
+
+    async fn fetch_metadata(
+        &self,
+        query: &MetadataQuery,
+    ) -> Result<Option<FetchedMetadata>, MetadataError> {
+        let title = match query {
+            MetadataQuery::Title(t) | MetadataQuery::TitleAuthor { title: t, .. } => t,
+            _ => return Ok(None),
+        };
+
+        let volume = match self.search_volume(title).await? {
+            Some(volume) => volume,
+            None => return Ok(None),
+        };
+
+        // Issue details are a bonus; keep the volume if only they fail
+        let issues = match self.volume_issues(volume.id).await {
+            Ok(issues) => issues,
+            Err(e @ MetadataError::RateLimited { .. }) => return Err(e),
+            Err(e) => {
+                log::warn!(
+                    "[ComicVine] Failed to fetch issues for volume {}: {}",
+                    volume.id,
+                    e
+                );
+                Vec::new()
+            }
+        };
+
+        // Prefer the short plain-text deck; descriptions are full HTML articles
+        let description = volume
+            .deck
+            .filter(|deck| !deck.trim().is_empty())
+            .or_else(|| volume.description.as_deref().map(strip_html_tags));
+
+        let issues: Vec<serde_json::Value> = issues
+            .into_iter()
+            .map(|issue| {
+                serde_json::json!({
+                    "comicvine_id": issue.id,
+                    "issue_number": issue.issue_number,
+                    "name": issue.name,
+                    "cover_date": issue.cover_date,
+                    "cover_url": issue.image.and_then(|image| image.best_url()),
+                })
+            })
+            .collect();
+
+        Ok(Some(FetchedMetadata {
+            provider_id: Some(self.name().to_string()),
+            title: Some(volume.name),
+            authors: Vec::new(),
+            description,
+            cover_url: volume.image.and_then(|image| image.best_url()),
+            genres: Vec::new(),
+            extra_data: Some(serde_json::json!({
+                "comicvine_id": volume.id,
+                "publisher": volume.publisher.map(|p| p.name),
+                "start_year": volume.start_year,
+                "issue_count": volume.count_of_issues,
+                "site_url": volume.site_detail_url,
+                "issues": issues,
+            })),
+            confidence: None,
+        }))
+    }
+
+    async fn fetch_cover(&self, cover_url: &str) -> Result<Vec<u8>, MetadataError> {
+        let response = http::client()
+            .get(cover_url)
+            .send()
+            .await
+            .map_err(MetadataError::RequestFailed)?;
+
+        if matches!(response.status().as_u16(), 420 | 429) {
+            return Err(MetadataError::RateLimited { retry_after: 60 });
+        } else if !response.status().is_success() {
+            return Err(MetadataError::ParseFailed(format!(
+                "Failed to download cover: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|_| MetadataError::ImageError("Failed to read image bytes".to_string()))?;
+
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_response_and_pick_closest_volume() {
+        let json = r#"{
+            "error": "OK",
+            "status_code": 1,
+            "results": [
+                {
+                    "id": 1,
+                    "name": "Saga of the Swamp Thing",
+                    "image": { "original_url": "https://comicvine.example/a.jpg" },
+                    "resource_type": "volume"
+                },
+                {
+                    "id": 2,
+                    "name": "Saga",
+                    "deck": "Space opera by Brian K. Vaughan.",
+                    "image": { "original_url": "https://comicvine.example/6373148-blank.png" },
+                    "publisher": { "name": "Image" },
+                    "start_year": "2012",
+                    "count_of_issues": 66,
+                    "resource_type": "volume"
+                }
+            ]
+        }"#;
+
+        let response: ApiResponse<Vec<Volume>> = serde_json::from_str(json).unwrap();
+        assert_eq!(response.status_code, 1);
+
+        let volume = best_volume("saga", response.results).unwrap();
+        assert_eq!(volume.id, 2);
+        assert_eq!(volume.publisher.unwrap().name, "Image");
+        assert_eq!(volume.image.unwrap().best_url(), None);
+
+        let error: ApiResponse<Vec<Volume>> =
+            serde_json::from_str(r#"{"error":"Invalid API Key","status_code":100,"results":[]}"#)
+                .unwrap();
+        assert_eq!(error.status_code, 100);
+        assert!(error.results.is_empty());
+    }
+
+    #[test]
+    fn test_claims_only_comics_and_follows_key_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let provider = ComicVineProvider::new(db.clone());
+        assert!(!provider.supports_item(ItemType::Comic));
+
+        db.get_connection()
+            .unwrap()
+            .execute(
+                "UPDATE user_preferences SET comicvine_api_key = ' key ' WHERE id = 1",
+                [],
+            )
+            .unwrap();
+        assert!(provider.supports_item(ItemType::Comic));
+        assert_eq!(provider.api_key().as_deref(), Some("key"));
+        assert!(!provider.supports_item(ItemType::Manga));
+        assert!(!provider.supports_item(ItemType::Book));
+    }
+}
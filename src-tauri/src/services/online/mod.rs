@@ -1,4 +1,6 @@
 pub mod anilist;
+pub mod cache;
+pub mod comicvine;
 pub mod openlibrary;
 pub mod provider;
 pub mod worker;
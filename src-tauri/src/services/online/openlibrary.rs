@@ -1,25 +1,16 @@
 use super::provider::{FetchedMetadata, MetadataError, MetadataProvider, MetadataQuery};
+use crate::utils::http;
 use async_trait::async_trait;
-use reqwest::Client;
 use serde::Deserialize;
-use std::time::Duration;
 
 pub struct OpenLibraryProvider {
-    client: Client,
     base_url: String,
     covers_url: String,
 }
 
 impl OpenLibraryProvider {
     pub fn new() -> Result<Self, MetadataError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Shiori/0.1.0")
-            .build()
-            .map_err(MetadataError::RequestFailed)?;
-
         Ok(Self {
-            client,
             base_url: "https://openlibrary.org".to_string(),
             covers_url: "https://covers.openlibrary.org".to_string(),
         })
@@ -78,8 +69,7 @@ impl MetadataProvider for OpenLibraryProvider {
             }
         };
 
-        let response = self
-            .client
+        let response = http::client()
             .get(&url)
             .send()
             .await
@@ -112,6 +102,7 @@ impl MetadataProvider for OpenLibraryProvider {
                 cover_url,
                 genres: doc.subject.unwrap_or_default(),
                 extra_data: Some(serde_json::json!({"openlibrary_id": doc.key})),
+                confidence: None,
             }));
         }
 
@@ -119,8 +110,7 @@ impl MetadataProvider for OpenLibraryProvider {
     }
 
     async fn fetch_cover(&self, cover_url: &str) -> Result<Vec<u8>, MetadataError> {
-        let response = self
-            .client
+        let response = http::client()
             .get(cover_url)
             .send()
             .await
@@ -28,10 +28,14 @@ pub enum MetadataQuery {
     Title(String),
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemType {
     Book,
     Manga,
+    /// Prose light novels, catalogued by AniList as `MANGA` with `format: NOVEL`
+    LightNovel,
+    /// Comic archives routed to Comic Vine, with AniList as the fallback
+    Comic,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -43,6 +47,9 @@ pub struct FetchedMetadata {
     pub cover_url: Option<String>,
     pub genres: Vec<String>,
     pub extra_data: Option<serde_json::Value>,
+    /// How closely the result matches the query (0.0–1.0), set by the worker
+    #[serde(default)]
+    pub confidence: Option<f64>,
 }
 
 #[allow(dead_code)]
@@ -54,12 +61,27 @@ pub trait MetadataProvider: Send + Sync {
     /// Does the provider support this media type?
     fn supports_media(&self, is_manga: bool) -> bool;
 
+    /// Does the provider support this item type? Defaults to `supports_media`.
+    fn supports_item(&self, item_type: ItemType) -> bool {
+        self.supports_media(matches!(item_type, ItemType::Manga | ItemType::Comic))
+    }
+
     /// Primary entry point for metadata fetching
     async fn fetch_metadata(
         &self,
         query: &MetadataQuery,
     ) -> Result<Option<FetchedMetadata>, MetadataError>;
 
+    /// Fetch metadata for a specific item type. Providers that distinguish
+    /// between item types (e.g. manga vs light novels) override this.
+    async fn fetch_for_item(
+        &self,
+        _item_type: ItemType,
+        query: &MetadataQuery,
+    ) -> Result<Option<FetchedMetadata>, MetadataError> {
+        self.fetch_metadata(query).await
+    }
+
     /// Dedicated cover fetcher (bypasses URL extraction logic for retries)
     async fn fetch_cover(&self, cover_url: &str) -> Result<Vec<u8>, MetadataError>;
 }
@@ -2,16 +2,68 @@ use crate::db::Database;
 use crate::services::online::provider::{
     FetchedMetadata, ItemType, MetadataProvider, MetadataQuery,
 };
+use dashmap::{DashMap, DashSet};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Semaphore};
 
+/// Results scoring below this are held for user confirmation instead of applied
+pub const MIN_AUTO_APPLY_CONFIDENCE: f64 = 0.6;
+
+/// A low-confidence provider match awaiting user confirmation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMetadataMatch {
+    pub id: i64,
+    pub book_id: i64,
+    pub provider: String,
+    pub query_title: Option<String>,
+    pub confidence: f64,
+    pub metadata: FetchedMetadata,
+    pub created_at: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct MetadataJob {
     pub item_id: i64,
     pub item_type: ItemType,
     pub query: MetadataQuery,
     pub force_refresh: bool,
+    /// Set when the job was queued as part of a collection-wide enrichment
+    pub batch: Option<Arc<MetadataBatch>>,
+}
+
+/// Aggregate progress shared by every job of a collection enrichment
+#[derive(Debug)]
+pub struct MetadataBatch {
+    pub collection_id: i64,
+    pub total: usize,
+    completed: AtomicUsize,
+}
+
+impl MetadataBatch {
+    pub fn new(collection_id: i64, total: usize) -> Self {
+        Self {
+            collection_id,
+            total,
+            completed: AtomicUsize::new(0),
+        }
+    }
+
+    /// Mark one job finished and return the number completed so far
+    fn complete_one(&self) -> usize {
+        self.completed.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+/// How one provider's lookup for a job ended
+enum LookupOutcome {
+    Found(FetchedMetadata),
+    NotFound,
+    /// Cancelled, failed or still rate limited; any event was already emitted
+    Stopped,
 }
 
 pub struct MetadataWorker {
@@ -19,6 +71,10 @@ pub struct MetadataWorker {
     pub providers: Vec<Arc<dyn MetadataProvider>>,
     pub sender: mpsc::Sender<MetadataJob>,
     pub app_handle: Option<tauri::AppHandle>,
+    /// Book ids whose queued or running jobs should stop (soft cancellation)
+    pub cancelled: Arc<DashSet<i64>>,
+    /// Number of queued or running jobs per book id, see [`Self::track_queued`]
+    pub queued: Arc<DashMap<i64, usize>>,
 }
 
 impl MetadataWorker {
@@ -30,6 +86,8 @@ impl MetadataWorker {
             providers: Vec::new(),
             sender: tx,
             app_handle: None,
+            cancelled: Arc::new(DashSet::new()),
+            queued: Arc::new(DashMap::new()),
         };
 
         (worker, rx)
@@ -47,6 +105,8 @@ impl MetadataWorker {
         let db = self.db.clone();
         let providers = self.providers.clone();
         let handle_opt = self.app_handle.clone();
+        let cancelled = self.cancelled.clone();
+        let queued = self.queued.clone();
 
         tauri::async_runtime::spawn(async move {
             let semaphore = Arc::new(Semaphore::new(2)); // Max 2 concurrent HTTP requests
@@ -56,13 +116,23 @@ impl MetadataWorker {
                     "[MetadataWorker] Processing job for item_id: {}",
                     job.item_id
                 );
+                if Self::take_cancelled(&cancelled, &job, handle_opt.as_ref()) {
+                    Self::finish_job(&cancelled, &queued, job.item_id);
+                    Self::report_batch_progress(&job, handle_opt.as_ref());
+                    continue;
+                }
+
                 // 1. Determine if manga or book
-                let is_manga = matches!(job.item_type, ItemType::Manga);
+                let is_manga = matches!(job.item_type, ItemType::Manga | ItemType::Comic);
 
-                // 2. Select appropriate provider
-                let provider = providers.iter().find(|p| p.supports_media(is_manga));
+                // 2. Select providers in registration order; later ones are
+                // fallbacks (comics Comic Vine doesn't know go on to AniList)
+                let candidates: Vec<&Arc<dyn MetadataProvider>> = providers
+                    .iter()
+                    .filter(|p| p.supports_item(job.item_type))
+                    .collect();
 
-                let provider_name = provider.map(|p| p.name()).unwrap_or("unknown");
+                let provider_name = candidates.first().map(|p| p.name()).unwrap_or("unknown");
 
                 // Emit loading state
                 if let Some(handle) = &handle_opt {
@@ -77,163 +147,437 @@ impl MetadataWorker {
                     );
                 }
 
-                if let Some(p) = provider {
-                    let query_hash = Self::compute_query_hash(&job.query);
-
-                    let mut cached_metadata = None;
+                if candidates.is_empty() {
+                    log::warn!(
+                        "[MetadataWorker] No provider supports item type {:?}",
+                        job.item_type
+                    );
+                }
 
-                    // Check local cache first (unless forced)
-                    if !job.force_refresh {
-                        if let Ok(conn) = db.get_connection() {
-                            let stmt = conn.prepare(
-                                "SELECT response_json FROM metadata_cache 
-                                 WHERE provider = ?1 AND query_hash = ?2 AND expires_at > CURRENT_TIMESTAMP"
-                            );
-                            if let Ok(mut stmt) = stmt {
-                                if let Ok(json_str) = stmt
-                                    .query_row(rusqlite::params![p.name(), query_hash], |row| {
-                                        row.get::<_, String>(0)
-                                    })
-                                {
-                                    if let Ok(metadata) =
-                                        serde_json::from_str::<FetchedMetadata>(&json_str)
-                                    {
-                                        cached_metadata = Some(metadata);
-                                    }
-                                }
-                            }
+                let mut not_found_via = None;
+                for p in candidates {
+                    match Self::lookup(
+                        &db,
+                        p.as_ref(),
+                        &job,
+                        &cancelled,
+                        handle_opt.as_ref(),
+                        &semaphore,
+                    )
+                    .await
+                    {
+                        LookupOutcome::Found(metadata) => {
+                            // 5. Update DB or hold for confirmation, then emit Tauri event
+                            Self::resolve_metadata(
+                                &db,
+                                &job,
+                                p.name(),
+                                metadata,
+                                is_manga,
+                                handle_opt.as_ref(),
+                            )
+                            .await;
+                            not_found_via = None;
+                            break;
+                        }
+                        LookupOutcome::NotFound => {
+                            log::info!("[MetadataWorker] No metadata found via {}", p.name());
+                            not_found_via = Some(p.name());
+                        }
+                        LookupOutcome::Stopped => {
+                            not_found_via = None;
+                            break;
                         }
                     }
+                }
 
-                    if let Some(metadata) = cached_metadata {
-                        log::info!(
-                            "[MetadataWorker] Cache HIT for query {} via {}",
-                            query_hash,
-                            p.name()
+                if let Some(provider) = not_found_via {
+                    if let Some(handle) = &handle_opt {
+                        use tauri::Emitter;
+                        let _ = handle.emit(
+                            "metadata-update",
+                            serde_json::json!({
+                                "bookId": job.item_id,
+                                "status": "not_found",
+                                "provider": provider
+                            }),
                         );
-                        Self::apply_metadata(&db, job.item_id, metadata, is_manga).await;
-
-                        if let Some(handle) = &handle_opt {
-                            use tauri::Emitter;
-                            let _ = handle.emit(
-                                "metadata-update",
-                                serde_json::json!({
-                                    "bookId": job.item_id,
-                                    "status": "success",
-                                    "provider": p.name()
-                                }),
-                            );
-                        }
-                        continue;
                     }
+                }
 
-                    let sem_clone = semaphore.clone();
-                    let permit = sem_clone.acquire().await;
-
-                    if let Ok(_permit) = permit {
-                        let mut attempts = 0;
-                        while attempts < 3 {
-                            match p.fetch_metadata(&job.query).await {
-                                Ok(Some(metadata)) => {
-                                    log::info!(
-                                        "[MetadataWorker] Successfully fetched metadata via {}",
-                                        p.name()
-                                    );
-
-                                    // Cache the result
-                                    if let Ok(conn) = db.get_connection() {
-                                        if let Ok(json_str) = serde_json::to_string(&metadata) {
-                                            let _ = conn.execute(
-                                                "INSERT OR REPLACE INTO metadata_cache (provider, query_hash, response_json, expires_at)
-                                                 VALUES (?1, ?2, ?3, datetime('now', '+7 days'))",
-                                                rusqlite::params![p.name(), query_hash, json_str]
-                                            );
-                                        }
-                                    }
-
-                                    // 5. Update DB (resolve conflicts with offline-first hierarchy)
-                                    Self::apply_metadata(&db, job.item_id, metadata, is_manga)
-                                        .await;
-
-                                    // 6. Emit Tauri event
-                                    if let Some(handle) = &handle_opt {
-                                        use tauri::Emitter;
-                                        let _ = handle.emit(
-                                            "metadata-update",
-                                            serde_json::json!({
-                                                "bookId": job.item_id,
-                                                "status": "success",
-                                                "provider": p.name()
-                                            }),
-                                        );
-                                    }
-
-                                    break;
-                                }
-                                Ok(None) => {
-                                    log::info!(
-                                        "[MetadataWorker] No metadata found via {}",
-                                        p.name()
-                                    );
-                                    if let Some(handle) = &handle_opt {
-                                        use tauri::Emitter;
-                                        let _ = handle.emit(
-                                            "metadata-update",
-                                            serde_json::json!({
-                                                "bookId": job.item_id,
-                                                "status": "not_found",
-                                                "provider": p.name()
-                                            }),
-                                        );
-                                    }
-                                    break;
-                                }
-                                Err(
-                                    crate::services::online::provider::MetadataError::RateLimited {
-                                        retry_after,
-                                    },
-                                ) => {
-                                    log::warn!(
-                                        "[MetadataWorker] Rate limited, waiting {}s",
-                                        retry_after
-                                    );
-                                    tokio::time::sleep(std::time::Duration::from_secs(retry_after))
-                                        .await;
-                                    attempts += 1;
-                                }
-                                Err(e) => {
-                                    log::error!(
-                                        "[MetadataWorker] Error fetching metadata: {:?}",
-                                        e
-                                    );
-                                    if let Some(handle) = &handle_opt {
-                                        use tauri::Emitter;
-                                        let _ = handle.emit(
-                                            "metadata-update",
-                                            serde_json::json!({
-                                                "bookId": job.item_id,
-                                                "status": "error",
-                                                "error": e.to_string(),
-                                                "provider": p.name()
-                                            }),
-                                        );
-                                    }
-                                    break;
-                                }
+                Self::finish_job(&cancelled, &queued, job.item_id);
+                Self::report_batch_progress(&job, handle_opt.as_ref());
+            }
+        });
+    }
+
+    /// Count a job about to be queued for `item_id`. A new request supersedes
+    /// an earlier cancellation of the same book.
+    pub fn track_queued(cancelled: &DashSet<i64>, queued: &DashMap<i64, usize>, item_id: i64) {
+        cancelled.remove(&item_id);
+        *queued.entry(item_id).or_insert(0) += 1;
+    }
+
+    /// Cancel every queued or running job. Returns the number of books affected.
+    pub fn cancel_all(cancelled: &DashSet<i64>, queued: &DashMap<i64, usize>) -> usize {
+        let mut count = 0;
+        for entry in queued.iter() {
+            cancelled.insert(*entry.key());
+            count += 1;
+        }
+        count
+    }
+
+    /// Release a finished job's slot. Once no job for the book is left, a
+    /// cancellation that arrived too late to stop it is dropped as well.
+    pub fn finish_job(cancelled: &DashSet<i64>, queued: &DashMap<i64, usize>, item_id: i64) {
+        queued.remove_if_mut(&item_id, |_, count| {
+            *count = count.saturating_sub(1);
+            *count == 0
+        });
+        if !queued.contains_key(&item_id) {
+            cancelled.remove(&item_id);
+        }
+    }
+
+    /// Look the job up with one provider: the local cache first (unless
+    /// forced), then the provider itself, retrying when rate limited
+    async fn lookup(
+        db: &Database,
+        p: &dyn MetadataProvider,
+        job: &MetadataJob,
+        cancelled: &DashSet<i64>,
+        handle: Option<&tauri::AppHandle>,
+        semaphore: &Semaphore,
+    ) -> LookupOutcome {
+        let query_hash = Self::compute_query_hash(job.item_type, &job.query);
+
+        // Check local cache first (unless forced)
+        if !job.force_refresh {
+            if let Ok(conn) = db.get_connection() {
+                let stmt = conn.prepare(
+                    "SELECT response_json FROM metadata_cache 
+                     WHERE provider = ?1 AND query_hash = ?2 AND expires_at > CURRENT_TIMESTAMP",
+                );
+                if let Ok(mut stmt) = stmt {
+                    if let Ok(json_str) = stmt
+                        .query_row(rusqlite::params![p.name(), query_hash], |row| {
+                            row.get::<_, String>(0)
+                        })
+                    {
+                        if let Ok(metadata) = serde_json::from_str::<FetchedMetadata>(&json_str) {
+                            if Self::take_cancelled(cancelled, job, handle) {
+                                return LookupOutcome::Stopped;
                             }
+                            log::info!(
+                                "[MetadataWorker] Cache HIT for query {} via {}",
+                                query_hash,
+                                p.name()
+                            );
+                            return LookupOutcome::Found(metadata);
                         }
                     }
-                } else {
-                    log::warn!(
-                        "[MetadataWorker] No provider supports item type {:?}",
-                        job.item_type
+                }
+            }
+        }
+
+        let _permit = match semaphore.acquire().await {
+            Ok(permit) => permit,
+            Err(_) => return LookupOutcome::Stopped,
+        };
+
+        for _ in 0..3 {
+            if Self::take_cancelled(cancelled, job, handle) {
+                return LookupOutcome::Stopped;
+            }
+            match p.fetch_for_item(job.item_type, &job.query).await {
+                Ok(Some(metadata)) => {
+                    log::info!(
+                        "[MetadataWorker] Successfully fetched metadata via {}",
+                        p.name()
                     );
+
+                    // Cache the result
+                    if let Ok(conn) = db.get_connection() {
+                        if let Ok(json_str) = serde_json::to_string(&metadata) {
+                            let _ = conn.execute(
+                                "INSERT OR REPLACE INTO metadata_cache (provider, query_hash, response_json, expires_at)
+                                 VALUES (?1, ?2, ?3, datetime('now', '+7 days'))",
+                                rusqlite::params![p.name(), query_hash, json_str]
+                            );
+                        }
+                    }
+
+                    if Self::take_cancelled(cancelled, job, handle) {
+                        return LookupOutcome::Stopped;
+                    }
+                    return LookupOutcome::Found(metadata);
+                }
+                Ok(None) => return LookupOutcome::NotFound,
+                Err(crate::services::online::provider::MetadataError::RateLimited {
+                    retry_after,
+                }) => {
+                    log::warn!("[MetadataWorker] Rate limited, waiting {}s", retry_after);
+                    tokio::time::sleep(std::time::Duration::from_secs(retry_after)).await;
+                }
+                Err(e) => {
+                    log::error!("[MetadataWorker] Error fetching metadata: {:?}", e);
+                    if let Some(handle) = handle {
+                        use tauri::Emitter;
+                        let _ = handle.emit(
+                            "metadata-update",
+                            serde_json::json!({
+                                "bookId": job.item_id,
+                                "status": "error",
+                                "error": e.to_string(),
+                                "provider": p.name()
+                            }),
+                        );
+                    }
+                    return LookupOutcome::Stopped;
                 }
             }
-        });
+        }
+        LookupOutcome::Stopped
+    }
+
+    /// Consume a pending cancellation for the job's book, emitting
+    /// `metadata:cancelled` when there was one
+    fn take_cancelled(
+        cancelled: &DashSet<i64>,
+        job: &MetadataJob,
+        handle: Option<&tauri::AppHandle>,
+    ) -> bool {
+        if cancelled.remove(&job.item_id).is_none() {
+            return false;
+        }
+        log::info!(
+            "[MetadataWorker] Cancelled job for item_id: {}",
+            job.item_id
+        );
+        if let Some(handle) = handle {
+            use tauri::Emitter;
+            let _ = handle.emit(
+                "metadata:cancelled",
+                serde_json::json!({ "bookId": job.item_id }),
+            );
+        }
+        true
+    }
+
+    /// Emit the aggregate `metadata:collection_progress` event for batched jobs
+    fn report_batch_progress(job: &MetadataJob, handle: Option<&tauri::AppHandle>) {
+        let batch = match &job.batch {
+            Some(batch) => batch,
+            None => return,
+        };
+        let completed = batch.complete_one();
+        if let Some(handle) = handle {
+            use tauri::Emitter;
+            let _ = handle.emit(
+                "metadata:collection_progress",
+                serde_json::json!({
+                    "collectionId": batch.collection_id,
+                    "completed": completed,
+                    "total": batch.total,
+                    "done": completed >= batch.total
+                }),
+            );
+        }
+    }
+
+    /// Score a provider result and either apply it or park it in
+    /// `pending_metadata_matches`, emitting the matching event
+    async fn resolve_metadata(
+        db: &Database,
+        job: &MetadataJob,
+        provider: &str,
+        mut metadata: FetchedMetadata,
+        is_manga: bool,
+        handle: Option<&tauri::AppHandle>,
+    ) {
+        let confidence = Self::compute_confidence(&job.query, &metadata);
+        metadata.confidence = Some(confidence);
+
+        if confidence < MIN_AUTO_APPLY_CONFIDENCE {
+            log::info!(
+                "[MetadataWorker] Low confidence match ({:.2}) for book {}, awaiting confirmation",
+                confidence,
+                job.item_id
+            );
+            let match_id = match Self::store_pending_match(db, job, provider, &metadata) {
+                Ok(id) => id,
+                Err(e) => {
+                    log::error!("[MetadataWorker] Failed to store pending match: {}", e);
+                    return;
+                }
+            };
+            if let Some(handle) = handle {
+                use tauri::Emitter;
+                let _ = handle.emit(
+                    "metadata:needs_confirmation",
+                    serde_json::json!({
+                        "bookId": job.item_id,
+                        "matchId": match_id,
+                        "confidence": confidence,
+                        "title": metadata.title,
+                        "provider": provider
+                    }),
+                );
+            }
+            return;
+        }
+
+        Self::apply_metadata(db, job.item_id, metadata, is_manga).await;
+
+        if let Some(handle) = handle {
+            use tauri::Emitter;
+            let _ = handle.emit(
+                "metadata-update",
+                serde_json::json!({
+                    "bookId": job.item_id,
+                    "status": "success",
+                    "provider": provider,
+                    "confidence": confidence
+                }),
+            );
+        }
     }
 
-    async fn apply_metadata(db: &Database, item_id: i64, meta: FetchedMetadata, _is_manga: bool) {
+    /// Similarity between the queried and returned titles, boosted when the
+    /// author also matches. ISBN lookups are exact and always score 1.0.
+    pub fn compute_confidence(query: &MetadataQuery, meta: &FetchedMetadata) -> f64 {
+        let (title, author) = match query {
+            MetadataQuery::Isbn(_) => return 1.0,
+            MetadataQuery::Title(title) => (title, None),
+            MetadataQuery::TitleAuthor { title, author } => (title, author.as_ref()),
+        };
+
+        let found = match &meta.title {
+            Some(found) => found,
+            None => return 0.0,
+        };
+        let mut score = strsim::normalized_levenshtein(
+            &Self::normalize_for_match(title),
+            &Self::normalize_for_match(found),
+        );
+
+        if let Some(author) = author {
+            let wanted = Self::normalize_for_match(author);
+            let author_matches = !wanted.is_empty()
+                && meta.authors.iter().any(|a| {
+                    strsim::normalized_levenshtein(&wanted, &Self::normalize_for_match(a)) >= 0.8
+                });
+            if author_matches {
+                score += 0.2;
+            }
+        }
+
+        score.min(1.0)
+    }
+
+    fn normalize_for_match(value: &str) -> String {
+        value
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn store_pending_match(
+        db: &Database,
+        job: &MetadataJob,
+        provider: &str,
+        meta: &FetchedMetadata,
+    ) -> crate::error::Result<i64> {
+        let query_title = match &job.query {
+            MetadataQuery::Isbn(isbn) => isbn.clone(),
+            MetadataQuery::Title(title) => title.clone(),
+            MetadataQuery::TitleAuthor { title, .. } => title.clone(),
+        };
+        let conn = db.get_connection()?;
+        // Replace any earlier unresolved match for the same book
+        conn.execute(
+            "DELETE FROM pending_metadata_matches WHERE book_id = ?1",
+            rusqlite::params![job.item_id],
+        )?;
+        conn.execute(
+            "INSERT INTO pending_metadata_matches (book_id, provider, query_title, confidence, metadata_json)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                job.item_id,
+                provider,
+                query_title,
+                meta.confidence.unwrap_or(0.0),
+                serde_json::to_string(meta)?
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// List unresolved low-confidence matches, newest first
+    pub fn list_pending_matches(db: &Database) -> crate::error::Result<Vec<PendingMetadataMatch>> {
+        let conn = db.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, book_id, provider, query_title, confidence, metadata_json, created_at
+             FROM pending_metadata_matches ORDER BY created_at DESC, id DESC",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_pending_match)?;
+        let mut matches = Vec::new();
+        for row in rows {
+            matches.push(row?);
+        }
+        Ok(matches)
+    }
+
+    /// Remove a pending match and return it, if it exists
+    pub fn take_pending_match(
+        db: &Database,
+        match_id: i64,
+    ) -> crate::error::Result<Option<PendingMetadataMatch>> {
+        let conn = db.get_connection()?;
+        let pending = match conn.query_row(
+            "SELECT id, book_id, provider, query_title, confidence, metadata_json, created_at
+             FROM pending_metadata_matches WHERE id = ?1",
+            rusqlite::params![match_id],
+            Self::row_to_pending_match,
+        ) {
+            Ok(pending) => pending,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        conn.execute(
+            "DELETE FROM pending_metadata_matches WHERE id = ?1",
+            rusqlite::params![match_id],
+        )?;
+        Ok(Some(pending))
+    }
+
+    fn row_to_pending_match(row: &rusqlite::Row) -> rusqlite::Result<PendingMetadataMatch> {
+        let json: String = row.get(5)?;
+        let metadata = serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+        Ok(PendingMetadataMatch {
+            id: row.get(0)?,
+            book_id: row.get(1)?,
+            provider: row.get(2)?,
+            query_title: row.get(3)?,
+            confidence: row.get(4)?,
+            metadata,
+            created_at: row.get(6)?,
+        })
+    }
+
+    pub(crate) async fn apply_metadata(
+        db: &Database,
+        item_id: i64,
+        meta: FetchedMetadata,
+        _is_manga: bool,
+    ) {
         let conn_res = db.get_connection();
         if let Ok(conn) = conn_res {
             // Retrieve current book state
@@ -320,8 +664,8 @@ impl MetadataWorker {
             Ok(id) => Ok(id),
             Err(rusqlite::Error::QueryReturnedNoRows) => {
                 conn.execute(
-                    "INSERT INTO authors (name) VALUES (?1)",
-                    rusqlite::params![name],
+                    "INSERT INTO authors (name, sort_name) VALUES (?1, ?2)",
+                    rusqlite::params![name, crate::services::author_service::normalize_name(name)],
                 )?;
                 Ok(conn.last_insert_rowid())
             }
@@ -347,16 +691,231 @@ impl MetadataWorker {
         }
     }
 
-    fn compute_query_hash(query: &MetadataQuery) -> String {
+    fn compute_query_hash(item_type: ItemType, query: &MetadataQuery) -> String {
         let mut hasher = Sha256::new();
-        let q_str = match query {
+        let mut q_str = match query {
             MetadataQuery::Isbn(isbn) => format!("isbn:{}", isbn),
             MetadataQuery::Title(title) => format!("title:{}", title),
             MetadataQuery::TitleAuthor { title, author } => {
                 format!("title:{},author:{:?}", title, author)
             }
         };
+        // Keep novel and comic lookups from colliding with manga entries of the same title
+        match item_type {
+            ItemType::LightNovel => q_str.insert_str(0, "novel:"),
+            ItemType::Comic => q_str.insert_str(0, "comic:"),
+            ItemType::Book | ItemType::Manga => {}
+        }
         hasher.update(q_str.as_bytes());
         hex::encode(hasher.finalize())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::online::provider::MetadataError;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Records the title of every query it is asked about
+    struct RecordingProvider {
+        calls: Arc<Mutex<Vec<String>>>,
+        /// Answer with a matching result instead of "not found"
+        found: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl MetadataProvider for RecordingProvider {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn supports_media(&self, _is_manga: bool) -> bool {
+            true
+        }
+
+        async fn fetch_metadata(
+            &self,
+            query: &MetadataQuery,
+        ) -> Result<Option<FetchedMetadata>, MetadataError> {
+            let title = match query {
+                MetadataQuery::Title(title) => title,
+                _ => return Ok(None),
+            };
+            self.calls.lock().unwrap().push(title.clone());
+            Ok(self.found.then(|| result(title, &[])))
+        }
+
+        async fn fetch_cover(&self, _cover_url: &str) -> Result<Vec<u8>, MetadataError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn result(title: &str, authors: &[&str]) -> FetchedMetadata {
+        FetchedMetadata {
+            title: Some(title.to_string()),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_job_is_skipped_before_provider_call() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let (mut worker, rx) = MetadataWorker::new(db);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        worker.add_provider(Arc::new(RecordingProvider {
+            calls: Arc::clone(&calls),
+            found: false,
+        }));
+        worker.start(rx);
+
+        let job = |item_id: i64, title: &str| MetadataJob {
+            item_id,
+            item_type: ItemType::Book,
+            query: MetadataQuery::Title(title.to_string()),
+            force_refresh: true,
+            batch: None,
+        };
+        worker.cancelled.insert(1);
+        worker.sender.send(job(1, "cancelled")).await.unwrap();
+        worker.sender.send(job(2, "kept")).await.unwrap();
+
+        // Jobs run in order, so once "kept" was fetched the first job is done
+        for _ in 0..100 {
+            if !calls.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(*calls.lock().unwrap(), vec!["kept".to_string()]);
+        assert!(worker.cancelled.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_skips_queued_jobs_and_clears_bookkeeping() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let (mut worker, rx) = MetadataWorker::new(db);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        worker.add_provider(Arc::new(RecordingProvider {
+            calls: Arc::clone(&calls),
+            found: false,
+        }));
+
+        let job = |item_id: i64, title: &str| MetadataJob {
+            item_id,
+            item_type: ItemType::Book,
+            query: MetadataQuery::Title(title.to_string()),
+            force_refresh: true,
+            batch: None,
+        };
+        for (item_id, title) in [(1, "one"), (2, "two")] {
+            MetadataWorker::track_queued(&worker.cancelled, &worker.queued, item_id);
+            worker.sender.send(job(item_id, title)).await.unwrap();
+        }
+        assert_eq!(
+            MetadataWorker::cancel_all(&worker.cancelled, &worker.queued),
+            2
+        );
+
+        MetadataWorker::track_queued(&worker.cancelled, &worker.queued, 3);
+        worker.sender.send(job(3, "three")).await.unwrap();
+        worker.start(rx);
+
+        for _ in 0..100 {
+            if worker.queued.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(*calls.lock().unwrap(), vec!["three".to_string()]);
+        assert!(worker.queued.is_empty());
+        assert!(worker.cancelled.is_empty());
+
+        // A cancellation that arrives too late to stop a job stays armed while
+        // the book has another job queued and is dropped with the last one
+        MetadataWorker::track_queued(&worker.cancelled, &worker.queued, 5);
+        MetadataWorker::track_queued(&worker.cancelled, &worker.queued, 5);
+        worker.cancelled.insert(5);
+        MetadataWorker::finish_job(&worker.cancelled, &worker.queued, 5);
+        assert!(worker.cancelled.contains(&5));
+        MetadataWorker::finish_job(&worker.cancelled, &worker.queued, 5);
+        assert!(worker.cancelled.is_empty());
+        assert!(worker.queued.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_not_found_falls_back_to_next_provider() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let (mut worker, rx) = MetadataWorker::new(db);
+        let first = Arc::new(Mutex::new(Vec::new()));
+        let second = Arc::new(Mutex::new(Vec::new()));
+        worker.add_provider(Arc::new(RecordingProvider {
+            calls: Arc::clone(&first),
+            found: false,
+        }));
+        worker.add_provider(Arc::new(RecordingProvider {
+            calls: Arc::clone(&second),
+            found: true,
+        }));
+        worker.start(rx);
+
+        let job = |item_id: i64, title: &str| MetadataJob {
+            item_id,
+            item_type: ItemType::Comic,
+            query: MetadataQuery::Title(title.to_string()),
+            force_refresh: true,
+            batch: None,
+        };
+        worker.sender.send(job(1, "Saga")).await.unwrap();
+        worker.sender.send(job(2, "Paper Girls")).await.unwrap();
+
+        for _ in 0..100 {
+            if second.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let titles = vec!["Saga".to_string(), "Paper Girls".to_string()];
+        assert_eq!(*first.lock().unwrap(), titles);
+        assert_eq!(*second.lock().unwrap(), titles);
+    }
+
+    #[test]
+    fn test_confidence_scoring() {
+        let exact = MetadataQuery::Title("The Hobbit".to_string());
+        assert!(MetadataWorker::compute_confidence(&exact, &result("the hobbit", &[])) > 0.99);
+
+        let wrong = MetadataQuery::Title("Dune".to_string());
+        assert!(
+            MetadataWorker::compute_confidence(&wrong, &result("Dune Messiah", &[]))
+                < MIN_AUTO_APPLY_CONFIDENCE
+        );
+
+        let with_author = MetadataQuery::TitleAuthor {
+            title: "Foundation".to_string(),
+            author: Some("Isaac Asimov".to_string()),
+        };
+        let partial = result("Foundation and Empire", &["Isaac Asimov"]);
+        let without = MetadataWorker::compute_confidence(
+            &MetadataQuery::Title("Foundation".to_string()),
+            &partial,
+        );
+        let boosted = MetadataWorker::compute_confidence(&with_author, &partial);
+        assert!((boosted - (without + 0.2)).abs() < 1e-9);
+
+        let isbn = MetadataQuery::Isbn("9780261103344".to_string());
+        assert_eq!(
+            MetadataWorker::compute_confidence(&isbn, &result("Anything", &[])),
+            1.0
+        );
+        assert_eq!(
+            MetadataWorker::compute_confidence(&exact, &FetchedMetadata::default()),
+            0.0
+        );
+    }
+}
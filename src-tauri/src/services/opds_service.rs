@@ -0,0 +1,791 @@
+//! OPDS 1.2 catalog served alongside the share server so e-readers such as
+//! KOReader or Moon+ Reader can browse and download the library.
+
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use axum::{
+    extract::{ConnectInfo, Path, Query, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tower_http::services::ServeFile;
+
+use crate::db::Database;
+use crate::error::{Result, ShioriError};
+use crate::models::Book;
+use crate::services::library_service;
+
+/// Books per page of an acquisition feed
+pub const PAGE_SIZE: u32 = 50;
+
+const NAVIGATION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+const ACQUISITION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+const ACQUISITION_REL: &str = "http://opds-spec.org/acquisition";
+const AUTH_REALM: &str = "Basic realm=\"Shiori OPDS\"";
+
+/// Failed logins from one client tolerated within [`AUTH_FAILURE_WINDOW`]
+/// before the catalog stops checking its passwords
+const MAX_AUTH_FAILURES: usize = 10;
+
+/// Sliding window used when counting failed logins
+const AUTH_FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// OPDS catalog configuration as shown in settings; the password hash never
+/// leaves the backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpdsSettings {
+    pub enabled: bool,
+    pub username: String,
+    #[serde(default)]
+    pub has_password: bool,
+}
+
+pub fn load_opds_settings(db: &Database) -> Result<OpdsSettings> {
+    let conn = db.get_connection()?;
+    let settings = conn.query_row(
+        "SELECT enabled, username, password_hash IS NOT NULL
+         FROM opds_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(OpdsSettings {
+                enabled: row.get(0)?,
+                username: row.get(1)?,
+                has_password: row.get(2)?,
+            })
+        },
+    )?;
+    Ok(settings)
+}
+
+/// Save the catalog configuration. `password` of `None` keeps the stored one
+/// and an empty string removes it, which turns basic-auth off.
+pub fn save_opds_settings(
+    db: &Database,
+    enabled: bool,
+    username: &str,
+    password: Option<&str>,
+) -> Result<OpdsSettings> {
+    let username = username.trim();
+    if username.contains(':') {
+        return Err(ShioriError::Validation(
+            "OPDS username must not contain ':'".to_string(),
+        ));
+    }
+    if username.is_empty() && password.is_some_and(|p| !p.is_empty()) {
+        return Err(ShioriError::Validation(
+            "OPDS password requires a username".to_string(),
+        ));
+    }
+
+    let password_hash = match password {
+        Some(p) if !p.is_empty() => {
+            let salt = SaltString::generate(&mut OsRng);
+            let hash = Argon2::default()
+                .hash_password(p.as_bytes(), &salt)
+                .map_err(|e| ShioriError::Other(format!("Failed to hash password: {}", e)))?;
+            Some(Some(hash.to_string()))
+        }
+        Some(_) => Some(None),
+        None => None,
+    };
+
+    let conn = db.get_connection()?;
+    conn.execute(
+        "UPDATE opds_settings
+         SET enabled = ?1, username = ?2, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        params![enabled, username],
+    )?;
+    if let Some(hash) = password_hash {
+        conn.execute(
+            "UPDATE opds_settings SET password_hash = ?1 WHERE id = 1",
+            params![hash],
+        )?;
+    }
+    drop(conn);
+
+    load_opds_settings(db)
+}
+
+#[derive(Clone)]
+struct OpdsState {
+    db: Database,
+    storage_path: PathBuf,
+    /// Username and argon2 hash; `None` leaves the catalog open
+    credentials: Option<(String, String)>,
+    auth: Arc<AuthTracker>,
+}
+
+/// Verified credentials and recent failures, so argon2 runs once per client
+/// and guessing is throttled
+#[derive(Default)]
+struct AuthTracker {
+    /// SHA-256 of the last `Authorization` header that verified
+    verified: Mutex<Option<Vec<u8>>>,
+    /// Failed attempts per client address, so one misbehaving device can't
+    /// lock every other reader out. `None` when the address is unknown.
+    failures: Mutex<HashMap<Option<IpAddr>, VecDeque<Instant>>>,
+}
+
+impl AuthTracker {
+    fn is_throttled(&self, client: Option<IpAddr>) -> bool {
+        let mut failures = self.failures.lock().unwrap();
+        // Forget every client whose failures have all expired
+        failures.retain(|_, attempts| {
+            while attempts
+                .front()
+                .is_some_and(|at| at.elapsed() > AUTH_FAILURE_WINDOW)
+            {
+                attempts.pop_front();
+            }
+            !attempts.is_empty()
+        });
+        failures
+            .get(&client)
+            .is_some_and(|attempts| attempts.len() >= MAX_AUTH_FAILURES)
+    }
+
+    fn record_failure(&self, client: Option<IpAddr>) {
+        self.failures
+            .lock()
+            .unwrap()
+            .entry(client)
+            .or_default()
+            .push_back(Instant::now());
+    }
+}
+
+/// Build the `/opds` routes. Credentials are read once here, so the share
+/// server must be restarted for changed settings to apply.
+pub fn router(db: Database, storage_path: PathBuf) -> Result<Router> {
+    let credentials = {
+        let conn = db.get_connection()?;
+        conn.query_row(
+            "SELECT username, password_hash FROM opds_settings WHERE id = 1",
+            [],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )?
+    };
+    let credentials = match credentials {
+        (username, Some(hash)) if !username.is_empty() => Some((username, hash)),
+        _ => None,
+    };
+
+    let state = OpdsState {
+        db,
+        storage_path,
+        credentials,
+        auth: Arc::new(AuthTracker::default()),
+    };
+
+    Ok(Router::new()
+        .route("/opds", get(handle_root_feed))
+        .route("/opds/books", get(handle_books_feed))
+        .route("/opds/books/:id/file", get(handle_book_file))
+        .route("/opds/books/:id/cover", get(handle_book_cover))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_basic_auth,
+        ))
+        .with_state(state))
+}
+
+async fn require_basic_auth(
+    State(state): State<OpdsState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((username, hash)) = state.credentials.clone() else {
+        return next.run(request).await;
+    };
+    let unauthorized = || {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, AUTH_REALM)],
+            "Authentication required",
+        )
+            .into_response()
+    };
+
+    // Clients probe without credentials first; that isn't a failed attempt
+    let Some(supplied) = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        return unauthorized();
+    };
+
+    let digest = Sha256::digest(supplied.as_bytes()).to_vec();
+    if state.auth.verified.lock().unwrap().as_ref() == Some(&digest) {
+        return next.run(request).await;
+    }
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    if state.auth.is_throttled(client) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed login attempts",
+        )
+            .into_response();
+    }
+
+    // Argon2 is deliberately slow; keep it off the async workers
+    let authorized =
+        tokio::task::spawn_blocking(move || is_authorized(&supplied, &username, &hash))
+            .await
+            .unwrap_or(false);
+    if authorized {
+        *state.auth.verified.lock().unwrap() = Some(digest);
+        next.run(request).await
+    } else {
+        state.auth.record_failure(client);
+        unauthorized()
+    }
+}
+
+/// Check an `Authorization` header value against the stored credentials
+fn is_authorized(header_value: &str, username: &str, hash: &str) -> bool {
+    let supplied = header_value
+        .strip_prefix("Basic ")
+        .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok());
+    let Some((user, password)) = supplied.as_deref().and_then(|s| s.split_once(':')) else {
+        return false;
+    };
+
+    user == username
+        && PasswordHash::new(hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+}
+
+fn atom_response(xml: String) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/atom+xml;charset=utf-8")],
+        xml,
+    )
+        .into_response()
+}
+
+async fn handle_root_feed(
+    State(state): State<OpdsState>,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    let total = library_service::get_total_books(&state.db)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(atom_response(root_feed_xml(total, &now())))
+}
+
+#[derive(Deserialize)]
+struct BooksQuery {
+    page: Option<u32>,
+    sort: Option<String>,
+}
+
+async fn handle_books_feed(
+    State(state): State<OpdsState>,
+    Query(query): Query<BooksQuery>,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    let page = query.page.unwrap_or(1).max(1);
+    let recent = query.sort.as_deref() == Some("recent");
+    let (sort_by, sort_order) = if recent {
+        ("added_date", "desc")
+    } else {
+        ("title", "asc")
+    };
+
+    let total = library_service::get_total_books(&state.db)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let books = library_service::get_all_books(
+        &state.db,
+        PAGE_SIZE,
+        (page - 1).saturating_mul(PAGE_SIZE),
+        Some(sort_by),
+        Some(sort_order),
+    )
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(atom_response(books_feed_xml(
+        &books,
+        page,
+        total.max(0) as u64,
+        recent,
+        &now(),
+    )))
+}
+
+async fn handle_book_file(
+    State(state): State<OpdsState>,
+    Path(id): Path<i64>,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    let book = library_service::get_book_by_id(&state.db, id)
+        .map_err(|_| (StatusCode::NOT_FOUND, "Book not found".to_string()))?;
+    let full_path = state.storage_path.join(&book.file_path);
+    if book.in_trash || !full_path.exists() {
+        return Err((StatusCode::NOT_FOUND, "Book file not found".to_string()));
+    }
+
+    let mut response = ServeFile::new(&full_path)
+        .try_call(axum::http::Request::new(axum::body::Body::empty()))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_response();
+    let filename = download_filename(&book);
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+async fn handle_book_cover(
+    State(state): State<OpdsState>,
+    Path(id): Path<i64>,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    let book = library_service::get_book_by_id(&state.db, id)
+        .map_err(|_| (StatusCode::NOT_FOUND, "Book not found".to_string()))?;
+    if book.in_trash {
+        return Err((StatusCode::NOT_FOUND, "Cover not available".to_string()));
+    }
+    let cover_path = book
+        .cover_path
+        .as_deref()
+        .ok_or((StatusCode::NOT_FOUND, "Cover not available".to_string()))?;
+    let bytes = tokio::fs::read(state.storage_path.join(cover_path))
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "Cover not available".to_string()))?;
+    let mime = mime_guess::from_path(cover_path).first_or_octet_stream();
+
+    Ok(([(header::CONTENT_TYPE, mime.to_string())], bytes).into_response())
+}
+
+/// ASCII-only file name for Content-Disposition, keeping the extension
+fn download_filename(book: &Book) -> String {
+    let stem: String = book
+        .title
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == ' ' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = stem.trim();
+    let stem = if stem.is_empty() { "book" } else { stem };
+    format!("{}.{}", stem, book.file_format.to_lowercase())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Atom requires RFC 3339; library timestamps are stored as either RFC 3339
+/// or SQLite's `YYYY-MM-DD HH:MM:SS`.
+fn atom_date(value: &str, fallback: &str) -> String {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return dt
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map(|dt| dt.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_else(|_| fallback.to_string())
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn feed_open(id: &str, title: &str, updated: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\" \
+         xmlns:opds=\"http://opds-spec.org/2010/catalog\" \
+         xmlns:dc=\"http://purl.org/dc/terms/\">\n\
+         <id>{}</id>\n<title>{}</title>\n<updated>{}</updated>\n\
+         <author><name>Shiori</name></author>\n",
+        id,
+        escape_xml(title),
+        updated
+    )
+}
+
+fn link(rel: &str, href: &str, kind: &str) -> String {
+    format!(
+        "<link rel=\"{}\" href=\"{}\" type=\"{}\"/>\n",
+        rel,
+        escape_xml(href),
+        kind
+    )
+}
+
+fn root_feed_xml(total_books: i64, updated: &str) -> String {
+    let mut xml = feed_open("urn:shiori:opds:root", "Shiori Library", updated);
+    xml.push_str(&link("self", "/opds", NAVIGATION_TYPE));
+    xml.push_str(&link("start", "/opds", NAVIGATION_TYPE));
+
+    let sections = [
+        (
+            "urn:shiori:opds:books",
+            "All Books",
+            format!("{} books sorted by title", total_books),
+            "subsection",
+            "/opds/books",
+        ),
+        (
+            "urn:shiori:opds:recent",
+            "Recently Added",
+            "Newest additions first".to_string(),
+            "http://opds-spec.org/sort/new",
+            "/opds/books?sort=recent",
+        ),
+    ];
+    for (id, title, summary, rel, href) in sections {
+        xml.push_str(&format!(
+            "<entry>\n<title>{}</title>\n<id>{}</id>\n<updated>{}</updated>\n\
+             <content type=\"text\">{}</content>\n",
+            title,
+            id,
+            updated,
+            escape_xml(&summary)
+        ));
+        xml.push_str(&link(rel, href, ACQUISITION_TYPE));
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn books_feed_xml(books: &[Book], page: u32, total: u64, recent: bool, updated: &str) -> String {
+    let (id, title, sort_param) = if recent {
+        ("urn:shiori:opds:recent", "Recently Added", "sort=recent&")
+    } else {
+        ("urn:shiori:opds:books", "All Books", "")
+    };
+    let last_page = total.div_ceil(PAGE_SIZE as u64).max(1) as u32;
+    let page_href = |p: u32| format!("/opds/books?{}page={}", sort_param, p);
+
+    let mut xml = feed_open(&format!("{}:{}", id, page), title, updated);
+    xml.push_str(&link("self", &page_href(page), ACQUISITION_TYPE));
+    xml.push_str(&link("start", "/opds", NAVIGATION_TYPE));
+    xml.push_str(&link("up", "/opds", NAVIGATION_TYPE));
+    xml.push_str(&link("first", &page_href(1), ACQUISITION_TYPE));
+    if page > 1 {
+        xml.push_str(&link(
+            "previous",
+            &page_href(page.min(last_page + 1) - 1),
+            ACQUISITION_TYPE,
+        ));
+    }
+    if page < last_page {
+        xml.push_str(&link("next", &page_href(page + 1), ACQUISITION_TYPE));
+    }
+    xml.push_str(&link("last", &page_href(last_page), ACQUISITION_TYPE));
+
+    for book in books {
+        book_entry_xml(&mut xml, book, updated);
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn book_entry_xml(xml: &mut String, book: &Book, updated: &str) {
+    let Some(id) = book.id else {
+        return;
+    };
+
+    xml.push_str("<entry>\n");
+    xml.push_str(&format!(
+        "<title>{}</title>\n<id>urn:uuid:{}</id>\n<updated>{}</updated>\n",
+        escape_xml(&book.title),
+        escape_xml(&book.uuid),
+        atom_date(&book.modified_date, updated)
+    ));
+    for author in &book.authors {
+        xml.push_str(&format!(
+            "<author><name>{}</name></author>\n",
+            escape_xml(&author.name)
+        ));
+    }
+    if !book.language.is_empty() {
+        xml.push_str(&format!(
+            "<dc:language>{}</dc:language>\n",
+            escape_xml(&book.language)
+        ));
+    }
+    if let Some(publisher) = &book.publisher {
+        xml.push_str(&format!(
+            "<dc:publisher>{}</dc:publisher>\n",
+            escape_xml(publisher)
+        ));
+    }
+    if let Some(pubdate) = &book.pubdate {
+        xml.push_str(&format!("<dc:issued>{}</dc:issued>\n", escape_xml(pubdate)));
+    }
+    for tag in &book.tags {
+        xml.push_str(&format!(
+            "<category term=\"{0}\" label=\"{0}\"/>\n",
+            escape_xml(&tag.name)
+        ));
+    }
+
+    let mime = mime_guess::from_path(&book.file_path).first_or_octet_stream();
+    xml.push_str(&link(
+        ACQUISITION_REL,
+        &format!("/opds/books/{}/file", id),
+        mime.as_ref(),
+    ));
+    if let Some(cover_path) = &book.cover_path {
+        let cover_mime = mime_guess::from_path(cover_path).first_or_octet_stream();
+        let href = format!("/opds/books/{}/cover", id);
+        xml.push_str(&link(
+            "http://opds-spec.org/image",
+            &href,
+            cover_mime.as_ref(),
+        ));
+        xml.push_str(&link(
+            "http://opds-spec.org/image/thumbnail",
+            &href,
+            cover_mime.as_ref(),
+        ));
+    }
+    xml.push_str("</entry>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+    use tower::ServiceExt;
+
+    async fn get(app: Router, uri: &str, auth: Option<&str>) -> Response {
+        get_from(app, uri, auth, None).await
+    }
+
+    /// Like [`get`], as if the request came over a connection from `client`
+    async fn get_from(
+        app: Router,
+        uri: &str,
+        auth: Option<&str>,
+        client: Option<&str>,
+    ) -> Response {
+        let mut request = axum::http::Request::get(uri);
+        if let Some(auth) = auth {
+            request = request.header(header::AUTHORIZATION, auth);
+        }
+        let mut request = request.body(axum::body::Body::empty()).unwrap();
+        if let Some(client) = client {
+            let addr: SocketAddr = client.parse().unwrap();
+            request.extensions_mut().insert(ConnectInfo(addr));
+        }
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_root_feed_is_atom_with_catalog_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+
+        let response = get(router(db, dir.path().to_path_buf()).unwrap(), "/opds", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("application/atom+xml"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+
+        let mut reader = Reader::from_str(&xml);
+        let mut root_namespace = None;
+        let mut links = Vec::new();
+        loop {
+            match reader.read_event().expect("feed must be well-formed XML") {
+                Event::Start(e) if e.name().as_ref() == b"feed" => {
+                    root_namespace = e.try_get_attribute("xmlns").unwrap().map(|a| {
+                        a.decode_and_unescape_value(reader.decoder())
+                            .unwrap()
+                            .into_owned()
+                    });
+                }
+                Event::Empty(e) if e.name().as_ref() == b"link" => {
+                    let attr = |name: &str| {
+                        e.try_get_attribute(name)
+                            .unwrap()
+                            .map(|a| {
+                                a.decode_and_unescape_value(reader.decoder())
+                                    .unwrap()
+                                    .into_owned()
+                            })
+                            .unwrap_or_default()
+                    };
+                    links.push((attr("rel"), attr("href"), attr("type")));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        assert_eq!(
+            root_namespace.as_deref(),
+            Some("http://www.w3.org/2005/Atom")
+        );
+        assert!(links.contains(&(
+            "self".to_string(),
+            "/opds".to_string(),
+            NAVIGATION_TYPE.to_string()
+        )));
+        assert!(links.contains(&(
+            "subsection".to_string(),
+            "/opds/books".to_string(),
+            ACQUISITION_TYPE.to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_guards_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        save_opds_settings(&db, true, "reader", Some("secret")).unwrap();
+
+        let app = router(db, dir.path().to_path_buf()).unwrap();
+        let denied = get(app.clone(), "/opds", None).await;
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(denied.headers()[header::WWW_AUTHENTICATE], AUTH_REALM);
+
+        let auth = format!("Basic {}", STANDARD.encode("reader:secret"));
+        let allowed = get(app, "/opds", Some(&auth)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_bad_logins_are_throttled() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        save_opds_settings(&db, true, "reader", Some("secret")).unwrap();
+        let app = router(db, dir.path().to_path_buf()).unwrap();
+
+        let good = format!("Basic {}", STANDARD.encode("reader:secret"));
+        let bad = format!("Basic {}", STANDARD.encode("reader:guess"));
+        assert_eq!(
+            get(app.clone(), "/opds", Some(&good)).await.status(),
+            StatusCode::OK
+        );
+        for _ in 0..MAX_AUTH_FAILURES {
+            let denied = get(app.clone(), "/opds", Some(&bad)).await;
+            assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        }
+        let throttled = get(app.clone(), "/opds", Some(&bad)).await;
+        assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Already verified credentials skip the throttle
+        assert_eq!(
+            get(app, "/opds", Some(&good)).await.status(),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_throttle_is_per_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        save_opds_settings(&db, true, "reader", Some("secret")).unwrap();
+        let app = router(db, dir.path().to_path_buf()).unwrap();
+
+        let bad = format!("Basic {}", STANDARD.encode("reader:guess"));
+        let other_bad = format!("Basic {}", STANDARD.encode("reader:typo"));
+        for _ in 0..MAX_AUTH_FAILURES {
+            get_from(app.clone(), "/opds", Some(&bad), Some("10.0.0.2:5000")).await;
+        }
+        let throttled = get_from(app.clone(), "/opds", Some(&bad), Some("10.0.0.2:5001")).await;
+        assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Another device still gets its password checked
+        let denied = get_from(
+            app.clone(),
+            "/opds",
+            Some(&other_bad),
+            Some("10.0.0.3:5000"),
+        )
+        .await;
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        let good = format!("Basic {}", STANDARD.encode("reader:secret"));
+        let allowed = get_from(app, "/opds", Some(&good), Some("10.0.0.3:5000")).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_trashed_book_cover_is_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).unwrap();
+        std::fs::write(dir.path().join("cover.jpg"), b"jpeg").unwrap();
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid, cover_path)
+             VALUES ('Kept', 'kept.epub', 'epub', 'uuid-kept', 'cover.jpg'),
+                    ('Trashed', 'trashed.epub', 'epub', 'uuid-trashed', 'cover.jpg')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE books SET in_trash = 1 WHERE uuid = 'uuid-trashed'",
+            [],
+        )
+        .unwrap();
+        let id = |uuid: &str| -> i64 {
+            conn.query_row("SELECT id FROM books WHERE uuid = ?1", [uuid], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        let (kept, trashed) = (id("uuid-kept"), id("uuid-trashed"));
+        drop(conn);
+
+        let app = router(db, dir.path().to_path_buf()).unwrap();
+        let visible = get(app.clone(), &format!("/opds/books/{}/cover", kept), None).await;
+        assert_eq!(visible.status(), StatusCode::OK);
+        let hidden = get(app, &format!("/opds/books/{}/cover", trashed), None).await;
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+    }
+}
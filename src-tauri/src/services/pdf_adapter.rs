@@ -1,11 +1,105 @@
 use crate::error::{Result, ShioriError};
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchMatcher, SearchOptions, SearchResult, TocEntry,
+};
 use async_trait::async_trait;
 use lopdf::{content::Content, Document, Object};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long `pdftoppm` may take on one page before it is killed, so a
+/// hostile PDF can't stall an import worker or the reader forever
+const PDFTOPPM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// True when poppler's `pdftoppm` can be launched
+pub fn is_available() -> bool {
+    Command::new("pdftoppm")
+        .arg("-v")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Rasterize page `index` (0-based) to PNG with poppler's `pdftoppm`, the
+/// longer side scaled to `long_side` pixels. lopdf can't draw pages itself.
+/// `password` is the user password of an encrypted PDF.
+pub fn render_page_png(
+    path: &Path,
+    index: usize,
+    long_side: u32,
+    password: Option<&str>,
+) -> Result<Vec<u8>> {
+    let work_dir = tempfile::tempdir()?;
+    let prefix = work_dir.path().join("page");
+    let page = (index + 1).to_string();
+
+    let mut command = Command::new("pdftoppm");
+    command
+        .args(["-png", "-singlefile"])
+        .args(["-f", &page, "-l", &page])
+        .args(["-scale-to", &long_side.max(1).to_string()]);
+    if let Some(password) = password {
+        command.args(["-upw", password]);
+    }
+    let mut child = command
+        .arg(path)
+        .arg(&prefix)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                ShioriError::UnsupportedFeature(
+                    "Install poppler (pdftoppm) to render PDF pages".to_string(),
+                )
+            } else {
+                ShioriError::Io(e)
+            }
+        })?;
+
+    let deadline = Instant::now() + PDFTOPPM_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ShioriError::PdfRenderFailed {
+                page: index,
+                cause: format!("pdftoppm timed out after {:?}", PDFTOPPM_TIMEOUT),
+            });
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    if !status.success() {
+        return Err(ShioriError::PdfRenderFailed {
+            page: index,
+            cause: format!("pdftoppm exited with {}", status),
+        });
+    }
+    Ok(std::fs::read(prefix.with_extension("png"))?)
+}
+
+/// Rasterize page `index` of a `(width, height)` point page at `scale`
+pub fn render_scaled_page(
+    path: &Path,
+    index: usize,
+    (width, height): (f32, f32),
+    scale: f32,
+    password: Option<&str>,
+) -> Result<Vec<u8>> {
+    let long_side = (width.max(height) * scale.clamp(0.1, 8.0)).round() as u32;
+    render_page_png(path, index, long_side, password)
+}
 
 pub struct PdfAdapter {
     doc: Option<Document>,
     path: String,
+    /// Needed again whenever `pdftoppm` rasterizes a page of an encrypted PDF
+    password: Option<String>,
     metadata: Option<BookMetadata>,
     page_count: usize,
     page_ids: Vec<lopdf::ObjectId>,
@@ -19,12 +113,81 @@ impl PdfAdapter {
         Self {
             doc: None,
             path: String::new(),
+            password: None,
             metadata: None,
             page_count: 0,
             page_ids: Vec::new(),
         }
     }
 
+    /// Path of the loaded PDF
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Password the PDF was opened with, if any
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+
+    /// Load `path`, decrypting it if it is encrypted. PDFs with only an owner
+    /// password open without one; a user password must be supplied.
+    fn open_document(path: &str, password: Option<&str>) -> Result<Document> {
+        let mut doc = Document::load(path).map_err(|e| ShioriError::CorruptedPdf {
+            path: path.to_string(),
+            details: format!("{:?}", e),
+        })?;
+
+        if doc.is_encrypted() && doc.decrypt("").is_err() {
+            let password = password.ok_or_else(|| ShioriError::PasswordRequired {
+                path: path.to_string(),
+            })?;
+            doc.decrypt(password)
+                .map_err(|_| ShioriError::IncorrectPassword {
+                    path: path.to_string(),
+                })?;
+        }
+        Ok(doc)
+    }
+
+    /// Like `load`, but with a password for encrypted PDFs. The password is
+    /// kept in memory for rendering pages and never stored.
+    pub async fn load_with_password(&mut self, path: &str, password: Option<&str>) -> Result<()> {
+        let path_str = path.to_string();
+        let password = password.map(str::to_string);
+        let kept_password = password.clone();
+
+        // Load in a blocking task using Tauri's runtime to avoid panic
+        let doc_result = tauri::async_runtime::spawn_blocking(move || {
+            Self::open_document(&path_str, password.as_deref())
+        })
+        .await
+        .map_err(|e| ShioriError::Other(format!("Task spawn failed: {:?}", e)))?;
+
+        let doc = doc_result?;
+        let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+        let page_count = page_ids.len();
+
+        let title = "Unknown Title".to_string();
+        let author = None;
+
+        self.metadata = Some(BookMetadata {
+            title,
+            author,
+            total_chapters: page_count,
+            total_pages: Some(page_count),
+            format: "pdf".to_string(),
+        });
+
+        self.page_count = page_count;
+        self.page_ids = page_ids;
+        self.doc = Some(doc);
+        self.path = path.to_string();
+        self.password = kept_password;
+
+        Ok(())
+    }
+
     fn extract_text_from_page(&self, page_number: usize) -> Result<String> {
         let doc = self
             .doc
@@ -75,6 +238,42 @@ impl PdfAdapter {
         }
     }
 
+    /// Page size in points from its MediaBox, which may be inherited from
+    /// an ancestor in the page tree
+    fn media_box_size(&self, page_number: usize) -> Option<(f32, f32)> {
+        let doc = self.doc.as_ref()?;
+        let resolve = |obj: &Object| -> Option<Object> {
+            match obj {
+                Object::Reference(id) => doc.get_object(*id).ok().cloned(),
+                other => Some(other.clone()),
+            }
+        };
+
+        let mut node = *self.page_ids.get(page_number)?;
+        // Bounded walk so a malformed, cyclic Parent chain can't loop forever
+        for _ in 0..32 {
+            let dict = doc.get_object(node).ok()?.as_dict().ok()?;
+            if let Ok(media_box) = dict.get(b"MediaBox") {
+                let coords: Vec<f32> = resolve(media_box)?
+                    .as_array()
+                    .ok()?
+                    .iter()
+                    .filter_map(|value| match resolve(value)? {
+                        Object::Integer(i) => Some(i as f32),
+                        Object::Real(r) => Some(r),
+                        _ => None,
+                    })
+                    .collect();
+                return match coords[..] {
+                    [x0, y0, x1, y1] => Some(((x1 - x0).abs(), (y1 - y0).abs())),
+                    _ => None,
+                };
+            }
+            node = dict.get(b"Parent").ok()?.as_reference().ok()?;
+        }
+        None
+    }
+
     fn get_pdf_string(obj: &Object) -> Option<String> {
         match obj {
             Object::String(bytes, _) => String::from_utf8(bytes.clone())
@@ -102,39 +301,7 @@ impl PdfAdapter {
 #[async_trait]
 impl BookReaderAdapter for PdfAdapter {
     async fn load(&mut self, path: &str) -> Result<()> {
-        let path_str = path.to_string();
-
-        // Load in a blocking task using Tauri's runtime to avoid panic
-        let doc_result = tauri::async_runtime::spawn_blocking(move || {
-            Document::load(&path_str).map_err(|e| ShioriError::CorruptedPdf {
-                path: path_str.clone(),
-                details: format!("{:?}", e),
-            })
-        })
-        .await
-        .map_err(|e| ShioriError::Other(format!("Task spawn failed: {:?}", e)))?;
-
-        let doc = doc_result?;
-        let page_ids: Vec<_> = doc.get_pages().into_values().collect();
-        let page_count = page_ids.len();
-
-        let title = "Unknown Title".to_string();
-        let author = None;
-
-        self.metadata = Some(BookMetadata {
-            title,
-            author,
-            total_chapters: page_count,
-            total_pages: Some(page_count),
-            format: "pdf".to_string(),
-        });
-
-        self.page_count = page_count;
-        self.page_ids = page_ids;
-        self.doc = Some(doc);
-        self.path = path.to_string();
-
-        Ok(())
+        self.load_with_password(path, None).await
     }
 
     fn get_metadata(&self) -> Result<BookMetadata> {
@@ -169,6 +336,8 @@ impl BookReaderAdapter for PdfAdapter {
             title: format!("Page {}", index + 1),
             content,
             location: format!("page:{}", index + 1),
+            word_count: 0,
+            estimated_minutes: 0.0,
         })
     }
 
@@ -176,44 +345,19 @@ impl BookReaderAdapter for PdfAdapter {
         self.page_count
     }
 
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let Some(matcher) = SearchMatcher::new(query, options) else {
+            return Ok(Vec::new());
+        };
         let mut results = Vec::new();
         for page_num in 0..self.page_count {
             if let Ok(content) = self.extract_text_from_page(page_num) {
-                let content_lower = content.to_lowercase();
-                let matches: Vec<_> = content_lower.match_indices(&query_lower).collect();
-                if !matches.is_empty() {
-                    let first_match_pos = matches[0].0;
-
-                    // Safely slice using character boundaries to avoid panics on multi-byte UTF-8
-                    let char_indices: Vec<(usize, char)> = content.char_indices().collect();
-                    let char_idx = char_indices
-                        .iter()
-                        .position(|&(b_idx, _)| b_idx >= first_match_pos)
-                        .unwrap_or(0);
-                    let start_char_idx = char_idx.saturating_sub(50);
-                    let end_char_idx =
-                        (char_idx + query.chars().count() + 50).min(char_indices.len());
-                    let start_byte = char_indices
-                        .get(start_char_idx)
-                        .map(|&(b, _)| b)
-                        .unwrap_or(0);
-                    let end_byte = if end_char_idx >= char_indices.len() {
-                        content.len()
-                    } else {
-                        char_indices[end_char_idx].0
-                    };
-
-                    let snippet = format!("...{}...", &content[start_byte..end_byte]);
-                    results.push(SearchResult {
-                        chapter_index: page_num,
-                        chapter_title: format!("Page {}", page_num + 1),
-                        snippet,
-                        location: format!("page:{}", page_num + 1),
-                        match_count: matches.len(),
-                    });
-                }
+                results.extend(matcher.search_chapter(
+                    &content,
+                    page_num,
+                    format!("Page {}", page_num + 1),
+                    format!("page:{}", page_num + 1),
+                ));
             }
         }
         Ok(results)
@@ -236,19 +380,69 @@ impl BookReaderAdapter for PdfAdapter {
         false
     }
 
-    async fn render_page(&self, _page_number: usize, _scale: f32) -> Result<Vec<u8>> {
-        Err(ShioriError::Other(
-            "Native image rendering is configured off for lopdf".into(),
-        ))
+    async fn render_page(&self, page_number: usize, scale: f32) -> Result<Vec<u8>> {
+        let size = self.get_page_dimensions(page_number)?;
+        let path = std::path::PathBuf::from(&self.path);
+        let password = self.password.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            render_scaled_page(&path, page_number, size, scale, password.as_deref())
+        })
+        .await
+        .map_err(|e| ShioriError::Other(format!("Task spawn failed: {:?}", e)))?
     }
 
-    fn get_page_dimensions(&self, _page_number: usize) -> Result<(f32, f32)> {
-        // Lopdf doesn't easily expose this through a standardized property without checking CropBox, MediaBox, etc.
-        // Return standard A4 dimensions roughly as fallback
-        Ok((595.0, 842.0))
+    fn get_page_dimensions(&self, page_number: usize) -> Result<(f32, f32)> {
+        if page_number >= self.page_count {
+            return Err(ShioriError::ChapterReadFailed {
+                chapter_index: page_number,
+                cause: "Page index out of bounds".to_string(),
+            });
+        }
+        // Roughly A4 when the page has no usable MediaBox
+        Ok(self.media_box_size(page_number).unwrap_or((595.0, 842.0)))
     }
 
     fn page_count(&self) -> usize {
         self.page_count
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One page, RC4-encrypted with user password "secret"
+    const ENCRYPTED_FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/encrypted.pdf");
+
+    #[test]
+    fn test_encrypted_pdf_requires_password() {
+        assert!(matches!(
+            PdfAdapter::open_document(ENCRYPTED_FIXTURE, None),
+            Err(ShioriError::PasswordRequired { .. })
+        ));
+        assert!(matches!(
+            PdfAdapter::open_document(ENCRYPTED_FIXTURE, Some("wrong")),
+            Err(ShioriError::IncorrectPassword { .. })
+        ));
+
+        let doc = PdfAdapter::open_document(ENCRYPTED_FIXTURE, Some("secret")).unwrap();
+        assert_eq!(doc.get_pages().len(), 1);
+        assert!(doc
+            .extract_text(&[1])
+            .unwrap()
+            .contains("Hello encrypted world"));
+    }
+
+    #[test]
+    fn test_page_dimensions_come_from_media_box() {
+        let doc = PdfAdapter::open_document(ENCRYPTED_FIXTURE, Some("secret")).unwrap();
+        let mut adapter = PdfAdapter::new();
+        adapter.page_ids = doc.get_pages().into_values().collect();
+        adapter.page_count = adapter.page_ids.len();
+        adapter.doc = Some(doc);
+
+        assert_eq!(adapter.get_page_dimensions(0).unwrap(), (612.0, 792.0));
+        assert!(adapter.get_page_dimensions(1).is_err());
+    }
+}
@@ -1,14 +1,41 @@
-use crate::error::Result;
+use crate::error::{Result, ShioriError};
 use crate::models::{
     Annotation, AnnotationCategory, AnnotationExportData, AnnotationExportOptions,
-    AnnotationSearchResult, BookReadingStats, DailyReadingStats, ReaderSettings, ReadingGoal,
-    ReadingProgress, ReadingSession, ReadingStreak,
+    AnnotationSearchResult, BookReadingStats, ColorCount, DailyReadingStats, ReaderSettings,
+    ReadingGoal, ReadingProgress, ReadingSession, ReadingStreak,
 };
+use crate::utils::natural_sort;
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use scraper::{ElementRef, Html};
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Same as the `annotations.color` column default
+const DEFAULT_BOOKMARK_COLOR: &str = "#FFEB3B";
 
 pub struct ReaderService;
 
+/// Map a row selected as `id, book_id, type, location, cfi_range,
+/// selected_text, note_content, color, category_id, chapter_title,
+/// created_at, updated_at`
+fn annotation_from_row(row: &rusqlite::Row) -> rusqlite::Result<Annotation> {
+    Ok(Annotation {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        annotation_type: row.get(2)?,
+        location: row.get(3)?,
+        cfi_range: row.get(4)?,
+        selected_text: row.get(5)?,
+        note_content: row.get(6)?,
+        color: row.get(7)?,
+        category_id: row.get(8)?,
+        chapter_title: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
 impl ReaderService {
     // ==================== Reading Progress ====================
 
@@ -170,22 +197,7 @@ impl ReaderService {
         )?;
 
         let annotations = stmt
-            .query_map(params![book_id], |row| {
-                Ok(Annotation {
-                    id: row.get(0)?,
-                    book_id: row.get(1)?,
-                    annotation_type: row.get(2)?,
-                    location: row.get(3)?,
-                    cfi_range: row.get(4)?,
-                    selected_text: row.get(5)?,
-                    note_content: row.get(6)?,
-                    color: row.get(7)?,
-                    category_id: row.get(8)?,
-                    chapter_title: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                })
-            })?
+            .query_map(params![book_id], annotation_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(annotations)
@@ -305,6 +317,114 @@ impl ReaderService {
         Ok(())
     }
 
+    // ==================== Highlight Colors ====================
+
+    /// Highlights and notes of a book in `color`, compared case-insensitively
+    pub fn get_annotations_by_color(
+        conn: &Connection,
+        book_id: i64,
+        color: &str,
+    ) -> Result<Vec<Annotation>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, book_id, type, location, cfi_range, selected_text,
+                    note_content, color, category_id, chapter_title, created_at, updated_at
+             FROM annotations
+             WHERE book_id = ?1 AND type != 'bookmark' AND LOWER(TRIM(color)) = ?2
+             ORDER BY created_at DESC",
+        )?;
+
+        let annotations = stmt
+            .query_map(
+                params![book_id, normalize_color(color)],
+                annotation_from_row,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(annotations)
+    }
+
+    /// Number of highlights and notes per color, most used first. Colors are
+    /// lowercased so `#FFEB3B` and `#ffeb3b` count together.
+    pub fn get_color_distribution(conn: &Connection, book_id: i64) -> Result<Vec<ColorCount>> {
+        let mut stmt = conn.prepare(
+            "SELECT LOWER(TRIM(color)) AS normalized, COUNT(*)
+             FROM annotations
+             WHERE book_id = ?1 AND type != 'bookmark' AND color IS NOT NULL
+             GROUP BY normalized
+             ORDER BY COUNT(*) DESC, normalized",
+        )?;
+
+        let counts = stmt
+            .query_map(params![book_id], |row| {
+                Ok(ColorCount {
+                    color: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(counts)
+    }
+
+    // ==================== Bookmarks ====================
+
+    /// Bookmark annotations of a book in reading order
+    pub fn get_bookmarks(conn: &Connection, book_id: i64) -> Result<Vec<Annotation>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, book_id, type, location, cfi_range, selected_text,
+                    note_content, color, category_id, chapter_title, created_at, updated_at
+             FROM annotations
+             WHERE book_id = ?1 AND type = 'bookmark'",
+        )?;
+
+        let mut bookmarks = stmt
+            .query_map(params![book_id], annotation_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // Locations are strings, so SQL ordering would put "/6/10" before "/6/4"
+        bookmarks.sort_by(|a, b| compare_locations(&a.location, &b.location));
+        Ok(bookmarks)
+    }
+
+    pub fn add_bookmark(
+        conn: &Connection,
+        book_id: i64,
+        location: &str,
+        label: Option<&str>,
+    ) -> Result<Annotation> {
+        Self::create_annotation(
+            conn,
+            book_id,
+            "bookmark",
+            location,
+            None,
+            None,
+            label,
+            DEFAULT_BOOKMARK_COLOR,
+            None,
+            None,
+        )
+    }
+
+    /// Delete a bookmark. Other annotation types are left alone.
+    pub fn delete_bookmark(conn: &Connection, id: i64) -> Result<()> {
+        let is_bookmark = conn
+            .query_row(
+                "SELECT 1 FROM annotations WHERE id = ?1 AND type = 'bookmark'",
+                params![id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !is_bookmark {
+            return Err(ShioriError::Validation(format!(
+                "Bookmark {} not found",
+                id
+            )));
+        }
+        Self::delete_annotation(conn, id)
+    }
+
     // ==================== Annotation Categories ====================
 
     pub fn get_annotation_categories(conn: &Connection) -> Result<Vec<AnnotationCategory>> {
@@ -406,6 +526,11 @@ impl ReaderService {
 
     // ==================== Global Annotation Search ====================
 
+    /// Search highlight text, notes and chapter titles in books not in the
+    /// trash, optionally narrowed to one book, type or category. Each word
+    /// matches as a prefix and all must appear; results are ranked with
+    /// highlighted text weighted above notes and chapter titles, newest first
+    /// among equals.
     pub fn search_annotations_global(
         conn: &Connection,
         query: &str,
@@ -415,7 +540,16 @@ impl ReaderService {
         limit: i64,
         offset: i64,
     ) -> Result<Vec<AnnotationSearchResult>> {
-        let fts_query = format!("\"{}\"", query.replace('"', "\"\""));
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|term| term.replace('"', ""))
+            .filter(|term| !term.is_empty())
+            .map(|term| format!("\"{}\"*", term))
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let fts_query = terms.join(" ");
 
         let sql = r#"
             SELECT a.id, a.book_id, a.type, a.location, a.cfi_range, a.selected_text,
@@ -426,14 +560,15 @@ impl ReaderService {
                         JOIN books_authors ba ON au.id = ba.author_id
                         WHERE ba.book_id = b.id), ''
                    ) as author_names
-            FROM annotations a
-            JOIN annotations_fts fts ON a.id = fts.rowid
+            FROM annotations_fts
+            JOIN annotations a ON a.id = annotations_fts.rowid
             JOIN books b ON a.book_id = b.id
             WHERE annotations_fts MATCH ?1
+              AND b.in_trash = 0
               AND (?2 IS NULL OR a.book_id = ?2)
               AND (?3 IS NULL OR a.type = ?3)
               AND (?4 IS NULL OR a.category_id = ?4)
-            ORDER BY a.created_at DESC
+            ORDER BY bm25(annotations_fts, 2.0, 1.0, 0.5), a.updated_at DESC, a.id DESC
             LIMIT ?5 OFFSET ?6
         "#;
 
@@ -450,20 +585,7 @@ impl ReaderService {
                 ],
                 |row| {
                     Ok(AnnotationSearchResult {
-                        annotation: Annotation {
-                            id: row.get(0)?,
-                            book_id: row.get(1)?,
-                            annotation_type: row.get(2)?,
-                            location: row.get(3)?,
-                            cfi_range: row.get(4)?,
-                            selected_text: row.get(5)?,
-                            note_content: row.get(6)?,
-                            color: row.get(7)?,
-                            category_id: row.get(8)?,
-                            chapter_title: row.get(9)?,
-                            created_at: row.get(10)?,
-                            updated_at: row.get(11)?,
-                        },
+                        annotation: annotation_from_row(row)?,
                         book_title: row.get(12)?,
                         book_author: row.get(13)?,
                     })
@@ -530,6 +652,15 @@ impl ReaderService {
         Ok(results)
     }
 
+    /// `search_annotations_global` across the whole library
+    pub fn search_annotations(
+        conn: &Connection,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<AnnotationSearchResult>> {
+        Self::search_annotations_global(conn, query, None, None, None, limit, 0)
+    }
+
     // ==================== Annotation Export ====================
 
     pub fn export_annotations(
@@ -896,14 +1027,17 @@ impl ReaderService {
         let now = Utc::now().to_rfc3339();
         
         let updated = conn.execute(
-            "UPDATE reading_goals SET daily_minutes_target = ?1, yearly_books_target = ?2, updated_at = ?3 WHERE is_active = 1",
+            "UPDATE reading_goals SET daily_minutes_target = ?1, yearly_books_target = ?2,
+                 books_target = CASE WHEN books_period = 'year' THEN ?2 ELSE books_target END,
+                 updated_at = ?3
+             WHERE is_active = 1",
             params![daily_minutes_target, yearly_books_target, now],
         )?;
 
         if updated == 0 {
             conn.execute(
-                "INSERT INTO reading_goals (daily_minutes_target, yearly_books_target, is_active, created_at, updated_at)
-                 VALUES (?1, ?2, 1, ?3, ?3)",
+                "INSERT INTO reading_goals (daily_minutes_target, yearly_books_target, books_target, is_active, created_at, updated_at)
+                 VALUES (?1, ?2, ?2, 1, ?3, ?3)",
                 params![daily_minutes_target, yearly_books_target, now],
             )?;
         }
@@ -1039,6 +1173,81 @@ impl ReaderService {
         })
     }
 
+    // ==================== EPUB Progress ====================
+
+    /// Map a CFI to a whole-book percentage weighted by chapter word counts,
+    /// so the result does not depend on how the reader paginates.
+    /// `chapter_html` is the content of the chapter the CFI points into.
+    pub fn compute_epub_progress(
+        cfi: &EpubCfi,
+        chapter_words: &[usize],
+        chapter_html: &str,
+    ) -> Result<f64> {
+        let chapter_count = chapter_words.len();
+        if cfi.spine_index >= chapter_count {
+            return Err(ShioriError::Validation(format!(
+                "CFI points to chapter {} but the book has {}",
+                cfi.spine_index + 1,
+                chapter_count
+            )));
+        }
+
+        let total: usize = chapter_words.iter().sum();
+        if total == 0 {
+            // No text at all (image-only book): fall back to chapter position
+            return Ok(cfi.spine_index as f64 / chapter_count as f64 * 100.0);
+        }
+
+        let before: usize = chapter_words[..cfi.spine_index].iter().sum();
+        let within = words_before_cfi(chapter_html, &cfi.steps, cfi.offset)
+            .min(chapter_words[cfi.spine_index]);
+        Ok(((before + within) as f64 / total as f64 * 100.0).clamp(0.0, 100.0))
+    }
+
+    // ==================== Text-to-Speech ====================
+
+    /// Split chapter HTML into plain-text sentences for text-to-speech.
+    /// Offsets are character positions in [`tts_text`] of the same HTML.
+    pub fn tts_segments(html: &str) -> Vec<TtsSegment> {
+        let text: Vec<char> = tts_text(html).chars().collect();
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i < text.len() {
+            let end = match text[i] {
+                // Paragraph breaks always end a sentence
+                '\n' => i,
+                c if is_sentence_terminator(c) => {
+                    let mut j = i + 1;
+                    // "?!", "..." and closing quotes belong to the sentence
+                    while j < text.len() && is_sentence_terminator(text[j]) {
+                        j += 1;
+                    }
+                    while j < text.len() && is_closing_punctuation(text[j]) {
+                        j += 1;
+                    }
+                    if ends_sentence(&text, i, j) {
+                        j
+                    } else {
+                        i = j;
+                        continue;
+                    }
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            push_segment(&mut segments, &text, start, end);
+            start = end;
+            i = end.max(i + 1);
+        }
+        push_segment(&mut segments, &text, start, text.len());
+
+        segments
+    }
+
     // ==================== Auto-Sync Logic ====================
 
     pub fn trigger_auto_sync(conn: &Connection, book_id: i64) {
@@ -1094,3 +1303,535 @@ impl ReaderService {
         }
     }
 }
+
+/// One sentence of chapter text, `char_start..char_end` in the stripped text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsSegment {
+    pub index: usize,
+    pub text: String,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// Words that end in a period without ending the sentence
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "etc", "e.g", "i.e", "no",
+    "vol", "ch", "fig", "approx", "dept", "inc", "ltd", "co",
+];
+
+/// Elements whose text is never read aloud
+const TTS_SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "title", "rt", "rp"];
+
+/// Elements that start a new paragraph
+const TTS_BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "hr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "dt",
+    "dd",
+    "blockquote",
+    "pre",
+    "tr",
+    "section",
+    "article",
+    "aside",
+    "figcaption",
+    "header",
+    "footer",
+];
+
+/// Visible chapter text with tags removed, whitespace runs collapsed to one
+/// space and block boundaries kept as single newlines
+pub fn tts_text(html: &str) -> String {
+    fn collect(element: ElementRef, raw: &mut String) {
+        for child in element.children() {
+            if let Some(child_element) = ElementRef::wrap(child) {
+                let name = child_element.value().name();
+                if TTS_SKIPPED_ELEMENTS.contains(&name) {
+                    continue;
+                }
+                let block = TTS_BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    raw.push('\n');
+                }
+                collect(child_element, raw);
+                if block {
+                    raw.push('\n');
+                }
+            } else if let Some(text) = child.value().as_text() {
+                raw.push_str(text);
+            }
+        }
+    }
+
+    let document = Html::parse_document(html);
+    let mut raw = String::new();
+    collect(document.root_element(), &mut raw);
+
+    let mut text = String::with_capacity(raw.len());
+    let mut pending: Option<char> = None;
+    for c in raw.chars() {
+        if c.is_whitespace() {
+            if c == '\n' || pending.is_none() {
+                pending = Some(if c == '\n' { '\n' } else { ' ' });
+            }
+            continue;
+        }
+        if let Some(separator) = pending.take() {
+            if !text.is_empty() {
+                text.push(separator);
+            }
+        }
+        text.push(c);
+    }
+    text
+}
+
+fn is_sentence_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+fn is_closing_punctuation(c: char) -> bool {
+    matches!(c, '"' | '\'' | '”' | '’' | '»' | ')' | ']')
+}
+
+/// Whether the terminator run `text[i..j]` ends a sentence: it has to be
+/// followed by whitespace, must not close an abbreviation or initial, and
+/// the next sentence must not start in lowercase
+fn ends_sentence(text: &[char], i: usize, j: usize) -> bool {
+    if j < text.len() && !text[j].is_whitespace() {
+        return false;
+    }
+    if let Some(next) = text[j..].iter().find(|c| !c.is_whitespace()) {
+        if next.is_lowercase() {
+            return false;
+        }
+    }
+    if text[i] == '.' && j == i + 1 {
+        let word_start = text[..i]
+            .iter()
+            .rposition(|c| c.is_whitespace() || matches!(*c, '"' | '“' | '(' | '‘'))
+            .map_or(0, |p| p + 1);
+        let word: String = text[word_start..i]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+        let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+        if is_initial || ABBREVIATIONS.contains(&word.as_str()) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Push `text[start..end]` without surrounding whitespace, if anything is left
+fn push_segment(segments: &mut Vec<TtsSegment>, text: &[char], start: usize, end: usize) {
+    let Some(first) = text[start..end].iter().position(|c| !c.is_whitespace()) else {
+        return;
+    };
+    let last = text[start..end]
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .unwrap_or(first);
+    let (char_start, char_end) = (start + first, start + last + 1);
+    segments.push(TtsSegment {
+        index: segments.len(),
+        text: text[char_start..char_end].iter().collect(),
+        char_start,
+        char_end,
+    });
+}
+
+/// Lowercase hex so stored colors group regardless of how they were written
+fn normalize_color(color: &str) -> String {
+    color.trim().to_lowercase()
+}
+
+/// Order reading locations: EPUB CFIs by spine item, step path and offset,
+/// anything else (`page-12`, plain page numbers) naturally so pages compare
+/// by value.
+pub fn compare_locations(a: &str, b: &str) -> Ordering {
+    match (EpubCfi::parse(a), EpubCfi::parse(b)) {
+        (Ok(a), Ok(b)) => {
+            (a.spine_index, a.steps, a.offset).cmp(&(b.spine_index, b.steps, b.offset))
+        }
+        _ => natural_sort::compare(a, b),
+    }
+}
+
+/// The parts of an EPUB CFI needed to locate a reading position:
+/// `epubcfi(/6/8[chap03]!/4/10/3:42)` is spine item 3 (0-based), then the
+/// step path `/4/10/3` inside that chapter and character offset 42.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubCfi {
+    pub spine_index: usize,
+    pub steps: Vec<usize>,
+    pub offset: usize,
+}
+
+impl EpubCfi {
+    /// Parse a CFI. Range CFIs (`parent,start,end`) resolve to their start;
+    /// id assertions (`[...]`) and spatial/temporal offsets are ignored.
+    pub fn parse(cfi: &str) -> Result<Self> {
+        let invalid = || ShioriError::Validation(format!("Invalid EPUB CFI: {}", cfi));
+
+        let inner = cfi
+            .trim()
+            .strip_prefix("epubcfi(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let inner = strip_assertions(inner);
+
+        // For a range keep the common parent plus the start of the range
+        let mut parts = inner.split(',');
+        let mut path = parts.next().unwrap_or_default().to_string();
+        if let Some(start) = parts.next() {
+            path.push_str(start);
+        }
+
+        let (package, content) = path.split_once('!').ok_or_else(invalid)?;
+        let package_steps = parse_steps(package).ok_or_else(invalid)?;
+        // `/6` is the spine, the next step the itemref (even, 1-based pairs)
+        let spine_step = match package_steps.as_slice() {
+            [_, step, ..] if *step >= 2 && step % 2 == 0 => *step,
+            _ => return Err(invalid()),
+        };
+
+        let (content, offset) = match content.split_once(':') {
+            Some((steps, offset)) => {
+                let digits: String = offset.chars().take_while(|c| c.is_ascii_digit()).collect();
+                (steps, digits.parse::<usize>().map_err(|_| invalid())?)
+            }
+            None => (content, 0),
+        };
+        let content = content.split(['~', '@']).next().unwrap_or_default();
+
+        Ok(Self {
+            spine_index: spine_step / 2 - 1,
+            steps: parse_steps(content).ok_or_else(invalid)?,
+            offset,
+        })
+    }
+}
+
+/// Remove `[...]` id assertions, honouring `^` escapes inside them
+fn strip_assertions(cfi: &str) -> String {
+    let mut out = String::with_capacity(cfi.len());
+    let mut in_assertion = false;
+    let mut chars = cfi.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '^' if in_assertion => {
+                chars.next();
+            }
+            '[' => in_assertion = true,
+            ']' => in_assertion = false,
+            _ if !in_assertion => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn parse_steps(path: &str) -> Option<Vec<usize>> {
+    path.split('/')
+        .skip(1)
+        .map(|step| step.parse().ok())
+        .collect()
+}
+
+/// Words of chapter text that come before the position a CFI step path
+/// points to. Steps that don't resolve stop at the deepest node reached.
+fn words_before_cfi(html: &str, steps: &[usize], offset: usize) -> usize {
+    let document = Html::parse_document(html);
+    let mut target = *document.root_element();
+
+    for &step in steps {
+        let elements: Vec<_> = target
+            .children()
+            .filter(|n| n.value().is_element())
+            .collect();
+        if step % 2 == 0 {
+            // Even steps are element children, counted from 2
+            match (step / 2).checked_sub(1).and_then(|i| elements.get(i)) {
+                Some(element) => target = *element,
+                None => break,
+            }
+        } else {
+            // Odd steps are the text between element children
+            let text = match step / 2 {
+                0 => target.children().find(|n| n.value().is_text()),
+                i => elements.get(i - 1).and_then(|element| {
+                    element
+                        .next_siblings()
+                        .take_while(|n| !n.value().is_element())
+                        .find(|n| n.value().is_text())
+                }),
+            };
+            if let Some(text) = text {
+                target = text;
+            }
+            break;
+        }
+    }
+
+    // Descendants come in document order, so everything seen before the
+    // target precedes the reading position
+    let mut before = String::new();
+    for node in document.tree.root().descendants() {
+        if node.id() == target.id() {
+            if let Some(text) = node.value().as_text() {
+                before.push(' ');
+                before.extend(text.chars().take(offset));
+            }
+            break;
+        }
+        if let Some(text) = node.value().as_text() {
+            before.push(' ');
+            before.push_str(text);
+        }
+    }
+    before.split_whitespace().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(paragraphs: usize) -> String {
+        let body: String = (0..paragraphs)
+            .map(|i| format!("<p>paragraph {} has five words</p>", i))
+            .collect();
+        format!(
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\"><head></head><body>{}</body></html>",
+            body
+        )
+    }
+
+    #[test]
+    fn test_search_annotations_across_books() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::Database::new(&dir.path().join("annotations.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        for (uuid, title) in [("dune", "Dune"), ("emma", "Emma")] {
+            conn.execute(
+                "INSERT INTO books (uuid, title, file_path, file_format) VALUES (?1, ?2, ?3, 'epub')",
+                params![uuid, title, format!("/books/{}.epub", uuid)],
+            )
+            .unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO annotations (book_id, type, location, selected_text)
+                 SELECT id, 'highlight', 'chapter-1', 'Fear is the mind-killer.' FROM books WHERE uuid = 'dune';
+             INSERT INTO annotations (book_id, type, location, selected_text, note_content)
+                 SELECT id, 'note', 'chapter-3', 'Handsome, clever, and rich', 'Opening line'
+                 FROM books WHERE uuid = 'emma';",
+        )
+        .unwrap();
+
+        let results = ReaderService::search_annotations(&conn, "mind fear", 20).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].book_title, "Dune");
+        assert_eq!(results[0].annotation.location, "chapter-1");
+
+        // Notes are searched too, and words match as prefixes
+        let results = ReaderService::search_annotations(&conn, "open", 20).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].book_title, "Emma");
+
+        assert!(ReaderService::search_annotations(&conn, "  \"  ", 20)
+            .unwrap()
+            .is_empty());
+
+        // Filters narrow the same ranked search
+        let dune_id: i64 = conn
+            .query_row("SELECT id FROM books WHERE uuid = 'dune'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let search = |query: &str, book_id: Option<i64>, kind: Option<&str>| {
+            ReaderService::search_annotations_global(&conn, query, book_id, kind, None, 20, 0)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(search("fear", Some(dune_id), None), 1);
+        assert_eq!(search("open", Some(dune_id), None), 0);
+        assert_eq!(search("open", None, Some("highlight")), 0);
+        assert_eq!(search("open", None, Some("note")), 1);
+
+        // Trashed books drop out of the results
+        conn.execute("UPDATE books SET in_trash = 1 WHERE uuid = 'emma'", [])
+            .unwrap();
+        assert_eq!(search("open", None, None), 0);
+    }
+
+    #[test]
+    fn test_mid_book_cfi_maps_to_word_weighted_percentage() {
+        let cfi = EpubCfi::parse("epubcfi(/6/6[chap02]!/4[body]/10/1:0)").unwrap();
+        assert_eq!(
+            cfi,
+            EpubCfi {
+                spine_index: 2,
+                steps: vec![4, 10, 1],
+                offset: 0,
+            }
+        );
+
+        // Five chapters of 100 words; the CFI is at the 5th of 20 paragraphs
+        // of the third chapter, i.e. 200 + 20 words into 500
+        let html = chapter(20);
+        let words = vec![100; 5];
+        let progress = ReaderService::compute_epub_progress(&cfi, &words, &html).unwrap();
+        assert!((progress - 44.0).abs() < 0.01, "got {}", progress);
+
+        // An offset inside the paragraph text moves past its first two words
+        let cfi = EpubCfi::parse("epubcfi(/6/6!/4/10,/1:12,/1:20)").unwrap();
+        assert_eq!(cfi.offset, 12);
+        let progress = ReaderService::compute_epub_progress(&cfi, &words, &html).unwrap();
+        assert!((progress - 44.4).abs() < 0.01, "got {}", progress);
+
+        let past_end = EpubCfi::parse("epubcfi(/6/14!/4/2)").unwrap();
+        assert!(ReaderService::compute_epub_progress(&past_end, &words, &html).is_err());
+        assert!(EpubCfi::parse("/6/4!/4/2").is_err());
+    }
+
+    #[test]
+    fn test_bookmarks_are_listed_in_reading_order() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = crate::db::Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (id, uuid, title, file_path, file_format) VALUES (1, 'u1', 'Book', '/b.epub', 'epub')",
+            [],
+        )
+        .unwrap();
+
+        // Lexically "/6/10" sorts before "/6/4" and "/4/12" before "/4/2"
+        ReaderService::add_bookmark(&conn, 1, "epubcfi(/6/10!/4/2/1:0)", Some("Late")).unwrap();
+        ReaderService::add_bookmark(&conn, 1, "epubcfi(/6/4[ch1]!/4/12/1:5)", None).unwrap();
+        let first = ReaderService::add_bookmark(&conn, 1, "epubcfi(/6/4!/4/2/1:30)", None).unwrap();
+        let highlight = ReaderService::create_annotation(
+            &conn,
+            1,
+            "highlight",
+            "epubcfi(/6/2!/4/2/1:0)",
+            None,
+            None,
+            None,
+            "#fff",
+            None,
+            None,
+        )
+        .unwrap();
+
+        let bookmarks = ReaderService::get_bookmarks(&conn, 1).unwrap();
+        let locations: Vec<&str> = bookmarks.iter().map(|b| b.location.as_str()).collect();
+        assert_eq!(
+            locations,
+            vec![
+                "epubcfi(/6/4!/4/2/1:30)",
+                "epubcfi(/6/4[ch1]!/4/12/1:5)",
+                "epubcfi(/6/10!/4/2/1:0)",
+            ]
+        );
+        assert_eq!(bookmarks[2].note_content.as_deref(), Some("Late"));
+
+        assert_eq!(compare_locations("page-9", "page-10"), Ordering::Less);
+
+        ReaderService::delete_bookmark(&conn, first.id.unwrap()).unwrap();
+        assert_eq!(ReaderService::get_bookmarks(&conn, 1).unwrap().len(), 2);
+        assert!(ReaderService::delete_bookmark(&conn, highlight.id.unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_tts_segments_split_sentences_with_offsets() {
+        let html = r#"<html><head><title>Ch 1</title></head><body>
+            <p>Mr. Smith arrived at 5 p.m. on <em>Friday</em>. "Is it late?" she asked.
+               He nodded... Then he left!</p>
+            <p>Q&amp;A follows</p></body></html>"#;
+
+        let text = tts_text(html);
+        assert_eq!(
+            text,
+            "Mr. Smith arrived at 5 p.m. on Friday. \"Is it late?\" she asked. \
+             He nodded... Then he left!\nQ&A follows"
+        );
+
+        let segments = ReaderService::tts_segments(html);
+        let sentences: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            sentences,
+            vec![
+                "Mr. Smith arrived at 5 p.m. on Friday.",
+                "\"Is it late?\" she asked.",
+                "He nodded...",
+                "Then he left!",
+                "Q&A follows",
+            ]
+        );
+
+        let chars: Vec<char> = text.chars().collect();
+        for (i, segment) in segments.iter().enumerate() {
+            assert_eq!(segment.index, i);
+            let slice: String = chars[segment.char_start..segment.char_end].iter().collect();
+            assert_eq!(slice, segment.text);
+        }
+        assert_eq!((segments[0].char_start, segments[0].char_end), (0, 38));
+        assert_eq!(segments[1].char_start, 39);
+    }
+
+    #[test]
+    fn test_color_distribution_groups_case_insensitively() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = crate::db::Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (id, uuid, title, file_path, file_format) VALUES (1, 'u1', 'Book', '/b.epub', 'epub')",
+            [],
+        )
+        .unwrap();
+
+        for (i, color) in ["#FFEB3B", "#ffeb3b", "#2196F3", "#FFEB3B"]
+            .iter()
+            .enumerate()
+        {
+            ReaderService::create_annotation(
+                &conn,
+                1,
+                "highlight",
+                &format!("page-{}", i + 1),
+                None,
+                Some("text"),
+                None,
+                color,
+                None,
+                None,
+            )
+            .unwrap();
+        }
+        // Bookmarks carry the default color but are not highlights
+        ReaderService::add_bookmark(&conn, 1, "page-9", None).unwrap();
+
+        let distribution = ReaderService::get_color_distribution(&conn, 1).unwrap();
+        let counts: Vec<(&str, i64)> = distribution
+            .iter()
+            .map(|c| (c.color.as_str(), c.count))
+            .collect();
+        assert_eq!(counts, vec![("#ffeb3b", 3), ("#2196f3", 1)]);
+
+        let yellow = ReaderService::get_annotations_by_color(&conn, 1, "#FfEb3b").unwrap();
+        assert_eq!(yellow.len(), 3);
+        assert!(yellow.iter().all(|a| a.annotation_type == "highlight"));
+        let blue = ReaderService::get_annotations_by_color(&conn, 1, "#2196f3").unwrap();
+        assert_eq!(blue[0].location, "page-3");
+    }
+}
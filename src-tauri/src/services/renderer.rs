@@ -10,6 +10,11 @@ pub struct Chapter {
     /// For EPUB: CFI (Canonical Fragment Identifier)
     /// For PDF: Page number as string
     pub location: String,
+    /// Filled in by `RenderingService`; adapters leave these at zero
+    #[serde(default)]
+    pub word_count: usize,
+    #[serde(default)]
+    pub estimated_minutes: f32,
 }
 
 /// Represents a rendered page (for PDF or paginated views)
@@ -65,7 +70,7 @@ pub trait BookReaderAdapter: Send + Sync {
     fn chapter_count(&self) -> usize;
 
     /// Search within the book content
-    fn search(&self, query: &str) -> Result<Vec<SearchResult>>;
+    fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>>;
 
     /// Get resource by path (images, stylesheets, etc. inside the archive)
     fn get_resource(&self, path: &str) -> Result<Vec<u8>>;
@@ -123,4 +128,93 @@ pub struct SearchResult {
     pub snippet: String,
     pub location: String,
     pub match_count: usize,
+    /// Context around the first match, split so the UI can highlight `matched`
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+/// Options for in-book search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Characters of context kept on each side of a match
+    pub context_chars: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            whole_word: false,
+            context_chars: 40,
+        }
+    }
+}
+
+/// A compiled query shared by every adapter's `search`
+pub struct SearchMatcher {
+    regex: regex::Regex,
+    context_chars: usize,
+}
+
+impl SearchMatcher {
+    /// None for a blank query
+    pub fn new(query: &str, options: &SearchOptions) -> Option<Self> {
+        if query.trim().is_empty() {
+            return None;
+        }
+        let mut pattern = regex::escape(query);
+        if options.whole_word {
+            pattern = format!(r"\b{}\b", pattern);
+        }
+        let regex = regex::RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .ok()?;
+        Some(Self {
+            regex,
+            context_chars: options.context_chars,
+        })
+    }
+
+    /// Match one chapter's plain text; None when the query does not occur
+    pub fn search_chapter(
+        &self,
+        text: &str,
+        chapter_index: usize,
+        chapter_title: String,
+        location: String,
+    ) -> Option<SearchResult> {
+        let first = self.regex.find(text)?;
+        let before_start = text[..first.start()]
+            .char_indices()
+            .rev()
+            .take(self.context_chars)
+            .last()
+            .map_or(first.start(), |(i, _)| i);
+        let after_end = text[first.end()..]
+            .char_indices()
+            .nth(self.context_chars)
+            .map_or(text.len(), |(i, _)| first.end() + i);
+
+        // Newlines and tabs would break a one-line results list
+        let flatten = |s: &str| s.replace(char::is_whitespace, " ");
+        let before = flatten(&text[before_start..first.start()]);
+        let matched = flatten(first.as_str());
+        let after = flatten(&text[first.end()..after_end]);
+
+        Some(SearchResult {
+            chapter_index,
+            chapter_title,
+            snippet: format!("...{}{}{}...", before, matched, after),
+            location,
+            match_count: self.regex.find_iter(text).count(),
+            before,
+            matched,
+            after,
+        })
+    }
 }
@@ -1,5 +1,6 @@
 use crate::error::{Result, ShioriError};
 use crate::services::cache::{BookCache, CacheItemType, CacheKey, CachedContent};
+use crate::services::djvu_adapter::DjvuAdapter;
 use crate::services::docx_adapter::DocxAdapter;
 use crate::services::epub_adapter::EpubAdapter;
 use crate::services::fb2_reader_adapter::Fb2ReaderAdapter;
@@ -7,11 +8,24 @@ use crate::services::html_reader_adapter::HtmlReaderAdapter;
 use crate::services::markdown_reader_adapter::MarkdownReaderAdapter;
 use crate::services::mobi_adapter::MobiAdapter;
 use crate::services::pdf_adapter::PdfAdapter;
-use crate::services::renderer::{BookMetadata, BookReaderAdapter, Chapter, SearchResult, TocEntry};
+use crate::services::reader_service::{EpubCfi, ReaderService, TtsSegment};
+use crate::services::renderer::{
+    BookMetadata, BookReaderAdapter, Chapter, SearchOptions, SearchResult, TocEntry,
+};
 use crate::services::txt_reader_adapter::TxtReaderAdapter;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
+/// Reading speed used for time estimates until the user sets their own
+pub const DEFAULT_WORDS_PER_MINUTE: u32 = 250;
+
+/// Most pages a single `render_page_range` call will render
+pub const MAX_RENDER_PAGE_RANGE: usize = 50;
+
+/// Pages of one range rendered at the same time
+const PAGE_RENDER_CONCURRENCY: usize = 4;
+
 /// Manages book renderers and caching
 pub struct RenderingService {
     cache: Arc<BookCache>,
@@ -24,6 +38,20 @@ pub struct RenderingService {
     html_renderers: Arc<Mutex<HashMap<i64, HtmlReaderAdapter>>>,
     txt_renderers: Arc<Mutex<HashMap<i64, TxtReaderAdapter>>>,
     md_renderers: Arc<Mutex<HashMap<i64, MarkdownReaderAdapter>>>,
+    djvu_renderers: Arc<Mutex<HashMap<i64, DjvuAdapter>>>,
+    // Word counts per (book, chapter); cheap to keep after content is evicted
+    word_counts: Mutex<HashMap<(i64, usize), usize>>,
+    words_per_minute: AtomicU32,
+}
+
+/// Total reading time for a book at the current reading speed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadingEstimate {
+    pub book_id: i64,
+    pub chapter_count: usize,
+    pub word_count: usize,
+    pub estimated_minutes: f32,
+    pub words_per_minute: u32,
 }
 
 impl RenderingService {
@@ -38,11 +66,93 @@ impl RenderingService {
             html_renderers: Arc::new(Mutex::new(HashMap::new())),
             txt_renderers: Arc::new(Mutex::new(HashMap::new())),
             md_renderers: Arc::new(Mutex::new(HashMap::new())),
+            djvu_renderers: Arc::new(Mutex::new(HashMap::new())),
+            word_counts: Mutex::new(HashMap::new()),
+            words_per_minute: AtomicU32::new(DEFAULT_WORDS_PER_MINUTE),
         }
     }
 
-    /// Open a book and prepare it for rendering
-    pub fn open_book(&self, book_id: i64, path: &str, format: &str) -> Result<BookMetadata> {
+    pub fn set_words_per_minute(&self, wpm: u32) {
+        self.words_per_minute.store(wpm.max(1), Ordering::Relaxed);
+    }
+
+    fn minutes_for(&self, words: usize) -> f32 {
+        words as f32 / self.words_per_minute.load(Ordering::Relaxed) as f32
+    }
+
+    /// Word count of a chapter's HTML, computed once per chapter
+    fn chapter_word_count(&self, book_id: i64, chapter_index: usize, html: &str) -> usize {
+        *self
+            .word_counts
+            .lock()
+            .unwrap()
+            .entry((book_id, chapter_index))
+            .or_insert_with(|| count_words(html))
+    }
+
+    /// Word count of every chapter of an open book, in reading order
+    pub fn chapter_word_counts(&self, book_id: i64) -> Result<Vec<usize>> {
+        let chapter_count = self.get_chapter_count(book_id)?;
+        let mut counts = Vec::with_capacity(chapter_count);
+        for index in 0..chapter_count {
+            let cached = self
+                .word_counts
+                .lock()
+                .unwrap()
+                .get(&(book_id, index))
+                .copied();
+            counts.push(match cached {
+                Some(words) => words,
+                None => self.get_chapter(book_id, index, "")?.word_count,
+            });
+        }
+        Ok(counts)
+    }
+
+    /// Sum word counts over every chapter of an open book
+    pub fn get_reading_estimate(&self, book_id: i64) -> Result<ReadingEstimate> {
+        let chapter_counts = self.chapter_word_counts(book_id)?;
+        let chapter_count = chapter_counts.len();
+        let word_count: usize = chapter_counts.iter().sum();
+        Ok(ReadingEstimate {
+            book_id,
+            chapter_count,
+            word_count,
+            estimated_minutes: self.minutes_for(word_count),
+            words_per_minute: self.words_per_minute.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Whole-book percentage for a CFI in an open EPUB, weighted by chapter
+    /// word counts rather than the reader's current pagination
+    pub fn compute_epub_progress(&self, book_id: i64, cfi: &str) -> Result<f64> {
+        if !self.epub_renderers.lock().unwrap().contains_key(&book_id) {
+            return Err(ShioriError::Validation(format!(
+                "Book {} is not an open EPUB",
+                book_id
+            )));
+        }
+        let cfi = EpubCfi::parse(cfi)?;
+        let chapter_words = self.chapter_word_counts(book_id)?;
+        let chapter = self.get_chapter(book_id, cfi.spine_index, "")?;
+        ReaderService::compute_epub_progress(&cfi, &chapter_words, &chapter.content)
+    }
+
+    /// Sentence segments of a chapter's text for text-to-speech
+    pub fn get_tts_segments(&self, book_id: i64, chapter_index: usize) -> Result<Vec<TtsSegment>> {
+        let chapter = self.get_chapter(book_id, chapter_index, "")?;
+        Ok(ReaderService::tts_segments(&chapter.content))
+    }
+
+    /// Open a book and prepare it for rendering. `password` is only used for
+    /// encrypted PDFs and is kept in memory, never stored, while the book is open.
+    pub fn open_book(
+        &self,
+        book_id: i64,
+        path: &str,
+        format: &str,
+        password: Option<&str>,
+    ) -> Result<BookMetadata> {
         println!("[RenderingService::open_book] Starting...");
         println!("  book_id: {}", book_id);
         println!("  path: {}", path);
@@ -107,7 +217,7 @@ impl RenderingService {
                 let path_clone = path.to_string();
                 let load_result = tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current()
-                        .block_on(async { adapter.load(&path_clone).await })
+                        .block_on(async { adapter.load_with_password(&path_clone, password).await })
                 });
                 match load_result {
                     Ok(_) => println!("[RenderingService] ✅ adapter.load() succeeded"),
@@ -302,6 +412,28 @@ impl RenderingService {
                 }
                 Ok(metadata)
             }
+            "djvu" | "djv" => {
+                log::debug!("[RenderingService] Creating DjvuAdapter...");
+                let mut adapter = DjvuAdapter::new();
+                let path_clone = path.to_string();
+                let load_result = tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current()
+                        .block_on(async { adapter.load(&path_clone).await })
+                });
+                match load_result {
+                    Ok(_) => log::debug!("[RenderingService] DjVu adapter.load() succeeded"),
+                    Err(e) => {
+                        log::debug!("[RenderingService] DjVu adapter.load() failed: {}", e);
+                        return Err(e);
+                    }
+                }
+                let metadata = adapter.get_metadata()?;
+                {
+                    let mut renderers = self.djvu_renderers.lock().unwrap();
+                    renderers.insert(book_id, adapter);
+                }
+                Ok(metadata)
+            }
             _ => Err(ShioriError::UnsupportedFormat {
                 format: format.to_string(),
                 path: path.to_string(),
@@ -335,8 +467,15 @@ impl RenderingService {
         let mut md_renderers = self.md_renderers.lock().unwrap();
         md_renderers.remove(&book_id);
 
+        let mut djvu_renderers = self.djvu_renderers.lock().unwrap();
+        djvu_renderers.remove(&book_id);
+
         // Clear cache for this book
         self.cache.clear_book(book_id);
+        self.word_counts
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| *id != book_id);
     }
 
     /// Get table of contents for a book
@@ -381,14 +520,25 @@ impl RenderingService {
             return adapter.get_toc();
         }
 
+        // Try DjVu
+        if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
+            return adapter.get_toc();
+        }
+
         Err(ShioriError::BookNotFound(format!(
             "Book {} not opened",
             book_id
         )))
     }
 
-    /// Get a chapter with caching
-    pub fn get_chapter(&self, book_id: i64, chapter_index: usize) -> Result<Chapter> {
+    /// Get a chapter with caching. `custom_css` is injected after the cache
+    /// lookup, so edited CSS applies immediately without invalidating chapters.
+    pub fn get_chapter(
+        &self,
+        book_id: i64,
+        chapter_index: usize,
+        custom_css: &str,
+    ) -> Result<Chapter> {
         println!(
             "[RenderingService::get_chapter] book_id: {}, chapter_index: {}",
             book_id, chapter_index
@@ -403,12 +553,15 @@ impl RenderingService {
 
         if let Some(CachedContent::Html(content)) = self.cache.get(&cache_key) {
             println!("[RenderingService::get_chapter] ✅ Cache hit");
+            let word_count = self.chapter_word_count(book_id, chapter_index, &content);
             // Return cached chapter (construct from cached data)
             return Ok(Chapter {
                 index: chapter_index,
                 title: format!("Chapter {}", chapter_index + 1), // Simplified
-                content,
+                content: inject_custom_css(&content, custom_css),
                 location: format!("chapter:{}", chapter_index),
+                word_count,
+                estimated_minutes: self.minutes_for(word_count),
             });
         }
 
@@ -491,14 +644,25 @@ impl RenderingService {
                                             drop(md_renderers);
                                             result?
                                         } else {
-                                            println!(
-                                                "[RenderingService::get_chapter] ❌ Book {} not in any renderer!",
-                                                book_id
-                                            );
-                                            return Err(ShioriError::BookNotFound(format!(
-                                                "Book {} not opened",
-                                                book_id
-                                            )));
+                                            drop(md_renderers);
+
+                                            let djvu_renderers =
+                                                self.djvu_renderers.lock().unwrap();
+                                            if let Some(adapter) = djvu_renderers.get(&book_id) {
+                                                log::debug!("[RenderingService::get_chapter] Found in DjVu renderers");
+                                                let result = adapter.get_chapter(chapter_index);
+                                                drop(djvu_renderers);
+                                                result?
+                                            } else {
+                                                println!(
+                                                    "[RenderingService::get_chapter] ❌ Book {} not in any renderer!",
+                                                    book_id
+                                                );
+                                                return Err(ShioriError::BookNotFound(format!(
+                                                    "Book {} not opened",
+                                                    book_id
+                                                )));
+                                            }
                                         }
                                     }
                                 }
@@ -521,7 +685,13 @@ impl RenderingService {
         // Preload next chapters in background
         self.preload_adjacent_chapters(book_id, chapter_index);
 
-        Ok(chapter)
+        let word_count = self.chapter_word_count(book_id, chapter_index, &chapter.content);
+        Ok(Chapter {
+            content: inject_custom_css(&chapter.content, custom_css),
+            word_count,
+            estimated_minutes: self.minutes_for(word_count),
+            ..chapter
+        })
     }
 
     /// Get chapter count
@@ -558,6 +728,10 @@ impl RenderingService {
             return Ok(adapter.chapter_count());
         }
 
+        if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
+            return Ok(adapter.chapter_count());
+        }
+
         Err(ShioriError::BookNotFound(format!(
             "Book {} not opened",
             book_id
@@ -565,37 +739,46 @@ impl RenderingService {
     }
 
     /// Search within a book
-    pub fn search_book(&self, book_id: i64, query: &str) -> Result<Vec<SearchResult>> {
+    pub fn search_book(
+        &self,
+        book_id: i64,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
         if let Some(adapter) = self.epub_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.pdf_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.docx_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.mobi_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.fb2_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.html_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.txt_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
         }
 
         if let Some(adapter) = self.md_renderers.lock().unwrap().get(&book_id) {
-            return adapter.search(query);
+            return adapter.search(query, options);
+        }
+
+        if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
+            return adapter.search(query, options);
         }
 
         Err(ShioriError::BookNotFound(format!(
@@ -670,6 +853,11 @@ impl RenderingService {
                         self.cache
                             .put(cache_key, CachedContent::Html(chapter.content.clone()));
                     }
+                } else if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
+                    if let Ok(chapter) = adapter.get_chapter(next_index) {
+                        self.cache
+                            .put(cache_key, CachedContent::Html(chapter.content.clone()));
+                    }
                 }
             }
         }
@@ -680,6 +868,11 @@ impl RenderingService {
         self.cache.stats()
     }
 
+    /// Resize the chapter/page cache, evicting if it shrinks
+    pub fn resize_cache(&self, size_mb: usize) {
+        self.cache.resize(size_mb);
+    }
+
     /// Clear all caches
     pub fn clear_all_caches(&self) {
         self.cache.clear();
@@ -687,7 +880,32 @@ impl RenderingService {
 
     /// Render a specific page as a PNG image Buffer (for native PDF/image books)
     pub fn render_page(&self, book_id: i64, page_index: usize, scale: f32) -> Result<Vec<u8>> {
-        if let Some(adapter) = self.pdf_renderers.lock().unwrap().get(&book_id) {
+        // Only the path and page size are read under the lock, so other pages
+        // and books can render while pdftoppm runs
+        let pdf_page = self
+            .pdf_renderers
+            .lock()
+            .unwrap()
+            .get(&book_id)
+            .map(|adapter| {
+                let size = adapter.get_page_dimensions(page_index)?;
+                let password = adapter.password().map(str::to_string);
+                Ok::<_, ShioriError>((std::path::PathBuf::from(adapter.path()), size, password))
+            });
+        if let Some(pdf_page) = pdf_page {
+            let (path, size, password) = pdf_page?;
+            return tokio::task::block_in_place(|| {
+                crate::services::pdf_adapter::render_scaled_page(
+                    &path,
+                    page_index,
+                    size,
+                    scale,
+                    password.as_deref(),
+                )
+            });
+        }
+
+        if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
             return tokio::task::block_in_place(|| {
                 tokio::runtime::Handle::current()
                     .block_on(async { adapter.render_page(page_index, scale).await })
@@ -700,15 +918,185 @@ impl RenderingService {
         )))
     }
 
+    /// Render pages `start..=end` as PNGs, a few at a time on the blocking
+    /// pool. Each page's scale is reduced as needed so its longer side stays
+    /// within `max_dimension` pixels. Results come back in page order.
+    pub async fn render_page_range(
+        self: &Arc<Self>,
+        book_id: i64,
+        start: usize,
+        end: usize,
+        scale: f32,
+        max_dimension: u32,
+    ) -> Result<Vec<(usize, Vec<u8>)>> {
+        use futures::{StreamExt, TryStreamExt};
+
+        let pages = checked_page_range(start, end, self.get_chapter_count(book_id)?)?;
+        futures::stream::iter(pages)
+            .map(|page| {
+                let service = Arc::clone(self);
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        let (width, height) = service.get_page_dimensions(book_id, page)?;
+                        let scale = capped_scale(width, height, scale, max_dimension);
+                        Ok((page, service.render_page(book_id, page, scale)?))
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(ShioriError::Other(format!("Task panicked: {}", e))))
+                }
+            })
+            .buffered(PAGE_RENDER_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
     /// Get native page dimensions (width, height) at 1.0 scale
     pub fn get_page_dimensions(&self, book_id: i64, page_index: usize) -> Result<(f32, f32)> {
         if let Some(adapter) = self.pdf_renderers.lock().unwrap().get(&book_id) {
             return adapter.get_page_dimensions(page_index);
         }
 
+        if let Some(adapter) = self.djvu_renderers.lock().unwrap().get(&book_id) {
+            return adapter.get_page_dimensions(page_index);
+        }
+
         Err(ShioriError::BookNotFound(format!(
             "Book {} not opened or doesn't support dimension querying",
             book_id
         )))
     }
 }
+
+/// Validate an inclusive page range against a book's page count
+fn checked_page_range(
+    start: usize,
+    end: usize,
+    page_count: usize,
+) -> Result<std::ops::RangeInclusive<usize>> {
+    if start > end || end >= page_count {
+        return Err(ShioriError::Validation(format!(
+            "Page range {}..={} is outside the book's {} pages",
+            start, end, page_count
+        )));
+    }
+    if end - start >= MAX_RENDER_PAGE_RANGE {
+        return Err(ShioriError::Validation(format!(
+            "At most {} pages can be rendered at once",
+            MAX_RENDER_PAGE_RANGE
+        )));
+    }
+    Ok(start..=end)
+}
+
+/// `scale`, lowered if needed so a `width` x `height` point page renders no
+/// larger than `max_dimension` pixels on its longer side
+fn capped_scale(width: f32, height: f32, scale: f32, max_dimension: u32) -> f32 {
+    let longest = width.max(height);
+    if longest <= 0.0 {
+        return scale;
+    }
+    scale.min(max_dimension as f32 / longest)
+}
+
+/// Words in chapter HTML, ignoring markup, scripts and styles
+fn count_words(html: &str) -> usize {
+    // Space before each tag so "<h1>Title</h1><p>Body" doesn't fuse into one word
+    crate::conversion::utils::strip_html_tags(&html.replace('<', " <"))
+        .split_whitespace()
+        .count()
+}
+
+/// Add `css` to chapter HTML as a `<style>` block, inside `<head>` when the
+/// chapter has one. `<` is escaped so the CSS cannot close the style element.
+pub fn inject_custom_css(html: &str, css: &str) -> String {
+    if css.trim().is_empty() {
+        return html.to_string();
+    }
+    let style = format!(
+        "<style data-shiori-custom-css>\n{}\n</style>",
+        css.replace('<', "\\3c ")
+    );
+    match html.to_ascii_lowercase().find("</head>") {
+        Some(pos) => format!("{}{}{}", &html[..pos], style, &html[pos..]),
+        None => format!("{}\n{}", style, html),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_custom_css() {
+        assert_eq!(inject_custom_css("<p>x</p>", "  "), "<p>x</p>");
+
+        let fragment = inject_custom_css("<p>x</p>", "p { color: red; }");
+        assert!(fragment.starts_with("<style data-shiori-custom-css>"));
+        assert!(fragment.ends_with("<p>x</p>"));
+
+        let doc = inject_custom_css(
+            "<html><HEAD><title>t</title></HEAD><body></body></html>",
+            "body { margin: 0 }",
+        );
+        let style_at = doc.find("<style").unwrap();
+        assert!(style_at > doc.find("<title>").unwrap());
+        assert!(style_at < doc.find("</HEAD>").unwrap());
+    }
+
+    #[test]
+    fn test_count_words_ignores_markup() {
+        let html = "<html><head><style>p { margin: 0 }</style></head>\
+                    <body><h1>Title</h1><p>One <em>two</em> three.</p></body></html>";
+        assert_eq!(count_words(html), 4);
+    }
+
+    #[test]
+    fn test_page_range_and_scale_cap() {
+        assert_eq!(checked_page_range(0, 2, 10).unwrap(), 0..=2);
+        assert!(checked_page_range(3, 2, 10).is_err());
+        assert!(checked_page_range(0, 10, 10).is_err());
+        assert!(checked_page_range(0, MAX_RENDER_PAGE_RANGE, 1000).is_err());
+        assert!(checked_page_range(0, MAX_RENDER_PAGE_RANGE - 1, 1000).is_ok());
+
+        // A 612x792pt page at 4x would be 3168px tall; capped to 2000px
+        assert_eq!(capped_scale(612.0, 792.0, 1.5, 2000), 1.5);
+        let capped = capped_scale(612.0, 792.0, 4.0, 2000);
+        assert!((792.0 * capped - 2000.0).abs() < 0.01);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs poppler's pdftoppm on PATH"]
+    async fn test_render_page_range_from_pdf_fixture() {
+        let fixture = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/three_pages.pdf"
+        );
+        let service = Arc::new(RenderingService::new(16));
+        service.open_book(1, fixture, "pdf", None).unwrap();
+
+        let pages = service.render_page_range(1, 0, 2, 2.0, 400).await.unwrap();
+        assert_eq!(
+            pages.iter().map(|(page, _)| *page).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let sizes: Vec<(u32, u32)> = pages
+            .iter()
+            .map(|(_, png)| {
+                let image = image::load_from_memory(png).unwrap();
+                (image.width(), image.height())
+            })
+            .collect();
+        // Portrait letter pages and a landscape A4 page, each capped at 400px
+        assert_eq!(sizes[0].1, 400);
+        assert!(sizes[0].0 < 400);
+        assert_eq!(sizes[2].0, 400);
+        assert!(sizes[2].1 < 400);
+    }
+
+    #[test]
+    fn test_inject_custom_css_cannot_close_style() {
+        let out = inject_custom_css("<p>x</p>", "p{}</style><script>alert(1)</script>");
+        assert_eq!(out.matches("</style>").count(), 1);
+        assert!(!out.contains("<script>"));
+    }
+}
@@ -82,7 +82,21 @@ impl RssScheduler {
         self.scheduler.add(update_job).await?;
         info!("RSS Scheduler: Added feed update job (every 30 minutes)");
 
-        // Job 2: Daily EPUB generation (if enabled)
+        // Job 2: Enforce article retention settings every 6 hours
+        let rss_service = Arc::clone(&self.rss_service);
+        let cleanup_job = Job::new_async("0 0 */6 * * *", move |_uuid, _lock| {
+            let service = Arc::clone(&rss_service);
+            Box::pin(async move {
+                if let Err(e) = service.cleanup_old_articles(false) {
+                    warn!("RSS Scheduler: Failed to clean up old articles: {}", e);
+                }
+            })
+        })?;
+
+        self.scheduler.add(cleanup_job).await?;
+        info!("RSS Scheduler: Added article cleanup job (every 6 hours)");
+
+        // Job 3: Daily EPUB generation (if enabled)
         if self.daily_epub_enabled {
             let rss_service = Arc::clone(&self.rss_service);
             let cron_schedule = self.daily_epub_time.clone();
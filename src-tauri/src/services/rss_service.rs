@@ -2,13 +2,13 @@ use ammonia::clean;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use feed_rs::parser;
-use reqwest::Client;
 use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use super::epub_builder::{EpubBuilder, EpubMetadata};
 use crate::db::Database;
+use crate::utils::http;
 
 /// RSS feed metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +42,15 @@ pub struct RssArticle {
     pub created_at: DateTime<Utc>,
 }
 
+/// Articles removed by [`RssService::cleanup_old_articles`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArticleCleanupReport {
+    /// Read articles older than `article_cleanup_days`
+    pub expired: usize,
+    /// Articles beyond a feed's `max_articles_per_feed` newest
+    pub trimmed: usize,
+}
+
 /// Options for generating daily EPUB
 #[derive(Debug, Clone)]
 pub struct DailyEpubOptions {
@@ -64,10 +73,23 @@ impl Default for DailyEpubOptions {
     }
 }
 
+/// HTTP cache validators stored per feed from the last full download
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// Outcome of a conditional feed fetch
+enum FeedFetch {
+    Modified(feed_rs::model::Feed, CacheValidators),
+    /// The server answered 304, so the stored articles are current
+    NotModified,
+}
+
 /// RSS feed management service
 pub struct RssService {
     db: Database,
-    client: Client,
     storage_path: PathBuf,
 }
 
@@ -93,17 +115,7 @@ fn parse_datetime_required(s: String) -> rusqlite::Result<DateTime<Utc>> {
 impl RssService {
     /// Create a new RSS service
     pub fn new(db: Database, storage_path: PathBuf) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("Shiori/2.0 RSS Reader")
-            .build()
-            .context("Failed to create HTTP client")?;
-
-        Ok(Self {
-            db,
-            client,
-            storage_path,
-        })
+        Ok(Self { db, storage_path })
     }
 
     /// Get a database connection from the shared pool
@@ -260,6 +272,16 @@ impl RssService {
 
     /// Fetch and parse feed data from URL or local file
     async fn fetch_feed_data(&self, url: &str) -> Result<feed_rs::model::Feed> {
+        match self.fetch_feed(url, &CacheValidators::default()).await? {
+            FeedFetch::Modified(feed, _) => Ok(feed),
+            FeedFetch::NotModified => Err(anyhow::anyhow!("Unexpected 304 Not Modified")),
+        }
+    }
+
+    /// Fetch a feed, sending `If-None-Match` / `If-Modified-Since` for any
+    /// validators from the previous download. Local files are always read.
+    async fn fetch_feed(&self, url: &str, cached: &CacheValidators) -> Result<FeedFetch> {
+        let mut validators = CacheValidators::default();
         let content = if url.starts_with("file://") || std::path::Path::new(url).is_absolute() {
             // Handle local file
             let path_str = if url.starts_with("file://") {
@@ -283,12 +305,33 @@ impl RssService {
                 .with_context(|| format!("Failed to read local feed file: {}", path_str))?
         } else {
             // Handle remote URL
-            let response = self
-                .client
-                .get(url)
-                .send()
-                .await
-                .context("HTTP request failed")?;
+            let mut request = http::client().get(url);
+            if let Some(etag) = &cached.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+
+            let response = request.send().await.context("HTTP request failed")?;
+            if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(FeedFetch::NotModified);
+            }
+            let response = response
+                .error_for_status()
+                .context("Feed server returned an error")?;
+
+            let header = |name: reqwest::header::HeaderName| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            validators = CacheValidators {
+                etag: header(reqwest::header::ETAG),
+                last_modified: header(reqwest::header::LAST_MODIFIED),
+            };
 
             response
                 .bytes()
@@ -298,7 +341,22 @@ impl RssService {
         };
 
         let feed = parser::parse(&content[..]).context("Failed to parse feed")?;
-        Ok(feed)
+        Ok(FeedFetch::Modified(feed, validators))
+    }
+
+    fn cache_validators(&self, feed_id: i64) -> Result<CacheValidators> {
+        let conn = self.get_connection()?;
+        let validators = conn.query_row(
+            "SELECT etag, last_modified FROM rss_feeds WHERE id = ?1",
+            params![feed_id],
+            |row| {
+                Ok(CacheValidators {
+                    etag: row.get(0)?,
+                    last_modified: row.get(1)?,
+                })
+            },
+        )?;
+        Ok(validators)
     }
 
     /// Update a specific feed (fetch new articles)
@@ -308,14 +366,30 @@ impl RssService {
             .ok_or_else(|| anyhow::anyhow!("Feed not found"))?;
 
         // Fetch feed data
-        let feed_data = match self.fetch_feed_data(&feed.url).await {
-            Ok(data) => {
-                // Reset failure count on success
+        let cached = self.cache_validators(feed_id)?;
+        let feed_data = match self.fetch_feed(&feed.url, &cached).await {
+            Ok(FeedFetch::NotModified) => {
+                // Nothing changed since the last download, skip parsing
                 let conn = self.get_connection()?;
                 conn.execute(
                     "UPDATE rss_feeds SET failure_count = 0, last_checked = ?1 WHERE id = ?2",
                     params![Utc::now().to_rfc3339(), feed_id],
                 )?;
+                return Ok(0);
+            }
+            Ok(FeedFetch::Modified(data, validators)) => {
+                // Reset failure count on success
+                let conn = self.get_connection()?;
+                conn.execute(
+                    "UPDATE rss_feeds SET failure_count = 0, last_checked = ?1,
+                     etag = ?2, last_modified = ?3 WHERE id = ?4",
+                    params![
+                        Utc::now().to_rfc3339(),
+                        validators.etag,
+                        validators.last_modified,
+                        feed_id
+                    ],
+                )?;
                 data
             }
             Err(e) => {
@@ -602,7 +676,7 @@ impl RssService {
             cover_path: None,
             page_count: None,
             word_count: None,
-            language: "eng".to_string(),
+            language: crate::utils::lang_detect::DEFAULT_LANGUAGE.to_string(),
             added_date: now_str.clone(),
             modified_date: now_str,
             last_opened: None,
@@ -636,6 +710,68 @@ impl RssService {
         Ok(output_path)
     }
 
+    /// Enforce `rss_settings`: delete read articles older than
+    /// `article_cleanup_days`, then keep only the `max_articles_per_feed`
+    /// newest articles of each feed. A setting of 0 or less disables that
+    /// rule. Articles that went into a generated EPUB are kept unless
+    /// `include_epub_linked` is set.
+    pub fn cleanup_old_articles(&self, include_epub_linked: bool) -> Result<ArticleCleanupReport> {
+        let conn = self.get_connection()?;
+
+        let (cleanup_days, max_per_feed): (i64, i64) = conn
+            .query_row(
+                "SELECT article_cleanup_days, max_articles_per_feed FROM rss_settings WHERE id = 1",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<i64>>(0)?.unwrap_or(30),
+                        row.get::<_, Option<i64>>(1)?.unwrap_or(100),
+                    ))
+                },
+            )
+            .optional()?
+            .unwrap_or((30, 100));
+
+        let mut report = ArticleCleanupReport::default();
+
+        if cleanup_days > 0 {
+            report.expired = conn.execute(
+                "DELETE FROM rss_articles
+                 WHERE is_read = 1
+                   AND julianday(created_at) < julianday('now', ?1)
+                   AND (?2 OR epub_book_id IS NULL)",
+                params![format!("-{} days", cleanup_days), include_epub_linked],
+            )?;
+        }
+
+        if max_per_feed > 0 {
+            // Linked articles still count towards the limit when they are kept
+            report.trimmed = conn.execute(
+                "DELETE FROM rss_articles WHERE id IN (
+                     SELECT id FROM (
+                         SELECT id, epub_book_id, ROW_NUMBER() OVER (
+                             PARTITION BY feed_id
+                             ORDER BY COALESCE(julianday(published), julianday(created_at)) DESC, id DESC
+                         ) AS position
+                         FROM rss_articles
+                     )
+                     WHERE position > ?1 AND (?2 OR epub_book_id IS NULL)
+                 )",
+                params![max_per_feed, include_epub_linked],
+            )?;
+        }
+
+        if report.expired + report.trimmed > 0 {
+            log::info!(
+                "RSS: Removed {} expired and {} excess articles",
+                report.expired,
+                report.trimmed
+            );
+        }
+
+        Ok(report)
+    }
+
     /// Get feeds that need updating
     pub fn get_feeds_due_for_update(&self) -> Result<Vec<RssFeed>> {
         let conn = self.get_connection()?;
@@ -750,4 +886,143 @@ mod tests {
             "Test Item 1"
         );
     }
+
+    #[tokio::test]
+    async fn test_not_modified_skips_articles_and_updates_last_checked() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let service = RssService::new(db, temp_dir.path().to_path_buf()).unwrap();
+
+        // Serves the feed with an ETag, then 304 whenever it is sent back
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let body = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Conditional</title>
+<item><title>Only Item</title><guid>item-1</guid><description>Body</description></item>
+</channel></rss>"#;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\n\r\n"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Type: application/rss+xml\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let conn = service.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO rss_feeds (url, check_interval_hours, is_active) VALUES (?1, 1, 1)",
+            params![format!("http://{}/feed.xml", addr)],
+        )
+        .unwrap();
+        let feed_id = conn.last_insert_rowid();
+
+        assert_eq!(service.update_feed_articles(feed_id).await.unwrap(), 1);
+        assert_eq!(
+            service.cache_validators(feed_id).unwrap().etag.as_deref(),
+            Some("\"v1\"")
+        );
+
+        let stale = "2000-01-01T00:00:00+00:00";
+        conn.execute(
+            "UPDATE rss_feeds SET last_checked = ?1 WHERE id = ?2",
+            params![stale, feed_id],
+        )
+        .unwrap();
+
+        assert_eq!(service.update_feed_articles(feed_id).await.unwrap(), 0);
+        let articles: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM rss_articles WHERE feed_id = ?1",
+                params![feed_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(articles, 1);
+        let last_checked = service.get_feed(feed_id).unwrap().unwrap().last_checked;
+        assert!(last_checked.unwrap() > parse_datetime_str(stale).unwrap());
+    }
+
+    #[test]
+    fn test_cleanup_old_articles_keeps_epub_linked_articles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&temp_dir.path().join("test.db")).unwrap();
+        let service = RssService::new(db, temp_dir.path().to_path_buf()).unwrap();
+
+        let conn = service.get_connection().unwrap();
+        conn.execute_batch(
+            "UPDATE rss_settings SET article_cleanup_days = 30, max_articles_per_feed = 3 WHERE id = 1;
+             INSERT INTO books (id, uuid, title, file_path, file_format)
+                 VALUES (1, 'daily', 'Daily Reading', '/daily.epub', 'epub');
+             INSERT INTO rss_feeds (id, url) VALUES (1, 'https://example.com/feed.xml');",
+        )
+        .unwrap();
+
+        let insert = |guid: &str, is_read: bool, epub_book_id: Option<i64>, age: &str| {
+            conn.execute(
+                "INSERT INTO rss_articles (feed_id, title, guid, is_read, epub_book_id, published, created_at)
+                 VALUES (1, ?1, ?1, ?2, ?3, datetime('now', ?4), datetime('now', ?4))",
+                params![guid, is_read, epub_book_id, age],
+            )
+            .unwrap();
+        };
+        insert("old-read", true, None, "-60 days");
+        insert("old-read-in-epub", true, Some(1), "-60 days");
+        insert("old-unread", false, None, "-60 days");
+        for hours in 1..=4 {
+            insert(
+                &format!("fresh-{}", hours),
+                false,
+                None,
+                &format!("-{} hours", hours),
+            );
+        }
+
+        let guids = || -> Vec<String> {
+            let mut stmt = conn
+                .prepare("SELECT guid FROM rss_articles ORDER BY id")
+                .unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+
+        let report = service.cleanup_old_articles(false).unwrap();
+        assert_eq!(
+            report,
+            ArticleCleanupReport {
+                expired: 1,
+                trimmed: 2
+            }
+        );
+        assert_eq!(
+            guids(),
+            vec!["old-read-in-epub", "fresh-1", "fresh-2", "fresh-3"]
+        );
+
+        // Only the flag lets linked articles go
+        let report = service.cleanup_old_articles(true).unwrap();
+        assert_eq!(report.expired, 1);
+        assert_eq!(guids(), vec!["fresh-1", "fresh-2", "fresh-3"]);
+    }
 }
@@ -1,21 +1,37 @@
 use crate::db::Database;
 use crate::error::Result;
-use crate::models::{SearchQuery, SearchResult};
+use crate::models::{RankedSearchResult, SearchQuery, SearchResult};
 use crate::services::library_service;
 use rusqlite::types::Value;
 
+/// Longest free-text query accepted by `search_ranked`
+pub const MAX_RANKED_QUERY_LEN: usize = 200;
+
+/// Column weights for `bm25()`: title, authors, publisher, description, tags, isbn
+const BM25_WEIGHTS: &str = "10.0, 5.0, 1.0, 2.0, 3.0, 1.0";
+
 pub fn build_search_query(query: &SearchQuery) -> (String, Vec<Value>, String, Vec<Value>) {
     let mut from_sql = String::from(" FROM books b");
     let mut where_clauses: Vec<String> = Vec::new();
     let mut base_params: Vec<Value> = Vec::new();
 
-    // Full-text search
-    if let Some(ref q) = query.query {
-        if !q.is_empty() {
-            let fts_query = format!("\"{}\"", q.replace('"', "\"\""));
-            from_sql.push_str(" JOIN books_fts fts ON b.id = fts.rowid");
-            where_clauses.push("books_fts MATCH ?".to_string());
-            base_params.push(Value::Text(fts_query));
+    // Full-text search (supports `field:term` and "quoted phrases")
+    if let Some(fts_query) = query.query.as_deref().and_then(parse_query_dsl) {
+        match query.query.as_deref().and_then(cjk_subquery) {
+            // CJK text: union with the trigram index, which finds substrings
+            Some((cjk_sql, cjk_params)) => {
+                where_clauses.push(format!(
+                    "(b.id IN (SELECT rowid FROM books_fts WHERE books_fts MATCH ?) OR b.id IN ({}))",
+                    cjk_sql
+                ));
+                base_params.push(Value::Text(fts_query));
+                base_params.extend(cjk_params);
+            }
+            None => {
+                from_sql.push_str(" JOIN books_fts fts ON b.id = fts.rowid");
+                where_clauses.push("books_fts MATCH ?".to_string());
+                base_params.push(Value::Text(fts_query));
+            }
         }
     }
 
@@ -261,6 +277,261 @@ pub fn search(db: &Database, query: SearchQuery) -> Result<SearchResult> {
     })
 }
 
+/// Map a user-facing field prefix to its `books_fts` column
+fn fts_column(field: &str) -> Option<&'static str> {
+    match field.to_lowercase().as_str() {
+        "title" => Some("title"),
+        "author" | "authors" => Some("authors"),
+        "publisher" => Some("publisher"),
+        "tag" | "tags" => Some("tags"),
+        "isbn" => Some("isbn"),
+        _ => None,
+    }
+}
+
+/// Translate the search box DSL into an FTS5 MATCH expression.
+///
+/// `author:Tolkien title:"Two Towers" ring` becomes
+/// `authors:"Tolkien" title:"Two Towers" "ring"`. Every term is emitted as a
+/// quoted string, so FTS syntax typed by the user (`*`, `^`, parentheses,
+/// `NEAR`, column filters for unknown fields) can never reach the parser.
+/// Bare `AND`/`OR`/`NOT`/`NEAR` keywords are dropped rather than matched.
+/// Returns `None` when nothing searchable remains.
+pub fn parse_query_dsl(input: &str) -> Option<String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // Optional `field:` prefix
+        let mut column = None;
+        let start = i;
+        while i < chars.len() && chars[i].is_ascii_alphabetic() {
+            i += 1;
+        }
+        if i < chars.len() && chars[i] == ':' && i > start {
+            let field: String = chars[start..i].iter().collect();
+            if let Some(col) = fts_column(&field) {
+                column = Some(col);
+                i += 1;
+            } else {
+                i = start;
+            }
+        } else {
+            i = start;
+        }
+
+        // Quoted phrase (an unterminated quote runs to the end) or a bare term
+        let text: String = if i < chars.len() && chars[i] == '"' {
+            i += 1;
+            let phrase_start = i;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            let phrase = chars[phrase_start..i].iter().collect();
+            i += 1;
+            phrase
+        } else {
+            let term_start = i;
+            while i < chars.len() && !chars[i].is_whitespace() {
+                i += 1;
+            }
+            chars[term_start..i].iter().collect()
+        };
+
+        let text = text.replace('"', "");
+        let text = text.trim();
+        if !text.chars().any(|c| c.is_alphanumeric()) {
+            continue;
+        }
+        if column.is_none() && matches!(text, "AND" | "OR" | "NOT" | "NEAR") {
+            continue;
+        }
+
+        match column {
+            Some(col) => parts.push(format!("{}:\"{}\"", col, text)),
+            None => parts.push(format!("\"{}\"", text)),
+        }
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// Turn free text into a safe FTS5 MATCH expression: every whitespace-separated
+/// term is quoted (so operators like `-`, `*`, `:` or `NEAR` are literal) and
+/// prefix-matched. Returns `None` when nothing searchable is left.
+pub fn to_fts_match(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .chars()
+        .take(MAX_RANKED_QUERY_LEN)
+        .collect::<String>()
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| term.chars().any(|c| c.is_alphanumeric()))
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Kana, CJK ideographs (including extension A) and Hangul syllables
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}' | '\u{3400}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}')
+}
+
+/// Book ids in `books_fts_cjk` matching every term of `input`, as SQL
+/// selecting `rowid` plus its parameters. `None` unless the input contains
+/// CJK text. Field prefixes are ignored; the CJK index is title, authors and
+/// tags only.
+///
+/// Trigram MATCH needs terms of at least three characters, so shorter
+/// terms fall back to a LIKE scan of the (small) CJK index.
+fn cjk_subquery(input: &str) -> Option<(String, Vec<Value>)> {
+    if !input.chars().any(is_cjk) {
+        return None;
+    }
+    let terms: Vec<String> = input
+        .chars()
+        .take(MAX_RANKED_QUERY_LEN)
+        .collect::<String>()
+        .split_whitespace()
+        .map(|term| {
+            let term = match term.split_once(':') {
+                Some((field, rest)) if fts_column(field).is_some() => rest,
+                _ => term,
+            };
+            term.replace('"', "")
+        })
+        .filter(|term| term.chars().any(|c| c.is_alphanumeric()))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+
+    if terms.iter().all(|term| term.chars().count() >= 3) {
+        let fts_query = terms
+            .iter()
+            .map(|term| format!("\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(" ");
+        return Some((
+            "SELECT rowid FROM books_fts_cjk WHERE books_fts_cjk MATCH ?".to_string(),
+            vec![Value::Text(fts_query)],
+        ));
+    }
+
+    let mut params = Vec::new();
+    let conditions = terms
+        .iter()
+        .map(|term| {
+            let pattern = format!(
+                "%{}%",
+                term.replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            params.extend(std::iter::repeat(Value::Text(pattern)).take(3));
+            "(title LIKE ? ESCAPE '\\' OR authors LIKE ? ESCAPE '\\' OR tags LIKE ? ESCAPE '\\')"
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    Some((
+        format!("SELECT rowid FROM books_fts_cjk WHERE {}", conditions),
+        params,
+    ))
+}
+
+/// Full-text search ordered by `bm25()` relevance, with highlighted snippets.
+/// CJK queries also search `books_fts_cjk`; those hits rank after the
+/// `books_fts` ones and use the title as their snippet.
+pub fn search_ranked(
+    db: &Database,
+    query: &str,
+    format: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<RankedSearchResult>> {
+    let fts_query = match to_fts_match(query) {
+        Some(q) => q,
+        None => return Ok(Vec::new()),
+    };
+
+    let format = format.filter(|f| !f.is_empty()).map(str::to_lowercase);
+    let format_sql = if format.is_some() {
+        " AND LOWER(b.file_format) = ?"
+    } else {
+        ""
+    };
+
+    let mut sql = format!(
+        "SELECT b.id, bm25(books_fts, {weights}) AS score,
+                CASE WHEN instr(snippet(books_fts, 3, '<mark>', '</mark>', '…', 12), '<mark>') > 0
+                     THEN snippet(books_fts, 3, '<mark>', '</mark>', '…', 12)
+                     ELSE snippet(books_fts, 0, '<mark>', '</mark>', '…', 12)
+                END AS snippet
+         FROM books_fts
+         JOIN books b ON b.id = books_fts.rowid
+         WHERE books_fts MATCH ? AND b.in_trash = 0{format_sql}",
+        weights = BM25_WEIGHTS
+    );
+    let mut params: Vec<Value> = vec![Value::Text(fts_query)];
+    params.extend(format.clone().map(Value::Text));
+
+    if let Some((cjk_sql, cjk_params)) = cjk_subquery(query) {
+        // bm25 scores are negative, so 0.0 sorts CJK-only hits last; MIN
+        // keeps the books_fts row (and its snippet) for books in both
+        sql = format!(
+            "SELECT id, MIN(score) AS score, snippet FROM (
+                 SELECT id, score, snippet FROM ({sql})
+                 UNION ALL
+                 SELECT b.id, 0.0, b.title FROM books b
+                 WHERE b.id IN ({cjk_sql}) AND b.in_trash = 0{format_sql}
+             ) AS b GROUP BY id"
+        );
+        params.extend(cjk_params);
+        params.extend(format.map(Value::Text));
+    }
+    sql.push_str(&format!(
+        " ORDER BY score ASC, b.id ASC LIMIT {} OFFSET {}",
+        limit.clamp(1, 500),
+        offset.max(0)
+    ));
+
+    let conn = db.get_connection()?;
+    let mut stmt = conn.prepare(&sql)?;
+    let hits: Vec<(i64, f64, String)> = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let ids: Vec<i64> = hits.iter().map(|(id, _, _)| *id).collect();
+    let books = library_service::get_books_by_ids(db, &ids)?;
+
+    Ok(hits
+        .into_iter()
+        .zip(books)
+        .map(|((_, rank, snippet), book)| RankedSearchResult {
+            book,
+            rank,
+            snippet,
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -315,4 +586,161 @@ mod tests {
         assert_eq!(page_params[2], Value::Integer(10));
         assert_eq!(page_params[3], Value::Integer(20));
     }
+
+    #[test]
+    fn test_to_fts_match_escapes_operators() {
+        assert_eq!(to_fts_match("dune"), Some("\"dune\"*".to_string()));
+        assert_eq!(
+            to_fts_match("  \"frank\" -herbert NEAR "),
+            Some("\"frank\"* \"-herbert\"* \"NEAR\"*".to_string())
+        );
+        assert_eq!(to_fts_match("* - :"), None);
+    }
+
+    #[test]
+    fn test_search_ranked_orders_by_relevance() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("search.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+        for (uuid, title, format, notes) in [
+            (
+                "u1",
+                "A Desert Planet Anthology",
+                "epub",
+                "Includes an essay on Dune.",
+            ),
+            ("u2", "Dune", "epub", "Paul Atreides arrives on Arrakis."),
+            ("u3", "Children of Dune Companion", "pdf", "Notes and maps."),
+        ] {
+            conn.execute(
+                "INSERT INTO books (uuid, title, file_path, file_format, notes)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    uuid,
+                    title,
+                    format!("/books/{}.{}", uuid, format),
+                    format,
+                    notes
+                ],
+            )
+            .unwrap();
+        }
+
+        let results = search_ranked(&db, "dune", None, 10, 0).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].book.title, "Dune");
+        assert!(results[0].rank <= results[1].rank);
+        assert!(results[0].snippet.contains("<mark>"));
+
+        let pdf_only = search_ranked(&db, "dune", Some("PDF"), 10, 0).unwrap();
+        assert_eq!(pdf_only.len(), 1);
+        assert_eq!(pdf_only[0].book.title, "Children of Dune Companion");
+
+        let paged = search_ranked(&db, "dune", None, 1, 1).unwrap();
+        assert_eq!(paged.len(), 1);
+        assert_eq!(paged[0].book.id, results[1].book.id);
+
+        // Operator characters must not produce a malformed MATCH
+        assert!(search_ranked(&db, "dune\" OR -(", None, 10, 0).is_ok());
+    }
+
+    #[test]
+    fn test_parse_query_dsl() {
+        assert_eq!(parse_query_dsl("manga").as_deref(), Some("\"manga\""));
+        assert_eq!(
+            parse_query_dsl("author:Tolkien title:\"Two Towers\" ring").as_deref(),
+            Some("authors:\"Tolkien\" title:\"Two Towers\" \"ring\"")
+        );
+        // Unknown fields and raw operators stay literal or are dropped
+        assert_eq!(
+            parse_query_dsl("rowid:1 OR NEAR(a b) *").as_deref(),
+            Some("\"rowid:1\" \"NEAR(a\" \"b)\"")
+        );
+        assert_eq!(
+            parse_query_dsl("tag:\"unterminated").as_deref(),
+            Some("tags:\"unterminated\"")
+        );
+        assert_eq!(parse_query_dsl("  AND \"\" "), None);
+    }
+
+    fn insert_book(db: &Database, uuid: &str, title: &str, author: &str) {
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (uuid, title, file_path, file_format) VALUES (?1, ?2, ?3, 'epub')",
+            rusqlite::params![uuid, title, format!("/books/{}.epub", uuid)],
+        )
+        .unwrap();
+        let book_id = conn.last_insert_rowid();
+        conn.execute("INSERT OR IGNORE INTO authors (name) VALUES (?1)", [author])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO books_authors (book_id, author_id)
+             SELECT ?1, id FROM authors WHERE name = ?2",
+            rusqlite::params![book_id, author],
+        )
+        .unwrap();
+        library_service::refresh_fts_row(&conn, book_id).unwrap();
+    }
+
+    #[test]
+    fn test_search_dsl_field_and_phrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("dsl.db")).unwrap();
+        insert_book(&db, "u1", "The Two Towers", "J. R. R. Tolkien");
+        insert_book(&db, "u2", "Towers of Midnight", "Robert Jordan");
+        insert_book(&db, "u3", "Two Small Towers of Tolkien Trivia", "Some Fan");
+
+        let titles = |q: &str| -> Vec<String> {
+            let mut query = SearchQuery::default();
+            query.query = Some(q.to_string());
+            search(&db, query)
+                .unwrap()
+                .books
+                .into_iter()
+                .map(|b| b.title)
+                .collect()
+        };
+
+        assert_eq!(titles("author:Tolkien towers"), vec!["The Two Towers"]);
+        assert_eq!(titles("\"two towers\""), vec!["The Two Towers"]);
+        assert_eq!(
+            titles("title:\"Two Towers\" author:Tolkien"),
+            vec!["The Two Towers"]
+        );
+        // A leading `-` is a literal character, not FTS negation
+        assert_eq!(titles("towers -midnight"), vec!["Towers of Midnight"]);
+    }
+
+    #[test]
+    fn test_search_japanese_title_substring() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(&dir.path().join("cjk.db")).unwrap();
+        insert_book(&db, "u1", "進撃の巨人 第1巻", "諫山創");
+        insert_book(&db, "u2", "鋼の錬金術師", "荒川弘");
+        insert_book(&db, "u3", "Attack on Titan", "Hajime Isayama");
+
+        let titles = |q: &str| -> Vec<String> {
+            let mut query = SearchQuery::default();
+            query.query = Some(q.to_string());
+            search(&db, query)
+                .unwrap()
+                .books
+                .into_iter()
+                .map(|b| b.title)
+                .collect()
+        };
+
+        // Trigram MATCH (3+ characters) and the short-term LIKE fallback
+        assert_eq!(titles("撃の巨"), vec!["進撃の巨人 第1巻"]);
+        assert_eq!(titles("巨人"), vec!["進撃の巨人 第1巻"]);
+        assert_eq!(titles("author:荒川"), vec!["鋼の錬金術師"]);
+        assert_eq!(titles("titan"), vec!["Attack on Titan"]);
+
+        let ranked = search_ranked(&db, "錬金術", None, 10, 0).unwrap();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].book.title, "鋼の錬金術師");
+        assert!(search_ranked(&db, "錬金術", Some("pdf"), 10, 0)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -2,10 +2,10 @@ use anyhow::{anyhow, Result};
 use argon2::password_hash::{rand_core::OsRng, SaltString};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::get,
+    extract::{ConnectInfo, Form, Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
     Router,
 };
 use chrono::{DateTime, Duration, Utc};
@@ -16,11 +16,23 @@ use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tower_http::services::ServeFile;
 use tower_http::trace::TraceLayer;
 
 use crate::db::Database;
+use crate::services::api_service;
+use crate::services::conversion_engine::{can_convert, ConversionEngine};
+use crate::services::cover_service::CoverService;
+use crate::services::format_adapter::BookMetadata;
+use crate::services::opds_service;
+
+/// Failed password attempts tolerated per share within the throttle window
+pub const MAX_FAILED_PASSWORD_ATTEMPTS: i64 = 10;
+
+/// Sliding window used when counting failed password attempts
+const PASSWORD_ATTEMPT_WINDOW_MINUTES: i64 = 15;
 
 // Helper functions for DateTime conversion
 fn parse_datetime(s: Option<String>) -> Option<DateTime<Utc>> {
@@ -63,12 +75,34 @@ pub struct ShareAccessLog {
     pub accessed_at: DateTime<Utc>,
 }
 
+/// Share joined with the metadata of the shared book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedBookDetails {
+    pub share: Share,
+    pub book_uuid: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub cover_path: Option<String>,
+}
+
+/// Outcome of a share password check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordCheck {
+    Accepted,
+    Rejected,
+    Throttled,
+}
+
 /// Options for creating a share
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShareOptions {
     pub password: Option<String>,
     pub expires_in_hours: Option<i64>,
     pub max_accesses: Option<i32>,
+    /// Serve the book converted to this format instead of the stored one
+    #[serde(default)]
+    pub deliver_format: Option<String>,
 }
 
 impl Default for ShareOptions {
@@ -77,6 +111,7 @@ impl Default for ShareOptions {
             password: None,
             expires_in_hours: Some(24), // 24 hours default
             max_accesses: None,
+            deliver_format: None,
         }
     }
 }
@@ -95,13 +130,16 @@ pub struct ShareResponse {
 struct AppState {
     db: Database,
     storage_path: PathBuf,
+    cover_service: Option<Arc<CoverService>>,
 }
 
 /// Book sharing service
 pub struct ShareService {
     db: Database,
     storage_path: PathBuf,
+    cover_service: Option<Arc<CoverService>>,
     server_handle: Option<JoinHandle<Result<()>>>,
+    api_handle: Option<JoinHandle<Result<()>>>,
     port: u16,
 }
 
@@ -111,22 +149,46 @@ impl ShareService {
         Self {
             db,
             storage_path,
+            cover_service: None,
             server_handle: None,
+            api_handle: None,
             port: port.unwrap_or(8080),
         }
     }
 
+    /// Set the cover service used by the share landing page
+    pub fn set_cover_service(&mut self, cover_service: Arc<CoverService>) {
+        self.cover_service = Some(cover_service);
+    }
+
     /// Create a share for a book
     pub fn create_share(&self, book_id: i64, options: ShareOptions) -> Result<Share> {
         // Verify book exists and get format
         let conn = self.db.get_connection().map_err(|e| anyhow!("{}", e))?;
-        let format: String = conn
+        let stored_format: String = conn
             .query_row(
                 "SELECT file_format FROM books WHERE id = ?1",
                 params![book_id],
                 |row| row.get(0),
             )
             .map_err(|_| anyhow::anyhow!("Book not found"))?;
+        let stored_format = stored_format.to_lowercase();
+
+        // The share's format is what gets served; reject unsupported conversions
+        // now rather than failing when the recipient downloads.
+        let format = match options.deliver_format.map(|f| f.trim().to_lowercase()) {
+            Some(target) if !target.is_empty() && target != stored_format => {
+                if !can_convert(&stored_format, &target) {
+                    return Err(anyhow!(
+                        "Cannot deliver {} book as {}",
+                        stored_format,
+                        target
+                    ));
+                }
+                target
+            }
+            _ => stored_format,
+        };
 
         // Generate cryptographically secure random token (32 characters, URL-safe)
         use rand::Rng;
@@ -211,23 +273,27 @@ impl ShareService {
         Ok(share)
     }
 
-    /// Verify share password
+    /// Get a share together with the shared book's title, authors and cover
+    pub fn get_share_details(&self, token: &str) -> Result<Option<SharedBookDetails>> {
+        let conn = self.db.get_connection().map_err(|e| anyhow!("{}", e))?;
+        load_share_details(&conn, token)
+    }
+
+    /// Verify share password, throttling repeated failures per token. Failed
+    /// attempts are logged against `ip_address`.
     #[allow(dead_code)]
-    pub fn verify_password(&self, token: &str, password: &str) -> Result<bool> {
+    pub fn verify_password(
+        &self,
+        token: &str,
+        password: &str,
+        ip_address: &str,
+    ) -> Result<PasswordCheck> {
         let share = self
             .get_share(token)?
             .ok_or_else(|| anyhow::anyhow!("Share not found"))?;
 
-        if let Some(hash) = share.password_hash {
-            let parsed_hash =
-                PasswordHash::new(&hash).map_err(|e| anyhow!("Invalid password hash: {}", e))?;
-            let argon2 = Argon2::default();
-            Ok(argon2
-                .verify_password(password.as_bytes(), &parsed_hash)
-                .is_ok())
-        } else {
-            Ok(true) // No password required
-        }
+        let conn = self.db.get_connection().map_err(|e| anyhow!("{}", e))?;
+        check_share_password(&conn, &share, Some(password), ip_address)
     }
 
     /// Check if share is valid (not expired, not over download limit, active)
@@ -365,7 +431,7 @@ impl ShareService {
             .ok_or_else(|| anyhow::anyhow!("Share not found"))?;
 
         // Get local IP (simplified - just use localhost for now)
-        let url = format!("http://localhost:{}/share/{}", self.port, token);
+        let url = format!("http://localhost:{}/s/{}", self.port, token);
 
         // Generate QR code
         let qr = QrCode::new(&url)?;
@@ -389,20 +455,35 @@ impl ShareService {
         let state = AppState {
             db: self.db.clone(),
             storage_path: self.storage_path.clone(),
+            cover_service: self.cover_service.clone(),
         };
 
-        let app = Router::new()
+        let mut app = Router::new()
             .route("/share/:token", get(handle_share_download))
+            .route("/s/:token", get(handle_share_landing))
+            .route("/s/:token/verify", post(handle_share_verify))
+            .route("/s/:token/cover", get(handle_share_cover))
             .route("/health", get(|| async { "OK" }))
-            .layer(TraceLayer::new_for_http())
             .with_state(state);
 
+        if opds_service::load_opds_settings(&self.db)?.enabled {
+            app = app.merge(opds_service::router(self.db.clone(), self.storage_path.clone())?);
+            info!("OPDS catalog enabled at /opds");
+        }
+        let app = app.layer(TraceLayer::new_for_http());
+
         let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
         info!("Share server starting on {}", addr);
 
+        // Bind before spawning so a taken port is reported to the caller
+        let listener = tokio::net::TcpListener::bind(addr).await?;
         let handle = tokio::spawn(async move {
-            let listener = tokio::net::TcpListener::bind(addr).await?;
-            axum::serve(listener, app).await?;
+            // Connect info lets handlers log the client address
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await?;
             Ok::<(), anyhow::Error>(())
         });
 
@@ -416,6 +497,8 @@ impl ShareService {
     pub async fn stop_server(&mut self) -> Result<()> {
         if let Some(handle) = self.server_handle.take() {
             handle.abort();
+            // Wait for the task to drop its listener so the port can be rebound
+            let _ = handle.await;
             info!("Share server stopped");
         }
         Ok(())
@@ -425,6 +508,55 @@ impl ShareService {
     pub fn is_running(&self) -> bool {
         self.server_handle.is_some()
     }
+
+    /// Start the local JSON API on its own listener if it is enabled in
+    /// settings. Binds to localhost unless LAN access was opted into.
+    pub async fn start_api_server(&mut self) -> Result<()> {
+        if self.api_handle.is_some() {
+            log::warn!("Local API server already running");
+            return Ok(());
+        }
+
+        let settings = api_service::load_api_settings(&self.db)?;
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let app = api_service::router(
+            self.db.clone(),
+            self.storage_path.clone(),
+            settings.token.clone(),
+        )
+        .layer(TraceLayer::new_for_http());
+
+        let addr = settings.bind_addr();
+        // Bind before spawning so a taken port is reported to the caller
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("Local API server listening on {}", addr);
+
+        self.api_handle = Some(tokio::spawn(async move {
+            axum::serve(listener, app).await?;
+            Ok::<(), anyhow::Error>(())
+        }));
+
+        Ok(())
+    }
+
+    /// Stop the local JSON API
+    pub async fn stop_api_server(&mut self) -> Result<()> {
+        if let Some(handle) = self.api_handle.take() {
+            handle.abort();
+            // Wait for the task to drop its listener so the port can be rebound
+            let _ = handle.await;
+            info!("Local API server stopped");
+        }
+        Ok(())
+    }
+
+    /// Check if the local JSON API is running
+    pub fn is_api_running(&self) -> bool {
+        self.api_handle.is_some()
+    }
 }
 
 /// Query parameters for share download
@@ -436,6 +568,7 @@ struct ShareQuery {
 /// Handle share download request
 async fn handle_share_download(
     State(state): State<AppState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Path(token): Path<String>,
     Query(query): Query<ShareQuery>,
 ) -> Result<Response, (StatusCode, String)> {
@@ -444,6 +577,7 @@ async fn handle_share_download(
         .db
         .get_connection()
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let ip_address = client.ip().to_string();
 
     let share = conn.query_row(
             "SELECT id, book_id, token, format, password_hash, expires_at, max_accesses, access_count, revoked_at, created_at
@@ -466,51 +600,42 @@ async fn handle_share_download(
         )
         .map_err(|_| (StatusCode::NOT_FOUND, "Share not found".to_string()))?;
 
-    // Check revoked
-    if share.revoked_at.is_some() {
-        return Err((StatusCode::GONE, "Share has been revoked".to_string()));
-    }
-
-    // Check expiration
-    if share.expires_at < Utc::now() {
-        return Err((StatusCode::GONE, "Share has expired".to_string()));
-    }
-
-    // Check download limit
-    if let Some(max) = share.max_accesses {
-        if share.access_count >= max {
-            return Err((StatusCode::GONE, "Download limit reached".to_string()));
-        }
+    if let Some(reason) = share_unavailable_reason(&share) {
+        return Err((StatusCode::GONE, reason.to_string()));
     }
 
     // Verify password if required
-    if let Some(hash) = &share.password_hash {
+    if share.password_hash.is_some() {
         let password = query
             .password
             .ok_or((StatusCode::UNAUTHORIZED, "Password required".to_string()))?;
 
-        let parsed_hash = PasswordHash::new(hash)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        let argon2 = Argon2::default();
-
-        if argon2
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .is_err()
+        match check_share_password(&conn, &share, Some(&password), &ip_address)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         {
-            return Err((StatusCode::UNAUTHORIZED, "Invalid password".to_string()));
+            PasswordCheck::Accepted => {}
+            PasswordCheck::Rejected => {
+                return Err((StatusCode::UNAUTHORIZED, "Invalid password".to_string()));
+            }
+            PasswordCheck::Throttled => {
+                return Err((
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too many failed password attempts, try again later".to_string(),
+                ));
+            }
         }
     }
 
     // Get book file path
-    let book_path: String = conn
+    let (book_path, book_format): (String, String) = conn
         .query_row(
-            "SELECT file_path FROM books WHERE id = ?1",
+            "SELECT file_path, file_format FROM books WHERE id = ?1",
             params![share.book_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .map_err(|_| (StatusCode::NOT_FOUND, "Book file not found".to_string()))?;
 
-    let full_path = state.storage_path.join(&book_path);
+    let mut full_path = state.storage_path.join(&book_path);
 
     if !full_path.exists() {
         return Err((
@@ -519,6 +644,15 @@ async fn handle_share_download(
         ));
     }
 
+    // Convert on demand when the share delivers a different format
+    let book_format = book_format.to_lowercase();
+    if share.format != book_format {
+        full_path =
+            converted_share_file(&state, share.book_id, &full_path, &book_format, &share.format)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
     // Increment download count
     conn.execute(
         "UPDATE shares SET access_count = access_count + 1 WHERE id = ?1",
@@ -529,7 +663,7 @@ async fn handle_share_download(
     // Log access (best-effort, don't fail the download)
     let _ = conn.execute(
         "INSERT INTO share_access_log (share_token, ip_address) VALUES (?1, ?2)",
-        params![share.token, "unknown"],
+        params![share.token, ip_address],
     );
 
     // Serve file
@@ -540,6 +674,386 @@ async fn handle_share_download(
         .into_response())
 }
 
+/// Form body posted by the landing page password gate
+#[derive(Deserialize)]
+struct VerifyForm {
+    password: String,
+}
+
+/// Why a share can no longer be downloaded, if it can't
+fn share_unavailable_reason(share: &Share) -> Option<&'static str> {
+    if share.revoked_at.is_some() {
+        return Some("Share has been revoked");
+    }
+    if share.expires_at < Utc::now() {
+        return Some("Share has expired");
+    }
+    if let Some(max) = share.max_accesses {
+        if share.access_count >= max {
+            return Some("Download limit reached");
+        }
+    }
+    None
+}
+
+/// Load a share joined with its book title and ordered author names
+fn load_share_details(
+    conn: &rusqlite::Connection,
+    token: &str,
+) -> Result<Option<SharedBookDetails>> {
+    let details = conn
+        .query_row(
+            "SELECT s.id, s.book_id, s.token, s.format, s.password_hash, s.expires_at,
+                    s.max_accesses, s.access_count, s.revoked_at, s.created_at,
+                    b.uuid, b.title, b.cover_path
+             FROM shares s
+             JOIN books b ON b.id = s.book_id
+             WHERE s.token = ?1",
+            params![token],
+            |row| {
+                Ok(SharedBookDetails {
+                    share: Share {
+                        id: row.get(0)?,
+                        book_id: row.get(1)?,
+                        token: row.get(2)?,
+                        format: row.get(3)?,
+                        password_hash: row.get(4)?,
+                        expires_at: parse_datetime(row.get(5)?)
+                            .ok_or_else(|| rusqlite::Error::InvalidQuery)?,
+                        max_accesses: row.get(6)?,
+                        access_count: row.get(7)?,
+                        revoked_at: parse_datetime(row.get(8)?),
+                        created_at: parse_datetime_required(row.get(9)?)?,
+                    },
+                    book_uuid: row.get(10)?,
+                    title: row.get(11)?,
+                    authors: Vec::new(),
+                    cover_path: row.get(12)?,
+                })
+            },
+        )
+        .optional()?;
+
+    let mut details = match details {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT a.name FROM authors a
+         JOIN books_authors ba ON ba.author_id = a.id
+         WHERE ba.book_id = ?1
+         ORDER BY ba.author_order",
+    )?;
+    details.authors = stmt
+        .query_map(params![details.share.book_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    Ok(Some(details))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Render the share landing page. The download link is only included once the
+/// password gate (if any) has been passed.
+fn render_share_page(
+    details: &SharedBookDetails,
+    download_url: Option<&str>,
+    error: Option<&str>,
+) -> String {
+    let token = escape_html(&details.share.token);
+    let authors = if details.authors.is_empty() {
+        "Unknown author".to_string()
+    } else {
+        escape_html(&details.authors.join(", "))
+    };
+
+    let action = match download_url {
+        Some(url) => format!(
+            r#"<a class="button" href="{}">Download {}</a>"#,
+            escape_html(url),
+            escape_html(&details.share.format.to_uppercase())
+        ),
+        None => format!(
+            r#"<form method="post" action="/s/{}/verify">
+        <input type="password" name="password" placeholder="Password" required autofocus>
+        <button class="button" type="submit">Unlock</button>
+      </form>"#,
+            token
+        ),
+    };
+    let error = error
+        .map(|e| format!(r#"<p class="error">{}</p>"#, escape_html(e)))
+        .unwrap_or_default();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>{title} — Shiori</title>
+  <style>
+    body {{ font-family: system-ui, sans-serif; background: #111; color: #eee; display: flex; justify-content: center; padding: 2rem; }}
+    main {{ max-width: 360px; text-align: center; }}
+    img {{ width: 100%; border-radius: 8px; box-shadow: 0 4px 16px rgba(0,0,0,.5); }}
+    .muted {{ color: #999; font-size: .9rem; }}
+    .error {{ color: #f66; }}
+    .button {{ display: inline-block; margin-top: 1rem; padding: .6rem 1.4rem; border: 0; border-radius: 6px; background: #eee; color: #111; text-decoration: none; font-size: 1rem; cursor: pointer; }}
+    input {{ padding: .5rem; border-radius: 6px; border: 1px solid #444; background: #222; color: #eee; width: 100%; box-sizing: border-box; margin-top: 1rem; }}
+  </style>
+</head>
+<body>
+  <main>
+    <img src="/s/{token}/cover" alt="Cover">
+    <h1>{title}</h1>
+    <p>{authors}</p>
+    <p class="muted">Link expires {expires}</p>
+    {error}
+    {action}
+  </main>
+</body>
+</html>"#,
+        title = escape_html(&details.title),
+        token = token,
+        authors = authors,
+        expires = details.share.expires_at.format("%Y-%m-%d %H:%M UTC"),
+        error = error,
+        action = action,
+    )
+}
+
+/// Load share details for an HTML route, mapping unavailable shares to errors
+fn landing_share_details(
+    state: &AppState,
+    token: &str,
+) -> Result<SharedBookDetails, (StatusCode, String)> {
+    let conn = state
+        .db
+        .get_connection()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let details = load_share_details(&conn, token)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Share not found".to_string()))?;
+
+    if let Some(reason) = share_unavailable_reason(&details.share) {
+        return Err((StatusCode::GONE, reason.to_string()));
+    }
+    Ok(details)
+}
+
+/// Handle share landing page request
+async fn handle_share_landing(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let details = landing_share_details(&state, &token)?;
+
+    let download_url = format!("/share/{}", details.share.token);
+    let download_url = details
+        .share
+        .password_hash
+        .is_none()
+        .then_some(download_url.as_str());
+
+    Ok(Html(render_share_page(&details, download_url, None)))
+}
+
+/// Handle the landing page password form, revealing the download link on success
+async fn handle_share_verify(
+    State(state): State<AppState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Path(token): Path<String>,
+    Form(form): Form<VerifyForm>,
+) -> Result<Response, (StatusCode, String)> {
+    let details = landing_share_details(&state, &token)?;
+    let conn = state
+        .db
+        .get_connection()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let ip_address = client.ip().to_string();
+    let check = check_share_password(&conn, &details.share, Some(&form.password), &ip_address)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let response = match check {
+        PasswordCheck::Accepted => {
+            let download_url = format!(
+                "/share/{}?password={}",
+                details.share.token,
+                urlencoding::encode(&form.password)
+            );
+            Html(render_share_page(&details, Some(&download_url), None)).into_response()
+        }
+        PasswordCheck::Rejected => (
+            StatusCode::UNAUTHORIZED,
+            Html(render_share_page(&details, None, Some("Incorrect password"))),
+        )
+            .into_response(),
+        PasswordCheck::Throttled => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Html(render_share_page(
+                &details,
+                None,
+                Some("Too many failed attempts, try again later"),
+            )),
+        )
+            .into_response(),
+    };
+    Ok(response)
+}
+
+/// Handle share cover request, preferring the extracted cover over a generated one
+async fn handle_share_cover(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    let details = landing_share_details(&state, &token)?;
+
+    if let Some(cover_path) = &details.cover_path {
+        if let Ok(bytes) = tokio::fs::read(cover_path).await {
+            let mime = mime_guess::from_path(cover_path).first_or_octet_stream();
+            return Ok(([(header::CONTENT_TYPE, mime.to_string())], bytes).into_response());
+        }
+    }
+
+    let cover_service = state
+        .cover_service
+        .as_ref()
+        .ok_or((StatusCode::NOT_FOUND, "Cover not available".to_string()))?;
+    let uuid = uuid::Uuid::parse_str(&details.book_uuid)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let metadata = BookMetadata {
+        title: details.title.clone(),
+        authors: details.authors.clone(),
+        ..Default::default()
+    };
+    let cover_set = cover_service
+        .get_or_generate_cover(uuid, None, &metadata)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let bytes = tokio::fs::read(&cover_set.medium)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(([(header::CONTENT_TYPE, "image/webp".to_string())], bytes).into_response())
+}
+
+/// Return a converted copy of a shared book, converting only when no fresh
+/// cached artifact exists for `(book_id, target_format)`.
+async fn converted_share_file(
+    state: &AppState,
+    book_id: i64,
+    source: &std::path::Path,
+    source_format: &str,
+    target_format: &str,
+) -> Result<PathBuf> {
+    let cache_dir = state.storage_path.join("share_conversions");
+    std::fs::create_dir_all(&cache_dir)?;
+    let cached = cache_dir.join(format!("{}.{}", book_id, target_format));
+
+    // Reuse the cached artifact unless the source changed after it was built
+    let source_modified = std::fs::metadata(source)?.modified()?;
+    if let Ok(meta) = std::fs::metadata(&cached) {
+        if meta.modified().map(|m| m >= source_modified).unwrap_or(false) {
+            return Ok(cached);
+        }
+    }
+
+    info!(
+        "Share: converting book {} from {} to {}",
+        book_id, source_format, target_format
+    );
+
+    // Convert into a temp file so concurrent downloads never see a partial artifact
+    let temp = tempfile::Builder::new()
+        .prefix(&format!("{}-", book_id))
+        .suffix(&format!(".{}", target_format))
+        .tempfile_in(&cache_dir)?;
+    ConversionEngine::convert_direct(
+        source,
+        temp.path(),
+        source_format,
+        target_format,
+        Some(&state.db),
+        None,
+    )
+    .await
+    .map_err(|e| anyhow!("Conversion failed: {}", e))?;
+    temp.persist(&cached).map_err(|e| anyhow!("{}", e))?;
+
+    Ok(cached)
+}
+
+/// Count failed password attempts for a share within the throttle window
+fn count_recent_password_failures(conn: &rusqlite::Connection, token: &str) -> Result<i64> {
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM share_access_log
+         WHERE share_token = ?1 AND success = 0 AND failure_reason = 'bad_password'
+           AND accessed_at >= datetime('now', ?2)",
+        params![token, format!("-{} minutes", PASSWORD_ATTEMPT_WINDOW_MINUTES)],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Record a failed share access with its reason
+fn log_failed_access(
+    conn: &rusqlite::Connection,
+    token: &str,
+    ip_address: &str,
+    reason: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO share_access_log (share_token, ip_address, success, failure_reason)
+         VALUES (?1, ?2, 0, ?3)",
+        params![token, ip_address, reason],
+    )?;
+    Ok(())
+}
+
+/// Check a password against a share, enforcing the failed-attempt throttle.
+/// Bad passwords and throttled attempts are both recorded in the access log.
+fn check_share_password(
+    conn: &rusqlite::Connection,
+    share: &Share,
+    password: Option<&str>,
+    ip_address: &str,
+) -> Result<PasswordCheck> {
+    let hash = match &share.password_hash {
+        Some(hash) => hash,
+        None => return Ok(PasswordCheck::Accepted), // No password required
+    };
+
+    if count_recent_password_failures(conn, &share.token)? >= MAX_FAILED_PASSWORD_ATTEMPTS {
+        log_failed_access(conn, &share.token, ip_address, "rate_limited")?;
+        return Ok(PasswordCheck::Throttled);
+    }
+
+    let parsed_hash =
+        PasswordHash::new(hash).map_err(|e| anyhow!("Invalid password hash: {}", e))?;
+    let verified = password
+        .map(|p| {
+            Argon2::default()
+                .verify_password(p.as_bytes(), &parsed_hash)
+                .is_ok()
+        })
+        .unwrap_or(false);
+
+    if verified {
+        Ok(PasswordCheck::Accepted)
+    } else {
+        log_failed_access(conn, &share.token, ip_address, "bad_password")?;
+        Ok(PasswordCheck::Rejected)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -563,6 +1077,7 @@ mod tests {
         assert_eq!(options.expires_in_hours, Some(24));
         assert!(options.password.is_none());
         assert!(options.max_accesses.is_none());
+        assert!(options.deliver_format.is_none());
     }
 
     #[test]
@@ -602,4 +1117,160 @@ mod tests {
         assert!(share1.token.chars().all(|c| c.is_ascii_alphanumeric()));
         assert!(share2.token.chars().all(|c| c.is_ascii_alphanumeric()));
     }
+
+    #[test]
+    fn test_password_attempts_are_throttled() {
+        let temp_dir = std::env::temp_dir().join(format!(
+            "shiori-test-share-throttle-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .subsec_nanos()
+        ));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let db = Database::new(temp_dir.join("test-throttle.db")).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (id, uuid, title, file_path, file_format) VALUES (1, 'test-uuid', 'Test Book', 'test.epub', 'epub')",
+                [],
+            ).unwrap();
+        }
+
+        let service = ShareService::new(db, temp_dir, Some(8888));
+        let share = service
+            .create_share(
+                1,
+                ShareOptions {
+                    password: Some("correct horse".to_string()),
+                    ..ShareOptions::default()
+                },
+            )
+            .unwrap();
+
+        for _ in 0..MAX_FAILED_PASSWORD_ATTEMPTS {
+            assert_eq!(
+                service
+                    .verify_password(&share.token, "wrong", "192.0.2.7")
+                    .unwrap(),
+                PasswordCheck::Rejected
+            );
+        }
+
+        // Failures are logged against the client address
+        let ips: Vec<String> = {
+            let conn = service.db.get_connection().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT DISTINCT ip_address FROM share_access_log WHERE share_token = ?1")
+                .unwrap();
+            let ips = stmt
+                .query_map([&share.token], |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap();
+            ips
+        };
+        assert_eq!(ips, vec!["192.0.2.7".to_string()]);
+
+        // The 11th attempt is blocked, even with the right password
+        assert_eq!(
+            service
+                .verify_password(&share.token, "correct horse", "192.0.2.7")
+                .unwrap(),
+            PasswordCheck::Throttled
+        );
+    }
+
+    #[test]
+    fn test_share_deliver_format() {
+        let temp_dir = std::env::temp_dir().join(format!(
+            "shiori-test-share-deliver-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .subsec_nanos()
+        ));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let db = Database::new(temp_dir.join("test-deliver.db")).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (id, uuid, title, file_path, file_format) VALUES (1, 'test-uuid', 'Test Book', 'test.epub', 'epub')",
+                [],
+            ).unwrap();
+        }
+
+        let service = ShareService::new(db, temp_dir, Some(8888));
+        let share = service
+            .create_share(
+                1,
+                ShareOptions {
+                    deliver_format: Some("PDF".to_string()),
+                    ..ShareOptions::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(share.format, "pdf");
+
+        // Unsupported conversions are rejected when the share is created
+        let rejected = service.create_share(
+            1,
+            ShareOptions {
+                deliver_format: Some("cbz".to_string()),
+                ..ShareOptions::default()
+            },
+        );
+        assert!(rejected.is_err());
+    }
+
+    #[test]
+    fn test_share_details_and_landing_page() {
+        let temp_dir = std::env::temp_dir().join(format!(
+            "shiori-test-share-landing-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .subsec_nanos()
+        ));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let db = Database::new(temp_dir.join("test-landing.db")).unwrap();
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO books (id, uuid, title, file_path, file_format) VALUES (1, 'test-uuid', 'Tom & <Jerry>', 'test.epub', 'epub')",
+                [],
+            ).unwrap();
+            conn.execute("INSERT INTO authors (id, name) VALUES (1, 'Ann Author')", [])
+                .unwrap();
+            conn.execute(
+                "INSERT INTO books_authors (book_id, author_id, author_order) VALUES (1, 1, 0)",
+                [],
+            )
+            .unwrap();
+        }
+
+        let service = ShareService::new(db, temp_dir, Some(8888));
+        let share = service
+            .create_share(
+                1,
+                ShareOptions {
+                    password: Some("secret".to_string()),
+                    ..ShareOptions::default()
+                },
+            )
+            .unwrap();
+
+        let details = service.get_share_details(&share.token).unwrap().unwrap();
+        assert_eq!(details.title, "Tom & <Jerry>");
+        assert_eq!(details.authors, vec!["Ann Author".to_string()]);
+
+        // Password-protected shares render the gate, not the download link
+        let page = render_share_page(&details, None, None);
+        assert!(page.contains("Tom &amp; &lt;Jerry&gt;"));
+        assert!(page.contains(&format!("/s/{}/verify", share.token)));
+        assert!(!page.contains(&format!("/share/{}", share.token)));
+    }
 }
@@ -0,0 +1,333 @@
+//! Reading statistics aggregated over a date range ("year in review").
+//!
+//! Activity comes from `reading_sessions` (time and pages), `reading_progress`
+//! (finished books and last-read days) and `annotations`. Dates are compared
+//! as `YYYY-MM-DD` in UTC, and both ends of the range are inclusive.
+
+use crate::error::{Result, ShioriError};
+use crate::models::{AuthorReadingCount, MonthlyReadingStats, ReadingStats};
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn parse_date(value: &str, field: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+        ShioriError::Validation(format!(
+            "{} must be a YYYY-MM-DD date, got '{}'",
+            field, value
+        ))
+    })
+}
+
+/// Run a `(month, a, b)` grouping query and collect both values by month
+fn monthly_counts(
+    conn: &Connection,
+    sql: &str,
+    from: &str,
+    to: &str,
+) -> Result<HashMap<String, (i64, i64)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+                row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows.into_iter().map(|(m, a, b)| (m, (a, b))).collect())
+}
+
+/// `YYYY-MM` for every month touched by `from..=to`
+fn months_between(from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let mut months = Vec::new();
+    let (mut year, mut month) = (from.year(), from.month());
+    while (year, month) <= (to.year(), to.month()) {
+        months.push(format!("{:04}-{:02}", year, month));
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+    }
+    months
+}
+
+/// Longest run of consecutive days in an ascending, de-duplicated list
+fn longest_streak(days: &[NaiveDate]) -> i32 {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        current = match previous {
+            Some(prev) if day.signed_duration_since(prev).num_days() == 1 => current + 1,
+            _ => 1,
+        };
+        longest = longest.max(current);
+        previous = Some(*day);
+    }
+    longest
+}
+
+/// Days with any reading activity in the range, ascending
+fn reading_days(conn: &Connection, from: &str, to: &str) -> Result<Vec<NaiveDate>> {
+    let mut stmt = conn.prepare(
+        "SELECT date(last_read) AS d FROM reading_progress
+         WHERE date(last_read) BETWEEN ?1 AND ?2
+         UNION
+         SELECT date(started_at) FROM reading_sessions
+         WHERE duration_seconds > 0 AND date(started_at) BETWEEN ?1 AND ?2
+         UNION
+         SELECT date(created_at) FROM annotations
+         WHERE date(created_at) BETWEEN ?1 AND ?2
+         ORDER BY d",
+    )?;
+    let days = stmt
+        .query_map(params![from, to], |row| row.get::<_, Option<String>>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(days
+        .into_iter()
+        .flatten()
+        .filter_map(|d| NaiveDate::parse_from_str(&d, DATE_FORMAT).ok())
+        .collect())
+}
+
+/// Author with the most distinct books read in the range, ties broken by name
+fn most_read_author(conn: &Connection, from: &str, to: &str) -> Result<Option<AuthorReadingCount>> {
+    let author = conn
+        .query_row(
+            "WITH active AS (
+                 SELECT book_id FROM reading_progress
+                 WHERE date(last_read) BETWEEN ?1 AND ?2
+                 UNION
+                 SELECT book_id FROM reading_sessions
+                 WHERE duration_seconds > 0 AND date(started_at) BETWEEN ?1 AND ?2
+             )
+             SELECT a.name, COUNT(DISTINCT ba.book_id) AS books_count
+             FROM active
+             JOIN books_authors ba ON ba.book_id = active.book_id
+             JOIN authors a ON a.id = ba.author_id
+             GROUP BY a.id
+             ORDER BY books_count DESC, a.name ASC
+             LIMIT 1",
+            params![from, to],
+            |row| {
+                Ok(AuthorReadingCount {
+                    name: row.get(0)?,
+                    books_count: row.get(1)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(author)
+}
+
+/// Aggregate reading activity between `from_date` and `to_date` (inclusive,
+/// `YYYY-MM-DD`) with a per-month breakdown covering every month in the range
+pub fn get_reading_stats(
+    conn: &Connection,
+    from_date: &str,
+    to_date: &str,
+) -> Result<ReadingStats> {
+    let from = parse_date(from_date, "from_date")?;
+    let to = parse_date(to_date, "to_date")?;
+    if from > to {
+        return Err(ShioriError::Validation(format!(
+            "from_date {} is after to_date {}",
+            from_date, to_date
+        )));
+    }
+
+    // A book counts as finished in the month its progress last reached 100%
+    // (or was marked completed)
+    let finished = monthly_counts(
+        conn,
+        "SELECT strftime('%Y-%m', rp.last_read) AS month, COUNT(*), 0
+         FROM reading_progress rp
+         JOIN books b ON b.id = rp.book_id
+         WHERE date(rp.last_read) BETWEEN ?1 AND ?2
+           AND (rp.progress_percent >= 100 OR b.reading_status = 'completed')
+         GROUP BY month",
+        from_date,
+        to_date,
+    )?;
+    let sessions = monthly_counts(
+        conn,
+        "SELECT strftime('%Y-%m', started_at) AS month,
+                SUM(CASE WHEN pages_end > pages_start THEN pages_end - pages_start ELSE 0 END),
+                SUM(duration_seconds)
+         FROM reading_sessions
+         WHERE duration_seconds > 0 AND date(started_at) BETWEEN ?1 AND ?2
+         GROUP BY month",
+        from_date,
+        to_date,
+    )?;
+    let annotations = monthly_counts(
+        conn,
+        "SELECT strftime('%Y-%m', created_at) AS month, COUNT(*), 0
+         FROM annotations
+         WHERE date(created_at) BETWEEN ?1 AND ?2
+         GROUP BY month",
+        from_date,
+        to_date,
+    )?;
+
+    let months: Vec<MonthlyReadingStats> = months_between(from, to)
+        .into_iter()
+        .map(|month| {
+            let (pages_read, total_seconds) = sessions.get(&month).copied().unwrap_or_default();
+            MonthlyReadingStats {
+                books_finished: finished.get(&month).map_or(0, |c| c.0),
+                pages_read,
+                total_seconds,
+                annotations_count: annotations.get(&month).map_or(0, |c| c.0),
+                month,
+            }
+        })
+        .collect();
+
+    let days = reading_days(conn, from_date, to_date)?;
+
+    Ok(ReadingStats {
+        from_date: from_date.to_string(),
+        to_date: to_date.to_string(),
+        books_finished: months.iter().map(|m| m.books_finished).sum(),
+        pages_read: months.iter().map(|m| m.pages_read).sum(),
+        total_seconds: months.iter().map(|m| m.total_seconds).sum(),
+        annotations_count: months.iter().map(|m| m.annotations_count).sum(),
+        most_read_author: most_read_author(conn, from_date, to_date)?,
+        reading_days: days.len() as i32,
+        longest_streak: longest_streak(&days),
+        months,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use tempfile::tempdir;
+
+    fn add_book(conn: &Connection, title: &str, author: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+            params![title, format!("/tmp/{}.epub", title)],
+        )
+        .unwrap();
+        let book_id = conn.last_insert_rowid();
+        conn.execute("INSERT OR IGNORE INTO authors (name) VALUES (?1)", [author])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO books_authors (book_id, author_id)
+             SELECT ?1, id FROM authors WHERE name = ?2",
+            params![book_id, author],
+        )
+        .unwrap();
+        book_id
+    }
+
+    fn add_progress(conn: &Connection, book_id: i64, percent: f64, last_read: &str) {
+        conn.execute(
+            "INSERT INTO reading_progress (book_id, current_location, progress_percent, last_read)
+             VALUES (?1, '0', ?2, ?3)",
+            params![book_id, percent, last_read],
+        )
+        .unwrap();
+    }
+
+    fn add_session(conn: &Connection, book_id: i64, started_at: &str, pages: (i32, i32)) {
+        conn.execute(
+            "INSERT INTO reading_sessions (id, book_id, started_at, duration_seconds, pages_start, pages_end)
+             VALUES (?1, ?2, ?3, 600, ?4, ?5)",
+            params![
+                format!("{}-{}", book_id, started_at),
+                book_id,
+                started_at,
+                pages.0,
+                pages.1
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_reading_stats_over_a_year() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("stats.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        let dune = add_book(&conn, "Dune", "Frank Herbert");
+        let messiah = add_book(&conn, "Dune Messiah", "Frank Herbert");
+        let emma = add_book(&conn, "Emma", "Jane Austen");
+        let old = add_book(&conn, "Old Book", "Jane Austen");
+
+        // Three consecutive days in January, then a gap, then two in March
+        add_session(&conn, dune, "2026-01-10T20:00:00+00:00", (0, 40));
+        add_session(&conn, dune, "2026-01-11T20:00:00+00:00", (40, 90));
+        add_session(&conn, messiah, "2026-01-12T21:00:00+00:00", (0, 10));
+        add_session(&conn, emma, "2026-03-01T08:00:00+00:00", (5, 5));
+        add_progress(&conn, dune, 100.0, "2026-01-11T21:00:00+00:00");
+        add_progress(&conn, messiah, 35.0, "2026-01-12T22:00:00+00:00");
+        add_progress(&conn, emma, 100.0, "2026-03-02T09:00:00+00:00");
+        // Finished before the range starts
+        add_progress(&conn, old, 100.0, "2025-12-31T23:00:00+00:00");
+        conn.execute(
+            "INSERT INTO annotations (book_id, type, location, created_at)
+             VALUES (?1, 'highlight', 'loc', '2026-03-02 09:30:00')",
+            [emma],
+        )
+        .unwrap();
+
+        let stats = get_reading_stats(&conn, "2026-01-01", "2026-12-31").unwrap();
+        assert_eq!(stats.books_finished, 2);
+        assert_eq!(stats.pages_read, 100);
+        assert_eq!(stats.total_seconds, 2400);
+        assert_eq!(stats.annotations_count, 1);
+        assert_eq!(stats.reading_days, 5);
+        assert_eq!(stats.longest_streak, 3);
+
+        let author = stats.most_read_author.unwrap();
+        assert_eq!(author.name, "Frank Herbert");
+        assert_eq!(author.books_count, 2);
+
+        assert_eq!(stats.months.len(), 12);
+        assert_eq!(stats.months[0].month, "2026-01");
+        assert_eq!(stats.months[0].books_finished, 1);
+        assert_eq!(stats.months[0].pages_read, 100);
+        assert_eq!(stats.months[1].total_seconds, 0);
+        assert_eq!(stats.months[2].books_finished, 1);
+        assert_eq!(stats.months[2].annotations_count, 1);
+
+        let march = get_reading_stats(&conn, "2026-03-01", "2026-03-31").unwrap();
+        assert_eq!(march.longest_streak, 2);
+        assert_eq!(march.most_read_author.unwrap().name, "Jane Austen");
+    }
+
+    #[test]
+    fn test_reading_stats_rejects_bad_ranges() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("stats.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        assert!(get_reading_stats(&conn, "2026-13-01", "2026-12-31").is_err());
+        assert!(get_reading_stats(&conn, "2026-06-01", "2026-01-01").is_err());
+        let empty = get_reading_stats(&conn, "2026-02-10", "2026-02-10").unwrap();
+        assert_eq!(empty.months.len(), 1);
+        assert_eq!(empty.longest_streak, 0);
+        assert!(empty.most_read_author.is_none());
+    }
+
+    #[test]
+    fn test_longest_streak_counts_consecutive_days() {
+        let days: Vec<NaiveDate> = ["2026-01-30", "2026-01-31", "2026-02-01", "2026-02-03"]
+            .iter()
+            .map(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).unwrap())
+            .collect();
+        assert_eq!(longest_streak(&days), 3);
+        assert_eq!(longest_streak(&[]), 0);
+    }
+}
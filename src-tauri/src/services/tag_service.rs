@@ -1,7 +1,14 @@
 use crate::db::Database;
-use crate::error::Result;
-use crate::models::Tag;
-use rusqlite::params;
+use crate::error::{Result, ShioriError};
+use crate::models::{Book, Tag, TagNode, TagRule};
+use crate::services::cover_service::{hsl_to_rgb, seed_hue};
+use crate::services::library_service;
+use rusqlite::types::Value;
+use rusqlite::{params, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+
+/// Separator between the segments of a nested tag path ("Fiction/Sci-Fi")
+pub const TAG_PATH_SEPARATOR: char = '/';
 
 pub fn get_all_tags(db: &Database) -> Result<Vec<Tag>> {
     let conn = db.get_connection()?;
@@ -33,15 +40,262 @@ pub fn get_book_tag_ids(db: &Database, book_id: i64) -> Result<Vec<i64>> {
     Ok(ids)
 }
 
+/// Trimmed segments of a tag path, rejecting empty ones ("Fiction//Horror")
+fn tag_path_segments(name: &str) -> Result<Vec<&str>> {
+    let segments: Vec<&str> = name.split(TAG_PATH_SEPARATOR).map(str::trim).collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(ShioriError::Validation(format!(
+            "Tag path '{}' contains an empty segment",
+            name
+        )));
+    }
+    Ok(segments)
+}
+
+/// Create a tag. A path such as "Fiction/Sci-Fi/Cyberpunk" creates any
+/// missing ancestors ("Fiction", "Fiction/Sci-Fi") and returns the id of the
+/// leaf, which gets `color`.
 pub fn create_tag(db: &Database, name: String, color: Option<String>) -> Result<i64> {
+    let segments = tag_path_segments(&name)?;
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    let id = insert_tag_path(&tx, &segments, color.as_deref(), false)?;
+    tx.commit()?;
+    Ok(id)
+}
+
+/// Walk `segments`, reusing existing ancestor tags and inserting missing
+/// ones. The leaf is reused too when `reuse_leaf` is set, otherwise a
+/// duplicate leaf fails on the UNIQUE name constraint.
+fn insert_tag_path(
+    conn: &rusqlite::Connection,
+    segments: &[&str],
+    color: Option<&str>,
+    reuse_leaf: bool,
+) -> Result<i64> {
+    let mut parent_id: Option<i64> = None;
+    let mut path = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let is_leaf = i + 1 == segments.len();
+        if i > 0 {
+            path.push(TAG_PATH_SEPARATOR);
+        }
+        path.push_str(segment);
+
+        if !is_leaf || reuse_leaf {
+            let existing: Option<i64> = conn
+                .query_row("SELECT id FROM tags WHERE name = ?1", [&path], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            if let Some(id) = existing {
+                parent_id = Some(id);
+                continue;
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO tags (name, color, parent_id) VALUES (?1, ?2, ?3)",
+            params![path, if is_leaf { color } else { None }, parent_id],
+        )?;
+        parent_id = Some(conn.last_insert_rowid());
+    }
+
+    Ok(parent_id.expect("tag path has at least one segment"))
+}
+
+/// All tags as a forest, children sorted by name. A `parent_id` cycle is
+/// broken at the first tag of the cycle reached, which becomes a root.
+pub fn get_tag_tree(db: &Database) -> Result<Vec<TagNode>> {
     let conn = db.get_connection()?;
 
-    conn.execute(
-        "INSERT INTO tags (name, color) VALUES (?1, ?2)",
-        params![name, color],
+    let rows = conn
+        .prepare("SELECT id, name, color, parent_id FROM tags ORDER BY name")?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let ids: HashSet<i64> = rows.iter().map(|r| r.0).collect();
+    let mut children: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (id, _, _, parent_id) in &rows {
+        match parent_id {
+            Some(parent) if ids.contains(parent) && parent != id => {
+                children.entry(*parent).or_default().push(*id)
+            }
+            _ => roots.push(*id),
+        }
+    }
+    let tags: HashMap<i64, (String, Option<String>)> = rows
+        .into_iter()
+        .map(|(id, name, color, _)| (id, (name, color)))
+        .collect();
+
+    fn build(
+        id: i64,
+        parent_name: Option<&str>,
+        tags: &HashMap<i64, (String, Option<String>)>,
+        children: &HashMap<i64, Vec<i64>>,
+        visited: &mut HashSet<i64>,
+    ) -> TagNode {
+        visited.insert(id);
+        let (name, color) = &tags[&id];
+        let label = parent_name
+            .and_then(|p| name.strip_prefix(p))
+            .and_then(|rest| rest.strip_prefix(TAG_PATH_SEPARATOR))
+            .unwrap_or(name.as_str())
+            .to_string();
+        let mut child_nodes = Vec::new();
+        for &child in children.get(&id).into_iter().flatten() {
+            if !visited.contains(&child) {
+                child_nodes.push(build(child, Some(name.as_str()), tags, children, visited));
+            }
+        }
+        TagNode {
+            id,
+            name: name.clone(),
+            label,
+            color: color.clone(),
+            children: child_nodes,
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut forest = Vec::new();
+    for id in roots {
+        forest.push(build(id, None, &tags, &children, &mut visited));
+    }
+    // Tags only reachable through a cycle were never visited
+    let mut orphans: Vec<i64> = tags
+        .keys()
+        .filter(|id| !visited.contains(*id))
+        .copied()
+        .collect();
+    orphans.sort_by(|a, b| tags[a].0.cmp(&tags[b].0));
+    for id in orphans {
+        if !visited.contains(&id) {
+            forest.push(build(id, None, &tags, &children, &mut visited));
+        }
+    }
+
+    Ok(forest)
+}
+
+/// Books tagged `tag_id`, plus books tagged with any of its descendants when
+/// `include_descendants` is set. Trashed books are excluded.
+pub fn get_books_by_tag(
+    db: &Database,
+    tag_id: i64,
+    include_descendants: bool,
+) -> Result<Vec<Book>> {
+    let conn = db.get_connection()?;
+    // UNION (not UNION ALL) drops already-seen ids, so a parent_id cycle
+    // terminates instead of recursing forever
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id) AS (
+             SELECT ?1
+             UNION
+             SELECT t.id FROM tags t JOIN subtree s ON t.parent_id = s.id
+             WHERE ?2
+         )
+         SELECT b.id FROM books b
+         WHERE b.in_trash = 0
+           AND b.id IN (SELECT book_id FROM books_tags
+                        WHERE tag_id IN (SELECT id FROM subtree))
+         ORDER BY b.title COLLATE NOCASE",
     )?;
+    let ids = stmt
+        .query_map(params![tag_id, include_descendants], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<i64>, _>>()?;
+    drop(stmt);
+    drop(conn);
+
+    library_service::get_books_by_ids(db, &ids)
+}
 
-    Ok(conn.last_insert_rowid())
+/// How [`get_books_by_tags`] combines several tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagFilterMode {
+    /// Books with at least one of the tags
+    Any,
+    /// Books with every one of the tags
+    All,
+}
+
+/// Books matching `tag_ids` under `mode`, optionally limited to a domain
+/// ("books", "manga" or "comics"), ordered by title. Trashed books are
+/// excluded.
+pub fn get_books_by_tags(
+    db: &Database,
+    tag_ids: &[i64],
+    mode: TagFilterMode,
+    domain: Option<&str>,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<Book>> {
+    let mut tag_ids = tag_ids.to_vec();
+    tag_ids.sort_unstable();
+    tag_ids.dedup();
+    if tag_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    // Placeholders are bound in one statement, unlike the chunked batch helpers
+    if tag_ids.len() > 500 {
+        return Err(ShioriError::Validation(
+            "Cannot filter by more than 500 tags".to_string(),
+        ));
+    }
+
+    let mut values = vec![Value::Integer(limit as i64), Value::Integer(offset as i64)];
+    let placeholders = tag_ids
+        .iter()
+        .map(|id| {
+            values.push(Value::Integer(*id));
+            format!("?{}", values.len())
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let domain_clause = match domain {
+        Some(domain @ ("books" | "manga" | "comics")) => {
+            values.push(Value::Text(domain.to_string()));
+            format!("AND b.domain = ?{}", values.len())
+        }
+        _ => String::new(),
+    };
+    // Tag ids are de-duplicated above, so a book has all of them exactly
+    // when it matches that many distinct ones
+    let having = match mode {
+        TagFilterMode::Any => String::new(),
+        TagFilterMode::All => format!("HAVING COUNT(DISTINCT bt.tag_id) = {}", tag_ids.len()),
+    };
+
+    let conn = db.get_connection()?;
+    let sql = format!(
+        "SELECT b.id FROM books b
+         JOIN books_tags bt ON bt.book_id = b.id
+         WHERE b.in_trash = 0 AND bt.tag_id IN ({}) {}
+         GROUP BY b.id
+         {}
+         ORDER BY b.title COLLATE NOCASE, b.id
+         LIMIT ?1 OFFSET ?2",
+        placeholders, domain_clause, having
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let ids = stmt
+        .query_map(rusqlite::params_from_iter(values), |row| row.get(0))?
+        .collect::<std::result::Result<Vec<i64>, _>>()?;
+    drop(stmt);
+    drop(conn);
+
+    library_service::get_books_by_ids(db, &ids)
 }
 
 pub fn add_tag_to_book(db: &Database, book_id: i64, tag_id: i64) -> Result<()> {
@@ -51,6 +305,7 @@ pub fn add_tag_to_book(db: &Database, book_id: i64, tag_id: i64) -> Result<()> {
         "INSERT OR IGNORE INTO books_tags (book_id, tag_id) VALUES (?1, ?2)",
         params![book_id, tag_id],
     )?;
+    library_service::refresh_fts_row(&conn, book_id)?;
 
     Ok(())
 }
@@ -62,6 +317,672 @@ pub fn remove_tag_from_book(db: &Database, book_id: i64, tag_id: i64) -> Result<
         "DELETE FROM books_tags WHERE book_id = ?1 AND tag_id = ?2",
         params![book_id, tag_id],
     )?;
+    library_service::refresh_fts_row(&conn, book_id)?;
 
     Ok(())
 }
+
+/// Tag many books in one transaction. Books that already have the tag, or
+/// do not exist, are skipped. Returns the number of books newly tagged.
+pub fn add_tag_to_books(db: &Database, tag_id: i64, book_ids: &[i64]) -> Result<usize> {
+    update_books_tag(db, tag_id, book_ids, true)
+}
+
+/// Untag many books in one transaction. Returns the number of books that
+/// had the tag.
+pub fn remove_tag_from_books(db: &Database, tag_id: i64, book_ids: &[i64]) -> Result<usize> {
+    update_books_tag(db, tag_id, book_ids, false)
+}
+
+fn update_books_tag(db: &Database, tag_id: i64, book_ids: &[i64], add: bool) -> Result<usize> {
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM tags WHERE id = ?1",
+        [tag_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(ShioriError::TagNotFound(tag_id.to_string()));
+    }
+
+    let mut changed = 0;
+    // Stay under SQLite's bound parameter limit, as get_books_by_ids does
+    for chunk in book_ids.chunks(500) {
+        let placeholders = (2..chunk.len() + 2)
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = if add {
+            format!(
+                "INSERT OR IGNORE INTO books_tags (book_id, tag_id)
+                 SELECT id, ?1 FROM books WHERE id IN ({})",
+                placeholders
+            )
+        } else {
+            format!(
+                "DELETE FROM books_tags WHERE tag_id = ?1 AND book_id IN ({})",
+                placeholders
+            )
+        };
+        let values = std::iter::once(&tag_id).chain(chunk.iter());
+        changed += tx.execute(&sql, rusqlite::params_from_iter(values))?;
+    }
+
+    // Reindex once per book after all junction rows changed, instead of
+    // once per inserted row
+    if changed > 0 {
+        library_service::refresh_fts_rows(&tx, book_ids)?;
+    }
+    tx.commit()?;
+    Ok(changed)
+}
+
+/// `#rrggbb` color for a tag, derived from its name so it never changes
+fn tag_color(name: &str) -> String {
+    let [r, g, b] = hsl_to_rgb(seed_hue(&name.trim().to_lowercase()), 0.65, 0.5);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Give every tag without a color one derived from its name. Returns the
+/// number of tags updated.
+pub fn auto_assign_colors(db: &Database) -> Result<usize> {
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+
+    let uncolored = tx
+        .prepare("SELECT id, name FROM tags WHERE color IS NULL OR TRIM(color) = ''")?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (id, name) in &uncolored {
+        tx.execute(
+            "UPDATE tags SET color = ?1 WHERE id = ?2",
+            params![tag_color(name), id],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(uncolored.len())
+}
+
+/// Move every book tagged `from_id` to `into_id`, then delete `from_id`.
+/// Books that already had both tags keep a single `into_id` row.
+pub fn merge_tags(db: &Database, from_id: i64, into_id: i64) -> Result<()> {
+    if from_id == into_id {
+        return Err(ShioriError::Validation(
+            "Cannot merge a tag into itself".to_string(),
+        ));
+    }
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+
+    for id in [from_id, into_id] {
+        let exists: bool =
+            tx.query_row("SELECT COUNT(*) > 0 FROM tags WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })?;
+        if !exists {
+            return Err(ShioriError::TagNotFound(id.to_string()));
+        }
+    }
+
+    // Sub-tags are named after their parent's path; merging would orphan them
+    let has_children: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM tags WHERE parent_id = ?1",
+        [from_id],
+        |row| row.get(0),
+    )?;
+    if has_children {
+        return Err(ShioriError::Validation(
+            "Cannot merge a tag that has sub-tags".to_string(),
+        ));
+    }
+
+    let book_ids: Vec<i64> = tx
+        .prepare("SELECT book_id FROM books_tags WHERE tag_id = ?1")?
+        .query_map([from_id], |row| row.get(0))?
+        .collect::<std::result::Result<_, _>>()?;
+
+    tx.execute(
+        "UPDATE OR IGNORE books_tags SET tag_id = ?1 WHERE tag_id = ?2",
+        params![into_id, from_id],
+    )?;
+    // Anything left conflicted with an existing (book, into_id) row
+    tx.execute("DELETE FROM books_tags WHERE tag_id = ?1", [from_id])?;
+    tx.execute("DELETE FROM tags WHERE id = ?1", [from_id])?;
+    library_service::refresh_fts_rows(&tx, &book_ids)?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+// ==================== Auto-tagging rules ====================
+
+/// Fields a tag rule may match on, as `books` columns
+const RULE_TEXT_FIELDS: [&str; 4] = ["publisher", "language", "series", "file_format"];
+
+/// Parse "1990-1999" (or a single year "1995") into an inclusive range
+fn parse_year_range(value: &str) -> Option<(i64, i64)> {
+    let (from, to) = value.split_once('-').unwrap_or((value, value));
+    let from: i64 = from.trim().parse().ok()?;
+    let to: i64 = to.trim().parse().ok()?;
+    (from <= to).then_some((from, to))
+}
+
+/// SQL condition on `books b` for a rule, with its bound values
+fn rule_condition(rule: &TagRule) -> Result<(String, Vec<Value>)> {
+    let field = rule.field.as_str();
+    let value = rule.value.trim();
+    let condition = match (field, rule.operator.as_str()) {
+        ("always", _) => ("1".to_string(), vec![]),
+        (f, "equals") if RULE_TEXT_FIELDS.contains(&f) && !value.is_empty() => (
+            format!("b.{} = ? COLLATE NOCASE", f),
+            vec![Value::Text(value.to_string())],
+        ),
+        (f, "contains") if RULE_TEXT_FIELDS.contains(&f) && !value.is_empty() => (
+            format!("b.{} LIKE ?", f),
+            vec![Value::Text(format!("%{}%", value))],
+        ),
+        (f, "is_not_empty") if RULE_TEXT_FIELDS.contains(&f) => {
+            (format!("COALESCE(b.{}, '') != ''", f), vec![])
+        }
+        ("pubdate_year", "between" | "equals") => {
+            let (from, to) = parse_year_range(value).ok_or_else(|| {
+                ShioriError::Validation(format!("Invalid year range '{}'", rule.value))
+            })?;
+            (
+                "b.pubdate GLOB '[0-9][0-9][0-9][0-9]*'
+                 AND CAST(substr(b.pubdate, 1, 4) AS INTEGER) BETWEEN ? AND ?"
+                    .to_string(),
+                vec![Value::Integer(from), Value::Integer(to)],
+            )
+        }
+        _ => {
+            return Err(ShioriError::Validation(format!(
+                "Unsupported tag rule: {} {} '{}'",
+                rule.field, rule.operator, rule.value
+            )))
+        }
+    };
+    Ok(condition)
+}
+
+pub fn list_tag_rules(db: &Database) -> Result<Vec<TagRule>> {
+    let conn = db.get_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, field, operator, value, tag_name, enabled FROM tag_rules ORDER BY id",
+    )?;
+    let rules = stmt
+        .query_map([], |row| {
+            Ok(TagRule {
+                id: Some(row.get(0)?),
+                field: row.get(1)?,
+                operator: row.get(2)?,
+                value: row.get(3)?,
+                tag_name: row.get(4)?,
+                enabled: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+/// Insert a rule, or update it when `rule.id` is set. Returns its id.
+pub fn set_tag_rule(db: &Database, rule: TagRule) -> Result<i64> {
+    rule_condition(&rule)?;
+    tag_path_segments(&rule.tag_name)?;
+
+    let conn = db.get_connection()?;
+    match rule.id {
+        Some(id) => {
+            let updated = conn.execute(
+                "UPDATE tag_rules SET field = ?1, operator = ?2, value = ?3, tag_name = ?4, enabled = ?5
+                 WHERE id = ?6",
+                params![rule.field, rule.operator, rule.value, rule.tag_name, rule.enabled, id],
+            )?;
+            if updated == 0 {
+                return Err(ShioriError::Validation(format!(
+                    "Tag rule {} not found",
+                    id
+                )));
+            }
+            Ok(id)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO tag_rules (field, operator, value, tag_name, enabled)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    rule.field,
+                    rule.operator,
+                    rule.value,
+                    rule.tag_name,
+                    rule.enabled
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        }
+    }
+}
+
+pub fn delete_tag_rule(db: &Database, id: i64) -> Result<()> {
+    let conn = db.get_connection()?;
+    conn.execute("DELETE FROM tag_rules WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Apply the enabled tag rules to one book. Called after import.
+/// Returns the number of tags added.
+pub fn apply_tag_rules(db: &Database, book_id: i64) -> Result<usize> {
+    run_tag_rules(db, Some(book_id))
+}
+
+/// Apply the enabled tag rules to every book in the library, e.g. after
+/// adding a rule. Returns the number of tags added.
+pub fn apply_tag_rules_to_library(db: &Database) -> Result<usize> {
+    run_tag_rules(db, None)
+}
+
+fn run_tag_rules(db: &Database, book_id: Option<i64>) -> Result<usize> {
+    let rules: Vec<TagRule> = list_tag_rules(db)?
+        .into_iter()
+        .filter(|r| r.enabled)
+        .collect();
+    if rules.is_empty() {
+        return Ok(0);
+    }
+
+    let mut conn = db.get_connection()?;
+    let tx = conn.transaction()?;
+    let mut added = 0;
+    let mut touched = HashSet::new();
+
+    for rule in &rules {
+        let (condition, mut values) = match rule_condition(rule) {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("[TagRules] Skipping rule {:?}: {}", rule.id, e);
+                continue;
+            }
+        };
+        let mut sql = format!(
+            "SELECT b.id FROM books b WHERE b.in_trash = 0 AND ({})",
+            condition
+        );
+        if let Some(id) = book_id {
+            sql.push_str(" AND b.id = ?");
+            values.push(Value::Integer(id));
+        }
+        let matches = tx
+            .prepare(&sql)?
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                row.get::<_, i64>(0)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if matches.is_empty() {
+            continue;
+        }
+
+        let segments = tag_path_segments(&rule.tag_name)?;
+        let tag_id = insert_tag_path(&tx, &segments, None, true)?;
+        for id in matches {
+            if tx.execute(
+                "INSERT OR IGNORE INTO books_tags (book_id, tag_id) VALUES (?1, ?2)",
+                params![id, tag_id],
+            )? > 0
+            {
+                added += 1;
+                touched.insert(id);
+            }
+        }
+    }
+
+    library_service::refresh_fts_rows(&tx, &touched.into_iter().collect::<Vec<_>>())?;
+    tx.commit()?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn add_book(db: &Database, uuid: &str) -> i64 {
+        let conn = db.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO books (title, file_path, file_format, uuid) VALUES (?1, ?2, 'epub', ?1)",
+            params![uuid, format!("/tmp/{}.epub", uuid)],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn tag_id(db: &Database, name: &str) -> i64 {
+        let conn = db.get_connection().unwrap();
+        conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_tags_repoints_books_and_removes_source() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        let scifi = create_tag(&db, "scifi".to_string(), None).unwrap();
+        let sci_fi = create_tag(&db, "sci-fi".to_string(), Some("#123456".to_string())).unwrap();
+
+        let only_old = add_book(&db, "only-old");
+        let both = add_book(&db, "both");
+        let only_new = add_book(&db, "only-new");
+        add_tag_to_book(&db, only_old, scifi).unwrap();
+        add_tag_to_book(&db, both, scifi).unwrap();
+        add_tag_to_book(&db, both, sci_fi).unwrap();
+        add_tag_to_book(&db, only_new, sci_fi).unwrap();
+
+        merge_tags(&db, scifi, sci_fi).unwrap();
+
+        for book in [only_old, both, only_new] {
+            assert_eq!(get_book_tag_ids(&db, book).unwrap(), vec![sci_fi]);
+        }
+        let tags = get_all_tags(&db).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "sci-fi");
+        let conn = db.get_connection().unwrap();
+        let indexed = |query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH ?1",
+                [query],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(indexed("tags:scifi"), 0);
+        assert_eq!(indexed("tags:\"sci fi\""), 3);
+        drop(conn);
+
+        assert!(matches!(
+            merge_tags(&db, scifi, sci_fi),
+            Err(ShioriError::TagNotFound(_))
+        ));
+        assert!(merge_tags(&db, sci_fi, sci_fi).is_err());
+
+        create_tag(&db, "Fiction/Horror".to_string(), None).unwrap();
+        assert!(matches!(
+            merge_tags(&db, tag_id(&db, "Fiction"), sci_fi),
+            Err(ShioriError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_tag_path_creates_and_reuses_parents() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+
+        let cyberpunk = create_tag(
+            &db,
+            "Fiction / Sci-Fi/Cyberpunk".to_string(),
+            Some("#00ff00".to_string()),
+        )
+        .unwrap();
+        let fantasy = create_tag(&db, "Fiction/Fantasy".to_string(), None).unwrap();
+        assert_ne!(cyberpunk, fantasy);
+        assert!(create_tag(&db, "Fiction//Horror".to_string(), None).is_err());
+
+        let names: Vec<String> = get_all_tags(&db)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Fiction",
+                "Fiction/Fantasy",
+                "Fiction/Sci-Fi",
+                "Fiction/Sci-Fi/Cyberpunk"
+            ]
+        );
+
+        let tree = get_tag_tree(&db).unwrap();
+        assert_eq!(tree.len(), 1);
+        let fiction = &tree[0];
+        assert_eq!(fiction.label, "Fiction");
+        let labels: Vec<&str> = fiction.children.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["Fantasy", "Sci-Fi"]);
+        let leaf = &fiction.children[1].children[0];
+        assert_eq!(leaf.id, cyberpunk);
+        assert_eq!(leaf.name, "Fiction/Sci-Fi/Cyberpunk");
+        assert_eq!(leaf.color.as_deref(), Some("#00ff00"));
+        assert!(fiction.color.is_none());
+    }
+
+    #[test]
+    fn test_get_books_by_tag_descendants_and_leaf_search() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        let cyberpunk = create_tag(&db, "Fiction/Sci-Fi/Cyberpunk".to_string(), None).unwrap();
+        create_tag(&db, "Fiction/Fantasy".to_string(), None).unwrap();
+        let fiction = tag_id(&db, "Fiction");
+
+        let general = add_book(&db, "general");
+        let neuromancer = add_book(&db, "neuromancer");
+        add_tag_to_book(&db, general, fiction).unwrap();
+        add_tag_to_book(&db, neuromancer, cyberpunk).unwrap();
+
+        let ids = |books: Vec<Book>| books.into_iter().map(|b| b.id.unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(get_books_by_tag(&db, fiction, false).unwrap()),
+            vec![general]
+        );
+        assert_eq!(
+            ids(get_books_by_tag(&db, fiction, true).unwrap()),
+            vec![general, neuromancer]
+        );
+
+        // A parent_id cycle must not hang the recursive query or the tree
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+                params![cyberpunk, fiction],
+            )
+            .unwrap();
+        }
+        assert_eq!(get_books_by_tag(&db, cyberpunk, true).unwrap().len(), 2);
+        assert_eq!(count_tree(&get_tag_tree(&db).unwrap()), 4);
+
+        // Only the leaf name is indexed, not the ancestors in its path
+        let conn = db.get_connection().unwrap();
+        let indexed: String = conn
+            .query_row(
+                "SELECT tags FROM books_fts WHERE rowid = ?1",
+                [neuromancer],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, "Cyberpunk");
+
+        // A flat tag whose name contains '/' has no parent and stays whole
+        conn.execute("INSERT INTO tags (name) VALUES ('AC/DC')", [])
+            .unwrap();
+        let acdc = conn.last_insert_rowid();
+        drop(conn);
+        add_tag_to_book(&db, general, acdc).unwrap();
+        let conn = db.get_connection().unwrap();
+        let indexed: String = conn
+            .query_row(
+                "SELECT tags FROM books_fts WHERE rowid = ?1",
+                [general],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(indexed.split(' ').any(|t| t == "AC/DC"), "{indexed}");
+    }
+
+    #[test]
+    fn test_decade_rule_tags_matching_books() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        let nineties = add_book(&db, "nineties");
+        let eighties = add_book(&db, "eighties");
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "UPDATE books SET pubdate = '1995-06-01' WHERE id = ?1",
+                [nineties],
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE books SET pubdate = '1984' WHERE id = ?1",
+                [eighties],
+            )
+            .unwrap();
+        }
+
+        let rule = TagRule {
+            id: None,
+            field: "pubdate_year".to_string(),
+            operator: "between".to_string(),
+            value: "1990-1999".to_string(),
+            tag_name: "90s".to_string(),
+            enabled: true,
+        };
+        assert!(set_tag_rule(
+            &db,
+            TagRule {
+                value: "1999-1990".to_string(),
+                ..rule.clone()
+            }
+        )
+        .is_err());
+        set_tag_rule(&db, rule).unwrap();
+        assert_eq!(list_tag_rules(&db).unwrap().len(), 1);
+
+        assert_eq!(apply_tag_rules(&db, eighties).unwrap(), 0);
+        assert_eq!(apply_tag_rules(&db, nineties).unwrap(), 1);
+        // Re-applying is a no-op and does not duplicate the tag
+        assert_eq!(apply_tag_rules_to_library(&db).unwrap(), 0);
+
+        let tag = tag_id(&db, "90s");
+        assert_eq!(get_book_tag_ids(&db, nineties).unwrap(), vec![tag]);
+        assert!(get_book_tag_ids(&db, eighties).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bulk_tagging_updates_junction_and_fts() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        let tag = create_tag(&db, "Shelved".to_string(), None).unwrap();
+        let books: Vec<i64> = (0..50)
+            .map(|i| add_book(&db, &format!("book-{}", i)))
+            .collect();
+
+        assert_eq!(add_tag_to_books(&db, tag, &books).unwrap(), 50);
+        assert_eq!(add_tag_to_books(&db, tag, &books).unwrap(), 0);
+        assert!(matches!(
+            add_tag_to_books(&db, tag + 100, &books),
+            Err(ShioriError::TagNotFound(_))
+        ));
+
+        let conn = db.get_connection().unwrap();
+        let counts = || -> (i64, i64) {
+            let junction = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM books_tags WHERE tag_id = ?1",
+                    [tag],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            let indexed = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM books_fts WHERE books_fts MATCH 'tags:shelved'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            (junction, indexed)
+        };
+        assert_eq!(counts(), (50, 50));
+
+        assert_eq!(remove_tag_from_books(&db, tag, &books[..20]).unwrap(), 20);
+        assert_eq!(counts(), (30, 30));
+    }
+
+    fn count_tree(nodes: &[TagNode]) -> usize {
+        nodes.iter().map(|n| 1 + count_tree(&n.children)).sum()
+    }
+
+    #[test]
+    fn test_auto_assign_colors_fills_only_uncolored() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        create_tag(&db, "Fantasy".to_string(), None).unwrap();
+        create_tag(&db, "Horror".to_string(), Some("#000000".to_string())).unwrap();
+
+        assert_eq!(auto_assign_colors(&db).unwrap(), 1);
+        assert_eq!(auto_assign_colors(&db).unwrap(), 0);
+
+        let tags = get_all_tags(&db).unwrap();
+        let fantasy = tags.iter().find(|t| t.name == "Fantasy").unwrap();
+        assert_eq!(
+            fantasy.color.as_deref(),
+            Some(tag_color("fantasy").as_str())
+        );
+        let horror = tags.iter().find(|t| t.name == "Horror").unwrap();
+        assert_eq!(horror.color.as_deref(), Some("#000000"));
+    }
+
+    #[test]
+    fn test_get_books_by_tags_any_and_all() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("tags.db")).unwrap();
+        let a = add_book(&db, "a");
+        let b = add_book(&db, "b");
+        let c = add_book(&db, "c");
+        let unread = create_tag(&db, "unread".to_string(), None).unwrap();
+        let scifi = create_tag(&db, "sci-fi".to_string(), None).unwrap();
+        let fantasy = create_tag(&db, "fantasy".to_string(), None).unwrap();
+        for (book, tag) in [
+            (a, unread),
+            (a, scifi),
+            (b, scifi),
+            (c, unread),
+            (c, fantasy),
+        ] {
+            add_tag_to_book(&db, book, tag).unwrap();
+        }
+        let ids = |books: Vec<Book>| books.into_iter().filter_map(|b| b.id).collect::<Vec<_>>();
+
+        let any = get_books_by_tags(&db, &[unread, scifi], TagFilterMode::Any, None, 50, 0);
+        assert_eq!(ids(any.unwrap()), vec![a, b, c]);
+        let all = get_books_by_tags(&db, &[unread, scifi], TagFilterMode::All, None, 50, 0);
+        assert_eq!(ids(all.unwrap()), vec![a]);
+        // Repeated ids must not make All unsatisfiable
+        let all = get_books_by_tags(
+            &db,
+            &[scifi, unread, scifi],
+            TagFilterMode::All,
+            None,
+            50,
+            0,
+        );
+        assert_eq!(ids(all.unwrap()), vec![a]);
+        let none = get_books_by_tags(&db, &[scifi, fantasy], TagFilterMode::All, None, 50, 0);
+        assert!(none.unwrap().is_empty());
+
+        let page = get_books_by_tags(&db, &[unread, scifi], TagFilterMode::Any, None, 1, 1);
+        assert_eq!(ids(page.unwrap()), vec![b]);
+
+        db.get_connection()
+            .unwrap()
+            .execute("UPDATE books SET domain = 'manga' WHERE id = ?1", [c])
+            .unwrap();
+        let manga = get_books_by_tags(&db, &[unread], TagFilterMode::Any, Some("manga"), 50, 0);
+        assert_eq!(ids(manga.unwrap()), vec![c]);
+    }
+}
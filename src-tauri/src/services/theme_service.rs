@@ -0,0 +1,263 @@
+//! Built-in and user-defined app themes.
+//!
+//! Built-in themes are styled by the frontend's stylesheets; `black` and
+//! `white` also carry a palette so the window can be painted before the CSS
+//! loads. Custom themes are stored in `custom_themes` as a palette JSON.
+
+use crate::error::{Result, ShioriError};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Theme used when the stored one no longer exists
+pub const DEFAULT_THEME: &str = "black";
+
+/// Theme names the frontend ships stylesheets for
+pub const BUILTIN_THEMES: [&str; 14] = [
+    "black",
+    "white",
+    "light",
+    "dark",
+    "system",
+    "sepia",
+    "gray",
+    "high-contrast",
+    "rose-pine-moon",
+    "catppuccin-mocha",
+    "nord",
+    "dracula",
+    "tokyo-night",
+    "premium-dark",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemePalette {
+    pub background: String,
+    pub text: String,
+    pub accent: String,
+    pub link: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeInfo {
+    pub name: String,
+    pub builtin: bool,
+    /// `None` for built-in themes that are defined only in CSS
+    pub palette: Option<ThemePalette>,
+}
+
+fn builtin_palette(name: &str) -> Option<ThemePalette> {
+    let (background, text, accent, link) = match name {
+        "black" => ("#000000", "#e6e6e6", "#4a9eff", "#6cb4ff"),
+        "white" => ("#ffffff", "#1a1a1a", "#4a9eff", "#1a6fd1"),
+        _ => return None,
+    };
+    Some(ThemePalette {
+        background: background.to_string(),
+        text: text.to_string(),
+        accent: accent.to_string(),
+        link: link.to_string(),
+    })
+}
+
+fn builtin_theme(name: &str) -> ThemeInfo {
+    ThemeInfo {
+        name: name.to_string(),
+        builtin: true,
+        palette: builtin_palette(name),
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn custom_theme(conn: &Connection, name: &str) -> Result<Option<ThemeInfo>> {
+    let palette: Option<String> = conn
+        .query_row(
+            "SELECT palette_json FROM custom_themes WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    palette
+        .map(|json| -> Result<ThemeInfo> {
+            Ok(ThemeInfo {
+                name: name.to_string(),
+                builtin: false,
+                palette: Some(serde_json::from_str(&json)?),
+            })
+        })
+        .transpose()
+}
+
+/// Look up a built-in or custom theme by name
+pub fn get_theme(conn: &Connection, name: &str) -> Result<Option<ThemeInfo>> {
+    if BUILTIN_THEMES.contains(&name) {
+        return Ok(Some(builtin_theme(name)));
+    }
+    custom_theme(conn, name)
+}
+
+/// Built-in themes first, then custom themes by name
+pub fn list_themes(conn: &Connection) -> Result<Vec<ThemeInfo>> {
+    let mut themes: Vec<ThemeInfo> = BUILTIN_THEMES.iter().map(|n| builtin_theme(n)).collect();
+
+    let mut stmt = conn.prepare("SELECT name, palette_json FROM custom_themes ORDER BY name")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (name, json) in rows {
+        match serde_json::from_str(&json) {
+            Ok(palette) => themes.push(ThemeInfo {
+                name,
+                builtin: false,
+                palette: Some(palette),
+            }),
+            Err(e) => log::warn!("[Themes] Skipping custom theme '{}': {}", name, e),
+        }
+    }
+    Ok(themes)
+}
+
+/// Create or replace a custom theme
+pub fn save_custom_theme(conn: &Connection, name: &str, palette: &ThemePalette) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 50 {
+        return Err(ShioriError::Validation(
+            "Theme name must be 1 to 50 characters".to_string(),
+        ));
+    }
+    if BUILTIN_THEMES.contains(&name) {
+        return Err(ShioriError::Validation(format!(
+            "'{}' is a built-in theme",
+            name
+        )));
+    }
+    for (field, color) in [
+        ("background", &palette.background),
+        ("text", &palette.text),
+        ("accent", &palette.accent),
+        ("link", &palette.link),
+    ] {
+        if !is_hex_color(color) {
+            return Err(ShioriError::Validation(format!(
+                "{} must be a hex color like #1a2b3c, got '{}'",
+                field, color
+            )));
+        }
+    }
+
+    let json = serde_json::to_string(palette)?;
+    let updated = conn.execute(
+        "UPDATE custom_themes SET palette_json = ?1, updated_at = CURRENT_TIMESTAMP WHERE name = ?2",
+        params![json, name],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO custom_themes (name, palette_json) VALUES (?1, ?2)",
+            params![name, json],
+        )?;
+    }
+    Ok(())
+}
+
+/// Delete a custom theme. If it was the active theme the app falls back to
+/// [`DEFAULT_THEME`].
+pub fn delete_custom_theme(conn: &Connection, name: &str) -> Result<()> {
+    if BUILTIN_THEMES.contains(&name) {
+        return Err(ShioriError::Validation(format!(
+            "Cannot delete built-in theme '{}'",
+            name
+        )));
+    }
+    conn.execute("DELETE FROM custom_themes WHERE name = ?1", [name])?;
+    conn.execute(
+        "UPDATE user_preferences SET theme = ?1 WHERE id = 1 AND theme = ?2",
+        params![DEFAULT_THEME, name],
+    )?;
+    Ok(())
+}
+
+/// The active theme with its palette, falling back to [`DEFAULT_THEME`] when
+/// the stored name no longer exists
+pub fn resolve_active_theme(conn: &Connection) -> Result<ThemeInfo> {
+    let name: Option<String> = conn
+        .query_row(
+            "SELECT theme FROM user_preferences WHERE id = 1",
+            [],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?
+        .flatten();
+    match name {
+        Some(name) => Ok(get_theme(conn, &name)?.unwrap_or_else(|| builtin_theme(DEFAULT_THEME))),
+        None => Ok(builtin_theme(DEFAULT_THEME)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use tempfile::tempdir;
+
+    fn palette() -> ThemePalette {
+        ThemePalette {
+            background: "#1e1e2e".to_string(),
+            text: "#cdd6f4".to_string(),
+            accent: "#f5c2e7".to_string(),
+            link: "#89b4fa".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_resolve_custom_theme() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("themes.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        save_custom_theme(&conn, "Midnight", &palette()).unwrap();
+        let mut brighter = palette();
+        brighter.text = "#ffffff".to_string();
+        save_custom_theme(&conn, "Midnight", &brighter).unwrap();
+
+        let themes = list_themes(&conn).unwrap();
+        assert_eq!(themes.len(), BUILTIN_THEMES.len() + 1);
+        assert_eq!(themes.last().unwrap().palette, Some(brighter.clone()));
+
+        conn.execute(
+            "UPDATE user_preferences SET theme = 'Midnight' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+        let active = resolve_active_theme(&conn).unwrap();
+        assert_eq!(active.name, "Midnight");
+        assert!(!active.builtin);
+        assert_eq!(active.palette, Some(brighter));
+
+        delete_custom_theme(&conn, "Midnight").unwrap();
+        let active = resolve_active_theme(&conn).unwrap();
+        assert_eq!(active.name, DEFAULT_THEME);
+        assert!(active.palette.is_some());
+    }
+
+    #[test]
+    fn test_custom_theme_validation() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(dir.path().join("themes.db")).unwrap();
+        let conn = db.get_connection().unwrap();
+
+        assert!(save_custom_theme(&conn, "white", &palette()).is_err());
+        assert!(save_custom_theme(&conn, "  ", &palette()).is_err());
+        let mut bad = palette();
+        bad.link = "blue".to_string();
+        assert!(save_custom_theme(&conn, "Bad", &bad).is_err());
+        assert!(delete_custom_theme(&conn, "black").is_err());
+        assert!(get_theme(&conn, "Bad").unwrap().is_none());
+        assert!(get_theme(&conn, "nord").unwrap().unwrap().palette.is_none());
+    }
+}
@@ -49,7 +49,7 @@ pub fn is_supported_format(path: &Path) -> bool {
             let ext_lower = ext_str.to_lowercase();
             return matches!(
                 ext_lower.as_str(),
-                "epub" | "pdf" | "mobi" | "azw" | "azw3" | "txt" | "cbz" | "cbr"
+                "epub" | "pdf" | "mobi" | "azw" | "azw3" | "txt" | "djvu" | "cbz" | "cbr"
             );
         }
     }
@@ -0,0 +1,147 @@
+//! Shared HTTP client for online services (metadata providers, RSS).
+//!
+//! Proxy, timeout and user agent come from the `network_settings` table.
+//! Services fetch the current client per request with [`client`], so
+//! [`apply`] takes effect without recreating them.
+
+use crate::error::{Result, ShioriError};
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const TIMEOUT_RANGE_SECS: RangeInclusive<u64> = 1..=300;
+pub const DEFAULT_USER_AGENT: &str = concat!("Shiori/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSettings {
+    /// `http://` or `https://` proxy for every request. `None` keeps
+    /// reqwest's default of honouring `HTTP(S)_PROXY` from the environment.
+    pub proxy_url: Option<String>,
+    pub timeout_secs: u64,
+    /// Replaces [`DEFAULT_USER_AGENT`]
+    pub user_agent: Option<String>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            user_agent: None,
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+pub fn build_client(settings: &NetworkSettings) -> Result<Client> {
+    if !TIMEOUT_RANGE_SECS.contains(&settings.timeout_secs) {
+        return Err(ShioriError::Validation(format!(
+            "Network timeout must be between {} and {} seconds, got {}",
+            TIMEOUT_RANGE_SECS.start(),
+            TIMEOUT_RANGE_SECS.end(),
+            settings.timeout_secs
+        )));
+    }
+
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(settings.timeout_secs))
+        .user_agent(non_empty(&settings.user_agent).unwrap_or(DEFAULT_USER_AGENT));
+
+    if let Some(proxy_url) = non_empty(&settings.proxy_url) {
+        let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| {
+            ShioriError::Validation(format!("Invalid proxy URL '{}': {}", proxy_url, e))
+        })?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder
+        .build()
+        .map_err(|e| ShioriError::Other(format!("Failed to create HTTP client: {}", e)))
+}
+
+static CLIENT: Lazy<RwLock<Client>> = Lazy::new(|| {
+    RwLock::new(build_client(&NetworkSettings::default()).unwrap_or_else(|_| Client::new()))
+});
+
+/// The client built from the current network settings. Cheap to call:
+/// clones share one connection pool.
+pub fn client() -> Client {
+    CLIENT.read().unwrap().clone()
+}
+
+/// Rebuild the shared client. On error the previous client stays in use.
+pub fn apply(settings: &NetworkSettings) -> Result<()> {
+    let client = build_client(settings)?;
+    *CLIENT.write().unwrap() = client;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_builder_applies_configured_timeout() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                open.push(socket);
+            }
+        });
+
+        let client = build_client(&NetworkSettings {
+            timeout_secs: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let started = Instant::now();
+        let err = client
+            .get(format!("http://{}/", addr))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let zero_timeout = NetworkSettings {
+            timeout_secs: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_client(&zero_timeout),
+            Err(ShioriError::Validation(_))
+        ));
+
+        let bad_proxy = NetworkSettings {
+            proxy_url: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_client(&bad_proxy),
+            Err(ShioriError::Validation(_))
+        ));
+
+        let blank = NetworkSettings {
+            proxy_url: Some("  ".to_string()),
+            user_agent: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(build_client(&blank).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+//! ISBN parsing, checksum validation and ISBN-10 ⇄ ISBN-13 conversion.
+//!
+//! Input may contain hyphens, spaces or an `ISBN` prefix; only digits and a
+//! trailing `X` check digit are significant.
+
+/// Both forms of a validated ISBN. `isbn10` is `None` for 979-prefixed
+/// ISBN-13s, which have no ISBN-10 equivalent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedIsbn {
+    pub isbn10: Option<String>,
+    pub isbn13: String,
+}
+
+/// Parse `raw` as an ISBN-10 or ISBN-13, returning `None` when it has the
+/// wrong length or a bad check digit.
+pub fn normalize(raw: &str) -> Option<NormalizedIsbn> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x')
+        .collect::<String>()
+        .to_uppercase();
+
+    match cleaned.len() {
+        10 => {
+            let body = &cleaned[..9];
+            if !body.chars().all(|c| c.is_ascii_digit()) || cleaned[9..] != isbn10_check_digit(body)
+            {
+                return None;
+            }
+            let isbn13_body = format!("978{}", body);
+            Some(NormalizedIsbn {
+                isbn10: Some(cleaned.clone()),
+                isbn13: format!("{}{}", isbn13_body, isbn13_check_digit(&isbn13_body)),
+            })
+        }
+        13 => {
+            if !cleaned.chars().all(|c| c.is_ascii_digit())
+                || cleaned[12..] != isbn13_check_digit(&cleaned[..12])
+            {
+                return None;
+            }
+            let isbn10 = cleaned.strip_prefix("978").map(|rest| {
+                let body = &rest[..9];
+                format!("{}{}", body, isbn10_check_digit(body))
+            });
+            Some(NormalizedIsbn {
+                isbn10,
+                isbn13: cleaned,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Check digit for the first nine digits of an ISBN-10 (`0`-`9` or `X`)
+fn isbn10_check_digit(body: &str) -> String {
+    let sum: u32 = body
+        .chars()
+        .zip((2..=10).rev())
+        .map(|(c, weight)| c.to_digit(10).unwrap_or(0) * weight)
+        .sum();
+    match (11 - sum % 11) % 11 {
+        10 => "X".to_string(),
+        check => check.to_string(),
+    }
+}
+
+/// Check digit for the first twelve digits of an ISBN-13
+fn isbn13_check_digit(body: &str) -> String {
+    let sum: u32 = body
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let d = c.to_digit(10).unwrap_or(0);
+            if i % 2 == 0 {
+                d
+            } else {
+                d * 3
+            }
+        })
+        .sum();
+    ((10 - sum % 10) % 10).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isbn10_and_isbn13_convert_both_ways() {
+        let expected = NormalizedIsbn {
+            isbn10: Some("0261103342".to_string()),
+            isbn13: "9780261103344".to_string(),
+        };
+        assert_eq!(normalize("0-261-10334-2"), Some(expected.clone()));
+        assert_eq!(normalize("ISBN 978-0-261-10334-4"), Some(expected));
+
+        assert_eq!(
+            normalize("080442957x").unwrap().isbn10.as_deref(),
+            Some("080442957X")
+        );
+        assert_eq!(
+            normalize("9780804429573").unwrap().isbn10.as_deref(),
+            Some("080442957X")
+        );
+        // 979 ISBNs have no ISBN-10 form
+        assert_eq!(normalize("979-10-90636-07-1").unwrap().isbn10, None);
+    }
+
+    #[test]
+    fn test_invalid_checksums_are_rejected() {
+        assert_eq!(normalize("0-261-10334-3"), None);
+        assert_eq!(normalize("978-0-261-10334-5"), None);
+        assert_eq!(normalize("12345"), None);
+        assert_eq!(normalize("X261103342"), None);
+    }
+}
@@ -0,0 +1,51 @@
+//! Language detection for imported books, over a sample of their text.
+//!
+//! Codes are ISO 639-3 (`eng`, `fra`, `deu`, …), as whatlang reports them
+//! and as the `books.language` column defaults to.
+
+/// Code used when the text is too short or too mixed to call
+pub const DEFAULT_LANGUAGE: &str = "eng";
+
+/// Bytes of text worth sampling; more rarely changes the answer
+pub const SAMPLE_BYTES: usize = 4096;
+
+/// Below this whatlang confidence (0–1) the guess is discarded
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// Detect the language of `text`, or `None` when the detector is unsure
+pub fn detect(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() || info.confidence() < MIN_CONFIDENCE {
+        return None;
+    }
+    Some(info.lang().code())
+}
+
+/// `detect`, falling back to `DEFAULT_LANGUAGE`
+pub fn detect_or_default(text: &str) -> String {
+    detect(text).unwrap_or(DEFAULT_LANGUAGE).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_english_and_french() {
+        let english = "It was the best of times, it was the worst of times, it was the age \
+                       of wisdom, it was the age of foolishness, it was the epoch of belief.";
+        let french = "Longtemps, je me suis couché de bonne heure. Parfois, à peine ma bougie \
+                      éteinte, mes yeux se fermaient si vite que je n'avais pas le temps de \
+                      me dire : je m'endors.";
+
+        assert_eq!(detect(english), Some("eng"));
+        assert_eq!(detect(french), Some("fra"));
+        assert_eq!(detect_or_default(french), "fra");
+    }
+
+    #[test]
+    fn test_low_confidence_defaults_to_english() {
+        assert_eq!(detect_or_default(""), "eng");
+        assert_eq!(detect_or_default("42 — 17"), "eng");
+    }
+}
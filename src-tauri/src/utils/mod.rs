@@ -1,2 +1,7 @@
 pub mod file;
+pub mod http;
+pub mod isbn;
+pub mod lang_detect;
+pub mod natural_sort;
+pub mod secrets;
 pub mod validate;
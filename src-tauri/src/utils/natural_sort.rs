@@ -0,0 +1,105 @@
+//! Natural ordering for file names, shared by every comic archive reader.
+//!
+//! Digit runs compare by numeric value, so `page9.jpg` sorts before
+//! `page10.jpg` and `007.png` equals `7.png` up to the final tie-break.
+//! Everything else compares case-insensitively, character by character.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Compare two names in natural order. Names that only differ in case or
+/// zero padding fall back to a plain byte comparison so the order is total.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let mut left = a.chars().peekable();
+    let mut right = b.chars().peekable();
+
+    loop {
+        let ord = match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                compare_numbers(&take_digits(&mut left), &take_digits(&mut right))
+            }
+            (Some(x), Some(y)) => {
+                left.next();
+                right.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// Compare digit strings by value without parsing, so long runs can't overflow
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        names.sort_by(|a, b| compare(a, b));
+        names
+    }
+
+    #[test]
+    fn test_numbers_compare_by_value_with_or_without_padding() {
+        assert_eq!(
+            sorted(&["page10.jpg", "page9.jpg", "page1.jpg"]),
+            vec!["page1.jpg", "page9.jpg", "page10.jpg"]
+        );
+        assert_eq!(
+            sorted(&["page010.jpg", "page9.jpg", "page002.jpg"]),
+            vec!["page002.jpg", "page9.jpg", "page010.jpg"]
+        );
+        assert_eq!(compare("7.png", "007.png"), Ordering::Greater);
+        assert_eq!(
+            compare(
+                "99999999999999999999999.png",
+                "100000000000000000000000.png"
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_mixed_prefixes_and_cover_pages() {
+        assert_eq!(
+            sorted(&["002.png", "001_cover.png", "010.png", "003.png"]),
+            vec!["001_cover.png", "002.png", "003.png", "010.png"]
+        );
+        assert_eq!(
+            sorted(&[
+                "b2.jpg",
+                "A10.jpg",
+                "a2.jpg",
+                "Chapter 10/1.jpg",
+                "Chapter 2/1.jpg"
+            ]),
+            vec![
+                "a2.jpg",
+                "A10.jpg",
+                "b2.jpg",
+                "Chapter 2/1.jpg",
+                "Chapter 10/1.jpg"
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Passwords kept in the OS credential store (Keychain, Credential Manager or
+//! the Secret Service) rather than in the database.
+//!
+//! Mobile builds have no credential store; `AVAILABLE` is false there and
+//! callers keep the secret in the app's private database instead. Tests use
+//! an in-memory store so they never touch the user's keyring.
+
+use crate::error::Result;
+
+/// Service name the entries are filed under
+#[cfg(not(any(test, target_os = "android", target_os = "ios")))]
+const SERVICE: &str = "io.github.vinayydv3695.shiori";
+
+/// Whether this build has a credential store to use
+pub const AVAILABLE: bool = !cfg!(any(target_os = "android", target_os = "ios")) || cfg!(test);
+
+/// Read the secret stored under `name`
+#[cfg(not(any(test, target_os = "android", target_os = "ios")))]
+pub fn get(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+/// Store `secret` under `name`; `None` removes it
+#[cfg(not(any(test, target_os = "android", target_os = "ios")))]
+pub fn set(name: &str, secret: Option<&str>) -> Result<()> {
+    let entry = entry(name)?;
+    match secret {
+        Some(secret) => entry.set_password(secret).map_err(keyring_error),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keyring_error(e)),
+        },
+    }
+}
+
+#[cfg(not(any(test, target_os = "android", target_os = "ios")))]
+fn entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, name).map_err(keyring_error)
+}
+
+#[cfg(not(any(test, target_os = "android", target_os = "ios")))]
+fn keyring_error(e: keyring::Error) -> crate::error::ShioriError {
+    crate::error::ShioriError::Other(format!("Credential store error: {}", e))
+}
+
+#[cfg(test)]
+static TEST_STORE: std::sync::Mutex<Option<std::collections::HashMap<String, String>>> =
+    std::sync::Mutex::new(None);
+
+#[cfg(test)]
+pub fn get(name: &str) -> Result<Option<String>> {
+    let store = TEST_STORE.lock().unwrap();
+    Ok(store.as_ref().and_then(|store| store.get(name).cloned()))
+}
+
+#[cfg(test)]
+pub fn set(name: &str, secret: Option<&str>) -> Result<()> {
+    let mut store = TEST_STORE.lock().unwrap();
+    let store = store.get_or_insert_with(Default::default);
+    match secret {
+        Some(secret) => store.insert(name.to_string(), secret.to_string()),
+        None => store.remove(name),
+    };
+    Ok(())
+}
+
+#[cfg(all(not(test), any(target_os = "android", target_os = "ios")))]
+pub fn get(_name: &str) -> Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(all(not(test), any(target_os = "android", target_os = "ios")))]
+pub fn set(_name: &str, _secret: Option<&str>) -> Result<()> {
+    Err(crate::error::ShioriError::UnsupportedFeature(
+        "No credential store on this platform".to_string(),
+    ))
+}
@@ -107,6 +107,34 @@ pub fn require_valid_url_with_options(url: &str, field: &str, allow_private: boo
     Ok(())
 }
 
+/// Validate the basic shape of an email address: `local@domain.tld`,
+/// without whitespace or angle brackets.
+pub fn require_valid_email(address: &str, field: &str) -> Result<()> {
+    require_non_empty(address, field)?;
+    require_max_length(address, 254, field)?;
+
+    let valid = match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !address
+                    .chars()
+                    .any(|c| c.is_whitespace() || c == '<' || c == '>')
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(ShioriError::Validation(format!(
+            "{} is not a valid email address: '{}'",
+            field, address
+        )));
+    }
+    Ok(())
+}
+
 /// Validate a domain string against a list of allowed values.
 pub fn require_one_of(value: &str, allowed: &[&str], field: &str) -> Result<()> {
     if !allowed.contains(&value) {
@@ -118,6 +146,17 @@ pub fn require_one_of(value: &str, allowed: &[&str], field: &str) -> Result<()>
     Ok(())
 }
 
+/// Validate that a number lies within `min..=max`.
+pub fn require_in_range(value: i64, min: i64, max: i64, field: &str) -> Result<()> {
+    if value < min || value > max {
+        return Err(ShioriError::Validation(format!(
+            "{} must be between {} and {}, got {}",
+            field, min, max, value
+        )));
+    }
+    Ok(())
+}
+
 /// Validate that a vector is not empty.
 pub fn require_non_empty_vec<T>(items: &[T], field: &str) -> Result<()> {
     if items.is_empty() {
@@ -157,6 +196,14 @@ mod tests {
         assert!(require_max_length("", 5, "field").is_ok());
     }
 
+    #[test]
+    fn test_require_in_range() {
+        assert!(require_in_range(1, 1, 8, "field").is_ok());
+        assert!(require_in_range(8, 1, 8, "field").is_ok());
+        assert!(require_in_range(0, 1, 8, "field").is_err());
+        assert!(require_in_range(9, 1, 8, "field").is_err());
+    }
+
     #[test]
     fn test_require_safe_path() {
         assert!(require_safe_path("normal/path/file.txt", "field").is_ok());
@@ -209,6 +256,17 @@ mod tests {
         assert!(require_one_of("", &allowed, "field").is_err());
     }
 
+    #[test]
+    fn test_require_valid_email() {
+        assert!(require_valid_email("reader@kindle.com", "email").is_ok());
+        assert!(require_valid_email("first.last+tag@mail.example.org", "email").is_ok());
+        assert!(require_valid_email("", "email").is_err());
+        assert!(require_valid_email("no-at-sign.com", "email").is_err());
+        assert!(require_valid_email("two@@kindle.com", "email").is_err());
+        assert!(require_valid_email("reader@localhost", "email").is_err());
+        assert!(require_valid_email("Reader <reader@kindle.com>", "email").is_err());
+    }
+
     #[test]
     fn test_require_non_empty_vec() {
         let empty: Vec<i32> = vec![];
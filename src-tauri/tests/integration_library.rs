@@ -45,7 +45,8 @@ fn test_library_scan_and_search_flow() {
     assert_eq!(import_result.success.len(), 1, "Should import 1 manga series");
 
     // 2. Verify imported successfully
-    let books = library_service::get_all_books(&db, 10, 0).expect("Failed to get books");
+    let books =
+        library_service::get_all_books(&db, 10, 0, None, None).expect("Failed to get books");
     assert_eq!(books.len(), 1);
     let book = &books[0];
     
@@ -68,7 +69,8 @@ fn test_library_scan_and_search_flow() {
     library_service::delete_book(&db, book.id.unwrap()).expect("Failed to move book to trash");
     library_service::permanent_delete_book(&db, book.id.unwrap()).expect("Failed to delete book");
 
-    let final_books = library_service::get_all_books(&db, 10, 0).expect("Failed to get books");
+    let final_books =
+        library_service::get_all_books(&db, 10, 0, None, None).expect("Failed to get books");
     assert_eq!(final_books.len(), 0, "Book should be deleted");
 
     // Cleanup
@@ -1,14 +1,15 @@
 import { useState, useEffect } from 'react';
 import * as Dialog from '@radix-ui/react-dialog';
-import { X, BookOpen, Calendar, FileText, Tag, Star, Globe, Hash, Download, Loader2, BookmarkCheck } from 'lucide-react';
+import { X, BookOpen, Calendar, FileText, Tag, Star, Globe, Hash, Download, Loader2, BookmarkCheck, ImagePlus, Link, Mail } from 'lucide-react';
 import { convertFileSrc } from '@tauri-apps/api/core';
+import { open as openDialog } from '@tauri-apps/plugin-dialog';
 
 function resolveCoverSrc(path: string): string {
   if (path.startsWith('http://') || path.startsWith('https://')) return path;
   return convertFileSrc(path.replace(/\\/g, '/'));
 }
 import { listen } from '@tauri-apps/api/event';
-import { api, type Book } from '../../lib/tauri';
+import { api, type Book, type CoverSource } from '../../lib/tauri';
 import { logger } from '@/lib/logger';
 import { useToast } from '../../store/toastStore';
 import { Button } from '../ui/button';
@@ -37,6 +38,7 @@ export const BookDetailsDialog = ({
   const [loading, setLoading] = useState(true);
   const [metadataDialogOpen, setMetadataDialogOpen] = useState(false);
   const [autoEnrichLoading, setAutoEnrichLoading] = useState(false);
+  const [sendingEmail, setSendingEmail] = useState(false);
   const [readingStatus, setReadingStatus] = useState(book?.reading_status || 'planning');
   const toast = useToast();
 
@@ -48,8 +50,14 @@ export const BookDetailsDialog = ({
 
   useEffect(() => {
     let unlisten: (() => void) | undefined;
+    let unlistenCancelled: (() => void) | undefined;
 
     const setupListener = async () => {
+      unlistenCancelled = await listen<{ bookId: number }>("metadata:cancelled", (event) => {
+        if (event.payload.bookId === bookId) {
+          setAutoEnrichLoading(false);
+        }
+      });
       unlisten = await listen<{ bookId: number; status: string; provider?: string; error?: string }>("metadata-update", (event) => {
         const payload = event.payload;
         if (payload.bookId === bookId) {
@@ -76,6 +84,7 @@ export const BookDetailsDialog = ({
 
     return () => {
       if (unlisten) unlisten();
+      if (unlistenCancelled) unlistenCancelled();
     };
     // loadBook is defined below and recreated each render - would cause infinite loop if added
     // eslint-disable-next-line react-hooks/exhaustive-deps
@@ -106,6 +115,51 @@ export const BookDetailsDialog = ({
   const isManga = book?.file_format.toLowerCase() === 'cbz' || book?.file_format.toLowerCase() === 'cbr';
 
 
+  const handleSetCover = async (source: CoverSource) => {
+    try {
+      await api.setCustomCover(bookId, source);
+      toast.success("Cover Updated");
+      await loadBook();
+    } catch (e) {
+      logger.error("Failed to set cover:", e);
+      toast.error("Cover Not Updated", String(e));
+    }
+  };
+
+  const handleSendToEmail = async () => {
+    const saved = localStorage.getItem('shiori-send-to-email') ?? '';
+    const address = window.prompt("Send this book to (e.g. your Kindle address)", saved)?.trim();
+    if (!address) return;
+    localStorage.setItem('shiori-send-to-email', address);
+    try {
+      setSendingEmail(true);
+      const sent = await api.sendBookToEmail(bookId, address);
+      toast.success("Book Sent", `${sent.fileName} was emailed to ${sent.toAddress}`);
+    } catch (e) {
+      logger.error("Failed to email book:", e);
+      toast.error("Book Not Sent", String(e));
+    } finally {
+      setSendingEmail(false);
+    }
+  };
+
+  const handleCoverFromFile = async () => {
+    const path = await openDialog({
+      multiple: false,
+      filters: [{ name: "Images", extensions: ["png", "jpg", "jpeg", "webp", "gif", "bmp"] }],
+    });
+    if (typeof path === "string") {
+      await handleSetCover({ type: "file", path });
+    }
+  };
+
+  const handleCoverFromUrl = async () => {
+    const url = window.prompt("Image URL for the new cover")?.trim();
+    if (url) {
+      await handleSetCover({ type: "url", url });
+    }
+  };
+
   const handleMetadataFetched = async () => {
     // Reload book data after metadata update
     await loadBook();
@@ -176,6 +230,22 @@ export const BookDetailsDialog = ({
                     </div>
                   )}
                 </div>
+                <div className="flex gap-2 justify-center mt-3">
+                  <Button variant="outline" size="sm" onClick={handleCoverFromFile} className="gap-1.5">
+                    <ImagePlus className="w-4 h-4" />
+                    Cover from File
+                  </Button>
+                  <Button variant="outline" size="sm" onClick={handleCoverFromUrl} className="gap-1.5">
+                    <Link className="w-4 h-4" />
+                    From URL
+                  </Button>
+                </div>
+                <div className="flex justify-center mt-2">
+                  <Button variant="outline" size="sm" onClick={handleSendToEmail} disabled={sendingEmail} className="gap-1.5">
+                    {sendingEmail ? <Loader2 className="w-4 h-4 animate-spin" /> : <Mail className="w-4 h-4" />}
+                    Send to Email
+                  </Button>
+                </div>
               </div>
 
               {/* Book Information */}
@@ -374,8 +444,15 @@ export const BookDetailsDialog = ({
               <Button
                 variant="secondary"
                 className="w-full"
-                disabled={autoEnrichLoading}
                 onClick={async () => {
+                  if (autoEnrichLoading) {
+                    try {
+                      await api.cancelMetadataJob(bookId);
+                    } catch (e) {
+                      logger.error("Cancel enrich failed:", e);
+                    }
+                    return;
+                  }
                   try {
                     setAutoEnrichLoading(true);
                     await api.enrichBookMetadata(bookId);
@@ -389,7 +466,7 @@ export const BookDetailsDialog = ({
                 {autoEnrichLoading ? (
                   <>
                     <Loader2 className="w-4 h-4 md:mr-2 animate-spin" />
-                    <span className="hidden md:inline">Enriching...</span>
+                    <span className="hidden md:inline">Cancel Enriching</span>
                   </>
                 ) : (
                   "Auto-Enrich"
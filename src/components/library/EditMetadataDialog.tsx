@@ -32,6 +32,7 @@ export const EditMetadataDialog = ({ open, onOpenChange, bookId }: EditMetadataD
     notes: '',
   });
   const [lockedFields, setLockedFields] = useState<Record<string, boolean>>({});
+  const [writeToFile, setWriteToFile] = useState(false);
   const toast = useToast();
   const setBooks = useLibraryStore(state => state.setBooks);
 
@@ -103,7 +104,8 @@ export const EditMetadataDialog = ({ open, onOpenChange, bookId }: EditMetadataD
         metadata_locked: lockedFields,
       };
 
-      await api.updateBook(updatedBook);
+      const isEpub = book.file_format.toLowerCase() === 'epub';
+      await api.updateBook(updatedBook, isEpub && writeToFile);
       
       const books = await api.getBooks();
       setBooks(books);
@@ -361,6 +363,17 @@ export const EditMetadataDialog = ({ open, onOpenChange, bookId }: EditMetadataD
 
           {/* Footer */}
           <div className="flex items-center justify-end gap-3 p-6 border-t border-border bg-muted/30">
+            {book?.file_format.toLowerCase() === 'epub' && (
+              <label className="mr-auto flex items-center gap-2 text-sm text-muted-foreground">
+                <input
+                  type="checkbox"
+                  checked={writeToFile}
+                  onChange={(e) => setWriteToFile(e.target.checked)}
+                  disabled={saving}
+                />
+                Also write to EPUB file
+              </label>
+            )}
             <Dialog.Close asChild>
               <Button variant="outline" disabled={saving}>
                 Cancel
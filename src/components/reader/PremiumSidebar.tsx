@@ -3,7 +3,7 @@ import { AnimatePresence, motion } from 'framer-motion';
 import { useReaderUIStore } from '@/store/premiumReaderStore';
 import { api } from '@/lib/tauri';
 import { logger } from '@/lib/logger';
-import type { TocEntry, Annotation, BookSearchResult, AnnotationCategory } from '@/lib/tauri';
+import type { TocEntry, Annotation, BookSearchResult, AnnotationCategory, ColorCount } from '@/lib/tauri';
 import { X, BookOpen, Highlighter, FileText, Search, Loader2, Trash2, Edit2, Download } from '@/components/icons';
 import DOMPurify from 'dompurify';
 import { useToastStore } from '@/store/toastStore';
@@ -62,6 +62,9 @@ export function PremiumSidebar({ bookId, currentIndex, onNavigate }: PremiumSide
   const [toc, setToc] = useState<TocEntry[]>([]);
   const [annotations, setAnnotations] = useState<Annotation[]>([]);
   const [categories, setCategories] = useState<AnnotationCategory[]>([]);
+  const [colorCounts, setColorCounts] = useState<ColorCount[]>([]);
+  const [colorFilter, setColorFilter] = useState<string | null>(null);
+  const [colorFiltered, setColorFiltered] = useState<Annotation[]>([]);
   const [searchQuery, setSearchQuery] = useState('');
   const [searchResults, setSearchResults] = useState<BookSearchResult[]>([]);
   const [isSearching, setIsSearching] = useState(false);
@@ -123,6 +126,7 @@ export function PremiumSidebar({ bookId, currentIndex, onNavigate }: PremiumSide
       try {
         const annotationsData = await api.getAnnotations(bookId);
         setAnnotations(annotationsData);
+        setColorCounts(await api.getColorDistribution(bookId));
       } catch (err) {
         logger.error('[PremiumSidebar] Failed to load annotations:', err);
       }
@@ -415,8 +419,16 @@ export function PremiumSidebar({ bookId, currentIndex, onNavigate }: PremiumSide
     }
   }, []);
   
+  // Refresh the color-filtered list whenever the filter or annotations change
+  useEffect(() => {
+    if (!colorFilter) return;
+    api.getAnnotationsByColor(bookId, colorFilter)
+      .then(setColorFiltered)
+      .catch((err) => logger.error('[PremiumSidebar] Failed to filter by color:', err));
+  }, [bookId, colorFilter, annotations]);
+
   // Filter annotations by type
-  const highlights = annotations.filter(a => a.annotationType === 'highlight');
+  const highlights = (colorFilter ? colorFiltered : annotations).filter(a => a.annotationType === 'highlight');
   const notes = annotations.filter(a => a.annotationType === 'note');
 
   /** Format a raw location string for display */
@@ -556,8 +568,23 @@ export function PremiumSidebar({ bookId, currentIndex, onNavigate }: PremiumSide
                   </button>
                 )}
               </div>
+              {colorCounts.length > 1 && (
+                <div className="flex flex-wrap items-center gap-2 mb-4">
+                  {colorCounts.map((c) => (
+                    <button
+                      key={c.color}
+                      onClick={() => setColorFilter(colorFilter === c.color ? null : c.color)}
+                      className={`flex items-center gap-1.5 px-2 py-1 text-xs rounded-full border transition-opacity ${colorFilter === c.color ? 'border-zinc-500' : 'border-transparent opacity-70 hover:opacity-100'}`}
+                      title={colorFilter === c.color ? 'Show all colors' : 'Show only this color'}
+                    >
+                      <span className="w-3 h-3 rounded-full" style={{ backgroundColor: c.color }} />
+                      {c.count}
+                    </button>
+                  ))}
+                </div>
+              )}
               {highlights.length === 0 ? (
-                <p className="premium-sidebar-empty">No highlights yet</p>
+                <p className="premium-sidebar-empty">{colorFilter ? 'No highlights in this color' : 'No highlights yet'}</p>
               ) : (
                 <motion.div 
                   className="premium-annotations-list"
@@ -6,7 +6,7 @@ import { Settings, Columns, ChevronDown, ChevronUp } from '@/components/icons';
 import { READING_FONTS, normalizeLegacyFontPreference } from '@/lib/readingFonts';
 import { isAndroid } from '@/lib/tauri';
 
-export type ReaderFormat = 'epub' | 'pdf' | 'mobi' | 'azw' | 'azw3' | 'manga' | 'fb2' | 'docx' | 'html' | 'htm' | 'txt' | 'md' | 'markdown';
+export type ReaderFormat = 'epub' | 'pdf' | 'mobi' | 'azw' | 'azw3' | 'manga' | 'fb2' | 'docx' | 'html' | 'htm' | 'txt' | 'md' | 'markdown' | 'djvu';
 
 interface ReaderSettingsProps {
   /** Current book format — controls which settings are visible */
@@ -22,7 +22,7 @@ import type {
 } from '../../types/preferences'
 import { DEFAULT_USER_PREFERENCES, DEFAULT_BOOK_PREFERENCES, DEFAULT_MANGA_PREFERENCES } from '../../types/preferences'
 import { api, isTauri, isAndroid } from '../../lib/tauri'
-import type { BackupInfo, CacheStats } from '../../lib/tauri'
+import type { ApiSettings, BackupInfo, CacheStats, MetadataCacheStats, NetworkSettings, OpdsSettings, SmtpSettings } from '../../lib/tauri'
 import { TTSEngine } from '@/lib/ttsEngine'
 import { open as openDialog } from '@tauri-apps/plugin-dialog'
 import { useToast } from '../../store/toastStore'
@@ -105,6 +105,17 @@ const ALL_SETTINGS: SettingDefinition[] = [
   { label: 'Progress Bar', description: 'Progress bar position', tab: 'manga-reading', section: 'Display Options' },
   { label: 'Preload Pages', description: 'Number of pages to preload', tab: 'manga-reading', section: 'Performance' },
   { label: 'Margin Size', description: 'Page margin in pixels', tab: 'manga-reading', section: 'Performance' },
+  { label: 'Proxy URL', description: 'HTTP or HTTPS proxy for online requests', tab: 'advanced', section: 'Network' },
+  { label: 'Request Timeout', description: 'Seconds before an online request gives up', tab: 'advanced', section: 'Network' },
+  { label: 'User Agent', description: 'User agent sent with online requests', tab: 'advanced', section: 'Network' },
+  { label: 'SMTP Server', description: 'Mail server used to email books', tab: 'advanced', section: 'Email' },
+  { label: 'SMTP Account', description: 'Username and password for the mail server', tab: 'advanced', section: 'Email' },
+  { label: 'Sender Address', description: 'From address for emailed books', tab: 'advanced', section: 'Email' },
+  { label: 'Attachment Limit', description: 'Largest book that will be emailed', tab: 'advanced', section: 'Email' },
+  { label: 'OPDS Catalog', description: 'Browse and download the library from e-reader apps', tab: 'advanced', section: 'OPDS' },
+  { label: 'OPDS Login', description: 'Optional username and password for the catalog', tab: 'advanced', section: 'OPDS' },
+  { label: 'Local API', description: 'JSON API for scripts and community integrations', tab: 'advanced', section: 'Local API' },
+  { label: 'API Token', description: 'Bearer token required by every API request', tab: 'advanced', section: 'Local API' },
   { label: 'Export Database', description: 'Export library data', tab: 'advanced', section: 'Database' },
   { label: 'Import Database', description: 'Import library data', tab: 'advanced', section: 'Database' },
   { label: 'Clean Up Database', description: 'Remove orphaned records and unused covers', tab: 'advanced', section: 'Database' },
@@ -112,6 +123,7 @@ const ALL_SETTINGS: SettingDefinition[] = [
   { label: 'Reset Onboarding', description: 'Re-show welcome screens', tab: 'advanced', section: 'Database' },
   { label: 'Enable Logging', description: 'Save debug logs', tab: 'advanced', section: 'Debug' },
   { label: 'Renderer Cache', description: 'Temporary reading resources', tab: 'advanced', section: 'Cache' },
+  { label: 'Metadata Cache', description: 'Cached online metadata lookups', tab: 'advanced', section: 'Cache' },
   { label: 'Max Cache Size', description: 'Maximum cache storage limit', tab: 'advanced', section: 'Cache' },
   { label: 'Cache Clear Policy', description: 'When to auto-clear cache', tab: 'advanced', section: 'Cache' },
   { label: 'Backup', description: 'Create library backup', tab: 'advanced', section: 'Backup & Restore' },
@@ -121,6 +133,7 @@ const ALL_SETTINGS: SettingDefinition[] = [
   { label: 'Reading History Retention', description: 'How long to keep reading history', tab: 'advanced', section: 'Privacy' },
   { label: 'Clear Reading History', description: 'Delete all reading history', tab: 'advanced', section: 'Privacy' },
   { label: 'AniList Token', description: 'API Token for AniList two-way sync', tab: 'general', section: 'Integrations' },
+  { label: 'Comic Vine API Key', description: 'Comic and issue metadata for CBZ/CBR files', tab: 'general', section: 'Integrations' },
 ]
 
 const EPUB_RESUME_CHOICE_STORAGE_KEY = 'shiori-epub-resume-choice:v1'
@@ -507,6 +520,18 @@ const GeneralSettings = ({
     // Actually we can just remove the whole useEffect since it only did AniList stuff.
   }, []);
 
+  const handleProgressRateChange = async (rate: number) => {
+    const previous = progressRate
+    setProgressRate(rate)
+    try {
+      await api.setConversionProgressRate(rate)
+    } catch (err) {
+      logger.error('Failed to set conversion progress rate:', err)
+      setProgressRate(previous)
+      toast.error('Failed to update progress updates')
+    }
+  }
+
   if (!preferences) return null
 
   const currentScale = preferences.uiScale ?? 1.0
@@ -811,13 +836,29 @@ const GeneralSettings = ({
         </div>
       </SettingSection>
 
-      {isSectionVisible('Integrations', ['AniList Token']) && (
+      {isSectionVisible('Integrations', ['AniList Token', 'Comic Vine API Key']) && (
         <SettingSection title="Integrations" description="Connect to third-party services">
           {isSettingVisible('AniList Token', 'API Token for AniList two-way sync', 'Integrations') && (
             <SettingItem label="AniList API Token" description="Token for two-way sync with AniList.">
               <AniListSettings />
             </SettingItem>
           )}
+          {isSettingVisible('Comic Vine API Key', 'Comic and issue metadata for CBZ/CBR files', 'Integrations') && (
+            <SettingItem label="Comic Vine API Key" description="Fetch comic and issue metadata for CBZ/CBR files from Comic Vine (Requires restart)">
+              <Input
+                type="password"
+                placeholder="Not set"
+                defaultValue={preferences.comicvineApiKey ?? ''}
+                onBlur={(e) => {
+                  const key = e.target.value.trim()
+                  if (key !== (preferences.comicvineApiKey ?? '')) {
+                    updateGeneralSettings({ comicvineApiKey: key || null })
+                  }
+                }}
+                className="max-w-sm"
+              />
+            </SettingItem>
+          )}
         </SettingSection>
       )}
     </div>
@@ -1409,13 +1450,28 @@ const AdvancedSettings = ({
   const [isExporting, setIsExporting] = useState(false)
   const [isResetting, setIsResetting] = useState(false)
   const [isCleaningUp, setIsCleaningUp] = useState(false)
+  const [isCheckingHealth, setIsCheckingHealth] = useState(false)
+  const [coverProgress, setCoverProgress] = useState<{ processed: number; total: number } | null>(null)
+  const [validationProgress, setValidationProgress] = useState<{ processed: number; total: number } | null>(null)
+  const [isCompacting, setIsCompacting] = useState(false)
+  const [isApplyingTagRules, setIsApplyingTagRules] = useState(false)
   const [isBackingUp, setIsBackingUp] = useState(false)
   const [isRestoring, setIsRestoring] = useState(false)
+  const [isSnapshotting, setIsSnapshotting] = useState(false)
   const [includeBooks, setIncludeBooks] = useState(false)
   const [backupResult, setBackupResult] = useState<BackupInfo | null>(null)
   const [restoreSuccess, setRestoreSuccess] = useState(false)
   const [error, setError] = useState<string | null>(null)
   const [cacheStats, setCacheStats] = useState<CacheStats | null>(null)
+  const [metadataCacheStats, setMetadataCacheStats] = useState<MetadataCacheStats | null>(null)
+  const [workerCount, setWorkerCount] = useState<number | null>(null)
+  const [progressRate, setProgressRate] = useState<number | null>(null)
+  const [network, setNetwork] = useState<NetworkSettings | null>(null)
+  const [smtp, setSmtp] = useState<SmtpSettings | null>(null)
+  const [smtpPassword, setSmtpPassword] = useState('')
+  const [opds, setOpds] = useState<OpdsSettings | null>(null)
+  const [opdsPassword, setOpdsPassword] = useState('')
+  const [localApi, setLocalApi] = useState<ApiSettings | null>(null)
   const toast = useToast()
 
   useEffect(() => {
@@ -1428,8 +1484,99 @@ const AdvancedSettings = ({
       }
     }
     loadCacheStats()
+    api.getMetadataCacheStats()
+      .then(setMetadataCacheStats)
+      .catch((err) => logger.debug('Failed to load metadata cache stats:', err))
+    api.getConversionWorkerCount()
+      .then(setWorkerCount)
+      .catch((err) => logger.debug('Failed to load conversion worker count:', err))
+    api.getConversionProgressRate()
+      .then(setProgressRate)
+      .catch((err) => logger.debug('Failed to load conversion progress rate:', err))
+    api.getNetworkSettings()
+      .then(setNetwork)
+      .catch((err) => logger.debug('Failed to load network settings:', err))
+    api.getSmtpSettings()
+      .then(setSmtp)
+      .catch((err) => logger.debug('Failed to load SMTP settings:', err))
+    api.getOpdsSettings()
+      .then(setOpds)
+      .catch((err) => logger.debug('Failed to load OPDS settings:', err))
+    api.getApiSettings()
+      .then(setLocalApi)
+      .catch((err) => logger.debug('Failed to load API settings:', err))
   }, [])
 
+  const handleSaveSmtp = async () => {
+    if (!smtp) return
+    try {
+      // An empty field keeps the stored password
+      setSmtp(await api.updateSmtpSettings(smtp, smtpPassword || undefined))
+      setSmtpPassword('')
+      toast.success('Email settings saved')
+    } catch (err) {
+      logger.error('Failed to save SMTP settings:', err)
+      toast.error('Failed to save email settings', String(err))
+    }
+  }
+
+  const handleSaveOpds = async (password?: string) => {
+    if (!opds) return
+    try {
+      setOpds(await api.updateOpdsSettings(opds.enabled, opds.username, password))
+      setOpdsPassword('')
+      toast.success('OPDS settings saved')
+    } catch (err) {
+      logger.error('Failed to save OPDS settings:', err)
+      toast.error('Failed to save OPDS settings', String(err))
+    }
+  }
+
+  const handleSaveLocalApi = async () => {
+    if (!localApi) return
+    try {
+      setLocalApi(await api.updateApiSettings(localApi.enabled, localApi.allowLan, localApi.port))
+      toast.success('API settings saved')
+    } catch (err) {
+      logger.error('Failed to save API settings:', err)
+      toast.error('Failed to save API settings', String(err))
+    }
+  }
+
+  const handleRegenerateApiToken = async () => {
+    if (!confirm('Generate a new API token? Integrations using the current token will stop working.')) return
+    try {
+      setLocalApi(await api.regenerateApiToken())
+      toast.success('API token regenerated')
+    } catch (err) {
+      logger.error('Failed to regenerate API token:', err)
+      toast.error('Failed to regenerate API token', String(err))
+    }
+  }
+
+  const handleSaveNetwork = async () => {
+    if (!network) return
+    try {
+      setNetwork(await api.updateNetworkSettings(network))
+      toast.success('Network settings saved')
+    } catch (err) {
+      logger.error('Failed to save network settings:', err)
+      toast.error('Failed to save network settings', String(err))
+    }
+  }
+
+  const handleWorkerCountChange = async (count: number) => {
+    const previous = workerCount
+    setWorkerCount(count)
+    try {
+      await api.setConversionWorkerCount(count)
+    } catch (err) {
+      logger.error('Failed to set conversion worker count:', err)
+      setWorkerCount(previous)
+      toast.error('Failed to update conversion workers')
+    }
+  }
+
   if (!preferences) return null
 
   const formatBytes = (bytes: number): string => {
@@ -1517,6 +1664,100 @@ const AdvancedSettings = ({
     }
   }
 
+  const handleRegenerateCovers = async () => {
+    setCoverProgress({ processed: 0, total: 0 })
+    const unlisten = await listen<{ processed: number; total: number }>('cover-regeneration-progress', (event) => {
+      setCoverProgress(event.payload)
+    })
+    try {
+      const count = await api.regenerateMissingCovers()
+      toast.success(count > 0 ? `Regenerated ${count} cover${count === 1 ? '' : 's'}` : 'No missing covers found')
+    } catch (err) {
+      logger.error('Cover regeneration failed:', err)
+      toast.error('Failed to regenerate covers')
+    } finally {
+      unlisten()
+      setCoverProgress(null)
+    }
+  }
+
+  const handleValidateLibrary = async () => {
+    setValidationProgress({ processed: 0, total: 0 })
+    const unlisten = await listen<{ processed: number; total: number }>('library-validation-progress', (event) => {
+      setValidationProgress(event.payload)
+    })
+    try {
+      const report = await api.validateLibrary()
+      const count = (status: string) => report.filter(r => r.status === status).length
+      const problems = report.length - count('Ok')
+      if (problems === 0) {
+        toast.success(`All ${report.length} book files are intact`)
+      } else {
+        toast.warning(
+          `${problems} book file${problems === 1 ? '' : 's'} need attention`,
+          `${count('Missing')} missing, ${count('HashMismatch')} changed, ${count('Corrupt')} corrupt`
+        )
+      }
+    } catch (err) {
+      logger.error('Library validation failed:', err)
+      toast.error('Failed to validate library files')
+    } finally {
+      unlisten()
+      setValidationProgress(null)
+    }
+  }
+
+  const handleCheckHealth = async () => {
+    try {
+      setIsCheckingHealth(true)
+      const report = await api.checkDatabaseHealth()
+      const problems = report.integrity_errors.length + report.foreign_key_violations.length
+      if (problems > 0) {
+        logger.error('Database health check found problems:', report)
+        toast.error(`Database check found ${problems} problem${problems === 1 ? '' : 's'}. Restoring a backup is recommended.`)
+      }
+      if (!report.search_index_ok) {
+        if (confirm('The search index is damaged, so library search may not work. Rebuild it now?')) {
+          await api.rebuildSearchIndex()
+          toast.success('Search index rebuilt')
+        }
+      } else if (problems === 0) {
+        toast.success('Database is healthy')
+      }
+    } catch (err) {
+      logger.error('Health check failed:', err)
+      toast.error('Failed to check database health')
+    } finally {
+      setIsCheckingHealth(false)
+    }
+  }
+
+  const handleCompact = async () => {
+    try {
+      setIsCompacting(true)
+      const report = await api.compactDatabase()
+      toast.success(`Database compacted: freed ${formatBytes(report.freed_bytes)}`)
+    } catch (err) {
+      logger.error('Compaction failed:', err)
+      toast.error('Failed to compact database. Close any open books and try again.')
+    } finally {
+      setIsCompacting(false)
+    }
+  }
+
+  const handleApplyTagRules = async () => {
+    try {
+      setIsApplyingTagRules(true)
+      const added = await api.applyTagRulesToLibrary()
+      toast.success(added > 0 ? `Tag rules added ${added} tag${added === 1 ? '' : 's'}` : 'All books already match the tag rules')
+    } catch (err) {
+      logger.error('Applying tag rules failed:', err)
+      toast.error('Failed to apply tag rules')
+    } finally {
+      setIsApplyingTagRules(false)
+    }
+  }
+
   const handleReset = async () => {
     if (confirm('Are you sure you want to reset the database? This will delete all your books and settings. This cannot be undone.')) {
       try {
@@ -1590,6 +1831,42 @@ const AdvancedSettings = ({
     }
   }
 
+  const handleLibrarySnapshot = async () => {
+    setError(null)
+    try {
+      const dir = await api.openFolderDialog()
+      if (!dir) return
+      setIsSnapshotting(true)
+      const info = await api.backupLibrary(dir)
+      toast.success('Library backed up', `${info.book_count} books and ${info.files_copied} files, ${formatBytes(info.total_size_bytes)}`)
+    } catch (err) {
+      setError(`Backup failed: ${err instanceof Error ? err.message : String(err)}`)
+    } finally {
+      setIsSnapshotting(false)
+    }
+  }
+
+  const handleLibrarySnapshotRestore = async () => {
+    setError(null)
+    const confirmed = confirm(
+      'Restoring a library folder will REPLACE your current library database. This cannot be undone.\n\nContinue?'
+    )
+    if (!confirmed) return
+
+    try {
+      const dir = await api.openFolderDialog()
+      if (!dir) return
+      setIsSnapshotting(true)
+      const info = await api.restoreLibrary(dir)
+      toast.success('Library restored', `Restored ${info.book_count} books.`)
+      setTimeout(() => window.location.reload(), 2000)
+    } catch (err) {
+      setError(`Restore failed: ${err instanceof Error ? err.message : String(err)}`)
+    } finally {
+      setIsSnapshotting(false)
+    }
+  }
+
   const handleRestore = async () => {
     setError(null)
     setRestoreSuccess(false)
@@ -1646,6 +1923,17 @@ const AdvancedSettings = ({
     }
   }
 
+  const handleCleanupMetadataCache = async () => {
+    try {
+      const removed = await api.cleanupMetadataCache()
+      setMetadataCacheStats(await api.getMetadataCacheStats())
+      toast.success('Metadata cache cleaned', `Removed ${removed} entries`)
+    } catch (err) {
+      logger.error('Failed to clean metadata cache:', err)
+      toast.error('Failed to clean metadata cache')
+    }
+  }
+
   const handleClearResumePromptMemory = () => {
     try {
       localStorage.removeItem(EPUB_RESUME_CHOICE_STORAGE_KEY)
@@ -1657,7 +1945,7 @@ const AdvancedSettings = ({
 
   return (
     <div className="space-y-8">
-      {isSectionVisible('Database', ['Export Database', 'Import Database', 'Clean Up Database', 'Reset Database', 'Reset Onboarding']) && (
+      {isSectionVisible('Database', ['Export Database', 'Import Database', 'Clean Up Database', 'Validate Book Files', 'Check Database Health', 'Compact Database', 'Apply Tag Rules', 'Reset Database', 'Reset Onboarding']) && (
         <SettingSection title="Database" description="Manage your library database">
           <div className="flex flex-wrap gap-3">
             <Button variant="outline" onClick={handleExport} disabled={isExporting}>
@@ -1669,6 +1957,25 @@ const AdvancedSettings = ({
             <Button variant="outline" onClick={handleCleanUpDatabase} disabled={isCleaningUp}>
               {isCleaningUp ? 'Cleaning...' : 'Clean Up Database'}
             </Button>
+            <Button variant="outline" onClick={handleRegenerateCovers} disabled={coverProgress !== null}>
+              {coverProgress
+                ? `Regenerating... ${coverProgress.processed}/${coverProgress.total}`
+                : 'Regenerate Missing Covers'}
+            </Button>
+            <Button variant="outline" onClick={handleValidateLibrary} disabled={validationProgress !== null}>
+              {validationProgress
+                ? `Validating... ${validationProgress.processed}/${validationProgress.total}`
+                : 'Validate Book Files'}
+            </Button>
+            <Button variant="outline" onClick={handleCheckHealth} disabled={isCheckingHealth}>
+              {isCheckingHealth ? 'Checking...' : 'Check Database Health'}
+            </Button>
+            <Button variant="outline" onClick={handleCompact} disabled={isCompacting}>
+              {isCompacting ? 'Compacting...' : 'Compact Database'}
+            </Button>
+            <Button variant="outline" onClick={handleApplyTagRules} disabled={isApplyingTagRules}>
+              {isApplyingTagRules ? 'Applying...' : 'Apply Tag Rules'}
+            </Button>
             <Button variant="destructive" onClick={handleReset} disabled={isResetting}>
               {isResetting ? 'Resetting...' : 'Reset Database'}
             </Button>
@@ -1747,7 +2054,7 @@ const AdvancedSettings = ({
         </SettingSection>
       )}
 
-      {isSectionVisible('Cache', ['Renderer Cache', 'Max Cache Size', 'Cache Clear Policy']) && (
+      {isSectionVisible('Cache', ['Renderer Cache', 'Metadata Cache', 'Max Cache Size', 'Cache Clear Policy']) && (
         <SettingSection title="Cache" description="Manage cached data">
           {isSettingVisible('Renderer Cache', 'Temporary reading resources', 'Cache') && (
             <div className="rounded-lg border p-4 space-y-2">
@@ -1761,8 +2068,30 @@ const AdvancedSettings = ({
               {cacheStats && (
                 <div className="text-sm text-muted-foreground">
                   <div>Items: {cacheStats.item_count}</div>
-                  <div>Size: {formatBytes(cacheStats.total_size_bytes)}</div>
+                  <div>Size: {formatBytes(cacheStats.size_bytes)} / {formatBytes(cacheStats.max_size_bytes)}</div>
                   <div>Hit rate: {(cacheStats.hit_rate * 100).toFixed(1)}%</div>
+                  <div>Evictions: {cacheStats.evictions}</div>
+                </div>
+              )}
+            </div>
+          )}
+
+          {isSettingVisible('Metadata Cache', 'Cached online metadata lookups', 'Cache') && (
+            <div className="rounded-lg border p-4 space-y-2">
+              <div className="flex items-center justify-between">
+                <span className="text-sm font-medium">Metadata Cache</span>
+                <Button variant="outline" size="sm" onClick={handleCleanupMetadataCache} className="gap-1.5">
+                  <Trash2 size={14} />
+                  Remove Expired
+                </Button>
+              </div>
+              {metadataCacheStats && (
+                <div className="text-sm text-muted-foreground">
+                  <div>Entries: {metadataCacheStats.rows} ({metadataCacheStats.expiredRows} expired)</div>
+                  <div>Size: {formatBytes(metadataCacheStats.bytes)}</div>
+                  {metadataCacheStats.providers.map((p) => (
+                    <div key={p.provider}>{p.provider}: {p.rows} entries, {formatBytes(p.bytes)}</div>
+                  ))}
                 </div>
               )}
             </div>
@@ -1804,6 +2133,240 @@ const AdvancedSettings = ({
         </SettingSection>
       )}
 
+      {isSectionVisible('Conversion', ['Conversion Workers', 'Progress Updates']) && workerCount !== null && (
+        <SettingSection title="Conversion" description="Background format conversion">
+          {isSettingVisible('Conversion Workers', 'Conversions that run at the same time', 'Conversion') && (
+            <SettingItem label="Conversion Workers" description="How many conversions run at the same time. Lowering this lets running jobs finish first.">
+              <select
+                value={String(workerCount)}
+                onChange={(e) => handleWorkerCountChange(Number(e.target.value))}
+                className="px-3 py-2 rounded-lg border border-border/50 bg-background/50 backdrop-blur-sm hover:border-primary/50 focus:border-primary focus:ring-1 focus:ring-primary transition-all outline-none "
+                aria-label="Conversion workers"
+              >
+                {[1, 2, 3, 4, 5, 6, 7, 8].map((n) => (
+                  <option key={n} value={n}>{n}</option>
+                ))}
+              </select>
+            </SettingItem>
+          )}
+          {isSettingVisible('Progress Updates', 'Progress events per conversion each second', 'Conversion') && progressRate !== null && (
+            <SettingItem label="Progress Updates" description="Progress events per conversion each second. Finished, failed and cancelled jobs always report right away.">
+              <select
+                value={String(progressRate)}
+                onChange={(e) => handleProgressRateChange(Number(e.target.value))}
+                className="px-3 py-2 rounded-lg border border-border/50 bg-background/50 backdrop-blur-sm hover:border-primary/50 focus:border-primary focus:ring-1 focus:ring-primary transition-all outline-none "
+                aria-label="Conversion progress updates per second"
+              >
+                {[1, 2, 4, 10, 30, 60].map((n) => (
+                  <option key={n} value={n}>{n}/s</option>
+                ))}
+              </select>
+            </SettingItem>
+          )}
+        </SettingSection>
+      )}
+
+      {isSectionVisible('Network', ['Proxy URL', 'Request Timeout', 'User Agent']) && network !== null && (
+        <SettingSection title="Network" description="Used by metadata providers, AniList and RSS feeds">
+          {isSettingVisible('Proxy URL', 'HTTP or HTTPS proxy for online requests', 'Network') && (
+            <SettingItem label="Proxy URL" description="Leave empty to use the system proxy">
+              <Input
+                value={network.proxyUrl ?? ''}
+                onChange={(e) => setNetwork({ ...network, proxyUrl: e.target.value || null })}
+                placeholder="http://127.0.0.1:8080"
+                className="max-w-sm"
+              />
+            </SettingItem>
+          )}
+          {isSettingVisible('Request Timeout', 'Seconds before an online request gives up', 'Network') && (
+            <SettingItem label="Request Timeout" description="Seconds before an online request gives up (1-300)">
+              <Input
+                type="number"
+                min={1}
+                max={300}
+                value={network.timeoutSecs}
+                onChange={(e) => setNetwork({ ...network, timeoutSecs: Number(e.target.value) })}
+                className="w-24"
+              />
+            </SettingItem>
+          )}
+          {isSettingVisible('User Agent', 'User agent sent with online requests', 'Network') && (
+            <SettingItem label="User Agent" description="Leave empty for the default">
+              <Input
+                value={network.userAgent ?? ''}
+                onChange={(e) => setNetwork({ ...network, userAgent: e.target.value || null })}
+                placeholder="Shiori"
+                className="max-w-sm"
+              />
+            </SettingItem>
+          )}
+          <div className="flex justify-end">
+            <Button onClick={handleSaveNetwork}>Save Network Settings</Button>
+          </div>
+        </SettingSection>
+      )}
+
+      {isSectionVisible('Email', ['SMTP Server', 'SMTP Account', 'Sender Address', 'Attachment Limit']) && smtp !== null && (
+        <SettingSection title="Email" description="Send books to an email address, such as your Kindle's">
+          {isSettingVisible('SMTP Server', 'Mail server used to email books', 'Email') && (
+            <SettingItem label="SMTP Server" description="Host, port and encryption">
+              <div className="flex gap-2">
+                <Input
+                  value={smtp.host}
+                  onChange={(e) => setSmtp({ ...smtp, host: e.target.value })}
+                  placeholder="smtp.gmail.com"
+                  className="max-w-xs"
+                />
+                <Input
+                  type="number"
+                  min={1}
+                  max={65535}
+                  value={smtp.port}
+                  onChange={(e) => setSmtp({ ...smtp, port: Number(e.target.value) })}
+                  className="w-24"
+                />
+                <select
+                  value={smtp.security}
+                  onChange={(e) => setSmtp({ ...smtp, security: e.target.value as SmtpSettings['security'] })}
+                  className="px-3 py-2 rounded-lg bg-muted border border-border text-sm"
+                >
+                  <option value="starttls">STARTTLS</option>
+                  <option value="tls">TLS</option>
+                  <option value="none">None</option>
+                </select>
+              </div>
+            </SettingItem>
+          )}
+          {isSettingVisible('SMTP Account', 'Username and password for the mail server', 'Email') && (
+            <SettingItem label="SMTP Account" description="Use an app password where your provider offers one">
+              <div className="flex gap-2">
+                <Input
+                  value={smtp.username}
+                  onChange={(e) => setSmtp({ ...smtp, username: e.target.value })}
+                  placeholder="Username"
+                  className="max-w-xs"
+                />
+                <Input
+                  type="password"
+                  value={smtpPassword}
+                  onChange={(e) => setSmtpPassword(e.target.value)}
+                  placeholder={smtp.hasPassword ? 'Saved (unchanged)' : 'Password'}
+                  autoComplete="new-password"
+                  className="max-w-xs"
+                />
+              </div>
+            </SettingItem>
+          )}
+          {isSettingVisible('Sender Address', 'From address for emailed books', 'Email') && (
+            <SettingItem label="Sender Address" description="Add it to your Kindle's approved senders">
+              <Input
+                type="email"
+                value={smtp.fromAddress}
+                onChange={(e) => setSmtp({ ...smtp, fromAddress: e.target.value })}
+                placeholder="me@example.com"
+                className="max-w-sm"
+              />
+            </SettingItem>
+          )}
+          {isSettingVisible('Attachment Limit', 'Largest book that will be emailed', 'Email') && (
+            <SettingItem label="Attachment Limit" description="Largest book that will be emailed, in MB (1-100)">
+              <Input
+                type="number"
+                min={1}
+                max={100}
+                value={smtp.maxAttachmentMb}
+                onChange={(e) => setSmtp({ ...smtp, maxAttachmentMb: Number(e.target.value) })}
+                className="w-24"
+              />
+            </SettingItem>
+          )}
+          <div className="flex justify-end">
+            <Button onClick={handleSaveSmtp}>Save Email Settings</Button>
+          </div>
+        </SettingSection>
+      )}
+
+      {isSectionVisible('OPDS', ['OPDS Catalog', 'OPDS Login']) && opds !== null && (
+        <SettingSection title="OPDS" description="Serve the library as an OPDS catalog at /opds on the share server">
+          {isSettingVisible('OPDS Catalog', 'Browse and download the library from e-reader apps', 'OPDS') && (
+            <SettingItem label="OPDS Catalog" description="Add http://<this computer>:<share port>/opds in KOReader or similar apps">
+              <Switch checked={opds.enabled} onChange={(checked) => setOpds({ ...opds, enabled: checked })} />
+            </SettingItem>
+          )}
+          {isSettingVisible('OPDS Login', 'Optional username and password for the catalog', 'OPDS') && (
+            <SettingItem label="OPDS Login" description="Leave the username empty to allow anyone on your network">
+              <div className="flex gap-2">
+                <Input
+                  value={opds.username}
+                  onChange={(e) => setOpds({ ...opds, username: e.target.value })}
+                  placeholder="Username"
+                  className="max-w-xs"
+                />
+                <Input
+                  type="password"
+                  value={opdsPassword}
+                  onChange={(e) => setOpdsPassword(e.target.value)}
+                  placeholder={opds.hasPassword ? 'Saved (unchanged)' : 'Password'}
+                  autoComplete="new-password"
+                  className="max-w-xs"
+                />
+              </div>
+            </SettingItem>
+          )}
+          <div className="flex justify-end gap-2">
+            {opds.hasPassword && (
+              <Button variant="outline" onClick={() => handleSaveOpds('')}>Remove Login</Button>
+            )}
+            {/* An empty field keeps the stored password */}
+            <Button onClick={() => handleSaveOpds(opdsPassword || undefined)}>Save OPDS Settings</Button>
+          </div>
+        </SettingSection>
+      )}
+
+      {isSectionVisible('Local API', ['Local API', 'API Token']) && localApi !== null && (
+        <SettingSection title="Local API" description="JSON endpoints under /api/books and /api/search">
+          {isSettingVisible('Local API', 'JSON API for scripts and community integrations', 'Local API') && (
+            <SettingItem label="Local API" description="Serve the API on this port; LAN access exposes it to other devices">
+              <div className="flex items-center gap-3">
+                <Switch checked={localApi.enabled} onChange={(checked) => setLocalApi({ ...localApi, enabled: checked })} />
+                <Input
+                  type="number"
+                  min={1024}
+                  max={65535}
+                  value={localApi.port}
+                  onChange={(e) => setLocalApi({ ...localApi, port: Number(e.target.value) })}
+                  className="w-24"
+                />
+                <label className="flex items-center gap-2 text-sm">
+                  <Switch checked={localApi.allowLan} onChange={(checked) => setLocalApi({ ...localApi, allowLan: checked })} />
+                  LAN
+                </label>
+              </div>
+            </SettingItem>
+          )}
+          {isSettingVisible('API Token', 'Bearer token required by every API request', 'Local API') && (
+            <SettingItem label="API Token" description="Send as Authorization: Bearer <token>">
+              <div className="flex gap-2">
+                <Input value={localApi.token} readOnly className="max-w-sm font-mono text-xs" />
+                <Button
+                  variant="outline"
+                  onClick={() => {
+                    navigator.clipboard.writeText(localApi.token)
+                    toast.success('API token copied')
+                  }}
+                >
+                  Copy
+                </Button>
+                <Button variant="outline" onClick={handleRegenerateApiToken}>Regenerate</Button>
+              </div>
+            </SettingItem>
+          )}
+          <div className="flex justify-end">
+            <Button onClick={handleSaveLocalApi}>Save API Settings</Button>
+          </div>
+        </SettingSection>
+      )}
+
       {isSectionVisible('Backup & Restore', ['Backup', 'Restore']) && (
         <SettingSection
           title="Backup & Restore"
@@ -1868,6 +2431,25 @@ const AdvancedSettings = ({
               )}
             </div>
 
+            {!isAndroid && (
+              <div className="rounded-lg border p-4 space-y-3">
+                <div>
+                  <h4 className="text-sm font-medium">Library Folder</h4>
+                  <p className="text-xs text-muted-foreground">
+                    Copy the library database, covers and RSS downloads into a folder, or restore from one. Safe to run while reading.
+                  </p>
+                </div>
+                <div className="flex gap-2">
+                  <Button variant="outline" onClick={handleLibrarySnapshot} disabled={isSnapshotting} className="flex-1 gap-2">
+                    <Download className="w-4 h-4" /> Back Up to Folder
+                  </Button>
+                  <Button variant="outline" onClick={handleLibrarySnapshotRestore} disabled={isSnapshotting} className="flex-1 gap-2">
+                    <Upload className="w-4 h-4" /> Restore from Folder
+                  </Button>
+                </div>
+              </div>
+            )}
+
             {error && (
               <div className="p-4 rounded-lg bg-red-500/10 border border-red-500/30">
                 <p className="text-sm text-red-600 dark:text-red-400">{error}</p>
@@ -38,6 +38,8 @@ export type ErrorKind =
   | "validation"
   | "corrupted"
   | "size_limit"
+  | "password_required"
+  | "incorrect_password"
   | "unknown";
 
 /**
@@ -106,6 +108,14 @@ export function isNotFoundError(error: unknown): boolean {
   return parsed.kind === "not_found" || parsed.kind === "file_not_found";
 }
 
+/**
+ * Check whether opening a PDF failed for want of a (correct) password.
+ */
+export function isPasswordError(error: unknown): boolean {
+  const parsed = parseError(error);
+  return parsed.kind === "password_required" || parsed.kind === "incorrect_password";
+}
+
 /**
  * Check whether an error is a validation error.
  */
@@ -34,6 +34,30 @@ export const isAndroid = (() => {
   return isTauriEnv && /android/i.test(navigator.userAgent);
 })();
 
+/** Fields set on every book by `bulkUpdateBooks`; omitted fields are left alone.
+ * An empty series or publisher clears it, as does a rating of 0. */
+export interface BookPatch {
+  series?: string
+  publisher?: string
+  language?: string
+  rating?: number
+  add_tag_ids?: number[]
+  remove_tag_ids?: number[]
+}
+
+/** One file of a book; a book may hold several formats, one of them primary */
+export interface BookFormat {
+  id: number
+  book_id: number
+  format: string
+  file_path: string
+  file_size: number
+  file_hash: string
+  page_count?: number
+  is_primary: boolean
+  added_at: string
+}
+
 export interface Book {
   id?: number
   uuid: string
@@ -106,12 +130,122 @@ export interface Author {
   link?: string
 }
 
+export interface BookIntegrity {
+  book_id: number
+  status: "Ok" | "Missing" | "HashMismatch" | "Corrupt"
+  detail: string | null
+}
+
+export interface DuplicateGroup {
+  reason: string
+  bookIds: number[]
+}
+
+export interface AuthorBookCount {
+  id: number
+  name: string
+  bookCount: number
+}
+
+/** Author rows that are spellings of the same person, most-credited first */
+export interface DuplicateAuthorGroup {
+  sortName: string
+  authors: AuthorBookCount[]
+}
+
+export interface SeriesGroup {
+  series: string | null
+  books: Book[]
+}
+
+export interface SeriesSummary {
+  name: string
+  bookCount: number
+  coverPaths: string[]
+}
+
 export interface Tag {
   id?: number
   name: string
   color?: string
 }
 
+export interface ThemePalette {
+  background: string
+  text: string
+  accent: string
+  link: string
+}
+
+export interface ThemeInfo {
+  name: string
+  builtin: boolean
+  palette?: ThemePalette | null
+}
+
+export interface PreferencesImportReport {
+  bookOverrides: number
+  mangaOverrides: number
+  skippedOverrides: number
+}
+
+export interface NetworkSettings {
+  proxyUrl: string | null
+  timeoutSecs: number
+  userAgent: string | null
+}
+
+export interface SmtpSettings {
+  host: string
+  port: number
+  security: 'tls' | 'starttls' | 'none'
+  username: string
+  fromAddress: string
+  maxAttachmentMb: number
+  /** Whether a password is stored; the password itself is never returned */
+  hasPassword: boolean
+}
+
+export interface OpdsSettings {
+  enabled: boolean
+  username: string
+  /** Whether basic-auth is configured; the password itself is never returned */
+  hasPassword: boolean
+}
+
+export interface ApiSettings {
+  enabled: boolean
+  /** Listen on all interfaces instead of localhost only */
+  allowLan: boolean
+  port: number
+  /** Sent as `Authorization: Bearer <token>` */
+  token: string
+}
+
+export interface SentBook {
+  toAddress: string
+  fileName: string
+  format: string
+  sizeBytes: number
+}
+
+export interface TagRule {
+  id?: number
+  field: "publisher" | "language" | "series" | "pubdate_year" | "file_format" | "always"
+  operator: "equals" | "contains" | "is_not_empty" | "between"
+  value: string
+  tag_name: string
+  enabled: boolean
+}
+
+export interface TagNode {
+  id: number
+  name: string
+  label: string
+  color?: string
+  children: TagNode[]
+}
+
 export interface VoiceInfo {
   id: string
   name: string
@@ -188,6 +322,36 @@ export interface ImportResult {
   success: string[]
   failed: [string, string][]
   duplicates: string[]
+  merged?: string[]
+  replaced?: string[]
+}
+
+/** A folder the background auto-scan keeps imported */
+export interface AutoScanFolder {
+  path: string
+  domain?: "books" | "manga" | "comics" | null
+}
+
+/** Payload of the `library:scan_complete` event emitted after each auto-scan pass */
+export interface AutoScanReport {
+  folders: number
+  added: number
+  updated: number
+  unchanged: number
+  missing: number
+  failed: number
+}
+
+/** What `importBooks` does with a file that is already in the library */
+export interface ImportPolicy {
+  onDuplicate: "skip" | "add_format" | "replace"
+}
+
+/** Payload of the `import:progress` event emitted by `importBooks` after each file */
+export interface ImportProgress {
+  processed: number
+  total: number
+  path: string
 }
 
 export interface ReadingProgress {
@@ -216,6 +380,11 @@ export interface Annotation {
   chapterTitle?: string
 }
 
+export interface ColorCount {
+  color: string
+  count: number
+}
+
 export interface AnnotationCategory {
   id?: number;
   name: string;
@@ -289,6 +458,26 @@ export interface ExportOptions {
   file_path: string
 }
 
+export interface ReadingStateImportReport {
+  progressUpdated: number
+  annotationsAdded: number
+  annotationsUpdated: number
+  skippedBooks: string[]
+}
+
+export interface AnthologyExport {
+  filePath: string
+  included: string[]
+  skipped: string[]
+}
+
+export interface HtmlExport {
+  filePath: string
+  sizeBytes: number
+  /** Set when inlined images make the file unusually large */
+  warning: string | null
+}
+
 // Phase 2 Rendering System Types
 export interface BookMetadata {
   title: string
@@ -310,6 +499,29 @@ export interface Chapter {
   title: string
   content: string
   location: string
+  word_count: number
+  estimated_minutes: number
+}
+
+/** One sentence of chapter text; offsets index the tag-stripped chapter text */
+export interface TtsSegment {
+  index: number
+  text: string
+  charStart: number
+  charEnd: number
+}
+
+export interface ReadingEstimate {
+  book_id: number
+  chapter_count: number
+  word_count: number
+  estimated_minutes: number
+  words_per_minute: number
+}
+
+export interface ChapterCssPreview {
+  appliedCss: string
+  chapter: Chapter
 }
 
 export interface BookSearchResult {
@@ -318,16 +530,46 @@ export interface BookSearchResult {
   snippet: string
   location: string
   match_count: number
+  before: string
+  matched: string
+  after: string
+}
+
+export interface BookSearchOptions {
+  caseSensitive?: boolean
+  wholeWord?: boolean
+  /** Characters of context on each side of a match (default 40) */
+  contextChars?: number
 }
 
 export interface CacheStats {
-  total_size_bytes: number
   item_count: number
-  hit_count: number
-  miss_count: number
+  size_bytes: number
+  max_size_bytes: number
+  utilization_percent: number
+  hits: number
+  misses: number
+  evictions: number
   hit_rate: number
 }
 
+export type CoverSource =
+  | { type: "file"; path: string }
+  | { type: "url"; url: string }
+
+export interface ProviderCacheStats {
+  provider: string
+  rows: number
+  bytes: number
+}
+
+export interface MetadataCacheStats {
+  rows: number
+  bytes: number
+  expiredRows: number
+  providers: ProviderCacheStats[]
+}
+
 export interface MangaMetadata {
   title: string
   page_count: number
@@ -338,6 +580,35 @@ export interface MangaMetadata {
   page_dimensions: [number, number][]
 }
 
+export interface DetailedFormat {
+  format: string
+  detected_by: "extension" | "magic" | "content"
+  confidence: number
+  mime_type: string
+}
+
+export interface MangaCacheStats {
+  used_bytes: number
+  budget_bytes: number
+  entries: number
+  hits: number
+  misses: number
+  evictions: number
+  hit_rate: number
+}
+
+export interface StripPage {
+  index: number
+  width: number
+  height: number
+  offset_y: number
+}
+
+/** One double-page screen; `left`/`right` are screen positions (already swapped for RTL) */
+export type MangaSpread =
+  | { kind: "single"; page: number }
+  | { kind: "pair"; left: number; right: number }
+
 export interface Doodle {
   id?: number
   book_id: number
@@ -377,12 +648,51 @@ export interface ReadingGoal {
     updated_at: string
 }
 
+export type GoalPeriod = "week" | "month" | "year"
+
+export interface GoalProgress {
+  period: GoalPeriod
+  target: number
+  period_start: string
+  period_end: string
+  books_finished: number
+  completion_ratio: number
+  expected_by_now: number
+  pace: "ahead" | "on_track" | "behind"
+}
+
 export interface ReadingStreak {
   current_streak: number
   longest_streak: number
   total_reading_days: number
 }
 
+export interface AuthorReadingCount {
+  name: string
+  books_count: number
+}
+
+export interface MonthlyReadingStats {
+  month: string
+  books_finished: number
+  pages_read: number
+  total_seconds: number
+  annotations_count: number
+}
+
+export interface ReadingStats {
+  from_date: string
+  to_date: string
+  books_finished: number
+  pages_read: number
+  total_seconds: number
+  annotations_count: number
+  most_read_author: AuthorReadingCount | null
+  reading_days: number
+  longest_streak: number
+  months: MonthlyReadingStats[]
+}
+
 export interface BookReadingStats {
   book_id: number
   total_seconds: number
@@ -402,6 +712,24 @@ export interface BackupInfo {
   total_size_bytes: number
 }
 
+export interface DatabaseHealthReport {
+  integrity_errors: string[]
+  foreign_key_violations: string[]
+  search_index_ok: boolean
+}
+
+export interface LibrarySnapshotInfo {
+  book_count: number
+  files_copied: number
+  total_size_bytes: number
+}
+
+export interface CompactReport {
+  size_before: number
+  size_after: number
+  freed_bytes: number
+}
+
 export interface RestoreInfo {
   books_restored: number
   annotations_restored: number
@@ -612,8 +940,8 @@ export const api = {
     return invoke("add_book", { book })
   },
 
-  async updateBook(book: Book): Promise<void> {
-    return invoke("update_book", { book })
+  async updateBook(book: Book, writeToFile?: boolean): Promise<void> {
+    return invoke("update_book", { book, writeToFile })
   },
 
   async deleteBook(id: number): Promise<void> {
@@ -624,6 +952,53 @@ export const api = {
     return invoke("find_duplicate_books", { criteria, threshold })
   },
 
+  async findDuplicateGroups(mode: "hash" | "isbn" | "title_author"): Promise<DuplicateGroup[]> {
+    return invoke("find_duplicate_groups", { mode })
+  },
+
+  async mergeBooks(keepId: number, mergeIds: number[]): Promise<number> {
+    return invoke("merge_books", { keepId, mergeIds })
+  },
+
+  async findDuplicateAuthors(): Promise<DuplicateAuthorGroup[]> {
+    return invoke("find_duplicate_authors")
+  },
+
+  async mergeAuthors(fromId: number, intoId: number): Promise<number> {
+    return invoke("merge_authors", { fromId, intoId })
+  },
+
+  /** Apply the same changes to many books in one transaction; returns how many were updated */
+  async bulkUpdateBooks(bookIds: number[], patch: BookPatch): Promise<number> {
+    return invoke("bulk_update_books", { bookIds, patch })
+  },
+
+  async getSeriesAutofill(): Promise<boolean> {
+    return invoke("get_series_autofill")
+  },
+
+  /** When on, imports without series metadata take it from names like "Dune 2 - Messiah" */
+  async setSeriesAutofill(enabled: boolean): Promise<void> {
+    return invoke("set_series_autofill", { enabled })
+  },
+
+  async getBookFormats(bookId: number): Promise<BookFormat[]> {
+    return invoke("get_book_formats", { bookId })
+  },
+
+  /** Attach another file to a book; its format comes from the extension */
+  async addBookFormat(bookId: number, path: string): Promise<BookFormat> {
+    return invoke("add_book_format", { bookId, path })
+  },
+
+  async setPrimaryFormat(bookId: number, format: string): Promise<void> {
+    return invoke("set_primary_format", { bookId, format })
+  },
+
+  async deleteBookFormat(formatId: number): Promise<void> {
+    return invoke("delete_book_format", { formatId })
+  },
+
   async deleteBooks(ids: number[]): Promise<void> {
     return invoke("delete_books", { ids })
   },
@@ -644,10 +1019,18 @@ export const api = {
     return invoke("clean_up_database")
   },
 
-  async importBooks(paths: string[]): Promise<ImportResult> {
+  async regenerateMissingCovers(): Promise<number> {
+    return invoke("regenerate_missing_covers")
+  },
+
+  async validateLibrary(): Promise<BookIntegrity[]> {
+    return invoke("validate_library")
+  },
+
+  async importBooks(paths: string[], policy?: ImportPolicy): Promise<ImportResult> {
     logger.debug('[API] importBooks called with:', paths)
     try {
-      const result = await invoke<ImportResult>("import_books", { paths })
+      const result = await invoke<ImportResult>("import_books", { paths, policy })
       logger.debug('[API] importBooks result:', result)
       return result
     } catch (error) {
@@ -660,6 +1043,18 @@ export const api = {
     return invoke("enrich_book_metadata", { bookId })
   },
 
+  async cancelMetadataJob(bookId: number): Promise<void> {
+    return invoke("cancel_metadata_job", { bookId })
+  },
+
+  async cancelMetadataJobs(bookIds: number[]): Promise<void> {
+    return invoke("cancel_metadata_jobs", { bookIds })
+  },
+
+  async cancelAllMetadataJobs(): Promise<number> {
+    return invoke("cancel_all_metadata_jobs")
+  },
+
   // Search
   async searchBooks(query: SearchQuery): Promise<SearchResult> {
     return invoke("search_books", { query })
@@ -670,6 +1065,24 @@ export const api = {
     return invoke("get_tags")
   },
 
+  async getTagTree(): Promise<TagNode[]> {
+    return invoke("get_tag_tree")
+  },
+
+  async getBooksByTag(tagId: number, includeDescendants = false): Promise<Book[]> {
+    return invoke("get_books_by_tag", { tagId, includeDescendants })
+  },
+
+  async getBooksByTags(
+    tagIds: number[],
+    mode: "any" | "all",
+    domain?: "books" | "manga" | "comics" | "all",
+    limit: number = 50,
+    offset: number = 0,
+  ): Promise<Book[]> {
+    return invoke("get_books_by_tags", { tagIds, mode, domain, limit, offset })
+  },
+
   async getBookTagIds(bookId: number): Promise<number[]> {
     return invoke("get_book_tag_ids", { bookId })
   },
@@ -686,6 +1099,38 @@ export const api = {
     return invoke("remove_tag_from_book", { bookId, tagId })
   },
 
+  async addTagToBooks(tagId: number, bookIds: number[]): Promise<number> {
+    return invoke("add_tag_to_books", { tagId, bookIds })
+  },
+
+  async removeTagFromBooks(tagId: number, bookIds: number[]): Promise<number> {
+    return invoke("remove_tag_from_books", { tagId, bookIds })
+  },
+
+  async autoAssignTagColors(): Promise<number> {
+    return invoke("auto_assign_tag_colors")
+  },
+
+  async mergeTags(fromId: number, intoId: number): Promise<void> {
+    return invoke("merge_tags", { fromId, intoId })
+  },
+
+  async listTagRules(): Promise<TagRule[]> {
+    return invoke("list_tag_rules")
+  },
+
+  async setTagRule(rule: TagRule): Promise<number> {
+    return invoke("set_tag_rule", { rule })
+  },
+
+  async deleteTagRule(id: number): Promise<void> {
+    return invoke("delete_tag_rule", { id })
+  },
+
+  async applyTagRulesToLibrary(): Promise<number> {
+    return invoke("apply_tag_rules_to_library")
+  },
+
   // Reader - Reading Progress
   async getReadingProgress(bookId: number): Promise<ReadingProgress | null> {
     const raw = await invoke<unknown>("get_reading_progress", { bookId })
@@ -778,6 +1223,26 @@ export const api = {
     return invoke("delete_annotation", { id })
   },
 
+  async getAnnotationsByColor(bookId: number, color: string): Promise<Annotation[]> {
+    return invoke("get_annotations_by_color", { bookId, color })
+  },
+
+  async getColorDistribution(bookId: number): Promise<ColorCount[]> {
+    return invoke("get_color_distribution", { bookId })
+  },
+
+  async getBookmarks(bookId: number): Promise<Annotation[]> {
+    return invoke("get_bookmarks", { bookId })
+  },
+
+  async addBookmark(bookId: number, location: string, label?: string): Promise<Annotation> {
+    return invoke("add_bookmark", { bookId, location, label })
+  },
+
+  async deleteBookmark(id: number): Promise<void> {
+    return invoke("delete_bookmark", { id })
+  },
+
   // Annotation Categories
   async getAnnotationCategories(): Promise<AnnotationCategory[]> {
     return invoke("get_annotation_categories")
@@ -807,6 +1272,11 @@ export const api = {
     return invoke("search_annotations_global", { query, bookId, annotationType, categoryId, limit: limit || 50, offset: offset || 0 })
   },
 
+  /** Search highlights and notes across the whole library, ranked by relevance then recency */
+  async searchAllAnnotations(query: string, limit?: number): Promise<AnnotationSearchResult[]> {
+    return invoke("search_all_annotations", { query, limit })
+  },
+
   async getAllAnnotations(
     bookId?: number,
     annotationType?: string,
@@ -852,8 +1322,8 @@ export const api = {
   },
 
   // Reader - Book File Access
-  async getBookFilePath(bookId: number): Promise<string> {
-    return invoke("get_book_file_path", { bookId })
+  async getBookFilePath(bookId: number, format?: string): Promise<string> {
+    return invoke("get_book_file_path", { bookId, format })
   },
 
   // Reader - Format Detection
@@ -861,6 +1331,10 @@ export const api = {
     return invoke("detect_book_format", { path })
   },
 
+  async detectFormatDetailed(path: string): Promise<DetailedFormat> {
+    return invoke("detect_format_detailed", { path })
+  },
+
   async validateBookFile(path: string, format: string): Promise<boolean> {
     return invoke("validate_book_file", { path, format })
   },
@@ -939,8 +1413,8 @@ export const api = {
     return invoke("add_books_to_collection", { collectionId, bookIds })
   },
 
-  async getCollectionBooks(collectionId: number): Promise<Book[]> {
-    return invoke("get_collection_books", { collectionId })
+  async getCollectionBooks(collectionId: number, includeDescendants = false): Promise<Book[]> {
+    return invoke("get_collection_books", { collectionId, includeDescendants })
   },
 
   async getNestedCollections(): Promise<Collection[]> {
@@ -963,6 +1437,14 @@ export const api = {
     return invoke("preview_smart_collection", { smartRules })
   },
 
+  async getAutoScanFolders(): Promise<AutoScanFolder[]> {
+    return invoke("get_auto_scan_folders")
+  },
+
+  async setAutoScanFolders(folders: AutoScanFolder[]): Promise<void> {
+    return invoke("set_auto_scan_folders", { folders })
+  },
+
   async scanFolderUnified(folderPath: string): Promise<ImportResult> {
     if (!isTauri) {
       return Promise.resolve({
@@ -1049,6 +1531,18 @@ export const api = {
     return invoke("get_reading_history", { limit, offset })
   },
 
+  async getRecentlyOpened(limit?: number): Promise<Book[]> {
+    return invoke("get_recently_opened", { limit })
+  },
+
+  async getBooksGroupedBySeries(limit: number = 50, offset: number = 0): Promise<SeriesGroup[]> {
+    return invoke("get_books_grouped_by_series", { limit, offset })
+  },
+
+  async getSeriesList(): Promise<SeriesSummary[]> {
+    return invoke("get_series_list")
+  },
+
   async resetDatabase(): Promise<void> {
     return invoke("reset_database")
   },
@@ -1057,6 +1551,30 @@ export const api = {
     return invoke("export_library", { options })
   },
 
+  async exportLibraryCatalog(format: "csv" | "json", filePath: string): Promise<number> {
+    return invoke("export_library_catalog", { format, filePath })
+  },
+
+  async exportReadingState(): Promise<string> {
+    return invoke("export_reading_state")
+  },
+
+  async importReadingState(json: string): Promise<ReadingStateImportReport> {
+    return invoke("import_reading_state", { json })
+  },
+
+  async exportBookAnnotations(bookId: number, format: "markdown" | "csv"): Promise<string> {
+    return invoke("export_book_annotations", { bookId, format })
+  },
+
+  async exportCollectionAnthology(collectionId: number): Promise<AnthologyExport> {
+    return invoke("export_collection_anthology", { collectionId })
+  },
+
+  async exportBookHtml(bookId: number): Promise<HtmlExport> {
+    return invoke("export_book_html", { bookId })
+  },
+
   // File dialogs
   async openFileDialog(): Promise<string[] | null> {
     if (!isTauri) {
@@ -1098,7 +1616,7 @@ export const api = {
         filters: [
           {
             name: "Books",
-            extensions: ["epub", "pdf", "mobi", "azw", "azw3", "txt", "cbz", "cbr", "fb2", "docx", "html", "htm", "md"],
+            extensions: ["epub", "pdf", "mobi", "azw", "azw3", "txt", "cbz", "cbr", "fb2", "docx", "html", "htm", "md", "djvu"],
           },
         ],
       }) as string[] | null
@@ -1194,8 +1712,8 @@ export const api = {
   },
 
   // Phase 2 Rendering System
-  async openBookRenderer(bookId: number, path: string, format: string): Promise<BookMetadata> {
-    return invoke("open_book_renderer", { bookId, path, format })
+  async openBookRenderer(bookId: number, path: string, format: string, password?: string): Promise<BookMetadata> {
+    return invoke("open_book_renderer", { bookId, path, format, password })
   },
 
   async closeBookRenderer(bookId: number): Promise<void> {
@@ -1210,22 +1728,58 @@ export const api = {
     return invoke("get_book_chapter", { bookId, chapterIndex })
   },
 
+  async previewChapterCss(bookId: number, chapterIndex: number, css?: string): Promise<ChapterCssPreview> {
+    return invoke("preview_chapter_css", { bookId, chapterIndex, css })
+  },
+
+  async getBookReadingEstimate(bookId: number): Promise<ReadingEstimate> {
+    return invoke("get_book_reading_estimate", { bookId })
+  },
+
+  async getTtsSegments(bookId: number, chapterIndex: number): Promise<TtsSegment[]> {
+    return invoke("get_tts_segments", { bookId, chapterIndex })
+  },
+
+  async computeEpubProgress(bookId: number, cfi: string): Promise<number> {
+    return invoke("compute_epub_progress", { bookId, cfi })
+  },
+
+  async setReadingSpeed(wpm: number): Promise<void> {
+    return invoke("set_reading_speed", { wpm })
+  },
+
   async getBookChapterCount(bookId: number): Promise<number> {
     return invoke("get_book_chapter_count", { bookId })
   },
 
-  async searchInBook(bookId: number, query: string): Promise<BookSearchResult[]> {
-    return invoke("search_in_book", { bookId, query })
+  async searchInBook(bookId: number, query: string, options?: BookSearchOptions): Promise<BookSearchResult[]> {
+    return invoke("search_in_book", { bookId, query, options })
   },
 
   async getRendererCacheStats(): Promise<CacheStats> {
     return invoke("get_renderer_cache_stats")
   },
 
+  async setRendererCacheSize(mb: number): Promise<CacheStats> {
+    return invoke("set_renderer_cache_size", { mb })
+  },
+
   async clearRendererCache(): Promise<void> {
     return invoke("clear_renderer_cache")
   },
 
+  async setCustomCover(bookId: number, source: CoverSource): Promise<string> {
+    return invoke("set_custom_cover", { bookId, source })
+  },
+
+  async getMetadataCacheStats(): Promise<MetadataCacheStats> {
+    return invoke("get_metadata_cache_stats")
+  },
+
+  async cleanupMetadataCache(): Promise<number> {
+    return invoke("cleanup_metadata_cache")
+  },
+
   async getEpubResource(bookId: number, resourcePath: string): Promise<Uint8Array> {
     return invoke("get_epub_resource", { bookId, resourcePath })
   },
@@ -1234,6 +1788,16 @@ export const api = {
     return invoke("render_pdf_page", { bookId, pageIndex, scale })
   },
 
+  async renderPdfPages(
+    bookId: number,
+    start: number,
+    end: number,
+    scale: number = 1.0,
+    maxDimension?: number
+  ): Promise<[number, number[]][]> {
+    return invoke("render_pdf_pages", { bookId, start, end, scale, maxDimension })
+  },
+
   async getPdfPageDimensions(bookId: number, pageIndex: number): Promise<[number, number]> {
     return invoke("get_pdf_page_dimensions", { bookId, pageIndex })
   },
@@ -1255,6 +1819,28 @@ export const api = {
     return invoke("get_manga_page_dimensions", { bookId, pageIndices })
   },
 
+  async getMangaCacheStats(): Promise<MangaCacheStats> {
+    return invoke("get_manga_cache_stats")
+  },
+
+  async getMangaStripManifest(bookId: number, columnWidth: number): Promise<StripPage[]> {
+    return invoke("get_manga_strip_manifest", { bookId, columnWidth })
+  },
+
+  async prefetchMangaStrip(
+    bookId: number,
+    columnWidth: number,
+    scrollY: number,
+    viewportHeight: number,
+    maxDimension: number
+  ): Promise<number[]> {
+    return invoke("prefetch_manga_strip", { bookId, columnWidth, scrollY, viewportHeight, maxDimension })
+  },
+
+  async getMangaSpreads(bookId: number, direction: "ltr" | "rtl"): Promise<MangaSpread[]> {
+    return invoke("get_manga_spreads", { bookId, direction })
+  },
+
   async closeManga(bookId: number): Promise<void> {
     return invoke("close_manga", { bookId })
   },
@@ -1292,16 +1878,82 @@ export const api = {
     return invoke("get_user_preferences")
   },
 
-  async getThemeSync(): Promise<string> {
-    if (!isTauri) return Promise.resolve('system')
+  async getThemeSync(): Promise<ThemeInfo> {
+    if (!isTauri) return Promise.resolve({ name: 'system', builtin: true })
     return invoke("get_theme_sync")
   },
 
+  async listThemes(): Promise<ThemeInfo[]> {
+    return invoke("list_themes")
+  },
+
+  async saveCustomTheme(name: string, palette: ThemePalette): Promise<void> {
+    return invoke("save_custom_theme", { name, palette })
+  },
+
+  async deleteCustomTheme(name: string): Promise<void> {
+    return invoke("delete_custom_theme", { name })
+  },
+
   async updateUserPreferences(updates: Partial<UserPreferences>): Promise<void> {
     if (!isTauri) return Promise.resolve()
     return invoke("update_user_preferences", { updates })
   },
 
+  async getNetworkSettings(): Promise<NetworkSettings> {
+    return invoke("get_network_settings")
+  },
+
+  async updateNetworkSettings(settings: NetworkSettings): Promise<NetworkSettings> {
+    return invoke("update_network_settings", { settings })
+  },
+
+  async getSmtpSettings(): Promise<SmtpSettings> {
+    return invoke("get_smtp_settings")
+  },
+
+  /** Omit `password` to keep the stored one; an empty string removes it */
+  async updateSmtpSettings(settings: SmtpSettings, password?: string): Promise<SmtpSettings> {
+    return invoke("update_smtp_settings", { settings, password })
+  },
+
+  async getOpdsSettings(): Promise<OpdsSettings> {
+    return invoke("get_opds_settings")
+  },
+
+  /** Omit `password` to keep the stored one; an empty string disables basic-auth */
+  async updateOpdsSettings(enabled: boolean, username: string, password?: string): Promise<OpdsSettings> {
+    return invoke("update_opds_settings", { enabled, username, password })
+  },
+
+  async getApiSettings(): Promise<ApiSettings> {
+    return invoke("get_api_settings")
+  },
+
+  async updateApiSettings(enabled: boolean, allowLan: boolean, port: number): Promise<ApiSettings> {
+    return invoke("update_api_settings", { enabled, allowLan, port })
+  },
+
+  async regenerateApiToken(): Promise<ApiSettings> {
+    return invoke("regenerate_api_token")
+  },
+
+  async isApiServerRunning(): Promise<boolean> {
+    return invoke("is_api_server_running")
+  },
+
+  async sendBookToEmail(bookId: number, toAddress: string, convertTo?: string): Promise<SentBook> {
+    return invoke("send_book_to_email", { bookId, toAddress, convertTo })
+  },
+
+  async exportPreferences(): Promise<string> {
+    return invoke("export_preferences")
+  },
+
+  async importPreferences(json: string): Promise<PreferencesImportReport> {
+    return invoke("import_preferences", { json })
+  },
+
   async getBookPreferenceOverrides(): Promise<PreferenceOverride[]> {
     return invoke("get_book_preference_overrides")
   },
@@ -1370,6 +2022,29 @@ export const api = {
     return invoke("delete_book_doodles", { bookId })
   },
 
+  async exportDoodle(
+    bookId: number,
+    pageNumber: string,
+    format: "svg" | "png",
+    width: number,
+    height: number,
+    filePath: string
+  ): Promise<void> {
+    return invoke("export_doodle", { bookId, pageNumber, format, width, height, filePath })
+  },
+
+  async listDoodlePages(bookId: number): Promise<string[]> {
+    return invoke("list_doodle_pages", { bookId })
+  },
+
+  async copyDoodles(fromBookId: number, toBookId: number, overwrite = false): Promise<number> {
+    return invoke("copy_doodles", { fromBookId, toBookId, overwrite })
+  },
+
+  async moveDoodle(bookId: number, fromPage: string, toPage: string, overwrite = false): Promise<void> {
+    return invoke("move_doodle", { bookId, fromPage, toPage, overwrite })
+  },
+
   // Reading Sessions & Statistics
   async startReadingSession(bookId: number, pagesStart?: number): Promise<ReadingSession> {
     return invoke("start_reading_session", { bookId, pagesStart })
@@ -1395,6 +2070,10 @@ export const api = {
     return invoke("get_reading_streak")
   },
 
+  async getReadingStats(fromDate: string, toDate: string): Promise<ReadingStats> {
+    return invoke("get_reading_stats", { fromDate, toDate })
+  },
+
   async getReadingGoal(): Promise<ReadingGoal> {
     return invoke("get_reading_goal")
   },
@@ -1403,6 +2082,14 @@ export const api = {
     return invoke("update_reading_goal", { dailyMinutesTarget, yearlyBooksTarget })
   },
 
+  async setGoal(period: GoalPeriod, target: number): Promise<GoalProgress> {
+    return invoke("set_goal", { period, target })
+  },
+
+  async getGoalProgress(): Promise<GoalProgress | null> {
+    return invoke("get_goal_progress")
+  },
+
   async getTodayReadingTime(): Promise<number> {
     return invoke("get_today_reading_time")
   },
@@ -1420,6 +2107,43 @@ export const api = {
     return invoke("get_backup_info", { backupPath })
   },
 
+  async getConversionWorkerCount(): Promise<number> {
+    return invoke("get_conversion_worker_count")
+  },
+
+  async setConversionWorkerCount(count: number): Promise<void> {
+    return invoke("set_conversion_worker_count", { count })
+  },
+
+  async getConversionProgressRate(): Promise<number> {
+    return invoke("get_conversion_progress_rate")
+  },
+
+  /** Max `conversion:progress` events per job per second (1-60) */
+  async setConversionProgressRate(rate: number): Promise<void> {
+    return invoke("set_conversion_progress_rate", { rate })
+  },
+
+  async backupLibrary(backupDir: string): Promise<LibrarySnapshotInfo> {
+    return invoke("backup_library", { backupDir })
+  },
+
+  async restoreLibrary(backupDir: string): Promise<LibrarySnapshotInfo> {
+    return invoke("restore_library", { backupDir })
+  },
+
+  async checkDatabaseHealth(): Promise<DatabaseHealthReport> {
+    return invoke("check_database_health")
+  },
+
+  async rebuildSearchIndex(): Promise<void> {
+    return invoke("rebuild_search_index")
+  },
+
+  async compactDatabase(): Promise<CompactReport> {
+    return invoke("compact_database")
+  },
+
   async dictionaryLookup(word: string, lang?: string): Promise<DictionaryResponse> {
     return invoke("dictionary_lookup", { word, lang })
   },
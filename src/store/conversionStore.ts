@@ -22,6 +22,43 @@ export interface ConversionJob {
   started_at: string | null;
   completed_at: string | null;
   error: string | null;
+  /** Set from `conversion:error`; look up remediation with `getErrorDetails` */
+  error_code?: string;
+  ocr: boolean;
+  options?: ConversionOptions;
+}
+
+/** Layout of natively rendered PDFs; omitted fields use A4, 15 mm, 11 pt Times */
+export interface PdfOutputOptions {
+  page_size?: 'a4' | 'letter' | 'a5';
+  margin_mm?: number;
+  /** 6–36 pt */
+  font_size?: number;
+  font?: 'times' | 'helvetica' | 'courier';
+}
+
+/** Converter settings carried by a job; stored as a profile's options */
+export interface ConversionOptions extends PdfOutputOptions {
+  chapter_split?: 'auto' | 'none';
+  /** Regex a whole paragraph must match to start a chapter (TXT → EPUB) */
+  chapter_pattern?: string | null;
+  /** Heading score 1–5 a paragraph needs to start a chapter; lower splits more */
+  heading_threshold?: number | null;
+}
+
+export interface ConversionProfile {
+  id: number;
+  name: string;
+  source_format: string;
+  target_format: string;
+  options: ConversionOptions;
+  created_at: string;
+}
+
+export interface ConversionErrorDetails {
+  error_code: string;
+  title: string;
+  remediation: string;
 }
 
 export interface SupportedConversion {
@@ -43,9 +80,21 @@ interface ConversionState {
     inputPath: string,
     outputFormat: string,
     outputDir?: string,
-    bookId?: number
+    bookId?: number,
+    ocr?: boolean,
+    pdfOptions?: PdfOutputOptions
   ) => Promise<string>;
+  submitWithProfile: (inputPath: string, profileName: string, bookId?: number) => Promise<string>;
+  listProfiles: () => Promise<ConversionProfile[]>;
+  createProfile: (
+    name: string,
+    sourceFormat: string,
+    targetFormat: string,
+    options?: ConversionOptions
+  ) => Promise<ConversionProfile>;
+  deleteProfile: (id: number) => Promise<boolean>;
   cancelJob: (jobId: string) => Promise<void>;
+  getErrorDetails: (errorCode: string) => Promise<ConversionErrorDetails>;
   clearCompletedJobs: () => void;
 }
 
@@ -88,13 +137,18 @@ export const useConversionStore = create<ConversionState>((set) => ({
       }
     );
 
-    const unlistenError = await listen<{ job_id: string; error: string }>(
+    const unlistenError = await listen<{ job_id: string; error_code: string; error_message: string }>(
       'conversion:error',
       ({ payload }) => {
         set(state => ({
           jobs: state.jobs.map(j =>
             j.id === payload.job_id
-              ? { ...j, status: 'Failed' as const, error: payload.error }
+              ? {
+                  ...j,
+                  status: 'Failed' as const,
+                  error: payload.error_message,
+                  error_code: payload.error_code,
+                }
               : j
           ),
         }));
@@ -129,7 +183,7 @@ export const useConversionStore = create<ConversionState>((set) => ({
      }
   },
 
-  submitConversion: async (inputPath, outputFormat, outputDir, bookId) => {
+  submitConversion: async (inputPath, outputFormat, outputDir, bookId, ocr, pdfOptions) => {
     try {
       set({ isLoading: true, error: null });
       const jobId = await invoke<string>('convert_book', {
@@ -137,6 +191,8 @@ export const useConversionStore = create<ConversionState>((set) => ({
         outputFormat,
         outputDir,
         bookId,
+        ocr,
+        pdfOptions,
       });
       set({ isLoading: false });
       return jobId;
@@ -147,6 +203,35 @@ export const useConversionStore = create<ConversionState>((set) => ({
      }
   },
 
+  submitWithProfile: async (inputPath, profileName, bookId) => {
+    try {
+      set({ isLoading: true, error: null });
+      const jobId = await invoke<string>('convert_book_with_profile', {
+        inputPath,
+        profileName,
+        bookId,
+      });
+      set({ isLoading: false });
+      return jobId;
+    } catch (error) {
+      logger.error('Failed to submit conversion with profile:', error);
+      set({ error: String(error), isLoading: false });
+      throw error;
+    }
+  },
+
+  listProfiles: () => invoke<ConversionProfile[]>('list_conversion_profiles'),
+
+  createProfile: (name, sourceFormat, targetFormat, options) =>
+    invoke<ConversionProfile>('create_conversion_profile', {
+      name,
+      sourceFormat,
+      targetFormat,
+      options,
+    }),
+
+  deleteProfile: (id: number) => invoke<boolean>('delete_conversion_profile', { id }),
+
   cancelJob: async (jobId: string) => {
     try {
       await invoke('cancel_conversion', { jobId });
@@ -162,6 +247,9 @@ export const useConversionStore = create<ConversionState>((set) => ({
      }
   },
 
+  getErrorDetails: (errorCode: string) =>
+    invoke<ConversionErrorDetails>('get_conversion_error_details', { errorCode }),
+
   clearCompletedJobs: () => {
     set(state => ({
       jobs: state.jobs.filter(
@@ -30,6 +30,11 @@ export interface RssArticle {
   created_at: string;
 }
 
+export interface ArticleCleanupReport {
+  expired: number;
+  trimmed: number;
+}
+
 interface RssState {
   feeds: RssFeed[];
   articles: RssArticle[];
@@ -48,6 +53,7 @@ interface RssState {
   updateAllFeeds: () => Promise<void>;
   markArticleRead: (articleId: number) => Promise<void>;
   markAllArticlesRead: (feedId?: number) => Promise<void>;
+  cleanupArticles: (includeEpubLinked?: boolean) => Promise<ArticleCleanupReport>;
   generateDailyEpub: (options?: { include_images?: boolean; image_quality?: number; title?: string; author?: string; maxArticles?: number; feeds?: number[] }) => Promise<string>;
   setSelectedFeed: (feedId: number | null) => void;
   triggerSync: () => Promise<void>;
@@ -225,6 +231,20 @@ export const useRssStore = create<RssState>((set, get) => ({
     }
   },
 
+  cleanupArticles: async (includeEpubLinked = false) => {
+    try {
+      const report = await invoke<ArticleCleanupReport>('cleanup_rss_articles', { includeEpubLinked });
+
+      // Cleanup may remove unread articles when a feed is over its limit
+      await get().loadArticles(get().selectedFeedId ?? undefined);
+      return report;
+    } catch (error) {
+      logger.error('Failed to clean up articles:', error);
+      set({ error: String(error) });
+      throw error;
+    }
+  },
+
   generateDailyEpub: async (options = {}) => {
     try {
       set({ isLoading: true, error: null });
@@ -107,6 +107,7 @@ export interface UserPreferences {
   includeNsfw?: boolean;
   legacyLibraryMigrationStatus?: 'none' | 'unmigrated' | 'migrated';
   anilistToken?: string;
+  comicvineApiKey?: string | null;
 }
 
 export interface PreferenceOverride {